@@ -0,0 +1,101 @@
+//! Throughput of a command's output through rx: `cargo bench -p rx-core --bench output`.
+//! Criterion reports it in MiB/s, both for the pipeline alone and for a command writing its
+//! output as fast as it can.
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use encoding_rs::UTF_8;
+use rx_core::{
+    builders::config::ConfigBuilder,
+    executor::{
+        output::{pump, Echo, OutputObserver, OutputStream},
+        runner::Runner,
+        shutdown::ShutdownSignal,
+    },
+    models::config::{CommandContext, CommandType, Config},
+    session::Session,
+};
+
+/// Output per iteration, in lines of 80 characters and a newline
+const SIZE: usize = 16 * 1024 * 1024;
+const LINE: &str =
+    "rx output benchmark line of eighty characters, give or take a few more to pad.\n";
+
+/// An observer counting the bytes of the lines it gets, newlines aside.
+fn counter() -> (Arc<AtomicUsize>, OutputObserver) {
+    let bytes = Arc::new(AtomicUsize::new(0));
+    let counted = bytes.clone();
+    let observer: OutputObserver = Arc::new(move |_, _, line: &str| {
+        counted.fetch_add(line.len(), Ordering::Relaxed);
+    });
+    (bytes, observer)
+}
+
+fn bench_pump(c: &mut Criterion) {
+    let output = LINE.repeat(SIZE / LINE.len());
+    let mut group = c.benchmark_group("pump");
+    group.throughput(Throughput::Bytes(output.len() as u64));
+    group.sample_size(20);
+    let session = Session::new();
+    group.bench_function("16MiB", |b| {
+        b.iter(|| {
+            let (bytes, observer) = counter();
+            session.block_on(pump(
+                output.as_bytes(),
+                "bench".to_string(),
+                OutputStream::Stdout,
+                observer,
+                Echo::Off,
+                UTF_8,
+            ));
+            assert!(bytes.load(Ordering::Relaxed) > 0);
+        })
+    });
+    group.finish();
+}
+
+#[cfg(unix)]
+fn bench_command(c: &mut Criterion) {
+    let line = LINE.trim_end();
+    let details = ConfigBuilder::new(CommandContext::Script)
+        .command_type(CommandType::Shell)
+        .command(&format!("yes '{}' | head -c {}", line, SIZE))
+        .working_directory("")
+        .build()
+        .unwrap();
+    let mut config = Config::default();
+    config
+        .commands
+        .get_or_default_config(CommandContext::Script)
+        .update_config("noisy", details);
+
+    let mut group = c.benchmark_group("command");
+    group.throughput(Throughput::Bytes(SIZE as u64));
+    group.sample_size(10);
+    // Shared by the runs, so the runtime starts once
+    let session = Session::new();
+    group.bench_function("16MiB", |b| {
+        b.iter(|| {
+            let (bytes, observer) = counter();
+            let status = Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+                .session(&session)
+                .echo_output(false)
+                .observe_output(observer)
+                .run(Some("noisy"), &[])
+                .unwrap();
+            assert!(status.success());
+            assert!(bytes.load(Ordering::Relaxed) > 0);
+        })
+    });
+    group.finish();
+}
+
+#[cfg(not(unix))]
+fn bench_command(_: &mut Criterion) {}
+
+criterion_group!(benches, bench_pump, bench_command);
+criterion_main!(benches);
@@ -0,0 +1,129 @@
+use std::{
+    fmt::{self, Display},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use serde::Serialize;
+
+use crate::{
+    daemon::default_socket_path, errors::ConfigError, executor::lock::locks_dir, store::Store,
+};
+
+/// How `rx --version` prints the build it describes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VersionFormat {
+    /// `rx <version> (<sha> <date>)`, for people
+    #[default]
+    Text,
+    /// Every field of [`BuildInfo`], for package managers and bug reports
+    Json,
+}
+
+impl FromStr for VersionFormat {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(VersionFormat::Text),
+            "json" => Ok(VersionFormat::Json),
+            _ => Err(ConfigError::UnknownVersionFormat(s.to_string())),
+        }
+    }
+}
+
+/// What a build of rx is made of, recorded when it was compiled.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BuildInfo {
+    pub version: String,
+    /// The short sha of the commit built, `unknown` outside of a git checkout
+    pub git_sha: String,
+    /// The UTC day it was built, `YYYY-MM-DD`
+    pub build_date: String,
+    pub target: String,
+    /// The cargo features it was built with
+    pub features: Vec<String>,
+}
+
+impl BuildInfo {
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        Ok(serde_json::to_string_pretty(self)? + "\n")
+    }
+
+    pub fn format(&self, format: VersionFormat) -> serde_json::Result<String> {
+        match format {
+            VersionFormat::Text => Ok(format!("{}\n", self)),
+            VersionFormat::Json => self.to_json(),
+        }
+    }
+}
+
+impl Display for BuildInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "rx {} ({} {})",
+            self.version, self.git_sha, self.build_date
+        )
+    }
+}
+
+/// Where rx reads and keeps its files for the current directory, what `rx env` lists. `None`
+/// when the platform has no such directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Locations {
+    /// The config file rx loads, whether or not it exists yet
+    pub config: PathBuf,
+    pub config_exists: bool,
+    /// The project's store: its run history, stats and cached results
+    pub history_dir: Option<PathBuf>,
+    /// The fetched scripts and team configs
+    pub cache_dir: Option<PathBuf>,
+    /// The files of the named `lock`s
+    pub locks_dir: Option<PathBuf>,
+    /// Where `rx daemon` listens unless told otherwise
+    pub daemon_socket: PathBuf,
+}
+
+impl Locations {
+    /// The locations for the config file `config`, the project being the one rooted at
+    /// `workspace_folder`.
+    pub fn new(config: &Path, workspace_folder: &Path) -> Self {
+        Self {
+            config: config.to_path_buf(),
+            config_exists: config.exists(),
+            history_dir: Store::for_workspace(workspace_folder)
+                .map(|store| store.root().to_path_buf()),
+            cache_dir: dirs::cache_dir().map(|dir| dir.join("cargo_runner")),
+            locks_dir: locks_dir(),
+            daemon_socket: default_socket_path(),
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        Ok(serde_json::to_string_pretty(self)? + "\n")
+    }
+
+    /// A line per location, its name and path apart by a tab so scripts can `cut` them.
+    pub fn report(&self) -> String {
+        let path = |path: &Option<PathBuf>| {
+            path.as_ref()
+                .map_or("(none)".to_string(), |path| path.display().to_string())
+        };
+        let config = match self.config_exists {
+            true => self.config.display().to_string(),
+            false => format!("{} (missing)", self.config.display()),
+        };
+        [
+            ("config", config),
+            ("history_dir", path(&self.history_dir)),
+            ("cache_dir", path(&self.cache_dir)),
+            ("locks_dir", path(&self.locks_dir)),
+            ("daemon_socket", self.daemon_socket.display().to_string()),
+        ]
+        .iter()
+        .map(|(name, value)| format!("{}\t{}", name, value))
+        .collect::<Vec<_>>()
+        .join("\n")
+    }
+}
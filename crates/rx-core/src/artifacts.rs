@@ -0,0 +1,89 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use crate::interpolation::InterpolationContext;
+
+/// A binary or library cargo built for a workspace package, from its `compiler-artifact`
+/// message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Artifact {
+    /// Name of the cargo target
+    pub name: String,
+    /// `bin`, `lib`, `cdylib`, ...
+    pub kind: String,
+    /// The executable, or the library files without their `.rmeta`
+    pub paths: Vec<PathBuf>,
+}
+
+impl Artifact {
+    /// The profile directory the artifact was built into, e.g. `debug` or `release`.
+    pub fn profile(&self) -> Option<String> {
+        profile_dir(self.paths.first()?)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+    }
+
+    /// The target triple it was built for, `host` unless it was built with `--target`. Cargo
+    /// puts the artifacts of a `--target` build under a directory named after the triple, the
+    /// target dir itself is tagged with `CACHEDIR.TAG`.
+    pub fn target(&self, host: &str) -> String {
+        let triple_dir = self
+            .paths
+            .first()
+            .and_then(|path| profile_dir(path).parent().map(Path::to_path_buf));
+        match triple_dir {
+            Some(dir) if !dir.join("CACHEDIR.TAG").is_file() => dir
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| host.to_string()),
+            _ => host.to_string(),
+        }
+    }
+}
+
+/// `target/<profile>` for `target/<profile>/app` and `target/<profile>/deps/libapp.so`.
+fn profile_dir(path: &Path) -> &Path {
+    let parent = path.parent().unwrap_or(path);
+    match parent.file_name().and_then(|name| name.to_str()) {
+        Some("deps" | "examples") => parent.parent().unwrap_or(parent),
+        _ => parent,
+    }
+}
+
+/// Copies the files of `artifacts` into `destination`, with `${profile}` and `${target}`
+/// resolved per artifact next to the usual placeholders, `host` being the triple of a build
+/// without `--target`. Returns the copies.
+pub fn copy_artifacts(
+    artifacts: &[Artifact],
+    destination: &str,
+    interpolation: &InterpolationContext,
+    host: &str,
+) -> io::Result<Vec<PathBuf>> {
+    let mut copies = Vec::new();
+    for artifact in artifacts {
+        let mut interpolation = interpolation.clone();
+        interpolation.set("profile", &artifact.profile().unwrap_or_default());
+        interpolation.set("target", &artifact.target(host));
+        let dir = PathBuf::from(interpolation.interpolate(destination));
+        fs::create_dir_all(&dir)?;
+
+        for path in &artifact.paths {
+            let Some(file_name) = path.file_name() else {
+                continue;
+            };
+            let copy = dir.join(file_name);
+            fs::copy(path, &copy).map_err(|err| {
+                io::Error::new(
+                    err.kind(),
+                    format!("cannot copy {}: {}", path.display(), err),
+                )
+            })?;
+            copies.push(copy);
+        }
+    }
+    Ok(copies)
+}
@@ -0,0 +1,201 @@
+use std::{
+    error::Error,
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::models::{config::Config, format::ConfigFormat};
+
+/// Backups kept of a config file when `settings.backup_limit` is unset.
+pub const DEFAULT_BACKUP_LIMIT: usize = 20;
+
+/// A copy of the config file, named by when it was taken.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backup {
+    /// UTC, e.g. `20261016T101500.123Z`, so backups sort by name
+    pub timestamp: String,
+    pub path: PathBuf,
+}
+
+/// The backups of a config file, in a `backups` dir next to it. Past `limit` the oldest are
+/// dropped, a limit of 0 turns them off.
+pub struct Backups {
+    config_path: PathBuf,
+    dir: PathBuf,
+    limit: usize,
+}
+
+impl Backups {
+    pub fn new(config_path: &Path, limit: usize) -> Self {
+        let dir = config_path
+            .parent()
+            .map(|parent| parent.join("backups"))
+            .unwrap_or_else(|| PathBuf::from("backups"));
+        Self {
+            config_path: config_path.to_path_buf(),
+            dir,
+            limit,
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Copies the config file as it is now. `None` when there is no file or backups are off.
+    pub fn create(&self) -> io::Result<Option<Backup>> {
+        if self.limit == 0 || !self.config_path.is_file() {
+            return Ok(None);
+        }
+        fs::create_dir_all(&self.dir)?;
+
+        let timestamp = timestamp(SystemTime::now());
+        let path = self.dir.join(format!(
+            "{}-{}.{}",
+            self.stem(),
+            timestamp,
+            self.format().extension()
+        ));
+        fs::copy(&self.config_path, &path)?;
+
+        let backups = self.list()?;
+        for old in backups
+            .iter()
+            .take(backups.len().saturating_sub(self.limit))
+        {
+            fs::remove_file(&old.path)?;
+        }
+        Ok(Some(Backup { timestamp, path }))
+    }
+
+    /// The backups of the config file, oldest first.
+    pub fn list(&self) -> io::Result<Vec<Backup>> {
+        if !self.dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let prefix = format!("{}-", self.stem());
+        let suffix = format!(".{}", self.format().extension());
+
+        let mut backups: Vec<Backup> = fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                let timestamp = name.strip_prefix(&prefix)?.strip_suffix(&suffix)?;
+                Some(Backup {
+                    timestamp: timestamp.to_string(),
+                    path: entry.path(),
+                })
+            })
+            .collect();
+        backups.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        Ok(backups)
+    }
+
+    /// The backup whose timestamp starts with `timestamp`, or the latest one.
+    pub fn find(&self, timestamp: Option<&str>) -> Result<Backup, Box<dyn Error>> {
+        let backups = self.list()?;
+        let backup = match timestamp {
+            None => backups
+                .last()
+                .ok_or_else(|| format!("No backups in {}", self.dir.display()))?,
+            Some(timestamp) => {
+                let matching: Vec<&Backup> = backups
+                    .iter()
+                    .filter(|backup| backup.timestamp.starts_with(timestamp))
+                    .collect();
+                match matching.as_slice() {
+                    [backup] => *backup,
+                    [] => {
+                        let timestamps: Vec<&str> = backups
+                            .iter()
+                            .map(|backup| backup.timestamp.as_str())
+                            .collect();
+                        return Err(format!(
+                            "No backup taken at '{}', expected one of: {}",
+                            timestamp,
+                            timestamps.join(", ")
+                        )
+                        .into());
+                    }
+                    _ => {
+                        return Err(format!(
+                            "'{}' matches {} backups, give more of the timestamp",
+                            timestamp,
+                            matching.len()
+                        )
+                        .into())
+                    }
+                }
+            }
+        };
+
+        Ok(backup.clone())
+    }
+
+    /// The content of `backup`, checked to be a config.
+    pub fn read(&self, backup: &Backup) -> Result<String, Box<dyn Error>> {
+        let content = fs::read_to_string(&backup.path)?;
+        self.format()
+            .parse::<Config>(&content)
+            .map_err(|err| format!("{} is not a valid config: {}", backup.path.display(), err))?;
+        Ok(content)
+    }
+
+    /// Puts `backup` back in place of the config. The config as it was is backed up first, so
+    /// a restore can be undone the same way.
+    pub fn restore(&self, backup: &Backup) -> Result<(), Box<dyn Error>> {
+        let content = self.read(backup)?;
+        self.create()?;
+        fs::write(&self.config_path, content)?;
+        Ok(())
+    }
+
+    fn stem(&self) -> String {
+        self.config_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| "config".to_string())
+    }
+
+    fn format(&self) -> ConfigFormat {
+        ConfigFormat::from_path(&self.config_path)
+    }
+}
+
+/// `time` in UTC as `20261016T101500.123Z`.
+pub fn timestamp(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = since_epoch.as_secs();
+    let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+    let seconds_of_day = seconds % 86_400;
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        seconds_of_day / 3600,
+        seconds_of_day / 60 % 60,
+        seconds_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+/// The date `days` after 1970-01-01, from Howard Hinnant's `civil_from_days`.
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
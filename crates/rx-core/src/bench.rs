@@ -0,0 +1,145 @@
+use std::{error::Error, fs};
+
+use serde::{Deserialize, Serialize};
+
+use crate::store::Store;
+
+pub const DEFAULT_REGRESSION_THRESHOLD: u32 = 5;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BenchResult {
+    pub name: String,
+    pub ns_per_iter: f64,
+}
+
+/// Benchmark results of one bench run, stored per git revision.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct BenchRun {
+    pub revision: String,
+    #[serde(default)]
+    pub results: Vec<BenchResult>,
+}
+
+impl BenchRun {
+    fn path(store: &Store, revision: &str) -> std::path::PathBuf {
+        store.path(&format!("bench/{}.toml", revision))
+    }
+
+    pub fn load(store: &Store, revision: &str) -> Result<Option<BenchRun>, Box<dyn Error>> {
+        let path = Self::path(store, revision);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        Ok(Some(toml::from_str(&fs::read_to_string(path)?)?))
+    }
+
+    pub fn save(&self, store: &Store) -> Result<(), Box<dyn Error>> {
+        let path = Self::path(store, &self.revision);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Extracts results from libtest (`bench: 1,234 ns/iter`) and criterion (`time: [a b c]`)
+/// output. Criterion's median estimate is used.
+pub fn parse_output(output: &str) -> Vec<BenchResult> {
+    let mut results = Vec::new();
+    let mut previous_line = "";
+
+    for line in output.lines() {
+        if let Some(result) = parse_libtest_line(line) {
+            results.push(result);
+        } else if let Some(result) = parse_criterion_line(line, previous_line) {
+            results.push(result);
+        }
+
+        if !line.trim().is_empty() {
+            previous_line = line;
+        }
+    }
+
+    results
+}
+
+fn parse_libtest_line(line: &str) -> Option<BenchResult> {
+    let rest = line.trim().strip_prefix("test ")?;
+    let (name, measurement) = rest.split_once(" ... bench:")?;
+    let value = measurement.split_whitespace().next()?.replace(',', "");
+
+    Some(BenchResult {
+        name: name.trim().to_string(),
+        ns_per_iter: value.parse().ok()?,
+    })
+}
+
+fn parse_criterion_line(line: &str, previous_line: &str) -> Option<BenchResult> {
+    let (name, estimates) = line.split_once("time:")?;
+    let estimates = estimates.trim().strip_prefix('[')?.strip_suffix(']')?;
+    let parts: Vec<&str> = estimates.split_whitespace().collect();
+    if parts.len() != 6 {
+        return None;
+    }
+
+    // Long names are printed on their own line above the estimates
+    let name = match name.trim() {
+        "" => previous_line.trim(),
+        name => name,
+    };
+
+    Some(BenchResult {
+        name: name.to_string(),
+        ns_per_iter: parts[2].parse::<f64>().ok()? * unit_in_ns(parts[3])?,
+    })
+}
+
+fn unit_in_ns(unit: &str) -> Option<f64> {
+    match unit {
+        "ps" => Some(0.001),
+        "ns" => Some(1.0),
+        "us" | "µs" => Some(1_000.0),
+        "ms" => Some(1_000_000.0),
+        "s" => Some(1_000_000_000.0),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub name: String,
+    pub baseline_ns: f64,
+    pub current_ns: f64,
+    /// Positive when the current run is slower
+    pub change_percent: f64,
+}
+
+impl Comparison {
+    pub fn is_regression(&self, threshold_percent: u32) -> bool {
+        self.change_percent > threshold_percent as f64
+    }
+}
+
+/// Pairs up benchmarks present in both runs by name.
+pub fn compare(baseline: &BenchRun, current: &BenchRun) -> Vec<Comparison> {
+    current
+        .results
+        .iter()
+        .filter_map(|result| {
+            let base = baseline
+                .results
+                .iter()
+                .find(|base| base.name == result.name)?;
+
+            Some(Comparison {
+                name: result.name.clone(),
+                baseline_ns: base.ns_per_iter,
+                current_ns: result.ns_per_iter,
+                change_percent: (result.ns_per_iter - base.ns_per_iter) / base.ns_per_iter * 100.0,
+            })
+        })
+        .collect()
+}
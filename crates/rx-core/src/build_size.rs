@@ -0,0 +1,160 @@
+use std::{collections::BTreeMap, error::Error, fs, path::PathBuf, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{artifacts::Artifact, store::Store};
+
+/// Kinds of artifacts whose size is recorded, libraries only once linked.
+const SIZED_KINDS: [&str; 4] = ["bin", "cdylib", "dylib", "staticlib"];
+
+/// Size of a file a build produced.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct BinarySize {
+    /// File name, e.g. `rx` or `libplugin.so`
+    pub name: String,
+    pub profile: String,
+    pub target: String,
+    pub bytes: u64,
+}
+
+impl BinarySize {
+    /// The binary with its profile and target, e.g. `rx (release, x86_64-unknown-linux-gnu)`.
+    pub fn label(&self) -> String {
+        format!("{} ({}, {})", self.name, self.profile, self.target)
+    }
+
+    fn is_same_binary(&self, other: &BinarySize) -> bool {
+        self.name == other.name && self.profile == other.profile && self.target == other.target
+    }
+}
+
+/// Binary sizes and build times of the builds of one git revision.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct BuildRun {
+    pub revision: String,
+    /// Wall-clock milliseconds of the last build of each config, by label
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub durations: BTreeMap<String, u64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub binaries: Vec<BinarySize>,
+}
+
+impl BuildRun {
+    fn path(store: &Store, revision: &str) -> PathBuf {
+        store.path(&format!("builds/{}.toml", revision))
+    }
+
+    pub fn load(store: &Store, revision: &str) -> Result<Option<BuildRun>, Box<dyn Error>> {
+        let path = Self::path(store, revision);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        Ok(Some(toml::from_str(&fs::read_to_string(path)?)?))
+    }
+
+    pub fn save(&self, store: &Store) -> Result<(), Box<dyn Error>> {
+        let path = Self::path(store, &self.revision);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Records the build of `label` that took `elapsed`, with the sizes of the binaries and
+    /// linked libraries of `artifacts`, built on `host`, replacing those of an earlier build.
+    pub fn record(&mut self, label: &str, elapsed: Duration, artifacts: &[Artifact], host: &str) {
+        self.durations
+            .insert(label.to_string(), elapsed.as_millis() as u64);
+        for artifact in artifacts
+            .iter()
+            .filter(|artifact| SIZED_KINDS.contains(&artifact.kind.as_str()))
+        {
+            let (profile, target) = (
+                artifact.profile().unwrap_or_default(),
+                artifact.target(host),
+            );
+            for path in &artifact.paths {
+                let (Some(name), Ok(metadata)) = (path.file_name(), fs::metadata(path)) else {
+                    continue;
+                };
+                let size = BinarySize {
+                    name: name.to_string_lossy().to_string(),
+                    profile: profile.clone(),
+                    target: target.clone(),
+                    bytes: metadata.len(),
+                };
+                self.binaries.retain(|binary| !binary.is_same_binary(&size));
+                self.binaries.push(size);
+            }
+        }
+        self.binaries.sort_by_key(BinarySize::label);
+    }
+}
+
+/// A binary size, or a build time in milliseconds, of the current build next to the
+/// baseline's.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeChange {
+    pub name: String,
+    pub baseline: u64,
+    pub current: u64,
+}
+
+impl SizeChange {
+    /// Positive when the current build is bigger or slower.
+    pub fn change_percent(&self) -> f64 {
+        match self.baseline {
+            0 => 0.0,
+            baseline => (self.current as f64 - baseline as f64) / baseline as f64 * 100.0,
+        }
+    }
+}
+
+/// Pairs up the binaries, then the build times, present in both runs.
+pub fn compare(baseline: &BuildRun, current: &BuildRun) -> (Vec<SizeChange>, Vec<SizeChange>) {
+    let sizes = current
+        .binaries
+        .iter()
+        .filter_map(|binary| {
+            let base = baseline
+                .binaries
+                .iter()
+                .find(|base| base.is_same_binary(binary))?;
+            Some(SizeChange {
+                name: binary.label(),
+                baseline: base.bytes,
+                current: binary.bytes,
+            })
+        })
+        .collect();
+    let durations = current
+        .durations
+        .iter()
+        .filter_map(|(label, millis)| {
+            Some(SizeChange {
+                name: label.clone(),
+                baseline: *baseline.durations.get(label)?,
+                current: *millis,
+            })
+        })
+        .collect();
+    (sizes, durations)
+}
+
+/// `bytes` in the largest binary unit that keeps it above 1, e.g. `4.2 MiB`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
@@ -0,0 +1,116 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    errors::ConfigError,
+    models::config::{
+        CommandConfig, CommandContext, CommandDetails, CoverageOptions, DefaultKey, NextestOptions,
+        TestRunner,
+    },
+};
+
+/// Builds the [`CommandConfig`] of a context. `build` checks that every default key has a
+/// config, that the test runner is only set for `test`, the coverage options for `coverage` and
+/// the scripts dir for `script`.
+pub struct CommandConfigBuilder {
+    context: CommandContext,
+    default: DefaultKey,
+    configs: BTreeMap<String, CommandDetails>,
+    runner: Option<TestRunner>,
+    nextest: Option<NextestOptions>,
+    coverage: Option<CoverageOptions>,
+    scripts_dir: Option<String>,
+}
+
+impl CommandConfigBuilder {
+    pub fn new(context: CommandContext) -> Self {
+        let CommandConfig { default, .. } = CommandConfig::default();
+        Self {
+            context,
+            default,
+            configs: BTreeMap::new(),
+            runner: None,
+            nextest: None,
+            coverage: None,
+            scripts_dir: None,
+        }
+    }
+
+    pub fn default_key(mut self, default: impl Into<DefaultKey>) -> Self {
+        self.default = default.into();
+        self
+    }
+
+    pub fn config(mut self, key: &str, details: CommandDetails) -> Self {
+        self.configs.insert(key.to_string(), details);
+        self
+    }
+
+    pub fn runner(mut self, runner: TestRunner) -> Self {
+        self.runner = Some(runner);
+        self
+    }
+
+    pub fn nextest(mut self, nextest: NextestOptions) -> Self {
+        self.nextest = Some(nextest);
+        self
+    }
+
+    pub fn coverage(mut self, coverage: CoverageOptions) -> Self {
+        self.coverage = Some(coverage);
+        self
+    }
+
+    pub fn scripts_dir(mut self, scripts_dir: &str) -> Self {
+        self.scripts_dir = Some(scripts_dir.to_string());
+        self
+    }
+
+    pub fn build(self) -> Result<CommandConfig, ConfigError> {
+        if let Some(missing) = self
+            .default
+            .keys()
+            .into_iter()
+            .find(|key| !self.configs.contains_key(*key))
+        {
+            return Err(ConfigError::ConfigKeyNotFound(format!(
+                "{}::{}",
+                self.context, missing
+            )));
+        }
+        if self.context != CommandContext::Test && (self.runner.is_some() || self.nextest.is_some())
+        {
+            return Err(ConfigError::InvalidValue(
+                format!("commands.{}.runner", self.context),
+                "only read for the test context".to_string(),
+            ));
+        }
+        if self.context != CommandContext::Coverage && self.coverage.is_some() {
+            return Err(ConfigError::InvalidValue(
+                format!("commands.{}.coverage", self.context),
+                "only read for the coverage context".to_string(),
+            ));
+        }
+        if let Some(Err(reason)) = self.coverage.as_ref().map(CoverageOptions::check) {
+            return Err(ConfigError::InvalidValue(
+                format!("commands.{}.coverage", self.context),
+                reason,
+            ));
+        }
+
+        if self.context != CommandContext::Script && self.scripts_dir.is_some() {
+            return Err(ConfigError::InvalidValue(
+                format!("commands.{}.scripts_dir", self.context),
+                "only read for the script context".to_string(),
+            ));
+        }
+
+        Ok(CommandConfig {
+            default: self.default,
+            configs: self.configs,
+            runner: self.runner,
+            nextest: self.nextest,
+            coverage: self.coverage,
+            scripts_dir: self.scripts_dir,
+        })
+    }
+}
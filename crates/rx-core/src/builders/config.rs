@@ -0,0 +1,253 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    errors::ConfigError,
+    interpolation::check_syntax,
+    models::{
+        config::{CommandContext, CommandDetails, CommandType},
+        params::Params,
+    },
+    validator::ValidateCommandDetails,
+};
+
+/// The name library users look for; the same builder `rx add` uses.
+pub type CommandDetailsBuilder = ConfigBuilder;
+
+/// Builds a [`CommandDetails`] with the defaults of a context. `build` checks the placeholder
+/// syntax of every templated field before the added validators run.
+#[derive(Default)]
+pub struct ConfigBuilder {
+    command_type: CommandType,
+    description: Option<String>,
+    command: String,
+    params: Params,
+    binary_name: Option<String>,
+    env: BTreeMap<String, String>,
+    env_file: Option<String>,
+    shell: Option<String>,
+    steps: Vec<String>,
+    parallel: bool,
+    depends_on: Vec<String>,
+    tags: Vec<String>,
+    allow_multiple_instances: bool,
+    working_directory: String,
+    pre_command: BTreeSet<String>,
+    shutdown_grace_period: Option<u64>,
+    success_exit_codes: Option<Vec<i32>>,
+    target_dir: Option<String>,
+    validators: Vec<Box<dyn ValidateCommandDetails>>,
+}
+
+impl ConfigBuilder {
+    pub fn add_validator<T: ValidateCommandDetails + 'static>(mut self, validator: T) -> Self {
+        self.validators.push(Box::new(validator));
+        self
+    }
+
+    pub fn new(context: CommandContext) -> Self {
+        match context {
+            CommandContext::Run => Self {
+                command_type: CommandType::Cargo,
+                command: String::from("run --package ${packageName} --bin ${binaryName}"),
+                working_directory: "${workspaceFolder}".to_string(),
+                ..Default::default()
+            },
+            CommandContext::Test => Self {
+                command_type: CommandType::Cargo,
+                command: String::from("test"),
+                working_directory: "${workspaceFolder}".to_string(),
+                ..Default::default()
+            },
+            CommandContext::Build => Self {
+                command_type: CommandType::Cargo,
+                command: String::from("build"),
+                working_directory: "${workspaceFolder}".to_string(),
+                ..Default::default()
+            },
+            CommandContext::Bench => Self {
+                command_type: CommandType::Cargo,
+                command: String::from("bench"),
+                working_directory: "${workspaceFolder}".to_string(),
+                ..Default::default()
+            },
+            CommandContext::Lint => Self {
+                command_type: CommandType::Cargo,
+                command: String::from("clippy"),
+                working_directory: "${workspaceFolder}".to_string(),
+                ..Default::default()
+            },
+            CommandContext::Fmt => Self {
+                command_type: CommandType::Cargo,
+                command: String::from("fmt"),
+                params: Params::from("--check"),
+                working_directory: "${workspaceFolder}".to_string(),
+                ..Default::default()
+            },
+            CommandContext::Coverage => Self {
+                command_type: CommandType::Cargo,
+                command: String::from("llvm-cov"),
+                working_directory: "${workspaceFolder}".to_string(),
+                ..Default::default()
+            },
+            CommandContext::Script => Self {
+                command_type: CommandType::Shell,
+                working_directory: "${workspaceFolder}".to_string(),
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn command_type(mut self, command_type: CommandType) -> Self {
+        self.command_type = command_type;
+        self
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    pub fn command(mut self, command: &str) -> Self {
+        self.command = command.to_string();
+        self
+    }
+
+    pub fn params(mut self, params: impl Into<Params>) -> Self {
+        self.params = params.into();
+        self
+    }
+
+    pub fn binary_name(mut self, binary_name: &str) -> Self {
+        self.binary_name = Some(binary_name.to_string());
+        self
+    }
+
+    pub fn env(mut self, env: BTreeMap<String, String>) -> Self {
+        self.env = env;
+        self
+    }
+
+    /// Adds one variable to the env.
+    pub fn env_var(mut self, name: &str, value: &str) -> Self {
+        self.env.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    pub fn env_file(mut self, env_file: &str) -> Self {
+        self.env_file = Some(env_file.to_string());
+        self
+    }
+
+    pub fn shell(mut self, shell: &str) -> Self {
+        self.shell = Some(shell.to_string());
+        self
+    }
+
+    /// Adds a step of a composite config.
+    pub fn step(mut self, step: &str) -> Self {
+        self.steps.push(step.to_string());
+        self
+    }
+
+    pub fn parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
+    pub fn depends_on(mut self, key: &str) -> Self {
+        self.depends_on.push(key.to_string());
+        self
+    }
+
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    pub fn allow_multiple_instances(mut self, allow_multiple_instances: bool) -> Self {
+        self.allow_multiple_instances = allow_multiple_instances;
+        self
+    }
+
+    pub fn working_directory(mut self, working_directory: &str) -> Self {
+        self.working_directory = working_directory.to_string();
+        self
+    }
+
+    pub fn pre_command(mut self, pre_command: BTreeSet<String>) -> Self {
+        self.pre_command = pre_command;
+        self
+    }
+
+    pub fn shutdown_grace_period(mut self, seconds: u64) -> Self {
+        self.shutdown_grace_period = Some(seconds);
+        self
+    }
+
+    pub fn success_exit_codes(mut self, codes: Vec<i32>) -> Self {
+        self.success_exit_codes = Some(codes);
+        self
+    }
+
+    pub fn target_dir(mut self, target_dir: &str) -> Self {
+        self.target_dir = Some(target_dir.to_string());
+        self
+    }
+
+    pub fn build(self) -> Result<CommandDetails, ConfigError> {
+        let params = self.params.to_string();
+        let mut templated = vec![
+            ("command".to_string(), self.command.as_str()),
+            ("params".to_string(), params.as_str()),
+            (
+                "working_directory".to_string(),
+                self.working_directory.as_str(),
+            ),
+        ];
+        templated.extend(
+            [
+                ("binary_name", &self.binary_name),
+                ("env_file", &self.env_file),
+                ("target_dir", &self.target_dir),
+            ]
+            .into_iter()
+            .filter_map(|(field, value)| Some((field.to_string(), value.as_deref()?))),
+        );
+        templated.extend(
+            self.env
+                .iter()
+                .map(|(name, value)| (format!("env.{}", name), value.as_str())),
+        );
+        for (field, text) in templated {
+            check_syntax(text).map_err(|reason| ConfigError::InvalidValue(field, reason))?;
+        }
+
+        let command_details = CommandDetails {
+            command_type: self.command_type,
+            description: self.description,
+            command: self.command.into(),
+            params: self.params,
+            binary_name: self.binary_name,
+            env: self.env,
+            env_file: self.env_file,
+            shell: self.shell,
+            steps: self.steps,
+            parallel: self.parallel,
+            depends_on: self.depends_on,
+            tags: self.tags,
+            allow_multiple_instances: self.allow_multiple_instances,
+            working_directory: self.working_directory,
+            pre_command: self.pre_command,
+            shutdown_grace_period: self.shutdown_grace_period,
+            success_exit_codes: self.success_exit_codes,
+            target_dir: self.target_dir,
+            ..Default::default()
+        };
+
+        for validator in self.validators {
+            validator.validate(&command_details)?;
+        }
+
+        Ok(command_details)
+    }
+}
@@ -0,0 +1,2 @@
+pub mod command_config;
+pub mod config;
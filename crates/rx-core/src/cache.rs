@@ -0,0 +1,90 @@
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use glob::Pattern;
+use serde::{Deserialize, Serialize};
+
+use crate::store::{fnv1a, Store, FNV_OFFSET_BASIS};
+
+/// Fingerprints of a config's last successful run, stored per `context::key`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct CacheEntry {
+    /// The config itself, the run's arguments and the contents of its `inputs`
+    pub inputs: String,
+    /// The contents of its `outputs`, which must still be there for the entry to count
+    pub outputs: String,
+}
+
+impl CacheEntry {
+    fn path(store: &Store, label: &str) -> PathBuf {
+        store.path(&format!("cache/{}.toml", label.replace("::", "/")))
+    }
+
+    pub fn load(store: &Store, label: &str) -> Option<CacheEntry> {
+        let content = fs::read_to_string(Self::path(store, label)).ok()?;
+        toml::from_str(&content).ok()
+    }
+
+    pub fn save(&self, store: &Store, label: &str) -> Result<(), Box<dyn Error>> {
+        let path = Self::path(store, label);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Files matched by `patterns` relative to `dir`, sorted. A matched directory stands for every
+/// file below it, so `src` and `src/**` are the same.
+pub fn matching_files(dir: &Path, patterns: &[String]) -> Result<Vec<PathBuf>, String> {
+    let dir = Pattern::escape(&dir.to_string_lossy());
+    let mut files = Vec::new();
+
+    for pattern in patterns {
+        // `glob` matches nothing for a trailing `**`, the directory itself covers the same files
+        let pattern = pattern.strip_suffix("/**").unwrap_or(pattern);
+        let full_pattern = if Path::new(pattern).is_absolute() || dir.is_empty() {
+            pattern.to_string()
+        } else {
+            format!("{}/{}", dir.trim_end_matches('/'), pattern)
+        };
+        let paths = glob::glob(&full_pattern)
+            .map_err(|err| format!("invalid pattern '{}': {}", pattern, err.msg))?;
+        for path in paths.flatten() {
+            collect_files(&path, &mut files);
+        }
+    }
+
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) {
+    if path.is_file() {
+        files.push(path.to_path_buf());
+    } else if let Ok(entries) = fs::read_dir(path) {
+        for entry in entries.flatten() {
+            collect_files(&entry.path(), files);
+        }
+    }
+}
+
+/// Hashes `seed` followed by the path and contents of every file, `None` when a file can't be
+/// read.
+pub fn fingerprint(seed: &str, files: &[PathBuf]) -> Option<String> {
+    let mut hash = fnv1a(FNV_OFFSET_BASIS, seed.as_bytes());
+    for file in files {
+        hash = fnv1a(hash, file.to_string_lossy().as_bytes());
+        hash = fnv1a(hash, &[0]);
+        hash = fnv1a(hash, &fs::read(file).ok()?);
+        hash = fnv1a(hash, &[0]);
+    }
+
+    Some(format!("{:016x}", hash))
+}
@@ -0,0 +1,15 @@
+use sha2::{Digest, Sha256};
+
+/// The SHA-256 of `bytes` as lowercase hex, to check downloaded scripts against the digest
+/// pinned in the config.
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Whether `digest` is a SHA-256 written as hex, either case.
+pub fn is_sha256(digest: &str) -> bool {
+    digest.len() == 64 && digest.chars().all(|c| c.is_ascii_hexdigit())
+}
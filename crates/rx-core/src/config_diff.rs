@@ -0,0 +1,121 @@
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display},
+};
+
+use toml::{Table, Value};
+
+use crate::models::config::{CommandContext, Config};
+
+/// A difference `rx config diff` reports, going from the first config to the second.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigChange {
+    /// The label of an entry only the second config has
+    Added(String),
+    /// The label of an entry only the first config has
+    Removed(String),
+    /// A field of an entry both have, such as `build::release.params`, or of the rest of the
+    /// config, such as `settings.notify`, with its value in each, `None` when unset
+    Changed {
+        path: String,
+        old: Option<Value>,
+        new: Option<Value>,
+    },
+}
+
+impl Display for ConfigChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let shown = |value: &Option<Value>| match value {
+            Some(value) => value.to_string(),
+            None => "unset".to_string(),
+        };
+        match self {
+            ConfigChange::Added(label) => write!(f, "+ {}", label),
+            ConfigChange::Removed(label) => write!(f, "- {}", label),
+            ConfigChange::Changed { path, old, new } => {
+                write!(f, "~ {}: {} -> {}", path, shown(old), shown(new))
+            }
+        }
+    }
+}
+
+/// The entries `new` adds and removes compared to `old` and the fields it changes, those of
+/// the entries first, context by context, then the rest of the config.
+pub fn diff_configs(old: &Config, new: &Config) -> Vec<ConfigChange> {
+    let (Value::Table(mut old), Value::Table(mut new)) = (old.to_value(), new.to_value()) else {
+        unreachable!("a config serializes to a table")
+    };
+    let old_commands = as_table(old.remove("commands"));
+    let new_commands = as_table(new.remove("commands"));
+
+    let mut changes = Vec::new();
+    for context in CommandContext::ALL {
+        let name = context.as_str();
+        let mut old_context = as_table(old_commands.get(name).cloned());
+        let mut new_context = as_table(new_commands.get(name).cloned());
+        let old_configs = as_table(old_context.remove("configs"));
+        let new_configs = as_table(new_context.remove("configs"));
+
+        let keys: BTreeSet<&String> = old_configs.keys().chain(new_configs.keys()).collect();
+        for key in keys {
+            let label = format!("{}::{}", context, key);
+            match (old_configs.get(key), new_configs.get(key)) {
+                (None, Some(_)) => changes.push(ConfigChange::Added(label)),
+                (Some(_), None) => changes.push(ConfigChange::Removed(label)),
+                (old, new) => diff_fields(&label, old, new, &mut changes),
+            }
+        }
+        // A context only one config has shows as its entries alone
+        if old_commands.contains_key(name) && new_commands.contains_key(name) {
+            diff_tables(
+                &format!("commands.{}", name),
+                &old_context,
+                &new_context,
+                &mut changes,
+            );
+        }
+    }
+    diff_tables("", &old, &new, &mut changes);
+    changes
+}
+
+fn as_table(value: Option<Value>) -> Table {
+    match value {
+        Some(Value::Table(table)) => table,
+        _ => Table::new(),
+    }
+}
+
+/// Compares the values at `path`, tables field by field with a table missing on one side taken
+/// as an empty one, so only the values that differ are reported.
+fn diff_fields(
+    path: &str,
+    old: Option<&Value>,
+    new: Option<&Value>,
+    changes: &mut Vec<ConfigChange>,
+) {
+    if old == new {
+        return;
+    }
+    match (old, new) {
+        (Some(Value::Table(old)), Some(Value::Table(new))) => diff_tables(path, old, new, changes),
+        (Some(Value::Table(old)), None) => diff_tables(path, old, &Table::new(), changes),
+        (None, Some(Value::Table(new))) => diff_tables(path, &Table::new(), new, changes),
+        _ => changes.push(ConfigChange::Changed {
+            path: path.to_string(),
+            old: old.cloned(),
+            new: new.cloned(),
+        }),
+    }
+}
+
+fn diff_tables(path: &str, old: &Table, new: &Table, changes: &mut Vec<ConfigChange>) {
+    let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    for key in keys {
+        let path = match path {
+            "" => key.clone(),
+            path => format!("{}.{}", path, key),
+        };
+        diff_fields(&path, old.get(key), new.get(key), changes);
+    }
+}
@@ -0,0 +1,143 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    fs,
+    path::Path,
+};
+
+use crate::{
+    env::{is_secret, parse_env_file},
+    interpolation::InterpolationContext,
+    models::config::{CommandContext, CommandDetails, CommandType, Config},
+    secret::is_sealed,
+};
+
+/// Something valid but likely unintended in a config, as `rx lint-config` reports it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    /// `context::key` of the entry
+    pub label: String,
+    pub message: String,
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.label, self.message)
+    }
+}
+
+/// The warnings of every entry, ordered by context then key. `env_file`s are read relative to
+/// the working directories `interpolation` resolves, an unreadable one is left to the run.
+pub fn lint(config: &Config, interpolation: &InterpolationContext) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    for context in CommandContext::ALL {
+        let Some(command_config) = config.commands.get_config(context) else {
+            continue;
+        };
+        let mut keys: Vec<&String> = command_config.configs.keys().collect();
+        keys.sort();
+
+        for key in keys {
+            let details = &command_config.configs[key];
+            let label = format!("{}::{}", context, key);
+            let mut messages = lint_details(details, interpolation);
+            let references = config.references(context, key);
+            if details.is_deprecated() {
+                let users: Vec<String> = command_config
+                    .default
+                    .refers_to(key)
+                    .then(|| "default".to_string())
+                    .into_iter()
+                    .chain(references)
+                    .collect();
+                if !users.is_empty() {
+                    messages.push(format!(
+                        "is deprecated but still used by {}",
+                        users.join(", ")
+                    ));
+                }
+            } else if !command_config.default.refers_to(key) && references.is_empty() {
+                messages.push(
+                    "is neither a default nor referenced by another entry or hook, it only runs when asked for by name"
+                        .to_string(),
+                );
+            }
+            warnings.extend(messages.into_iter().map(|message| Warning {
+                label: label.clone(),
+                message,
+            }));
+        }
+    }
+    warnings
+}
+
+fn lint_details(details: &CommandDetails, interpolation: &InterpolationContext) -> Vec<String> {
+    let mut messages = Vec::new();
+
+    if details.command_type == CommandType::Cargo && details.command.line().trim().is_empty() {
+        messages.push("command is empty, cargo alone only prints its help".to_string());
+    }
+
+    let command_flags: Vec<&str> = details.command.line().split_whitespace().collect();
+    let repeated: Vec<String> = details
+        .params
+        .words()
+        .into_iter()
+        .filter(|param| param.starts_with('-') && command_flags.contains(&param.as_str()))
+        .collect();
+    if !repeated.is_empty() {
+        messages.push(format!("params repeat {} from command", repeated.join(" ")));
+    }
+
+    let working_directory = &details.working_directory;
+    if !working_directory.starts_with("${") && Path::new(working_directory).is_absolute() {
+        messages.push(format!(
+            "working_directory '{}' is absolute, ${{workspaceFolder}} keeps the config portable",
+            working_directory
+        ));
+    }
+
+    let mut names: Vec<&String> = details.env.keys().collect();
+    names.sort();
+    let mut by_case: HashMap<String, &str> = HashMap::new();
+    for name in names {
+        let value = &details.env[name];
+        if is_secret(name) && !value.is_empty() && !value.contains("${") && !is_sealed(value) {
+            messages.push(format!(
+                "env {} holds a credential in plain text, `rx secret set` encrypts it",
+                name
+            ));
+        }
+        if let Some(other) = by_case.insert(name.to_uppercase(), name) {
+            messages.push(format!(
+                "env sets both {} and {}, which are the same variable on Windows",
+                other, name
+            ));
+        }
+    }
+
+    if let Some(env_file) = &details.env_file {
+        let path = Path::new(&interpolation.interpolate(working_directory))
+            .join(interpolation.interpolate(env_file));
+        let variables = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| parse_env_file(&content).ok())
+            .unwrap_or_default();
+        let mut shadowed: Vec<&str> = variables
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .filter(|name| details.env.contains_key(*name))
+            .collect();
+        shadowed.sort();
+        shadowed.dedup();
+        if !shadowed.is_empty() {
+            messages.push(format!(
+                "env overrides {} from env_file {}",
+                shadowed.join(", "),
+                env_file
+            ));
+        }
+    }
+
+    messages
+}
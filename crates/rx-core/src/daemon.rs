@@ -0,0 +1,260 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    executor::{output::OutputStream, queue::QueueSnapshot},
+    models::config::{CommandContext, CommandType, Config},
+};
+
+/// A request to `rx daemon`, one JSON object per line such as
+/// `{"request":"run","context":"test","key":"unit","cwd":"/src/app"}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "request", rename_all = "snake_case")]
+pub enum DaemonRequest {
+    /// Runs a config, answered with its [`crate::events::RunEvent`]s
+    Run(RunRequest),
+    /// Lists the configs, of a single context if given
+    List {
+        #[serde(default)]
+        context: Option<CommandContext>,
+    },
+    /// Checks the config file as it is on disk
+    Validate,
+    /// Stops the runs of `label`, or every run
+    Kill {
+        #[serde(default)]
+        label: Option<String>,
+    },
+    /// Lists the processes running and those queued under `max_concurrent_runs`
+    Queue,
+    /// Proves the client may use a daemon listening on TCP, its first request there
+    Auth { token: String },
+}
+
+impl DaemonRequest {
+    /// Reads a request line.
+    pub fn from_json(line: &str) -> Result<Self, String> {
+        serde_json::from_str(line).map_err(|err| format!("invalid request: {}", err))
+    }
+
+    /// The request as a single line of JSON, without the newline.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// A request to run a config, also what `rx serve` takes to start a run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunRequest {
+    pub context: CommandContext,
+    /// Defaults to the context's `default`
+    #[serde(default)]
+    pub key: Option<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// `--env` overrides
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Values of `${input:..}` placeholders, the daemon never asks for them
+    #[serde(default)]
+    pub inputs: BTreeMap<String, String>,
+    /// Directory placeholders such as `${workspaceFolder}` and a per-directory `default`
+    /// resolve from, the daemon's own by default
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
+    #[serde(default)]
+    pub no_cache: bool,
+    /// Workspace member to target, like `--package`
+    #[serde(default)]
+    pub package: Option<String>,
+    /// Apply fixes, like `--fix` of lint and fmt
+    #[serde(default)]
+    pub fix: bool,
+}
+
+impl RunRequest {
+    /// Reads a request such as `{"context":"test","key":"unit"}`.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|err| format!("invalid request: {}", err))
+    }
+}
+
+/// An answer of `rx daemon` that isn't part of a run, serialized like a run event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum DaemonEvent {
+    Configs {
+        configs: Vec<ConfigSummary>,
+    },
+    Valid,
+    Invalid {
+        error: String,
+    },
+    /// Labels of the runs asked to stop
+    Killed {
+        labels: Vec<String>,
+    },
+    Queue {
+        #[serde(flatten)]
+        queue: QueueSnapshot,
+    },
+    /// The request couldn't be read or carried out
+    Error {
+        message: String,
+    },
+    /// The config file changed and was reloaded, sent to every connection
+    ConfigReloaded,
+    /// The config file changed but can't be used, the daemon keeps the previous config
+    ConfigError {
+        error: String,
+    },
+    /// The token of an `auth` request was right
+    Authenticated,
+}
+
+impl DaemonEvent {
+    /// The event as a single line of JSON, without the newline.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// What a client driving a run makes of the events of the daemon, those it doesn't need read
+/// as [`ClientEvent::Other`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ClientEvent {
+    Started {
+        label: String,
+    },
+    OutputChunk {
+        stream: OutputStream,
+        data: String,
+    },
+    Finished {
+        exit_code: i32,
+        #[serde(default)]
+        error: Option<String>,
+    },
+    Authenticated,
+    Error {
+        message: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+impl ClientEvent {
+    pub fn from_json(line: &str) -> Result<Self, String> {
+        serde_json::from_str(line).map_err(|err| format!("invalid event: {}", err))
+    }
+}
+
+/// Where `rx --remote` finds a daemon listening on TCP: `host:port` over TLS, or
+/// `tcp://host:port` in the clear, e.g. through an SSH tunnel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteAddress {
+    pub host: String,
+    pub port: u16,
+    pub tls: bool,
+}
+
+impl FromStr for RemoteAddress {
+    type Err = String;
+
+    fn from_str(address: &str) -> Result<Self, Self::Err> {
+        let (tls, rest) = match address.split_once("://") {
+            Some(("tls", rest)) => (true, rest),
+            Some(("tcp", rest)) => (false, rest),
+            Some((scheme, _)) => {
+                return Err(format!("unknown scheme '{}', use tls:// or tcp://", scheme))
+            }
+            None => (true, address),
+        };
+        let invalid = || format!("'{}' is not host:port", address);
+        let (host, port) = rest.rsplit_once(':').ok_or_else(invalid)?;
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        if host.is_empty() {
+            return Err(invalid());
+        }
+        Ok(RemoteAddress {
+            host: host.to_string(),
+            port: port.parse().map_err(|_| invalid())?,
+            tls,
+        })
+    }
+}
+
+/// Whether `given` is the daemon's `token`, taking as long whatever it differs in.
+pub fn same_token(token: &str, given: &str) -> bool {
+    let (token, given) = (token.as_bytes(), given.as_bytes());
+    let differs = token
+        .iter()
+        .zip(given)
+        .fold(0, |differs, (a, b)| differs | (a ^ b));
+    token.len() == given.len() && differs == 0
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfigSummary {
+    pub label: String,
+    pub context: CommandContext,
+    pub key: String,
+    #[serde(rename = "type")]
+    pub command_type: CommandType,
+    /// Whether this is its context's `default`
+    pub default: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// The configs of `context`, or of every context, ordered by context then key, leaving out
+/// those whose `platforms` the current OS isn't one of.
+pub fn list_configs(config: &Config, context: Option<CommandContext>) -> Vec<ConfigSummary> {
+    let mut configs = Vec::new();
+    for current in CommandContext::ALL {
+        if context.is_some_and(|context| context != current) {
+            continue;
+        }
+        let Some(command_config) = config.commands.get_config(current) else {
+            continue;
+        };
+
+        let mut keys: Vec<&String> = command_config
+            .configs
+            .iter()
+            .filter(|(_, details)| details.runs_on_this_platform())
+            .map(|(key, _)| key)
+            .collect();
+        keys.sort();
+        configs.extend(keys.into_iter().map(|key| ConfigSummary {
+            label: format!("{}::{}", current, key),
+            context: current,
+            key: key.clone(),
+            command_type: command_config.configs[key].command_type.clone(),
+            default: command_config.default.refers_to(key),
+            description: command_config.configs[key].description.clone(),
+            tags: command_config.configs[key].tags.clone(),
+        }));
+    }
+    configs
+}
+
+/// Where `rx daemon` listens unless told otherwise: `rx.sock` in the user's runtime dir, or a
+/// per-user socket in the temp dir.
+pub fn default_socket_path() -> PathBuf {
+    match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(runtime_dir) if !runtime_dir.is_empty() => Path::new(&runtime_dir).join("rx.sock"),
+        _ => {
+            let user = std::env::var("USER").unwrap_or_else(|_| "default".to_string());
+            std::env::temp_dir().join(format!("rx-{}.sock", user))
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use std::{
+    fmt::{self, Display},
+    path::PathBuf,
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{artifacts::Artifact, errors::ConfigError};
+
+/// How cargo commands report compiler messages during `rx run`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MessageFormat {
+    /// Cargo's own output
+    #[default]
+    Human,
+    /// Cargo prints JSON messages that rx parses: a summary of the diagnostics at the end, or
+    /// `diagnostic` events with `--event-format ndjson`
+    Json,
+}
+
+impl FromStr for MessageFormat {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(MessageFormat::Human),
+            "json" => Ok(MessageFormat::Json),
+            _ => Err(ConfigError::UnknownMessageFormat(s.to_string())),
+        }
+    }
+}
+
+/// A compiler or clippy diagnostic, from cargo's `--message-format=json` output.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    /// `error`, `warning`, `note`, ...
+    pub level: String,
+    pub message: String,
+    /// The lint or error code, e.g. `clippy::needless_return`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// Location of the primary span, relative to the workspace
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    /// The diagnostic as the terminal would show it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rendered: Option<String>,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file)?;
+            for number in [self.line, self.column].into_iter().flatten() {
+                write!(f, "{}:", number)?;
+            }
+            write!(f, " ")?;
+        }
+        match &self.code {
+            Some(code) => write!(f, "{}[{}]: {}", self.level, code, self.message),
+            None => write!(f, "{}: {}", self.level, self.message),
+        }
+    }
+}
+
+/// Counts the diagnostics of a run and keeps them for the report at its end.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiagnosticSummary {
+    pub errors: usize,
+    pub warnings: usize,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl DiagnosticSummary {
+    /// Adds a diagnostic unless it has no location, like the compiler's own tallies such as
+    /// "aborting due to 2 previous errors".
+    pub fn add(&mut self, diagnostic: Diagnostic) {
+        if diagnostic.file.is_none() {
+            return;
+        }
+        match diagnostic.level.as_str() {
+            "error" | "error: internal compiler error" => self.errors += 1,
+            "warning" => self.warnings += 1,
+            _ => {}
+        }
+        self.diagnostics.push(diagnostic);
+    }
+
+    /// The counts followed by the first `limit` diagnostics, one per line.
+    pub fn report(&self, limit: usize) -> String {
+        let mut report = format!(
+            "{} {}, {} {}",
+            self.errors,
+            if self.errors == 1 { "error" } else { "errors" },
+            self.warnings,
+            if self.warnings == 1 {
+                "warning"
+            } else {
+                "warnings"
+            },
+        );
+        for diagnostic in self.diagnostics.iter().take(limit) {
+            report.push_str(&format!("\n  {}", diagnostic));
+        }
+        if self.diagnostics.len() > limit {
+            report.push_str(&format!(
+                "\n  ... and {} more",
+                self.diagnostics.len() - limit
+            ));
+        }
+        report
+    }
+}
+
+/// A line of cargo's JSON output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CargoMessage {
+    Diagnostic(Diagnostic),
+    /// A binary or library of a workspace package
+    Artifact(Artifact),
+    /// Dependencies, build script runs and the like
+    Other,
+}
+
+#[derive(Deserialize)]
+struct RawMessage {
+    reason: String,
+    message: Option<RawDiagnostic>,
+    #[serde(default)]
+    package_id: String,
+    target: Option<RawTarget>,
+    #[serde(default)]
+    filenames: Vec<PathBuf>,
+    executable: Option<PathBuf>,
+}
+
+#[derive(Deserialize)]
+struct RawTarget {
+    name: String,
+    kind: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct RawDiagnostic {
+    level: String,
+    message: String,
+    code: Option<RawCode>,
+    #[serde(default)]
+    spans: Vec<RawSpan>,
+    rendered: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawCode {
+    code: String,
+}
+
+#[derive(Deserialize)]
+struct RawSpan {
+    file_name: String,
+    line_start: usize,
+    column_start: usize,
+    is_primary: bool,
+}
+
+/// Reads a line cargo printed with `--message-format=json`, `None` when it isn't one.
+pub fn parse_message(line: &str) -> Option<CargoMessage> {
+    if !line.starts_with('{') {
+        return None;
+    }
+    let raw: RawMessage = serde_json::from_str(line).ok()?;
+
+    let diagnostic = match (raw.reason.as_str(), raw.message, raw.target) {
+        ("compiler-message", Some(diagnostic), _) => diagnostic,
+        ("compiler-artifact", _, Some(target)) => {
+            // Registry and git dependencies aren't reported, nor build scripts
+            let local = raw.package_id.contains("path+file://");
+            let kind = target.kind.into_iter().next().unwrap_or_default();
+            if !local || kind == "custom-build" {
+                return Some(CargoMessage::Other);
+            }
+            let paths = match raw.executable {
+                Some(executable) => vec![executable],
+                None => raw
+                    .filenames
+                    .into_iter()
+                    .filter(|path| {
+                        path.extension()
+                            .is_none_or(|extension| extension != "rmeta")
+                    })
+                    .collect(),
+            };
+            return Some(CargoMessage::Artifact(Artifact {
+                name: target.name,
+                kind,
+                paths,
+            }));
+        }
+        _ => return Some(CargoMessage::Other),
+    };
+    let span = diagnostic.spans.iter().find(|span| span.is_primary);
+    Some(CargoMessage::Diagnostic(Diagnostic {
+        level: diagnostic.level,
+        message: diagnostic.message,
+        code: diagnostic.code.map(|code| code.code),
+        file: span.map(|span| span.file_name.clone()),
+        line: span.map(|span| span.line_start),
+        column: span.map(|span| span.column_start),
+        rendered: diagnostic.rendered,
+    }))
+}
@@ -0,0 +1,43 @@
+use similar::TextDiff;
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const CYAN: &str = "\x1b[36m";
+const RESET: &str = "\x1b[0m";
+
+/// Unified diff between the current and the would-be file content, empty when unchanged.
+pub fn unified_diff(current: &str, updated: &str, label: &str) -> String {
+    if current == updated {
+        return String::new();
+    }
+
+    TextDiff::from_lines(current, updated)
+        .unified_diff()
+        .context_radius(3)
+        .header(&format!("a/{}", label), &format!("b/{}", label))
+        .to_string()
+}
+
+/// Adds ANSI colors to a unified diff for terminal output.
+pub fn colorize(diff: &str) -> String {
+    diff.lines()
+        .map(|line| {
+            let color = if line.starts_with("+++") || line.starts_with("---") {
+                None
+            } else if line.starts_with('+') {
+                Some(GREEN)
+            } else if line.starts_with('-') {
+                Some(RED)
+            } else if line.starts_with("@@") {
+                Some(CYAN)
+            } else {
+                None
+            };
+
+            match color {
+                Some(color) => format!("{}{}{}\n", color, line, RESET),
+                None => format!("{}\n", line),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,194 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Display},
+};
+
+/// Where a variable of a command's environment comes from, in increasing precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EnvSource {
+    /// Inherited from the process running rx
+    Process,
+    /// What direnv loads from the `.envrc` of the working directory, see `direnv`
+    Direnv,
+    /// The config's `env_file`
+    EnvFile,
+    /// The `[envsets]` the config's `env_sets` names
+    EnvSet,
+    /// The config's `env` table
+    Config,
+    /// `--env KEY=VALUE` on the command line
+    Cli,
+}
+
+impl Display for EnvSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let source = match self {
+            EnvSource::Process => "process",
+            EnvSource::Direnv => "direnv",
+            EnvSource::EnvFile => "env_file",
+            EnvSource::EnvSet => "env_sets",
+            EnvSource::Config => "config",
+            EnvSource::Cli => "cli",
+        };
+        write!(f, "{}", source)
+    }
+}
+
+/// Process variables a config with `inherit_env = false` still gets, so it can find and run its
+/// tools, cargo and rustup included.
+pub const ALWAYS_INHERITED: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LOGNAME",
+    "SHELL",
+    "TERM",
+    "LANG",
+    "TMPDIR",
+    "CARGO_HOME",
+    "RUSTUP_HOME",
+    "RUSTUP_TOOLCHAIN",
+    // Windows can't start processes or find its temp dir without these
+    "SYSTEMROOT",
+    "COMSPEC",
+    "PATHEXT",
+    "TEMP",
+    "TMP",
+    "USERPROFILE",
+];
+
+/// The process variables a command inherits: all of them, or only [`ALWAYS_INHERITED`].
+pub fn process_env(inherit_env: bool) -> Vec<(String, String)> {
+    std::env::vars()
+        .filter(|(name, _)| inherit_env || ALWAYS_INHERITED.contains(&name.as_str()))
+        .collect()
+}
+
+/// A command's environment assembled from layers, later layers override earlier ones:
+/// process env < direnv < `env_file` < `env_sets` < config `env` < `--env`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LayeredEnv {
+    variables: BTreeMap<String, (String, EnvSource)>,
+}
+
+impl LayeredEnv {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a layer, its variables override those of the layers added before.
+    pub fn layer<K, V>(
+        mut self,
+        source: EnvSource,
+        variables: impl IntoIterator<Item = (K, V)>,
+    ) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        for (name, value) in variables {
+            self.variables.insert(name.into(), (value.into(), source));
+        }
+        self
+    }
+
+    /// Adds every variable of `other` on top, keeping their sources.
+    pub fn layer_all(mut self, other: LayeredEnv) -> Self {
+        self.variables.extend(other.variables);
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.variables.get(name).map(|(value, _)| value.as_str())
+    }
+
+    /// The variables with their source, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str, EnvSource)> {
+        self.variables
+            .iter()
+            .map(|(name, (value, source))| (name.as_str(), value.as_str(), *source))
+    }
+
+    /// The variables rx sets on top of the inherited process environment.
+    pub fn to_map(&self) -> HashMap<String, String> {
+        self.variables
+            .iter()
+            .filter(|(_, (_, source))| *source != EnvSource::Process)
+            .map(|(name, (value, _))| (name.clone(), value.clone()))
+            .collect()
+    }
+}
+
+/// Parses a dotenv file: `KEY=VALUE` lines with optional `export ` prefixes, quoted values
+/// and `#` comments.
+pub fn parse_env_file(content: &str) -> Result<Vec<(String, String)>, String> {
+    let mut variables = Vec::new();
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+
+        let (name, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("line {}: expected KEY=VALUE", index + 1))?;
+        let name = name.trim();
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(format!(
+                "line {}: invalid variable name '{}'",
+                index + 1,
+                name
+            ));
+        }
+
+        let value = value.trim();
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) if value.len() > 1 && value.ends_with(quote) => {
+                let inner = &value[1..value.len() - 1];
+                if quote == '"' {
+                    inner.replace("\\n", "\n").replace("\\\"", "\"")
+                } else {
+                    inner.to_string()
+                }
+            }
+            // Unquoted values end at an inline comment
+            _ => value
+                .split_once(" #")
+                .map_or(value, |(value, _)| value)
+                .trim_end()
+                .to_string(),
+        };
+
+        variables.push((name.to_string(), value));
+    }
+
+    Ok(variables)
+}
+
+/// Whether a variable name looks like it holds a credential.
+pub fn is_secret(name: &str) -> bool {
+    let name = name.to_ascii_uppercase();
+    [
+        "SECRET",
+        "TOKEN",
+        "PASSWORD",
+        "PASSWD",
+        "CREDENTIAL",
+        "PRIVATE",
+        "API_KEY",
+        "AUTH",
+    ]
+    .iter()
+    .any(|marker| name.contains(marker))
+}
+
+/// `value`, or a mask hiding it when `name` looks like a credential.
+pub fn masked<'a>(name: &str, value: &'a str) -> &'a str {
+    if is_secret(name) && !value.is_empty() {
+        "********"
+    } else {
+        value
+    }
+}
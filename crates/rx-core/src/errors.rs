@@ -0,0 +1,419 @@
+use std::{
+    fmt::{self, Display},
+    io,
+};
+
+use crate::ports::PortConflict;
+
+#[derive(Debug)]
+pub enum ConfigError {
+    MissingConfigFields(String),
+    ConfigKeyNotFound(String),
+    ConfigKeyExists(String),
+    InvalidPreCommand(String),
+    InvalidEnvFormat,
+    UnknownContext(String),
+    UnknownCommandType(String),
+    UnknownDebugger(String),
+    UnknownImportFormat(String),
+    UnknownExportFormat(String),
+    UnknownEventFormat(String),
+    UnknownMessageFormat(String),
+    UnknownColorChoice(String),
+    UnknownUiMode(String),
+    UnknownReportFormat(String),
+    UnknownVersionFormat(String),
+    UnknownRunnablesFormat(String),
+    UnknownConfigFormat(String),
+    UnknownCheckStep(String),
+    UnknownOnFailure(String),
+    /// `${binaryName}` has no value, with the binaries to pick it from
+    NoBinaryName(Vec<String>),
+    /// Placeholders without a value, as written, with `unknown_placeholders = "error"`
+    UnresolvedPlaceholders(Vec<String>),
+    InvalidPath(String),
+    InvalidValue(String, String),
+    /// The label of an entry and what still refers to it
+    KeyInUse(String, Vec<String>),
+    /// No pattern of a context's per-directory `default` matches the directory
+    NoDefaultKey(String, String),
+    /// The label a context's `default` refers to, which has no entry, and the keys to pick from
+    DanglingDefault(String, Vec<String>),
+    /// The label of a config and the platforms it runs on, which the current OS isn't one of
+    UnsupportedPlatform(String, Vec<String>),
+    /// An `${input:..}` without a value or default, with nobody to ask
+    MissingInput(String),
+    /// An `${arg:..}` without a `--arg` value or default
+    MissingArg(String),
+    /// A name of a config's `env_sets` that `[envsets]` has no entry for
+    UnknownEnvSet(String),
+    /// The label of an entry and the two files defining it
+    IncludeConflict(String, String, String),
+    /// The label of an entry changed in the main config and the file it was included from
+    IncludedEntryChanged(String, String),
+    /// The label of an entry two merged configs define differently
+    MergeConflict(String),
+    /// The directory a config file would be saved in, missing
+    MissingConfigDir(String),
+    ReadOnlyConfig(String),
+    /// A config file of another user, which replacing would take over
+    ConfigOwnedByOther(String),
+    /// The directory of a config file, where its replacement is written first
+    ConfigDirNotWritable(String),
+    /// A config file that can't be replaced by renaming, being on another filesystem than its
+    /// directory
+    CrossDeviceConfig(String),
+    /// The config file and why it couldn't be written otherwise
+    SaveFailed(String, String),
+    /// Fields of the config files rx doesn't know, each with where it is and a suggestion
+    UnknownFields(Vec<String>),
+    // You can add more error variants as needed
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::MissingConfigFields(str) => {
+                write!(f, "Missing Config Fields: {}", str)
+            }
+            ConfigError::ConfigKeyNotFound(key) => {
+                write!(f, "The config key '{}' does not exist.", key)
+            }
+            ConfigError::ConfigKeyExists(key) => {
+                write!(f, "The config key '{}' already exists.", key)
+            }
+            ConfigError::InvalidPreCommand(msg) => write!(f, "{}", msg),
+            ConfigError::InvalidEnvFormat => write!(f, "ENV define is not ALL_CAPS"),
+            ConfigError::UnknownContext(context) => write!(
+                f,
+                "Unknown context '{}', expected one of: run, test, build, bench, script, lint, fmt, coverage",
+                context
+            ),
+            ConfigError::UnknownCommandType(command_type) => write!(
+                f,
+                "Unknown command type '{}', expected one of: cargo, shell, composite",
+                command_type
+            ),
+            ConfigError::InvalidPath(path) => write!(f, "No config value at '{}'", path),
+            ConfigError::InvalidValue(path, reason) => {
+                write!(f, "Invalid value for '{}': {}", path, reason)
+            }
+            ConfigError::KeyInUse(label, references) => write!(
+                f,
+                "'{}' is referenced by {}, use --cascade to remove the references too or --force to keep them",
+                label,
+                references.join(", ")
+            ),
+            ConfigError::NoDefaultKey(context, dir) => write!(
+                f,
+                "No default of '{}' matches the directory '{}', pass a key or add a \"*\" pattern",
+                context, dir
+            ),
+            ConfigError::DanglingDefault(label, keys) => write!(
+                f,
+                "The default '{}' does not exist, pass one of {} or change it with rx set-default",
+                label,
+                keys.join(", ")
+            ),
+            ConfigError::UnsupportedPlatform(label, platforms) => write!(
+                f,
+                "'{}' is not available on this platform ({}), it runs on {}",
+                label,
+                std::env::consts::OS,
+                platforms.join(", ")
+            ),
+            ConfigError::MissingInput(name) => write!(
+                f,
+                "No value for input '{}', pass --input {}=VALUE",
+                name, name
+            ),
+            ConfigError::MissingArg(name) => write!(
+                f,
+                "No value for arg '{}', pass --arg {}=VALUE or give it a default",
+                name, name
+            ),
+            ConfigError::UnknownEnvSet(name) => write!(
+                f,
+                "No env set '{}', add it to [envsets] or remove it from env_sets",
+                name
+            ),
+            ConfigError::IncludeConflict(label, first, second) => write!(
+                f,
+                "'{}' is defined in both {} and {}",
+                label, first, second
+            ),
+            ConfigError::IncludedEntryChanged(label, file) => write!(
+                f,
+                "'{}' is included from {}, change it there",
+                label, file
+            ),
+            ConfigError::UnknownFields(fields) => write!(
+                f,
+                "Unknown config fields:\n  {}",
+                fields.join("\n  ")
+            ),
+            ConfigError::UnknownImportFormat(format) => write!(
+                f,
+                "Unknown import format '{}', expected one of: just, make, bacon",
+                format
+            ),
+            ConfigError::UnknownExportFormat(format) => write!(
+                f,
+                "Unknown export format '{}', expected one of: cargo-make, xtask, gha",
+                format
+            ),
+            ConfigError::UnknownEventFormat(format) => write!(
+                f,
+                "Unknown event format '{}', expected one of: text, ndjson",
+                format
+            ),
+            ConfigError::UnknownMessageFormat(format) => write!(
+                f,
+                "Unknown message format '{}', expected one of: human, json",
+                format
+            ),
+            ConfigError::UnknownColorChoice(choice) => write!(
+                f,
+                "Unknown color choice '{}', expected one of: auto, always, never",
+                choice
+            ),
+            ConfigError::UnknownUiMode(mode) => write!(
+                f,
+                "Unknown ui mode '{}', expected one of: interleaved, prefixed, split",
+                mode
+            ),
+            ConfigError::UnknownReportFormat(format) => write!(
+                f,
+                "Unknown report format '{}', expected one of: markdown, json",
+                format
+            ),
+            ConfigError::UnknownVersionFormat(format) => write!(
+                f,
+                "Unknown version format '{}', expected one of: text, json",
+                format
+            ),
+            ConfigError::UnknownCheckStep(step) => write!(
+                f,
+                "Unknown check step '{}', expected one of: check, clippy, test",
+                step
+            ),
+            ConfigError::UnknownOnFailure(on_failure) => write!(
+                f,
+                "Unknown on_failure '{}', expected one of: stop, continue, run:<step>",
+                on_failure
+            ),
+            ConfigError::NoBinaryName(binaries) if binaries.is_empty() => write!(
+                f,
+                "No value for ${{binaryName}}, the package has no binary target"
+            ),
+            ConfigError::NoBinaryName(binaries) => write!(
+                f,
+                "No value for ${{binaryName}}, set binary_name on the config or default-run in Cargo.toml to one of: {}",
+                binaries.join(", ")
+            ),
+            ConfigError::UnresolvedPlaceholders(placeholders) => write!(
+                f,
+                "No value for {}, write ${} to keep it as it is",
+                placeholders.join(", "),
+                placeholders[0]
+            ),
+            ConfigError::MergeConflict(label) => write!(
+                f,
+                "'{}' is defined differently in both configs, pass --overwrite to take the merged one",
+                label
+            ),
+            ConfigError::MissingConfigDir(dir) => write!(
+                f,
+                "Cannot save the config, {} doesn't exist; pass --create-dirs to create it",
+                dir
+            ),
+            ConfigError::ReadOnlyConfig(path) => write!(
+                f,
+                "Cannot save {}, it's read-only; make it writable with `chmod u+w {}`",
+                path, path
+            ),
+            ConfigError::ConfigOwnedByOther(path) => write!(
+                f,
+                "Cannot save {}, it belongs to another user (was rx run with sudo?); take it back with `sudo chown $(id -un) {}`",
+                path, path
+            ),
+            ConfigError::ConfigDirNotWritable(dir) => write!(
+                f,
+                "Cannot save the config, no permission to create files in {}; make the directory writable or keep the config elsewhere with --config",
+                dir
+            ),
+            ConfigError::CrossDeviceConfig(path) => write!(
+                f,
+                "Cannot replace {}, it's on another filesystem than its directory, as a bind-mounted file is; mount its directory instead",
+                path
+            ),
+            ConfigError::SaveFailed(path, reason) => {
+                write!(f, "Cannot save {}: {}", path, reason)
+            }
+            ConfigError::UnknownConfigFormat(format) => write!(
+                f,
+                "Unknown config format '{}', expected one of: toml, yaml, json",
+                format
+            ),
+            ConfigError::UnknownRunnablesFormat(format) => write!(
+                f,
+                "Unknown runnables format '{}', expected one of: text, json",
+                format
+            ),
+            ConfigError::UnknownDebugger(debugger) => write!(
+                f,
+                "Unknown debugger '{}', expected one of: lldb, gdb, dap",
+                debugger
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[derive(Debug)]
+pub enum ExecutionError {
+    Config(ConfigError),
+    PreCommandCycle(String),
+    StepCycle(String),
+    Spawn(String, io::Error),
+    CommandFailed(String, Option<i32>),
+    Interrupted(String),
+    RemoteSync(String, Option<i32>),
+    HookFailed(String),
+    NotDebuggable(String),
+    EnvFile(String, String),
+    /// The directory whose `.envrc` direnv couldn't load, and why
+    Direnv(String, String),
+    StdinFile(String, String),
+    /// The resolved working directory that doesn't exist or couldn't be created, and why
+    WorkingDirectory(String, String),
+    NoSingleExecutable(String, usize),
+    /// A tool the config needs, and how to install it
+    ToolMissing(String, String),
+    /// The URL of a script that couldn't be downloaded or didn't match its SHA-256, and why
+    ScriptDownload(String, String),
+    /// The label of a deprecated config and of its replacement
+    Deprecated(String, Option<String>),
+    /// The label of a config and the port it needs that is held by another process
+    PortInUse(String, PortConflict),
+    /// The label of a config with `wait_for` that didn't get ready, and why
+    NotReady(String, String),
+    /// The label of a config started with `rx up` that isn't a service
+    NotAService(String),
+    /// The env variable whose value couldn't be encrypted, decrypted or read from the keychain,
+    /// and why
+    Secret(String, String),
+    /// The label of a config whose sandbox couldn't be set up or whose outputs couldn't be
+    /// copied back, and why
+    Sandbox(String, String),
+    /// A command type no executor is registered for, and the registered ones
+    UnknownExecutor(String, String),
+    /// The `lock` whose file couldn't be opened or locked, and why
+    Lock(String, String),
+}
+
+impl Display for ExecutionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExecutionError::Config(err) => write!(f, "{}", err),
+            ExecutionError::PreCommandCycle(key) => {
+                write!(f, "Pre-command cycle detected at '{}'", key)
+            }
+            ExecutionError::StepCycle(key) => {
+                write!(f, "'{}' is a step of itself", key)
+            }
+            ExecutionError::Spawn(key, err) => write!(f, "Failed to start '{}': {}", key, err),
+            ExecutionError::CommandFailed(key, Some(code)) => {
+                write!(f, "'{}' failed with exit code {}", key, code)
+            }
+            ExecutionError::CommandFailed(key, None) => {
+                write!(f, "'{}' was terminated by a signal", key)
+            }
+            ExecutionError::Interrupted(key) => write!(f, "'{}' was interrupted", key),
+            ExecutionError::HookFailed(hook) => write!(f, "The {} failed", hook),
+            ExecutionError::EnvFile(path, reason) => {
+                write!(f, "Could not load env_file '{}': {}", path, reason)
+            }
+            ExecutionError::Direnv(dir, reason) => {
+                write!(
+                    f,
+                    "Could not load the .envrc of '{}' with direnv: {}",
+                    dir, reason
+                )
+            }
+            ExecutionError::StdinFile(path, reason) => {
+                write!(f, "Could not open stdin_file '{}': {}", path, reason)
+            }
+            ExecutionError::WorkingDirectory(dir, reason) => {
+                write!(
+                    f,
+                    "Cannot run in the working directory '{}': {}",
+                    dir, reason
+                )
+            }
+            ExecutionError::NotDebuggable(key) => write!(
+                f,
+                "'{}' is not a cargo run or cargo test config, there is nothing to debug",
+                key
+            ),
+            ExecutionError::NoSingleExecutable(key, 0) => {
+                write!(f, "Building '{}' produced no executable", key)
+            }
+            ExecutionError::NoSingleExecutable(key, count) => write!(
+                f,
+                "Building '{}' produced {} executables, pick one with --bin, --lib or --test",
+                key, count
+            ),
+            ExecutionError::ToolMissing(tool, hint) => {
+                write!(f, "{} is not installed, install it with: {}", tool, hint)
+            }
+            ExecutionError::ScriptDownload(url, reason) => {
+                write!(f, "Could not get the script at {}: {}", url, reason)
+            }
+            ExecutionError::Deprecated(key, Some(replacement)) => {
+                write!(f, "'{}' is deprecated, run '{}' instead", key, replacement)
+            }
+            ExecutionError::Deprecated(key, None) => write!(f, "'{}' is deprecated", key),
+            ExecutionError::PortInUse(key, conflict) => write!(
+                f,
+                "'{}' can't start, {}; stop it or set kill_conflicts = true",
+                key, conflict
+            ),
+            ExecutionError::NotReady(key, reason) => {
+                write!(f, "'{}' didn't get ready: {}", key, reason)
+            }
+            ExecutionError::NotAService(key) => {
+                write!(f, "'{}' is not a service, set service = true on it", key)
+            }
+            ExecutionError::Secret(name, reason) => {
+                write!(f, "Could not get the secret {}: {}", name, reason)
+            }
+            ExecutionError::Lock(name, reason) => {
+                write!(f, "Could not take the lock '{}': {}", name, reason)
+            }
+            ExecutionError::Sandbox(key, reason) => {
+                write!(f, "Could not sandbox '{}': {}", key, reason)
+            }
+            ExecutionError::UnknownExecutor(command_type, names) => write!(
+                f,
+                "No executor is registered for command type '{}', expected one of: {}, composite",
+                command_type, names
+            ),
+            ExecutionError::RemoteSync(key, code) => write!(
+                f,
+                "Syncing the working directory of '{}' to the remote host failed (exit code {})",
+                key,
+                code.map_or("none".to_string(), |code| code.to_string())
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ExecutionError {}
+
+impl From<ConfigError> for ExecutionError {
+    fn from(err: ConfigError) -> Self {
+        ExecutionError::Config(err)
+    }
+}
@@ -0,0 +1,214 @@
+use std::{str::FromStr, time::Duration};
+
+use serde::Serialize;
+
+use crate::{
+    artifacts::Artifact,
+    diagnostics::Diagnostic,
+    errors::ConfigError,
+    executor::{
+        composite::{StepEvent, StepReport},
+        output::OutputStream,
+    },
+    failure::FailureSummary,
+    test_summary::TestSummary,
+};
+
+/// How `rx run` reports what happens during a run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EventFormat {
+    /// Progress lines on stderr for people
+    #[default]
+    Text,
+    /// One [`RunEvent`] per line as JSON, for editors and other tools
+    Ndjson,
+}
+
+impl FromStr for EventFormat {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(EventFormat::Text),
+            "ndjson" => Ok(EventFormat::Ndjson),
+            _ => Err(ConfigError::UnknownEventFormat(s.to_string())),
+        }
+    }
+}
+
+/// A lifecycle event of a run, serialized with its kind under `event`, e.g.
+/// `{"event":"started","label":"run::default"}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum RunEvent {
+    Started {
+        label: String,
+    },
+    PreCommandStarted {
+        label: String,
+    },
+    PreCommandFinished {
+        label: String,
+        success: bool,
+        exit_code: Option<i32>,
+        duration_ms: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    /// A pre_command failed and the config runs anyway, as its `pre_command_policy` says
+    PreCommandIgnored {
+        label: String,
+        error: String,
+    },
+    StepStarted {
+        label: String,
+    },
+    StepFinished {
+        label: String,
+        success: bool,
+        exit_code: Option<i32>,
+        duration_ms: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    Skipped {
+        label: String,
+        reason: String,
+    },
+    Cached {
+        label: String,
+    },
+    /// Every cell of a matrix config ran, each also has its `step_finished`
+    MatrixFinished {
+        label: String,
+        passed: usize,
+        failed: usize,
+    },
+    /// A config with `wait_for` got ready, its `step_finished` or `pre_command_finished` comes
+    /// once it's stopped
+    Ready {
+        label: String,
+        duration_ms: u64,
+    },
+    /// A process waits for its turn under `max_concurrent_runs`, `position` 1 being next. Sent
+    /// again as it moves up
+    Queued {
+        label: String,
+        position: usize,
+    },
+    /// A process waits for the `lock` another command holds
+    Locked {
+        label: String,
+        lock: String,
+    },
+    /// A line a command wrote, without its line ending
+    OutputChunk {
+        label: String,
+        stream: OutputStream,
+        data: String,
+    },
+    /// A compiler or clippy diagnostic, in place of the JSON line cargo printed for it
+    Diagnostic {
+        label: String,
+        #[serde(flatten)]
+        diagnostic: Diagnostic,
+    },
+    /// A binary or library the build produced
+    Artifact {
+        label: String,
+        #[serde(flatten)]
+        artifact: Artifact,
+    },
+    /// The passed, failed and ignored tests of a test run, before its `finished`
+    Tests {
+        label: String,
+        #[serde(flatten)]
+        summary: TestSummary,
+    },
+    /// What failed and why, before the `finished` of a run that failed
+    Failure {
+        label: String,
+        #[serde(flatten)]
+        summary: FailureSummary,
+    },
+    /// The end of the run with the code rx exits with
+    Finished {
+        label: String,
+        success: bool,
+        exit_code: i32,
+        duration_ms: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+}
+
+impl RunEvent {
+    /// The event as a single line of JSON, without the newline.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+impl From<&StepEvent> for RunEvent {
+    fn from(event: &StepEvent) -> Self {
+        match event.clone() {
+            StepEvent::Started(label) => RunEvent::StepStarted { label },
+            StepEvent::Finished(report) => {
+                let StepReport {
+                    label,
+                    success,
+                    code,
+                    error,
+                    elapsed,
+                } = report;
+                RunEvent::StepFinished {
+                    label,
+                    success,
+                    exit_code: code,
+                    duration_ms: millis(elapsed),
+                    error,
+                }
+            }
+            StepEvent::Skipped(label, reason) => RunEvent::Skipped { label, reason },
+            StepEvent::Cached(label) => RunEvent::Cached { label },
+            StepEvent::PreCommandStarted(label) => RunEvent::PreCommandStarted { label },
+            StepEvent::PreCommandFinished(report) => {
+                let StepReport {
+                    label,
+                    success,
+                    code,
+                    error,
+                    elapsed,
+                } = report;
+                RunEvent::PreCommandFinished {
+                    label,
+                    success,
+                    exit_code: code,
+                    duration_ms: millis(elapsed),
+                    error,
+                }
+            }
+            StepEvent::PreCommandIgnored(label, error) => {
+                RunEvent::PreCommandIgnored { label, error }
+            }
+            StepEvent::Ready(label, elapsed) => RunEvent::Ready {
+                label,
+                duration_ms: millis(elapsed),
+            },
+            StepEvent::Queued(label, position) => RunEvent::Queued { label, position },
+            StepEvent::Locked(label, lock) => RunEvent::Locked { label, lock },
+            StepEvent::MatrixFinished(label, reports) => {
+                let passed = reports.iter().filter(|report| report.success).count();
+                RunEvent::MatrixFinished {
+                    label,
+                    passed,
+                    failed: reports.len() - passed,
+                }
+            }
+        }
+    }
+}
+
+pub fn millis(duration: Duration) -> u64 {
+    duration.as_millis().try_into().unwrap_or(u64::MAX)
+}
@@ -0,0 +1,111 @@
+use std::path::PathBuf;
+
+use crate::{
+    cache::{fingerprint, matching_files, CacheEntry},
+    errors::{ConfigError, ExecutionError},
+    executor::runner::Runner,
+    models::config::CommandDetails,
+    store::Store,
+};
+
+/// A config's last recorded run next to the fingerprint of its current inputs.
+pub(crate) struct CacheState {
+    store: Store,
+    label: String,
+    inputs: String,
+    entry: Option<CacheEntry>,
+}
+
+impl<'a> Runner<'a> {
+    /// The cache state of `key`, `None` when it has no `inputs`, caching is off, there is no
+    /// store or an input can't be read.
+    pub(crate) fn cache_state(
+        &self,
+        key: &str,
+        details: &CommandDetails,
+        args: &[String],
+    ) -> Result<Option<CacheState>, ExecutionError> {
+        if self.no_cache || details.inputs.is_empty() {
+            return Ok(None);
+        }
+        let Some(store) = self.project_store() else {
+            return Ok(None);
+        };
+
+        // Changing the config or the arguments invalidates the cache just like an input does
+        let seed = format!(
+            "{}\0{}",
+            toml::to_string(details).unwrap_or_default(),
+            args.join("\0")
+        );
+        let files = self.files(details, "inputs", &details.inputs)?;
+
+        let label = self.label(key);
+        Ok(fingerprint(&seed, &files).map(|inputs| CacheState {
+            entry: CacheEntry::load(&store, &label),
+            store,
+            label,
+            inputs,
+        }))
+    }
+
+    /// Whether the last successful run had the same inputs and its outputs are untouched.
+    pub(crate) fn is_cached(
+        &self,
+        cache: &CacheState,
+        details: &CommandDetails,
+    ) -> Result<bool, ExecutionError> {
+        let Some(entry) = &cache.entry else {
+            return Ok(false);
+        };
+        if entry.inputs != cache.inputs {
+            return Ok(false);
+        }
+
+        Ok(self.output_fingerprint(details)?.as_ref() == Some(&entry.outputs))
+    }
+
+    /// Records a successful run with the outputs it left behind.
+    pub(crate) fn record_cache(
+        &self,
+        cache: CacheState,
+        details: &CommandDetails,
+    ) -> Result<(), ExecutionError> {
+        let entry = CacheEntry {
+            inputs: cache.inputs,
+            outputs: self.output_fingerprint(details)?.unwrap_or_default(),
+        };
+
+        // A failed write only costs the next run its cache hit
+        let _ = entry.save(&cache.store, &cache.label);
+        Ok(())
+    }
+
+    /// `None` when an output is missing or can't be read.
+    fn output_fingerprint(
+        &self,
+        details: &CommandDetails,
+    ) -> Result<Option<String>, ExecutionError> {
+        let files = self.files(details, "outputs", &details.outputs)?;
+        if files.is_empty() && !details.outputs.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(fingerprint("", &files))
+    }
+
+    pub(crate) fn files(
+        &self,
+        details: &CommandDetails,
+        field: &str,
+        patterns: &[String],
+    ) -> Result<Vec<PathBuf>, ExecutionError> {
+        let patterns: Vec<String> = patterns
+            .iter()
+            .map(|pattern| self.interpolation.interpolate(pattern))
+            .collect();
+
+        matching_files(&self.working_directory(details), &patterns)
+            .map_err(|reason| ConfigError::InvalidValue(field.to_string(), reason).into())
+    }
+}
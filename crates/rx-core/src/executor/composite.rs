@@ -0,0 +1,247 @@
+use std::{
+    process::ExitStatus,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    errors::{ConfigError, ExecutionError},
+    executor::{ready::Services, runner::Runner},
+    models::config::{CommandContext, CommandDetails, CommandType, OnFailure},
+    rerun::parse_label,
+};
+
+/// How a step of a composite config or a pre_command ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepReport {
+    /// `context::key` of the step
+    pub label: String,
+    pub success: bool,
+    /// Exit code of the step, `None` when it was killed by a signal or never started
+    pub code: Option<i32>,
+    /// Why the step couldn't run to completion, if it failed before exiting
+    pub error: Option<String>,
+    pub elapsed: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StepEvent {
+    Started(String),
+    Finished(StepReport),
+    /// A config, composite step or not, skipped by its guards, with the reason
+    Skipped(String, String),
+    /// A config not run because its `inputs` are unchanged since its last successful run
+    Cached(String),
+    PreCommandStarted(String),
+    PreCommandFinished(StepReport),
+    /// A pre_command whose failure its config's `pre_command_policy` let pass, with the reason
+    PreCommandIgnored(String, String),
+    /// Every cell of a matrix config ran, with the config's label and the cells' reports
+    MatrixFinished(String, Vec<StepReport>),
+    /// A step or pre_command with `wait_for` got ready after the time given and runs on, its
+    /// `Finished` or `PreCommandFinished` follows once it's stopped
+    Ready(String, Duration),
+    /// A process waits for its turn under `max_concurrent_runs`, with its position in the queue,
+    /// 1 being next. Sent again as it moves up
+    Queued(String, usize),
+    /// A process waits for the `lock` another command holds, with the lock's name
+    Locked(String, String),
+}
+
+/// Called when a composite step or a pre_command starts and finishes, from the step's thread
+/// for parallel groups, and when any config is skipped by its guards or cached.
+pub type StepObserver = Arc<dyn Fn(&StepEvent) + Send + Sync>;
+
+/// Resolves a step entry: `context::key`, or a bare `key` of `context`.
+pub fn parse_step(
+    step: &str,
+    context: CommandContext,
+) -> Result<(CommandContext, &str), ConfigError> {
+    match step.split_once("::") {
+        Some((step_context, key)) => Ok((step_context.parse()?, key)),
+        None => Ok((context, step)),
+    }
+}
+
+impl<'a> Runner<'a> {
+    /// Reports the steps of composite configs and the pre_commands to `observer`.
+    pub fn observe_steps(mut self, observer: StepObserver) -> Self {
+        self.step_observer = Some(observer);
+        self
+    }
+
+    /// Runs the steps of a composite config, one after another until one fails, or all at once
+    /// with `parallel = true`. A failing step fails the composite with that step's label unless
+    /// its `on_failure` lets the composite go on, or runs a cleanup step first.
+    pub(crate) fn run_composite(
+        &self,
+        key: &str,
+        details: &CommandDetails,
+    ) -> Result<ExitStatus, ExecutionError> {
+        let label = self.label(key);
+        if self.composite_chain.contains(&label) {
+            return Err(ExecutionError::StepCycle(label));
+        }
+
+        let steps = details
+            .steps
+            .iter()
+            .map(|step| Ok((step.as_str(), parse_step(step, self.context)?)))
+            .collect::<Result<Vec<_>, ConfigError>>()?;
+
+        let step_runner = |context: CommandContext| {
+            let mut runner = self.for_context(context);
+            runner.composite_chain.push(label.clone());
+            runner
+        };
+
+        if details.parallel {
+            let results: Vec<_> = thread::scope(|scope| {
+                let handles: Vec<_> = steps
+                    .iter()
+                    .map(|(_, (context, key))| {
+                        let runner = step_runner(*context);
+                        scope.spawn(move || runner.run_step(key))
+                    })
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|handle| handle.join().expect("composite step panicked"))
+                    .collect()
+            });
+
+            // The cleanups run once every step is done, the first failure not let through wins
+            let mut failure = None;
+            for ((step, _), result) in steps.iter().zip(results) {
+                if let Err(err) = result {
+                    let err = self.step_failed(details, step, err, &step_runner);
+                    failure = failure.or(err);
+                }
+            }
+            if let Some(err) = failure {
+                return Err(err);
+            }
+        } else {
+            // Steps with `wait_for` run on until the last step is done
+            thread::scope(|scope| {
+                let mut services = Services::new(scope);
+                let result = steps.iter().try_for_each(|(step, (context, key))| {
+                    let runner = step_runner(*context);
+                    let result = match runner.details(key)?.wait_for {
+                        Some(_) => runner.start_service_step(key, &mut services),
+                        None => runner.run_step(key),
+                    };
+                    match result {
+                        Ok(()) => Ok(()),
+                        Err(err) => match self.step_failed(details, step, err, &step_runner) {
+                            Some(err) => Err(err),
+                            None => Ok(()),
+                        },
+                    }
+                });
+                services.stop(self);
+                result
+            })?;
+        }
+
+        Ok(ExitStatus::default())
+    }
+
+    /// Handles `err` of the step entry `step` of the composite `details` as its `on_failure`
+    /// says: `None` when the composite goes on, else the error to fail it with once the
+    /// cleanup step, if any, ran. An interrupted step always stops the composite.
+    fn step_failed<'r>(
+        &self,
+        details: &CommandDetails,
+        step: &str,
+        err: ExecutionError,
+        step_runner: &impl Fn(CommandContext) -> Runner<'r>,
+    ) -> Option<ExecutionError> {
+        if matches!(err, ExecutionError::Interrupted(_)) {
+            return Some(err);
+        }
+        match details.on_failure.get(step).unwrap_or(&OnFailure::Stop) {
+            OnFailure::Stop => Some(err),
+            OnFailure::Continue => None,
+            OnFailure::Run(cleanup) => {
+                match parse_step(cleanup, self.context) {
+                    // Its failure is reported as a step of its own, the step's own is kept
+                    Ok((context, key)) => {
+                        let _ = step_runner(context).run_step(key);
+                    }
+                    Err(cleanup_err) => return Some(cleanup_err.into()),
+                }
+                Some(err)
+            }
+        }
+    }
+
+    /// Runs a single step with its pre_commands and reports it, a step that doesn't succeed
+    /// becomes an error. A skipped step is only reported as skipped.
+    pub(crate) fn run_step(&self, key: &str) -> Result<(), ExecutionError> {
+        let label = self.label(key);
+        self.notify_step(StepEvent::Started(label.clone()));
+
+        let started = Instant::now();
+        let result = match self.run_unless_skipped(key, &[]) {
+            Ok(None) => return Ok(()),
+            Ok(Some(status)) if self.details(key)?.is_success(status.code()) => Ok(()),
+            Ok(Some(status)) => Err(ExecutionError::CommandFailed(label.clone(), status.code())),
+            Err(err) => Err(err),
+        };
+
+        let (success, code, error) = match &result {
+            Ok(()) => (true, Some(0), None),
+            Err(ExecutionError::CommandFailed(failed, code)) if *failed == label => {
+                (false, *code, None)
+            }
+            Err(err) => (false, None, Some(err.to_string())),
+        };
+        self.notify_step(StepEvent::Finished(StepReport {
+            label,
+            success,
+            code,
+            error,
+            elapsed: started.elapsed(),
+        }));
+
+        result
+    }
+
+    /// Labels of the steps, matrix cells and `depends_on` configs that failed so far, those
+    /// running a command of their own: a failed composite step or matrix config is left out
+    /// for the steps or cells that made it fail.
+    pub fn failed_steps(&self) -> Vec<String> {
+        let failed = self.failed_steps.lock().expect("failed steps poisoned");
+        let mut labels: Vec<String> = Vec::new();
+        for label in failed.iter() {
+            let Ok((context, key, cell)) = parse_label(label) else {
+                continue;
+            };
+            let Ok(details) = self.for_context(context).details(key) else {
+                continue;
+            };
+            let own_command = details.command_type != CommandType::Composite
+                && (details.matrix.is_empty() || cell.is_some());
+            if own_command && !labels.contains(label) {
+                labels.push(label.clone());
+            }
+        }
+        labels
+    }
+
+    pub(crate) fn notify_step(&self, event: StepEvent) {
+        if let StepEvent::Finished(report) = &event {
+            if !report.success {
+                self.failed_steps
+                    .lock()
+                    .expect("failed steps poisoned")
+                    .push(report.label.clone());
+            }
+        }
+        if let Some(observer) = &self.step_observer {
+            observer(&event);
+        }
+    }
+}
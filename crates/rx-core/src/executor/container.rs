@@ -0,0 +1,69 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
+
+use crate::models::config::ContainerConfig;
+
+pub const DEFAULT_ENGINE: &str = "docker";
+pub const DEFAULT_WORKSPACE_MOUNT: &str = "/workspace";
+
+/// Builds the `docker run` argv executing `command` inside `container`, with a terminal when
+/// `tty` is set.
+///
+/// `workspace_folder` is mounted at the configured mount point and `working_directory` is mapped
+/// to the same relative location inside it, or mounted on its own when it lies outside.
+pub fn docker_argv(
+    container: &ContainerConfig,
+    workspace_folder: &Path,
+    working_directory: &Path,
+    env: &HashMap<String, String>,
+    command: Vec<String>,
+    tty: bool,
+) -> Vec<String> {
+    let engine = container.engine.as_deref().unwrap_or(DEFAULT_ENGINE);
+    let mount = container
+        .workspace_mount
+        .as_deref()
+        .unwrap_or(DEFAULT_WORKSPACE_MOUNT)
+        .trim_end_matches('/');
+
+    let mut argv: Vec<String> = vec![engine.into(), "run".into(), "--rm".into(), "--init".into()];
+    argv.push("-i".into());
+    if tty {
+        argv.push("-t".into());
+    }
+
+    argv.push("-v".into());
+    argv.push(format!("{}:{}", workspace_folder.display(), mount));
+
+    let workdir = match working_directory.strip_prefix(workspace_folder) {
+        Ok(relative) if relative.as_os_str().is_empty() => mount.to_string(),
+        Ok(relative) => format!("{}/{}", mount, relative.display()),
+        Err(_) if working_directory.as_os_str().is_empty() => mount.to_string(),
+        Err(_) => {
+            let outside = "/rx-working-directory";
+            argv.push("-v".into());
+            argv.push(format!("{}:{}", working_directory.display(), outside));
+            outside.to_string()
+        }
+    };
+    argv.push("-w".into());
+    argv.push(workdir);
+
+    for volume in &container.volumes {
+        argv.push("-v".into());
+        argv.push(volume.clone());
+    }
+
+    // Sorted so the generated line is stable
+    for (key, value) in env.iter().collect::<BTreeMap<_, _>>() {
+        argv.push("-e".into());
+        argv.push(format!("{}={}", key, value));
+    }
+
+    argv.extend(container.run_args.iter().cloned());
+    argv.push(container.image.clone());
+    argv.extend(command);
+    argv
+}
@@ -0,0 +1,53 @@
+use crate::models::config::{CoverageFormat, CoverageOptions};
+
+/// Adds the report and threshold of `options` to a `cargo llvm-cov ...` argv, before a `--`
+/// of its params. Flags the params already give win, and any other cargo command is left as
+/// it is.
+pub fn apply(argv: &mut Vec<String>, options: &CoverageOptions) {
+    if argv.get(1).map(String::as_str) != Some("llvm-cov") {
+        return;
+    }
+    let options_end = argv
+        .iter()
+        .position(|arg| arg == "--")
+        .unwrap_or(argv.len());
+    let given = |flags: &[&str]| {
+        argv[..options_end].iter().any(|arg| {
+            flags
+                .iter()
+                .any(|flag| arg == flag || arg.starts_with(&format!("{}=", flag)))
+        })
+    };
+
+    let mut inserted: Vec<String> = Vec::new();
+    let format = match options.format {
+        CoverageFormat::Text => None,
+        CoverageFormat::Lcov => Some(("--lcov", "--output-path")),
+        CoverageFormat::Html => Some(("--html", "--output-dir")),
+    };
+    // A report the params ask for replaces that of the options, output path included
+    let reported = given(&[
+        "--lcov",
+        "--html",
+        "--json",
+        "--cobertura",
+        "--codecov",
+        "--text",
+    ]);
+    if let Some((flag, output_flag)) = format.filter(|_| !reported) {
+        inserted.push(flag.to_string());
+        if let Some(output) = &options.output {
+            inserted.extend([output_flag.to_string(), output.clone()]);
+        }
+    }
+    if options.open && !given(&["--open"]) {
+        inserted.push("--open".to_string());
+    }
+    if let Some(percent) = options
+        .fail_under
+        .filter(|_| !given(&["--fail-under-lines"]))
+    {
+        inserted.extend(["--fail-under-lines".to_string(), percent.to_string()]);
+    }
+    argv.splice(options_end..options_end, inserted);
+}
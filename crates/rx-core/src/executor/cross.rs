@@ -0,0 +1,61 @@
+use std::process::{Command, Stdio};
+
+use crate::{
+    helpers::{host_triple, target_arg},
+    models::config::CrossRunner,
+    session::Session,
+};
+
+/// The `argv` of a cargo command for `runner`, `None` when it stays with cargo: for the
+/// native runner, without a `--target` or when the target is the host. `cargo zigbuild` only
+/// replaces `cargo build`.
+pub fn translate(session: &Session, argv: &[String], runner: CrossRunner) -> Option<Vec<String>> {
+    let target = target_arg(argv.iter().map(String::as_str))?;
+    if target == host_triple(session) {
+        return None;
+    }
+
+    let mut translated = argv.to_vec();
+    match runner {
+        CrossRunner::Native => return None,
+        CrossRunner::Cross => translated[0] = "cross".to_string(),
+        CrossRunner::Zigbuild if argv.get(1).is_some_and(|arg| arg == "build") => {
+            translated[1] = "zigbuild".to_string()
+        }
+        CrossRunner::Zigbuild => return None,
+    }
+    Some(translated)
+}
+
+/// Whether the tool of `runner` can be run, checked once per session.
+pub fn is_installed(session: &Session, runner: CrossRunner) -> bool {
+    let (tool, argv) = match runner {
+        CrossRunner::Native => return true,
+        CrossRunner::Cross => ("cross", ["cross", "--version"].as_slice()),
+        CrossRunner::Zigbuild => (
+            "cargo-zigbuild",
+            ["cargo", "zigbuild", "--version"].as_slice(),
+        ),
+    };
+    session.installed(tool, || {
+        Command::new(argv[0])
+            .args(&argv[1..])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    })
+}
+
+/// The tool of `runner` and how to install it.
+pub fn install_hint(runner: CrossRunner) -> (&'static str, &'static str) {
+    match runner {
+        CrossRunner::Native => ("cargo", "https://rustup.rs"),
+        CrossRunner::Cross => ("cross", "cargo install cross"),
+        CrossRunner::Zigbuild => (
+            "cargo-zigbuild",
+            "cargo install cargo-zigbuild, it also needs zig on the PATH",
+        ),
+    }
+}
@@ -0,0 +1,204 @@
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    process::{Command, Stdio},
+    str::FromStr,
+};
+
+use serde_json::json;
+
+use crate::{
+    errors::{ConfigError, ExecutionError},
+    executor::{nextest::CARGO_VALUE_OPTIONS, ready::Services, runner::Runner},
+    models::config::CommandType,
+};
+
+/// How `rx debug` launches the built executable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Debugger {
+    /// `rust-lldb`
+    Lldb,
+    /// `rust-gdb`
+    Gdb,
+    /// Print a DAP launch configuration (CodeLLDB format) instead of launching anything
+    Dap,
+}
+
+impl FromStr for Debugger {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lldb" => Ok(Debugger::Lldb),
+            "gdb" => Ok(Debugger::Gdb),
+            "dap" => Ok(Debugger::Dap),
+            _ => Err(ConfigError::UnknownDebugger(s.to_string())),
+        }
+    }
+}
+
+/// A built executable with the arguments, environment and directory of its config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugTarget {
+    pub label: String,
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    pub env: BTreeMap<String, String>,
+    pub cwd: PathBuf,
+}
+
+impl DebugTarget {
+    /// The debugger process to launch, `None` for [`Debugger::Dap`].
+    pub fn command(&self, debugger: Debugger) -> Option<Command> {
+        let mut command = match debugger {
+            Debugger::Lldb => {
+                let mut command = Command::new("rust-lldb");
+                command.arg("--").arg(&self.program).args(&self.args);
+                command
+            }
+            Debugger::Gdb => {
+                let mut command = Command::new("rust-gdb");
+                command.arg("--args").arg(&self.program).args(&self.args);
+                command
+            }
+            Debugger::Dap => return None,
+        };
+
+        command.envs(&self.env).current_dir(&self.cwd);
+        Some(command)
+    }
+
+    /// A DAP `launch` request body, as used in a `.vscode/launch.json` configuration.
+    pub fn dap_launch(&self) -> String {
+        let launch = json!({
+            "type": "lldb",
+            "request": "launch",
+            "name": format!("rx: {}", self.label),
+            "program": self.program,
+            "args": self.args,
+            "cwd": self.cwd,
+            "env": self.env,
+        });
+
+        serde_json::to_string_pretty(&launch).unwrap_or_default()
+    }
+}
+
+/// Splits a `cargo run` or `cargo test` argv into a build-only argv reporting its artifacts as
+/// JSON, and the arguments the built executable receives. `None` for other cargo commands.
+pub fn build_plan(argv: &[String]) -> Option<(Vec<String>, Vec<String>)> {
+    let subcommand = argv.get(1)?.as_str();
+    let rest = &argv[2..];
+    let (cargo_args, program_args) = match rest.iter().position(|arg| arg == "--") {
+        Some(index) => (&rest[..index], &rest[index + 1..]),
+        None => (rest, &[][..]),
+    };
+
+    let mut build: Vec<String> = match subcommand {
+        "run" => vec!["cargo".into(), "build".into()],
+        "test" => vec!["cargo".into(), "test".into(), "--no-run".into()],
+        _ => return None,
+    };
+    let mut filters = Vec::new();
+
+    let mut cargo_args = cargo_args.iter();
+    while let Some(arg) = cargo_args.next() {
+        if CARGO_VALUE_OPTIONS.contains(&arg.as_str()) {
+            build.push(arg.clone());
+            build.extend(cargo_args.next().cloned());
+        } else if arg.starts_with('-') {
+            build.push(arg.clone());
+        } else {
+            // A test name filter, which the test executable takes itself
+            filters.push(arg.clone());
+        }
+    }
+    build.push("--message-format=json-render-diagnostics".into());
+
+    filters.extend(program_args.iter().cloned());
+    Some((build, filters))
+}
+
+/// Executables from cargo's `--message-format=json` output, build scripts excluded.
+pub fn parse_executables(messages: &str) -> Vec<PathBuf> {
+    messages
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter(|message| message["reason"] == "compiler-artifact")
+        .filter(|message| {
+            let kinds = message["target"]["kind"].as_array();
+            !kinds.is_some_and(|kinds| kinds.iter().any(|kind| kind == "custom-build"))
+        })
+        .filter_map(|message| message["executable"].as_str().map(PathBuf::from))
+        .collect()
+}
+
+impl<'a> Runner<'a> {
+    /// Runs the pre_commands of `key`, then builds it without running and returns the single
+    /// executable it produced.
+    pub fn build_debug_target(
+        &self,
+        key: Option<&str>,
+        args: &[String],
+    ) -> Result<DebugTarget, ExecutionError> {
+        let key = self.resolve_key(key)?;
+        let label = self.label(&key);
+        let details = self.target_details(&key, self.details(&key)?, args);
+        let details = details.as_ref();
+
+        let plan = match details.command_type {
+            CommandType::Cargo => build_plan(&self.cargo_argv(details, args)),
+            CommandType::Shell | CommandType::Composite | CommandType::Custom(_) => None,
+        };
+        let (build, program_args) =
+            plan.ok_or_else(|| ExecutionError::NotDebuggable(label.clone()))?;
+
+        // The program is started by the debugger later, pre_commands with `wait_for` only get
+        // as far as ready
+        std::thread::scope(|scope| {
+            let mut services = Services::new(scope);
+            let result = self.run_pre_commands(&key, &mut Vec::new(), &mut services);
+            services.stop(self);
+            result
+        })?;
+
+        let env = self.environment(details)?.to_map();
+        let working_directory = self.working_directory(details);
+        let mut command = Command::new(&build[0]);
+        self.limit_inherited_env(&mut command, details);
+        command
+            .args(&build[1..])
+            .envs(&env)
+            .stdin(Stdio::null())
+            .stderr(Stdio::inherit());
+        if !working_directory.as_os_str().is_empty() {
+            command.current_dir(&working_directory);
+        }
+
+        let output = command
+            .output()
+            .map_err(|err| ExecutionError::Spawn(label.clone(), err))?;
+        if !output.status.success() {
+            return Err(ExecutionError::CommandFailed(label, output.status.code()));
+        }
+
+        let mut executables = parse_executables(&String::from_utf8_lossy(&output.stdout));
+        if executables.len() != 1 {
+            return Err(ExecutionError::NoSingleExecutable(label, executables.len()));
+        }
+
+        let cwd = if working_directory.as_os_str().is_empty() {
+            std::env::current_dir().unwrap_or_default()
+        } else {
+            working_directory
+        };
+
+        Ok(DebugTarget {
+            label,
+            program: executables.remove(0),
+            args: program_args,
+            env: env.into_iter().collect(),
+            cwd,
+        })
+    }
+}
@@ -0,0 +1,67 @@
+use std::{
+    collections::BTreeMap,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use tracing::debug;
+
+use crate::session::Session;
+
+/// Whether `direnv` can be run, checked once per session.
+pub fn is_installed(session: &Session) -> bool {
+    session.installed("direnv", || {
+        Command::new("direnv")
+            .arg("version")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    })
+}
+
+/// The variables the `.envrc` of `dir` exports, as `direnv export json` gives them. Nothing
+/// when `dir` has no `.envrc` or direnv isn't installed, an error when direnv refuses it, e.g.
+/// an `.envrc` that wasn't `direnv allow`ed.
+pub fn export(session: &Session, dir: &Path) -> Result<Vec<(String, String)>, String> {
+    if !dir.join(".envrc").is_file() {
+        return Ok(Vec::new());
+    }
+    if !is_installed(session) {
+        debug!(dir = %dir.display(), "direnv is not installed, .envrc not loaded");
+        return Ok(Vec::new());
+    }
+
+    let output = Command::new("direnv")
+        .args(["export", "json"])
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|err| err.to_string())?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    // direnv reports a blocked .envrc on stderr but still exits with 0
+    let blocked = stderr.lines().find(|line| line.contains("is blocked"));
+    if let Some(line) = blocked.filter(|_| output.stdout.is_empty()) {
+        return Err(line.trim().to_string());
+    }
+    if !output.status.success() {
+        return Err(stderr.trim().to_string());
+    }
+    parse_export(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Reads the output of `direnv export json`: the variables to set, leaving out those it
+/// unsets and its own `DIRENV_*` bookkeeping. Empty output means nothing changed.
+pub fn parse_export(json: &str) -> Result<Vec<(String, String)>, String> {
+    if json.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let variables: BTreeMap<String, Option<String>> =
+        serde_json::from_str(json).map_err(|err| err.to_string())?;
+    Ok(variables
+        .into_iter()
+        .filter(|(name, _)| !name.starts_with("DIRENV_"))
+        .filter_map(|(name, value)| Some((name, value?)))
+        .collect())
+}
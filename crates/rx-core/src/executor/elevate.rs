@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+#[cfg(unix)]
+use std::env;
+
+use crate::session::Session;
+
+/// The tool running commands with elevated rights: `sudo`, or `doas` where only it is
+/// installed. Looked up once per session.
+#[cfg(unix)]
+pub fn elevation_tool(session: &Session) -> &'static str {
+    session.elevation_tool(|| {
+        let on_path = |name: &str| {
+            env::var_os("PATH")
+                .is_some_and(|path| env::split_paths(&path).any(|dir| dir.join(name).is_file()))
+        };
+        if !on_path("sudo") && on_path("doas") {
+            "doas"
+        } else {
+            "sudo"
+        }
+    })
+}
+
+/// `argv` run with elevated rights, or as `user`. sudo and doas reset the environment, so the
+/// config's `env` is passed through `env NAME=VALUE`.
+#[cfg(unix)]
+pub fn wrap(
+    session: &Session,
+    argv: Vec<String>,
+    user: Option<&str>,
+    env: &HashMap<String, String>,
+) -> Vec<String> {
+    wrap_with(elevation_tool(session), argv, user, env)
+}
+
+/// [`wrap`] with the elevation `tool` given.
+#[cfg(unix)]
+pub fn wrap_with(
+    tool: &str,
+    argv: Vec<String>,
+    user: Option<&str>,
+    env: &HashMap<String, String>,
+) -> Vec<String> {
+    let mut wrapped = vec![tool.to_string()];
+    if let Some(user) = user {
+        wrapped.extend(["-u".to_string(), user.to_string()]);
+    }
+    if !env.is_empty() {
+        let mut assignments: Vec<String> = env
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect();
+        assignments.sort();
+        wrapped.push("env".to_string());
+        wrapped.extend(assignments);
+    }
+    wrapped.extend(argv);
+    wrapped
+}
+
+/// `argv` behind a UAC prompt, or started as `user` through `runas`, which asks for their
+/// password. Both start a new window: the output isn't captured, and `runas` doesn't report
+/// the exit code. The elevated process starts with a fresh environment, `env` is not passed.
+#[cfg(windows)]
+pub fn wrap(
+    _session: &Session,
+    argv: Vec<String>,
+    user: Option<&str>,
+    _env: &HashMap<String, String>,
+) -> Vec<String> {
+    match user {
+        Some(user) => vec![
+            "runas".to_string(),
+            format!("/user:{}", user),
+            argv.iter()
+                .map(|arg| quote_windows(arg))
+                .collect::<Vec<_>>()
+                .join(" "),
+        ],
+        None => {
+            let quoted: Vec<String> = argv[1..]
+                .iter()
+                .map(|arg| format!("'{}'", quote_windows(arg).replace('\'', "''")))
+                .collect();
+            let argument_list = if quoted.is_empty() {
+                String::new()
+            } else {
+                format!(" -ArgumentList {}", quoted.join(","))
+            };
+            vec![
+                "powershell".to_string(),
+                "-NoProfile".to_string(),
+                "-Command".to_string(),
+                format!(
+                    "$process = Start-Process -FilePath '{}'{} -Verb RunAs -Wait -PassThru; exit $process.ExitCode",
+                    argv[0].replace('\'', "''"),
+                    argument_list
+                ),
+            ]
+        }
+    }
+}
+
+/// `arg` quoted for a Windows command line where it needs it.
+#[cfg(windows)]
+fn quote_windows(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '"']) {
+        return arg.to_string();
+    }
+    format!("\"{}\"", arg.replace('"', "\\\""))
+}
@@ -0,0 +1,170 @@
+use std::{sync::mpsc, thread};
+
+use crate::{
+    errors::ExecutionError,
+    executor::{composite::parse_step, runner::Runner},
+    models::config::CommandContext,
+};
+
+/// A config of a dry run with what runs before it, see [`Runner::plan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanNode {
+    pub label: String,
+    pub command_line: String,
+    /// The config already runs earlier in the plan, `depends_on` runs each config once
+    pub repeated: bool,
+    /// The configs it depends on, then its pre_commands
+    pub children: Vec<PlanNode>,
+}
+
+impl<'a> Runner<'a> {
+    /// Runs at most `jobs` of the configs `depends_on` runs at a time, as many as there are CPUs
+    /// by default.
+    pub fn jobs(mut self, jobs: usize) -> Self {
+        self.jobs = jobs.max(1);
+        self
+    }
+
+    /// Runs the configs `key` depends on, each after its own dependencies and only once per
+    /// invocation, up to `jobs` of them at a time. Once one fails no others start, and the run
+    /// fails when those running are done.
+    pub(crate) fn run_dependencies(&self, key: &str) -> Result<(), ExecutionError> {
+        let mut pending: Vec<(CommandContext, String)> = self
+            .config
+            .dependency_order(self.context, key)?
+            .into_iter()
+            .filter(|(context, key)| !self.completed(&format!("{}::{}", context, key)))
+            .collect();
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        thread::scope(|scope| {
+            let (sender, receiver) = mpsc::channel();
+            let mut running = 0;
+            let mut failure = None;
+            loop {
+                if failure.is_none() && !self.shutdown.is_triggered() {
+                    let mut index = 0;
+                    while running < self.jobs && index < pending.len() {
+                        let (context, key) = &pending[index];
+                        if !self.dependencies_completed(*context, key)? {
+                            index += 1;
+                            continue;
+                        }
+                        let (context, key) = pending.remove(index);
+                        let runner = self.for_context(context);
+                        let sender = sender.clone();
+                        scope.spawn(move || {
+                            let result = runner.run_step(&key);
+                            let _ = sender.send((runner.label(&key), result));
+                        });
+                        running += 1;
+                    }
+                }
+                if running == 0 {
+                    break;
+                }
+
+                let (label, result) = receiver.recv().expect("a dependency's thread hung up");
+                running -= 1;
+                match result {
+                    Ok(()) => {
+                        self.completed_configs
+                            .lock()
+                            .expect("completed configs poisoned")
+                            .insert(label);
+                    }
+                    Err(err) => {
+                        failure.get_or_insert(err);
+                    }
+                }
+            }
+
+            match failure {
+                Some(err) => Err(err),
+                None if !pending.is_empty() => Err(ExecutionError::Interrupted(self.label(key))),
+                None => Ok(()),
+            }
+        })
+    }
+
+    /// Whether `label` already ran successfully during this invocation.
+    fn completed(&self, label: &str) -> bool {
+        self.completed_configs
+            .lock()
+            .expect("completed configs poisoned")
+            .contains(label)
+    }
+
+    fn dependencies_completed(
+        &self,
+        context: CommandContext,
+        key: &str,
+    ) -> Result<bool, ExecutionError> {
+        let runner = self.for_context(context);
+        for dependency in &runner.details(key)?.depends_on {
+            let (context, key) = parse_step(dependency, context)?;
+            if !self.completed(&format!("{}::{}", context, key)) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// What a run of `key` with `args` would execute, as a tree of the configs it depends on
+    /// and its pre_commands, without running anything. See [`Runner::dry_run`].
+    pub fn plan(&self, key: Option<&str>, args: &[String]) -> Result<PlanNode, ExecutionError> {
+        let key = self.resolve_key(key)?;
+        self.config.dependency_order(self.context, &key)?;
+        self.plan_node(&key, args, &mut Vec::new(), &mut Vec::new())
+    }
+
+    /// The node of `key`. `chain` holds the pre_commands being visited, to catch a cycle, and
+    /// `planned` the labels of the dependencies already in the plan.
+    fn plan_node(
+        &self,
+        key: &str,
+        args: &[String],
+        chain: &mut Vec<String>,
+        planned: &mut Vec<String>,
+    ) -> Result<PlanNode, ExecutionError> {
+        let label = self.label(key);
+        let details = self.details(key)?;
+        let mut children = Vec::new();
+        if !details.service {
+            for dependency in &details.depends_on {
+                let (context, dependency) = parse_step(dependency, self.context)?;
+                let runner = self.for_context(context);
+                let dependency_label = runner.label(dependency);
+                if planned.contains(&dependency_label) {
+                    children.push(PlanNode {
+                        label: dependency_label,
+                        command_line: runner.command_line(dependency, &[])?,
+                        repeated: true,
+                        children: Vec::new(),
+                    });
+                    continue;
+                }
+                children.push(runner.plan_node(dependency, &[], &mut Vec::new(), planned)?);
+                planned.push(dependency_label);
+            }
+        }
+
+        if chain.iter().any(|visited| visited == key) {
+            return Err(ExecutionError::PreCommandCycle(label));
+        }
+        chain.push(key.to_string());
+        for pre_command in &details.pre_command {
+            children.push(self.plan_node(pre_command, &[], chain, planned)?);
+        }
+        chain.pop();
+
+        Ok(PlanNode {
+            command_line: self.command_line(key, args)?,
+            label,
+            repeated: false,
+            children,
+        })
+    }
+}
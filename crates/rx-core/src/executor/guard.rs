@@ -0,0 +1,129 @@
+use std::process::Stdio;
+
+use glob::Pattern;
+
+use crate::{
+    env::process_env,
+    errors::ExecutionError,
+    executor::{
+        composite::StepEvent,
+        runner::{shell_command, Runner},
+        shutdown::DEFAULT_GRACE_PERIOD,
+    },
+    models::config::{BuiltinCondition, CommandDetails, Condition},
+};
+
+/// Whether `os` names the OS or OS family rx runs on.
+pub fn os_matches(os: &str) -> bool {
+    os == std::env::consts::OS || os == std::env::consts::FAMILY
+}
+
+/// Whether the branch `current` is `branch`, or matches it as a glob.
+pub fn branch_matches(branch: &str, current: &str) -> bool {
+    branch == current || Pattern::new(branch).is_ok_and(|pattern| pattern.matches(current))
+}
+
+impl<'a> Runner<'a> {
+    /// Why the config `key` shouldn't run, `None` when its `platforms` and its `only_if` and
+    /// `skip_if` guards let it.
+    pub fn skip_reason(
+        &self,
+        key: &str,
+        details: &CommandDetails,
+    ) -> Result<Option<String>, ExecutionError> {
+        if !details.runs_on_this_platform() {
+            return Ok(Some(format!("not available on {}", std::env::consts::OS)));
+        }
+        if let Some(condition) = &details.only_if {
+            if !self.holds(key, details, condition)? {
+                return Ok(Some(format!("only_if {} does not hold", condition)));
+            }
+        }
+        if let Some(condition) = &details.skip_if {
+            if self.holds(key, details, condition)? {
+                return Ok(Some(format!("skip_if {} holds", condition)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Whether `key` is skipped by its guards, reporting it to the step observer when it is.
+    pub(crate) fn skipped(&self, key: &str) -> Result<bool, ExecutionError> {
+        let Some(reason) = self.skip_reason(key, self.details(key)?)? else {
+            return Ok(false);
+        };
+
+        self.notify_step(StepEvent::Skipped(self.label(key), reason));
+        Ok(true)
+    }
+
+    fn holds(
+        &self,
+        key: &str,
+        details: &CommandDetails,
+        condition: &Condition,
+    ) -> Result<bool, ExecutionError> {
+        match condition {
+            Condition::Builtin(condition) => self.builtin_holds(details, condition),
+            Condition::Shell(expression) => {
+                let mut command = shell_command(&self.interpolation.interpolate(expression));
+                self.limit_inherited_env(&mut command, details);
+                command
+                    .envs(self.environment(details)?.to_map())
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::null());
+                let working_directory = self.working_directory(details);
+                if !working_directory.as_os_str().is_empty() {
+                    command.current_dir(working_directory);
+                }
+
+                let label = format!("{} guard {}", self.label(key), condition);
+                let status = self.spawn(&label, DEFAULT_GRACE_PERIOD, command, None)?;
+                Ok(status.success())
+            }
+        }
+    }
+
+    fn builtin_holds(
+        &self,
+        details: &CommandDetails,
+        condition: &BuiltinCondition,
+    ) -> Result<bool, ExecutionError> {
+        if let Some(os) = &condition.os {
+            if !os_matches(os) {
+                return Ok(false);
+            }
+        }
+        if let Some(name) = &condition.env_set {
+            let value = match self.environment(details)?.get(name) {
+                Some(value) => Some(value.to_string()),
+                None => process_env(details.inherits_env())
+                    .into_iter()
+                    .find_map(|(variable, value)| (variable == *name).then_some(value)),
+            };
+            if value.is_none_or(|value| value.is_empty()) {
+                return Ok(false);
+            }
+        }
+        if let Some(path) = &condition.file_exists {
+            let path = self
+                .working_directory(details)
+                .join(self.interpolation.interpolate(path));
+            if !path.exists() {
+                return Ok(false);
+            }
+        }
+        if let Some(branch) = &condition.branch {
+            if !self
+                .interpolation
+                .get("gitBranch")
+                .is_some_and(|current| branch_matches(branch, current))
+            {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+}
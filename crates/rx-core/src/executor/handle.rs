@@ -0,0 +1,141 @@
+use std::{
+    process::ExitStatus,
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use crate::{
+    errors::ExecutionError,
+    executor::{
+        composite::StepEvent, output::OutputStream, runner::Runner, shutdown::ShutdownSignal,
+    },
+    models::config::{CommandContext, Config},
+};
+
+/// What a run started with [`RunHandle::start`] reports as it goes, in the order it happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunOutput {
+    /// A line the command of the config labeled `label` wrote
+    Line {
+        label: String,
+        stream: OutputStream,
+        line: String,
+    },
+    /// A step, pre_command or guard of the run
+    Step(StepEvent),
+}
+
+/// A config running on a thread of its own, for embedders such as editor plugins to follow
+/// its output, stop it and wait for it without handling processes or signals themselves.
+pub struct RunHandle {
+    shutdown: ShutdownSignal,
+    events: Receiver<RunOutput>,
+    done: Receiver<()>,
+    thread: Option<JoinHandle<Result<ExitStatus, ExecutionError>>>,
+    outcome: Option<Result<ExitStatus, ExecutionError>>,
+}
+
+impl RunHandle {
+    /// Starts `key` of `context`, the default config when `None`, with the extra `args`.
+    /// `configure` sets up the runner, e.g. `|runner| runner.echo_output(false)` to have the
+    /// output only in [`RunHandle::events`]. Observers it sets still get everything.
+    pub fn start<F>(
+        config: Arc<Config>,
+        context: CommandContext,
+        key: Option<String>,
+        args: Vec<String>,
+        configure: F,
+    ) -> RunHandle
+    where
+        F: for<'c> FnOnce(Runner<'c>) -> Runner<'c> + Send + 'static,
+    {
+        let shutdown = ShutdownSignal::new();
+        let (sender, events) = mpsc::channel();
+        let (finished, done) = mpsc::channel();
+        let signal = shutdown.clone();
+        let thread = thread::spawn(move || {
+            let runner = configure(Runner::new(&config, context, signal));
+            let lines = sender.clone();
+            let output = runner.output_observer.clone();
+            let steps = runner.step_observer.clone();
+            let runner = runner
+                .observe_output(Arc::new(move |label, stream, line| {
+                    if let Some(output) = &output {
+                        output(label, stream, line);
+                    }
+                    let _ = lines.send(RunOutput::Line {
+                        label: label.to_string(),
+                        stream,
+                        line: line.to_string(),
+                    });
+                }))
+                .observe_steps(Arc::new(move |event| {
+                    if let Some(steps) = &steps {
+                        steps(event);
+                    }
+                    let _ = sender.send(RunOutput::Step(event.clone()));
+                }));
+            let result = runner.run(key.as_deref(), &args);
+            let _ = finished.send(());
+            result
+        });
+
+        RunHandle {
+            shutdown,
+            events,
+            done,
+            thread: Some(thread),
+            outcome: None,
+        }
+    }
+
+    /// The output and step events of the run. Iterating ends once the run is done.
+    pub fn events(&self) -> &Receiver<RunOutput> {
+        &self.events
+    }
+
+    /// Stops the run as Ctrl-C would: its processes are interrupted, then killed once their
+    /// `shutdown_grace_period` is over. The run then ends with [`ExecutionError::Interrupted`].
+    pub fn cancel(&self) {
+        self.shutdown.trigger();
+    }
+
+    /// Whether the run is done.
+    pub fn is_finished(&self) -> bool {
+        self.thread
+            .as_ref()
+            .is_none_or(|thread| thread.is_finished())
+    }
+
+    /// The outcome of the run once it's done, `None` when it's still running after `timeout`.
+    pub fn wait_with_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Option<&Result<ExitStatus, ExecutionError>> {
+        if self.outcome.is_none() {
+            match self.done.recv_timeout(timeout) {
+                Err(RecvTimeoutError::Timeout) => return None,
+                Ok(()) | Err(RecvTimeoutError::Disconnected) => self.join(),
+            }
+        }
+        self.outcome.as_ref()
+    }
+
+    /// Waits for the run to be done.
+    pub fn wait(mut self) -> Result<ExitStatus, ExecutionError> {
+        self.join();
+        self.outcome
+            .take()
+            .expect("the outcome is set once the run is joined")
+    }
+
+    fn join(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            self.outcome = Some(thread.join().expect("run thread panicked"));
+        }
+    }
+}
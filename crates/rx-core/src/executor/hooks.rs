@@ -0,0 +1,116 @@
+use std::process::ExitStatus;
+
+use crate::{
+    errors::ExecutionError,
+    executor::{
+        runner::{shell_command, Runner},
+        shutdown::DEFAULT_GRACE_PERIOD,
+    },
+    models::config::CommandContext,
+};
+
+/// A single `[hooks]` entry.
+#[derive(Debug, PartialEq, Eq)]
+pub enum HookEntry<'a> {
+    /// `context::key` reference to a config entry
+    Config(CommandContext, &'a str),
+    /// Anything else is run as a shell line
+    Inline(&'a str),
+}
+
+impl<'a> HookEntry<'a> {
+    pub fn parse(entry: &'a str) -> Self {
+        if let Some((context, key)) = entry.split_once("::") {
+            if let Ok(context) = context.parse() {
+                if !key.is_empty() && !key.contains(char::is_whitespace) {
+                    return HookEntry::Config(context, key);
+                }
+            }
+        }
+
+        HookEntry::Inline(entry)
+    }
+}
+
+impl<'a> Runner<'a> {
+    /// Like [`Runner::run`], wrapped in the config's global `[hooks]`.
+    ///
+    /// A failing `before_each` hook aborts the run. Failures of the hooks that run afterwards
+    /// only surface when the command itself succeeded. Inline hooks receive `RX_CONTEXT`,
+    /// `RX_KEY` and, after the run, `RX_EXIT_CODE` in their environment.
+    pub fn run_with_hooks(
+        &self,
+        key: Option<&str>,
+        args: &[String],
+    ) -> Result<ExitStatus, ExecutionError> {
+        let key = self.resolve_key(key)?;
+        let hooks = &self.config.hooks;
+        let mut env = vec![
+            ("RX_CONTEXT", self.context.to_string()),
+            ("RX_KEY", key.clone()),
+        ];
+
+        self.run_hooks("before_each", &hooks.before_each, &env)?;
+
+        let result = self.run(Some(&key), args);
+        let (success, code) = match &result {
+            Ok(status) => (self.details(&key)?.is_success(status.code()), status.code()),
+            Err(ExecutionError::CommandFailed(_, code)) => (false, *code),
+            Err(_) => (false, None),
+        };
+        env.push((
+            "RX_EXIT_CODE",
+            code.map_or(String::new(), |code| code.to_string()),
+        ));
+
+        let outcome_hooks = if success {
+            ("on_success", &hooks.on_success)
+        } else {
+            ("on_failure", &hooks.on_failure)
+        };
+        let after = self
+            .run_hooks("after_each", &hooks.after_each, &env)
+            .and_then(|_| self.run_hooks(outcome_hooks.0, outcome_hooks.1, &env));
+
+        match after {
+            Err(err) if success => Err(err),
+            _ => result,
+        }
+    }
+
+    fn run_hooks(
+        &self,
+        name: &str,
+        entries: &[String],
+        env: &[(&str, String)],
+    ) -> Result<(), ExecutionError> {
+        for entry in entries {
+            let label = format!("{} hook '{}'", name, entry);
+
+            let success = match HookEntry::parse(entry) {
+                HookEntry::Config(context, key) => {
+                    let runner = self.for_context(context);
+                    let status = runner.run(Some(key), &[])?;
+                    runner.details(key)?.is_success(status.code())
+                }
+                HookEntry::Inline(line) => {
+                    let mut command = shell_command(line);
+                    command.envs(env.iter().map(|(key, value)| (*key, value)));
+                    if let Some(workspace_folder) = self.interpolation.get("workspaceFolder") {
+                        command.current_dir(workspace_folder);
+                    }
+
+                    let observer = self.unechoed_observer();
+                    let status = self.spawn(&label, DEFAULT_GRACE_PERIOD, command, observer)?;
+                    status.success()
+                }
+            };
+
+            if !success {
+                return Err(ExecutionError::HookFailed(label));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,203 @@
+use std::{io, process::Command};
+
+use tokio::process::Child;
+
+use crate::models::config::Limits;
+
+/// Sets up `command` to start with `limits`: its address space, niceness, open files, core
+/// dumps and, on Linux, the CPUs it runs on, all inherited by the processes it starts. Windows
+/// applies them once the process exists, see [`attach`].
+#[cfg(unix)]
+pub fn apply(command: &mut Command, limits: &Limits) -> io::Result<()> {
+    use std::os::unix::process::CommandExt;
+
+    let memory = limits
+        .memory_bytes()
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    let nice = limits.nice;
+    let open_files = limits.open_files;
+    let core_dumps = limits.core_dumps;
+    let cpus = limits.cpus.clone();
+    if !cpus.is_empty() && !cfg!(target_os = "linux") {
+        tracing::warn!("limits.cpus is only applied on Linux and Windows");
+    }
+    if let Some(files) = open_files {
+        let hard = hard_limit(libc::RLIMIT_NOFILE);
+        if hard.is_some_and(|hard| (files as libc::rlim_t) > hard) {
+            tracing::warn!(
+                "limits.open_files {} is above the hard limit of {}, which takes the rights to \
+                 raise; the command gets the hard limit without them",
+                files,
+                hard.unwrap_or_default()
+            );
+        }
+    }
+
+    // Only async-signal-safe calls between fork and exec
+    unsafe {
+        command.pre_exec(move || {
+            if let Some(bytes) = memory {
+                let limit = libc::rlimit {
+                    rlim_cur: bytes as libc::rlim_t,
+                    rlim_max: bytes as libc::rlim_t,
+                };
+                if libc::setrlimit(libc::RLIMIT_AS, &limit) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            if let Some(nice) = nice {
+                if libc::setpriority(libc::PRIO_PROCESS as _, 0, nice) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            if let Some(files) = open_files {
+                // The hard limit is only raised when asked past it, which needs the rights to
+                // raise it (root or CAP_SYS_RESOURCE), so without them the soft limit stops at it
+                let files = files as libc::rlim_t;
+                let raised = set_limit(libc::RLIMIT_NOFILE, |hard| (files, hard.max(files)));
+                match raised {
+                    Err(err) if err.raw_os_error() == Some(libc::EPERM) => {
+                        set_limit(libc::RLIMIT_NOFILE, |hard| (files.min(hard), hard))?
+                    }
+                    raised => raised?,
+                }
+            }
+            if let Some(core_dumps) = core_dumps {
+                set_limit(libc::RLIMIT_CORE, |hard| match core_dumps {
+                    true => (hard, hard),
+                    false => (0, hard),
+                })?;
+            }
+            #[cfg(target_os = "linux")]
+            if !cpus.is_empty() {
+                let mut set: libc::cpu_set_t = std::mem::zeroed();
+                for cpu in &cpus {
+                    libc::CPU_SET(*cpu, &mut set);
+                }
+                if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        });
+    }
+    Ok(())
+}
+
+/// Sets the `resource` limit to the soft and hard limits `limits` makes of the current hard
+/// one. Async-signal-safe, for `pre_exec`.
+#[cfg(unix)]
+unsafe fn set_limit(
+    resource: LimitResource,
+    limits: impl Fn(libc::rlim_t) -> (libc::rlim_t, libc::rlim_t),
+) -> io::Result<()> {
+    let mut limit: libc::rlimit = std::mem::zeroed();
+    if libc::getrlimit(resource, &mut limit) != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    (limit.rlim_cur, limit.rlim_max) = limits(limit.rlim_max);
+    if libc::setrlimit(resource, &limit) != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// The hard `resource` limit of rx, which its commands inherit, `None` when unlimited.
+#[cfg(unix)]
+fn hard_limit(resource: LimitResource) -> Option<libc::rlim_t> {
+    let mut limit: libc::rlimit = unsafe { std::mem::zeroed() };
+    let read = unsafe { libc::getrlimit(resource, &mut limit) } == 0;
+    (read && limit.rlim_max != libc::RLIM_INFINITY).then_some(limit.rlim_max)
+}
+
+#[cfg(all(unix, target_os = "linux", target_env = "gnu"))]
+type LimitResource = libc::__rlimit_resource_t;
+#[cfg(not(all(unix, target_os = "linux", target_env = "gnu")))]
+type LimitResource = libc::c_int;
+
+#[cfg(not(unix))]
+pub fn apply(_command: &mut Command, limits: &Limits) -> io::Result<()> {
+    if limits.open_files.is_some() || limits.core_dumps.is_some() {
+        tracing::warn!("limits.open_files and limits.core_dumps are only applied on unix");
+    }
+    Ok(())
+}
+
+/// Puts `child` in a job object holding `limits`. Processes it started before joining escape
+/// them, which only matters for a command that forks right away.
+#[cfg(windows)]
+pub fn attach(child: &Child, limits: &Limits) -> io::Result<()> {
+    use windows_sys::Win32::{
+        Foundation::CloseHandle,
+        System::{
+            JobObjects::{
+                AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation,
+                SetInformationJobObject, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+                JOB_OBJECT_LIMIT_AFFINITY, JOB_OBJECT_LIMIT_JOB_MEMORY,
+                JOB_OBJECT_LIMIT_PRIORITY_CLASS,
+            },
+            Threading::{
+                ABOVE_NORMAL_PRIORITY_CLASS, BELOW_NORMAL_PRIORITY_CLASS, HIGH_PRIORITY_CLASS,
+                IDLE_PRIORITY_CLASS, NORMAL_PRIORITY_CLASS,
+            },
+        },
+    };
+
+    let memory = limits
+        .memory_bytes()
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    let mut info = JOBOBJECT_EXTENDED_LIMIT_INFORMATION::default();
+    let basic = &mut info.BasicLimitInformation;
+    if let Some(bytes) = memory {
+        basic.LimitFlags |= JOB_OBJECT_LIMIT_JOB_MEMORY;
+        info.JobMemoryLimit = bytes as usize;
+    }
+    if let Some(nice) = limits.nice {
+        basic.LimitFlags |= JOB_OBJECT_LIMIT_PRIORITY_CLASS;
+        basic.PriorityClass = match nice {
+            15.. => IDLE_PRIORITY_CLASS,
+            5..=14 => BELOW_NORMAL_PRIORITY_CLASS,
+            -4..=4 => NORMAL_PRIORITY_CLASS,
+            -14..=-5 => ABOVE_NORMAL_PRIORITY_CLASS,
+            _ => HIGH_PRIORITY_CLASS,
+        };
+    }
+    if !limits.cpus.is_empty() {
+        basic.LimitFlags |= JOB_OBJECT_LIMIT_AFFINITY;
+        basic.Affinity = limits
+            .cpus
+            .iter()
+            .filter(|cpu| **cpu < usize::BITS as usize)
+            .fold(0, |mask, cpu| mask | 1 << cpu);
+    }
+    // A child that was already waited for has no handle left
+    let Some(handle) = child.raw_handle().filter(|_| basic.LimitFlags != 0) else {
+        return Ok(());
+    };
+
+    unsafe {
+        let job = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+        if job.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        let attached = SetInformationJobObject(
+            job,
+            JobObjectExtendedLimitInformation,
+            &info as *const JOBOBJECT_EXTENDED_LIMIT_INFORMATION as *const _,
+            std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+        ) != 0
+            && AssignProcessToJobObject(job, handle as _) != 0;
+        let err = io::Error::last_os_error();
+        // The job lives on as long as a process is in it
+        CloseHandle(job);
+        if !attached {
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub fn attach(_child: &Child, _limits: &Limits) -> io::Result<()> {
+    Ok(())
+}
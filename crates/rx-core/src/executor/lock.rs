@@ -0,0 +1,71 @@
+use std::{
+    fs::{self, File, OpenOptions, TryLockError},
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+use tracing::debug;
+
+use crate::{errors::ExecutionError, executor::shutdown::ShutdownSignal};
+
+/// How often a command waiting for a lock tries again, and checks whether rx is stopping.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Where the files of the `lock`s live, shared by every rx of the user.
+pub fn locks_dir() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|dir| dir.join("cargo_runner/locks"))
+}
+
+/// A `lock` held by a command, released when dropped. The lock is the OS's lock of a file,
+/// so it's also released when rx dies, and each [`NamedLock::acquire`] opens the file anew so
+/// two commands of the same rx exclude each other too.
+#[derive(Debug)]
+pub struct NamedLock {
+    _file: File,
+}
+
+impl NamedLock {
+    /// Takes the lock `name`, whose file is in `dir`, for the command `label`. While another
+    /// command holds it, `on_wait` is called once and the lock is tried again until it's free
+    /// or `shutdown` fires.
+    pub fn acquire(
+        dir: &Path,
+        name: &str,
+        label: &str,
+        shutdown: &ShutdownSignal,
+        on_wait: impl FnOnce(),
+    ) -> Result<NamedLock, ExecutionError> {
+        let path = dir.join(format!("{}.lock", name));
+        let error = |err: std::io::Error| {
+            ExecutionError::Lock(name.to_string(), format!("{}: {}", path.display(), err))
+        };
+        fs::create_dir_all(dir).map_err(error)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .map_err(error)?;
+
+        let mut on_wait = Some(on_wait);
+        loop {
+            match file.try_lock() {
+                Ok(()) => {
+                    debug!(label, lock = name, "took the lock");
+                    return Ok(NamedLock { _file: file });
+                }
+                Err(TryLockError::WouldBlock) => {}
+                Err(TryLockError::Error(err)) => return Err(error(err)),
+            }
+            if shutdown.is_triggered() {
+                return Err(ExecutionError::Interrupted(label.to_string()));
+            }
+            if let Some(on_wait) = on_wait.take() {
+                debug!(label, lock = name, "waiting for the lock");
+                on_wait();
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use std::{collections::BTreeMap, process::ExitStatus, thread, time::Instant};
+
+use crate::{
+    errors::{ConfigError, ExecutionError},
+    executor::{
+        composite::{StepEvent, StepReport},
+        output::OutputObserver,
+        runner::Runner,
+    },
+    models::config::CommandDetails,
+};
+
+/// Every combination of the values of a `matrix`, as `(name, value)` pairs ordered by name.
+/// Values keep the order they are listed in, the first name's vary slowest.
+pub fn cells(matrix: &BTreeMap<String, Vec<String>>) -> Vec<Vec<(String, String)>> {
+    let mut cells = vec![Vec::new()];
+    for (name, values) in matrix {
+        cells = cells
+            .into_iter()
+            .flat_map(|cell: Vec<(String, String)>| {
+                values.iter().map(move |value| {
+                    let mut cell = cell.clone();
+                    cell.push((name.clone(), value.clone()));
+                    cell
+                })
+            })
+            .collect();
+    }
+    cells
+}
+
+/// How a cell appears in labels, e.g. `features=a, target=x86_64`.
+pub fn describe(cell: &[(String, String)]) -> String {
+    cell.iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl<'a> Runner<'a> {
+    /// Runs only the cell of the matrix of `key` described as `cell`, e.g. `target=x86_64`, as
+    /// it appears in labels.
+    pub fn only_cell(mut self, key: &str, cell: &str) -> Result<Self, ConfigError> {
+        let values = cells(&self.details(key)?.matrix)
+            .into_iter()
+            .find(|values| describe(values) == cell)
+            .ok_or_else(|| {
+                ConfigError::ConfigKeyNotFound(format!("{}::{} [{}]", self.context, key, cell))
+            })?;
+        for (name, value) in &values {
+            self.interpolation.set(&format!("matrix.{}", name), value);
+        }
+        self.matrix_cell = Some(describe(&values));
+        Ok(self)
+    }
+
+    /// Runs `key` once per cell of its matrix, with `${matrix.<name>}` set to the cell's values,
+    /// one after another or all at once with `parallel = true`. Every cell runs even when one
+    /// fails, the config fails with the first failed cell once all are done.
+    pub(crate) fn run_matrix(
+        &self,
+        key: &str,
+        details: &CommandDetails,
+        args: &[String],
+        observer: Option<&OutputObserver>,
+    ) -> Result<ExitStatus, ExecutionError> {
+        let runners: Vec<Runner> = cells(&details.matrix)
+            .iter()
+            .map(|cell| {
+                let mut runner = self.for_context(self.context);
+                for (name, value) in cell {
+                    runner.interpolation.set(&format!("matrix.{}", name), value);
+                }
+                runner.matrix_cell = Some(describe(cell));
+                runner.stdin = self.stdin.clone();
+                runner
+            })
+            .collect();
+
+        let reports = if details.parallel {
+            let results: Vec<_> = thread::scope(|scope| {
+                let handles: Vec<_> = runners
+                    .iter()
+                    .map(|runner| scope.spawn(move || runner.run_cell(key, args, observer)))
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|handle| handle.join().expect("matrix cell panicked"))
+                    .collect()
+            });
+            results.into_iter().collect::<Result<Vec<_>, _>>()?
+        } else {
+            runners
+                .iter()
+                .map(|runner| runner.run_cell(key, args, observer))
+                .collect::<Result<Vec<_>, _>>()?
+        };
+
+        self.notify_step(StepEvent::MatrixFinished(self.label(key), reports.clone()));
+        match reports.into_iter().find(|report| !report.success) {
+            Some(failed) => Err(ExecutionError::CommandFailed(failed.label, failed.code)),
+            None => Ok(ExitStatus::default()),
+        }
+    }
+
+    /// Runs a single cell and reports it. Only an interrupted cell is an error, a failure is
+    /// left in the report so the other cells still run.
+    fn run_cell(
+        &self,
+        key: &str,
+        args: &[String],
+        observer: Option<&OutputObserver>,
+    ) -> Result<StepReport, ExecutionError> {
+        let label = self.label(key);
+        self.notify_step(StepEvent::Started(label.clone()));
+
+        let started = Instant::now();
+        let (success, code, error) = match self.execute(key, args, observer) {
+            Ok(status) => (
+                self.details(key)?.is_success(status.code()),
+                status.code(),
+                None,
+            ),
+            Err(err @ ExecutionError::Interrupted(_)) => return Err(err),
+            Err(err) => (false, None, Some(err.to_string())),
+        };
+        let report = StepReport {
+            label,
+            success,
+            code,
+            error,
+            elapsed: started.elapsed(),
+        };
+        self.notify_step(StepEvent::Finished(report.clone()));
+        Ok(report)
+    }
+}
@@ -0,0 +1,30 @@
+pub mod cache;
+pub mod composite;
+pub mod container;
+pub mod coverage;
+pub mod cross;
+pub mod debug;
+pub mod direnv;
+pub mod elevate;
+pub mod graph;
+pub mod guard;
+pub mod handle;
+pub mod hooks;
+pub mod limits;
+pub mod lock;
+pub mod matrix;
+pub mod nextest;
+pub mod output;
+pub mod plugin;
+pub mod pty;
+pub mod queue;
+pub mod ready;
+pub mod registry;
+pub mod remote;
+pub mod runner;
+pub mod sandbox;
+pub mod script;
+pub mod shutdown;
+pub mod temp;
+pub mod vars;
+pub mod wasm;
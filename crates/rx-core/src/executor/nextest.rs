@@ -0,0 +1,168 @@
+use std::{
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use crate::{models::config::NextestOptions, session::Session};
+
+/// `cargo test` options followed by a value, needed to tell test name filters apart.
+pub(crate) const CARGO_VALUE_OPTIONS: &[&str] = &[
+    "-p",
+    "--package",
+    "--exclude",
+    "--test",
+    "--bin",
+    "--example",
+    "--bench",
+    "-F",
+    "--features",
+    "--target",
+    "--target-dir",
+    "--profile",
+    "-j",
+    "--jobs",
+    "--manifest-path",
+    "--color",
+    "--message-format",
+    "--config",
+    "-Z",
+];
+
+/// `cargo test` options nextest has no equivalent for, such configs keep using `cargo test`.
+const UNSUPPORTED_OPTIONS: &[&str] = &["--doc", "--no-run"];
+
+/// Whether `cargo nextest` can be run, checked once per session.
+pub fn is_installed(session: &Session) -> bool {
+    session.installed("cargo-nextest", || {
+        Command::new("cargo")
+            .args(["nextest", "--version"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    })
+}
+
+/// Translates a `cargo test ...` argv to the equivalent `cargo nextest run ...` argv.
+///
+/// Test name filters stay positional, `--exact` and `--skip` become a filter expression and
+/// the libtest flags nextest renames are mapped. Returns `None` for any other cargo command or
+/// when the invocation uses something nextest can't do, such as doctests.
+pub fn translate(
+    argv: &[String],
+    options: &NextestOptions,
+    tool_config: Option<&Path>,
+) -> Option<Vec<String>> {
+    if argv.get(1).map(String::as_str) != Some("test") {
+        return None;
+    }
+
+    let rest = &argv[2..];
+    let (cargo_args, harness_args) = match rest.iter().position(|arg| arg == "--") {
+        Some(index) => (&rest[..index], &rest[index + 1..]),
+        None => (rest, &[][..]),
+    };
+
+    let mut translated: Vec<String> = ["cargo", "nextest", "run"].map(String::from).to_vec();
+    let mut filters = Vec::new();
+
+    let mut cargo_args = cargo_args.iter();
+    while let Some(arg) = cargo_args.next() {
+        if UNSUPPORTED_OPTIONS.contains(&arg.as_str()) {
+            return None;
+        } else if CARGO_VALUE_OPTIONS.contains(&arg.as_str()) {
+            translated.push(arg.clone());
+            translated.extend(cargo_args.next().cloned());
+        } else if arg.starts_with('-') {
+            translated.push(arg.clone());
+        } else {
+            // The positional TESTNAME filter
+            filters.push(arg.clone());
+        }
+    }
+
+    let mut exact = false;
+    let mut skipped = Vec::new();
+    let mut passthrough = Vec::new();
+
+    let mut harness_args = harness_args.iter();
+    while let Some(arg) = harness_args.next() {
+        match arg.as_str() {
+            "--exact" => exact = true,
+            "--skip" => skipped.extend(harness_args.next().cloned()),
+            "--ignored" => translated.extend(["--run-ignored".into(), "only".into()]),
+            "--include-ignored" => translated.extend(["--run-ignored".into(), "all".into()]),
+            "--nocapture" => translated.push("--no-capture".into()),
+            "--test-threads" => {
+                translated.push("--test-threads".into());
+                translated.extend(harness_args.next().cloned());
+            }
+            arg if arg.starts_with("--test-threads=") => translated.push(arg.to_string()),
+            arg if !arg.starts_with('-') => filters.push(arg.to_string()),
+            _ => passthrough.push(arg.clone()),
+        }
+    }
+
+    // Multiple `-E` are unioned, so everything goes into a single expression. Name filters
+    // are intersected with it.
+    let matcher = |name: &String| {
+        if exact {
+            format!("test(={})", name)
+        } else {
+            format!("test({})", name)
+        }
+    };
+    let included = (exact && !filters.is_empty()).then(|| {
+        filters
+            .drain(..)
+            .map(|filter| matcher(&filter))
+            .collect::<Vec<_>>()
+            .join(" | ")
+    });
+    let excluded =
+        (!skipped.is_empty()).then(|| skipped.iter().map(matcher).collect::<Vec<_>>().join(" | "));
+
+    let expression = match (included, excluded) {
+        (Some(included), Some(excluded)) => Some(format!("({}) - ({})", included, excluded)),
+        (Some(included), None) => Some(included),
+        (None, Some(excluded)) => Some(format!("not ({})", excluded)),
+        (None, None) => None,
+    };
+    if let Some(expression) = expression {
+        translated.extend(["-E".into(), expression]);
+    }
+    translated.append(&mut filters);
+
+    if let Some(profile) = &options.profile {
+        translated.extend(["--profile".into(), profile.clone()]);
+    }
+    if let Some(partition) = &options.partition {
+        translated.extend(["--partition".into(), partition.clone()]);
+    }
+    if let Some(tool_config) = tool_config {
+        translated.extend([
+            "--tool-config-file".into(),
+            format!("rx:{}", tool_config.display()),
+        ]);
+    }
+
+    if !passthrough.is_empty() {
+        translated.push("--".into());
+        translated.append(&mut passthrough);
+    }
+
+    Some(translated)
+}
+
+/// Tool config setting the JUnit report path of the selected profile, nextest has no flag for it.
+pub fn junit_tool_config(options: &NextestOptions) -> Option<String> {
+    let junit = options.junit.as_ref()?;
+    let profile = options.profile.as_deref().unwrap_or("default");
+
+    Some(format!(
+        "[profile.{}.junit]\npath = {}\n",
+        toml::Value::String(profile.to_string()),
+        toml::Value::String(junit.clone())
+    ))
+}
@@ -0,0 +1,273 @@
+use std::{
+    borrow::Cow,
+    collections::VecDeque,
+    fs::File,
+    io::{self, Write},
+    sync::{Arc, Mutex},
+};
+
+use encoding_rs::{CoderResult, Decoder, Encoding};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt},
+    sync::mpsc,
+    task,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// Receives every line the main command writes with the label of the config writing it, after
+/// it was echoed to rx's own output.
+pub type OutputObserver = Arc<dyn Fn(&str, OutputStream, &str) + Send + Sync>;
+
+/// How [`pump`] copies the output to rx's matching stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Echo {
+    /// Not at all, the observer shows it
+    Off,
+    /// Byte for byte, as the command wrote it
+    Raw,
+    /// Decoded to UTF-8 line by line
+    Decoded,
+}
+
+/// Caps the lines of a command handed to its observer at `max_lines`: the first half as they
+/// come, then the latest of the rest in a ring buffer, handed over by [`OutputLimit::finish`]
+/// after a marker for the lines dropped in between.
+#[derive(Debug)]
+pub struct OutputLimit {
+    max_lines: usize,
+    state: Mutex<LimitState>,
+}
+
+#[derive(Debug, Default)]
+struct LimitState {
+    passed: usize,
+    tail: VecDeque<(OutputStream, String)>,
+    dropped: usize,
+}
+
+impl OutputLimit {
+    pub fn new(max_lines: usize) -> Self {
+        Self {
+            max_lines,
+            state: Mutex::new(LimitState::default()),
+        }
+    }
+
+    /// Whether `line` goes to the observer now, otherwise it's kept for `finish` or dropped.
+    pub(crate) fn admit(&self, stream: OutputStream, line: &str) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let tail_lines = self.max_lines / 2;
+        if state.passed < self.max_lines - tail_lines {
+            state.passed += 1;
+            return true;
+        }
+        state.tail.push_back((stream, line.to_string()));
+        if state.tail.len() > tail_lines {
+            state.tail.pop_front();
+            state.dropped += 1;
+        }
+        false
+    }
+
+    /// Hands the kept lines to `observer` once the command finished, after a marker on stderr
+    /// when lines were dropped.
+    pub fn finish(&self, label: &str, observer: &OutputObserver) {
+        let mut state = self.state.lock().unwrap();
+        if state.dropped > 0 {
+            let marker = format!("[rx: output truncated, {} lines dropped]", state.dropped);
+            observer(label, OutputStream::Stderr, &marker);
+        }
+        for (stream, line) in state.tail.drain(..) {
+            observer(label, stream, &line);
+        }
+    }
+}
+
+/// The `log_file` of a command, its lines appended as they come.
+#[derive(Debug)]
+pub struct OutputLog {
+    file: Mutex<File>,
+    keep_ansi: bool,
+}
+
+impl OutputLog {
+    pub fn new(file: File, keep_ansi: bool) -> Self {
+        Self {
+            file: Mutex::new(file),
+            keep_ansi,
+        }
+    }
+
+    pub(crate) fn write(&self, line: &str) {
+        let line = match self.keep_ansi {
+            true => Cow::Borrowed(line),
+            false => strip_ansi(line),
+        };
+        let _ = writeln!(self.file.lock().unwrap(), "{}", line);
+    }
+}
+
+/// `text` without its ANSI escape sequences: colors and cursor movements (CSI), titles and
+/// links (OSC, up to BEL or ST) and the two-character ones.
+pub fn strip_ansi(text: &str) -> Cow<'_, str> {
+    if !text.contains('\x1b') {
+        return Cow::Borrowed(text);
+    }
+
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            stripped.push(c);
+            continue;
+        }
+        match chars.next() {
+            // Parameters and intermediates up to a final byte from @ to ~
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Cow::Owned(stripped)
+}
+
+/// The encoding of `label`, such as `windows-1252`, `shift_jis` or `utf-16le`.
+pub fn encoding(label: &str) -> Option<&'static Encoding> {
+    Encoding::for_label(label.trim().as_bytes())
+}
+
+/// Chunks of output read ahead of their formatting, per stream. Once they're full the
+/// command's writes wait on its pipe until rx catches up, so a command writing megabytes holds
+/// rx to a few hundred KiB instead of its whole output.
+pub const OUTPUT_BUFFER_CHUNKS: usize = 64;
+
+/// Bytes read from a command's output at once.
+const CHUNK_SIZE: usize = 8192;
+
+/// Hands `reader` to `observer` line by line, decoded from `encoding` with the bytes that
+/// aren't valid replaced, copying the output to rx's matching stream first as `echo` says.
+///
+/// The reading stays on the runtime while the echo, decoding and observer run on a blocking
+/// thread, the two apart by a channel of [`OUTPUT_BUFFER_CHUNKS`]: a slow terminal or observer
+/// never holds the runtime's workers, and when the observer is gone the rest is read and
+/// dropped so the command isn't left blocked on a full pipe.
+pub async fn pump<R>(
+    mut reader: R,
+    label: String,
+    stream: OutputStream,
+    observer: OutputObserver,
+    echo: Echo,
+    encoding: &'static Encoding,
+) where
+    R: AsyncRead + Unpin,
+{
+    let (sender, receiver) = mpsc::channel(OUTPUT_BUFFER_CHUNKS);
+    let formatter = task::spawn_blocking(move || {
+        format_lines(receiver, &label, stream, &observer, echo, encoding)
+    });
+
+    let mut buffer = vec![0; CHUNK_SIZE];
+    let mut formatting = true;
+    loop {
+        let read = match reader.read(&mut buffer).await {
+            Ok(0) | Err(_) => break,
+            Ok(read) => read,
+        };
+        if formatting {
+            formatting = sender.send(buffer[..read].to_vec()).await.is_ok();
+        }
+    }
+    drop(sender);
+    let _ = formatter.await;
+}
+
+/// The blocking end of [`pump`]: echoes the chunks as they come and hands their lines to
+/// `observer`, the last one even without a newline.
+fn format_lines(
+    mut chunks: mpsc::Receiver<Vec<u8>>,
+    label: &str,
+    stream: OutputStream,
+    observer: &OutputObserver,
+    echo: Echo,
+    encoding: &'static Encoding,
+) {
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let mut text = String::new();
+
+    loop {
+        let chunk = chunks.blocking_recv();
+        let bytes = chunk.as_deref().unwrap_or_default();
+        if echo == Echo::Raw {
+            let _ = write_through(stream, bytes);
+        }
+
+        let last = chunk.is_none();
+        decode(&mut decoder, bytes, &mut text, last);
+        let mut start = 0;
+        while let Some(end) = text[start..].find('\n') {
+            emit(label, stream, observer, echo, &text[start..start + end + 1]);
+            start += end + 1;
+        }
+        text.drain(..start);
+        if last {
+            if !text.is_empty() {
+                emit(label, stream, observer, echo, &text);
+            }
+            break;
+        }
+    }
+}
+
+/// Appends `bytes` decoded to `text`, keeping an incomplete sequence at their end for the next
+/// call unless it's the `last`.
+fn decode(decoder: &mut Decoder, mut bytes: &[u8], text: &mut String, last: bool) {
+    loop {
+        let needed = decoder
+            .max_utf8_buffer_length(bytes.len())
+            .unwrap_or(bytes.len() * 3 + 16);
+        text.reserve(needed);
+        let (result, read, _) = decoder.decode_to_string(bytes, text, last);
+        bytes = &bytes[read..];
+        if result == CoderResult::InputEmpty {
+            return;
+        }
+    }
+}
+
+fn emit(label: &str, stream: OutputStream, observer: &OutputObserver, echo: Echo, line: &str) {
+    if echo == Echo::Decoded {
+        let _ = write_through(stream, line.as_bytes());
+    }
+    observer(label, stream, line.trim_end_matches(['\n', '\r']));
+}
+
+fn write_through(stream: OutputStream, bytes: &[u8]) -> io::Result<()> {
+    match stream {
+        OutputStream::Stdout => write_all(io::stdout().lock(), bytes),
+        OutputStream::Stderr => write_all(io::stderr().lock(), bytes),
+    }
+}
+
+fn write_all(mut writer: impl Write, bytes: &[u8]) -> io::Result<()> {
+    writer.write_all(bytes)?;
+    writer.flush()
+}
@@ -0,0 +1,97 @@
+use std::process::{Command, Stdio};
+
+use crate::session::Session;
+
+/// Subcommands cargo ships with, never looked up as third-party `cargo-<name>` binaries.
+const BUILTIN_SUBCOMMANDS: &[&str] = &[
+    "add",
+    "b",
+    "bench",
+    "build",
+    "c",
+    "check",
+    "clean",
+    "config",
+    "d",
+    "doc",
+    "fetch",
+    "fix",
+    "generate-lockfile",
+    "help",
+    "info",
+    "init",
+    "install",
+    "locate-project",
+    "login",
+    "logout",
+    "metadata",
+    "new",
+    "owner",
+    "package",
+    "pkgid",
+    "publish",
+    "r",
+    "remove",
+    "report",
+    "rm",
+    "run",
+    "rustc",
+    "rustdoc",
+    "search",
+    "t",
+    "test",
+    "tree",
+    "uninstall",
+    "update",
+    "vendor",
+    "verify-project",
+    "version",
+    "yank",
+];
+
+/// The third-party subcommand a cargo `argv` runs, such as `nextest` for `cargo nextest run`.
+/// `None` for cargo's own subcommands.
+pub fn plugin(argv: &[String]) -> Option<&str> {
+    argv.iter()
+        .skip(1)
+        .map(String::as_str)
+        // A `+toolchain` or global option such as `--locked` comes first
+        .find(|arg| !arg.starts_with('+') && !arg.starts_with('-'))
+        .filter(|subcommand| !BUILTIN_SUBCOMMANDS.contains(subcommand))
+}
+
+/// Whether `cargo <subcommand>` can be run, per `cargo --list` which also has the aliases. The
+/// list is read once per session.
+pub fn is_installed(session: &Session, subcommand: &str) -> bool {
+    session
+        .cargo_subcommands(|| {
+            Command::new("cargo")
+                .arg("--list")
+                .stdin(Stdio::null())
+                .stderr(Stdio::null())
+                .output()
+                .map(|output| listed_subcommands(&String::from_utf8_lossy(&output.stdout)))
+                .unwrap_or_default()
+        })
+        .iter()
+        .any(|listed| listed == subcommand)
+}
+
+/// The subcommands in the output of `cargo --list`, one indented per line after a header.
+pub fn listed_subcommands(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter(|line| line.starts_with(char::is_whitespace))
+        .filter_map(|line| line.split_whitespace().next())
+        .map(String::from)
+        .collect()
+}
+
+/// What `cargo install` takes to install `subcommand`: the config's `install_hint`, or else
+/// the `cargo-<subcommand>` crate most plugins are published as.
+pub fn install_args(subcommand: &str, install_hint: Option<&str>) -> Vec<String> {
+    match install_hint {
+        Some(hint) => hint.split_whitespace().map(String::from).collect(),
+        None => vec![format!("cargo-{}", subcommand)],
+    }
+}
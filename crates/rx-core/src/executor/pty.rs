@@ -0,0 +1,223 @@
+use std::{
+    fs::File,
+    io,
+    process::Command,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
+
+use crate::session::Session;
+
+/// How often the terminal is checked for input and a new size.
+#[cfg(unix)]
+const POLL_MILLIS: libc::c_int = 50;
+
+/// The rx side of a pseudo-terminal a command runs attached to, for `pty = true`.
+pub struct Pty {
+    master: File,
+}
+
+impl Pty {
+    /// Opens a pseudo-terminal the size of rx's terminal and makes it the controlling terminal,
+    /// stdin, stdout and stderr of `command`. The command leads a new session and so its own
+    /// process group, it must not also be put in one with
+    /// [`super::shutdown::isolate_process_group`].
+    #[cfg(unix)]
+    pub fn attach(command: &mut Command) -> io::Result<Pty> {
+        use std::os::{fd::FromRawFd, unix::process::CommandExt};
+
+        let (mut master, mut slave) = (0, 0);
+        let size = window_size();
+        let opened = unsafe {
+            libc::openpty(
+                &mut master,
+                &mut slave,
+                std::ptr::null_mut(),
+                std::ptr::null(),
+                &size,
+            )
+        };
+        if opened != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let master = unsafe { File::from_raw_fd(master) };
+        let slave = unsafe { File::from_raw_fd(slave) };
+        close_on_exec(&master)?;
+        close_on_exec(&slave)?;
+
+        command
+            .stdin(slave.try_clone()?)
+            .stdout(slave.try_clone()?)
+            .stderr(slave);
+        // Only async-signal-safe calls between fork and exec
+        unsafe {
+            command.pre_exec(|| {
+                if libc::setsid() == -1 || libc::ioctl(0, libc::TIOCSCTTY as _, 0) == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        Ok(Pty { master })
+    }
+
+    #[cfg(not(unix))]
+    pub fn attach(_command: &mut Command) -> io::Result<Pty> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "pseudo-terminals are only supported on unix",
+        ))
+    }
+
+    /// What the command writes, its stdout and stderr together as a terminal shows them. Reads
+    /// end once the command and whatever it started closed the terminal.
+    pub fn output(&self) -> io::Result<File> {
+        self.master.try_clone()
+    }
+
+    /// Keeps the terminal's size in step with rx's and, when rx's stdin is a terminal nobody
+    /// else reads, passes the keystrokes on with rx's terminal in raw mode, so Ctrl-C and
+    /// the like reach the command, a terminal read by one reader of `session` at a time. Runs
+    /// until [`PtySession::finish`].
+    pub fn session(&self, session: &Session) -> io::Result<PtySession> {
+        let master = self.master.try_clone()?;
+        let done = Arc::new(AtomicBool::new(false));
+        let stop = done.clone();
+        let session = session.clone();
+        let handle = thread::spawn(move || forward(master, &stop, &session));
+        Ok(PtySession {
+            done,
+            handle: Some(handle),
+        })
+    }
+}
+
+/// The thread serving a [`Pty`] while its command runs.
+pub struct PtySession {
+    done: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl PtySession {
+    /// Stops forwarding and gives rx's terminal its mode back.
+    pub fn finish(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        self.done.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for PtySession {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(unix)]
+fn forward(mut master: File, done: &AtomicBool, session: &Session) {
+    use std::io::{IsTerminal, Read, Write};
+    use std::os::fd::{AsRawFd, FromRawFd};
+
+    // Parallel steps, or `--keys`, would fight over the keystrokes
+    let owns_stdin = io::stdin().is_terminal() && crate::keys::claim_stdin(session);
+    let saved = if owns_stdin { raw_mode() } else { None };
+
+    let mut size = window_size();
+    let mut buffer = [0; 1024];
+    while !done.load(Ordering::SeqCst) {
+        let current = window_size();
+        if (current.ws_col, current.ws_row) != (size.ws_col, size.ws_row) {
+            size = current;
+            unsafe {
+                libc::ioctl(master.as_raw_fd(), libc::TIOCSWINSZ, &size);
+            }
+        }
+
+        if !owns_stdin {
+            thread::sleep(std::time::Duration::from_millis(POLL_MILLIS as u64));
+            continue;
+        }
+        let mut poll = libc::pollfd {
+            fd: libc::STDIN_FILENO,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        if unsafe { libc::poll(&mut poll, 1, POLL_MILLIS) } <= 0 || poll.revents == 0 {
+            continue;
+        }
+        // Read straight from the fd, std's buffered stdin could hold keystrokes back
+        let mut stdin = unsafe { std::mem::ManuallyDrop::new(File::from_raw_fd(0)) };
+        match stdin.read(&mut buffer) {
+            Ok(read) if read > 0 => {
+                if master.write_all(&buffer[..read]).is_err() {
+                    break;
+                }
+            }
+            _ => break,
+        }
+    }
+
+    if let Some(saved) = saved {
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &saved);
+        }
+    }
+    if owns_stdin {
+        crate::keys::release_stdin(session);
+    }
+}
+
+#[cfg(not(unix))]
+fn forward(_master: File, _done: &AtomicBool, _session: &Session) {}
+
+/// Puts rx's terminal in raw mode, keeping its output processing for rx's own lines, and
+/// returns the mode to restore.
+#[cfg(unix)]
+fn raw_mode() -> Option<libc::termios> {
+    unsafe {
+        let mut saved: libc::termios = std::mem::zeroed();
+        if libc::tcgetattr(libc::STDIN_FILENO, &mut saved) != 0 {
+            return None;
+        }
+        let mut raw = saved;
+        libc::cfmakeraw(&mut raw);
+        raw.c_oflag |= libc::OPOST;
+        if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) != 0 {
+            return None;
+        }
+        Some(saved)
+    }
+}
+
+#[cfg(unix)]
+fn window_size() -> libc::winsize {
+    let (width, height) = crate::split::terminal_size();
+    libc::winsize {
+        ws_row: height.min(u16::MAX as usize) as u16,
+        ws_col: width.min(u16::MAX as usize) as u16,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    }
+}
+
+#[cfg(unix)]
+fn close_on_exec(file: &File) -> io::Result<()> {
+    use std::os::fd::AsRawFd;
+
+    let fd = file.as_raw_fd();
+    unsafe {
+        let flags = libc::fcntl(fd, libc::F_GETFD);
+        if flags == -1 || libc::fcntl(fd, libc::F_SETFD, flags | libc::FD_CLOEXEC) == -1 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,150 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Condvar, Mutex},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{errors::ExecutionError, executor::shutdown::ShutdownSignal};
+
+/// How often a queued process checks whether rx is stopping.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The processes rx runs at a time, at most `max_concurrent_runs` of them, the others waiting
+/// their turn in the order they came. Shared by the runs of a daemon and the parallel steps,
+/// matrix cells and dependencies of a run. Services and pre_commands left running don't wait,
+/// only the processes rx waits for take a place.
+#[derive(Debug, Clone, Default)]
+pub struct RunQueue(Arc<(Mutex<QueueState>, Condvar)>);
+
+#[derive(Debug, Default)]
+struct QueueState {
+    max: Option<usize>,
+    running: Vec<(u64, String)>,
+    waiting: VecDeque<(u64, String)>,
+    next_id: u64,
+}
+
+impl QueueState {
+    fn has_room(&self) -> bool {
+        self.max.is_none_or(|max| self.running.len() < max)
+    }
+}
+
+/// The processes of a queue by label, as `rx queue` lists them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueueSnapshot {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<usize>,
+    pub running: Vec<String>,
+    /// First in line first
+    pub queued: Vec<String>,
+}
+
+impl QueueSnapshot {
+    /// Reads the `queue` answer of `rx daemon`.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|err| format!("invalid queue: {}", err))
+    }
+}
+
+/// A place in a [`RunQueue`], given back when dropped.
+#[derive(Debug)]
+pub struct QueueSlot {
+    queue: RunQueue,
+    id: u64,
+}
+
+impl Drop for QueueSlot {
+    fn drop(&mut self) {
+        let (state, changed) = &*self.queue.0;
+        state
+            .lock()
+            .unwrap()
+            .running
+            .retain(|(id, _)| *id != self.id);
+        changed.notify_all();
+    }
+}
+
+impl RunQueue {
+    /// A queue running at most `max` processes at a time, any number when `None`.
+    pub fn new(max: Option<usize>) -> Self {
+        let queue = Self::default();
+        queue.set_max(max);
+        queue
+    }
+
+    /// Changes the limit, e.g. when the config is reloaded. Processes already running go on.
+    pub fn set_max(&self, max: Option<usize>) {
+        let (state, changed) = &*self.0;
+        state.lock().unwrap().max = max;
+        changed.notify_all();
+    }
+
+    pub fn snapshot(&self) -> QueueSnapshot {
+        let state = self.0 .0.lock().unwrap();
+        QueueSnapshot {
+            max: state.max,
+            running: state
+                .running
+                .iter()
+                .map(|(_, label)| label.clone())
+                .collect(),
+            queued: state
+                .waiting
+                .iter()
+                .map(|(_, label)| label.clone())
+                .collect(),
+        }
+    }
+
+    /// Waits for a place for the process of `label`, telling `on_wait` its position in the
+    /// queue, 1 being next, whenever it changes. Leaves the queue when `shutdown` fires.
+    pub fn enter(
+        &self,
+        label: &str,
+        shutdown: &ShutdownSignal,
+        on_wait: impl Fn(usize),
+    ) -> Result<QueueSlot, ExecutionError> {
+        let (state, changed) = &*self.0;
+        let mut guard = state.lock().unwrap();
+        let id = guard.next_id;
+        guard.next_id += 1;
+        guard.waiting.push_back((id, label.to_string()));
+
+        let mut reported = None;
+        loop {
+            let position = guard
+                .waiting
+                .iter()
+                .position(|(waiting, _)| *waiting == id)
+                .expect("a waiting process stays in the queue")
+                + 1;
+            if position == 1 && guard.has_room() {
+                guard.waiting.pop_front();
+                guard.running.push((id, label.to_string()));
+                // The next in line may fit as well
+                changed.notify_all();
+                return Ok(QueueSlot {
+                    queue: self.clone(),
+                    id,
+                });
+            }
+            if shutdown.is_triggered() {
+                guard.waiting.retain(|(waiting, _)| *waiting != id);
+                changed.notify_all();
+                return Err(ExecutionError::Interrupted(label.to_string()));
+            }
+            if reported != Some(position) {
+                reported = Some(position);
+                drop(guard);
+                on_wait(position);
+                guard = state.lock().unwrap();
+                continue;
+            }
+            guard = changed.wait_timeout(guard, POLL_INTERVAL).unwrap().0;
+        }
+    }
+}
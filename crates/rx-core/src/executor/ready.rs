@@ -0,0 +1,449 @@
+use std::{
+    fs, io,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr},
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{Scope, ScopedJoinHandle},
+    time::{Duration, Instant},
+};
+
+use regex::Regex;
+use tokio::net::TcpStream;
+use tracing::info;
+
+use crate::{
+    errors::{ConfigError, ExecutionError},
+    executor::{
+        composite::{StepEvent, StepReport},
+        output::OutputObserver,
+        runner::Runner,
+        shutdown::ShutdownSignal,
+    },
+    models::config::{CommandDetails, WaitFor},
+    service,
+    session::Session,
+};
+
+/// How long a config gets to be ready when its `wait_for` has no timeout.
+pub const DEFAULT_READY_TIMEOUT: Duration = Duration::from_secs(60);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long a single check of a port or URL may take.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The configs with `wait_for` started for a run, each on a thread of `scope`, stopped
+/// together when the run is done with them.
+pub(crate) struct Services<'scope, 'env> {
+    scope: &'scope Scope<'scope, 'env>,
+    running: Vec<Service<'scope>>,
+}
+
+/// The thread running a service's command, with the exit status counting as success.
+type ServiceThread<'scope> = ScopedJoinHandle<'scope, Result<ExitStatus, ExecutionError>>;
+
+struct Service<'scope> {
+    label: String,
+    kind: ServiceKind,
+    started: Instant,
+    shutdown: ShutdownSignal,
+    /// `None` once the command exited, having started what it runs in the background
+    thread: Option<ServiceThread<'scope>>,
+}
+
+/// How a service was started, which decides the events it's reported with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ServiceKind {
+    Step,
+    PreCommand,
+}
+
+impl ServiceKind {
+    fn finished(self, report: StepReport) -> StepEvent {
+        match self {
+            ServiceKind::Step => StepEvent::Finished(report),
+            ServiceKind::PreCommand => StepEvent::PreCommandFinished(report),
+        }
+    }
+}
+
+impl<'scope, 'env> Services<'scope, 'env> {
+    pub(crate) fn new(scope: &'scope Scope<'scope, 'env>) -> Self {
+        Services {
+            scope,
+            running: Vec::new(),
+        }
+    }
+
+    /// Interrupts the services, the last started first, and reports each once it stopped. A
+    /// service whose command failed on its own before is reported with that failure.
+    pub(crate) fn stop(self, runner: &Runner) {
+        for service in self.running.into_iter().rev() {
+            // Checked before interrupting, a service stopping right away didn't exit on its own
+            let finished_before = service
+                .thread
+                .as_ref()
+                .is_some_and(|thread| thread.is_finished());
+            service.shutdown.trigger();
+            let (success, code, error) = match service.thread {
+                Some(thread) if finished_before => {
+                    match thread.join().expect("service thread panicked") {
+                        Ok(status) => (true, status.code(), None),
+                        Err(ExecutionError::CommandFailed(_, code)) => (false, code, None),
+                        Err(err) => (false, None, Some(err.to_string())),
+                    }
+                }
+                Some(thread) => {
+                    let _ = thread.join();
+                    (true, None, None)
+                }
+                None => (true, Some(0), None),
+            };
+            info!(label = service.label, success, "service stopped");
+            runner.notify_step(service.kind.finished(StepReport {
+                label: service.label,
+                success,
+                code,
+                error,
+                elapsed: service.started.elapsed(),
+            }));
+        }
+    }
+}
+
+impl<'a> Runner<'a> {
+    /// Runs the step `key` of a sequential composite, a config with `wait_for`, after its
+    /// pre_commands, returning once it's ready and leaving it to `services`.
+    pub(crate) fn start_service_step<'scope>(
+        &self,
+        key: &str,
+        services: &mut Services<'scope, '_>,
+    ) -> Result<(), ExecutionError>
+    where
+        'a: 'scope,
+    {
+        self.notify_step(StepEvent::Started(self.label(key)));
+        if self.skipped(key)? {
+            return Ok(());
+        }
+        self.for_context(self.context)
+            .run_pre_commands(key, &mut Vec::new(), services)?;
+        self.start_service(
+            key,
+            self.output_observer.clone(),
+            ServiceKind::Step,
+            services,
+        )
+    }
+
+    /// Starts `key` on a thread of its own and waits until its `wait_for` holds. Reported as
+    /// ready, or as finished without success when it fails or runs out of time first.
+    pub(crate) fn start_service<'scope>(
+        &self,
+        key: &str,
+        observer: Option<OutputObserver>,
+        kind: ServiceKind,
+        services: &mut Services<'scope, '_>,
+    ) -> Result<(), ExecutionError>
+    where
+        'a: 'scope,
+    {
+        let label = self.label(key);
+        let started = Instant::now();
+
+        match self.wait_until_ready(key, observer, services.scope) {
+            Ok((shutdown, thread)) => {
+                info!(label, "ready");
+                self.notify_step(StepEvent::Ready(label.clone(), started.elapsed()));
+                services.running.push(Service {
+                    label,
+                    kind,
+                    started,
+                    shutdown,
+                    thread,
+                });
+                Ok(())
+            }
+            Err(err) => {
+                let (code, error) = match &err {
+                    ExecutionError::CommandFailed(failed, code) if *failed == label => {
+                        (*code, None)
+                    }
+                    _ => (None, Some(err.to_string())),
+                };
+                self.notify_step(kind.finished(StepReport {
+                    label,
+                    success: false,
+                    code,
+                    error,
+                    elapsed: started.elapsed(),
+                }));
+                Err(err)
+            }
+        }
+    }
+
+    fn wait_until_ready<'scope>(
+        &self,
+        key: &str,
+        observer: Option<OutputObserver>,
+        scope: &'scope Scope<'scope, '_>,
+    ) -> Result<(ShutdownSignal, Option<ServiceThread<'scope>>), ExecutionError>
+    where
+        'a: 'scope,
+    {
+        let label = self.label(key);
+        let details = self.details(key)?;
+        let wait_for = details.wait_for.clone().unwrap_or_default();
+        let log = log_pattern(&label, &wait_for)?;
+        let (url, file) = self.ready_targets(details, &wait_for);
+
+        // The lines go on to the observer as usual, and are matched against `log`
+        let logged = Arc::new(AtomicBool::new(false));
+        let observer = match log {
+            Some(log) => {
+                let logged = logged.clone();
+                let matcher: OutputObserver = Arc::new(move |label, stream, line| {
+                    if let Some(observer) = &observer {
+                        observer(label, stream, line);
+                    }
+                    if log.is_match(line) {
+                        logged.store(true, Ordering::SeqCst);
+                    }
+                });
+                Some(matcher)
+            }
+            None => observer,
+        };
+
+        let shutdown = ShutdownSignal::new();
+        let mut runner = self.for_context(self.context);
+        runner.shutdown = shutdown.clone();
+        let service_key = key.to_string();
+        let mut running = Some(scope.spawn(move || {
+            let status = runner.execute(&service_key, &[], observer.as_ref())?;
+            match runner.details(&service_key)?.is_success(status.code()) {
+                true => Ok(status),
+                false => Err(ExecutionError::CommandFailed(
+                    runner.label(&service_key),
+                    status.code(),
+                )),
+            }
+        }));
+
+        let timeout = wait_for
+            .timeout
+            .map_or(DEFAULT_READY_TIMEOUT, Duration::from_secs);
+        let deadline = Instant::now() + timeout;
+        loop {
+            // A command that starts a server in the background and exits, `docker compose up
+            // -d` say, is waited for all the same
+            if running.as_ref().is_some_and(|thread| thread.is_finished()) {
+                if let Some(thread) = running.take() {
+                    thread.join().expect("service thread panicked")?;
+                }
+                if wait_for.log.is_some() && !logged.load(Ordering::SeqCst) {
+                    return Err(ExecutionError::NotReady(
+                        label,
+                        "it exited before its output matched the log pattern".to_string(),
+                    ));
+                }
+            }
+
+            let pending = pending(
+                &self.session,
+                &wait_for,
+                url.as_deref(),
+                file.as_deref(),
+                logged.load(Ordering::SeqCst),
+            )?;
+            if pending.is_empty() {
+                return Ok((shutdown, running));
+            }
+            if self.shutdown.is_triggered() || Instant::now() >= deadline {
+                shutdown.trigger();
+                if let Some(thread) = running {
+                    let _ = thread.join();
+                }
+                if self.shutdown.is_triggered() {
+                    return Err(ExecutionError::Interrupted(label));
+                }
+                return Err(not_ready(label, timeout, &pending));
+            }
+            self.session.block_on(self.shutdown.sleep(POLL_INTERVAL));
+        }
+    }
+}
+
+impl<'a> Runner<'a> {
+    /// Waits until the service `key`, started in the background as `pid`, is ready by its
+    /// `wait_for`, matching its log pattern against what `log` got past `offset`. Returns right
+    /// away for a service without `wait_for`.
+    pub fn wait_for_service(
+        &self,
+        key: &str,
+        pid: u32,
+        log: &Path,
+        offset: u64,
+    ) -> Result<(), ExecutionError> {
+        let label = self.label(key);
+        let details = self.details(key)?;
+        let Some(wait_for) = details.wait_for.clone() else {
+            return Ok(());
+        };
+        let pattern = log_pattern(&label, &wait_for)?;
+        let (url, file) = self.ready_targets(details, &wait_for);
+
+        let timeout = wait_for
+            .timeout
+            .map_or(DEFAULT_READY_TIMEOUT, Duration::from_secs);
+        let deadline = Instant::now() + timeout;
+        loop {
+            let exited = !service::is_running(pid);
+            let logged = pattern.as_ref().is_some_and(|pattern| {
+                let output = fs::read(log).unwrap_or_default();
+                let since = output.get(offset as usize..).unwrap_or_default();
+                String::from_utf8_lossy(since)
+                    .lines()
+                    .any(|line| pattern.is_match(line))
+            });
+            let pending = pending(
+                &self.session,
+                &wait_for,
+                url.as_deref(),
+                file.as_deref(),
+                logged,
+            )?;
+            if pending.is_empty() {
+                info!(label, "ready");
+                return Ok(());
+            }
+            if exited && pattern.is_some() && !logged {
+                return Err(ExecutionError::NotReady(
+                    label,
+                    format!(
+                        "it exited before its output matched the log pattern, see {}",
+                        log.display()
+                    ),
+                ));
+            }
+            if self.shutdown.is_triggered() {
+                return Err(ExecutionError::Interrupted(label));
+            }
+            if Instant::now() >= deadline {
+                return Err(not_ready(label, timeout, &pending));
+            }
+            self.session.block_on(self.shutdown.sleep(POLL_INTERVAL));
+        }
+    }
+
+    /// The URL and file of `wait_for` resolved, the file against the working directory.
+    fn ready_targets(
+        &self,
+        details: &CommandDetails,
+        wait_for: &WaitFor,
+    ) -> (Option<String>, Option<PathBuf>) {
+        let url = wait_for
+            .url
+            .as_ref()
+            .map(|url| self.interpolation.interpolate(url));
+        let file = wait_for.file.as_ref().map(|file| {
+            self.working_directory(details)
+                .join(self.interpolation.interpolate(file))
+        });
+        (url, file)
+    }
+}
+
+fn log_pattern(label: &str, wait_for: &WaitFor) -> Result<Option<Regex>, ExecutionError> {
+    match &wait_for.log {
+        Some(pattern) => Ok(Some(Regex::new(pattern).map_err(|err| {
+            ConfigError::InvalidValue(
+                format!("{}.wait_for.log", label),
+                format!("invalid log pattern: {}", err),
+            )
+        })?)),
+        None => Ok(None),
+    }
+}
+
+fn not_ready(label: String, timeout: Duration, pending: &[String]) -> ExecutionError {
+    ExecutionError::NotReady(
+        label,
+        format!(
+            "still waiting for {} after {}s",
+            pending.join(", "),
+            timeout.as_secs()
+        ),
+    )
+}
+
+/// The conditions of `wait_for` that don't hold yet, described. `url` and `file` are those of
+/// `wait_for` resolved, `logged` whether the output matched its log pattern.
+fn pending(
+    session: &Session,
+    wait_for: &WaitFor,
+    url: Option<&str>,
+    file: Option<&Path>,
+    logged: bool,
+) -> Result<Vec<String>, ExecutionError> {
+    let mut pending = Vec::new();
+    if let Some(port) = wait_for.port {
+        if !port_open(session, port) {
+            pending.push(format!("port {}", port));
+        }
+    }
+    if let Some(url) = url {
+        if !url_ok(url)? {
+            pending.push(format!("200 OK from {}", url));
+        }
+    }
+    if let Some(file) = file {
+        if !file.exists() {
+            pending.push(format!("file {}", file.display()));
+        }
+    }
+    if let Some(log) = &wait_for.log {
+        if !logged {
+            pending.push(format!("a line matching `{}`", log));
+        }
+    }
+    Ok(pending)
+}
+
+/// Whether something accepts connections on `port` of localhost, over IPv4 or IPv6.
+pub fn port_open(session: &Session, port: u16) -> bool {
+    session.block_on(async {
+        let connect = |address| tokio::time::timeout(CHECK_TIMEOUT, TcpStream::connect(address));
+        let (ipv4, ipv6) = tokio::join!(
+            connect(SocketAddr::from((Ipv4Addr::LOCALHOST, port))),
+            connect(SocketAddr::from((Ipv6Addr::LOCALHOST, port))),
+        );
+        matches!(ipv4, Ok(Ok(_))) || matches!(ipv6, Ok(Ok(_)))
+    })
+}
+
+/// Whether `url` answers with 200 OK.
+fn url_ok(url: &str) -> Result<bool, ExecutionError> {
+    let output = Command::new("curl")
+        .args(["--silent", "--location", "--max-time"])
+        .arg(CHECK_TIMEOUT.as_secs().to_string())
+        .args(["--output", if cfg!(windows) { "NUL" } else { "/dev/null" }])
+        .args(["--write-out", "%{http_code}"])
+        .arg(url)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => ExecutionError::ToolMissing(
+                "curl".into(),
+                "your system's package manager, e.g. apt install curl".into(),
+            ),
+            _ => ExecutionError::Spawn(format!("curl {}", url), err),
+        })?;
+    Ok(String::from_utf8_lossy(&output.stdout).trim() == "200")
+}
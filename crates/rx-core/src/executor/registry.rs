@@ -0,0 +1,126 @@
+use std::{
+    collections::BTreeMap,
+    process::{Command, ExitStatus},
+    sync::Arc,
+};
+
+use crate::{
+    errors::ExecutionError,
+    executor::{
+        output::OutputObserver,
+        runner::{Invocation, Runner},
+    },
+    models::config::{CommandDetails, CommandType},
+};
+
+/// Runs the configs of a command type. rx takes care of what all types share around it:
+/// pre_commands, the environment, containers, remotes, caching and supervising the process.
+///
+/// A run goes through the steps in order: [`CommandExecutor::resolve`] turns the config into
+/// what to execute, [`CommandExecutor::spawn`] adjusts the process before it starts,
+/// [`CommandExecutor::stream`] observes its output and [`CommandExecutor::finalize`] decides
+/// the outcome once it exited.
+pub trait CommandExecutor: Send + Sync {
+    /// What `details` executes with the extra `args`, its placeholders resolved, e.g. with
+    /// [`Runner::shell_line`].
+    fn resolve(
+        &self,
+        runner: &Runner,
+        details: &CommandDetails,
+        args: &[String],
+    ) -> Result<Invocation, ExecutionError>;
+
+    /// Adjusts the process of `details` before it's spawned, e.g. with variables of its own.
+    fn spawn(
+        &self,
+        _details: &CommandDetails,
+        _command: &mut Command,
+    ) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    /// Gets every line of output of `details`, next to the run's own observer.
+    fn stream(&self, _details: &CommandDetails) -> Option<OutputObserver> {
+        None
+    }
+
+    /// The outcome of `details` that exited with `status`, the status itself by default.
+    fn finalize(
+        &self,
+        _details: &CommandDetails,
+        status: ExitStatus,
+    ) -> Result<ExitStatus, ExecutionError> {
+        Ok(status)
+    }
+}
+
+/// Runs `cargo` with the config's subcommand, through cross, nextest or a wasm runner when
+/// the config asks for them.
+pub struct CargoExecutor;
+
+impl CommandExecutor for CargoExecutor {
+    fn resolve(
+        &self,
+        runner: &Runner,
+        details: &CommandDetails,
+        args: &[String],
+    ) -> Result<Invocation, ExecutionError> {
+        runner.cargo_invocation(details, args)
+    }
+}
+
+/// Runs the config's command line with the shell.
+pub struct ShellExecutor;
+
+impl CommandExecutor for ShellExecutor {
+    fn resolve(
+        &self,
+        runner: &Runner,
+        details: &CommandDetails,
+        args: &[String],
+    ) -> Result<Invocation, ExecutionError> {
+        Ok(Invocation::Script(runner.shell_line(details, args)))
+    }
+}
+
+/// The executors of the command types, by name. Composite configs aren't run by an executor,
+/// their steps are.
+#[derive(Clone)]
+pub struct ExecutorRegistry {
+    executors: BTreeMap<String, Arc<dyn CommandExecutor>>,
+}
+
+impl Default for ExecutorRegistry {
+    fn default() -> Self {
+        let mut registry = Self {
+            executors: BTreeMap::new(),
+        };
+        registry.register(&CommandType::Cargo.to_string(), Arc::new(CargoExecutor));
+        registry.register(&CommandType::Shell.to_string(), Arc::new(ShellExecutor));
+        registry
+    }
+}
+
+impl ExecutorRegistry {
+    /// Runs the configs whose `type` is `name` with `executor`, replacing the executor
+    /// registered for it before, a built-in one included.
+    pub fn register(&mut self, name: &str, executor: Arc<dyn CommandExecutor>) {
+        self.executors.insert(name.to_string(), executor);
+    }
+
+    /// The executor of `command_type`, an error naming the registered ones when there's none.
+    pub fn get(
+        &self,
+        command_type: &CommandType,
+    ) -> Result<&Arc<dyn CommandExecutor>, ExecutionError> {
+        let name = command_type.to_string();
+        self.executors
+            .get(&name)
+            .ok_or_else(|| ExecutionError::UnknownExecutor(name, self.names().join(", ")))
+    }
+
+    /// Names of the registered command types.
+    pub fn names(&self) -> Vec<&str> {
+        self.executors.keys().map(String::as_str).collect()
+    }
+}
@@ -0,0 +1,67 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+    process::Command,
+};
+
+use crate::helpers::shell_quote;
+
+/// Remote directory used when `remote_dir` is unset: the local directory's name under `~`.
+pub fn default_remote_dir(local_dir: &Path) -> String {
+    let name = local_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    format!("~/{}", name)
+}
+
+/// Runs `script` on `host` from `remote_dir` with `env` exported, streaming output back.
+pub fn ssh_command(
+    host: &str,
+    remote_dir: &str,
+    env: &HashMap<String, String>,
+    script: &str,
+) -> Command {
+    let mut remote_script = format!("cd {}", quote_remote_path(remote_dir));
+
+    // Sorted so the generated line is stable
+    for (key, value) in env.iter().collect::<BTreeMap<_, _>>() {
+        remote_script.push_str(&format!(" && export {}={}", key, shell_quote(value)));
+    }
+    remote_script.push_str(" && ");
+    remote_script.push_str(script);
+
+    let mut command = Command::new("ssh");
+    command.arg(host).arg(remote_script);
+    command
+}
+
+/// Mirrors `local_dir` into `remote_dir` on `host`, leaving build output behind.
+pub fn rsync_command(local_dir: &Path, host: &str, remote_dir: &str) -> Command {
+    // rsync resolves relative destinations against the remote home directory
+    let destination = remote_dir.strip_prefix("~/").unwrap_or(remote_dir);
+
+    let mut command = Command::new("rsync");
+    command
+        .args([
+            "-az",
+            "--delete",
+            "--exclude",
+            "/target/",
+            "--exclude",
+            "/.git/",
+        ])
+        .arg(format!("{}/", local_dir.display()))
+        .arg(format!("{}:{}/", host, destination));
+    command
+}
+
+/// Quotes a remote path while keeping a leading `~/` expandable.
+fn quote_remote_path(path: &str) -> String {
+    match path.strip_prefix("~/") {
+        Some(rest) => format!("\"$HOME\"/{}", shell_quote(rest)),
+        None if path == "~" => "\"$HOME\"".to_string(),
+        None => shell_quote(path),
+    }
+}
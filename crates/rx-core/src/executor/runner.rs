@@ -0,0 +1,2059 @@
+use std::{
+    borrow::Cow,
+    collections::HashSet,
+    fs::{self, File, OpenOptions},
+    io::{self, stdin, ErrorKind, IsTerminal},
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus, Stdio},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    env::{masked, parse_env_file, process_env, EnvSource, LayeredEnv},
+    errors::{ConfigError, ExecutionError},
+    executor::{
+        composite::{parse_step, StepEvent, StepObserver, StepReport},
+        container, coverage, cross, direnv, elevate,
+        hooks::HookEntry,
+        limits,
+        lock::{self, NamedLock},
+        nextest,
+        output::{self, Echo, OutputLimit, OutputLog, OutputObserver, OutputStream},
+        plugin,
+        pty::Pty,
+        queue::RunQueue,
+        ready::{ServiceKind, Services},
+        registry::{CommandExecutor, ExecutorRegistry},
+        remote,
+        sandbox::{self, SandboxDir},
+        script::{self, ScriptFile},
+        shutdown::{
+            isolate_process_group, supervise, ChildOutcome, Foreground, ShutdownSignal,
+            DEFAULT_GRACE_PERIOD,
+        },
+        wasm,
+    },
+    helpers::{host_triple, is_valid_env_var_name, shell_quote, target_arg},
+    interpolation::{find_args, find_inputs, Input, InterpolationContext, UnknownPlaceholders},
+    keys,
+    models::{
+        command::CommandSource,
+        config::{
+            ArgValue, CommandConfig, CommandContext, CommandDetails, CommandType, Config,
+            DefaultKey, Limits, PreCommandPolicy, Sandbox, TestRunner, TARGET_DIR_VARIABLE,
+        },
+    },
+    ports::{self, PortConflictHandler},
+    secret,
+    session::Session,
+    store::Store,
+    timings::{Timings, PREPARE},
+    watch::DEFAULT_WATCH,
+    workspace::{self, root_dir, selects_package, Member},
+};
+use encoding_rs::{Encoding, UTF_8};
+use tokio::io::{AsyncRead, AsyncWriteExt};
+use tracing::{debug, info, warn};
+
+/// Cargo subcommands that compile and accept `--message-format`.
+const BUILDING_SUBCOMMANDS: [&str; 8] = [
+    "build", "check", "clippy", "test", "bench", "run", "doc", "rustc",
+];
+
+/// Cargo subcommands taking `--offline`, the others only get `CARGO_NET_OFFLINE`.
+const OFFLINE_SUBCOMMANDS: [&str; 16] = [
+    "build", "check", "clippy", "test", "bench", "run", "doc", "rustc", "fix", "fetch", "update",
+    "tree", "metadata", "install", "package", "llvm-cov",
+];
+
+/// Variables that have cargo and most other tools color output that isn't a terminal.
+const FORCE_COLOR: [(&str, &str); 3] = [
+    ("CARGO_TERM_COLOR", "always"),
+    ("CLICOLOR_FORCE", "1"),
+    ("FORCE_COLOR", "1"),
+];
+
+/// Decides whether a config runs after its pre_command, labeled as given, failed with the
+/// error.
+pub type PreCommandFailureHandler = Arc<dyn Fn(&str, &ExecutionError) -> bool + Send + Sync>;
+
+/// Executes the configs of a single context, including their pre_command chains.
+pub struct Runner<'a> {
+    pub(crate) config: &'a Config,
+    pub(crate) context: CommandContext,
+    pub(crate) interpolation: InterpolationContext,
+    pub(crate) shutdown: ShutdownSignal,
+    pub(crate) output_observer: Option<OutputObserver>,
+    pub(crate) env_overrides: Vec<(String, String)>,
+    pub(crate) step_observer: Option<StepObserver>,
+    /// Labels of the composite configs being run, to catch a composite that includes itself
+    pub(crate) composite_chain: Vec<String>,
+    pub(crate) no_cache: bool,
+    pub(crate) store: Option<Store>,
+    pub(crate) echo_output: bool,
+    /// Workspace member cargo commands are pointed at with `--package`
+    pub(crate) package: Option<String>,
+    /// Directory rx was run from, picking a per-directory `default`
+    pub(crate) cwd: PathBuf,
+    /// Let lint and fmt configs apply their fixes
+    pub(crate) fix: bool,
+    /// Have cargo print its messages as JSON for the output observer
+    pub(crate) json_messages: bool,
+    /// Have cargo report its artifacts as JSON for the output observer
+    pub(crate) report_artifacts: bool,
+    /// The matrix cell being run, e.g. `target=x86_64`, part of the labels
+    pub(crate) matrix_cell: Option<String>,
+    /// Stdin of the config run by name, over its `stdin` and `stdin_file`
+    pub(crate) stdin: Option<StdinSource>,
+    /// Echo output byte for byte even when its config has an `output_encoding`
+    pub(crate) raw_output: bool,
+    /// Asked before stopping what holds a port, the process is stopped when unset
+    pub(crate) port_conflicts: Option<PortConflictHandler>,
+    /// Asked whether to go on when a pre_command of a config with `pre_command_policy =
+    /// "prompt"` fails, the config is aborted when unset
+    pub(crate) pre_command_failures: Option<PreCommandFailureHandler>,
+    /// How many configs `depends_on` runs at a time
+    pub(crate) jobs: usize,
+    /// Where the processes of the run wait for their turn, see [`RunQueue`]
+    pub(crate) queue: Option<RunQueue>,
+    /// Labels of the configs `depends_on` ran successfully, shared by every runner of the
+    /// invocation so each runs once
+    pub(crate) completed_configs: Arc<Mutex<HashSet<String>>>,
+    /// Labels of the steps, matrix cells and dependencies that failed, in the order they did
+    pub(crate) failed_steps: Arc<Mutex<Vec<String>>>,
+    /// Executors of the command types, see [`Runner::executor`]
+    pub(crate) executors: ExecutorRegistry,
+    /// Ends the [`PREPARE`] phase as the first command starts, see [`Runner::timings`]
+    pub(crate) timings: Option<Timings>,
+    /// The runtime, temp files and tool lookups the runs share, see [`Runner::session`]
+    pub(crate) session: Session,
+}
+
+/// Where a command reads its stdin from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StdinSource {
+    /// rx's own stdin
+    Inherit,
+    File(PathBuf),
+    Text(String),
+}
+
+/// What a spawned command gets beyond its output observer, see [`Runner::spawn_with`].
+#[derive(Default)]
+struct SpawnOptions<'o> {
+    /// Written to the command's piped stdin
+    input: Option<String>,
+    /// The command reads a file or `input` rather than rx's stdin
+    redirected_stdin: bool,
+    /// Reads the command's stdout, its output standing in for the command's
+    filter: Option<Command>,
+    limits: Option<&'o Limits>,
+    /// Of the command's output, UTF-8 when unset
+    encoding: Option<&'static Encoding>,
+    /// Attach the command to a pseudo-terminal
+    pty: bool,
+    /// Lines of output handed to the observer, see [`OutputLimit`]
+    max_output_lines: Option<usize>,
+    /// Gets a copy of the output, see [`OutputLog`]
+    log: Option<OutputLog>,
+}
+
+impl<'a> Runner<'a> {
+    pub fn new(config: &'a Config, context: CommandContext, shutdown: ShutdownSignal) -> Self {
+        let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+
+        Self {
+            config,
+            context,
+            interpolation: InterpolationContext::from_dir(&cwd),
+            shutdown,
+            output_observer: None,
+            env_overrides: Vec::new(),
+            step_observer: None,
+            composite_chain: Vec::new(),
+            no_cache: false,
+            store: None,
+            echo_output: true,
+            package: None,
+            cwd,
+            fix: false,
+            json_messages: false,
+            report_artifacts: false,
+            matrix_cell: None,
+            stdin: None,
+            raw_output: false,
+            port_conflicts: None,
+            pre_command_failures: None,
+            jobs: thread::available_parallelism().map_or(1, usize::from),
+            queue: None,
+            completed_configs: Arc::default(),
+            failed_steps: Arc::default(),
+            executors: ExecutorRegistry::default(),
+            timings: None,
+            session: Session::default(),
+        }
+    }
+
+    pub fn interpolation(mut self, interpolation: InterpolationContext) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    /// Ends the [`PREPARE`] phase of `timings` when the first command starts, telling rx's
+    /// overhead apart from the time of the commands.
+    pub fn timings(mut self, timings: Timings) -> Self {
+        self.timings = Some(timings);
+        self
+    }
+
+    /// Runs in `session`, sharing its runtime, temp files and tool lookups with the other
+    /// runners given it. A runner given none has a session of its own.
+    pub fn session(mut self, session: &Session) -> Self {
+        self.session = session.clone();
+        self
+    }
+
+    /// Resolves `${binaryName}` to `name`, a binary picked from [`Runner::binary_candidates`].
+    pub fn binary_name(mut self, name: &str) -> Self {
+        self.interpolation.set_binary(Ok(name.to_string()));
+        self
+    }
+
+    /// Values of `${input:..}` placeholders, by input name.
+    pub fn inputs(mut self, inputs: impl IntoIterator<Item = (String, String)>) -> Self {
+        for (name, value) in inputs {
+            self.interpolation.set_input(&name, &value);
+        }
+        self
+    }
+
+    /// Sets the `${arg:..}` parameters of `key` to `values` given as `--arg` NAME=VALUE, read as
+    /// their types. Those not given take their defaults.
+    pub fn arg_values(
+        mut self,
+        key: &str,
+        values: &[(String, String)],
+    ) -> Result<Self, ConfigError> {
+        let details = self.details(key)?;
+        for (name, value) in values {
+            let path = format!("--arg {}", name);
+            let Some(spec) = details.args.get(name) else {
+                let names: Vec<&str> = details.args.keys().map(String::as_str).collect();
+                let reason = match names.as_slice() {
+                    [] => format!("{} has no args", self.label(key)),
+                    _ => format!("{} takes {}", self.label(key), names.join(", ")),
+                };
+                return Err(ConfigError::InvalidValue(path, reason));
+            };
+            let value = spec
+                .arg_type
+                .parse(value)
+                .map_err(|reason| ConfigError::InvalidValue(path, reason))?;
+            self.interpolation.set_arg(name, &value.to_string());
+        }
+        Ok(self)
+    }
+
+    /// A runner for another context sharing this one's config, placeholders and shutdown signal.
+    pub fn for_context(&self, context: CommandContext) -> Runner<'a> {
+        Runner {
+            config: self.config,
+            context,
+            interpolation: self.interpolation.clone(),
+            shutdown: self.shutdown.clone(),
+            output_observer: self.output_observer.clone(),
+            env_overrides: self.env_overrides.clone(),
+            step_observer: self.step_observer.clone(),
+            composite_chain: self.composite_chain.clone(),
+            no_cache: self.no_cache,
+            store: self.store.clone(),
+            echo_output: self.echo_output,
+            package: self.package.clone(),
+            cwd: self.cwd.clone(),
+            fix: self.fix,
+            json_messages: self.json_messages,
+            report_artifacts: self.report_artifacts,
+            matrix_cell: None,
+            stdin: None,
+            raw_output: self.raw_output,
+            port_conflicts: self.port_conflicts.clone(),
+            pre_command_failures: self.pre_command_failures.clone(),
+            jobs: self.jobs,
+            queue: self.queue.clone(),
+            completed_configs: self.completed_configs.clone(),
+            failed_steps: self.failed_steps.clone(),
+            executors: self.executors.clone(),
+            timings: self.timings.clone(),
+            session: self.session.clone(),
+        }
+    }
+
+    /// Pipes the main command's stdout and stderr through `observer` while still echoing them.
+    /// pre_commands keep writing straight to the terminal, see [`Runner::echo_output`].
+    pub fn observe_output(mut self, observer: OutputObserver) -> Self {
+        self.output_observer = Some(observer);
+        self
+    }
+
+    /// Runs the configs with `type = "<name>"` with `executor`, e.g. `"docker"` for those with
+    /// `type = "docker"`. A built-in `cargo` or `shell` one is replaced under its name.
+    pub fn executor(mut self, name: &str, executor: Arc<dyn CommandExecutor>) -> Self {
+        self.executors.register(name, executor);
+        self
+    }
+
+    /// Resolves a per-directory `default` for `cwd` instead of the current directory.
+    pub fn cwd(mut self, cwd: &Path) -> Self {
+        self.cwd = cwd.to_path_buf();
+        self
+    }
+
+    /// Targets the workspace member `member`: `${packageName}` and `${binaryName}` resolve to it
+    /// and cargo commands get `--package <member>` unless they already pick their packages.
+    pub fn package(mut self, member: &Member) -> Self {
+        self.interpolation.set("packageName", &member.name);
+        self.interpolation
+            .set_binary(member.binary_name.clone().ok_or_else(|| member.binaries()));
+        self.package = Some(member.name.clone());
+        self
+    }
+
+    /// Hands output only to the output observer instead of also echoing it, leaving rx's stdout
+    /// to the embedder. pre_commands, hooks and syncs are then observed as well.
+    pub fn echo_output(mut self, echo_output: bool) -> Self {
+        self.echo_output = echo_output;
+        self
+    }
+
+    /// The observer for output beyond the main command's, which is only observed when it isn't
+    /// echoed.
+    pub(crate) fn unechoed_observer(&self) -> Option<&OutputObserver> {
+        self.output_observer.as_ref().filter(|_| !self.echo_output)
+    }
+
+    /// Echoes the output of configs with an `output_encoding` as the command wrote it instead
+    /// of decoded to UTF-8, for `--quiet` runs whose output is piped on.
+    pub fn raw_output(mut self, raw_output: bool) -> Self {
+        self.raw_output = raw_output;
+        self
+    }
+
+    /// Asks `handler` whether to stop the process holding a port a `kill_conflicts` config
+    /// needs, instead of stopping it right away.
+    pub fn on_port_conflict(mut self, handler: PortConflictHandler) -> Self {
+        self.port_conflicts = Some(handler);
+        self
+    }
+
+    /// Asks `handler` with the pre_command's label and error whether to run a config with
+    /// `pre_command_policy = "prompt"` after the pre_command failed.
+    pub fn on_pre_command_failure(mut self, handler: PreCommandFailureHandler) -> Self {
+        self.pre_command_failures = Some(handler);
+        self
+    }
+
+    /// Has each process rx waits for wait for its turn in `queue`, shared with other runs.
+    pub fn queue(mut self, queue: RunQueue) -> Self {
+        self.queue = Some(queue);
+        self
+    }
+
+    /// Applies fixes instead of only reporting: cargo commands of the lint context get
+    /// `--fix --allow-dirty`, those of the fmt context lose `--check`.
+    pub fn fix(mut self, fix: bool) -> Self {
+        self.fix = fix;
+        self
+    }
+
+    /// Adds `--message-format=json` to the cargo commands that build, so the output observer
+    /// gets the compiler's diagnostics as [`crate::diagnostics::parse_message`] reads them.
+    pub fn json_messages(mut self, json_messages: bool) -> Self {
+        self.json_messages = json_messages;
+        self
+    }
+
+    /// Has the cargo commands that build report their artifacts as JSON for the output
+    /// observer, leaving the diagnostics to cargo unless [`Runner::json_messages`] is set.
+    pub fn report_artifacts(mut self, report_artifacts: bool) -> Self {
+        self.report_artifacts = report_artifacts;
+        self
+    }
+
+    /// Variables set on every command this runner starts, over the config's own `env`.
+    pub fn env_overrides(mut self, env_overrides: Vec<(String, String)>) -> Self {
+        self.env_overrides = env_overrides;
+        self
+    }
+
+    /// Feeds the config run by name from `source` instead of its `stdin` or `stdin_file`. Its
+    /// pre_commands, steps and hooks keep their own.
+    pub fn stdin(mut self, source: StdinSource) -> Self {
+        self.stdin = Some(source);
+        self
+    }
+
+    /// Runs configs with `inputs` even when they are unchanged.
+    pub fn no_cache(mut self, no_cache: bool) -> Self {
+        self.no_cache = no_cache;
+        self
+    }
+
+    /// Where rx records between runs, the workspace's store under the user's data dir by default.
+    pub fn store(mut self, store: Store) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// The runner's store, see [`Runner::store`].
+    pub fn project_store(&self) -> Option<Store> {
+        self.store.clone().or_else(|| {
+            self.interpolation
+                .get("workspaceFolder")
+                .and_then(|workspace_folder| Store::for_workspace(Path::new(workspace_folder)))
+        })
+    }
+
+    /// The environment rx sets for `details`: what direnv loads, its `env_file`, then its
+    /// `env_sets` and its `env` with their secrets revealed, `CARGO_NET_OFFLINE` when it runs
+    /// offline, then the runner's overrides. The process environment is inherited below all
+    /// of them.
+    pub fn environment(&self, details: &CommandDetails) -> Result<LayeredEnv, ExecutionError> {
+        let env_file = match &details.env_file {
+            Some(env_file) => {
+                let path = self
+                    .working_directory(details)
+                    .join(self.interpolation.interpolate(env_file));
+                let content = fs::read_to_string(&path).map_err(|err| {
+                    ExecutionError::EnvFile(path.display().to_string(), err.to_string())
+                })?;
+                parse_env_file(&content)
+                    .map_err(|reason| ExecutionError::EnvFile(path.display().to_string(), reason))?
+            }
+            None => Vec::new(),
+        };
+
+        let direnv_env = match details.direnv {
+            true => {
+                let dir = self.working_directory(details);
+                direnv::export(&self.session, &dir)
+                    .map_err(|reason| ExecutionError::Direnv(dir.display().to_string(), reason))?
+            }
+            false => Vec::new(),
+        };
+
+        let mut env_set_vars = self.config.env_set_vars(details)?;
+        let mut config_env = details.env.clone();
+        if self.is_offline(details) {
+            config_env
+                .entry("CARGO_NET_OFFLINE".to_string())
+                .or_insert_with(|| "true".to_string());
+        }
+        for (name, value) in env_set_vars.iter_mut().chain(config_env.iter_mut()) {
+            if secret::is_sealed(value) {
+                *value = secret::reveal(name, value)?;
+            }
+        }
+
+        let env = LayeredEnv::new()
+            .layer(EnvSource::Direnv, direnv_env)
+            .layer(EnvSource::EnvFile, env_file)
+            .layer(EnvSource::EnvSet, env_set_vars)
+            .layer(EnvSource::Config, config_env)
+            .layer(EnvSource::Cli, self.env_overrides.clone());
+        for (name, value, source) in env.iter() {
+            let value = match self.is_sealed(details, name, source) {
+                true => "********",
+                false => masked(name, value),
+            };
+            debug!(name, value, %source, "environment variable");
+        }
+        Ok(env)
+    }
+
+    /// Whether cargo runs without network access for `details`, its `offline` or else
+    /// `settings.offline`.
+    pub fn is_offline(&self, details: &CommandDetails) -> bool {
+        details.offline.unwrap_or(self.config.settings.offline)
+    }
+
+    /// Why running `details` offline may fail: the packages of its `Cargo.lock` that aren't
+    /// downloaded, or a missing `Cargo.lock`. `None` for a config that isn't an offline cargo
+    /// config, or one whose packages are all there.
+    pub fn offline_warning(&self, details: &CommandDetails) -> Option<String> {
+        if details.command_type != CommandType::Cargo || !self.is_offline(details) {
+            return None;
+        }
+        let cargo_home = workspace::cargo_home()?;
+        let missing =
+            match workspace::missing_packages(&self.working_directory(details), &cargo_home) {
+                Ok(missing) if missing.is_empty() => return None,
+                Ok(missing) => missing,
+                Err(err) => {
+                    return Some(format!(
+                        "{}, cargo may need the network to resolve the dependencies",
+                        err
+                    ))
+                }
+            };
+        let shown: Vec<&str> = missing.iter().take(3).map(String::as_str).collect();
+        let more = match missing.len() - shown.len() {
+            0 => String::new(),
+            more => format!(" and {} more", more),
+        };
+        Some(format!(
+            "running offline without {}{} downloaded, `cargo fetch` while online gets them",
+            shown.join(", "),
+            more
+        ))
+    }
+
+    /// Whether the value of `name` that `details` takes from `source` is an encrypted secret,
+    /// never to be shown.
+    pub fn is_sealed(&self, details: &CommandDetails, name: &str, source: EnvSource) -> bool {
+        match source {
+            EnvSource::Config => details.env_is_sealed(name),
+            EnvSource::EnvSet => self.config.env_set_is_sealed(details, name),
+            _ => false,
+        }
+    }
+
+    /// Returns the given key, or the context's `default` for the directory rx was run from
+    /// when none was given. A `default` whose entry is gone, say after editing the file by hand,
+    /// falls back to the only entry left, and is an error listing the keys otherwise.
+    pub fn resolve_key(&self, key: Option<&str>) -> Result<String, ConfigError> {
+        if let Some(key) = key {
+            return Ok(key.to_string());
+        }
+
+        let key = self.default_key()?;
+        let keys = self
+            .config
+            .commands
+            .get_config(self.context)
+            .map(CommandConfig::keys)
+            .unwrap_or_default();
+        if keys.contains(&key.as_str()) {
+            return Ok(key);
+        }
+        match keys.as_slice() {
+            [only] => {
+                warn!(label = %self.label(&key), fallback = only, "the default does not exist");
+                Ok(only.to_string())
+            }
+            _ => Err(ConfigError::DanglingDefault(
+                self.label(&key),
+                keys.iter().map(|key| key.to_string()).collect(),
+            )),
+        }
+    }
+
+    /// The context's `default` for the directory rx was run from, as written, whether or not
+    /// its entry exists.
+    pub fn default_key(&self) -> Result<String, ConfigError> {
+        let command_config = self
+            .config
+            .commands
+            .get_config(self.context)
+            .ok_or_else(|| ConfigError::ConfigKeyNotFound(self.context.to_string()))?;
+        // Finding the directory relative to the workspace root reads the manifests around it,
+        // only a default by directory needs it
+        if let DefaultKey::Key(key) = &command_config.default {
+            debug!(context = %self.context, key, "resolved the default key");
+            return Ok(key.clone());
+        }
+        let root = root_dir(&self.cwd).unwrap_or_else(|| self.cwd.clone());
+        let dir = self.cwd.strip_prefix(&root).unwrap_or(&self.cwd);
+        let key = command_config.default.resolve(dir).ok_or_else(|| {
+            ConfigError::NoDefaultKey(self.context.to_string(), dir.display().to_string())
+        })?;
+        debug!(context = %self.context, dir = %dir.display(), key, "resolved the default key");
+        Ok(key.to_string())
+    }
+
+    /// The `${input:..}` placeholders of `key`, its pre_commands and steps, each name once with
+    /// the first default given.
+    pub fn find_inputs(&self, key: &str) -> Result<Vec<Input>, ConfigError> {
+        let mut inputs: Vec<Input> = Vec::new();
+        let mut configs = vec![(self.context, key.to_string())];
+        let mut next = 0;
+        while let Some((context, key)) = configs.get(next).cloned() {
+            next += 1;
+            let Some(details) = self
+                .config
+                .commands
+                .get_config(context)
+                .and_then(|command_config| command_config.configs.get(&key))
+            else {
+                continue;
+            };
+
+            let fields = toml::Value::try_from(details)
+                .map_err(|err| ConfigError::InvalidValue(self.label(&key), err.to_string()))?;
+            for input in strings(&fields).into_iter().flat_map(find_inputs) {
+                match inputs.iter_mut().find(|known| known.name == input.name) {
+                    Some(known) if known.default.is_none() => known.default = input.default,
+                    Some(_) => {}
+                    None => inputs.push(input),
+                }
+            }
+
+            let mut referenced = Vec::new();
+            for pre_command in &details.pre_command {
+                referenced.push((context, pre_command.clone()));
+            }
+            for step in &details.steps {
+                let (step_context, step_key) = parse_step(step, context)?;
+                referenced.push((step_context, step_key.to_string()));
+            }
+            for config in referenced {
+                if !configs.contains(&config) {
+                    configs.push(config);
+                }
+            }
+        }
+        Ok(inputs)
+    }
+
+    pub fn details(&self, key: &str) -> Result<&'a CommandDetails, ConfigError> {
+        self.config
+            .commands
+            .get_config(self.context)
+            .and_then(|command_config| command_config.configs.get(key))
+            .ok_or_else(|| ConfigError::ConfigKeyNotFound(key.to_string()))
+    }
+
+    /// Runs the configs `key` depends on and its pre_commands, followed by `key` itself with
+    /// `args` appended. A config skipped by its guards succeeds without running anything.
+    pub fn run(&self, key: Option<&str>, args: &[String]) -> Result<ExitStatus, ExecutionError> {
+        let key = self.resolve_key(key)?;
+        self.check_platform(&key)?;
+        Ok(self.run_unless_skipped(&key, args)?.unwrap_or_default())
+    }
+
+    /// Refuses `key` when the OS rx runs on isn't one of its `platforms`.
+    pub fn check_platform(&self, key: &str) -> Result<(), ExecutionError> {
+        let details = self.details(key)?;
+        if details.runs_on_this_platform() {
+            return Ok(());
+        }
+        Err(ConfigError::UnsupportedPlatform(self.label(key), details.platforms.clone()).into())
+    }
+
+    /// The label and command line of each process a run of `key` with `args` would start, in
+    /// order, without running anything.
+    pub fn dry_run(
+        &self,
+        key: Option<&str>,
+        args: &[String],
+    ) -> Result<Vec<(String, String)>, ExecutionError> {
+        let key = self.resolve_key(key)?;
+        let mut lines = Vec::new();
+        if !self.details(&key)?.service {
+            for (context, dependency) in self.config.dependency_order(self.context, &key)? {
+                let runner = self.for_context(context);
+                runner.dry_run_pre_commands(&dependency, &mut Vec::new(), &mut lines)?;
+                lines.push((
+                    runner.label(&dependency),
+                    runner.command_line(&dependency, &[])?,
+                ));
+            }
+        }
+        self.dry_run_pre_commands(&key, &mut Vec::new(), &mut lines)?;
+        lines.push((self.label(&key), self.command_line(&key, args)?));
+        Ok(lines)
+    }
+
+    fn dry_run_pre_commands(
+        &self,
+        key: &str,
+        chain: &mut Vec<String>,
+        lines: &mut Vec<(String, String)>,
+    ) -> Result<(), ExecutionError> {
+        if chain.iter().any(|visited| visited == key) {
+            return Err(ExecutionError::PreCommandCycle(self.label(key)));
+        }
+        chain.push(key.to_string());
+        for pre_command in &self.details(key)?.pre_command {
+            self.dry_run_pre_commands(pre_command, chain, lines)?;
+            lines.push((
+                self.label(pre_command),
+                self.command_line(pre_command, &[])?,
+            ));
+        }
+        chain.pop();
+        Ok(())
+    }
+
+    /// The command line `key` runs, wrappers such as sudo, docker or ssh included.
+    pub fn command_line(&self, key: &str, args: &[String]) -> Result<String, ExecutionError> {
+        let details = self.target_details(key, self.details(key)?, args);
+        if details.command_type == CommandType::Composite {
+            return Ok(format!("steps {}", details.steps.join(", ")));
+        }
+        if let Some(script) = details.command.script_url() {
+            return Ok(format!("script {} (sha256 {})", script.url, script.sha256));
+        }
+        Ok(describe(&self.build_command(&details, args)?))
+    }
+
+    /// Like [`Runner::run`], `None` when `key` was skipped.
+    pub(crate) fn run_unless_skipped(
+        &self,
+        key: &str,
+        args: &[String],
+    ) -> Result<Option<ExitStatus>, ExecutionError> {
+        if self.skipped(key)? {
+            return Ok(None);
+        }
+        // The services a service depends on are started by `rx up`
+        if !self.details(key)?.service {
+            self.run_dependencies(key)?;
+        }
+
+        // pre_commands with `wait_for` run on until `key` is done
+        thread::scope(|scope| {
+            let mut services = Services::new(scope);
+            // A runner without the stdin meant for `key`
+            let result = self
+                .for_context(self.context)
+                .run_pre_commands(key, &mut Vec::new(), &mut services)
+                .and_then(|()| self.execute(key, args, self.output_observer.as_ref()));
+            services.stop(self);
+            result.map(Some)
+        })
+    }
+
+    /// Runs the pre_commands of `key`, and theirs first. Those with `wait_for` are left running
+    /// to `services` once they're ready.
+    pub(crate) fn run_pre_commands<'scope>(
+        &self,
+        key: &str,
+        chain: &mut Vec<String>,
+        services: &mut Services<'scope, '_>,
+    ) -> Result<(), ExecutionError>
+    where
+        'a: 'scope,
+    {
+        if chain.iter().any(|visited| visited == key) {
+            return Err(ExecutionError::PreCommandCycle(self.label(key)));
+        }
+        chain.push(key.to_string());
+
+        let details = self.details(key)?;
+        for pre_command in &details.pre_command {
+            if self.skipped(pre_command)? {
+                continue;
+            }
+            let depth = chain.len();
+            match self.run_pre_command(pre_command, chain, services) {
+                Err(err) if self.goes_on_after(details, pre_command, &err) => {
+                    warn!(label = %self.label(key), pre_command, %err, "pre_command failed, running anyway");
+                    chain.truncate(depth);
+                    self.notify_step(StepEvent::PreCommandIgnored(
+                        self.label(pre_command),
+                        err.to_string(),
+                    ));
+                }
+                result => result?,
+            }
+        }
+
+        chain.pop();
+        Ok(())
+    }
+
+    /// Runs a single pre_command after its own, a failure becomes an error.
+    fn run_pre_command<'scope>(
+        &self,
+        pre_command: &str,
+        chain: &mut Vec<String>,
+        services: &mut Services<'scope, '_>,
+    ) -> Result<(), ExecutionError>
+    where
+        'a: 'scope,
+    {
+        self.run_pre_commands(pre_command, chain, services)?;
+
+        let label = self.label(pre_command);
+        self.notify_step(StepEvent::PreCommandStarted(label.clone()));
+        if self.details(pre_command)?.wait_for.is_some() {
+            return self.start_service(
+                pre_command,
+                self.unechoed_observer().cloned(),
+                ServiceKind::PreCommand,
+                services,
+            );
+        }
+        let started = Instant::now();
+        let result = self.execute(pre_command, &[], self.unechoed_observer());
+
+        let (success, code, error) = match &result {
+            Ok(status) => (
+                self.details(pre_command)?.is_success(status.code()),
+                status.code(),
+                None,
+            ),
+            Err(err) => (false, None, Some(err.to_string())),
+        };
+        self.notify_step(StepEvent::PreCommandFinished(StepReport {
+            label,
+            success,
+            code,
+            error,
+            elapsed: started.elapsed(),
+        }));
+
+        let status = result?;
+        if !success {
+            // Killed by a signal it fails with 128 + the signal, as the command itself would
+            return Err(ExecutionError::CommandFailed(
+                self.label(pre_command),
+                Some(exit_code(&status)),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether the config of `details` runs after `pre_command` failed with `err`, as its
+    /// `pre_command_policy` says. An interrupt or a broken config always stops it.
+    fn goes_on_after(
+        &self,
+        details: &CommandDetails,
+        pre_command: &str,
+        err: &ExecutionError,
+    ) -> bool {
+        if matches!(
+            err,
+            ExecutionError::Interrupted(_)
+                | ExecutionError::Config(_)
+                | ExecutionError::PreCommandCycle(_)
+        ) {
+            return false;
+        }
+        match details.pre_command_policy {
+            PreCommandPolicy::AbortOnFailure => false,
+            PreCommandPolicy::Continue => true,
+            PreCommandPolicy::Prompt => self
+                .pre_command_failures
+                .as_ref()
+                .is_some_and(|handler| handler(&self.label(pre_command), err)),
+        }
+    }
+
+    pub(crate) fn execute(
+        &self,
+        key: &str,
+        args: &[String],
+        observer: Option<&OutputObserver>,
+    ) -> Result<ExitStatus, ExecutionError> {
+        let details = self.target_details(key, self.details(key)?, args);
+        let details = details.as_ref();
+
+        if !details.matrix.is_empty() && self.matrix_cell.is_none() {
+            return self.run_matrix(key, details, args, observer);
+        }
+        if details.command_type == CommandType::Composite {
+            return self.run_composite(key, details);
+        }
+
+        let cache = self.cache_state(key, details, args)?;
+        if let Some(cache) = &cache {
+            if self.is_cached(cache, details)? {
+                self.notify_step(StepEvent::Cached(self.label(key)));
+                return Ok(ExitStatus::default());
+            }
+        }
+
+        if details.sync {
+            if let Some(host) = &details.remote {
+                let working_directory = self.working_directory(details);
+                let remote_dir = self.remote_dir(details, &working_directory);
+                let rsync = remote::rsync_command(&working_directory, host, &remote_dir);
+
+                let status = self.spawn(
+                    &self.label(key),
+                    grace_period(details),
+                    rsync,
+                    self.unechoed_observer(),
+                )?;
+                if !status.success() {
+                    return Err(ExecutionError::RemoteSync(self.label(key), status.code()));
+                }
+            }
+        }
+
+        self.ensure_plugin(key, details, args)?;
+        self.ensure_ports(key, details)?;
+
+        // The temp file of an inline script has to outlive the process
+        let (details, _script_file) = self.inline_script(key, details, false)?;
+        let executor = self.executors.get(&details.command_type)?;
+        self.ensure_working_directory(&details)?;
+        let mut command = self.build_command(&details, args)?;
+        executor.spawn(&details, &mut command)?;
+        let sandbox = match details.sandbox {
+            Some(Sandbox::Tempdir) => {
+                let inputs = self.files(&details, "inputs", &details.inputs)?;
+                let sandbox =
+                    SandboxDir::create(&self.session, &self.working_directory(&details), &inputs)
+                        .map_err(|reason| ExecutionError::Sandbox(self.label(key), reason))?;
+                debug!(label = %self.label(key), dir = %sandbox.path().display(), "sandboxed");
+                command.current_dir(sandbox.path());
+                Some(sandbox)
+            }
+            None => None,
+        };
+        if let Some(umask) = details.umask_bits().map_err(|reason| {
+            ConfigError::InvalidValue(format!("{}.umask", self.label(key)), reason)
+        })? {
+            sandbox::apply_umask(&mut command, umask);
+        }
+        let stdin_source = self.stdin_source(&details);
+        let pty =
+            details.pty && stdin_source == StdinSource::Inherit && details.output_filter.is_none();
+        if details.pty && !pty {
+            warn!(label = %self.label(key), "stdin or an output_filter is given, running without a pty");
+        }
+        let redirected_stdin = stdin_source != StdinSource::Inherit;
+        let input = match stdin_source {
+            StdinSource::Text(text) => Some(text),
+            _ => None,
+        };
+        let filter = match &details.output_filter {
+            Some(filter) => Some(self.filter_command(filter, &details)?),
+            None => None,
+        };
+        let encoding = match &details.output_encoding {
+            Some(label) => Some(output::encoding(label).ok_or_else(|| {
+                ConfigError::InvalidValue(
+                    format!("{}.output_encoding", self.label(key)),
+                    format!("unknown encoding '{}'", label),
+                )
+            })?),
+            None => None,
+        };
+        let options = SpawnOptions {
+            input,
+            redirected_stdin,
+            filter,
+            limits: Some(&details.limits).filter(|limits| !limits.is_empty()),
+            encoding,
+            pty,
+            max_output_lines: details.max_output_lines,
+            log: self.open_log(key, &details)?,
+        };
+        let label = self.label(key);
+        // Taken before a place in the queue, which waiting for the lock would hold idle
+        let _lock = match &details.lock {
+            Some(name) => {
+                let dir = lock::locks_dir().ok_or_else(|| {
+                    ExecutionError::Lock(name.clone(), "no local data directory".to_string())
+                })?;
+                Some(NamedLock::acquire(
+                    &dir,
+                    name,
+                    &label,
+                    &self.shutdown,
+                    || self.notify_step(StepEvent::Locked(label.clone(), name.clone())),
+                )?)
+            }
+            None => None,
+        };
+        let _slot = match &self.queue {
+            Some(queue) => Some(queue.enter(&label, &self.shutdown, |position| {
+                self.notify_step(StepEvent::Queued(label.clone(), position))
+            })?),
+            None => None,
+        };
+        let streamed = executor.stream(&details).map(|stream| -> OutputObserver {
+            let observer = observer.cloned();
+            Arc::new(move |label: &str, kind: OutputStream, line: &str| {
+                stream(label, kind, line);
+                if let Some(observer) = &observer {
+                    observer(label, kind, line);
+                }
+            })
+        });
+        let observer = streamed.as_ref().or(observer);
+        let status = self.spawn_with(&label, grace_period(&details), command, observer, options)?;
+        let status = executor.finalize(&details, status)?;
+
+        if let Some(sandbox) = sandbox {
+            if details.is_success(status.code()) {
+                let outputs: Vec<String> = details
+                    .outputs
+                    .iter()
+                    .map(|pattern| self.interpolation.interpolate(pattern))
+                    .collect();
+                sandbox
+                    .copy_back(&self.working_directory(&details), &outputs)
+                    .map_err(|reason| ExecutionError::Sandbox(self.label(key), reason))?;
+            }
+        }
+        if let Some(cache) = cache {
+            if details.is_success(status.code()) {
+                self.record_cache(cache, &details)?;
+            }
+        }
+        Ok(status)
+    }
+
+    /// For a multi-line shell `command` or a script from a URL, details running it as a single
+    /// command line: from an executable temp file locally, through `interpreter -c` in a
+    /// container, over ssh or `detached` from rx, outliving the temp file. Downloaded scripts
+    /// run as they are, without placeholders.
+    pub(crate) fn inline_script<'d>(
+        &self,
+        key: &str,
+        details: &'d CommandDetails,
+        detached: bool,
+    ) -> Result<(Cow<'d, CommandDetails>, Option<ScriptFile>), ExecutionError> {
+        if let CommandSource::Url(url) = &details.command {
+            let refused = match details.command_type {
+                CommandType::Shell => details.command.check().err(),
+                _ => Some("only shell configs run a script from a URL".to_string()),
+            };
+            if let Some(reason) = refused {
+                return Err(ExecutionError::ScriptDownload(url.url.clone(), reason));
+            }
+        }
+        if details.command_type != CommandType::Shell {
+            return Ok((Cow::Borrowed(details), None));
+        }
+        let body = match &details.command {
+            CommandSource::Url(url) => {
+                let dir = script::scripts_dir().ok_or_else(|| {
+                    ExecutionError::ScriptDownload(url.url.clone(), "no cache directory".into())
+                })?;
+                script::fetch(url, &dir)?
+            }
+            CommandSource::Line(line) if script::is_inline(line) => {
+                self.check_placeholders(details)?;
+                self.interpolation_for(details)
+                    .interpolate_as(line, self.unknown_placeholders(details))
+            }
+            CommandSource::Line(_) => return Ok((Cow::Borrowed(details), None)),
+        };
+        let shell = details.shell.as_deref();
+        let mut inline = details.clone();
+
+        if detached || details.container.is_some() || details.remote.is_some() {
+            inline.command = script::inline_command_line(&body, shell).into();
+            return Ok((Cow::Owned(inline), None));
+        }
+
+        let script_file = ScriptFile::create(&self.session, &body, shell)
+            .map_err(|err| ExecutionError::Spawn(self.label(key), err))?;
+        inline.command = shell_quote(&script_file.path().to_string_lossy()).into();
+        Ok((Cow::Owned(inline), Some(script_file)))
+    }
+
+    /// `details` of `key` with its override for the `--target` given in its params or `args`
+    /// applied, or for the host when there is none, the working directory of its namespace
+    /// when it has none of its own, and its `target_dir` in the env.
+    pub fn target_details(
+        &self,
+        key: &str,
+        details: &'a CommandDetails,
+        args: &[String],
+    ) -> Cow<'a, CommandDetails> {
+        let mut details = self.target_override(details, args);
+        if let Some(dir) = self
+            .config
+            .namespace(key)
+            .and_then(|(_, namespace)| namespace.working_directory.as_ref())
+        {
+            if details.has_default_working_directory() {
+                details.to_mut().working_directory = dir.clone();
+            }
+        }
+        if let Some(target_dir) = self.target_dir(key, &details) {
+            details.to_mut().env.insert(
+                TARGET_DIR_VARIABLE.to_string(),
+                target_dir.display().to_string(),
+            );
+        }
+        details
+    }
+
+    /// Where the cargo config `key` builds into when it has a `target_dir`.
+    pub fn target_dir(&self, key: &str, details: &CommandDetails) -> Option<PathBuf> {
+        let target_dir = self.interpolation.interpolate(&details.target_dir(key)?);
+        Some(self.working_directory(details).join(target_dir))
+    }
+
+    /// Where the config `key` appends its output when it has a `log_file`.
+    pub fn log_file(&self, key: &str, details: &CommandDetails) -> Option<PathBuf> {
+        let log_file = self.interpolation.interpolate(&details.log_file(key)?);
+        Some(self.working_directory(details).join(log_file))
+    }
+
+    fn open_log(
+        &self,
+        key: &str,
+        details: &CommandDetails,
+    ) -> Result<Option<OutputLog>, ExecutionError> {
+        let Some(path) = self.log_file(key, details) else {
+            return Ok(None);
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|err| ExecutionError::Spawn(self.label(key), err))?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|err| ExecutionError::Spawn(self.label(key), err))?;
+        debug!(label = %self.label(key), path = %path.display(), "logging the output");
+        Ok(Some(OutputLog::new(file, details.keep_ansi)))
+    }
+
+    fn target_override(
+        &self,
+        details: &'a CommandDetails,
+        args: &[String],
+    ) -> Cow<'a, CommandDetails> {
+        if details.target.is_empty() {
+            return Cow::Borrowed(details);
+        }
+
+        let params = details
+            .params
+            .args(|word| self.interpolation.interpolate(word));
+        let args = params
+            .iter()
+            .map(String::as_str)
+            .chain(args.iter().map(String::as_str));
+        let triple = target_arg(args)
+            .map(str::to_string)
+            .unwrap_or_else(|| host_triple(&self.session).to_string());
+
+        details.for_target(&triple)
+    }
+
+    /// Spawns `command` in its own process group and waits for it, honouring shutdown.
+    pub(crate) fn spawn(
+        &self,
+        label: &str,
+        grace_period: Duration,
+        command: Command,
+        observer: Option<&OutputObserver>,
+    ) -> Result<ExitStatus, ExecutionError> {
+        self.spawn_with(
+            label,
+            grace_period,
+            command,
+            observer,
+            SpawnOptions::default(),
+        )
+    }
+
+    /// Like [`Runner::spawn`], with the extras of `options`.
+    fn spawn_with(
+        &self,
+        label: &str,
+        grace_period: Duration,
+        mut command: Command,
+        observer: Option<&OutputObserver>,
+        options: SpawnOptions,
+    ) -> Result<ExitStatus, ExecutionError> {
+        let SpawnOptions {
+            input,
+            redirected_stdin,
+            filter,
+            limits,
+            encoding,
+            pty,
+            max_output_lines,
+            log,
+        } = options;
+        if self.shutdown.is_triggered() {
+            return Err(ExecutionError::Interrupted(label.to_string()));
+        }
+
+        info!(
+            label,
+            program = %command.get_program().to_string_lossy(),
+            args = ?command.get_args().collect::<Vec<_>>(),
+            cwd = ?command.get_current_dir(),
+            "spawning"
+        );
+        if let Some(timings) = &self.timings {
+            timings.end_once(PREPARE);
+        }
+        let pty = match pty {
+            true => match Pty::attach(&mut command) {
+                Ok(pty) => Some(pty),
+                Err(err) => {
+                    warn!(label, %err, "could not open a pty, running without");
+                    None
+                }
+            },
+            false => None,
+        };
+        let pty_output = pty
+            .as_ref()
+            .map(Pty::output)
+            .transpose()
+            .map_err(|err| ExecutionError::Spawn(label.to_string(), err))?;
+        if pty.is_none() {
+            isolate_process_group(&mut command);
+        }
+        // A command reading the terminal needs its foreground, unless rx reads it key by key
+        let foreground =
+            match pty.is_none() && !redirected_stdin && !keys::stdin_claimed(&self.session) {
+                true => Foreground::prepare(&mut command),
+                false => None,
+            };
+        // Output in another encoding is decoded for the terminal too, unless it's wanted raw
+        let decode_echo = self.echo_output
+            && !self.raw_output
+            && encoding.is_some_and(|encoding| encoding != UTF_8);
+        // A pty's output is always read back, nothing else writes it to rx's terminal
+        let piped = observer.is_some() || decode_echo || pty.is_some() || log.is_some();
+        if piped && pty.is_none() {
+            command.stdout(Stdio::piped()).stderr(Stdio::piped());
+        }
+        // Piped to be read back, the terminal should still get the colors it would have
+        if piped && pty.is_none() && self.echo_output && io::stdout().is_terminal() {
+            for (name, value) in FORCE_COLOR {
+                let set = command.get_envs().any(|(set, _)| set == name);
+                if !set && std::env::var_os(name).is_none() {
+                    command.env(name, value);
+                }
+            }
+        }
+        if let Some(limits) = limits {
+            limits::apply(&mut command, limits)
+                .map_err(|err| ExecutionError::Spawn(label.to_string(), err))?;
+        }
+
+        // The process is waited for, fed and read on the shared runtime
+        let _runtime = self.session.runtime().enter();
+        let mut filter = match filter {
+            Some(mut filter) => {
+                debug!(label, program = %filter.get_program().to_string_lossy(), "filtering the output");
+                isolate_process_group(&mut filter);
+                filter.stdin(Stdio::piped());
+                if piped {
+                    filter.stdout(Stdio::piped()).stderr(Stdio::piped());
+                }
+                let filter_error =
+                    |err| ExecutionError::Spawn(format!("{} output_filter", label), err);
+                let mut filter_child = tokio::process::Command::from(filter)
+                    .spawn()
+                    .map_err(filter_error)?;
+                if let Some(filter_stdin) = filter_child.stdin.take() {
+                    let filter_stdin: Stdio = filter_stdin.try_into().map_err(filter_error)?;
+                    command.stdout(filter_stdin);
+                }
+                Some(filter_child)
+            }
+            None => None,
+        };
+
+        let mut command = tokio::process::Command::from(command);
+        let spawned = command.spawn();
+        // Closes the command's end of the pipe to the filter, so the filter sees it finish
+        drop(command);
+        let mut child = match spawned {
+            Ok(child) => child,
+            Err(err) => {
+                if let Some(filter) = &mut filter {
+                    let _ = filter.start_kill();
+                    let _ = self.session.block_on(filter.wait());
+                }
+                return Err(ExecutionError::Spawn(label.to_string(), err));
+            }
+        };
+        if let Some(foreground) = &foreground {
+            foreground.hand_to(child.id());
+        }
+        if let Some(limits) = limits {
+            if let Err(err) = limits::attach(&child, limits) {
+                warn!(label, %err, "could not apply the limits");
+            }
+        }
+
+        let feeder = input
+            .zip(child.stdin.take())
+            .map(|(input, mut child_stdin)| {
+                self.session.runtime().spawn(async move {
+                    match child_stdin.write_all(input.as_bytes()).await {
+                        // The command doesn't have to read all of it
+                        Err(err) if err.kind() == ErrorKind::BrokenPipe => Ok(()),
+                        result => result,
+                    }
+                })
+            });
+
+        let mut pumps = Vec::new();
+        let observer = observer
+            .cloned()
+            .unwrap_or_else(|| Arc::new(|_: &str, _: OutputStream, _: &str| {}));
+        let limit = max_output_lines.map(|max_lines| Arc::new(OutputLimit::new(max_lines)));
+        let tapped: OutputObserver = match (&log, &limit) {
+            (None, None) => observer.clone(),
+            _ => {
+                let log = log.map(Arc::new);
+                let limit = limit.clone();
+                let observer = observer.clone();
+                Arc::new(move |label: &str, stream: OutputStream, line: &str| {
+                    if let Some(log) = &log {
+                        log.write(line);
+                    }
+                    if limit.as_ref().is_none_or(|limit| limit.admit(stream, line)) {
+                        observer(label, stream, line);
+                    }
+                })
+            }
+        };
+        if piped {
+            let echo = match (self.echo_output, decode_echo) {
+                (false, _) => Echo::Off,
+                (true, false) => Echo::Raw,
+                (true, true) => Echo::Decoded,
+            };
+            let pump = |reader: Box<dyn AsyncRead + Send + Unpin>, stream| {
+                self.session.runtime().spawn(output::pump(
+                    reader,
+                    label.to_string(),
+                    stream,
+                    tapped.clone(),
+                    echo,
+                    encoding.unwrap_or(UTF_8),
+                ))
+            };
+            if let Some(output) = pty_output {
+                let output = tokio::fs::File::from_std(output);
+                pumps.push(pump(Box::new(output), OutputStream::Stdout));
+            }
+            let stdout = match &mut filter {
+                Some(filter) => filter.stdout.take(),
+                None => child.stdout.take(),
+            };
+            if let Some(stdout) = stdout {
+                pumps.push(pump(Box::new(stdout), OutputStream::Stdout));
+            }
+            let filter_stderr = filter.as_mut().and_then(|filter| filter.stderr.take());
+            if let Some(stderr) = child.stderr.take() {
+                pumps.push(pump(Box::new(stderr), OutputStream::Stderr));
+            }
+            if let Some(stderr) = filter_stderr {
+                pumps.push(pump(Box::new(stderr), OutputStream::Stderr));
+            }
+        }
+
+        let session = pty.as_ref().and_then(|pty| pty.session(&self.session).ok());
+        let outcome = self.session.block_on(async {
+            let outcome = supervise(&mut child, &self.shutdown, grace_period).await;
+            if let Some(session) = session {
+                session.finish();
+            }
+            if let Some(filter) = &mut filter {
+                let filter_outcome = supervise(filter, &self.shutdown, grace_period).await;
+                debug!(label, outcome = ?filter_outcome, "output_filter finished");
+            }
+
+            for pump in pumps {
+                let _ = pump.await;
+            }
+            if let Some(limit) = &limit {
+                limit.finish(label, &observer);
+            }
+            if let Some(feeder) = feeder {
+                let _ = feeder.await;
+            }
+            outcome
+        });
+
+        let had_foreground = foreground.is_some();
+        drop(foreground);
+        match outcome {
+            // The Ctrl-C meant for rx went to the command holding the terminal
+            Ok(ChildOutcome::Exited(status))
+                if had_foreground && Foreground::interrupted(&status) =>
+            {
+                self.shutdown.trigger();
+                Err(ExecutionError::Interrupted(label.to_string()))
+            }
+            Ok(ChildOutcome::Exited(status)) => Ok(status),
+            Ok(ChildOutcome::Interrupted(_)) => Err(ExecutionError::Interrupted(label.to_string())),
+            Err(err) => Err(ExecutionError::Spawn(label.to_string(), err)),
+        }
+    }
+
+    /// Turns `details` into a ready to spawn process with placeholders resolved.
+    pub fn build_command(
+        &self,
+        details: &CommandDetails,
+        args: &[String],
+    ) -> Result<Command, ExecutionError> {
+        let env = self.environment(details)?.to_map();
+        let mut invocation = self.with_shell_init(self.invocation(details, args)?, details);
+        let working_directory = self.working_directory(details);
+        let stdin_source = self.stdin_source(details);
+        let command_stdin = match &stdin_source {
+            StdinSource::Inherit => None,
+            StdinSource::File(path) => Some(Stdio::from(File::open(path).map_err(|err| {
+                ExecutionError::StdinFile(path.display().to_string(), err.to_string())
+            })?)),
+            StdinSource::Text(_) => Some(Stdio::piped()),
+        };
+
+        if let Some(container) = &details.container {
+            let workspace_folder = self
+                .interpolation
+                .get("workspaceFolder")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| working_directory.clone());
+
+            invocation = Invocation::Argv(container::docker_argv(
+                container,
+                &workspace_folder,
+                &working_directory,
+                &env,
+                invocation.into_argv(),
+                stdin_source == StdinSource::Inherit && stdin().is_terminal(),
+            ));
+        }
+
+        if details.elevate || details.run_as.is_some() {
+            invocation = Invocation::Argv(elevate::wrap(
+                &self.session,
+                invocation.into_local_argv(),
+                details.run_as.as_deref(),
+                &env,
+            ));
+        }
+
+        if let Some(host) = &details.remote {
+            let remote_dir = self.remote_dir(details, &working_directory);
+            let command = remote::ssh_command(host, &remote_dir, &env, &invocation.to_script());
+            return Ok(with_stdin(command, command_stdin));
+        }
+
+        let mut command = with_stdin(invocation.into_command(), command_stdin);
+        self.limit_inherited_env(&mut command, details);
+        command.envs(&env);
+
+        if !working_directory.as_os_str().is_empty() {
+            command.current_dir(working_directory);
+        }
+
+        Ok(command)
+    }
+
+    fn invocation(
+        &self,
+        details: &CommandDetails,
+        args: &[String],
+    ) -> Result<Invocation, ExecutionError> {
+        self.check_placeholders(details)?;
+        // Composite configs have no command of their own, see `run_composite`
+        if details.command_type == CommandType::Composite {
+            return Ok(Invocation::Script(String::new()));
+        }
+        self.executors
+            .get(&details.command_type)?
+            .resolve(self, details, args)
+    }
+
+    /// The command line of `details` with its params and the extra `args`, placeholders
+    /// resolved, what a shell config runs.
+    pub fn shell_line(&self, details: &CommandDetails, args: &[String]) -> String {
+        let interpolation = self.interpolation_for(details);
+        let unknown = self.unknown_placeholders(details);
+        let command_line = interpolation.interpolate_as(details.command.line(), unknown);
+        let params = details
+            .params
+            .shell_line(|line| interpolation.interpolate_as(line, unknown));
+        [command_line.as_str(), params.as_str()]
+            .into_iter()
+            .chain(args.iter().map(String::as_str))
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// The cargo command of `details`, through cross, nextest or a wasm runner when it asks for
+    /// them and runs on this machine.
+    pub(crate) fn cargo_invocation(
+        &self,
+        details: &CommandDetails,
+        args: &[String],
+    ) -> Result<Invocation, ExecutionError> {
+        let argv = self.cargo_argv(details, args);
+        let local = details.container.is_none() && details.remote.is_none();
+        let wasm_argv = local
+            .then(|| wasm::runner(&argv, details.wasm_runner))
+            .flatten()
+            .and_then(|runner| Some((runner, wasm::translate(&argv, runner)?)));
+        if let Some((runner, wasm_argv)) = wasm_argv {
+            if !wasm::is_installed(&self.session, runner) {
+                let (tool, hint) = wasm::install_hint(runner);
+                return Err(ExecutionError::ToolMissing(tool.into(), hint.into()));
+            }
+            debug!(?runner, "running the wasm32 target");
+            return Ok(Invocation::Argv(wasm_argv));
+        }
+        let cross_runner = details.cross_runner.unwrap_or_default();
+        let cross_argv = local
+            .then(|| cross::translate(&self.session, &argv, cross_runner))
+            .flatten();
+        if cross_argv.is_some() && !cross::is_installed(&self.session, cross_runner) {
+            let (tool, hint) = cross::install_hint(cross_runner);
+            return Err(ExecutionError::ToolMissing(tool.into(), hint.into()));
+        }
+        if cross_argv.is_some() {
+            debug!(runner = ?cross_runner, "cross-compiling");
+        }
+        let nextest_argv = local.then(|| self.nextest_argv(&argv)).flatten();
+        if nextest_argv.is_some() {
+            debug!("running the tests with cargo-nextest");
+        }
+        Ok(Invocation::Argv(
+            cross_argv.or(nextest_argv).unwrap_or(argv),
+        ))
+    }
+
+    /// `invocation` run after sourcing the `source` files of `details`, through the user's
+    /// login shell when it sets `login_shell`.
+    fn with_shell_init(&self, invocation: Invocation, details: &CommandDetails) -> Invocation {
+        if !details.login_shell && details.source.is_empty() {
+            return invocation;
+        }
+        let script = details
+            .source
+            .iter()
+            .map(|file| format!(". {}", source_path(&self.interpolation.interpolate(file))))
+            .chain(std::iter::once(invocation.to_script()))
+            .collect::<Vec<_>>()
+            .join(" && ");
+        if !details.login_shell {
+            return Invocation::Script(script);
+        }
+        let shell = std::env::var("SHELL")
+            .ok()
+            .filter(|shell| !shell.is_empty())
+            .unwrap_or_else(|| script::DEFAULT_SHELL.to_string());
+        Invocation::Argv(vec![shell, "-l".into(), "-c".into(), script])
+    }
+
+    /// What the placeholders without a value in the command and params of `details` become.
+    fn unknown_placeholders(&self, details: &CommandDetails) -> UnknownPlaceholders {
+        details
+            .unknown_placeholders
+            .unwrap_or(self.config.settings.unknown_placeholders)
+    }
+
+    /// The placeholders with `${binaryName}` resolving to the `binary_name` of `details` when
+    /// it has one, and the `args` of `details` without a value to their defaults.
+    pub(crate) fn interpolation_for(
+        &self,
+        details: &CommandDetails,
+    ) -> Cow<'_, InterpolationContext> {
+        let defaults: Vec<(&String, &ArgValue)> = details
+            .args
+            .iter()
+            .filter(|(name, _)| self.interpolation.arg(name).is_none())
+            .filter_map(|(name, spec)| Some((name, spec.default.as_ref()?)))
+            .collect();
+        if details.binary_name.is_none() && defaults.is_empty() {
+            return Cow::Borrowed(&self.interpolation);
+        }
+
+        let mut interpolation = self.interpolation.clone();
+        if let Some(name) = &details.binary_name {
+            interpolation.set_binary(Ok(name.clone()));
+        }
+        for (name, default) in defaults {
+            interpolation.set_arg(name, &default.to_string());
+        }
+        Cow::Owned(interpolation)
+    }
+
+    /// The binaries to pick from for `${binaryName}` in the command or params of `key`, empty
+    /// when it resolves or isn't used.
+    pub fn binary_candidates(&self, key: &str) -> Result<Vec<String>, ConfigError> {
+        let details = self.details(key)?;
+        let interpolation = self.interpolation_for(details);
+        let text = format!("{} {}", details.command.line(), details.params);
+        if interpolation
+            .unresolved(&text)
+            .iter()
+            .any(|name| name == "${binaryName}")
+        {
+            Ok(interpolation.binaries().to_vec())
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    /// Refuses the command and params of `details` when `${binaryName}` has no value, which
+    /// would leave a broken command line, or they have other placeholders without a value and
+    /// unknown placeholders are an error.
+    fn check_placeholders(&self, details: &CommandDetails) -> Result<(), ConfigError> {
+        let interpolation = self.interpolation_for(details);
+        let text = format!("{} {}", details.command.line(), details.params);
+        let unresolved = interpolation.unresolved(&text);
+        if unresolved.iter().any(|name| name == "${binaryName}") {
+            return Err(ConfigError::NoBinaryName(interpolation.binaries().to_vec()));
+        }
+        if let Some(name) = find_args(&text)
+            .into_iter()
+            .find(|name| details.args.contains_key(name) && interpolation.arg(name).is_none())
+        {
+            return Err(ConfigError::MissingArg(name));
+        }
+        if unresolved.is_empty() || self.unknown_placeholders(details) != UnknownPlaceholders::Error
+        {
+            Ok(())
+        } else {
+            Err(ConfigError::UnresolvedPlaceholders(unresolved))
+        }
+    }
+
+    /// `cargo` with the config's command, params and `args`, placeholders resolved, and the
+    /// runner's package selected.
+    pub(crate) fn cargo_argv(&self, details: &CommandDetails, args: &[String]) -> Vec<String> {
+        let interpolation = self.interpolation_for(details);
+        let unknown = self.unknown_placeholders(details);
+        let command_line = interpolation.interpolate_as(details.command.line(), unknown);
+
+        // The params are split before their placeholders resolve, so they aren't split again
+        let mut argv: Vec<String> = std::iter::once("cargo")
+            .chain(command_line.split_whitespace())
+            .map(String::from)
+            .chain(
+                details
+                    .params
+                    .args(|word| interpolation.interpolate_as(word, unknown)),
+            )
+            .collect();
+
+        if self.context == CommandContext::Fmt && self.fix {
+            argv.retain(|arg| arg != "--check");
+        }
+        if self.context == CommandContext::Coverage {
+            if let Some(options) = self
+                .config
+                .commands
+                .get_config(self.context)
+                .and_then(|command_config| command_config.coverage.as_ref())
+            {
+                coverage::apply(&mut argv, options);
+            }
+        }
+
+        // Options for cargo go before a `--` of the params, whatever follows it goes to the
+        // binary or, for clippy, is a lint flag
+        let options_end = argv
+            .iter()
+            .position(|arg| arg == "--")
+            .unwrap_or(argv.len());
+        let options = &argv[..options_end];
+        let mut inserted: Vec<String> = Vec::new();
+
+        if let Some(package) = &self.package {
+            if !selects_package(argv.iter().chain(args).map(String::as_str)) {
+                inserted.extend(["--package".to_string(), package.clone()]);
+            }
+        }
+        let offline_subcommand = options
+            .get(1)
+            .is_some_and(|subcommand| OFFLINE_SUBCOMMANDS.contains(&subcommand.as_str()));
+        if self.is_offline(details)
+            && offline_subcommand
+            && !options.iter().any(|arg| arg == "--offline")
+        {
+            inserted.push("--offline".to_string());
+        }
+        if self.context == CommandContext::Lint
+            && self.fix
+            && !options.iter().any(|arg| arg == "--fix")
+        {
+            inserted.extend(["--fix".to_string(), "--allow-dirty".to_string()]);
+        }
+        // Clippy's diagnostics are handed to the observer as JSON when nobody reads the terminal
+        let observed_lint = self.context == CommandContext::Lint
+            && self.output_observer.is_some()
+            && !self.echo_output;
+        let builds = options
+            .get(1)
+            .is_some_and(|subcommand| BUILDING_SUBCOMMANDS.contains(&subcommand.as_str()));
+        let message_format = if self.json_messages || observed_lint {
+            Some("--message-format=json")
+        } else if self.report_artifacts {
+            // Cargo still renders the diagnostics itself, only the artifacts come as JSON
+            Some("--message-format=json-render-diagnostics")
+        } else {
+            None
+        };
+        if let Some(message_format) = message_format.filter(|_| builds) {
+            if !options
+                .iter()
+                .any(|arg| arg.starts_with("--message-format"))
+            {
+                inserted.push(message_format.to_string());
+            }
+        }
+        argv.splice(options_end..options_end, inserted);
+
+        argv.extend(args.iter().cloned());
+        argv
+    }
+
+    /// The `cargo nextest run` equivalent of `argv` when the test context uses nextest and it
+    /// is installed. Only local runs are translated, nextest is looked up on this machine.
+    fn nextest_argv(&self, argv: &[String]) -> Option<Vec<String>> {
+        // nextest has no JSON format for compiler messages
+        if self.context != CommandContext::Test || self.json_messages {
+            return None;
+        }
+        let command_config = self.config.commands.get_config(self.context)?;
+        if command_config.runner != Some(TestRunner::Nextest)
+            || !nextest::is_installed(&self.session)
+        {
+            return None;
+        }
+
+        let options = command_config.nextest.clone().unwrap_or_default();
+        let tool_config = nextest::junit_tool_config(&options).map(|content| {
+            let dir = self
+                .project_store()
+                .map(|store| store.root().to_path_buf())
+                .unwrap_or_else(std::env::temp_dir);
+            let path = dir.join("nextest.toml");
+            // A write failure shows up as nextest failing to read the file
+            let _ = fs::create_dir_all(&dir).and_then(|_| fs::write(&path, content));
+            path
+        });
+
+        nextest::translate(argv, &options, tool_config.as_deref())
+    }
+
+    /// Clears the process environment from `command` when `details` doesn't inherit it, keeping
+    /// only [`crate::env::ALWAYS_INHERITED`].
+    pub(crate) fn limit_inherited_env(&self, command: &mut Command, details: &CommandDetails) {
+        if !details.inherits_env() {
+            command.env_clear().envs(process_env(false));
+        }
+    }
+
+    /// Checks that the third-party subcommand of a cargo config, such as `cargo udeps`, is
+    /// installed, installing it first when the config sets `auto_install`. Containers and
+    /// remote hosts have their own tools, they are left to report a missing one.
+    pub(crate) fn ensure_plugin(
+        &self,
+        key: &str,
+        details: &CommandDetails,
+        args: &[String],
+    ) -> Result<(), ExecutionError> {
+        if details.command_type != CommandType::Cargo
+            || details.container.is_some()
+            || details.remote.is_some()
+        {
+            return Ok(());
+        }
+        let argv = self.cargo_argv(details, args);
+        let Some(subcommand) = plugin::plugin(&argv) else {
+            return Ok(());
+        };
+        if plugin::is_installed(&self.session, subcommand) {
+            return Ok(());
+        }
+
+        let install_args = plugin::install_args(subcommand, details.install_hint.as_deref());
+        let install_line = format!("cargo install {}", install_args.join(" "));
+        if !details.auto_install {
+            return Err(ExecutionError::ToolMissing(
+                format!("cargo {}", subcommand),
+                format!("{}, or set auto_install = true", install_line),
+            ));
+        }
+
+        info!(subcommand, "installing the missing cargo subcommand");
+        let mut install = Command::new("cargo");
+        install.arg("install").args(&install_args);
+        let status = self.spawn(
+            &self.label(key),
+            grace_period(details),
+            install,
+            self.unechoed_observer(),
+        )?;
+        if !status.success() {
+            return Err(ExecutionError::CommandFailed(install_line, status.code()));
+        }
+        Ok(())
+    }
+
+    /// Checks the ports of `requires_ports` are free, stopping what holds them when the config
+    /// has `kill_conflicts` and the handler agrees. Ports of a remote command are its host's.
+    pub(crate) fn ensure_ports(
+        &self,
+        key: &str,
+        details: &CommandDetails,
+    ) -> Result<(), ExecutionError> {
+        if details.remote.is_some() {
+            return Ok(());
+        }
+        for port in &details.requires_ports {
+            let Some(conflict) = ports::conflict(*port) else {
+                continue;
+            };
+            let label = self.label(key);
+            let stopped = match conflict.pid {
+                Some(pid) if details.kill_conflicts => {
+                    let agreed = match &self.port_conflicts {
+                        Some(handler) => handler(&label, &conflict),
+                        None => true,
+                    };
+                    info!(label, port, pid, agreed, "port in use");
+                    agreed && ports::stop(pid, *port)
+                }
+                _ => false,
+            };
+            if !stopped {
+                return Err(ExecutionError::PortInUse(label, conflict));
+            }
+        }
+        Ok(())
+    }
+
+    /// The process `filter` names, a `context::key` config or a shell line run like the
+    /// command of `details`, to read that command's stdout.
+    fn filter_command(
+        &self,
+        filter: &str,
+        details: &CommandDetails,
+    ) -> Result<Command, ExecutionError> {
+        match HookEntry::parse(filter) {
+            HookEntry::Config(context, key) => {
+                let runner = self.for_context(context);
+                runner.build_command(&runner.target_details(key, runner.details(key)?, &[]), &[])
+            }
+            HookEntry::Inline(line) => {
+                let mut command = shell_command(&self.interpolation.interpolate(line));
+                self.limit_inherited_env(&mut command, details);
+                command.envs(self.environment(details)?.to_map());
+                let working_directory = self.working_directory(details);
+                if !working_directory.as_os_str().is_empty() {
+                    command.current_dir(working_directory);
+                }
+                Ok(command)
+            }
+        }
+    }
+
+    /// Where the command of `details` reads its stdin from, the runner's own [`StdinSource`]
+    /// first.
+    fn stdin_source(&self, details: &CommandDetails) -> StdinSource {
+        if let Some(source) = &self.stdin {
+            return source.clone();
+        }
+        match (&details.stdin, &details.stdin_file) {
+            (Some(text), _) => StdinSource::Text(self.interpolation.interpolate(text)),
+            (None, Some(file)) => StdinSource::File(
+                self.working_directory(details)
+                    .join(self.interpolation.interpolate(file)),
+            ),
+            (None, None) => StdinSource::Inherit,
+        }
+    }
+
+    pub(crate) fn working_directory(&self, details: &CommandDetails) -> PathBuf {
+        PathBuf::from(self.interpolation.interpolate(&details.working_directory))
+    }
+
+    /// The files `rx run --watch` watches for the config `key`: those its `watch` globs match,
+    /// or [`DEFAULT_WATCH`] without them.
+    pub fn watched_files(&self, key: &str) -> Result<Vec<PathBuf>, ExecutionError> {
+        let details = self.details(key)?;
+        let patterns = match details.watch.is_empty() {
+            true => DEFAULT_WATCH.map(String::from).to_vec(),
+            false => details.watch.clone(),
+        };
+        self.files(details, "watch", &patterns)
+    }
+
+    /// Checks that the working directory of `details` is a directory before a command runs in
+    /// it, creating it when `create_working_directory` asks for it, rather than leaving the
+    /// spawn to fail with the OS's error. A remote command's directory is on its host.
+    pub(crate) fn ensure_working_directory(
+        &self,
+        details: &CommandDetails,
+    ) -> Result<(), ExecutionError> {
+        let dir = self.working_directory(details);
+        if details.remote.is_some() || dir.as_os_str().is_empty() || dir.is_dir() {
+            return Ok(());
+        }
+        let error =
+            |reason: String| ExecutionError::WorkingDirectory(dir.display().to_string(), reason);
+        if dir.exists() {
+            return Err(error("it is not a directory".to_string()));
+        }
+        if !details.create_working_directory {
+            return Err(error(
+                "it does not exist, `create_working_directory = true` creates it".to_string(),
+            ));
+        }
+        debug!(dir = %dir.display(), "creating the working directory");
+        fs::create_dir_all(&dir).map_err(|err| error(err.to_string()))
+    }
+
+    fn remote_dir(&self, details: &CommandDetails, working_directory: &Path) -> String {
+        details
+            .remote_dir
+            .as_ref()
+            .map(|remote_dir| self.interpolation.interpolate(remote_dir))
+            .unwrap_or_else(|| remote::default_remote_dir(working_directory))
+    }
+
+    pub fn label(&self, key: &str) -> String {
+        match &self.matrix_cell {
+            Some(cell) => format!("{}::{} [{}]", self.context, key, cell),
+            None => format!("{}::{}", self.context, key),
+        }
+    }
+}
+
+pub(crate) fn grace_period(details: &CommandDetails) -> Duration {
+    details
+        .shutdown_grace_period
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_GRACE_PERIOD)
+}
+
+fn with_stdin(mut command: Command, stdin: Option<Stdio>) -> Command {
+    if let Some(stdin) = stdin {
+        command.stdin(stdin);
+    }
+    command
+}
+
+/// Every string of `value`, nested ones included.
+fn strings(value: &toml::Value) -> Vec<&str> {
+    match value {
+        toml::Value::String(string) => vec![string],
+        toml::Value::Array(values) => values.iter().flat_map(strings).collect(),
+        toml::Value::Table(table) => table.values().flat_map(strings).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// What gets executed: a program with its arguments, or a script for the shell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Invocation {
+    Argv(Vec<String>),
+    Script(String),
+}
+
+impl Invocation {
+    fn into_command(self) -> Command {
+        match self {
+            Invocation::Argv(argv) => {
+                let mut command = Command::new(&argv[0]);
+                command.args(&argv[1..]);
+                command
+            }
+            Invocation::Script(script) => shell_command(&script),
+        }
+    }
+
+    fn into_argv(self) -> Vec<String> {
+        match self {
+            Invocation::Argv(argv) => argv,
+            Invocation::Script(script) => vec!["sh".into(), "-c".into(), script],
+        }
+    }
+
+    /// The program and arguments run on this machine, a script going to its shell.
+    fn into_local_argv(self) -> Vec<String> {
+        match self {
+            Invocation::Argv(argv) => argv,
+            Invocation::Script(script) => command_argv(&shell_command(&script)),
+        }
+    }
+
+    /// A single shell line, for executing somewhere other than the local machine.
+    fn to_script(&self) -> String {
+        match self {
+            Invocation::Argv(argv) => argv
+                .iter()
+                .map(|arg| shell_quote(arg))
+                .collect::<Vec<_>>()
+                .join(" "),
+            Invocation::Script(script) => script.clone(),
+        }
+    }
+}
+
+/// `file` quoted for the shell, a leading `~/` left out of the quotes so it expands to the home
+/// directory. Other relative paths start with `./`, `.` would look them up in `PATH`.
+fn source_path(file: &str) -> String {
+    match file.strip_prefix("~/") {
+        Some(rest) => format!("~/{}", shell_quote(rest)),
+        None if Path::new(file).is_relative() && !file.starts_with('.') => {
+            shell_quote(&format!("./{}", file))
+        }
+        None => shell_quote(file),
+    }
+}
+
+/// The program and arguments of `command`.
+fn command_argv(command: &Command) -> Vec<String> {
+    std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|arg| arg.to_string_lossy().to_string())
+        .collect()
+}
+
+/// `command` as a shell line, with the values of `NAME=VALUE` arguments masked when the name
+/// looks like a credential.
+pub fn describe(command: &Command) -> String {
+    command_argv(command)
+        .iter()
+        .map(|arg| match arg.split_once('=') {
+            Some((name, value)) if is_valid_env_var_name(name) => {
+                shell_quote(&format!("{}={}", name, masked(name, value)))
+            }
+            _ => shell_quote(arg),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The code rx should exit with to mirror `status`, using the shell's 128 + signal convention.
+pub fn exit_code(status: &ExitStatus) -> i32 {
+    if let Some(code) = status.code() {
+        return code;
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+
+    1
+}
+
+#[cfg(unix)]
+pub(crate) fn shell_command(script: &str) -> Command {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(script);
+    command
+}
+
+#[cfg(windows)]
+pub(crate) fn shell_command(script: &str) -> Command {
+    let mut command = Command::new("cmd");
+    command.arg("/C").arg(script);
+    command
+}
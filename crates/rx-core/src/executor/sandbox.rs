@@ -0,0 +1,95 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::{
+    cache::matching_files,
+    executor::temp::{temp_path, TempResource},
+    session::Session,
+};
+
+/// A fresh temporary directory a sandboxed command runs in, removed with everything in it when
+/// dropped.
+#[derive(Debug)]
+pub struct SandboxDir {
+    resource: TempResource,
+}
+
+impl SandboxDir {
+    /// Creates the directory, a live temp resource of `session`, with copies of `inputs`, files
+    /// below `working_directory`, at the same paths relative to it.
+    pub fn create(
+        session: &Session,
+        working_directory: &Path,
+        inputs: &[PathBuf],
+    ) -> Result<Self, String> {
+        let path = temp_path(session, "sandbox");
+        fs::create_dir(&path)
+            .map_err(|err| format!("cannot create {}: {}", path.display(), err))?;
+        let sandbox = Self {
+            resource: TempResource::new(session, path),
+        };
+
+        for input in inputs {
+            let relative = relative_to(input, working_directory)?;
+            copy(input, &sandbox.path().join(relative))?;
+        }
+        Ok(sandbox)
+    }
+
+    pub fn path(&self) -> &Path {
+        self.resource.path()
+    }
+
+    /// Copies the files below the sandbox matching the `outputs` globs to the same paths below
+    /// `working_directory`, replacing what is there.
+    pub fn copy_back(&self, working_directory: &Path, outputs: &[String]) -> Result<(), String> {
+        for output in matching_files(self.path(), outputs)? {
+            let relative = relative_to(&output, self.path())?;
+            copy(&output, &working_directory.join(relative))?;
+        }
+        Ok(())
+    }
+}
+
+fn relative_to<'p>(path: &'p Path, dir: &Path) -> Result<&'p Path, String> {
+    path.strip_prefix(dir)
+        .map_err(|_| format!("{} is outside of {}", path.display(), dir.display()))
+}
+
+fn copy(from: &Path, to: &Path) -> Result<(), String> {
+    let copied = match to.parent() {
+        Some(parent) => fs::create_dir_all(parent).and_then(|_| fs::copy(from, to)),
+        None => fs::copy(from, to),
+    };
+    copied.map(|_| ()).map_err(|err| {
+        format!(
+            "cannot copy {} to {}: {}",
+            from.display(),
+            to.display(),
+            err
+        )
+    })
+}
+
+/// Sets up `command` to start with the permission bits of `umask` cleared from the files it
+/// creates, inherited by the processes it starts.
+#[cfg(unix)]
+pub fn apply_umask(command: &mut Command, umask: u32) {
+    use std::os::unix::process::CommandExt;
+
+    // umask is async-signal-safe and can't fail
+    unsafe {
+        command.pre_exec(move || {
+            libc::umask(umask as libc::mode_t);
+            Ok::<(), std::io::Error>(())
+        });
+    }
+}
+
+#[cfg(not(unix))]
+pub fn apply_umask(_command: &mut Command, _umask: u32) {
+    tracing::warn!("umask is only applied on unix");
+}
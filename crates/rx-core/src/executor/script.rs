@@ -0,0 +1,162 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::{
+    checksum::sha256_hex,
+    errors::ExecutionError,
+    executor::temp::{temp_path, TempResource},
+    helpers::shell_quote,
+    models::command::ScriptUrl,
+    session::Session,
+};
+
+/// Interpreter used for inline scripts without a shebang or `shell`
+pub const DEFAULT_SHELL: &str = "sh";
+
+/// Whether `command` is an inline script body rather than a single command line.
+pub fn is_inline(command: &str) -> bool {
+    command.trim().contains('\n')
+}
+
+/// The interpreter of an inline script: its shebang, else `shell`, else `sh`.
+pub fn interpreter(body: &str, shell: Option<&str>) -> String {
+    let shebang = body
+        .trim_start()
+        .lines()
+        .next()
+        .and_then(|line| line.strip_prefix("#!"))
+        .map(|line| line.trim().to_string());
+
+    shebang
+        .or_else(|| shell.map(str::to_string))
+        .unwrap_or_else(|| DEFAULT_SHELL.to_string())
+}
+
+/// A single shell line running `body` through `interpreter -c`, for running an inline script
+/// somewhere the local temp file isn't visible. Shells get a `$0` so that the arguments
+/// appended to the line start at `$1`.
+pub fn inline_command_line(body: &str, shell: Option<&str>) -> String {
+    let interpreter = interpreter(body, shell);
+    let program = interpreter
+        .split_whitespace()
+        .last()
+        .and_then(|program| Path::new(program).file_name())
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let mut line = format!("{} -c {}", interpreter, shell_quote(body.trim_start()));
+    if program.ends_with("sh") {
+        line.push_str(" rx");
+    }
+    line
+}
+
+/// An inline script written to an executable temp file, removed again on drop.
+#[derive(Debug)]
+pub struct ScriptFile {
+    resource: TempResource,
+}
+
+impl ScriptFile {
+    /// Writes `body` with a shebang for `shell` unless it already starts with one, a live temp
+    /// resource of `session`.
+    pub fn create(session: &Session, body: &str, shell: Option<&str>) -> io::Result<Self> {
+        let body = body.trim_start();
+        let content = if body.starts_with("#!") {
+            body.to_string()
+        } else {
+            format!(
+                "#!/usr/bin/env {}\n{}",
+                shell.unwrap_or(DEFAULT_SHELL),
+                body
+            )
+        };
+
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o700);
+        }
+
+        let path = temp_path(session, "script");
+        let mut file = options.open(&path)?;
+        let resource = TempResource::new(session, path);
+        file.write_all(content.as_bytes())?;
+
+        Ok(Self { resource })
+    }
+
+    pub fn path(&self) -> &Path {
+        self.resource.path()
+    }
+}
+
+/// Where downloaded scripts are kept by their SHA-256, shared by every project.
+pub fn scripts_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("cargo_runner/scripts"))
+}
+
+/// The body of `script`, kept in `dir` from an earlier download or downloaded with curl. It is
+/// checked against the pinned SHA-256 either way and never returned when it doesn't match.
+pub fn fetch(script: &ScriptUrl, dir: &Path) -> Result<String, ExecutionError> {
+    let expected = script.sha256.to_ascii_lowercase();
+    let kept = dir.join(&expected);
+    if let Ok(body) = fs::read(&kept) {
+        if sha256_hex(&body) == expected {
+            return String::from_utf8(body).map_err(|_| download_error(script, "not UTF-8 text"));
+        }
+        // Changed since it was kept, downloaded again below
+        let _ = fs::remove_file(&kept);
+    }
+
+    fs::create_dir_all(dir).map_err(|err| download_error(script, &err.to_string()))?;
+    let partial = dir.join(format!("{}.{}.part", expected, std::process::id()));
+    let output = Command::new("curl")
+        .args([
+            "--fail",
+            "--silent",
+            "--show-error",
+            "--location",
+            "--proto",
+            "=https",
+        ])
+        .arg("--output")
+        .arg(&partial)
+        .arg(&script.url)
+        .output()
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => ExecutionError::ToolMissing(
+                "curl".into(),
+                "your system's package manager, e.g. apt install curl".into(),
+            ),
+            _ => download_error(script, &err.to_string()),
+        })?;
+    if !output.status.success() {
+        let _ = fs::remove_file(&partial);
+        let reason = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(download_error(script, &reason));
+    }
+
+    let body = fs::read(&partial).map_err(|err| download_error(script, &err.to_string()))?;
+    let actual = sha256_hex(&body);
+    if actual != expected {
+        let _ = fs::remove_file(&partial);
+        return Err(download_error(
+            script,
+            &format!("its SHA-256 is {}, the config pins {}", actual, expected),
+        ));
+    }
+    let body = String::from_utf8(body).map_err(|_| download_error(script, "not UTF-8 text"))?;
+    fs::rename(&partial, &kept).map_err(|err| download_error(script, &err.to_string()))?;
+    Ok(body)
+}
+
+fn download_error(script: &ScriptUrl, reason: &str) -> ExecutionError {
+    ExecutionError::ScriptDownload(script.url.clone(), reason.to_string())
+}
@@ -0,0 +1,234 @@
+use std::{
+    io::{self, IsTerminal},
+    pin::pin,
+    process::{Command, ExitStatus},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use tokio::{process::Child, sync::Notify};
+
+pub const DEFAULT_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// Shared flag flipped when rx is asked to stop (Ctrl-C, SIGTERM or SIGHUP).
+#[derive(Debug, Clone, Default)]
+pub struct ShutdownSignal(Arc<Shutdown>);
+
+#[derive(Debug, Default)]
+struct Shutdown {
+    triggered: AtomicBool,
+    /// Wakes the tasks waiting in [`ShutdownSignal::triggered`]
+    notify: Notify,
+}
+
+impl ShutdownSignal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the process-wide handler; can only be called once per process.
+    pub fn install_handler(&self) -> Result<(), ctrlc::Error> {
+        let signal = self.clone();
+        ctrlc::set_handler(move || signal.trigger())
+    }
+
+    pub fn trigger(&self) {
+        self.0.triggered.store(true, Ordering::SeqCst);
+        self.0.notify.notify_waiters();
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        self.0.triggered.load(Ordering::SeqCst)
+    }
+
+    /// Completes once the signal is triggered, right away when it already is.
+    pub async fn triggered(&self) {
+        loop {
+            let mut notified = pin!(self.0.notify.notified());
+            // Registered before checking, so a trigger in between isn't missed
+            notified.as_mut().enable();
+            if self.is_triggered() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Sleeps for `duration`, waking up early when the signal is triggered. Whether it was.
+    pub async fn sleep(&self, duration: Duration) -> bool {
+        tokio::select! {
+            () = tokio::time::sleep(duration) => false,
+            () = self.triggered() => true,
+        }
+    }
+
+    /// Clears the signal for another run, such as the restart of a run controlled from the
+    /// keyboard.
+    pub fn reset(&self) {
+        self.0.triggered.store(false, Ordering::SeqCst);
+    }
+}
+
+/// How a supervised child finished.
+#[derive(Debug)]
+pub enum ChildOutcome {
+    Exited(ExitStatus),
+    Interrupted(ExitStatus),
+}
+
+/// Puts the child in its own process group so the whole tree can be signalled at once.
+pub fn isolate_process_group(command: &mut Command) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    #[cfg(not(unix))]
+    let _ = command;
+}
+
+/// The foreground of rx's terminal, handed to the process group of a child reading rx's stdin
+/// so it isn't stopped with SIGTTIN for it, and given back to rx when dropped.
+#[derive(Debug)]
+pub struct Foreground {
+    #[cfg(unix)]
+    previous: libc::pid_t,
+}
+
+impl Foreground {
+    /// Makes `command`, put in its own process group, take the foreground when it starts. `None`
+    /// when stdin isn't a terminal or rx isn't in its foreground, as in the background of a shell.
+    #[cfg(unix)]
+    pub fn prepare(command: &mut Command) -> Option<Self> {
+        use std::os::unix::process::CommandExt;
+
+        if !io::stdin().is_terminal() {
+            return None;
+        }
+        let previous = unsafe { libc::tcgetpgrp(libc::STDIN_FILENO) };
+        if previous != unsafe { libc::getpgrp() } {
+            return None;
+        }
+        // Only async-signal-safe calls between fork and exec
+        unsafe {
+            command.pre_exec(|| {
+                set_foreground(libc::getpgrp());
+                Ok(())
+            });
+        }
+        Some(Self { previous })
+    }
+
+    #[cfg(not(unix))]
+    pub fn prepare(command: &mut Command) -> Option<Self> {
+        let _ = command;
+        None
+    }
+
+    /// Hands the foreground to the group of the child spawned as `pid` from rx's side too, which
+    /// may get there before the child does.
+    pub fn hand_to(&self, pid: Option<u32>) {
+        #[cfg(unix)]
+        if let Some(pid) = pid {
+            set_foreground(pid as libc::pid_t);
+        }
+        #[cfg(not(unix))]
+        let _ = pid;
+    }
+
+    /// Whether `status` is of a child stopped by a Ctrl-C, which the terminal sends to the group
+    /// in its foreground and so to the child holding it rather than to rx.
+    pub fn interrupted(status: &ExitStatus) -> bool {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            status.signal() == Some(libc::SIGINT)
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = status;
+            false
+        }
+    }
+}
+
+impl Drop for Foreground {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        set_foreground(self.previous);
+    }
+}
+
+/// Makes `group` the foreground process group of the terminal on stdin. SIGTTOU, which stops a
+/// process doing so from the background, is ignored meanwhile.
+#[cfg(unix)]
+fn set_foreground(group: libc::pid_t) {
+    unsafe {
+        let handler = libc::signal(libc::SIGTTOU, libc::SIG_IGN);
+        libc::tcsetpgrp(libc::STDIN_FILENO, group);
+        libc::signal(libc::SIGTTOU, handler);
+    }
+}
+
+/// Waits for `child`, forwarding an interrupt when `shutdown` fires and killing the
+/// process group once `grace_period` has elapsed.
+pub async fn supervise(
+    child: &mut Child,
+    shutdown: &ShutdownSignal,
+    grace_period: Duration,
+) -> io::Result<ChildOutcome> {
+    // Kept for the process group, the child has no id once it's waited for
+    let pid = child.id();
+    tokio::select! {
+        status = child.wait() => return status.map(ChildOutcome::Exited),
+        () = shutdown.triggered() => {}
+    }
+
+    interrupt(child, pid);
+    match tokio::time::timeout(grace_period, child.wait()).await {
+        Ok(status) => {
+            // The leader is gone, make sure nothing it spawned outlives it
+            kill(child, pid);
+            status.map(ChildOutcome::Interrupted)
+        }
+        Err(_) => {
+            kill(child, pid);
+            child.wait().await.map(ChildOutcome::Interrupted)
+        }
+    }
+}
+
+#[cfg(unix)]
+fn interrupt(_child: &mut Child, pid: Option<u32>) {
+    signal_group(pid, libc::SIGINT);
+}
+
+#[cfg(not(unix))]
+fn interrupt(child: &mut Child, pid: Option<u32>) {
+    // No portable SIGINT equivalent, the grace period still lets the child exit on its own
+    let _ = (child, pid);
+}
+
+#[cfg(unix)]
+fn kill(_child: &mut Child, pid: Option<u32>) {
+    signal_group(pid, libc::SIGKILL);
+}
+
+#[cfg(not(unix))]
+fn kill(child: &mut Child, _pid: Option<u32>) {
+    let _ = child.start_kill();
+}
+
+#[cfg(unix)]
+fn signal_group(pid: Option<u32>, signal: libc::c_int) {
+    let Some(pid) = pid else {
+        return;
+    };
+    // The child was spawned as a group leader, so its pid is also the group id
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), signal);
+    }
+}
@@ -0,0 +1,108 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::atomic::Ordering,
+};
+
+use tracing::debug;
+
+use crate::{service::is_running, session::Session};
+
+/// What starts the name of everything rx keeps in the temp dir while it runs.
+const PREFIX: &str = "rx-";
+
+/// The kinds of temp resources rx makes: inline scripts, sandboxes and the logs of tmux panes.
+pub const KINDS: [&str; 3] = ["script", "sandbox", "panes"];
+
+/// A fresh path in the temp dir for a resource of `kind`, one of [`KINDS`]:
+/// `rx-{kind}-{pid}-{n}`, so the rx it belongs to can be told from its name, see [`leftovers`].
+pub fn temp_path(session: &Session, kind: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "{}{}-{}-{}",
+        PREFIX,
+        kind,
+        std::process::id(),
+        session.temp_paths().fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// A file or directory of a run, tracked until it's dropped and removed, whether the run
+/// succeeded, failed, timed out or was interrupted.
+#[derive(Debug)]
+pub struct TempResource {
+    path: PathBuf,
+    session: Session,
+}
+
+impl TempResource {
+    /// Tracks `path`, which the caller creates, among the live resources of `session`.
+    pub fn new(session: &Session, path: PathBuf) -> Self {
+        session.live().lock().unwrap().push(path.clone());
+        Self {
+            path,
+            session: session.clone(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempResource {
+    fn drop(&mut self) {
+        remove(&self.path);
+        let mut live = self.session.live().lock().unwrap();
+        live.retain(|path| *path != self.path);
+    }
+}
+
+/// Removes the temp resources of `session` whose guards are still alive, such as those of a
+/// thread still running, for an exit that skips their drop.
+pub fn remove_live(session: &Session) {
+    for path in session.live().lock().unwrap().drain(..) {
+        remove(&path);
+    }
+}
+
+fn remove(path: &Path) {
+    let removed = match path.is_dir() {
+        true => fs::remove_dir_all(path),
+        false => fs::remove_file(path),
+    };
+    match removed {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => {
+            debug!(path = %path.display(), %err, "cannot remove a temp resource")
+        }
+        _ => {}
+    }
+}
+
+/// The pid of the rx a name in the temp dir belongs to, from `rx-{kind}-{pid}` and what may
+/// follow it.
+pub fn owner(name: &str) -> Option<u32> {
+    let (kind, rest) = name.strip_prefix(PREFIX)?.split_once('-')?;
+    if !KINDS.contains(&kind) {
+        return None;
+    }
+    let end = rest.find(['-', '.']).unwrap_or(rest.len());
+    rest[..end].parse().ok()
+}
+
+/// What rx processes that are gone left in `dir`, the temp dir, sorted.
+pub fn leftovers(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut leftovers: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name();
+            owner(&name.to_string_lossy())
+                .is_some_and(|pid| pid != std::process::id() && !is_running(pid))
+        })
+        .map(|entry| entry.path())
+        .collect();
+    leftovers.sort();
+    leftovers
+}
@@ -0,0 +1,216 @@
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    fmt::{self, Display},
+};
+
+use serde::Serialize;
+
+use crate::{
+    errors::ConfigError,
+    executor::runner::Runner,
+    interpolation::{find_placeholders, GIT_VARIABLES},
+    models::config::CommandDetails,
+};
+
+/// Placeholders rx resolves from the directory it runs in, listed even without a value
+const DIRECTORY_VARIABLES: [&str; 3] = ["workspaceFolder", "packageName", "binaryName"];
+
+/// Where the value of a placeholder listed by `rx vars` comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlaceholderSource {
+    /// The crate or workspace rx runs in
+    Directory,
+    /// The git checkout rx runs in
+    Git,
+    /// A `${input:name}` given or defaulted
+    Input,
+    /// A `${arg:name}` of the config, given or defaulted
+    Arg,
+    /// A `${matrix.name}` of the matrix cell being run
+    Matrix,
+    /// A variable of the config's `env` or `env_sets`, set for its command
+    Env,
+    /// Left to the shell, which expands it from the environment
+    Shell,
+}
+
+impl Display for PlaceholderSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            PlaceholderSource::Directory => "directory",
+            PlaceholderSource::Git => "git",
+            PlaceholderSource::Input => "input",
+            PlaceholderSource::Arg => "arg",
+            PlaceholderSource::Matrix => "matrix",
+            PlaceholderSource::Env => "env",
+            PlaceholderSource::Shell => "shell",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A placeholder and what it resolves to, `None` when it has no value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PlaceholderValue {
+    pub name: String,
+    pub value: Option<String>,
+    pub source: PlaceholderSource,
+    /// Whether the config refers to it
+    pub used: bool,
+}
+
+/// The placeholders `rx vars` lists.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Placeholders {
+    pub placeholders: Vec<PlaceholderValue>,
+}
+
+impl Placeholders {
+    /// The placeholders the config refers to that resolve to nothing.
+    pub fn unresolved(&self) -> Vec<&PlaceholderValue> {
+        self.placeholders
+            .iter()
+            .filter(|placeholder| placeholder.used && placeholder.value.is_none())
+            .collect()
+    }
+
+    /// The placeholders as pretty-printed JSON.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        Ok(serde_json::to_string_pretty(self)? + "\n")
+    }
+
+    /// A line per placeholder: its name, value and source, `✗` marking those the config
+    /// refers to without a value and `*` those it refers to.
+    pub fn report(&self) -> String {
+        let width = self
+            .placeholders
+            .iter()
+            .map(|placeholder| placeholder.name.chars().count() + 3)
+            .max()
+            .unwrap_or_default();
+        self.placeholders
+            .iter()
+            .map(|placeholder| {
+                let mark = match (placeholder.used, &placeholder.value) {
+                    (true, None) => "✗",
+                    (true, Some(_)) => "*",
+                    (false, _) => " ",
+                };
+                let name = format!("${{{}}}", placeholder.name);
+                let value = placeholder.value.as_deref().unwrap_or("(no value)");
+                format!(
+                    "{} {:<width$}  {}  [{}]",
+                    mark, name, value, placeholder.source
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Runner<'_> {
+    /// Every placeholder known in the current directory with its value, those of the config
+    /// `key` included when given: its args, its env and the placeholders its command, params,
+    /// working directory, env and files refer to. The values of sealed env variables are
+    /// left out.
+    pub fn placeholders(&self, key: Option<&str>) -> Result<Placeholders, ConfigError> {
+        let details = key
+            .map(|key| Ok::<_, ConfigError>(self.target_details(key, self.details(key)?, &[])))
+            .transpose()?;
+        let interpolation = match &details {
+            Some(details) => self.interpolation_for(details),
+            None => Cow::Borrowed(&self.interpolation),
+        };
+
+        let mut placeholders: BTreeMap<String, PlaceholderValue> = BTreeMap::new();
+        for name in DIRECTORY_VARIABLES.iter().chain(GIT_VARIABLES.iter()) {
+            let source = match GIT_VARIABLES.contains(name) {
+                true => PlaceholderSource::Git,
+                false => PlaceholderSource::Directory,
+            };
+            placeholders.insert(name.to_string(), placeholder(name, None, source));
+        }
+        for (name, value) in interpolation.variables() {
+            let source = prefixed_source(name).unwrap_or(match GIT_VARIABLES.contains(&name) {
+                true => PlaceholderSource::Git,
+                false => PlaceholderSource::Directory,
+            });
+            placeholders.insert(name.to_string(), placeholder(name, Some(value), source));
+        }
+
+        if let (Some(key), Some(details)) = (key, &details) {
+            let env = self.config.env_set_vars(details)?.into_iter().chain(
+                details
+                    .env
+                    .iter()
+                    .map(|(name, value)| (name.clone(), value.clone())),
+            );
+            for (name, value) in env {
+                let sealed =
+                    details.env_is_sealed(&name) || self.config.env_set_is_sealed(details, &name);
+                let value = match sealed {
+                    true => "(sealed)".to_string(),
+                    false => interpolation.interpolate(&value),
+                };
+                placeholders.insert(
+                    name.clone(),
+                    placeholder(&name, Some(&value), PlaceholderSource::Env),
+                );
+            }
+            for name in referred(key, details) {
+                let entry = placeholders.entry(name.clone()).or_insert_with(|| {
+                    let value = interpolation
+                        .value(&name)
+                        .or_else(|| std::env::var(&name).ok());
+                    let source = prefixed_source(&name).unwrap_or(PlaceholderSource::Shell);
+                    placeholder(&name, value.as_deref(), source)
+                });
+                entry.used = true;
+            }
+        }
+
+        Ok(Placeholders {
+            placeholders: placeholders.into_values().collect(),
+        })
+    }
+}
+
+/// The source of the placeholders told apart by their prefix, `${input:name}`, `${arg:name}`
+/// and `${matrix.name}`.
+fn prefixed_source(name: &str) -> Option<PlaceholderSource> {
+    match name {
+        _ if name.starts_with("input:") => Some(PlaceholderSource::Input),
+        _ if name.starts_with("arg:") => Some(PlaceholderSource::Arg),
+        _ if name.starts_with("matrix.") => Some(PlaceholderSource::Matrix),
+        _ => None,
+    }
+}
+
+fn placeholder(name: &str, value: Option<&str>, source: PlaceholderSource) -> PlaceholderValue {
+    PlaceholderValue {
+        name: name.to_string(),
+        value: value.map(String::from),
+        source,
+        used: false,
+    }
+}
+
+/// The names of the placeholders the config `key` refers to, `$${name}` escapes and the
+/// `${key}` of its `target_dir` and `log_file` aside.
+fn referred(key: &str, details: &CommandDetails) -> Vec<String> {
+    let command = details.command.line().to_string();
+    let params = details.params.to_string();
+    let target_dir = details.target_dir(key);
+    let log_file = details.log_file(key);
+    let texts = [&command, &params, &details.working_directory]
+        .into_iter()
+        .chain(details.env.values())
+        .chain(&details.env_file)
+        .chain(&details.stdin)
+        .chain(&details.stdin_file)
+        .chain(&log_file)
+        .chain(&target_dir);
+    texts.flat_map(|text| find_placeholders(text)).collect()
+}
@@ -0,0 +1,110 @@
+use std::process::{Command, Stdio};
+
+use crate::{helpers::target_arg, models::config::WasmRunner, session::Session};
+
+/// The runner of a cargo command building for a `wasm32-*` target, `configured` or else the
+/// default for the target and subcommand. `None` for other targets and for commands cargo
+/// runs as is, such as `cargo build --target wasm32-unknown-unknown`.
+pub fn runner(argv: &[String], configured: Option<WasmRunner>) -> Option<WasmRunner> {
+    let target = target_arg(argv.iter().map(String::as_str))?;
+    if !target.starts_with("wasm32-") {
+        return None;
+    }
+    if configured.is_some() {
+        return configured;
+    }
+
+    match argv.get(1).map(String::as_str) {
+        _ if target.starts_with("wasm32-wasi") => Some(WasmRunner::Wasmtime),
+        Some("test") => Some(WasmRunner::WasmPack),
+        Some("run") => Some(WasmRunner::Trunk),
+        _ => None,
+    }
+}
+
+/// The `argv` of a cargo command for a wasm32 target with `runner`, `None` when it doesn't
+/// apply to the subcommand: wasmtime set as cargo's runner of the target for `run`, `test`
+/// and `bench`, `cargo test` as
+/// `wasm-pack test` with cargo's arguments after `--`, `cargo run` as `trunk serve`.
+pub fn translate(argv: &[String], runner: WasmRunner) -> Option<Vec<String>> {
+    let target = target_arg(argv.iter().map(String::as_str))?;
+    let subcommand = argv.get(1)?;
+    match runner {
+        WasmRunner::Wasmtime if matches!(subcommand.as_str(), "run" | "test" | "bench") => {
+            let mut translated = argv.to_vec();
+            translated.splice(
+                1..1,
+                [
+                    "--config".to_string(),
+                    format!("target.{}.runner=\"wasmtime\"", target),
+                ],
+            );
+            Some(translated)
+        }
+        WasmRunner::WasmPack if subcommand == "test" => {
+            let rest = without_target(&argv[2..]);
+            let mut translated: Vec<String> = ["wasm-pack", "test", "--headless", "--chrome"]
+                .into_iter()
+                .map(String::from)
+                .collect();
+            if !rest.is_empty() {
+                translated.push("--".to_string());
+                translated.extend(rest);
+            }
+            Some(translated)
+        }
+        // trunk picks the target and the binary from its Trunk.toml and index.html
+        WasmRunner::Trunk if subcommand == "run" => {
+            let mut translated = vec!["trunk".to_string(), "serve".to_string()];
+            if argv.iter().any(|arg| arg == "--release") {
+                translated.push("--release".to_string());
+            }
+            Some(translated)
+        }
+        _ => None,
+    }
+}
+
+/// `args` without their `--target`, wasm-pack adds its own.
+fn without_target(args: &[String]) -> Vec<String> {
+    let mut kept = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--target" {
+            args.next();
+        } else if !arg.starts_with("--target=") {
+            kept.push(arg.clone());
+        }
+    }
+    kept
+}
+
+/// Whether the tool of `runner` can be run, checked once per session.
+pub fn is_installed(session: &Session, runner: WasmRunner) -> bool {
+    let tool = match runner {
+        WasmRunner::Wasmtime => "wasmtime",
+        WasmRunner::WasmPack => "wasm-pack",
+        WasmRunner::Trunk => "trunk",
+    };
+    session.installed(tool, || {
+        Command::new(tool)
+            .arg("--version")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    })
+}
+
+/// The tool of `runner` and how to install it.
+pub fn install_hint(runner: WasmRunner) -> (&'static str, &'static str) {
+    match runner {
+        WasmRunner::Wasmtime => (
+            "wasmtime",
+            "curl https://wasmtime.dev/install.sh -sSf | bash",
+        ),
+        WasmRunner::WasmPack => ("wasm-pack", "cargo install wasm-pack"),
+        WasmRunner::Trunk => ("trunk", "cargo install trunk"),
+    }
+}
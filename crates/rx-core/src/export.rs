@@ -0,0 +1,708 @@
+use std::{collections::BTreeMap, fmt::Write, path::Path, str::FromStr};
+
+use toml::{Table, Value};
+
+use crate::{
+    errors::ConfigError,
+    executor::{composite::parse_step, plugin::plugin, script},
+    helpers::shell_quote,
+    interpolation::InterpolationContext,
+    models::config::{CommandContext, CommandDetails, CommandType, Condition, Config, CrossRunner},
+};
+
+/// Tools `rx export` writes the config's commands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// A cargo-make `Makefile.toml`
+    CargoMake,
+    /// The `src/main.rs` of an xtask crate
+    Xtask,
+    /// A GitHub Actions workflow running the configs with rx itself
+    GithubActions,
+}
+
+impl FromStr for ExportFormat {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cargo-make" | "make" => Ok(ExportFormat::CargoMake),
+            "xtask" => Ok(ExportFormat::Xtask),
+            "gha" | "github-actions" => Ok(ExportFormat::GithubActions),
+            _ => Err(ConfigError::UnknownExportFormat(s.to_string())),
+        }
+    }
+}
+
+/// An exported file and the parts of the config it couldn't express.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Export {
+    pub content: String,
+    pub notes: Vec<String>,
+}
+
+/// Name of the exported task of `context::key`, e.g. `build-release`.
+pub fn task_name(context: CommandContext, key: &str) -> String {
+    format!("{}-{}", context, key)
+}
+
+impl ExportFormat {
+    /// Exports every config. Placeholders are resolved through `interpolation`, except
+    /// `${workspaceFolder}` which stays relative to wherever the tool runs, and for cargo-make
+    /// `${gitBranch}` and `${gitShortSha}`, which it resolves itself when the task runs. A
+    /// GitHub Actions workflow runs the default configs, see [`github_actions`].
+    pub fn export(
+        &self,
+        config: &Config,
+        interpolation: &InterpolationContext,
+    ) -> Result<Export, ConfigError> {
+        if *self == ExportFormat::GithubActions {
+            return github_actions(config, &WorkflowOptions::default());
+        }
+        let mut interpolation = interpolation.clone();
+        interpolation.set(
+            "workspaceFolder",
+            match self {
+                ExportFormat::CargoMake => "${CARGO_MAKE_WORKSPACE_WORKING_DIRECTORY}",
+                ExportFormat::Xtask | ExportFormat::GithubActions => ".",
+            },
+        );
+        if *self == ExportFormat::CargoMake {
+            interpolation.set("gitBranch", "${CARGO_MAKE_GIT_BRANCH}");
+            interpolation.set(
+                "gitShortSha",
+                "${CARGO_MAKE_GIT_HEAD_LAST_COMMIT_HASH_PREFIX}",
+            );
+        }
+
+        let mut tasks = Vec::new();
+        for context in CommandContext::ALL {
+            let Some(command_config) = config.commands.get_config(context) else {
+                continue;
+            };
+            for key in command_config.configs.keys() {
+                let task = Task::new(
+                    context,
+                    key,
+                    &command_config.configs[key],
+                    config,
+                    &interpolation,
+                )?;
+                tasks.push(task);
+            }
+        }
+
+        let mut export = match self {
+            ExportFormat::CargoMake => cargo_make(config, &tasks),
+            ExportFormat::Xtask => xtask(&tasks),
+            ExportFormat::GithubActions => unreachable!("workflows run rx instead of tasks"),
+        };
+        for task in &tasks {
+            export.notes.extend(
+                task.unsupported(*self)
+                    .into_iter()
+                    .map(|note| format!("{}::{}: {}", task.context, task.key, note)),
+            );
+        }
+        Ok(export)
+    }
+}
+
+/// A config with its placeholders resolved and its references turned into task names.
+struct Task<'a> {
+    context: CommandContext,
+    key: &'a str,
+    details: &'a CommandDetails,
+    name: String,
+    run: Run,
+    dependencies: Vec<String>,
+    cwd: String,
+    /// The variables of its `env_sets` and `env`, by name
+    env: BTreeMap<String, String>,
+}
+
+enum Run {
+    Cargo(Vec<String>),
+    Shell(String),
+    Steps(Vec<String>),
+}
+
+impl<'a> Task<'a> {
+    fn new(
+        context: CommandContext,
+        key: &'a str,
+        details: &'a CommandDetails,
+        config: &Config,
+        interpolation: &InterpolationContext,
+    ) -> Result<Self, ConfigError> {
+        let interpolate = |value: &str| interpolation.interpolate(value);
+
+        let run = match details.command_type {
+            CommandType::Cargo => Run::Cargo(
+                interpolate(details.command.line())
+                    .split_whitespace()
+                    .map(str::to_string)
+                    .chain(details.params.args(interpolate))
+                    .collect(),
+            ),
+            // rx downloads it and checks its SHA-256
+            CommandType::Shell if details.command.script_url().is_some() => {
+                Run::Shell(format!("rx {} {}", context, shell_quote(key)))
+            }
+            // Only rx knows the executor of the type
+            CommandType::Custom(_) => Run::Shell(format!("rx {} {}", context, shell_quote(key))),
+            CommandType::Shell => Run::Shell(
+                [
+                    interpolate(details.command.line()),
+                    details.params.shell_line(interpolate),
+                ]
+                .into_iter()
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+                .join(" "),
+            ),
+            CommandType::Composite => Run::Steps(
+                details
+                    .steps
+                    .iter()
+                    .map(|step| {
+                        parse_step(step, context).map(|(context, key)| task_name(context, key))
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+        };
+
+        Ok(Task {
+            context,
+            key,
+            details,
+            name: task_name(context, key),
+            run,
+            dependencies: details
+                .depends_on
+                .iter()
+                .filter(|_| !details.service)
+                .map(|dependency| {
+                    parse_step(dependency, context).map(|(context, key)| task_name(context, key))
+                })
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter()
+                .chain(
+                    details
+                        .pre_command
+                        .iter()
+                        .map(|pre_command| task_name(context, pre_command)),
+                )
+                .collect(),
+            cwd: interpolate(&details.working_directory),
+            env: config
+                .env_set_vars(details)?
+                .into_iter()
+                .chain(details.env.clone())
+                .collect(),
+        })
+    }
+
+    /// What the format can't express of this task.
+    fn unsupported(&self, format: ExportFormat) -> Vec<&'static str> {
+        let details = self.details;
+        let mut notes = Vec::new();
+
+        if details.command.script_url().is_some() {
+            notes.push("the script from a URL runs through rx, which has to be installed");
+        }
+        if let CommandType::Custom(_) = details.command_type {
+            notes.push("the custom command type runs through rx, which has to be installed");
+        }
+        if details.remote.is_some() {
+            notes.push("remote is not exported, the task runs locally");
+        }
+        if details.container.is_some() {
+            notes.push("container is not exported, the task runs on the host");
+        }
+        if !details.target.is_empty() {
+            notes.push("target overrides are not exported");
+        }
+        if details.success_exit_codes.is_some() {
+            notes.push("success_exit_codes are not exported, only 0 succeeds");
+        }
+        if !details.matrix.is_empty() {
+            notes.push("matrix is not exported, the task runs once");
+        }
+        if !details.on_failure.is_empty() {
+            notes.push("on_failure is not exported, a failing step stops the task");
+        }
+        if details.skip_if.is_some() {
+            notes.push("skip_if is not exported");
+        }
+        if details.stdin.is_some() || details.stdin_file.is_some() {
+            notes.push("stdin is not exported, the task reads the terminal");
+        }
+        if details.output_filter.is_some() {
+            notes.push("output_filter is not exported, the output is shown as is");
+        }
+        if !details.limits.is_empty() {
+            notes.push("limits are not exported, the task runs unlimited");
+        }
+        if details.lock.is_some() {
+            notes.push("lock is not exported, the task may run alongside others sharing it");
+        }
+        if details.elevate || details.run_as.is_some() {
+            notes.push("elevate and run_as are not exported, the task runs as the current user");
+        }
+        if !details.inherits_env() {
+            notes.push("inherit_env is not exported, the task inherits the whole environment");
+        }
+        if details.offline == Some(true) {
+            notes.push("offline is not exported, cargo may use the network");
+        }
+        if details.direnv {
+            notes.push("direnv is not exported, the task doesn't load the .envrc");
+        }
+        if details.login_shell || !details.source.is_empty() {
+            notes.push("login_shell and source are not exported, the task runs without shell init");
+        }
+        if details
+            .cross_runner
+            .is_some_and(|cross_runner| cross_runner != CrossRunner::Native)
+        {
+            notes.push("cross_runner is not exported, cargo builds for every target");
+        }
+        if details.sandbox.is_some() || details.umask.is_some() {
+            notes.push("sandbox and umask are not exported, the task runs in the working tree");
+        }
+        if details.wasm_runner.is_some() {
+            notes.push("wasm_runner is not exported, cargo runs wasm32 targets as configured");
+        }
+        if details.target_dir.is_some() {
+            notes
+                .push("target_dir is not exported, cargo builds into its default target directory");
+        }
+
+        if format == ExportFormat::CargoMake
+            && matches!(&details.only_if, Some(Condition::Builtin(condition)) if condition.branch.is_some())
+        {
+            notes.push("only_if.branch is not exported");
+        }
+        if format == ExportFormat::Xtask {
+            if details.only_if.is_some() {
+                notes.push("only_if is not exported");
+            }
+            if details.env_file.is_some() {
+                notes.push("env_file is not exported");
+            }
+            if details.parallel {
+                notes.push("steps run one after another");
+            }
+            if details.shell.is_some() || details.command.line().trim_start().starts_with("#!") {
+                notes.push("the script runs with sh");
+            }
+        }
+        notes
+    }
+}
+
+fn cargo_make(config: &Config, tasks: &[Task]) -> Export {
+    let mut table = Table::new();
+
+    for task in tasks {
+        let details = task.details;
+        let mut entry = Table::new();
+
+        match &task.run {
+            Run::Cargo(argv) => {
+                entry.insert("command".into(), "cargo".into());
+                let args = argv.iter().cloned().chain(["${@}".to_string()]);
+                entry.insert("args".into(), args.collect::<Vec<_>>().into());
+            }
+            Run::Shell(script) => {
+                entry.insert("script".into(), script.clone().into());
+            }
+            Run::Steps(steps) if details.parallel => {
+                let run_task = Table::from_iter([
+                    ("name".to_string(), Value::from(steps.clone())),
+                    ("parallel".to_string(), Value::from(true)),
+                ]);
+                entry.insert("run_task".into(), run_task.into());
+            }
+            Run::Steps(_) => {}
+        }
+
+        let mut dependencies = task.dependencies.clone();
+        if let (Run::Steps(steps), false) = (&task.run, details.parallel) {
+            dependencies.extend(steps.iter().cloned());
+        }
+        if !dependencies.is_empty() {
+            entry.insert("dependencies".into(), dependencies.into());
+        }
+        if !task.env.is_empty() {
+            let env: Table = task
+                .env
+                .iter()
+                .map(|(name, value)| (name.clone(), Value::from(value.clone())))
+                .collect();
+            entry.insert("env".into(), env.into());
+        }
+        if let Some(env_file) = &details.env_file {
+            entry.insert("env_files".into(), vec![env_file.clone()].into());
+        }
+        if !task.cwd.is_empty() {
+            entry.insert("cwd".into(), task.cwd.clone().into());
+        }
+        match &details.only_if {
+            Some(Condition::Shell(expression)) => {
+                entry.insert("condition_script".into(), vec![expression.clone()].into());
+            }
+            Some(Condition::Builtin(condition)) => {
+                let mut cargo_make_condition = Table::new();
+                if let Some(os) = &condition.os {
+                    let platform = if os == "macos" { "mac" } else { os };
+                    cargo_make_condition.insert("platforms".into(), vec![platform].into());
+                }
+                if let Some(name) = &condition.env_set {
+                    cargo_make_condition.insert("env_set".into(), vec![name.clone()].into());
+                }
+                if let Some(path) = &condition.file_exists {
+                    cargo_make_condition.insert("files_exist".into(), vec![path.clone()].into());
+                }
+                entry.insert("condition".into(), cargo_make_condition.into());
+            }
+            None => {}
+        }
+
+        table.insert(task.name.clone(), entry.into());
+    }
+
+    // `cargo make <context>` runs the context's default
+    for context in CommandContext::ALL {
+        let Some(command_config) = config.commands.get_config(context) else {
+            continue;
+        };
+        let Some(default) = command_config.default.resolve(Path::new("")) else {
+            continue;
+        };
+        if command_config.configs.contains_key(default) {
+            let alias = Table::from_iter([(
+                "alias".to_string(),
+                Value::from(task_name(context, default)),
+            )]);
+            table.insert(context.to_string(), alias.into());
+        }
+    }
+
+    let document = Table::from_iter([("tasks".to_string(), Value::from(table))]);
+    Export {
+        content: format!(
+            "# Generated by `rx export cargo-make`\n\n{}",
+            toml::to_string_pretty(&document).unwrap_or_default()
+        ),
+        notes: Vec::new(),
+    }
+}
+
+const XTASK_HELPERS: &str = r#"
+fn cargo(argv: &[&str], args: &[String], env: &[(&str, &str)], dir: &Path) -> Result<(), String> {
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let mut command = Command::new(cargo);
+    command.args(argv).args(args);
+    spawn(command, env, dir)
+}
+
+fn shell(script: &str, args: &[String], env: &[(&str, &str)], dir: &Path) -> Result<(), String> {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(script).arg("xtask").args(args);
+    spawn(command, env, dir)
+}
+
+fn spawn(mut command: Command, env: &[(&str, &str)], dir: &Path) -> Result<(), String> {
+    let status = command
+        .envs(env.iter().copied())
+        .current_dir(dir)
+        .status()
+        .map_err(|err| format!("{:?}: {}", command, err))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{:?} failed with {}", command, status))
+    }
+}
+"#;
+
+fn xtask(tasks: &[Task]) -> Export {
+    let mut code = String::new();
+    let names: Vec<&str> = tasks.iter().map(|task| task.name.as_str()).collect();
+
+    let _ = write!(
+        code,
+        r#"//! Generated by `rx export xtask`, run a task with `cargo xtask <task> [args...]`.
+
+use std::{{
+    env,
+    path::Path,
+    process::{{exit, Command}},
+}};
+
+const TASKS: &[&str] = &{:?};
+
+fn main() {{
+    let mut args = env::args().skip(1);
+    let task = args.next().unwrap_or_default();
+    let args: Vec<String> = args.collect();
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("xtask lives in the workspace")
+        .to_path_buf();
+
+    if let Err(err) = run(&task, &args, &root) {{
+        eprintln!("xtask: {{}}", err);
+        exit(1);
+    }}
+}}
+
+fn run(task: &str, args: &[String], root: &Path) -> Result<(), String> {{
+    match task {{
+"#,
+        names
+    );
+
+    for task in tasks {
+        let env = task
+            .env
+            .iter()
+            .map(|(name, value)| format!("({:?}, {:?})", name, value))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let dir = match task.cwd.trim_start_matches("./") {
+            "" | "." => "root".to_string(),
+            cwd => format!("&root.join({:?})", cwd),
+        };
+
+        let _ = writeln!(code, "        {:?} => {{", task.name);
+        for dependency in &task.dependencies {
+            let _ = writeln!(code, "            run({:?}, &[], root)?;", dependency);
+        }
+        let _ = match &task.run {
+            Run::Cargo(argv) => writeln!(
+                code,
+                "            cargo(&{:?}, args, &[{}], {})",
+                argv, env, dir
+            ),
+            Run::Shell(script) => {
+                // rx appends the arguments to a single command line
+                let script = if script::is_inline(script) {
+                    script.clone()
+                } else {
+                    format!("{} \"$@\"", script)
+                };
+                writeln!(
+                    code,
+                    "            shell({:?}, args, &[{}], {})",
+                    script, env, dir
+                )
+            }
+            Run::Steps(steps) => {
+                for step in steps {
+                    let _ = writeln!(code, "            run({:?}, &[], root)?;", step);
+                }
+                writeln!(code, "            Ok(())")
+            }
+        };
+        let _ = writeln!(code, "        }}");
+    }
+
+    let _ = writeln!(
+        code,
+        "        _ => Err(format!(\"unknown task '{{}}', expected one of: {{}}\", task, TASKS.join(\", \"))),\n    }}\n}}"
+    );
+    code.push_str(XTASK_HELPERS);
+
+    Export {
+        content: code,
+        notes: Vec::new(),
+    }
+}
+
+/// Contexts whose `default` a workflow runs when no configs are selected, the quick ones first.
+const WORKFLOW_CONTEXTS: [CommandContext; 4] = [
+    CommandContext::Fmt,
+    CommandContext::Lint,
+    CommandContext::Test,
+    CommandContext::Build,
+];
+
+/// What [`github_actions`] puts in the workflow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkflowOptions {
+    /// `context::key` of the configs to run, or a bare context for its `default`. The defaults
+    /// of fmt, lint, test and build when empty
+    pub configs: Vec<String>,
+    /// Path of the config file in the repository, which rx reads through `RX_CONFIG`
+    pub config_path: String,
+}
+
+impl Default for WorkflowOptions {
+    fn default() -> Self {
+        Self {
+            configs: Vec::new(),
+            config_path: "rx.toml".to_string(),
+        }
+    }
+}
+
+/// A GitHub Actions workflow with a job per selected config, installing a toolchain and rx,
+/// caching cargo's files and running `rx <context> <key>`, so CI runs the configs the way they
+/// run locally.
+pub fn github_actions(config: &Config, options: &WorkflowOptions) -> Result<Export, ConfigError> {
+    let mut selected = Vec::new();
+    if options.configs.is_empty() {
+        for context in WORKFLOW_CONTEXTS {
+            let Some(key) = config
+                .commands
+                .get_config(context)
+                .and_then(|command_config| command_config.default.resolve(Path::new("")))
+                .filter(|key| details_of(config, context, key).is_some())
+            else {
+                continue;
+            };
+            selected.push((context, key.to_string()));
+        }
+    } else {
+        for label in &options.configs {
+            let (context, key) = match label.split_once("::") {
+                Some((context, key)) => (context.parse()?, key.to_string()),
+                None => {
+                    let context: CommandContext = label.parse()?;
+                    let key = config
+                        .commands
+                        .get_config(context)
+                        .and_then(|command_config| command_config.default.resolve(Path::new("")))
+                        .ok_or_else(|| {
+                            ConfigError::NoDefaultKey(context.to_string(), ".".to_string())
+                        })?;
+                    (context, key.to_string())
+                }
+            };
+            if details_of(config, context, &key).is_none() {
+                return Err(ConfigError::ConfigKeyNotFound(format!(
+                    "{}::{}",
+                    context, key
+                )));
+            }
+            selected.push((context, key));
+        }
+    }
+
+    let mut workflow = format!(
+        r#"# Generated by `rx export gha`, runs the rx configs CI shares with local development.
+name: rx
+
+on:
+  push:
+    branches: [main]
+  pull_request:
+
+env:
+  CARGO_TERM_COLOR: always
+  RX_CONFIG: {}
+
+jobs:
+"#,
+        yaml_string(&options.config_path)
+    );
+    let mut notes = Vec::new();
+    for (index, (context, key)) in selected.iter().enumerate() {
+        if index > 0 {
+            workflow.push('\n');
+        }
+        let details = details_of(config, *context, key).expect("selected configs exist");
+        let components: Vec<&str> = [
+            (*context == CommandContext::Lint, "clippy"),
+            (*context == CommandContext::Fmt, "rustfmt"),
+            (*context == CommandContext::Coverage, "llvm-tools-preview"),
+        ]
+        .into_iter()
+        .filter_map(|(needed, component)| needed.then_some(component))
+        .collect();
+
+        let _ = writeln!(workflow, "  {}:", job_id(*context, key));
+        let _ = writeln!(
+            workflow,
+            "    name: {}",
+            yaml_string(&format!("{}::{}", context, key))
+        );
+        workflow.push_str("    runs-on: ubuntu-latest\n    steps:\n");
+        workflow.push_str("      - uses: actions/checkout@v4\n");
+        workflow.push_str("      - uses: dtolnay/rust-toolchain@stable\n");
+        if !components.is_empty() {
+            let _ = writeln!(
+                workflow,
+                "        with:\n          components: {}",
+                components.join(", ")
+            );
+        }
+        // The cache holds ~/.cargo/bin too, rx is only built on a cache miss
+        workflow.push_str("      - uses: Swatinem/rust-cache@v2\n");
+        workflow.push_str("      - name: Install rx\n");
+        workflow.push_str(
+            "        run: cargo install --locked --git https://github.com/codeitlikemiley/rx rx\n",
+        );
+        let _ = writeln!(workflow, "      - run: rx {} {}", context, shell_quote(key));
+
+        if details.command_type == CommandType::Cargo && !details.auto_install {
+            let argv: Vec<String> = std::iter::once("cargo".to_string())
+                .chain(
+                    details
+                        .command
+                        .line()
+                        .split_whitespace()
+                        .map(str::to_string),
+                )
+                .collect();
+            // clippy and rustfmt come with the toolchain's components
+            if let Some(subcommand) = plugin(&argv).filter(|sub| !["clippy", "fmt"].contains(sub)) {
+                notes.push(format!(
+                    "{}::{}: runs cargo {}, set auto_install = true or install it in the workflow",
+                    context, key, subcommand
+                ));
+            }
+        }
+    }
+    if selected.is_empty() {
+        notes.push("no configs selected, the workflow has no jobs".to_string());
+    }
+
+    Ok(Export {
+        content: workflow,
+        notes,
+    })
+}
+
+fn details_of<'a>(
+    config: &'a Config,
+    context: CommandContext,
+    key: &str,
+) -> Option<&'a CommandDetails> {
+    config
+        .commands
+        .get_config(context)
+        .and_then(|command_config| command_config.configs.get(key))
+}
+
+/// The id of the job running `context::key`, e.g. `test-unit`.
+fn job_id(context: CommandContext, key: &str) -> String {
+    task_name(context, key)
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// `value` as a double-quoted YAML string.
+fn yaml_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
@@ -0,0 +1,225 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::{self, Display},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use serde::Serialize;
+
+use crate::{
+    errors::ExecutionError,
+    executor::{
+        composite::{StepEvent, StepObserver, StepReport},
+        hooks::HookEntry,
+        output::{OutputObserver, OutputStream},
+        runner::Runner,
+    },
+    rerun::parse_label,
+};
+
+/// Lines of stderr the summary of a failed run shows unless `[settings]` says otherwise.
+pub const DEFAULT_SUMMARY_LINES: usize = 10;
+
+/// The part of a run that failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailedStage {
+    /// A `before_each`, `after_each`, `on_success` or `on_failure` hook
+    Hook,
+    PreCommand,
+    /// A composite step, a matrix cell or a `depends_on` config
+    Step,
+    /// The config itself
+    Command,
+}
+
+impl Display for FailedStage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FailedStage::Hook => write!(f, "hook"),
+            FailedStage::PreCommand => write!(f, "pre_command"),
+            FailedStage::Step => write!(f, "step"),
+            FailedStage::Command => write!(f, "command"),
+        }
+    }
+}
+
+/// What failed in a run and what to look at next, shown once the run is over.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FailureSummary {
+    /// Label of what failed, the config itself or a part of its run
+    pub failed: String,
+    pub stage: FailedStage,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    pub error: String,
+    /// The command line of what failed, its placeholders resolved
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// The last lines it wrote to stderr, when rx read its output
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stderr: Vec<String>,
+    /// Where its `log_file` has the whole output
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_file: Option<PathBuf>,
+}
+
+impl FailureSummary {
+    /// The summary as lines for the terminal.
+    pub fn lines(&self) -> Vec<String> {
+        let mut failed = format!("{} {} failed", self.stage, self.failed);
+        if let Some(code) = self.exit_code {
+            failed.push_str(&format!(" with exit code {}", code));
+        }
+        let mut lines = vec![failed];
+        if let Some(command) = &self.command {
+            lines.push(format!("  command: {}", command));
+        }
+        if let Some(log_file) = &self.log_file {
+            lines.push(format!("  log: {}", log_file.display()));
+        }
+        if !self.stderr.is_empty() {
+            lines.push("  stderr:".to_string());
+            lines.extend(self.stderr.iter().map(|line| format!("  | {}", line)));
+        }
+        lines
+    }
+}
+
+/// Follows a run through its observers to summarize it when it fails, the same for `rx run`
+/// and the runs of `rx daemon`.
+pub struct FailureReporter {
+    lines: usize,
+    /// The last lines of stderr per label
+    stderr: Mutex<HashMap<String, VecDeque<String>>>,
+    /// The steps, pre_commands and cells that failed, in the order they did
+    failures: Mutex<Vec<(FailedStage, StepReport)>>,
+}
+
+impl FailureReporter {
+    /// Keeps the last `lines` lines of stderr of each part of the run.
+    pub fn new(lines: usize) -> Arc<Self> {
+        Arc::new(Self {
+            lines,
+            stderr: Mutex::new(HashMap::new()),
+            failures: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// `observer`, if any, with the reporter taking the stderr lines.
+    pub fn observe_output(self: &Arc<Self>, observer: Option<OutputObserver>) -> OutputObserver {
+        let reporter = self.clone();
+        Arc::new(move |label, stream, line| {
+            if stream == OutputStream::Stderr && reporter.lines > 0 {
+                let mut stderr = reporter.stderr.lock().unwrap();
+                let tail = stderr.entry(label.to_string()).or_default();
+                if tail.len() == reporter.lines {
+                    tail.pop_front();
+                }
+                tail.push_back(line.to_string());
+            }
+            if let Some(observer) = &observer {
+                observer(label, stream, line);
+            }
+        })
+    }
+
+    /// `observer` with the reporter taking the steps and pre_commands that failed, save those
+    /// the run went on after.
+    pub fn observe_steps(self: &Arc<Self>, observer: StepObserver) -> StepObserver {
+        let reporter = self.clone();
+        Arc::new(move |event| {
+            let failure = match event {
+                StepEvent::PreCommandFinished(report) => Some((FailedStage::PreCommand, report)),
+                StepEvent::Finished(report) => Some((FailedStage::Step, report)),
+                _ => None,
+            };
+            if let StepEvent::PreCommandIgnored(label, _) = event {
+                let mut failures = reporter.failures.lock().unwrap();
+                failures.retain(|(_, report)| report.label != *label);
+            }
+            if let Some((stage, report)) = failure.filter(|(_, report)| !report.success) {
+                reporter
+                    .failures
+                    .lock()
+                    .unwrap()
+                    .push((stage, report.clone()));
+            }
+            observer(event)
+        })
+    }
+
+    /// The summary of `runner`'s run of `key` with `args` that ended in `error`.
+    pub fn summarize(
+        &self,
+        runner: &Runner,
+        key: &str,
+        args: &[String],
+        error: &ExecutionError,
+    ) -> FailureSummary {
+        let failures = self.failures.lock().unwrap().clone();
+        let reported = |label: &str| failures.iter().find(|(_, report)| report.label == label);
+        // A failed command names itself, other errors are those of the first part that failed
+        let (stage, failed, exit_code) = match error {
+            ExecutionError::HookFailed(hook) => (FailedStage::Hook, hook.clone(), None),
+            ExecutionError::CommandFailed(label, code) => match reported(label) {
+                Some((stage, _)) => (*stage, label.clone(), *code),
+                None if *label == runner.label(key) => (FailedStage::Command, label.clone(), *code),
+                None => (FailedStage::Step, label.clone(), *code),
+            },
+            _ => match failures.first() {
+                Some((stage, report)) => (*stage, report.label.clone(), report.code),
+                None => (FailedStage::Command, runner.label(key), None),
+            },
+        };
+        let args = match failed == runner.label(key) {
+            true => args,
+            false => &[],
+        };
+
+        let mut command = None;
+        let mut log_file = None;
+        if stage == FailedStage::Hook {
+            let entry = failed
+                .split_once(" hook '")
+                .and_then(|(_, entry)| entry.strip_suffix('\''));
+            command = match entry.map(HookEntry::parse) {
+                Some(HookEntry::Inline(line)) => Some(line.to_string()),
+                Some(HookEntry::Config(context, key)) => {
+                    runner.for_context(context).command_line(key, &[]).ok()
+                }
+                None => None,
+            };
+        } else if let Ok((context, key, cell)) = parse_label(&failed) {
+            let mut failed_runner = Some(runner.for_context(context));
+            if let Some(cell) = cell {
+                failed_runner = failed_runner.and_then(|runner| runner.only_cell(key, cell).ok());
+            }
+            if let Some(failed_runner) = failed_runner {
+                command = failed_runner.command_line(key, args).ok();
+                log_file = failed_runner
+                    .details(key)
+                    .ok()
+                    .and_then(|details| failed_runner.log_file(key, details));
+            }
+        }
+
+        let stderr = self
+            .stderr
+            .lock()
+            .unwrap()
+            .get(&failed)
+            .map(|tail| tail.iter().cloned().collect())
+            .unwrap_or_default();
+        FailureSummary {
+            failed,
+            stage,
+            exit_code,
+            error: error.to_string(),
+            command,
+            stderr,
+            log_file,
+        }
+    }
+}
@@ -0,0 +1,308 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    fmt::{self, Display},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    errors::ConfigError,
+    executor::{runner::Runner, shutdown::ShutdownSignal, temp::leftovers},
+    interpolation::InterpolationContext,
+    models::config::{CommandContext, Config, Removal},
+    rerun::parse_label,
+    service::{is_running, service_keys, ServiceRegistry},
+    stats::RunHistory,
+    store::Store,
+};
+
+/// Days without a run after which `rx gc` counts a config as stale.
+pub const DEFAULT_STALE_DAYS: u64 = 30;
+
+/// The file cargo leaves at the root of a target directory.
+const CACHEDIR_TAG: &str = "CACHEDIR.TAG";
+
+/// Something `rx gc` collects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Garbage {
+    /// A config whose last recorded run finished more than the given days ago
+    StaleConfig {
+        context: CommandContext,
+        key: String,
+        /// Seconds since the unix epoch
+        last_run: u64,
+    },
+    /// The recorded runs of a config that is gone
+    History(String),
+    /// The cache entry of a config that is gone
+    Cache(PathBuf),
+    /// The log of a service that is gone and isn't running
+    Log(PathBuf),
+    /// The pid `rx up` recorded for a service that stopped on its own
+    Pid { key: String, pid: u32 },
+    /// A per-key `target_dir` of a config that is gone
+    TargetDir(PathBuf),
+    /// A temp script, sandbox or pane log left by an rx that is gone
+    Runtime(PathBuf),
+}
+
+impl Display for Garbage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Garbage::StaleConfig { context, key, .. } => write!(f, "config {}::{}", context, key),
+            Garbage::History(label) => write!(f, "history of {}", label),
+            Garbage::Cache(path) => write!(f, "cache {}", path.display()),
+            Garbage::Log(path) => write!(f, "log {}", path.display()),
+            Garbage::Pid { key, pid } => write!(f, "pid {} of the stopped service {}", pid, key),
+            Garbage::TargetDir(path) => write!(f, "target dir {}", path.display()),
+            Garbage::Runtime(path) => write!(f, "leftover {}", path.display()),
+        }
+    }
+}
+
+/// What `rx gc` would collect from `store`, the project's, as of `now` in seconds since the
+/// unix epoch: the configs not run for `days`, and the history, cache entries, service logs
+/// and per-key target dirs of the configs that are gone or stale, with the pids of services
+/// that stopped. A config without a recorded run, or that another entry refers to, is kept.
+pub fn find_garbage(
+    config: &Config,
+    store: &Store,
+    interpolation: &InterpolationContext,
+    days: u64,
+    now: u64,
+) -> Result<Vec<Garbage>, Box<dyn Error>> {
+    let history = RunHistory::load(store)?;
+    // Runs of the matrix cells count for their config
+    let mut last_runs: BTreeMap<String, u64> = BTreeMap::new();
+    for (label, runs) in &history.runs {
+        if let Ok((context, key, _)) = parse_label(label) {
+            let finished_at = runs.iter().map(|run| run.finished_at).max().unwrap_or(0);
+            let last_run = last_runs
+                .entry(format!("{}::{}", context, key))
+                .or_default();
+            *last_run = (*last_run).max(finished_at);
+        }
+    }
+
+    let mut garbage = Vec::new();
+    let mut kept = BTreeSet::new();
+    for context in CommandContext::ALL {
+        let Some(command_config) = config.commands.get_config(context) else {
+            continue;
+        };
+        let mut keys: Vec<&String> = command_config.configs.keys().collect();
+        keys.sort();
+        for key in keys {
+            let label = format!("{}::{}", context, key);
+            let last_run = last_runs.get(&label).copied();
+            match last_run {
+                Some(last_run)
+                    if now.saturating_sub(last_run) > days * 86_400
+                        && config.references(context, key).is_empty() =>
+                {
+                    garbage.push(Garbage::StaleConfig {
+                        context,
+                        key: key.clone(),
+                        last_run,
+                    })
+                }
+                _ => {
+                    kept.insert(label);
+                }
+            }
+        }
+    }
+    let is_kept = |label: &str| {
+        parse_label(label).map_or(true, |(context, key, _)| {
+            kept.contains(&format!("{}::{}", context, key))
+        })
+    };
+
+    garbage.extend(
+        history
+            .runs
+            .keys()
+            .filter(|label| !is_kept(label))
+            .map(|label| Garbage::History(label.clone())),
+    );
+
+    let cache_dir = store.path("cache");
+    for file in files_below(&cache_dir) {
+        let Ok(relative) = file.strip_prefix(&cache_dir) else {
+            continue;
+        };
+        let relative = relative.to_string_lossy();
+        let Some((context, key)) = relative
+            .strip_suffix(".toml")
+            .and_then(|label| label.split_once('/'))
+        else {
+            continue;
+        };
+        if !is_kept(&format!("{}::{}", context, key)) {
+            garbage.push(Garbage::Cache(file));
+        }
+    }
+
+    let registry = ServiceRegistry::load(store)?;
+    let services = service_keys(config);
+    for (key, record) in &registry.services {
+        if !is_running(record.pid) {
+            garbage.push(Garbage::Pid {
+                key: key.clone(),
+                pid: record.pid,
+            });
+        }
+    }
+    for file in files_below(&store.path("services")) {
+        let key = file
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let running = registry
+            .services
+            .get(&key)
+            .is_some_and(|record| is_running(record.pid));
+        let is_service = services.contains(&key)
+            && kept.contains(&format!("{}::{}", CommandContext::Script, key));
+        if file.extension().is_some_and(|extension| extension == "log") && !is_service && !running {
+            garbage.push(Garbage::Log(file));
+        }
+    }
+
+    garbage.extend(
+        orphaned_target_dirs(config, interpolation, &kept)
+            .into_iter()
+            .map(Garbage::TargetDir),
+    );
+    Ok(garbage)
+}
+
+/// What `rx gc --runtime` sweeps: the temp resources in `temp_dir` of rx processes that
+/// crashed or were killed before removing them, and the pids recorded in the project's `store`
+/// of services that stopped.
+pub fn find_runtime_garbage(
+    store: &Store,
+    temp_dir: &Path,
+) -> Result<Vec<Garbage>, Box<dyn Error>> {
+    let registry = ServiceRegistry::load(store)?;
+    let mut garbage: Vec<Garbage> = registry
+        .services
+        .iter()
+        .filter(|(_, record)| !is_running(record.pid))
+        .map(|(key, record)| Garbage::Pid {
+            key: key.clone(),
+            pid: record.pid,
+        })
+        .collect();
+    garbage.extend(leftovers(temp_dir).into_iter().map(Garbage::Runtime));
+    Ok(garbage)
+}
+
+/// Removes the stale configs of `garbage` from `config`, for the caller to save.
+pub fn remove_stale_configs(config: &mut Config, garbage: &[Garbage]) -> Result<(), ConfigError> {
+    for item in garbage {
+        if let Garbage::StaleConfig { context, key, .. } = item {
+            config.remove_config(*context, key, Removal::Safe)?;
+        }
+    }
+    Ok(())
+}
+
+/// Removes the rest of `garbage` from the project's `store` and the disk.
+pub fn collect(store: &Store, garbage: &[Garbage]) -> Result<(), Box<dyn Error>> {
+    let mut history = RunHistory::load(store)?;
+    let mut registry = ServiceRegistry::load(store)?;
+    for item in garbage {
+        match item {
+            Garbage::StaleConfig { .. } => {}
+            Garbage::History(label) => {
+                history.runs.remove(label);
+            }
+            Garbage::Cache(path) | Garbage::Log(path) => fs::remove_file(path)
+                .map_err(|err| format!("cannot remove {}: {}", path.display(), err))?,
+            Garbage::Pid { key, .. } => {
+                registry.services.remove(key);
+            }
+            Garbage::TargetDir(path) => fs::remove_dir_all(path)
+                .map_err(|err| format!("cannot remove {}: {}", path.display(), err))?,
+            Garbage::Runtime(path) => match path.is_dir() {
+                true => fs::remove_dir_all(path),
+                false => fs::remove_file(path),
+            }
+            .map_err(|err| format!("cannot remove {}: {}", path.display(), err))?,
+        }
+    }
+    history.save(store)?;
+    registry.save(store)?;
+    Ok(())
+}
+
+/// The target dirs next to those of configs with a `target_dir` ending in `${key}` that no kept
+/// config builds into. Only directories cargo tagged as its own are taken, so `target/debug`
+/// next to `target/${key}` stays.
+fn orphaned_target_dirs(
+    config: &Config,
+    interpolation: &InterpolationContext,
+    kept: &BTreeSet<String>,
+) -> Vec<PathBuf> {
+    let mut parents = BTreeSet::new();
+    let mut used = BTreeSet::new();
+    for context in CommandContext::ALL {
+        let Some(command_config) = config.commands.get_config(context) else {
+            continue;
+        };
+        let runner = Runner::new(config, context, ShutdownSignal::new())
+            .interpolation(interpolation.clone());
+        for (key, details) in &command_config.configs {
+            let Some(target_dir) = runner.target_dir(key, details) else {
+                continue;
+            };
+            let per_key = details
+                .target_dir
+                .as_deref()
+                .is_some_and(|template| template.trim_end_matches('/').ends_with("${key}"));
+            if let Some(parent) = target_dir.parent().filter(|_| per_key) {
+                parents.insert(parent.to_path_buf());
+            }
+            if kept.contains(&format!("{}::{}", context, key)) {
+                used.insert(target_dir);
+            }
+        }
+    }
+
+    let mut orphaned = Vec::new();
+    for parent in parents {
+        let Ok(entries) = fs::read_dir(&parent) else {
+            continue;
+        };
+        let mut dirs: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.join(CACHEDIR_TAG).is_file() && !used.contains(path))
+            .collect();
+        dirs.sort();
+        orphaned.extend(dirs);
+    }
+    orphaned
+}
+
+/// The files below `dir`, sorted, none when it doesn't exist.
+fn files_below(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files
+}
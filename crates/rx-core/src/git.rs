@@ -0,0 +1,55 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Runs `git` in `dir` and returns its trimmed stdout when it succeeded.
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Abbreviated sha `rev` (a branch, tag or sha) points to.
+pub fn short_sha(dir: &Path, rev: &str) -> Option<String> {
+    git(dir, &["rev-parse", "--short", "--verify", "--quiet", rev]).filter(|sha| !sha.is_empty())
+}
+
+/// Name of the checked out branch, `None` on a detached HEAD.
+pub fn branch(dir: &Path) -> Option<String> {
+    git(dir, &["symbolic-ref", "--short", "--quiet", "HEAD"]).filter(|branch| !branch.is_empty())
+}
+
+/// Top-level directory of the work tree `dir` is in.
+pub fn root(dir: &Path) -> Option<String> {
+    git(dir, &["rev-parse", "--show-toplevel"]).filter(|root| !root.is_empty())
+}
+
+pub fn is_dirty(dir: &Path) -> bool {
+    git(dir, &["status", "--porcelain"]).is_some_and(|status| !status.is_empty())
+}
+
+/// Identifies the checked out state: the HEAD sha, suffixed with `-dirty` when there are
+/// uncommitted changes.
+pub fn revision(dir: &Path) -> Option<String> {
+    let sha = short_sha(dir, "HEAD")?;
+
+    if is_dirty(dir) {
+        Some(format!("{}-dirty", sha))
+    } else {
+        Some(sha)
+    }
+}
+
+/// Where the hooks of the repository at `dir` go, `core.hooksPath` included.
+pub fn hooks_dir(dir: &Path) -> Option<PathBuf> {
+    git(dir, &["rev-parse", "--git-path", "hooks"]).map(|path| dir.join(path))
+}
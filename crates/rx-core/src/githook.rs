@@ -0,0 +1,77 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::helpers::shell_quote;
+
+/// The client-side git hooks `[hooks.git]` can run configs from.
+pub const GIT_HOOKS: &[&str] = &[
+    "pre-commit",
+    "pre-merge-commit",
+    "prepare-commit-msg",
+    "commit-msg",
+    "post-commit",
+    "pre-rebase",
+    "post-checkout",
+    "post-merge",
+    "pre-push",
+];
+
+/// First line after the shebang of the hooks rx writes, the ones it may replace or remove.
+const MARKER: &str = "# Installed by `rx hook install`";
+
+/// The script of `hook`, handing over to `rx hook run` with the config at `config_path`, so
+/// changes to `[hooks.git]` apply without installing again.
+pub fn hook_script(hook: &str, rx: &Path, config_path: &Path) -> String {
+    format!(
+        "#!/bin/sh\n{} from [hooks.git.{}], `rx hook uninstall` removes it\nexec {} --config {} hook run {} \"$@\"\n",
+        MARKER,
+        hook,
+        shell_quote(&rx.to_string_lossy()),
+        shell_quote(&config_path.to_string_lossy()),
+        hook
+    )
+}
+
+/// Whether the hook at `path` was written by rx.
+pub fn is_rx_hook(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|script| {
+        script
+            .lines()
+            .nth(1)
+            .is_some_and(|line| line.starts_with(MARKER))
+    })
+}
+
+/// Writes `script` as `hook` in `dir`. A hook rx didn't write is only replaced with `force`.
+pub fn install(dir: &Path, hook: &str, script: &str, force: bool) -> io::Result<PathBuf> {
+    let path = dir.join(hook);
+    if path.exists() && !force && !is_rx_hook(&path) {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!(
+                "{} exists and wasn't written by rx, use --force to replace it",
+                path.display()
+            ),
+        ));
+    }
+    fs::create_dir_all(dir)?;
+    fs::write(&path, script)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(path)
+}
+
+/// Removes `hook` from `dir` when rx wrote it, returning whether it did.
+pub fn uninstall(dir: &Path, hook: &str) -> io::Result<bool> {
+    let path = dir.join(hook);
+    if !is_rx_hook(&path) {
+        return Ok(false);
+    }
+    fs::remove_file(&path)?;
+    Ok(true)
+}
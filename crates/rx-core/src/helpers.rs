@@ -0,0 +1,218 @@
+use std::{
+    error::Error,
+    fs::{self, File, Metadata},
+    io::{self, BufWriter, ErrorKind, Write},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use directories::ProjectDirs;
+use dirs::home_dir;
+
+use crate::{errors::ConfigError, models::config::Config, session::Session};
+
+/// Reads the whole file. Nothing is kept between calls, so loading a config twice reads it
+/// twice.
+pub fn read_file(path: &Path) -> Result<String, Box<dyn Error>> {
+    fs::read_to_string(path)
+        .map_err(|err| format!("cannot read {}: {}", path.display(), err).into())
+}
+
+pub fn write_to_config_file(path: &Path, content: &str) -> Result<(), Box<dyn Error>> {
+    let file = File::create(path)?;
+    let mut writer = BufWriter::new(file);
+
+    for line in content.lines() {
+        writeln!(writer, "{}", line)?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Replaces the config file at `path` with `content`, written next to it first and renamed
+/// over it so a reader never sees half a config. A link is followed and the file keeps its
+/// permissions. The directory is created with `create_dirs`, and each reason the file can't be
+/// written has an error of its own that says how to fix it.
+pub fn save_config_file(path: &Path, content: &str, create_dirs: bool) -> Result<(), ConfigError> {
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let shown = path.display().to_string();
+    let failed = |err: io::Error| ConfigError::SaveFailed(shown.clone(), err.to_string());
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    if !dir.is_dir() {
+        if !create_dirs {
+            return Err(ConfigError::MissingConfigDir(dir.display().to_string()));
+        }
+        fs::create_dir_all(dir).map_err(|err| match err.kind() {
+            ErrorKind::PermissionDenied => {
+                ConfigError::ConfigDirNotWritable(dir.display().to_string())
+            }
+            _ => failed(err),
+        })?;
+    }
+
+    let existing = fs::metadata(&path).ok();
+    if let Some(metadata) = &existing {
+        if metadata.permissions().readonly() {
+            return Err(ConfigError::ReadOnlyConfig(shown));
+        }
+        if owned_by_another_user(metadata) {
+            return Err(ConfigError::ConfigOwnedByOther(shown));
+        }
+    }
+
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "config".to_string());
+    let temp = dir.join(format!(".{}.{}.tmp", name, std::process::id()));
+    let written = write_to_config_file(&temp, content).and_then(|()| {
+        if let Some(metadata) = &existing {
+            fs::set_permissions(&temp, metadata.permissions())?;
+        }
+        Ok(())
+    });
+    if let Err(err) = written {
+        let _ = fs::remove_file(&temp);
+        return Err(match err.downcast::<io::Error>() {
+            Ok(err) if err.kind() == ErrorKind::PermissionDenied => {
+                ConfigError::ConfigDirNotWritable(dir.display().to_string())
+            }
+            Ok(err) => failed(*err),
+            Err(err) => ConfigError::SaveFailed(shown.clone(), err.to_string()),
+        });
+    }
+
+    fs::rename(&temp, &path).map_err(|err| {
+        let _ = fs::remove_file(&temp);
+        match err.kind() {
+            ErrorKind::CrossesDevices | ErrorKind::ResourceBusy => {
+                ConfigError::CrossDeviceConfig(shown.clone())
+            }
+            ErrorKind::PermissionDenied => {
+                ConfigError::ConfigDirNotWritable(dir.display().to_string())
+            }
+            _ => failed(err),
+        }
+    })
+}
+
+/// Whether the file belongs to a user other than the one rx runs as, who isn't root.
+#[cfg(unix)]
+fn owned_by_another_user(metadata: &Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    let user = unsafe { libc::geteuid() };
+    user != 0 && metadata.uid() != user
+}
+
+#[cfg(not(unix))]
+fn owned_by_another_user(_metadata: &Metadata) -> bool {
+    false
+}
+
+pub fn is_all_caps(s: &str) -> bool {
+    s.chars().all(|c| !c.is_alphabetic() || c.is_uppercase())
+}
+
+pub fn is_valid_env_var_name(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    // Check if the first character is an uppercase letter
+    match chars.next() {
+        Some(first_char) => first_char.is_ascii_uppercase(),
+        None => return false, // The name is empty, so it's invalid
+    };
+
+    // Check the rest of the characters
+    chars.all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Quotes `value` for a POSIX shell, leaving plain words untouched.
+pub fn shell_quote(value: &str) -> String {
+    let is_plain = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:@,+%".contains(c));
+
+    if is_plain {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
+
+/// The triple rustc compiles for by default, from `rustc -vV`, run once per session. Falls
+/// back to the architecture and OS rx itself was built for when rustc can't be run.
+pub fn host_triple(session: &Session) -> &str {
+    session.host_triple(|| {
+        Command::new("rustc")
+            .arg("-vV")
+            .output()
+            .ok()
+            .and_then(|output| {
+                String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .find_map(|line| line.strip_prefix("host: "))
+                    .map(str::to_string)
+            })
+            .unwrap_or_else(|| format!("{}-{}", std::env::consts::ARCH, std::env::consts::OS))
+    })
+}
+
+/// The value of a `--target` option in `args`, in either `--target x` or `--target=x` form.
+pub fn target_arg<'a>(args: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--target" {
+            return args.next();
+        }
+        if let Some(target) = arg.strip_prefix("--target=") {
+            return Some(target);
+        }
+    }
+    None
+}
+
+/// Environment variable naming the config file to use instead of the default one.
+pub const CONFIG_PATH_ENV: &str = "RX_CONFIG";
+
+/// The config file used when none is given: `$RX_CONFIG`, otherwise `config.toml` in rx's
+/// config dir of the platform, `$XDG_CONFIG_HOME/cargo_runner` (`~/.config/cargo_runner`) on
+/// Linux, `~/Library/Application Support/cargo_runner` on macOS and
+/// `%APPDATA%\cargo_runner\config` on Windows. A config at `~/.config/cargo_runner`, where
+/// earlier versions kept it on every platform, is used until one exists in the new place.
+/// `None` without a home directory.
+pub fn default_config_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os(CONFIG_PATH_ENV).filter(|path| !path.is_empty()) {
+        return Some(PathBuf::from(path));
+    }
+
+    let path = ProjectDirs::from_path(PathBuf::from("cargo_runner"))?
+        .config_dir()
+        .join("config.toml");
+    let legacy = home_dir().map(|home| home.join(".config/cargo_runner/config.toml"));
+    match legacy {
+        Some(legacy) if !path.exists() && legacy.exists() => Some(legacy),
+        _ => Some(path),
+    }
+}
+
+pub fn ensure_config_directory_and_file(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    if !path.exists() {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).expect("Failed to create configuration directory");
+        }
+        create_default_config_file(path)?;
+    }
+    Ok(())
+}
+pub fn create_default_config_file(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let default_config = Config::default();
+    let toml = toml::to_string(&default_config)?;
+    write_to_config_file(path, &toml)?;
+    Ok(())
+}
@@ -0,0 +1,761 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    str::FromStr,
+};
+
+use serde::Deserialize;
+
+use crate::{
+    errors::ConfigError,
+    helpers::shell_quote,
+    models::{
+        config::{CommandDetails, CommandType},
+        params::Params,
+    },
+    watch::DEFAULT_WATCH,
+};
+
+/// Task runner files `rx import` converts into script configs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    Just,
+    Make,
+    /// The jobs of a bacon.toml, each watching its files
+    Bacon,
+}
+
+impl FromStr for ImportFormat {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "just" => Ok(ImportFormat::Just),
+            "make" => Ok(ImportFormat::Make),
+            "bacon" => Ok(ImportFormat::Bacon),
+            _ => Err(ConfigError::UnknownImportFormat(s.to_string())),
+        }
+    }
+}
+
+impl ImportFormat {
+    /// File names looked up in the project root, in the order the tool itself tries them.
+    pub fn file_names(&self) -> &'static [&'static str] {
+        match self {
+            ImportFormat::Just => &["justfile", "Justfile", ".justfile"],
+            ImportFormat::Make => &["GNUmakefile", "makefile", "Makefile"],
+            ImportFormat::Bacon => &["bacon.toml", ".config/bacon.toml"],
+        }
+    }
+
+    /// The recipes of the file, a `cargo watch` recipe turned into the command it runs with
+    /// the files it watches, see [`Recipe::unwrap_cargo_watch`].
+    pub fn parse(&self, content: &str) -> Result<Vec<Recipe>, String> {
+        let mut recipes = match self {
+            ImportFormat::Just => parse_justfile(content),
+            ImportFormat::Make => parse_makefile(content),
+            ImportFormat::Bacon => return parse_bacon(content),
+        };
+        recipes.iter_mut().for_each(Recipe::unwrap_cargo_watch);
+        Ok(recipes)
+    }
+}
+
+/// A Justfile recipe or a Makefile target.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Recipe {
+    pub name: String,
+    /// Justfile recipe parameters, passed as arguments
+    pub parameters: Vec<Parameter>,
+    /// Recipes or targets that run first
+    pub dependencies: Vec<String>,
+    /// Shell lines with the tool's variables resolved
+    pub body: Vec<String>,
+    /// Files a Makefile target is built from
+    pub inputs: Vec<String>,
+    /// The file a Makefile target builds
+    pub outputs: Vec<String>,
+    /// Files whose changes run it again under `rx run --watch`, for a bacon job or a
+    /// `cargo watch` recipe
+    pub watch: Vec<String>,
+    /// Environment of a bacon job or a `cargo watch -E`
+    pub env: BTreeMap<String, String>,
+    /// Parts that couldn't be converted and need a look
+    pub notes: Vec<String>,
+}
+
+/// A Justfile recipe parameter.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Parameter {
+    pub name: String,
+    pub default: Option<String>,
+    /// `*args` or `+args`, taking the remaining arguments
+    pub variadic: bool,
+}
+
+impl Parameter {
+    /// The shell variable the parameter's argument is read into.
+    fn variable(&self) -> String {
+        self.name.replace('-', "_")
+    }
+}
+
+impl Recipe {
+    /// A script config running the body after the dependencies, or a composite config of the
+    /// dependencies when there is no body.
+    pub fn to_details(&self) -> CommandDetails {
+        let mut details = CommandDetails {
+            command_type: CommandType::Shell,
+            working_directory: "${workspaceFolder}".to_string(),
+            inputs: self.inputs.clone(),
+            outputs: self.outputs.clone(),
+            watch: self.watch.clone(),
+            env: self.env.clone(),
+            ..Default::default()
+        };
+
+        if self.body.is_empty() {
+            details.command_type = CommandType::Composite;
+            details.steps = self.dependencies.clone();
+            return details;
+        }
+
+        details.pre_command = self.dependencies.iter().cloned().collect();
+        if self.body[0].starts_with("#!") {
+            details.command = self.body.join("\n").into();
+            return details;
+        }
+
+        // Arguments are read into variables named after the parameters, the remaining ones
+        // stay in "$@" for a variadic parameter
+        let mut lines: Vec<String> = self
+            .parameters
+            .iter()
+            .filter(|parameter| !parameter.variadic)
+            .map(|parameter| {
+                format!(
+                    "{}=\"${{1-{}}}\"; shift $(( $# > 0 ))",
+                    parameter.variable(),
+                    parameter.default.as_deref().unwrap_or_default()
+                )
+            })
+            .collect();
+        lines.extend(self.body.iter().cloned());
+
+        let command = match lines.as_slice() {
+            [line] => line.clone(),
+            // Both tools stop at the first failing line
+            lines => format!("set -e\n{}", lines.join("\n")),
+        };
+        details.command = command.into();
+        details
+    }
+
+    /// Turns a recipe whose body is a single `cargo watch` line into the commands it runs,
+    /// chained with `&&` like cargo-watch does, and the files it watches, with notes for its
+    /// options rx has no counterpart for. Other recipes are left as they are.
+    pub fn unwrap_cargo_watch(&mut self) {
+        let [line] = self.body.as_slice() else {
+            return;
+        };
+        let words = Params::Line(line.clone()).words();
+        let args = match words.as_slice() {
+            [cargo, watch, args @ ..] if cargo == "cargo" && watch == "watch" => args,
+            [cargo_watch, args @ ..] if cargo_watch == "cargo-watch" => args,
+            _ => return,
+        };
+
+        let mut commands = Vec::new();
+        let mut watch = Vec::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let (option, inline) = match arg.split_once('=') {
+                Some((option, value)) if option.starts_with("--") => (option, Some(value)),
+                _ => (arg.as_str(), None),
+            };
+            let mut value = || inline.map(str::to_string).or_else(|| args.next().cloned());
+            match option {
+                "-x" | "--exec" => commands.extend(value().map(|exec| format!("cargo {}", exec))),
+                "-s" | "--shell" => commands.extend(value()),
+                "-w" | "--watch" => watch.extend(value()),
+                "-E" | "--env" => {
+                    if let Some((name, value)) = value().as_deref().and_then(|e| e.split_once('='))
+                    {
+                        self.env.insert(name.to_string(), value.to_string());
+                    }
+                }
+                "-B" => {
+                    let backtrace = value().unwrap_or_default();
+                    self.env.insert("RUST_BACKTRACE".to_string(), backtrace);
+                }
+                "-L" => {
+                    let log = value().unwrap_or_default();
+                    self.env.insert("RUST_LOG".to_string(), log);
+                }
+                "--" => {
+                    let command: Vec<String> = args.by_ref().map(|arg| shell_quote(arg)).collect();
+                    commands.push(command.join(" "));
+                }
+                // Clearing the screen and quieting cargo-watch itself change nothing to the run
+                "-c" | "--clear" | "-q" | "--quiet" => {}
+                "-i" | "--ignore" | "-C" | "--workdir" | "-d" | "--delay" | "--features"
+                | "--env-file" | "--use-shell" | "--poll" => {
+                    let value = value().unwrap_or_default();
+                    self.notes
+                        .push(format!("cargo watch {} {} was dropped", option, value));
+                }
+                _ => self.notes.push(format!("cargo watch {} was dropped", arg)),
+            }
+        }
+
+        // cargo-watch checks when told nothing to run
+        if commands.is_empty() {
+            commands.push("cargo check".to_string());
+        }
+        self.body = vec![commands.join(" && ")];
+        self.watch = watch;
+    }
+}
+
+/// Parses the recipes of a Justfile. Parameters become shell variables read from the arguments
+/// and `name := "value"` variables are substituted, other `{{...}}` expressions are left as
+/// they are with a note.
+pub fn parse_justfile(content: &str) -> Vec<Recipe> {
+    let mut variables = HashMap::new();
+    let mut recipes: Vec<Recipe> = Vec::new();
+    let mut parameters: Vec<Parameter> = Vec::new();
+    let mut in_body = false;
+
+    for line in content.lines() {
+        if in_body && line.starts_with([' ', '\t']) {
+            let recipe = recipes.last_mut().expect("a body follows a recipe");
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let line = just_line(line, recipe.body.is_empty());
+            let line = substitute_just(&line, &variables, &parameters, &mut recipe.notes);
+            recipe.body.push(line);
+            continue;
+        }
+
+        in_body = false;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(['#', '[']) {
+            continue;
+        }
+        let first_word = line.split_whitespace().next().unwrap_or_default();
+        if ["set", "alias", "import", "mod"].contains(&first_word) {
+            continue;
+        }
+
+        if let Some((name, value)) = line.split_once(":=") {
+            let name = name.trim().trim_start_matches("export ").trim();
+            if let Some(value) = unquote(value.trim()) {
+                variables.insert(name.to_string(), value);
+            }
+            continue;
+        }
+
+        let Some((header, dependencies)) = line.split_once(':') else {
+            continue;
+        };
+        let mut words = header.split_whitespace();
+        let Some(name) = words.next() else {
+            continue;
+        };
+
+        parameters = words
+            .map(|parameter| {
+                let (name, default) = match parameter.split_once('=') {
+                    Some((name, default)) => (
+                        name,
+                        Some(unquote(default).unwrap_or_else(|| default.to_string())),
+                    ),
+                    None => (parameter, None),
+                };
+                Parameter {
+                    name: name.trim_start_matches(['*', '+', '$']).to_string(),
+                    default,
+                    variadic: name.starts_with(['*', '+']),
+                }
+            })
+            .collect();
+
+        let mut recipe = Recipe {
+            name: name.trim_start_matches('@').to_string(),
+            parameters: parameters.clone(),
+            ..Default::default()
+        };
+        for dependency in just_dependencies(dependencies) {
+            if dependency.contains(' ') {
+                recipe.notes.push(format!(
+                    "dependency ({}) takes arguments, they were dropped",
+                    dependency
+                ));
+            }
+            let name = dependency.split_whitespace().next().unwrap_or_default();
+            recipe.dependencies.push(name.to_string());
+        }
+
+        recipes.push(recipe);
+        in_body = true;
+    }
+
+    recipes
+}
+
+/// Strips the `@` (don't echo) and `-` (ignore failure) prefixes of a recipe line.
+fn just_line(line: &str, first: bool) -> String {
+    if first && line.starts_with("#!") {
+        return line.to_string();
+    }
+    let line = line.trim_start_matches('@');
+    match line.strip_prefix('-') {
+        Some(line) => format!("{} || true", line),
+        None => line.to_string(),
+    }
+}
+
+/// `dep (dep-with-args "a")` into `dep` and `dep-with-args "a"`.
+fn just_dependencies(dependencies: &str) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut rest = dependencies.replace("&&", " ");
+    while let Some(start) = rest.find('(') {
+        let end = rest[start..]
+            .find(')')
+            .map_or(rest.len(), |end| start + end);
+        result.extend(rest[..start].split_whitespace().map(str::to_string));
+        result.push(rest[start + 1..end].trim().to_string());
+        rest = rest[(end + 1).min(rest.len())..].to_string();
+    }
+    result.extend(rest.split_whitespace().map(str::to_string));
+    result
+}
+
+fn substitute_just(
+    line: &str,
+    variables: &HashMap<String, String>,
+    parameters: &[Parameter],
+    notes: &mut Vec<String>,
+) -> String {
+    let mut result = String::new();
+    let mut rest = line;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}").map(|end| start + end) else {
+            break;
+        };
+        result.push_str(&rest[..start]);
+        let expression = rest[start + 2..end].trim();
+
+        if let Some(value) = variables.get(expression) {
+            result.push_str(value);
+        } else if let Some(parameter) = parameters.iter().find(|p| p.name == expression) {
+            if parameter.variadic {
+                result.push_str("\"$@\"");
+            } else {
+                result.push_str(&format!("\"${}\"", parameter.variable()));
+            }
+        } else {
+            notes.push(format!("{{{{{}}}}} is kept as is", expression));
+            result.push_str(&rest[start..end + 2]);
+        }
+        rest = &rest[end + 2..];
+    }
+
+    result.push_str(rest);
+    result
+}
+
+fn unquote(value: &str) -> Option<String> {
+    let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let inner = value.strip_prefix(quote)?.strip_suffix(quote)?;
+    Some(inner.to_string())
+}
+
+/// Parses the explicit targets of a Makefile. `.PHONY` targets only keep their dependencies on
+/// other targets, file targets also get their file prerequisites as `inputs` and themselves as
+/// `outputs` so rx skips them while they are up to date like make does. Pattern rules and
+/// special targets are left out.
+pub fn parse_makefile(content: &str) -> Vec<Recipe> {
+    let mut variables: HashMap<String, String> = HashMap::new();
+    let mut rules: Vec<(Vec<String>, Vec<String>, Vec<String>)> = Vec::new();
+    let mut phony = BTreeSet::new();
+    let mut in_define = false;
+    let mut in_rule = false;
+
+    for line in join_continuations(content) {
+        if in_define {
+            in_define = line.trim() != "endef";
+            continue;
+        }
+        if let Some(recipe_line) = line.strip_prefix('\t') {
+            if in_rule {
+                let (_, _, body) = rules.last_mut().expect("a recipe follows a rule");
+                body.push(recipe_line.trim().to_string());
+            }
+            continue;
+        }
+
+        // Blank and comment lines don't end a recipe
+        let line = strip_comment(&line);
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        in_rule = false;
+        let first_word = trimmed.split_whitespace().next().unwrap_or_default();
+        if first_word == "define" {
+            in_define = true;
+            continue;
+        }
+        if [
+            "include", "-include", "sinclude", "ifeq", "ifneq", "ifdef", "ifndef", "else", "endif",
+            "export", "unexport", "vpath", "override",
+        ]
+        .contains(&first_word)
+        {
+            continue;
+        }
+
+        if let Some((name, operator, value)) = make_assignment(trimmed) {
+            let value = value.trim().to_string();
+            match operator {
+                "+=" => {
+                    let entry = variables.entry(name).or_default();
+                    if !entry.is_empty() {
+                        entry.push(' ');
+                    }
+                    entry.push_str(&value);
+                }
+                "?=" => {
+                    variables.entry(name).or_insert(value);
+                }
+                _ => {
+                    variables.insert(name, value);
+                }
+            }
+            continue;
+        }
+
+        let Some((targets, prerequisites)) = trimmed.split_once(':') else {
+            continue;
+        };
+        let (prerequisites, inline_recipe) = match prerequisites.split_once(';') {
+            Some((prerequisites, recipe)) => (prerequisites, Some(recipe.trim().to_string())),
+            None => (prerequisites, None),
+        };
+        let targets: Vec<String> = targets.split_whitespace().map(str::to_string).collect();
+        let prerequisites: Vec<String> = prerequisites
+            .trim_start_matches(':')
+            .split_whitespace()
+            .filter(|prerequisite| *prerequisite != "|")
+            .map(str::to_string)
+            .collect();
+
+        if targets.iter().any(|target| target == ".PHONY") {
+            phony.extend(prerequisites);
+            continue;
+        }
+
+        rules.push((targets, prerequisites, inline_recipe.into_iter().collect()));
+        in_rule = true;
+    }
+
+    let expand = |value: &str| expand_make(value, &variables, &HashMap::new());
+    let rules: Vec<_> = rules
+        .into_iter()
+        .flat_map(|(targets, prerequisites, body)| {
+            let prerequisites: Vec<String> = prerequisites
+                .iter()
+                .flat_map(|prerequisite| {
+                    expand(prerequisite)
+                        .split_whitespace()
+                        .map(str::to_string)
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+            targets
+                .iter()
+                .map(|target| expand(target))
+                .filter(|target| !target.starts_with('.') && !target.contains('%'))
+                .map(|target| (target, prerequisites.clone(), body.clone()))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    let target_names: BTreeSet<&str> = rules.iter().map(|(target, _, _)| target.as_str()).collect();
+
+    rules
+        .iter()
+        .map(|(target, prerequisites, body)| {
+            let automatic = HashMap::from([
+                ("@", target.clone()),
+                ("<", prerequisites.first().cloned().unwrap_or_default()),
+                ("^", prerequisites.join(" ")),
+            ]);
+            let mut recipe = Recipe {
+                name: target.clone(),
+                ..Default::default()
+            };
+
+            for prerequisite in prerequisites {
+                if target_names.contains(prerequisite.as_str()) {
+                    recipe.dependencies.push(prerequisite.clone());
+                } else {
+                    recipe.inputs.push(prerequisite.clone());
+                }
+            }
+            if phony.contains(target) {
+                recipe.inputs.clear();
+            } else if !recipe.inputs.is_empty() {
+                recipe.outputs.push(target.clone());
+            }
+
+            for line in body {
+                if uses_make_function(line) {
+                    recipe
+                        .notes
+                        .push(format!("'{}' uses a make function", line.trim()));
+                }
+                recipe
+                    .body
+                    .push(make_line(&expand_make(line, &variables, &automatic)));
+            }
+            recipe
+        })
+        .collect()
+}
+
+/// Whether `line` calls a make function other than `$(shell ...)`.
+fn uses_make_function(line: &str) -> bool {
+    line.match_indices("$(").any(|(index, _)| {
+        let call = &line[index + 2..];
+        let name_end = call.find([' ', ')']).unwrap_or(call.len());
+        call[name_end..].starts_with(' ') && &call[..name_end] != "shell"
+    })
+}
+
+fn join_continuations(content: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+
+    for line in content.lines() {
+        let line = if current.is_empty() {
+            line
+        } else {
+            line.trim_start()
+        };
+        match line.strip_suffix('\\') {
+            Some(line) => {
+                current.push_str(line.trim_end());
+                current.push(' ');
+            }
+            None => {
+                current.push_str(line);
+                lines.push(std::mem::take(&mut current));
+            }
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+fn strip_comment(line: &str) -> String {
+    match line.find('#') {
+        Some(index) => line[..index].to_string(),
+        None => line.to_string(),
+    }
+}
+
+/// `NAME = value` and its `:=`, `::=`, `?=` and `+=` variants.
+fn make_assignment(line: &str) -> Option<(String, &str, &str)> {
+    let index = line.find('=')?;
+    if line[..index].contains(':') && !line[..index].ends_with(':') {
+        return None;
+    }
+
+    let before = &line[..index];
+    let (name, operator) = ["::", ":", "?", "+", "!"]
+        .iter()
+        .find_map(|prefix| {
+            before
+                .strip_suffix(prefix)
+                .map(|name| (name, &line[index - prefix.len()..=index]))
+        })
+        .unwrap_or((before, "="));
+
+    let name = name.trim();
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return None;
+    }
+    Some((name.to_string(), operator, &line[index + 1..]))
+}
+
+/// Expands `$(NAME)`, `${NAME}` and the automatic variables. Unknown variables become shell
+/// variables, which is what make falls back to through the environment, and `$(shell ...)`
+/// becomes a command substitution.
+fn expand_make(
+    value: &str,
+    variables: &HashMap<String, String>,
+    automatic: &HashMap<&str, String>,
+) -> String {
+    let mut result = String::new();
+    let mut chars = value.char_indices().peekable();
+
+    while let Some((index, c)) = chars.next() {
+        if c != '$' {
+            result.push(c);
+            continue;
+        }
+        match chars.peek().map(|(_, c)| *c) {
+            Some('$') => {
+                chars.next();
+                result.push_str("$$");
+            }
+            Some(open @ ('(' | '{')) => {
+                let close = if open == '(' { ')' } else { '}' };
+                let rest = &value[index + 2..];
+                let Some(end) = matching_close(rest, open, close) else {
+                    result.push_str(&value[index..]);
+                    break;
+                };
+                let name = &rest[..end];
+                for _ in 0..name.chars().count() + 2 {
+                    chars.next();
+                }
+
+                if let Some(command) = name.strip_prefix("shell ") {
+                    let command = expand_make(command, variables, automatic);
+                    result.push_str(&format!("$({})", command.trim()));
+                } else if let Some(value) = variables.get(name) {
+                    result.push_str(&expand_make(value, variables, automatic));
+                } else if let Some(value) = automatic.get(name) {
+                    result.push_str(value);
+                } else if name.contains(char::is_whitespace) {
+                    // A make function such as $(wildcard ...)
+                    result.push_str(&value[index..index + name.len() + 3]);
+                } else {
+                    result.push_str(&format!("${{{}}}", name));
+                }
+            }
+            Some(name) => {
+                chars.next();
+                match automatic.get(name.to_string().as_str()) {
+                    Some(value) => result.push_str(value),
+                    None => {
+                        result.push('$');
+                        result.push(name);
+                    }
+                }
+            }
+            None => result.push('$'),
+        }
+    }
+
+    result
+}
+
+fn matching_close(value: &str, open: char, close: char) -> Option<usize> {
+    let mut depth = 0;
+    for (index, c) in value.char_indices() {
+        if c == open {
+            depth += 1;
+        } else if c == close {
+            if depth == 0 {
+                return Some(index);
+            }
+            depth -= 1;
+        }
+    }
+    None
+}
+
+/// Strips the `@` (don't echo), `-` (ignore failure) and `+` prefixes of a recipe line and
+/// unescapes `$$`.
+fn make_line(line: &str) -> String {
+    let mut line = line.trim();
+    let mut ignore_failure = false;
+    while let Some(rest) = line.strip_prefix(['@', '-', '+']) {
+        ignore_failure |= line.starts_with('-');
+        line = rest.trim_start();
+    }
+
+    let line = line.replace("$$", "$");
+    if ignore_failure {
+        format!("{} || true", line)
+    } else {
+        line
+    }
+}
+
+/// The parts of a bacon.toml job rx converts.
+#[derive(Debug, Deserialize)]
+struct BaconJob {
+    #[serde(default)]
+    command: Vec<String>,
+    #[serde(default)]
+    watch: Vec<String>,
+    /// Whether `watch` adds to the package's sources instead of replacing them
+    #[serde(default = "default_true")]
+    default_watch: bool,
+    #[serde(default)]
+    env: BTreeMap<String, String>,
+    #[serde(default)]
+    kill: Option<Vec<String>>,
+    #[serde(default)]
+    on_change_strategy: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BaconFile {
+    #[serde(default)]
+    jobs: BTreeMap<String, BaconJob>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Parses the jobs of a bacon.toml, each into a recipe running its command with the files it
+/// watches. A job without `watch` watches what rx does by default, the package's sources.
+pub fn parse_bacon(content: &str) -> Result<Vec<Recipe>, String> {
+    let file: BaconFile = toml::from_str(content).map_err(|err| err.to_string())?;
+    let recipes = file
+        .jobs
+        .into_iter()
+        .filter(|(_, job)| !job.command.is_empty())
+        .map(|(name, job)| {
+            let command: Vec<String> = job.command.iter().map(|arg| shell_quote(arg)).collect();
+            let mut recipe = Recipe {
+                name,
+                body: vec![command.join(" ")],
+                env: job.env,
+                ..Default::default()
+            };
+            if !job.watch.is_empty() {
+                recipe.watch = match job.default_watch {
+                    true => DEFAULT_WATCH.iter().map(|path| path.to_string()).collect(),
+                    false => Vec::new(),
+                };
+                recipe.watch.extend(job.watch);
+            }
+            if job.kill.is_some() {
+                recipe
+                    .notes
+                    .push("kill was dropped, a change stops the command with a signal".to_string());
+            }
+            if job
+                .on_change_strategy
+                .is_some_and(|strategy| strategy == "wait_then_restart")
+            {
+                recipe.notes.push(
+                    "on_change_strategy was dropped, a change stops a run still going".to_string(),
+                );
+            }
+            recipe
+        })
+        .collect();
+    Ok(recipes)
+}
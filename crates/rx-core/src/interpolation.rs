@@ -0,0 +1,527 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock},
+    time::UNIX_EPOCH,
+};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+
+use crate::{git, store::Store, workspace::default_binary};
+
+/// Placeholders describing the git checkout
+pub const GIT_VARIABLES: [&str; 3] = ["gitRoot", "gitBranch", "gitShortSha"];
+
+/// File of the project's store keeping the package's names, see [`cached_package_names`]
+const PACKAGE_CACHE: &str = "package.toml";
+
+/// Placeholder of the binary target a config runs
+const BINARY_NAME: &str = "binaryName";
+
+/// Prefix of the placeholders asked for when a config runs
+const INPUT_PREFIX: &str = "input:";
+
+/// Prefix of the placeholders of a config's `args`
+const ARG_PREFIX: &str = "arg:";
+
+/// A `${input:name}` or `${input:name:default=value}` placeholder, like a VS Code task input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Input {
+    pub name: String,
+    pub default: Option<String>,
+}
+
+impl Input {
+    /// Reads what's between `${` and `}`, `None` when it isn't an input.
+    fn parse(placeholder: &str) -> Option<Input> {
+        let rest = placeholder.strip_prefix(INPUT_PREFIX)?;
+        let (name, default) = match rest.split_once(':') {
+            Some((name, option)) => (name, Some(option.strip_prefix("default=")?.to_string())),
+            None => (rest, None),
+        };
+        (!name.is_empty()).then(|| Input {
+            name: name.to_string(),
+            default,
+        })
+    }
+}
+
+/// The inputs `text` refers to, in order.
+pub fn find_inputs(text: &str) -> Vec<Input> {
+    segments(text)
+        .into_iter()
+        .filter_map(|segment| match segment {
+            Segment::Placeholder(name) => Input::parse(name),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The names of the `${arg:name}` placeholders of `text`, in order.
+pub fn find_args(text: &str) -> Vec<String> {
+    segments(text)
+        .into_iter()
+        .filter_map(|segment| match segment {
+            Segment::Placeholder(name) => name.strip_prefix(ARG_PREFIX).map(String::from),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The names of the placeholders of `text`, as written between `${` and `}`, in order.
+pub fn find_placeholders(text: &str) -> Vec<String> {
+    segments(text)
+        .into_iter()
+        .filter_map(|segment| match segment {
+            Segment::Placeholder(name) => Some(name.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// What `interpolate_as` does with a placeholder that has no value.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UnknownPlaceholders {
+    /// Leave it as it is, for the shell to expand `${HOME}`
+    #[default]
+    Keep,
+    /// Replace it with nothing
+    Empty,
+    /// Refuse to run the command
+    Error,
+}
+
+impl UnknownPlaceholders {
+    pub fn is_default(&self) -> bool {
+        *self == UnknownPlaceholders::default()
+    }
+}
+
+/// A part of a string with placeholders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Segment<'t> {
+    Text(&'t str),
+    /// What's between `${` and `}`
+    Placeholder(&'t str),
+}
+
+/// Splits `text` into text and placeholders. `$${name}` is the literal text `${name}`, as is an
+/// unterminated `${`.
+fn segments(text: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("${") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            break;
+        };
+        if rest[..start].ends_with('$') {
+            segments.push(Segment::Text(&rest[..start - 1]));
+            segments.push(Segment::Text(&rest[start..start + end + 3]));
+        } else {
+            segments.push(Segment::Text(&rest[..start]));
+            segments.push(Segment::Placeholder(&after[..end]));
+        }
+        rest = &after[end + 1..];
+    }
+
+    segments.push(Segment::Text(rest));
+    segments
+}
+
+/// Why the placeholders of `text` can't be read: a `${` without its `}`, an empty `${}`, a
+/// `${` inside another or an input without a name. Names that have no value aren't checked.
+pub fn check_syntax(text: &str) -> Result<(), String> {
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        let escaped = rest[..start].ends_with('$');
+        let after = &rest[start + 2..];
+        if escaped {
+            rest = after;
+            continue;
+        }
+        let Some(end) = after.find('}') else {
+            return Err(format!("'${{' without a closing '}}' in '{}'", text));
+        };
+        let name = &after[..end];
+        if name.trim().is_empty() {
+            return Err(format!("empty placeholder in '{}'", text));
+        }
+        if name.contains("${") {
+            return Err(format!("placeholder inside a placeholder in '{}'", text));
+        }
+        if name.starts_with(INPUT_PREFIX) && Input::parse(name).is_none() {
+            return Err(format!(
+                "'${{{}}}' is not a valid input in '{}'",
+                name, text
+            ));
+        }
+        rest = &after[end + 1..];
+    }
+    Ok(())
+}
+
+/// Values substituted for `${name}` placeholders in command strings.
+#[derive(Debug, Clone, Default)]
+pub struct InterpolationContext {
+    variables: HashMap<String, String>,
+    /// The binaries to pick `${binaryName}` from when it has no value
+    binaries: Vec<String>,
+    /// Whether `${binaryName}` was set, over the package's
+    binary_set: bool,
+    /// `${packageName}` and `${binaryName}`, read from the manifest of the package
+    package: Deferred<Option<PackageNames>>,
+    /// `${gitRoot}`, `${gitBranch}` and `${gitShortSha}`, from the git checkout
+    git: Deferred<HashMap<String, String>>,
+}
+
+/// The name and binary of a package, kept on disk between runs, see [`cached_package_names`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct PackageNames {
+    name: String,
+    binary: Result<String, Vec<String>>,
+}
+
+/// Placeholders read from `dir` the first time one of them is needed, as most commands need
+/// none and reading them costs a process or a parse. Clones share what was read.
+#[derive(Debug, Clone)]
+struct Deferred<T> {
+    dir: Option<PathBuf>,
+    value: Arc<OnceLock<T>>,
+}
+
+impl<T> Default for Deferred<T> {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            value: Arc::new(OnceLock::new()),
+        }
+    }
+}
+
+impl<T> Deferred<T> {
+    fn new(dir: &Path) -> Self {
+        Self {
+            dir: Some(dir.to_path_buf()),
+            ..Self::default()
+        }
+    }
+
+    fn get(&self, read: impl FnOnce(&Path) -> T) -> Option<&T> {
+        let dir = self.dir.as_deref()?;
+        Some(self.value.get_or_init(|| read(dir)))
+    }
+}
+
+impl InterpolationContext {
+    /// Builds the context for a run started from `dir`, resolving the crate it belongs to.
+    /// The package and git placeholders are only read once a command refers to them.
+    pub fn from_dir(dir: &Path) -> Self {
+        let workspace_folder = find_manifest_dir(dir).unwrap_or_else(|| dir.to_path_buf());
+
+        let mut context = Self::default();
+        context.set("workspaceFolder", &workspace_folder.to_string_lossy());
+        context.package = Deferred::new(&workspace_folder);
+        context.set_git(dir);
+        debug!(dir = %dir.display(), variables = ?context.variables, "resolved placeholders");
+
+        context
+    }
+
+    /// Sets `${gitRoot}`, `${gitBranch}` and `${gitShortSha}` from the repository `dir` is in,
+    /// leaving out those that don't apply, e.g. the branch on a detached HEAD. Called again to
+    /// pick up a checkout since. They are read once one of them is needed.
+    pub fn set_git(&mut self, dir: &Path) {
+        for name in GIT_VARIABLES {
+            self.variables.remove(name);
+        }
+        self.git = Deferred::new(dir);
+    }
+
+    pub fn set(&mut self, name: &str, value: &str) {
+        self.variables.insert(name.to_string(), value.to_string());
+    }
+
+    /// Sets `${binaryName}` to the binary `binary` resolved to, or leaves it without a value
+    /// and the binaries to pick it from, see [`crate::workspace::default_binary`].
+    pub fn set_binary(&mut self, binary: Result<String, Vec<String>>) {
+        self.binary_set = true;
+        match binary {
+            Ok(name) => {
+                self.set(BINARY_NAME, &name);
+                self.binaries.clear();
+            }
+            Err(binaries) => {
+                self.variables.remove(BINARY_NAME);
+                self.binaries = binaries;
+            }
+        }
+    }
+
+    /// The binaries to pick `${binaryName}` from, empty when it has a value.
+    pub fn binaries(&self) -> &[String] {
+        if self.binary_set {
+            return &self.binaries;
+        }
+        match self.package() {
+            Some(PackageNames {
+                binary: Err(binaries),
+                ..
+            }) => binaries,
+            _ => &[],
+        }
+    }
+
+    fn package(&self) -> Option<&PackageNames> {
+        self.package
+            .get(cached_package_names)
+            .and_then(Option::as_ref)
+    }
+
+    /// Sets the value of `${input:name}`, which otherwise resolves to its default if it has one.
+    pub fn set_input(&mut self, name: &str, value: &str) {
+        self.set(&format!("{}{}", INPUT_PREFIX, name), value);
+    }
+
+    /// Sets the value of `${arg:name}`.
+    pub fn set_arg(&mut self, name: &str, value: &str) {
+        self.set(&format!("{}{}", ARG_PREFIX, name), value);
+    }
+
+    /// The value of `${arg:name}`, if it has one.
+    pub fn arg(&self, name: &str) -> Option<&str> {
+        self.get(&format!("{}{}", ARG_PREFIX, name))
+    }
+
+    /// The value a placeholder resolves to.
+    pub fn value(&self, name: &str) -> Option<String> {
+        if let Some(value) = self.get(name) {
+            return Some(value.to_string());
+        }
+        let input = Input::parse(name)?;
+        self.variables
+            .get(&format!("{}{}", INPUT_PREFIX, input.name))
+            .cloned()
+            .or(input.default)
+    }
+
+    /// The value of the placeholder `name`, reading the package or the git checkout when it's
+    /// one of theirs.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        if let Some(value) = self.variables.get(name) {
+            return Some(value);
+        }
+        match name {
+            "packageName" => self.package().map(|package| package.name.as_str()),
+            BINARY_NAME if !self.binary_set => {
+                self.package()?.binary.as_ref().ok().map(String::as_str)
+            }
+            _ if GIT_VARIABLES.contains(&name) => {
+                self.git.get(read_git)?.get(name).map(String::as_str)
+            }
+            _ => None,
+        }
+    }
+
+    /// Every placeholder with a value and its value, by name, the deferred ones read.
+    pub fn variables(&self) -> BTreeMap<&str, &str> {
+        let deferred = ["packageName", BINARY_NAME]
+            .into_iter()
+            .chain(GIT_VARIABLES)
+            .filter_map(|name| Some((name, self.get(name)?)));
+        self.variables
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .chain(deferred)
+            .collect()
+    }
+
+    /// Replaces every known `${name}` placeholder, leaving unknown ones untouched. An input
+    /// without a value falls back to its default, and `$${name}` stands for `${name}` itself.
+    pub fn interpolate(&self, input: &str) -> String {
+        self.interpolate_as(input, UnknownPlaceholders::Keep)
+    }
+
+    /// Like [`InterpolationContext::interpolate`], with the unknown placeholders left or
+    /// removed as `unknown` says. They are left for [`UnknownPlaceholders::Error`], see
+    /// [`InterpolationContext::unresolved`].
+    pub fn interpolate_as(&self, input: &str, unknown: UnknownPlaceholders) -> String {
+        let mut output = String::with_capacity(input.len());
+        for segment in segments(input) {
+            match segment {
+                Segment::Text(text) => output.push_str(text),
+                Segment::Placeholder(name) => match self.value(name) {
+                    Some(value) => {
+                        trace!(name, value, "substituted placeholder");
+                        output.push_str(&value)
+                    }
+                    None if unknown == UnknownPlaceholders::Empty => {
+                        trace!(name, "removed unknown placeholder");
+                    }
+                    None => {
+                        trace!(name, "left unknown placeholder");
+                        output.push_str(&format!("${{{}}}", name))
+                    }
+                },
+            }
+        }
+        output
+    }
+
+    /// The placeholders of `input` without a value, as written.
+    pub fn unresolved(&self, input: &str) -> Vec<String> {
+        segments(input)
+            .into_iter()
+            .filter_map(|segment| match segment {
+                Segment::Placeholder(name) if self.value(name).is_none() => {
+                    Some(format!("${{{}}}", name))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// The git placeholders of the checkout `dir` is in, none outside of one.
+fn read_git(dir: &Path) -> HashMap<String, String> {
+    let mut variables = HashMap::new();
+    let Some(root) = git::root(dir) else {
+        return variables;
+    };
+    variables.insert("gitRoot".to_string(), root);
+    if let Some(branch) = git::branch(dir) {
+        variables.insert("gitBranch".to_string(), branch);
+    }
+    if let Some(sha) = git::short_sha(dir, "HEAD") {
+        variables.insert("gitShortSha".to_string(), sha);
+    }
+    trace!(dir = %dir.display(), ?variables, "read git placeholders");
+    variables
+}
+
+/// What an entry of the package cache is valid for: the modification times of the manifest
+/// and of the directories cargo discovers binaries in, in nanoseconds, 0 for one missing.
+fn package_stamp(manifest_dir: &Path) -> Vec<u64> {
+    ["Cargo.toml", "src", "src/bin"]
+        .iter()
+        .map(|path| {
+            fs::metadata(manifest_dir.join(path))
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |since| since.as_nanos() as u64)
+        })
+        .collect()
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedPackageNames {
+    stamp: Vec<u64>,
+    names: Option<PackageNames>,
+}
+
+/// The names of the package in `manifest_dir` from the project's store, read again from its
+/// manifest when the manifest or its binaries changed since.
+fn cached_package_names(manifest_dir: &Path) -> Option<PackageNames> {
+    let stamp = package_stamp(manifest_dir);
+    let path = Store::for_workspace(manifest_dir).map(|store| store.path(PACKAGE_CACHE));
+    let cached = path
+        .as_ref()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| toml::from_str::<CachedPackageNames>(&content).ok())
+        .filter(|cached| cached.stamp == stamp);
+    if let Some(cached) = cached {
+        trace!(dir = %manifest_dir.display(), "package names from the cache");
+        return cached.names;
+    }
+
+    let names =
+        read_package_names(manifest_dir).map(|(name, binary)| PackageNames { name, binary });
+    if let Some(path) = path {
+        let cached = CachedPackageNames {
+            stamp,
+            names: names.clone(),
+        };
+        // The cache only saves reading the manifest next time, a run goes on without it
+        if let (Some(dir), Ok(content)) = (path.parent(), toml::to_string(&cached)) {
+            let _ = fs::create_dir_all(dir).and_then(|_| fs::write(&path, content));
+        }
+    }
+    names
+}
+
+fn find_manifest_dir(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|ancestor| ancestor.join("Cargo.toml").is_file())
+        .map(Path::to_path_buf)
+}
+
+fn read_package_names(manifest_dir: &Path) -> Option<(String, Result<String, Vec<String>>)> {
+    let content = fs::read_to_string(manifest_dir.join("Cargo.toml")).ok()?;
+    let manifest: toml::Value = toml::from_str(&content).ok()?;
+
+    let package = manifest.get("package")?;
+    let package_name = package.get("name")?.as_str()?.to_string();
+    let default_run = package
+        .get("default-run")
+        .and_then(|name| name.as_str())
+        .map(String::from);
+    let binary = default_binary(
+        default_run,
+        binaries(&manifest, manifest_dir, &package_name),
+    );
+
+    Some((package_name, binary))
+}
+
+/// The binary targets of the package in `manifest_dir`: its `[[bin]]` tables, then those cargo
+/// discovers in `src/main.rs` and `src/bin/` unless `autobins = false`.
+fn binaries(manifest: &toml::Value, manifest_dir: &Path, package_name: &str) -> Vec<String> {
+    let mut binaries: Vec<String> = manifest
+        .get("bin")
+        .and_then(|bins| bins.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|bin| bin.get("name")?.as_str().map(String::from))
+        .collect();
+    let autobins = manifest
+        .get("package")
+        .and_then(|package| package.get("autobins"))
+        .and_then(|autobins| autobins.as_bool())
+        .unwrap_or(true);
+    if !autobins {
+        return binaries;
+    }
+
+    let mut discovered = Vec::new();
+    if manifest_dir.join("src/main.rs").is_file() {
+        discovered.push(package_name.to_string());
+    }
+    let mut bin_dir: Vec<PathBuf> = fs::read_dir(manifest_dir.join("src/bin"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+    bin_dir.sort();
+    for path in bin_dir {
+        let is_bin = match path.extension() {
+            Some(extension) => extension == "rs",
+            None => path.join("main.rs").is_file(),
+        };
+        if let Some(name) = path.file_stem().filter(|_| is_bin) {
+            discovered.push(name.to_string_lossy().into_owned());
+        }
+    }
+    for name in discovered {
+        if !binaries.contains(&name) {
+            binaries.push(name);
+        }
+    }
+    binaries
+}
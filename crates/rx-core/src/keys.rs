@@ -0,0 +1,162 @@
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
+
+use crate::session::Session;
+
+/// What the keys do, shown when a run controlled from the keyboard starts.
+pub const KEY_HELP: &str = "keys: r restart, q stop, c clear the screen, p pause the output";
+
+/// Claims rx's stdin for a reader of `session`, `false` when another reader has it.
+pub(crate) fn claim_stdin(session: &Session) -> bool {
+    session
+        .stdin_claimed()
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_ok()
+}
+
+/// Whether something reads rx's stdin key by key.
+pub(crate) fn stdin_claimed(session: &Session) -> bool {
+    session.stdin_claimed().load(Ordering::SeqCst)
+}
+
+pub(crate) fn release_stdin(session: &Session) {
+    session.stdin_claimed().store(false, Ordering::SeqCst);
+}
+
+/// What a key pressed during `rx run --keys` does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyAction {
+    /// Stop the run and start it again
+    Restart,
+    Stop,
+    ClearScreen,
+    /// Hold the output back, or show what was held and go on
+    TogglePause,
+}
+
+impl KeyAction {
+    pub fn from_key(key: u8) -> Option<Self> {
+        match key.to_ascii_lowercase() {
+            b'r' => Some(KeyAction::Restart),
+            b'q' => Some(KeyAction::Stop),
+            b'c' => Some(KeyAction::ClearScreen),
+            b'p' => Some(KeyAction::TogglePause),
+            _ => None,
+        }
+    }
+}
+
+/// Reads single keys off rx's terminal, which is switched to unbuffered input without echo
+/// until the reader is dropped. Ctrl-C still interrupts rx as usual.
+pub struct KeyReader {
+    done: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl KeyReader {
+    /// Starts handing the actions of the keys pressed to `on_key`, from a thread of its own.
+    /// An error when stdin isn't a terminal or is already read from in `session`.
+    #[cfg(unix)]
+    pub fn start(
+        session: &Session,
+        on_key: impl Fn(KeyAction) + Send + 'static,
+    ) -> io::Result<KeyReader> {
+        use std::io::IsTerminal;
+
+        if !io::stdin().is_terminal() {
+            return Err(io::Error::other("stdin is not a terminal"));
+        }
+        if !claim_stdin(session) {
+            return Err(io::Error::other("stdin is already read from"));
+        }
+        let saved = match key_mode() {
+            Ok(saved) => saved,
+            Err(err) => {
+                release_stdin(session);
+                return Err(err);
+            }
+        };
+
+        let done = Arc::new(AtomicBool::new(false));
+        let stop = done.clone();
+        let session = session.clone();
+        let handle = thread::spawn(move || {
+            read_keys(&stop, on_key);
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &saved);
+            }
+            release_stdin(&session);
+        });
+        Ok(KeyReader {
+            done,
+            handle: Some(handle),
+        })
+    }
+
+    #[cfg(not(unix))]
+    pub fn start(
+        _session: &Session,
+        _on_key: impl Fn(KeyAction) + Send + 'static,
+    ) -> io::Result<KeyReader> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "keyboard controls are only supported on unix",
+        ))
+    }
+}
+
+impl Drop for KeyReader {
+    fn drop(&mut self) {
+        self.done.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(unix)]
+fn read_keys(done: &AtomicBool, on_key: impl Fn(KeyAction)) {
+    let mut key = 0u8;
+    while !done.load(Ordering::SeqCst) {
+        let mut poll = libc::pollfd {
+            fd: libc::STDIN_FILENO,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        if unsafe { libc::poll(&mut poll, 1, 50) } <= 0 || poll.revents == 0 {
+            continue;
+        }
+        let read = unsafe { libc::read(libc::STDIN_FILENO, (&mut key as *mut u8).cast(), 1) };
+        if read <= 0 {
+            return;
+        }
+        if let Some(action) = KeyAction::from_key(key) {
+            on_key(action);
+        }
+    }
+}
+
+/// Turns off line buffering and echo on rx's terminal, returning the mode to restore.
+#[cfg(unix)]
+fn key_mode() -> io::Result<libc::termios> {
+    unsafe {
+        let mut saved: libc::termios = std::mem::zeroed();
+        if libc::tcgetattr(libc::STDIN_FILENO, &mut saved) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut keys = saved;
+        keys.c_lflag &= !(libc::ICANON | libc::ECHO);
+        keys.c_cc[libc::VMIN] = 1;
+        keys.c_cc[libc::VTIME] = 0;
+        if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &keys) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(saved)
+    }
+}
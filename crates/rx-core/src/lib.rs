@@ -0,0 +1,99 @@
+//! Core of the `rx` cargo runner: the config model, placeholder interpolation and the
+//! process executor, usable without the CLI.
+//!
+//! A [`models::config::Config`] holds one [`models::config::CommandConfig`] per
+//! [`models::config::CommandContext`] (run, test, build, bench, script, lint, fmt, coverage),
+//! each mapping keys to [`models::config::CommandDetails`]. An [`executor::runner::Runner`] resolves and
+//! executes those details, including their `pre_command` chains.
+//!
+//! The crate keeps no global state: the runtime the processes are driven on, the temp files of
+//! the runs and what was found out about the machine live on a [`session::Session`] the
+//! embedder hands to its runners. [`models::config::Config::load`] reads the file and its
+//! includes only, the team config and `scripts_dir` are merged in by the caller.
+//!
+//! ```no_run
+//! use rx_core::{
+//!     builders::config::ConfigBuilder,
+//!     executor::{runner::Runner, shutdown::ShutdownSignal},
+//!     models::config::{CommandContext, CommandType, Config},
+//!     session::Session,
+//! };
+//!
+//! let mut config = Config::default();
+//! let details = ConfigBuilder::new(CommandContext::Script)
+//!     .command_type(CommandType::Shell)
+//!     .command("echo hello")
+//!     .build()
+//!     .unwrap();
+//! config
+//!     .commands
+//!     .get_or_default_config(CommandContext::Script)
+//!     .update_config("hello", details);
+//!
+//! // Embedders trigger the signal themselves to stop a run
+//! let shutdown = ShutdownSignal::new();
+//! let session = Session::new();
+//! let status = Runner::new(&config, CommandContext::Script, shutdown)
+//!     .session(&session)
+//!     .run(Some("hello"), &[])
+//!     .unwrap();
+//! assert!(status.success());
+//! ```
+
+pub mod about;
+pub mod artifacts;
+pub mod backup;
+pub mod bench;
+pub mod build_size;
+pub mod builders;
+pub mod cache;
+pub mod checksum;
+pub mod config_diff;
+pub mod config_lint;
+pub mod daemon;
+pub mod diagnostics;
+pub mod diff;
+pub mod env;
+pub mod errors;
+pub mod events;
+pub mod executor;
+pub mod export;
+pub mod failure;
+pub mod gc;
+pub mod git;
+pub mod githook;
+pub mod helpers;
+pub mod import;
+pub mod interpolation;
+pub mod keys;
+pub mod models;
+pub mod notify;
+pub mod ports;
+pub mod prompt;
+pub mod record;
+pub mod remote_config;
+pub mod rerun;
+pub mod runnables;
+pub mod scripts_dir;
+pub mod search;
+pub mod secret;
+pub mod serve;
+pub mod service;
+pub mod session;
+pub mod source;
+pub mod split;
+pub mod stats;
+pub mod store;
+pub mod strict;
+pub mod test_filter;
+pub mod test_summary;
+pub mod theme;
+pub mod timings;
+#[cfg(all(unix, feature = "tls"))]
+pub mod tls;
+pub mod types;
+pub mod update;
+pub mod validator;
+pub mod watch;
+pub mod workspace;
+pub mod workspace_check;
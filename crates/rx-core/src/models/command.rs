@@ -0,0 +1,93 @@
+use std::fmt::{self, Display};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::checksum::is_sha256;
+
+/// The `command` of a config: a command line, an inline script for shell configs, or a shell
+/// script downloaded from a URL.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum CommandSource {
+    /// `command = "cargo build"`
+    Line(String),
+    /// `command = { url = "https://.../deploy.sh", sha256 = "..." }`
+    Url(ScriptUrl),
+}
+
+/// A shell script rx downloads, runs only when its SHA-256 matches and keeps for the next run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ScriptUrl {
+    pub url: String,
+    /// Hex digest of the script
+    pub sha256: String,
+}
+
+impl Default for CommandSource {
+    fn default() -> Self {
+        CommandSource::Line(String::new())
+    }
+}
+
+impl From<&str> for CommandSource {
+    fn from(line: &str) -> Self {
+        CommandSource::Line(line.to_string())
+    }
+}
+
+impl From<String> for CommandSource {
+    fn from(line: String) -> Self {
+        CommandSource::Line(line)
+    }
+}
+
+impl PartialEq<&str> for CommandSource {
+    fn eq(&self, other: &&str) -> bool {
+        matches!(self, CommandSource::Line(line) if line == other)
+    }
+}
+
+/// The line, or the URL of a downloaded script.
+impl Display for CommandSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CommandSource::Line(line) => write!(f, "{}", line),
+            CommandSource::Url(script) => write!(f, "{}", script.url),
+        }
+    }
+}
+
+impl CommandSource {
+    /// The command line, empty for a downloaded script, which is run from where it's kept.
+    pub fn line(&self) -> &str {
+        match self {
+            CommandSource::Line(line) => line,
+            CommandSource::Url(_) => "",
+        }
+    }
+
+    /// An error for a script URL that isn't https or a digest that isn't a SHA-256.
+    pub fn check(&self) -> Result<(), String> {
+        let CommandSource::Url(script) = self else {
+            return Ok(());
+        };
+        if !script.url.starts_with("https://") {
+            return Err(format!("'{}' is not an https URL", script.url));
+        }
+        if !is_sha256(&script.sha256) {
+            return Err(format!(
+                "sha256 '{}' is not 64 hex digits, `sha256sum` prints it",
+                script.sha256
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn script_url(&self) -> Option<&ScriptUrl> {
+        match self {
+            CommandSource::Url(script) => Some(script),
+            CommandSource::Line(_) => None,
+        }
+    }
+}
@@ -0,0 +1,2943 @@
+use glob::Pattern;
+use regex::Regex;
+use schemars::{generate::SchemaSettings, JsonSchema};
+use serde::ser::{SerializeMap, Serializer};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tracing::debug;
+
+use crate::errors::ConfigError;
+use crate::executor::{composite::parse_step, guard::os_matches, output};
+use crate::githook::GIT_HOOKS;
+use crate::helpers::{read_file, shell_quote};
+use crate::interpolation::{find_args, UnknownPlaceholders};
+use crate::models::command::CommandSource;
+use crate::models::document;
+use crate::models::format::ConfigFormat;
+use crate::models::include::Included;
+use crate::models::params::Params;
+use crate::secret;
+use crate::source::ConfigSource;
+use crate::theme::{escape_sequence, Theme};
+use crate::workspace::ProjectKind;
+
+/// The variable cargo reads the directory it builds into from, set from `target_dir`.
+pub const TARGET_DIR_VARIABLE: &str = "CARGO_TARGET_DIR";
+
+/// The OSes and OS families a config's `platforms` can name, as Rust names them.
+pub const PLATFORMS: [&str; 11] = [
+    "linux",
+    "macos",
+    "windows",
+    "unix",
+    "freebsd",
+    "openbsd",
+    "netbsd",
+    "dragonfly",
+    "solaris",
+    "android",
+    "ios",
+];
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq, Eq, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum CommandContext {
+    Run,
+    Test,
+    Build,
+    Bench,
+    Script,
+    /// `cargo clippy` by default
+    Lint,
+    /// `cargo fmt --check` by default
+    Fmt,
+    /// `cargo llvm-cov` by default
+    Coverage,
+}
+
+impl CommandContext {
+    pub const ALL: [CommandContext; 8] = [
+        CommandContext::Run,
+        CommandContext::Test,
+        CommandContext::Build,
+        CommandContext::Bench,
+        CommandContext::Script,
+        CommandContext::Lint,
+        CommandContext::Fmt,
+        CommandContext::Coverage,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CommandContext::Run => "run",
+            CommandContext::Test => "test",
+            CommandContext::Build => "build",
+            CommandContext::Bench => "bench",
+            CommandContext::Script => "script",
+            CommandContext::Lint => "lint",
+            CommandContext::Fmt => "fmt",
+            CommandContext::Coverage => "coverage",
+        }
+    }
+}
+
+impl Display for CommandContext {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for CommandContext {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "run" => Ok(CommandContext::Run),
+            "test" => Ok(CommandContext::Test),
+            "build" => Ok(CommandContext::Build),
+            "bench" => Ok(CommandContext::Bench),
+            "script" => Ok(CommandContext::Script),
+            "lint" => Ok(CommandContext::Lint),
+            "fmt" => Ok(CommandContext::Fmt),
+            "coverage" => Ok(CommandContext::Coverage),
+            _ => Err(ConfigError::UnknownContext(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CommandType {
+    #[default]
+    Cargo,
+    Shell,
+    /// Runs the configs listed in `steps` instead of a command of its own
+    Composite,
+    /// Run by the executor registered under this name, see
+    /// [`crate::executor::registry::CommandExecutor`]
+    #[serde(untagged)]
+    Custom(String),
+}
+
+impl Display for CommandType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            CommandType::Cargo => "cargo",
+            CommandType::Shell => "shell",
+            CommandType::Composite => "composite",
+            CommandType::Custom(name) => name,
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Only the built-in types, those of registered executors are set in the config file.
+impl FromStr for CommandType {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cargo" => Ok(CommandType::Cargo),
+            "shell" => Ok(CommandType::Shell),
+            "composite" => Ok(CommandType::Composite),
+            _ => Err(ConfigError::UnknownCommandType(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default, PartialEq, Eq)]
+pub struct Config {
+    /// Files adding configs, relative to this one, e.g. `["ci-commands.toml", "team/*.toml"]`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    /// A team config shared from a git repository or an HTTPS URL, whose configs this one's
+    /// are merged over. Fetched with `rx sync`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_config: Option<RemoteConfig>,
+    #[serde(default = "default_commands_on_empty_file")]
+    pub commands: Commands,
+    #[serde(default, skip_serializing_if = "Hooks::is_empty")]
+    pub hooks: Hooks,
+    #[serde(default, skip_serializing_if = "Settings::is_default")]
+    pub settings: Settings,
+    /// Services started and stopped together by name, `rx up dev` for `[groups.dev]`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub groups: BTreeMap<String, ServiceGroup>,
+    /// Environment variables configs take in with `env_sets`, by name, e.g.
+    /// `envsets.db = { DATABASE_URL = "postgres://localhost/app" }`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub envsets: BTreeMap<String, EnvSet>,
+    /// Where the runs of configs with `notify_on` are reported
+    #[serde(default, skip_serializing_if = "Notifications::is_empty")]
+    pub notifications: Notifications,
+    /// Settings shared by the configs whose keys start with `<name>/`, `[namespaces.api]` for
+    /// `api/run-server` and `api/migrate`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub namespaces: BTreeMap<String, Namespace>,
+    #[serde(skip)]
+    pub(crate) included: Included,
+}
+
+/// A bundle of environment variables of `[envsets]`, written like the `env` of a config.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct EnvSet {
+    #[serde(serialize_with = "serialize_env", deserialize_with = "deserialize_env")]
+    #[schemars(with = "BTreeMap<String, EnvValue>")]
+    pub vars: BTreeMap<String, String>,
+}
+
+/// `[namespaces.<name>]`, what the configs of a sub-project of a monorepo share.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default, PartialEq, Eq)]
+pub struct Namespace {
+    /// Where the configs of the namespace run when they don't set a `working_directory`,
+    /// e.g. `"${workspaceFolder}/services/api"`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_directory: Option<String>,
+}
+
+/// `[notifications]`, the webhooks told about the runs of configs with `notify_on`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default, PartialEq, Eq)]
+pub struct Notifications {
+    /// URLs a short message is posted to as JSON: Slack and Discord incoming webhooks, or any
+    /// endpoint taking `{"text": "..."}`. A URL can be encrypted or kept in the keychain like
+    /// an `env` value
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<String>,
+}
+
+impl Notifications {
+    pub fn is_empty(&self) -> bool {
+        self.webhooks.is_empty()
+    }
+
+    /// Why `webhooks` can't be posted to, a URL that isn't HTTP(S).
+    pub fn check(&self) -> Result<(), String> {
+        match self.webhooks.iter().find(|url| {
+            !secret::is_sealed(url) && !url.starts_with("https://") && !url.starts_with("http://")
+        }) {
+            Some(url) => Err(format!("'{}' is not an http:// or https:// URL", url)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Outcome of a run the `[notifications]` webhooks are told about.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NotifyOn {
+    Success,
+    Failure,
+}
+
+/// Global options, individual configs can override most of them.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default, PartialEq, Eq)]
+pub struct Settings {
+    /// Send a desktop notification when a run finishes
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub notify: bool,
+    /// Percent slowdown `rx bench --compare` reports as a regression, 5 when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bench_threshold: Option<u32>,
+    /// Colors of rx's own messages
+    #[serde(default, skip_serializing_if = "Theme::is_default")]
+    pub theme: Theme,
+    /// Backups kept of the config file, taken before each change rx makes to it, 20 when
+    /// unset and 0 to turn them off
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup_limit: Option<usize>,
+    /// Reject unknown fields in the config files and running deprecated configs, as `--strict`
+    /// does
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub strict: bool,
+    /// Run cargo without network access: cargo configs get `--offline`, and every config
+    /// `CARGO_NET_OFFLINE=true`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub offline: bool,
+    /// Who `rx secret set` encrypts values to, age recipients (`age1...` or SSH public keys) or
+    /// GPG key ids, e.g. the keys of everyone on the team
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secret_recipients: Vec<String>,
+    /// How `rx run path/to/file.rs` runs a standalone Rust file
+    #[serde(default, skip_serializing_if = "SingleFile::is_default")]
+    pub single_file: SingleFile,
+    /// What `${name}` placeholders without a value become in the command and params of a
+    /// config: `keep` (as they are, the default), `empty` or `error`. `$${name}` is always the
+    /// text `${name}`
+    #[serde(default, skip_serializing_if = "UnknownPlaceholders::is_default")]
+    pub unknown_placeholders: UnknownPlaceholders,
+    /// Lines of stderr the summary of a failed run shows, 10 when unset and 0 to show only what
+    /// failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary_lines: Option<usize>,
+    /// Processes rx runs at a time across the runs of a daemon or dashboard and the parallel
+    /// steps, matrix cells and dependencies of a run, the others queue for their turn. No
+    /// limit when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_runs: Option<usize>,
+}
+
+/// Seconds a fetched team config is used before it's fetched again, unless `ttl` says otherwise.
+pub const DEFAULT_REMOTE_TTL: u64 = 86_400;
+
+/// `[remote_config]`, where the team config is fetched from: a file served over HTTPS with
+/// `url`, or a file of a git repository with `git`. Only its configs are merged, like those of
+/// an included file, with the entries of the same key in this config taking precedence.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default, PartialEq, Eq)]
+pub struct RemoteConfig {
+    /// HTTPS URL of the file, e.g. `https://example.com/team/rx.toml`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// URL of the git repository holding the file, e.g. `git@github.com:team/rx-config.git`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<String>,
+    /// The file in the repository, `rx.toml` when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Branch or tag of the repository, its default branch when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rev: Option<String>,
+    /// Seconds the fetched copy is used before rx fetches it again, a day when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<u64>,
+}
+
+impl RemoteConfig {
+    /// Why the source can't be fetched as written.
+    pub fn check(&self) -> Result<(), String> {
+        match (&self.url, &self.git) {
+            (Some(_), Some(_)) => Err("give either url or git, not both".to_string()),
+            (None, None) => Err("give the url or the git repository to fetch from".to_string()),
+            (Some(url), None) if !url.starts_with("https://") => {
+                Err(format!("'{}' is not an https:// URL", url))
+            }
+            (Some(_), None) if self.path.is_some() || self.rev.is_some() => {
+                Err("path and rev are those of a git repository".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The command line a standalone Rust file runs with unless `[settings.single_file]` gives
+/// one: cargo's own script support, nightly only for now.
+pub const SINGLE_FILE_COMMAND: &str = "cargo +nightly -Zscript ${file}";
+
+/// `[settings.single_file]`, the defaults of `rx run path/to/file.rs` for scratch files and
+/// examples outside a crate.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default, PartialEq, Eq)]
+pub struct SingleFile {
+    /// Command line running the file, `${file}` standing for its path, e.g.
+    /// `rust-script ${file}`. `cargo +nightly -Zscript ${file}` when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+}
+
+impl SingleFile {
+    pub fn is_default(&self) -> bool {
+        self == &SingleFile::default()
+    }
+
+    /// Whether `rx run key` means the standalone file `key` rather than a config.
+    pub fn applies(key: &str) -> bool {
+        key.ends_with(".rs") && Path::new(key).is_file()
+    }
+
+    /// The config running `file`, from the current directory with the arguments after `--`
+    /// passed on to it.
+    pub fn details(&self, file: &str) -> CommandDetails {
+        let command = self.command.as_deref().unwrap_or(SINGLE_FILE_COMMAND);
+        CommandDetails {
+            command_type: CommandType::Shell,
+            command: command.replace("${file}", &shell_quote(file)).into(),
+            env: self.env.clone().into_iter().collect(),
+            working_directory: String::new(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Services `rx up` starts and `rx down` stops together, `[groups.dev]`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default, PartialEq, Eq)]
+pub struct ServiceGroup {
+    /// Keys of script configs with `service = true`, started after those they depend on
+    #[serde(default)]
+    pub services: Vec<String>,
+}
+
+impl Settings {
+    pub fn is_default(&self) -> bool {
+        self == &Settings::default()
+    }
+}
+
+/// Commands run around every rx invocation. Entries are either a `context::key` reference to a
+/// config or an inline shell line.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default, PartialEq, Eq)]
+pub struct Hooks {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub before_each: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub after_each: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub on_success: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub on_failure: Vec<String>,
+    /// Configs run by the git hooks `rx hook install` writes, by hook name such as `pre-commit`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub git: BTreeMap<String, GitHook>,
+}
+
+impl Hooks {
+    pub fn is_empty(&self) -> bool {
+        self.before_each.is_empty()
+            && self.after_each.is_empty()
+            && self.on_success.is_empty()
+            && self.on_failure.is_empty()
+            && self.git.is_empty()
+    }
+
+    /// Each hook list with its name in the config file.
+    pub fn entries(&self) -> [(&'static str, &Vec<String>); 4] {
+        [
+            ("before_each", &self.before_each),
+            ("after_each", &self.after_each),
+            ("on_success", &self.on_success),
+            ("on_failure", &self.on_failure),
+        ]
+    }
+
+    /// Drops the entries that are exactly `reference`, a `context::key` label.
+    pub fn remove_reference(&mut self, reference: &str) {
+        for entries in [
+            &mut self.before_each,
+            &mut self.after_each,
+            &mut self.on_success,
+            &mut self.on_failure,
+        ]
+        .into_iter()
+        .chain(self.git.values_mut().map(|hook| &mut hook.run))
+        {
+            entries.retain(|entry| entry != reference);
+        }
+    }
+
+    /// Points `context::old_key` references at `new_key`, inline entries are left alone.
+    pub fn rename_reference(&mut self, context: CommandContext, old_key: &str, new_key: &str) {
+        let old_reference = format!("{}::{}", context, old_key);
+        let new_reference = format!("{}::{}", context, new_key);
+
+        for entries in [
+            &mut self.before_each,
+            &mut self.after_each,
+            &mut self.on_success,
+            &mut self.on_failure,
+        ]
+        .into_iter()
+        .chain(self.git.values_mut().map(|hook| &mut hook.run))
+        {
+            for entry in entries.iter_mut().filter(|entry| **entry == old_reference) {
+                *entry = new_reference.clone();
+            }
+        }
+    }
+}
+
+/// The configs a git hook runs, `[hooks.git.pre-commit]`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default, PartialEq, Eq)]
+pub struct GitHook {
+    /// `context::key` labels, or a bare context for its `default`
+    pub run: Vec<String>,
+    /// Run them all at once instead of one after another until one fails
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub parallel: bool,
+}
+
+impl GitHook {
+    /// The configs as a composite config, each a `context::key` step.
+    pub fn to_composite(&self, config: &Config) -> Result<CommandDetails, ConfigError> {
+        let steps = self
+            .run
+            .iter()
+            .map(|entry| {
+                let (context, key) = match entry.split_once("::") {
+                    Some((context, key)) => (context.parse::<CommandContext>()?, key),
+                    None => {
+                        let context = entry.parse::<CommandContext>()?;
+                        let key = config
+                            .commands
+                            .get_config(context)
+                            .and_then(|command_config| {
+                                command_config.default.resolve(Path::new(""))
+                            })
+                            .ok_or_else(|| {
+                                ConfigError::NoDefaultKey(context.to_string(), ".".to_string())
+                            })?;
+                        (context, key)
+                    }
+                };
+                let exists = config
+                    .commands
+                    .get_config(context)
+                    .is_some_and(|command_config| command_config.configs.contains_key(key));
+                let label = format!("{}::{}", context, key);
+                if !exists {
+                    return Err(ConfigError::ConfigKeyNotFound(label));
+                }
+                Ok(label)
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(CommandDetails {
+            command_type: CommandType::Composite,
+            steps,
+            parallel: self.parallel,
+            ..Default::default()
+        })
+    }
+}
+
+fn default_commands_on_empty_file() -> Commands {
+    Commands {
+        run: Some(CommandConfig::with_context("run")),
+        test: Some(CommandConfig::with_context("test")),
+        build: Some(CommandConfig::with_context("build")),
+        bench: Some(CommandConfig::with_context("bench")),
+        script: None,
+        lint: Some(CommandConfig::with_context("lint")),
+        fmt: Some(CommandConfig::with_context("fmt")),
+        coverage: Some(CommandConfig::with_context("coverage")),
+    }
+}
+
+impl Config {
+    /// A JSON Schema (draft-07, what taplo reads) of the config file, for editors to complete
+    /// and validate it.
+    pub fn json_schema() -> String {
+        let mut schema = SchemaSettings::draft07()
+            .into_generator()
+            .into_root_schema_for::<Config>();
+        schema.insert("title".to_string(), "rx config".into());
+        serde_json::to_string_pretty(&schema).expect("a schema serializes to JSON")
+    }
+
+    /// The config `rx init` writes: the defaults, minus `run` for a library, and acting on
+    /// every member of a workspace.
+    pub fn for_project(kind: ProjectKind) -> Config {
+        let mut config = Config::default();
+        let commands = &mut config.commands;
+        match kind {
+            ProjectKind::Bin => {}
+            ProjectKind::Lib => commands.run = None,
+            ProjectKind::Workspace => {
+                for (command_config, params) in [
+                    (&mut commands.test, "--workspace"),
+                    (&mut commands.build, "--workspace"),
+                    (&mut commands.bench, "--workspace"),
+                    (&mut commands.lint, "--workspace --all-targets"),
+                    (&mut commands.fmt, "--all --check"),
+                ] {
+                    if let Some(details) = command_config
+                        .as_mut()
+                        .and_then(|command_config| command_config.configs.get_mut("default"))
+                    {
+                        details.params = Params::from(params);
+                    }
+                }
+            }
+        }
+        config
+    }
+
+    /// Reads `path`, the file [`ConfigSource::resolve`] finds when `None`, with its includes.
+    /// The team config and the scripts of `scripts_dir` are merged in only when asked, see
+    /// [`Config::merge_remote`] and [`Config::merge_scripts_dir`].
+    pub fn load(path: Option<PathBuf>) -> Result<Config, Box<dyn Error>> {
+        let file_path = ConfigSource::resolve(path)?.path().to_path_buf();
+        debug!(path = %file_path.display(), "loading config");
+        let mut config: Config =
+            ConfigFormat::from_path(&file_path).parse(&read_file(&file_path)?)?;
+        config.resolve_includes(&file_path)?;
+
+        Ok(config)
+    }
+
+    /// Writes the main file to `path`, the file [`ConfigSource::resolve`] finds when `None`.
+    pub fn save(&self, path: Option<PathBuf>) -> Result<(), Box<dyn Error>> {
+        ConfigSource::resolve(path)?.save(self)
+    }
+
+    /// The exact content `save` writes, used to preview changes before touching the file
+    pub fn to_toml_string(&self) -> Result<String, Box<dyn Error>> {
+        self.to_string_as(ConfigFormat::Toml)
+    }
+
+    /// The exact content `save` writes to `path`, in the format of its extension. A TOML file
+    /// that exists is patched rather than rewritten, only where this config differs from the
+    /// one the file holds, keeping its comments and formatting, see [`document::patch`].
+    pub fn to_file_string(&self, path: &Path) -> Result<String, Box<dyn Error>> {
+        let format = ConfigFormat::from_path(path);
+        let updated = self.to_string_as(format)?;
+        match (format, fs::read_to_string(path)) {
+            (ConfigFormat::Toml, Ok(current)) => {
+                let loaded = format
+                    .parse::<Config>(&current)
+                    .ok()
+                    .and_then(|loaded| loaded.to_string_as(format).ok())
+                    .unwrap_or_default();
+                Ok(document::patch(&current, &loaded, &updated))
+            }
+            _ => Ok(updated),
+        }
+    }
+
+    /// The main file of the config written as `format`, its includes left as references.
+    pub fn to_string_as(&self, format: ConfigFormat) -> Result<String, Box<dyn Error>> {
+        // We need Config Struct and all Other Fields (struct or enum) to be impl Serialize
+        Ok(format.to_string(&self.main_file_config()?)?)
+    }
+
+    /// Checks what deserializing can't: every context's `default` and `pre_command` keys refer
+    /// to existing entries of that context.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        for context in CommandContext::ALL {
+            let Some(command_config) = self.commands.get_config(context) else {
+                continue;
+            };
+            if !command_config.configs.is_empty() {
+                if let Some(missing) = command_config
+                    .default
+                    .keys()
+                    .into_iter()
+                    .find(|key| !command_config.configs.contains_key(*key))
+                {
+                    return Err(ConfigError::ConfigKeyNotFound(format!(
+                        "{}::{}",
+                        context, missing
+                    )));
+                }
+            }
+            if let DefaultKey::ByPath(patterns) = &command_config.default {
+                if let Some((pattern, err)) = patterns
+                    .keys()
+                    .find_map(|pattern| Some((pattern, Pattern::new(pattern).err()?)))
+                {
+                    return Err(ConfigError::InvalidValue(
+                        format!("commands.{}.default", context),
+                        format!("invalid pattern '{}': {}", pattern, err.msg),
+                    ));
+                }
+            }
+            if let Some(Err(reason)) = command_config.coverage.as_ref().map(CoverageOptions::check)
+            {
+                return Err(ConfigError::InvalidValue(
+                    format!("commands.{}.coverage", context),
+                    reason,
+                ));
+            }
+            for (key, details) in &command_config.configs {
+                if let Some((name, _)) = details.matrix.iter().find(|(_, values)| values.is_empty())
+                {
+                    return Err(ConfigError::InvalidValue(
+                        format!("commands.{}.configs.{}.matrix.{}", context, key, name),
+                        "a matrix dimension needs at least one value".to_string(),
+                    ));
+                }
+                if details.pty
+                    && (details.stdin.is_some()
+                        || details.stdin_file.is_some()
+                        || details.output_filter.is_some())
+                {
+                    return Err(ConfigError::InvalidValue(
+                        format!("commands.{}.configs.{}.pty", context, key),
+                        "a pty config reads from and writes to the terminal, it can't have stdin, stdin_file or output_filter"
+                            .to_string(),
+                    ));
+                }
+                if details.requires_ports.contains(&0) {
+                    return Err(ConfigError::InvalidValue(
+                        format!("commands.{}.configs.{}.requires_ports", context, key),
+                        "0 is not a port a command can require".to_string(),
+                    ));
+                }
+                if details.kill_conflicts && details.requires_ports.is_empty() {
+                    return Err(ConfigError::InvalidValue(
+                        format!("commands.{}.configs.{}.kill_conflicts", context, key),
+                        "kill_conflicts needs the ports in requires_ports".to_string(),
+                    ));
+                }
+                if details.service
+                    && (context != CommandContext::Script
+                        || details.command_type == CommandType::Composite)
+                {
+                    return Err(ConfigError::InvalidValue(
+                        format!("commands.{}.configs.{}.service", context, key),
+                        "only script configs running a command of their own can be services"
+                            .to_string(),
+                    ));
+                }
+                if let Some(lock) = &details.lock {
+                    if !is_lock_name(lock) {
+                        return Err(ConfigError::InvalidValue(
+                            format!("commands.{}.configs.{}.lock", context, key),
+                            "a lock is named with letters, digits, '-', '_' and '.'".to_string(),
+                        ));
+                    }
+                }
+                if !details.on_failure.is_empty() && details.command_type != CommandType::Composite
+                {
+                    return Err(ConfigError::InvalidValue(
+                        format!("commands.{}.configs.{}.on_failure", context, key),
+                        "only composite configs have steps to fail".to_string(),
+                    ));
+                }
+                for (step, on_failure) in &details.on_failure {
+                    let path = format!("commands.{}.configs.{}.on_failure.{}", context, key, step);
+                    if !details.steps.contains(step) {
+                        return Err(ConfigError::InvalidValue(
+                            path,
+                            format!("'{}' is not one of the steps", step),
+                        ));
+                    }
+                    if let OnFailure::Run(target) = on_failure {
+                        let (target_context, target_key) = parse_step(target, context)?;
+                        let exists = self.commands.get_config(target_context).is_some_and(
+                            |command_config| command_config.configs.contains_key(target_key),
+                        );
+                        if !exists {
+                            return Err(ConfigError::InvalidValue(
+                                path,
+                                format!("there is no config '{}' to run", target),
+                            ));
+                        }
+                    }
+                }
+                if details.service {
+                    if let Some(dependency) = details.depends_on.iter().find(|dependency| {
+                        !command_config
+                            .configs
+                            .get(*dependency)
+                            .is_some_and(|details| details.service)
+                    }) {
+                        return Err(ConfigError::InvalidValue(
+                            format!("commands.{}.configs.{}.depends_on", context, key),
+                            format!("'{}' is not a service of the script context", dependency),
+                        ));
+                    }
+                }
+                for dependency in details.depends_on.iter().filter(|_| !details.service) {
+                    let path = format!("commands.{}.configs.{}.depends_on", context, key);
+                    let (dependency_context, dependency_key) = parse_step(dependency, context)
+                        .map_err(|err| ConfigError::InvalidValue(path.clone(), err.to_string()))?;
+                    let reason = match self
+                        .commands
+                        .get_config(dependency_context)
+                        .and_then(|command_config| command_config.configs.get(dependency_key))
+                    {
+                        None => format!("'{}' is not a config", dependency),
+                        Some(details) if details.service => {
+                            format!("'{}' is a service, `rx up` starts it", dependency)
+                        }
+                        Some(_) => continue,
+                    };
+                    return Err(ConfigError::InvalidValue(path, reason));
+                }
+                if let Some(Err(err)) = details.wait_for.as_ref().map(WaitFor::check) {
+                    return Err(ConfigError::InvalidValue(
+                        format!("commands.{}.configs.{}.wait_for", context, key),
+                        err,
+                    ));
+                }
+                if details.stdin.is_some() && details.stdin_file.is_some() {
+                    return Err(ConfigError::InvalidValue(
+                        format!("commands.{}.configs.{}.stdin", context, key),
+                        "stdin and stdin_file can't both be set".to_string(),
+                    ));
+                }
+                if let Err(err) = details.max_log_bytes() {
+                    return Err(ConfigError::InvalidValue(
+                        format!("commands.{}.configs.{}.max_log_size", context, key),
+                        err,
+                    ));
+                }
+                if details
+                    .log_file
+                    .as_ref()
+                    .is_some_and(|log_file| log_file.trim().is_empty())
+                {
+                    return Err(ConfigError::InvalidValue(
+                        format!("commands.{}.configs.{}.log_file", context, key),
+                        "is empty".to_string(),
+                    ));
+                }
+                for (name, spec) in &details.args {
+                    let path = format!("commands.{}.configs.{}.args.{}", context, key, name);
+                    if name.is_empty() || name.contains([':', '}']) {
+                        return Err(ConfigError::InvalidValue(
+                            path,
+                            "is not a name `${arg:..}` can refer to".to_string(),
+                        ));
+                    }
+                    spec.check()
+                        .map_err(|err| ConfigError::InvalidValue(path, err))?;
+                }
+                let text = format!("{} {}", details.command.line(), details.params);
+                if let Some(name) = find_args(&text)
+                    .into_iter()
+                    .find(|name| !details.args.contains_key(name))
+                {
+                    return Err(ConfigError::InvalidValue(
+                        format!("commands.{}.configs.{}.command", context, key),
+                        format!("'${{arg:{}}}' refers to no entry of its args", name),
+                    ));
+                }
+                if let Some(platform) = details
+                    .platforms
+                    .iter()
+                    .find(|platform| !PLATFORMS.contains(&platform.as_str()))
+                {
+                    return Err(ConfigError::InvalidValue(
+                        format!("commands.{}.configs.{}.platforms", context, key),
+                        format!("'{}' is not one of {}", platform, PLATFORMS.join(", ")),
+                    ));
+                }
+                if details.max_output_lines == Some(0) {
+                    return Err(ConfigError::InvalidValue(
+                        format!("commands.{}.configs.{}.max_output_lines", context, key),
+                        "has to be at least 1".to_string(),
+                    ));
+                }
+                if let Err(err) = details.limits.memory_bytes() {
+                    return Err(ConfigError::InvalidValue(
+                        format!("commands.{}.configs.{}.limits.memory", context, key),
+                        err,
+                    ));
+                }
+                if let Some(nice) = details
+                    .limits
+                    .nice
+                    .filter(|nice| !(-20..=19).contains(nice))
+                {
+                    return Err(ConfigError::InvalidValue(
+                        format!("commands.{}.configs.{}.limits.nice", context, key),
+                        format!("{} is outside of -20 to 19", nice),
+                    ));
+                }
+                if details.limits.open_files == Some(0) {
+                    return Err(ConfigError::InvalidValue(
+                        format!("commands.{}.configs.{}.limits.open_files", context, key),
+                        "has to be at least 1".to_string(),
+                    ));
+                }
+                if let Err(err) = details.umask_bits() {
+                    return Err(ConfigError::InvalidValue(
+                        format!("commands.{}.configs.{}.umask", context, key),
+                        err,
+                    ));
+                }
+                if details.sandbox.is_some() {
+                    let reason = if details.command_type == CommandType::Composite {
+                        Some("a composite config has no command of its own to sandbox")
+                    } else if details.container.is_some() || details.remote.is_some() {
+                        Some("a command in a container or on a remote host isn't run locally")
+                    } else {
+                        None
+                    };
+                    if let Some(reason) = reason {
+                        return Err(ConfigError::InvalidValue(
+                            format!("commands.{}.configs.{}.sandbox", context, key),
+                            reason.to_string(),
+                        ));
+                    }
+                }
+                if details.remote.is_some() && (details.elevate || details.run_as.is_some()) {
+                    return Err(ConfigError::InvalidValue(
+                        format!("commands.{}.configs.{}.remote", context, key),
+                        "a remote command runs as the user of `remote = \"user@host\"`, it can't be elevated"
+                            .to_string(),
+                    ));
+                }
+                if details.command_type != CommandType::Cargo
+                    && (details.install_hint.is_some() || details.auto_install)
+                {
+                    return Err(ConfigError::InvalidValue(
+                        format!("commands.{}.configs.{}.install_hint", context, key),
+                        "only cargo configs run a subcommand cargo can install".to_string(),
+                    ));
+                }
+                if let Some(target_dir) = &details.target_dir {
+                    let reason = if details.command_type != CommandType::Cargo {
+                        Some("only cargo configs build into a target directory")
+                    } else if target_dir.trim().is_empty() {
+                        Some("the target directory is empty")
+                    } else if details.env.contains_key(TARGET_DIR_VARIABLE) {
+                        Some("env.CARGO_TARGET_DIR sets it too, keep one of them")
+                    } else {
+                        None
+                    };
+                    if let Some(reason) = reason {
+                        return Err(ConfigError::InvalidValue(
+                            format!("commands.{}.configs.{}.target_dir", context, key),
+                            reason.to_string(),
+                        ));
+                    }
+                }
+                if let Err(err) = details.command.check() {
+                    return Err(ConfigError::InvalidValue(
+                        format!("commands.{}.configs.{}.command", context, key),
+                        err,
+                    ));
+                }
+                if let CommandType::Custom(name) = &details.command_type {
+                    if name.trim().is_empty() || name.contains(char::is_whitespace) {
+                        return Err(ConfigError::InvalidValue(
+                            format!("commands.{}.configs.{}.type", context, key),
+                            format!("'{}' is not a command type name", name),
+                        ));
+                    }
+                }
+                if details.command.script_url().is_some()
+                    && details.command_type != CommandType::Shell
+                {
+                    return Err(ConfigError::InvalidValue(
+                        format!("commands.{}.configs.{}.command", context, key),
+                        "only shell configs run a script from a URL".to_string(),
+                    ));
+                }
+                if let Some(encoding) = details
+                    .output_encoding
+                    .as_ref()
+                    .filter(|encoding| output::encoding(encoding).is_none())
+                {
+                    return Err(ConfigError::InvalidValue(
+                        format!("commands.{}.configs.{}.output_encoding", context, key),
+                        format!(
+                            "unknown encoding '{}', expected a label such as utf-8, windows-1252 or shift_jis",
+                            encoding
+                        ),
+                    ));
+                }
+                if let Err(err) = details.params.check() {
+                    return Err(ConfigError::InvalidValue(
+                        format!("commands.{}.configs.{}.params", context, key),
+                        err,
+                    ));
+                }
+                if let Some(replacement) = details
+                    .replaced_by
+                    .as_ref()
+                    .filter(|replacement| !command_config.configs.contains_key(*replacement))
+                {
+                    return Err(ConfigError::InvalidValue(
+                        format!("commands.{}.configs.{}.replaced_by", context, key),
+                        format!("'{}' is not a key of the {} context", replacement, context),
+                    ));
+                }
+                if !details.notify_on.is_empty() && self.notifications.is_empty() {
+                    return Err(ConfigError::InvalidValue(
+                        format!("commands.{}.configs.{}.notify_on", context, key),
+                        "there is no webhook in [notifications] to post to".to_string(),
+                    ));
+                }
+                if let Some(missing) = details
+                    .pre_command
+                    .iter()
+                    .find(|pre_command| !command_config.configs.contains_key(*pre_command))
+                {
+                    return Err(ConfigError::InvalidPreCommand(format!(
+                        "'{}::{}' has pre_command '{}', which is not a key of the {} context",
+                        context, key, missing, context
+                    )));
+                }
+            }
+        }
+
+        if self.settings.max_concurrent_runs == Some(0) {
+            return Err(ConfigError::InvalidValue(
+                "settings.max_concurrent_runs".to_string(),
+                "at least one run has to be able to start".to_string(),
+            ));
+        }
+        if let Some(Err(err)) = self.remote_config.as_ref().map(RemoteConfig::check) {
+            return Err(ConfigError::InvalidValue("remote_config".to_string(), err));
+        }
+        if let Err(err) = self.notifications.check() {
+            return Err(ConfigError::InvalidValue(
+                "notifications.webhooks".to_string(),
+                err,
+            ));
+        }
+        for (name, namespace) in &self.namespaces {
+            if name.is_empty() || name.starts_with('/') || name.ends_with('/') {
+                return Err(ConfigError::InvalidValue(
+                    format!("namespaces.{}", name),
+                    "a namespace is named without a leading or trailing '/', e.g. 'api'"
+                        .to_string(),
+                ));
+            }
+            if namespace
+                .working_directory
+                .as_ref()
+                .is_some_and(|dir| dir.trim().is_empty())
+            {
+                return Err(ConfigError::InvalidValue(
+                    format!("namespaces.{}.working_directory", name),
+                    "the working directory is empty".to_string(),
+                ));
+            }
+        }
+
+        for (name, hook) in &self.hooks.git {
+            if !GIT_HOOKS.contains(&name.as_str()) {
+                return Err(ConfigError::InvalidValue(
+                    format!("hooks.git.{}", name),
+                    format!(
+                        "unknown git hook, expected one of: {}",
+                        GIT_HOOKS.join(", ")
+                    ),
+                ));
+            }
+            if let Err(err) = hook.to_composite(self) {
+                return Err(ConfigError::InvalidValue(
+                    format!("hooks.git.{}.run", name),
+                    err.to_string(),
+                ));
+            }
+        }
+
+        for context in CommandContext::ALL {
+            let Some(command_config) = self.commands.get_config(context) else {
+                continue;
+            };
+            for (key, details) in &command_config.configs {
+                if let Err(err) = self.env_set_vars(details) {
+                    return Err(ConfigError::InvalidValue(
+                        format!("commands.{}.configs.{}.env_sets", context, key),
+                        err.to_string(),
+                    ));
+                }
+            }
+        }
+
+        let script = self.commands.get_config(CommandContext::Script);
+        for (name, group) in &self.groups {
+            if script.is_some_and(|script| script.configs.contains_key(name)) {
+                return Err(ConfigError::InvalidValue(
+                    format!("groups.{}", name),
+                    "a group can't share its name with a script config".to_string(),
+                ));
+            }
+            if let Some(missing) = group.services.iter().find(|key| {
+                !script
+                    .and_then(|script| script.configs.get(*key))
+                    .is_some_and(|details| details.service)
+            }) {
+                return Err(ConfigError::InvalidValue(
+                    format!("groups.{}.services", name),
+                    format!("'{}' is not a service of the script context", missing),
+                ));
+            }
+        }
+        for context in CommandContext::ALL {
+            let Some(command_config) = self.commands.get_config(context) else {
+                continue;
+            };
+            let keys = command_config
+                .configs
+                .iter()
+                .filter(|(_, details)| !details.service && !details.depends_on.is_empty())
+                .map(|(key, _)| key);
+            for key in keys {
+                self.dependency_order(context, key)?;
+            }
+        }
+        if let Some(script) = script {
+            let mut services: Vec<String> = script
+                .configs
+                .iter()
+                .filter(|(_, details)| details.service)
+                .map(|(key, _)| key.clone())
+                .collect();
+            services.sort();
+            self.service_order(&services)?;
+        }
+
+        if let Some(command) = &self.settings.single_file.command {
+            if !command.contains("${file}") {
+                return Err(ConfigError::InvalidValue(
+                    "settings.single_file.command".to_string(),
+                    "has no ${file} standing for the file to run".to_string(),
+                ));
+            }
+        }
+
+        for (name, style) in self.settings.theme.styles() {
+            escape_sequence(style).map_err(|err| {
+                ConfigError::InvalidValue(format!("settings.theme.{}", name), err)
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// The services `rx up` starts for `names`, keys of script configs or groups standing for
+    /// their services, along with those they depend on, each after its dependencies.
+    pub fn service_order(&self, names: &[String]) -> Result<Vec<String>, ConfigError> {
+        let mut order = Vec::new();
+        for name in names {
+            match self.groups.get(name) {
+                Some(group) => {
+                    for key in &group.services {
+                        self.order_service(key, &mut Vec::new(), &mut order)?;
+                    }
+                }
+                None => self.order_service(name, &mut Vec::new(), &mut order)?,
+            }
+        }
+        Ok(order)
+    }
+
+    /// Adds the dependencies of `key` then `key` to `order`. `chain` holds the services
+    /// depending on `key` being visited, to catch a service depending on itself.
+    fn order_service(
+        &self,
+        key: &String,
+        chain: &mut Vec<String>,
+        order: &mut Vec<String>,
+    ) -> Result<(), ConfigError> {
+        if order.contains(key) {
+            return Ok(());
+        }
+        if let Some(position) = chain.iter().position(|visited| visited == key) {
+            let mut cycle = chain[position..].to_vec();
+            cycle.push(key.clone());
+            return Err(ConfigError::InvalidValue(
+                format!("commands.script.configs.{}.depends_on", key),
+                format!("services depend on each other: {}", cycle.join(" -> ")),
+            ));
+        }
+        let details = self
+            .commands
+            .get_config(CommandContext::Script)
+            .and_then(|script| script.configs.get(key))
+            .ok_or_else(|| ConfigError::ConfigKeyNotFound(format!("script::{}", key)))?;
+
+        chain.push(key.clone());
+        for dependency in &details.depends_on {
+            self.order_service(dependency, chain, order)?;
+        }
+        chain.pop();
+        order.push(key.clone());
+        Ok(())
+    }
+
+    /// The configs `context::key` depends on, along with those they depend on, each after its
+    /// dependencies. `key` itself isn't included.
+    pub fn dependency_order(
+        &self,
+        context: CommandContext,
+        key: &str,
+    ) -> Result<Vec<(CommandContext, String)>, ConfigError> {
+        let mut order = Vec::new();
+        self.order_dependency(context, key, &mut Vec::new(), &mut order)?;
+        order.pop();
+        Ok(order)
+    }
+
+    /// Adds the dependencies of `context::key` then itself to `order`. `chain` holds the labels
+    /// of the configs depending on it being visited, to catch a config depending on itself.
+    fn order_dependency(
+        &self,
+        context: CommandContext,
+        key: &str,
+        chain: &mut Vec<String>,
+        order: &mut Vec<(CommandContext, String)>,
+    ) -> Result<(), ConfigError> {
+        if order
+            .iter()
+            .any(|(ordered, ordered_key)| *ordered == context && ordered_key == key)
+        {
+            return Ok(());
+        }
+        let label = format!("{}::{}", context, key);
+        if let Some(position) = chain.iter().position(|visited| *visited == label) {
+            let mut cycle = chain[position..].to_vec();
+            cycle.push(label);
+            return Err(ConfigError::InvalidValue(
+                format!("commands.{}.configs.{}.depends_on", context, key),
+                format!("configs depend on each other: {}", cycle.join(" -> ")),
+            ));
+        }
+        let details = self
+            .commands
+            .get_config(context)
+            .and_then(|command_config| command_config.configs.get(key))
+            .ok_or_else(|| ConfigError::ConfigKeyNotFound(label.clone()))?;
+
+        chain.push(label);
+        for dependency in &details.depends_on {
+            let (dependency_context, dependency_key) = parse_step(dependency, context)?;
+            self.order_dependency(dependency_context, dependency_key, chain, order)?;
+        }
+        chain.pop();
+        order.push((context, key.to_string()));
+        Ok(())
+    }
+
+    /// Copies a config entry, possibly into another context. `pre_command` keys only refer to
+    /// entries of their own context, so a copy into another context starts without them.
+    pub fn copy_config(
+        &mut self,
+        src_context: CommandContext,
+        src_key: &str,
+        dst_context: CommandContext,
+        dst_key: &str,
+    ) -> Result<(), ConfigError> {
+        if src_context == dst_context {
+            return self
+                .commands
+                .get_config_mut(src_context)
+                .ok_or_else(|| ConfigError::ConfigKeyNotFound(src_key.to_string()))?
+                .duplicate_config(src_key, dst_key);
+        }
+
+        let mut details = self
+            .commands
+            .get_config(src_context)
+            .and_then(|command_config| command_config.configs.get(src_key))
+            .cloned()
+            .ok_or_else(|| ConfigError::ConfigKeyNotFound(src_key.to_string()))?;
+        details.pre_command.clear();
+
+        let destination = self.commands.get_or_default_config(dst_context);
+        if destination.configs.contains_key(dst_key) {
+            return Err(ConfigError::ConfigKeyExists(dst_key.to_string()));
+        }
+        destination.update_config(dst_key, details);
+
+        Ok(())
+    }
+
+    /// The variables of the `env_sets` of `details`, a later set overriding an earlier one.
+    pub fn env_set_vars(
+        &self,
+        details: &CommandDetails,
+    ) -> Result<BTreeMap<String, String>, ConfigError> {
+        let mut vars = BTreeMap::new();
+        for name in &details.env_sets {
+            let env_set = self
+                .envsets
+                .get(name)
+                .ok_or_else(|| ConfigError::UnknownEnvSet(name.clone()))?;
+            vars.extend(env_set.vars.clone());
+        }
+        Ok(vars)
+    }
+
+    /// Whether the value `name` takes from the `env_sets` of `details` is an encrypted secret.
+    pub fn env_set_is_sealed(&self, details: &CommandDetails, name: &str) -> bool {
+        details
+            .env_sets
+            .iter()
+            .rev()
+            .find_map(|set| self.envsets.get(set)?.vars.get(name))
+            .is_some_and(|value| secret::is_sealed(value))
+    }
+
+    /// Renames a config entry of `context`, including references to it from `[hooks]` and
+    /// `[groups]`.
+    pub fn rename_config(
+        &mut self,
+        context: CommandContext,
+        old_key: &str,
+        new_key: &str,
+    ) -> Result<(), ConfigError> {
+        self.commands
+            .get_config_mut(context)
+            .ok_or_else(|| ConfigError::ConfigKeyNotFound(old_key.to_string()))?
+            .rename_config(old_key, new_key)?;
+        self.hooks.rename_reference(context, old_key, new_key);
+        if context == CommandContext::Script {
+            for group in self.groups.values_mut() {
+                for service in &mut group.services {
+                    if service == old_key {
+                        *service = new_key.to_string();
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Labels of the configs tagged `tag`, of `context` or of every context, ordered by context
+    /// then key.
+    pub fn tagged(&self, tag: &str, context: Option<CommandContext>) -> Vec<String> {
+        let mut labels = Vec::new();
+        for current in CommandContext::ALL {
+            if context.is_some_and(|context| context != current) {
+                continue;
+            }
+            let Some(command_config) = self.commands.get_config(current) else {
+                continue;
+            };
+            let keys = command_config
+                .configs
+                .iter()
+                .filter(|(_, details)| details.tags.iter().any(|tagged| tagged == tag))
+                .map(|(key, _)| key);
+            labels.extend(keys.map(|key| format!("{}::{}", current, key)));
+        }
+        labels
+    }
+
+    /// Labels of the configs of `context` whose key matches the glob `pattern`, e.g. `unit-*`
+    /// or `deploy-{staging,prod}`, or starts with a namespace ending in `/`, e.g. `api/`,
+    /// ordered by key.
+    pub fn matching(&self, context: CommandContext, pattern: &str) -> Vec<String> {
+        if pattern.ends_with('/') {
+            return self
+                .commands
+                .get_configs(context)
+                .into_iter()
+                .filter(|key| key.starts_with(pattern))
+                .map(|key| format!("{}::{}", context, key))
+                .collect();
+        }
+        let patterns: Vec<Pattern> = expand_braces(pattern)
+            .iter()
+            .filter_map(|pattern| Pattern::new(pattern).ok())
+            .collect();
+        self.commands
+            .get_configs(context)
+            .into_iter()
+            .filter(|key| patterns.iter().any(|pattern| pattern.matches(key)))
+            .map(|key| format!("{}::{}", context, key))
+            .collect()
+    }
+
+    /// The namespace of `key` and its settings, the longest of `[namespaces]` the key starts
+    /// with followed by `/`, so `api/v2` wins over `api` for `api/v2/serve`.
+    pub fn namespace(&self, key: &str) -> Option<(&str, &Namespace)> {
+        self.namespaces
+            .iter()
+            .filter(|(name, _)| {
+                key.strip_prefix(name.as_str())
+                    .is_some_and(|rest| rest.starts_with('/'))
+            })
+            .max_by_key(|(name, _)| name.len())
+            .map(|(name, namespace)| (name.as_str(), namespace))
+    }
+
+    /// The entries referring to `context::key`, such as `run::release pre_command`,
+    /// `script::ci steps` or `hooks.before_each`.
+    pub fn references(&self, context: CommandContext, key: &str) -> Vec<String> {
+        let label = format!("{}::{}", context, key);
+        let mut references = Vec::new();
+
+        for current in CommandContext::ALL {
+            let Some(command_config) = self.commands.get_config(current) else {
+                continue;
+            };
+            for other in command_config.configs.keys() {
+                let details = &command_config.configs[other];
+                if current == context && other == key {
+                    continue;
+                }
+                if current == context && details.pre_command.contains(key) {
+                    references.push(format!("{}::{} pre_command", current, other));
+                }
+                if details
+                    .steps
+                    .iter()
+                    .any(|step| *step == label || (current == context && step == key))
+                {
+                    references.push(format!("{}::{} steps", current, other));
+                }
+                if details.on_failure.values().any(|on_failure| {
+                    matches!(on_failure, OnFailure::Run(step)
+                        if *step == label || (current == context && step == key))
+                }) {
+                    references.push(format!("{}::{} on_failure", current, other));
+                }
+                if details.output_filter.as_ref() == Some(&label) {
+                    references.push(format!("{}::{} output_filter", current, other));
+                }
+                if current == context && details.replaced_by.as_deref() == Some(key) {
+                    references.push(format!("{}::{} replaced_by", current, other));
+                }
+                if details.depends_on.iter().any(|dependency| {
+                    *dependency == label || (current == context && dependency == key)
+                }) {
+                    references.push(format!("{}::{} depends_on", current, other));
+                }
+            }
+        }
+
+        for (name, entries) in self.hooks.entries() {
+            if entries.contains(&label) {
+                references.push(format!("hooks.{}", name));
+            }
+        }
+        for (name, hook) in &self.hooks.git {
+            if hook.run.contains(&label) {
+                references.push(format!("hooks.git.{}", name));
+            }
+        }
+        if context == CommandContext::Script {
+            for (name, group) in &self.groups {
+                if group.services.iter().any(|service| service == key) {
+                    references.push(format!("groups.{}", name));
+                }
+            }
+        }
+        references
+    }
+
+    /// Adds the entries of `other`, context by context, leaving those both define the same way
+    /// as they are. A context without entries takes `other`'s as a whole, `default` included.
+    /// An entry `other` defines differently is an error unless `overwrite`, which takes
+    /// `other`'s. Returns the labels of the entries added or replaced.
+    pub fn merge(&mut self, other: &Config, overwrite: bool) -> Result<Vec<String>, ConfigError> {
+        let mut merged = Vec::new();
+        for context in CommandContext::ALL {
+            let Some(theirs) = other.commands.get_config(context) else {
+                continue;
+            };
+            let keys: Vec<&String> = theirs.configs.keys().collect();
+            let ours = self.commands.get_or_default_config(context);
+            if ours.configs.is_empty() {
+                *ours = theirs.clone();
+                merged.extend(keys.iter().map(|key| format!("{}::{}", context, key)));
+                continue;
+            }
+            for key in keys {
+                let details = &theirs.configs[key];
+                match ours.configs.get(key) {
+                    Some(existing) if existing == details => continue,
+                    Some(_) if !overwrite => {
+                        return Err(ConfigError::MergeConflict(format!("{}::{}", context, key)))
+                    }
+                    _ => {}
+                }
+                ours.update_config(key, details.clone());
+                merged.push(format!("{}::{}", context, key));
+            }
+        }
+        Ok(merged)
+    }
+
+    /// Removes `context::key`, which is refused while other entries refer to it unless
+    /// `removal` clears those references or leaves them dangling. Returns the references found.
+    pub fn remove_config(
+        &mut self,
+        context: CommandContext,
+        key: &str,
+        removal: Removal,
+    ) -> Result<Vec<String>, ConfigError> {
+        let exists = self
+            .commands
+            .get_config(context)
+            .is_some_and(|command_config| command_config.configs.contains_key(key));
+        if !exists {
+            return Err(ConfigError::ConfigKeyNotFound(format!(
+                "{}::{}",
+                context, key
+            )));
+        }
+
+        let references = self.references(context, key);
+        if !references.is_empty() && removal == Removal::Safe {
+            return Err(ConfigError::KeyInUse(
+                format!("{}::{}", context, key),
+                references,
+            ));
+        }
+        if removal == Removal::Cascade {
+            self.clear_references(context, key);
+        }
+        if let Some(command_config) = self.commands.get_config_mut(context) {
+            command_config.remove_config(key);
+        }
+
+        Ok(references)
+    }
+
+    fn clear_references(&mut self, context: CommandContext, key: &str) {
+        let label = format!("{}::{}", context, key);
+        for current in CommandContext::ALL {
+            let Some(command_config) = self.commands.get_config_mut(current) else {
+                continue;
+            };
+            for details in command_config.configs.values_mut() {
+                if current == context {
+                    details.pre_command.remove(key);
+                }
+                details
+                    .steps
+                    .retain(|step| *step != label && (current != context || step != key));
+                details.on_failure.retain(|step, on_failure| {
+                    details.steps.contains(step)
+                        && !matches!(on_failure, OnFailure::Run(step)
+                            if *step == label || (current == context && step == key))
+                });
+                if details.output_filter.as_ref() == Some(&label) {
+                    details.output_filter = None;
+                }
+                if current == context && details.replaced_by.as_deref() == Some(key) {
+                    details.replaced_by = None;
+                }
+                details.depends_on.retain(|dependency| {
+                    *dependency != label && (current != context || dependency != key)
+                });
+            }
+        }
+        self.hooks.remove_reference(&label);
+        if context == CommandContext::Script {
+            for group in self.groups.values_mut() {
+                group.services.retain(|service| service != key);
+            }
+        }
+    }
+}
+
+/// How [`Config::remove_config`] treats the entries referring to the removed one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Removal {
+    /// Refuse to remove a referenced entry
+    #[default]
+    Safe,
+    /// Remove the references along with the entry
+    Cascade,
+    /// Remove the entry and leave its references dangling
+    Force,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq, Eq)]
+pub struct Commands {
+    pub run: Option<CommandConfig>,
+    pub test: Option<CommandConfig>,
+    pub build: Option<CommandConfig>,
+    pub bench: Option<CommandConfig>,
+    pub script: Option<CommandConfig>,
+    #[serde(
+        default = "default_lint_config",
+        skip_serializing_if = "is_default_lint_config"
+    )]
+    pub lint: Option<CommandConfig>,
+    #[serde(
+        default = "default_fmt_config",
+        skip_serializing_if = "is_default_fmt_config"
+    )]
+    pub fmt: Option<CommandConfig>,
+    #[serde(
+        default = "default_coverage_config",
+        skip_serializing_if = "is_default_coverage_config"
+    )]
+    pub coverage: Option<CommandConfig>,
+}
+
+/// Whether `key` is a pattern of keys rather than a key: it has `*`, `?`, `[...]` or a
+/// `{a,b}` alternation, or is a namespace ending in `/`.
+pub fn is_key_pattern(key: &str) -> bool {
+    key.contains(['*', '?', '[']) || key.ends_with('/') || expand_braces(key).len() > 1
+}
+
+/// `pattern` with its `{a,b}` alternations expanded, nested ones included. A `{` without its
+/// `}` or a `,` is kept as it is.
+fn expand_braces(pattern: &str) -> Vec<String> {
+    let Some(start) = pattern.find('{') else {
+        return vec![pattern.to_string()];
+    };
+    let mut depth = 0;
+    let mut commas = Vec::new();
+    let mut end = None;
+    for (index, char) in pattern[start..].char_indices() {
+        match char {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    end = Some(start + index);
+                    break;
+                }
+            }
+            ',' if depth == 1 => commas.push(start + index),
+            _ => {}
+        }
+    }
+    let (prefix, rest) = pattern.split_at(start + 1);
+    let Some(end) = end.filter(|_| !commas.is_empty()) else {
+        return expand_braces(rest)
+            .into_iter()
+            .map(|rest| format!("{}{}", prefix, rest))
+            .collect();
+    };
+
+    let mut bounds = vec![start];
+    bounds.extend(commas);
+    bounds.push(end);
+    let suffix = &pattern[end + 1..];
+    bounds
+        .windows(2)
+        .flat_map(|bound| {
+            let alternative = &pattern[bound[0] + 1..bound[1]];
+            expand_braces(&format!("{}{}{}", &pattern[..start], alternative, suffix))
+        })
+        .collect()
+}
+
+// Unlike the other contexts, lint, fmt and coverage came later and are filled in for existing
+// files, at runtime only: left as they are, they're never written back
+fn default_lint_config() -> Option<CommandConfig> {
+    Some(CommandConfig::with_context("lint"))
+}
+
+fn default_fmt_config() -> Option<CommandConfig> {
+    Some(CommandConfig::with_context("fmt"))
+}
+
+fn default_coverage_config() -> Option<CommandConfig> {
+    Some(CommandConfig::with_context("coverage"))
+}
+
+fn is_default_lint_config(config: &Option<CommandConfig>) -> bool {
+    *config == default_lint_config()
+}
+
+fn is_default_fmt_config(config: &Option<CommandConfig>) -> bool {
+    *config == default_fmt_config()
+}
+
+fn is_default_coverage_config(config: &Option<CommandConfig>) -> bool {
+    *config == default_coverage_config()
+}
+
+impl Default for Commands {
+    fn default() -> Self {
+        Commands {
+            run: Some(CommandConfig::with_context("run")),
+            test: Some(CommandConfig::with_context("test")),
+            build: Some(CommandConfig::with_context("build")),
+            bench: Some(CommandConfig::with_context("bench")),
+            script: None,
+            lint: Some(CommandConfig::with_context("lint")),
+            fmt: Some(CommandConfig::with_context("fmt")),
+            coverage: Some(CommandConfig::with_context("coverage")),
+        }
+    }
+}
+
+impl Commands {
+    pub fn get_config(&self, context: CommandContext) -> Option<&CommandConfig> {
+        match context {
+            CommandContext::Run => self.run.as_ref(),
+            CommandContext::Test => self.test.as_ref(),
+            CommandContext::Build => self.build.as_ref(),
+            CommandContext::Bench => self.bench.as_ref(),
+            CommandContext::Script => self.script.as_ref(),
+            CommandContext::Lint => self.lint.as_ref(),
+            CommandContext::Fmt => self.fmt.as_ref(),
+            CommandContext::Coverage => self.coverage.as_ref(),
+        }
+    }
+
+    pub fn get_config_mut(&mut self, context: CommandContext) -> Option<&mut CommandConfig> {
+        match context {
+            CommandContext::Run => self.run.as_mut(),
+            CommandContext::Test => self.test.as_mut(),
+            CommandContext::Build => self.build.as_mut(),
+            CommandContext::Bench => self.bench.as_mut(),
+            CommandContext::Script => self.script.as_mut(),
+            CommandContext::Lint => self.lint.as_mut(),
+            CommandContext::Fmt => self.fmt.as_mut(),
+            CommandContext::Coverage => self.coverage.as_mut(),
+        }
+    }
+
+    pub fn get_configs(&self, context: CommandContext) -> Vec<String> {
+        match context {
+            CommandContext::Run => self
+                .run
+                .as_ref()
+                .map_or(vec![], |config| config.configs.keys().cloned().collect()),
+            CommandContext::Test => self
+                .test
+                .as_ref()
+                .map_or(vec![], |config| config.configs.keys().cloned().collect()),
+            CommandContext::Build => self
+                .build
+                .as_ref()
+                .map_or(vec![], |config| config.configs.keys().cloned().collect()),
+            CommandContext::Bench => self
+                .bench
+                .as_ref()
+                .map_or(vec![], |config| config.configs.keys().cloned().collect()),
+            CommandContext::Script => self
+                .script
+                .as_ref()
+                .map_or(vec![], |config| config.configs.keys().cloned().collect()),
+            CommandContext::Lint => self
+                .lint
+                .as_ref()
+                .map_or(vec![], |config| config.configs.keys().cloned().collect()),
+            CommandContext::Fmt => self
+                .fmt
+                .as_ref()
+                .map_or(vec![], |config| config.configs.keys().cloned().collect()),
+            CommandContext::Coverage => self
+                .coverage
+                .as_ref()
+                .map_or(vec![], |config| config.configs.keys().cloned().collect()),
+        }
+    }
+
+    pub fn get_or_default_config(&mut self, context: CommandContext) -> &mut CommandConfig {
+        match context {
+            CommandContext::Run => self.run.get_or_insert_with(CommandConfig::default),
+            CommandContext::Test => self.test.get_or_insert_with(CommandConfig::default),
+            CommandContext::Build => self.build.get_or_insert_with(CommandConfig::default),
+            CommandContext::Bench => self.bench.get_or_insert_with(CommandConfig::default),
+            CommandContext::Script => self.script.get_or_insert_with(CommandConfig::default),
+            CommandContext::Lint => self.lint.get_or_insert_with(CommandConfig::default),
+            CommandContext::Fmt => self.fmt.get_or_insert_with(CommandConfig::default),
+            CommandContext::Coverage => self.coverage.get_or_insert_with(CommandConfig::default),
+        }
+    }
+    pub fn set_default_config(
+        &mut self,
+        context: CommandContext,
+        new_default_key: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let command_config = match context {
+            CommandContext::Run => &mut self.run,
+            CommandContext::Test => &mut self.test,
+            CommandContext::Build => &mut self.build,
+            CommandContext::Bench => &mut self.bench,
+            CommandContext::Script => &mut self.script,
+            CommandContext::Lint => &mut self.lint,
+            CommandContext::Fmt => &mut self.fmt,
+            CommandContext::Coverage => &mut self.coverage,
+        };
+
+        if let Some(config) = command_config {
+            if config.configs.contains_key(new_default_key) {
+                config.default = new_default_key.into();
+                Ok(())
+            } else {
+                Err(Box::new(ConfigError::ConfigKeyNotFound(
+                    new_default_key.to_string(),
+                )))
+            }
+        } else {
+            Err(Box::new(ConfigError::ConfigKeyNotFound(
+                new_default_key.to_string(),
+            )))
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq, Eq)]
+pub struct CommandConfig {
+    #[serde(default = "default_command_config")]
+    pub default: DefaultKey,
+    pub configs: BTreeMap<String, CommandDetails>,
+    /// Harness for `cargo test` configs, only read for the `test` context
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runner: Option<TestRunner>,
+    /// Options used when `runner = "nextest"`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nextest: Option<NextestOptions>,
+    /// Report and threshold of `cargo llvm-cov`, only read for the `coverage` context
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coverage: Option<CoverageOptions>,
+    /// Directory whose executable files are script configs named after them, relative to the
+    /// project's `${workspaceFolder}`, e.g. `"scripts/"`. An entry of the same key in the config
+    /// takes precedence. Only read for the `script` context
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scripts_dir: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TestRunner {
+    #[default]
+    Cargo,
+    /// Translate `cargo test` to `cargo nextest run`, falling back when nextest isn't installed
+    Nextest,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CrossRunner {
+    /// Plain cargo, with whatever linker the target is configured with
+    #[default]
+    Native,
+    /// `cross`, building inside its docker images
+    Cross,
+    /// `cargo zigbuild`, linking with zig
+    Zigbuild,
+}
+
+/// Tool running what cargo builds for a `wasm32-*` target.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum WasmRunner {
+    /// `wasmtime` as cargo's target runner, for WASI binaries, tests and benches
+    Wasmtime,
+    /// `wasm-pack test --headless --chrome`, running tests in a headless browser
+    WasmPack,
+    /// `trunk serve`, building the app and serving it to a browser
+    Trunk,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default, PartialEq, Eq)]
+pub struct NextestOptions {
+    /// nextest profile from `.config/nextest.toml`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Run one partition of the tests, e.g. `count:1/3` or `hash:2/4`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partition: Option<String>,
+    /// JUnit report path, relative to nextest's `target/nextest/<profile>` directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub junit: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default, PartialEq, Eq)]
+pub struct CoverageOptions {
+    #[serde(default, skip_serializing_if = "CoverageFormat::is_default")]
+    pub format: CoverageFormat,
+    /// Path of the lcov file or directory of the html report, cargo-llvm-cov's defaults
+    /// under `target/llvm-cov` otherwise
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    /// Open the html report in the browser once written
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub open: bool,
+    /// Fail the run when less than this percentage of the lines is covered
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fail_under: Option<u8>,
+}
+
+impl CoverageOptions {
+    /// Why the options can't be used, checked by [`Config::validate`].
+    pub fn check(&self) -> Result<(), String> {
+        if self.fail_under.is_some_and(|percent| percent > 100) {
+            return Err("fail_under is a percentage, from 0 to 100".to_string());
+        }
+        if self.open && self.format != CoverageFormat::Html {
+            return Err("open needs format = \"html\"".to_string());
+        }
+        if self.output.is_some() && self.format == CoverageFormat::Text {
+            return Err("output needs format = \"lcov\" or \"html\"".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// What `cargo llvm-cov` reports the coverage as.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CoverageFormat {
+    /// The summary table in the terminal
+    #[default]
+    Text,
+    /// An lcov file, for editors and coverage services
+    Lcov,
+    /// A browsable html report
+    Html,
+}
+
+impl CoverageFormat {
+    pub fn is_default(&self) -> bool {
+        *self == CoverageFormat::default()
+    }
+}
+
+fn default_command_config() -> DefaultKey {
+    DefaultKey::from("default")
+}
+
+/// The key a context runs when none is given: a single key, or keys by the directory rx runs
+/// in, e.g. `default = { "crates/server/**" = "server", "*" = "app" }`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum DefaultKey {
+    Key(String),
+    /// Globs matched against the directory relative to the workspace root, the longest
+    /// matching pattern wins so `*` makes a fallback. `dir/**` also matches `dir` itself.
+    ByPath(BTreeMap<String, String>),
+}
+
+impl DefaultKey {
+    /// The key for `dir`, relative to the workspace root.
+    pub fn resolve(&self, dir: &Path) -> Option<&str> {
+        let patterns = match self {
+            DefaultKey::Key(key) => return Some(key),
+            DefaultKey::ByPath(patterns) => patterns,
+        };
+
+        let dir = dir
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        patterns
+            .iter()
+            .filter(|(pattern, _)| {
+                let base = pattern.strip_suffix("/**");
+                base == Some(dir.as_str())
+                    || Pattern::new(pattern).is_ok_and(|pattern| pattern.matches(&dir))
+            })
+            .max_by_key(|(pattern, _)| pattern.len())
+            .map(|(_, key)| key.as_str())
+    }
+
+    /// Every key it can resolve to.
+    pub fn keys(&self) -> Vec<&str> {
+        match self {
+            DefaultKey::Key(key) => vec![key],
+            DefaultKey::ByPath(patterns) => patterns.values().map(String::as_str).collect(),
+        }
+    }
+
+    pub fn refers_to(&self, key: &str) -> bool {
+        self.keys().contains(&key)
+    }
+
+    /// Makes what refers to `old_key` refer to `new_key` instead.
+    pub fn rename(&mut self, old_key: &str, new_key: &str) {
+        match self {
+            DefaultKey::Key(key) if key == old_key => *key = new_key.to_string(),
+            DefaultKey::Key(_) => {}
+            DefaultKey::ByPath(patterns) => {
+                for key in patterns.values_mut().filter(|key| *key == old_key) {
+                    *key = new_key.to_string();
+                }
+            }
+        }
+    }
+}
+
+impl From<&str> for DefaultKey {
+    fn from(key: &str) -> Self {
+        DefaultKey::Key(key.to_string())
+    }
+}
+
+impl From<String> for DefaultKey {
+    fn from(key: String) -> Self {
+        DefaultKey::Key(key)
+    }
+}
+
+impl PartialEq<str> for DefaultKey {
+    fn eq(&self, other: &str) -> bool {
+        matches!(self, DefaultKey::Key(key) if key == other)
+    }
+}
+
+impl PartialEq<&str> for DefaultKey {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+impl Display for DefaultKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DefaultKey::Key(key) => write!(f, "{}", key),
+            DefaultKey::ByPath(patterns) => {
+                let entries: Vec<String> = patterns
+                    .iter()
+                    .map(|(pattern, key)| format!("{:?} = {:?}", pattern, key))
+                    .collect();
+                write!(f, "{{ {} }}", entries.join(", "))
+            }
+        }
+    }
+}
+
+impl CommandConfig {
+    fn default_command_details(command: &str, command_type: CommandType) -> CommandDetails {
+        CommandDetails {
+            command_type,
+            command: command.into(),
+            params: Params::default(),
+            allow_multiple_instances: false,
+            working_directory: "${workspaceFolder}".to_string(),
+            pre_command: BTreeSet::new(),
+            env: BTreeMap::new(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_context(context: &str) -> Self {
+        let default_details = match context {
+            "run" => Self::default_command_details(
+                "run --package ${packageName} --bin ${binaryName}",
+                CommandType::Cargo,
+            ),
+            "test" => Self::default_command_details("test", CommandType::Cargo),
+            "build" => Self::default_command_details("build", CommandType::Cargo),
+            "bench" => Self::default_command_details("bench", CommandType::Cargo),
+            "lint" => Self::default_command_details("clippy", CommandType::Cargo),
+            "fmt" => CommandDetails {
+                params: Params::from("--check"),
+                ..Self::default_command_details("fmt", CommandType::Cargo)
+            },
+            "coverage" => Self::default_command_details("llvm-cov", CommandType::Cargo),
+            _ => Self::default_command_details("script", CommandType::Shell),
+        };
+
+        let mut configs = BTreeMap::new();
+        configs.insert("default".to_string(), default_details);
+
+        Self {
+            default: "default".into(),
+            configs,
+            runner: None,
+            nextest: None,
+            coverage: None,
+            scripts_dir: None,
+        }
+    }
+
+    pub fn update_config(&mut self, key: &str, details: CommandDetails) {
+        self.configs.insert(key.to_string(), details);
+    }
+
+    /// Copies the `src_key` entry to the new key `dst_key`.
+    pub fn duplicate_config(&mut self, src_key: &str, dst_key: &str) -> Result<(), ConfigError> {
+        if self.configs.contains_key(dst_key) {
+            return Err(ConfigError::ConfigKeyExists(dst_key.to_string()));
+        }
+        let details = self
+            .configs
+            .get(src_key)
+            .cloned()
+            .ok_or_else(|| ConfigError::ConfigKeyNotFound(src_key.to_string()))?;
+        self.configs.insert(dst_key.to_string(), details);
+
+        Ok(())
+    }
+
+    /// Renames `old_key`, keeping `default` and the `pre_command` references of the other
+    /// entries pointing at it.
+    pub fn rename_config(&mut self, old_key: &str, new_key: &str) -> Result<(), ConfigError> {
+        if self.configs.contains_key(new_key) {
+            return Err(ConfigError::ConfigKeyExists(new_key.to_string()));
+        }
+        let details = self
+            .configs
+            .remove(old_key)
+            .ok_or_else(|| ConfigError::ConfigKeyNotFound(old_key.to_string()))?;
+        self.configs.insert(new_key.to_string(), details);
+
+        self.default.rename(old_key, new_key);
+        for details in self.configs.values_mut() {
+            if details.pre_command.remove(old_key) {
+                details.pre_command.insert(new_key.to_string());
+            }
+            if details.replaced_by.as_deref() == Some(old_key) {
+                details.replaced_by = Some(new_key.to_string());
+            }
+            for dependency in &mut details.depends_on {
+                if dependency == old_key {
+                    *dependency = new_key.to_string();
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Removes the entry `key`. A `default` left without a key falls back to an entry named
+    /// `default`, or the first key left in order, so it never refers to a removed entry.
+    pub fn remove_config(&mut self, key: &str) {
+        self.configs.remove(key);
+
+        let orphaned = match &mut self.default {
+            DefaultKey::Key(default) => default == key,
+            DefaultKey::ByPath(patterns) => {
+                patterns.retain(|_, default| default != key);
+                patterns.is_empty()
+            }
+        };
+        if orphaned {
+            let fallback = match self.configs.contains_key("default") {
+                true => Some("default"),
+                false => self.keys().into_iter().next(),
+            };
+            self.default = fallback.unwrap_or("default").into();
+        }
+    }
+
+    /// The keys of the entries, sorted.
+    pub fn keys(&self) -> Vec<&str> {
+        self.configs.keys().map(String::as_str).collect()
+    }
+}
+
+impl Default for CommandConfig {
+    fn default() -> Self {
+        Self {
+            default: "default".into(),
+            configs: BTreeMap::new(), // An empty BTreeMap
+            runner: None,
+            nextest: None,
+            coverage: None,
+            scripts_dir: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default, PartialEq, Eq)]
+pub struct CommandDetails {
+    #[serde(rename = "type", default = "default_command_type")]
+    pub command_type: CommandType,
+    /// What the config is for, shown next to its label when configs are listed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default = "default_command")]
+    pub command: CommandSource,
+    /// Arguments after the command, a shell-like line or an array passed as is
+    #[serde(default, skip_serializing_if = "Params::is_default")]
+    pub params: Params,
+    /// Named, typed parameters `${arg:name}` refers to in the command and params, set with
+    /// `--arg name=value` and otherwise taking their default
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub args: BTreeMap<String, ArgSpec>,
+    /// The binary target `${binaryName}` is for this config, instead of the package's
+    /// `default-run` binary or its only one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binary_name: Option<String>,
+    #[serde(
+        serialize_with = "serialize_env",
+        deserialize_with = "deserialize_env",
+        default = "default_env",
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    #[schemars(with = "BTreeMap<String, EnvValue>")]
+    pub env: BTreeMap<String, String>,
+    /// Names of `[envsets]` whose variables the config takes, later sets overriding earlier
+    /// ones and its own `env` overriding them all
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env_sets: Vec<String>,
+    /// `context::key` (or `key` of the same context) entries a composite config runs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<String>,
+    /// What the composite does when a step fails, by step entry, `stop` when not listed, e.g.
+    /// `on_failure = { lint = "continue", test = "run:teardown" }`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub on_failure: BTreeMap<String, OnFailure>,
+    /// Run the steps of a composite config, or the cells of a matrix, at the same time instead
+    /// of one after another
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub parallel: bool,
+    /// Values to run the config with, once per combination, e.g.
+    /// `matrix.target = ["x86_64-unknown-linux-gnu", "aarch64-unknown-linux-gnu"]` with
+    /// `${matrix.target}` in its params
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub matrix: BTreeMap<String, Vec<String>>,
+    /// Run only when this condition holds, otherwise the config is skipped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub only_if: Option<Condition>,
+    /// Skip the config when this condition holds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skip_if: Option<Condition>,
+    /// Globs of the files a run depends on, relative to the working directory. While they, the
+    /// config and its `outputs` are unchanged since the last successful run, it is cached
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inputs: Vec<String>,
+    /// Globs of the files a run produces, a run is only cached while they are still there
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outputs: Vec<String>,
+    /// Globs of the files whose changes run the config again under `rx run --watch`, relative
+    /// to the working directory. Defaults to the sources and manifest of a cargo package
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub watch: Vec<String>,
+    /// Overrides `settings.unknown_placeholders` for this config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unknown_placeholders: Option<UnknownPlaceholders>,
+    /// Interpreter for a multi-line `command` without a shebang, `sh` when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shell: Option<String>,
+    /// Run the command through the user's login shell, `$SHELL -l -c`, so what its profile
+    /// sets up (nvm, pyenv, direnv, ...) is there
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub login_shell: bool,
+    /// Files sourced before the command, in order, e.g. `["~/.nvm/nvm.sh"]`. Relative paths
+    /// are relative to the working directory
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub source: Vec<String>,
+    /// dotenv file loaded below `env`, relative to the working directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_file: Option<String>,
+    /// Load what the `.envrc` of the working directory exports through `direnv export json`,
+    /// below `env_file` and `env`. Nothing is loaded without an `.envrc` or direnv
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub direnv: bool,
+    /// `false` runs the command with only its configured environment and the few process
+    /// variables in [`crate::env::ALWAYS_INHERITED`], instead of the whole process environment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inherit_env: Option<bool>,
+    /// Text fed to the command's stdin instead of rx's own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stdin: Option<String>,
+    /// File fed to the command's stdin, relative to the working directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stdin_file: Option<String>,
+    /// Shell line, or `context::key` of a config, reading the command's stdout, e.g. `jq .`.
+    /// What it prints is shown instead, the run keeps the command's exit code
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_filter: Option<String>,
+    /// Encoding of the command's output, such as `windows-1252` or `shift_jis`, decoded to
+    /// UTF-8 for the terminal, events and views. Without it bytes that aren't valid UTF-8 are
+    /// replaced in events and views and echoed as they are
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_encoding: Option<String>,
+    /// Lines of output handed to the event stream, the daemon, the dashboard and rx's grouped
+    /// views: the first half as they come and the latest of the rest once the command
+    /// finishes, after a marker telling how many were dropped. What's echoed as it is isn't cut
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_lines: Option<usize>,
+    /// Bytes, or with a K, M or G suffix such as `"10M"`, the log of a service may grow to. Each
+    /// `rx up` cuts it to its latest half, at a line, when it's bigger
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_log_size: Option<String>,
+    /// File the command's output is also appended to, relative to the working directory, e.g.
+    /// `logs/${key}.log`. ANSI escape sequences are stripped from it, the terminal still gets
+    /// them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_file: Option<String>,
+    /// Keep colors and other ANSI escape sequences in the `log_file`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub keep_ansi: bool,
+    /// TCP ports the command listens on, checked to be free before it starts
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub requires_ports: Vec<u16>,
+    /// Stop the process holding a port of `requires_ports` instead of failing, after asking
+    /// when rx runs in a terminal
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub kill_conflicts: bool,
+    /// When the command, a server say, is ready. Run as a step of a sequential composite or as a
+    /// pre_command it's started, and what comes after it once it's ready, the command running
+    /// on until the composite or the config it came before finishes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wait_for: Option<WaitFor>,
+    /// A script config started in the background with `rx up` and stopped with `rx down`, a
+    /// database or mock server the project needs while it's worked on
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub service: bool,
+    /// `context::key` (or `key` of the same context) entries run before this one, each once,
+    /// those not depending on each other at the same time up to `--jobs`. For a service, the
+    /// services `rx up` starts before it, waiting for those with `wait_for` to be ready
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    /// Run the command attached to a pseudo-terminal, so it shows colors and progress bars and
+    /// can be used interactively as in a terminal. Its stdout and stderr arrive together
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pty: bool,
+    #[serde(
+        default = "default_allow_multiple_instances",
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    pub allow_multiple_instances: bool,
+    /// Name of a lock the command holds while it runs: no two commands with the same `lock`
+    /// run at once, in this rx or any other of the user, e.g. `"db-migrations"`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lock: Option<String>,
+    #[serde(
+        default = "default_working_directory",
+        skip_serializing_if = "is_default_working_directory"
+    )]
+    pub working_directory: String,
+    /// Create the working directory, parents included, when it doesn't exist instead of
+    /// refusing to run, e.g. for an output directory a clean removes
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub create_working_directory: bool,
+    #[serde(
+        default = "default_pre_command",
+        skip_serializing_if = "BTreeSet::is_empty"
+    )]
+    pub pre_command: BTreeSet<String>,
+    /// What a failing pre_command, or one with `wait_for` that isn't ready in time, does to
+    /// the config: `abort_on_failure` (the default), `continue` or `prompt`
+    #[serde(default, skip_serializing_if = "PreCommandPolicy::is_default")]
+    pub pre_command_policy: PreCommandPolicy,
+    /// Seconds to wait after forwarding an interrupt before the process group is killed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shutdown_grace_period: Option<u64>,
+    /// Exit codes treated as success, only `0` when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub success_exit_codes: Option<Vec<i32>>,
+    /// `user@host` to run the command on over ssh
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote: Option<String>,
+    /// Directory on the remote host, defaults to the working directory's name under `~`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_dir: Option<String>,
+    /// rsync the working directory to `remote_dir` before running
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub sync: bool,
+    /// Run the command with elevated rights: through sudo (or doas) on unix, behind a UAC
+    /// prompt on Windows
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub elevate: bool,
+    /// Run the command as this user, through sudo (or doas) on unix and runas on Windows
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_as: Option<String>,
+    /// Memory, niceness, CPUs, open files and core dumps the command's processes are limited to
+    #[serde(default, skip_serializing_if = "Limits::is_empty")]
+    pub limits: Limits,
+    /// Run the command away from the working tree: `tempdir` runs it in a fresh temporary
+    /// directory holding copies of the `inputs` and copies the `outputs` back once it succeeds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<Sandbox>,
+    /// Octal permission bits cleared from the files the command creates, e.g. `"077"` to keep
+    /// them private, on unix
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub umask: Option<String>,
+    /// Run the command inside a container instead of on the host
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<ContainerConfig>,
+    /// Send a desktop notification when the run finishes, overrides `settings.notify`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notify: Option<bool>,
+    /// Outcomes of the run posted to the `[notifications]` webhooks, e.g. `["failure"]`, with
+    /// the last lines of stderr rx reads from the command for it. A stopped run is neither
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notify_on: Vec<NotifyOn>,
+    /// Run cargo without network access, overrides `settings.offline`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offline: Option<bool>,
+    /// Tool building for a `--target` other than the host, cargo itself by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cross_runner: Option<CrossRunner>,
+    /// Tool running a `wasm32-*` target: wasmtime for WASI targets, otherwise wasm-pack for
+    /// `cargo test` and trunk for `cargo run` when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wasm_runner: Option<WasmRunner>,
+    /// What `cargo install` takes to install the third-party subcommand a cargo config runs,
+    /// e.g. `cargo-udeps --locked`; `cargo-<subcommand>` when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub install_hint: Option<String>,
+    /// Install the third-party subcommand with `cargo install` when it's missing instead of
+    /// failing the run
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub auto_install: bool,
+    /// `CARGO_TARGET_DIR` of a cargo config, relative to the working directory, so configs
+    /// building with different flags don't evict each other's incremental builds, e.g.
+    /// `target/${key}`. `rx clean KEY` removes it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_dir: Option<String>,
+    /// Running it warns, or fails with `--strict`. Implied by `replaced_by`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deprecated: bool,
+    /// Key of the config in the same context to run instead, suggested when this one runs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replaced_by: Option<String>,
+    /// Groups across contexts, such as `ci` or `slow`, run and listed together with `--tag`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// OSes (`linux`, `macos`, `windows`) or OS families (`unix`) the config runs on, any when
+    /// empty. On the others it isn't listed and refuses to run.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub platforms: Vec<String>,
+    /// Overrides keyed by target triple (`x86_64-pc-windows-msvc`) or OS (`windows`, `macos`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub target: BTreeMap<String, TargetOverride>,
+}
+
+/// A guard of a config: a shell expression holding when it exits with 0, or built-in checks
+/// that all have to hold.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum Condition {
+    Shell(String),
+    Builtin(BuiltinCondition),
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct BuiltinCondition {
+    /// OS (`linux`, `macos`, `windows`) or OS family (`unix`) rx runs on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub os: Option<String>,
+    /// Environment variable that is set and not empty
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_set: Option<String>,
+    /// Path that exists, relative to the working directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_exists: Option<String>,
+    /// Checked out git branch, a glob such as `release/*` matches several
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+}
+
+impl Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Condition::Shell(expression) => write!(f, "`{}`", expression),
+            Condition::Builtin(condition) => {
+                let checks: Vec<String> = [
+                    ("os", &condition.os),
+                    ("env_set", &condition.env_set),
+                    ("file_exists", &condition.file_exists),
+                    ("branch", &condition.branch),
+                ]
+                .into_iter()
+                .filter_map(|(name, value)| {
+                    value
+                        .as_ref()
+                        .map(|value| format!("{} = \"{}\"", name, value))
+                })
+                .collect();
+                write!(f, "{}", checks.join(", "))
+            }
+        }
+    }
+}
+
+/// Fields replacing the config's own when it runs for a matching target.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default, PartialEq, Eq)]
+pub struct TargetOverride {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<Params>,
+    /// Merged into the config's env
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+}
+
+/// What a composite config does when one of its steps fails, written `"stop"`, `"continue"`
+/// or `"run:<step>"`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+#[schemars(with = "String")]
+pub enum OnFailure {
+    /// Fail the composite without running the steps left
+    #[default]
+    Stop,
+    /// Ignore the failure and go on with the next step, for optional steps
+    Continue,
+    /// Run the `context::key` (or `key` of the same context) entry, e.g. a teardown, then fail
+    /// the composite
+    Run(String),
+}
+
+impl FromStr for OnFailure {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stop" => Ok(OnFailure::Stop),
+            "continue" => Ok(OnFailure::Continue),
+            _ => match s.strip_prefix("run:") {
+                Some(step) if !step.trim().is_empty() => Ok(OnFailure::Run(step.to_string())),
+                _ => Err(ConfigError::UnknownOnFailure(s.to_string())),
+            },
+        }
+    }
+}
+
+impl TryFrom<String> for OnFailure {
+    type Error = ConfigError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<OnFailure> for String {
+    fn from(on_failure: OnFailure) -> Self {
+        on_failure.to_string()
+    }
+}
+
+impl Display for OnFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OnFailure::Stop => write!(f, "stop"),
+            OnFailure::Continue => write!(f, "continue"),
+            OnFailure::Run(step) => write!(f, "run:{}", step),
+        }
+    }
+}
+
+/// What a config does when one of its pre_commands fails.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PreCommandPolicy {
+    /// Fail without running the config
+    #[default]
+    AbortOnFailure,
+    /// Run the config anyway, for best-effort steps such as priming a cache
+    Continue,
+    /// Ask whether to run the config anyway, aborting when there's nobody to ask
+    Prompt,
+}
+
+impl PreCommandPolicy {
+    pub fn is_default(&self) -> bool {
+        *self == PreCommandPolicy::default()
+    }
+}
+
+/// Where a config's command runs instead of its working directory.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Sandbox {
+    /// A fresh temporary directory, removed after the run
+    Tempdir,
+}
+
+/// Resources a command's process may use, the processes it starts included. Only processes
+/// rx starts on this machine are limited, not what runs in a container or on a remote host.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// Bytes, or with a K, M or G suffix such as `"4G"`: the address space of each process on
+    /// unix, the memory of all of them together on Windows
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<String>,
+    /// Niceness from -20 (favored) to 19 (runs when nothing else does), mapped to a priority
+    /// class on Windows. Below 0 usually needs elevated rights
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nice: Option<i32>,
+    /// CPUs the processes may run on, e.g. `[0, 1]`, on Linux and Windows
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cpus: Vec<usize>,
+    /// Files each process may have open at once on unix, e.g. `65536` for a bench or fuzzer.
+    /// Raising it past the hard limit usually needs elevated rights
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub open_files: Option<u64>,
+    /// Whether a crashing process dumps its core on unix: `true` raises the size of core dumps
+    /// to the hard limit, `false` turns them off. Left as inherited when not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub core_dumps: Option<bool>,
+}
+
+impl Limits {
+    pub fn is_empty(&self) -> bool {
+        self == &Limits::default()
+    }
+
+    /// The `memory` limit in bytes.
+    pub fn memory_bytes(&self) -> Result<Option<u64>, String> {
+        self.memory.as_deref().map(parse_size).transpose()
+    }
+}
+
+/// Bytes of a size such as `512`, `512M` or `4G`.
+fn parse_size(size: &str) -> Result<u64, String> {
+    let size = size.trim();
+    let (digits, unit) = match size.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+        Some((index, _)) => size.split_at(index),
+        None => (size, ""),
+    };
+    let factor: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" => 1 << 10,
+        "M" | "MB" => 1 << 20,
+        "G" | "GB" => 1 << 30,
+        _ => return Err(format!("unknown unit in '{}', expected K, M or G", size)),
+    };
+    digits
+        .parse::<u64>()
+        .ok()
+        .and_then(|value| value.checked_mul(factor))
+        .filter(|bytes| *bytes > 0)
+        .ok_or_else(|| format!("'{}' is not a size such as 512M or 4G", size))
+}
+
+/// A parameter of a config, e.g. `args.port = { type = "int", default = 8080 }`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ArgSpec {
+    #[serde(rename = "type", default)]
+    pub arg_type: ArgType,
+    /// The value without `--arg`, which is required when there is none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<ArgValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl ArgSpec {
+    /// Why the parameter can't be used, if it can't: a default of another type.
+    pub fn check(&self) -> Result<(), String> {
+        match &self.default {
+            Some(default) if default.arg_type() != self.arg_type => {
+                Err(format!("the default {} is not {}", default, self.arg_type))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ArgType {
+    #[default]
+    String,
+    Int,
+    Bool,
+}
+
+impl Display for ArgType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArgType::String => write!(f, "a string"),
+            ArgType::Int => write!(f, "an int"),
+            ArgType::Bool => write!(f, "a bool"),
+        }
+    }
+}
+
+impl ArgType {
+    /// `value` as given on the command line read as this type, bools as `true` or `false`.
+    pub fn parse(&self, value: &str) -> Result<ArgValue, String> {
+        let parsed = match self {
+            ArgType::String => Some(ArgValue::String(value.to_string())),
+            ArgType::Int => value.parse().ok().map(ArgValue::Int),
+            ArgType::Bool => value.parse().ok().map(ArgValue::Bool),
+        };
+        parsed.ok_or_else(|| format!("'{}' is not {}", value, self))
+    }
+}
+
+/// The value of a parameter, written as a TOML string, integer or bool.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum ArgValue {
+    Bool(bool),
+    Int(i64),
+    String(String),
+}
+
+impl ArgValue {
+    pub fn arg_type(&self) -> ArgType {
+        match self {
+            ArgValue::Bool(_) => ArgType::Bool,
+            ArgValue::Int(_) => ArgType::Int,
+            ArgValue::String(_) => ArgType::String,
+        }
+    }
+}
+
+impl Display for ArgValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArgValue::Bool(value) => write!(f, "{}", value),
+            ArgValue::Int(value) => write!(f, "{}", value),
+            ArgValue::String(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// The conditions a config with `wait_for` is ready with, all of those given have to hold.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default, PartialEq, Eq)]
+pub struct WaitFor {
+    /// TCP port on localhost accepting connections
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    /// URL answering with 200 OK, requested with curl
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Path that exists, relative to the working directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Regular expression a line of the command's output matches, e.g. `"listening on"`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log: Option<String>,
+    /// Seconds to wait before the config fails, 60 when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+}
+
+impl WaitFor {
+    /// Why the conditions can't be waited for, if they can't.
+    pub fn check(&self) -> Result<(), String> {
+        if self.port.is_none() && self.url.is_none() && self.file.is_none() && self.log.is_none() {
+            return Err("wait_for needs a port, url, file or log to wait for".to_string());
+        }
+        if self.port == Some(0) {
+            return Err("0 is not a port a command can listen on".to_string());
+        }
+        if self.timeout == Some(0) {
+            return Err("a timeout of 0 seconds can't be waited for".to_string());
+        }
+        if let Some(log) = &self.log {
+            Regex::new(log).map_err(|err| format!("invalid log pattern: {}", err))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default, PartialEq, Eq)]
+pub struct ContainerConfig {
+    pub image: String,
+    /// Container CLI to use, `docker` when unset (`podman` is argument compatible)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub engine: Option<String>,
+    /// Where `${workspaceFolder}` is mounted inside the container, `/workspace` when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace_mount: Option<String>,
+    /// Extra `host:container` volume mounts
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub volumes: Vec<String>,
+    /// Extra arguments passed to `docker run` before the image
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub run_args: Vec<String>,
+}
+
+impl CommandDetails {
+    /// The `max_log_size` in bytes.
+    pub fn max_log_bytes(&self) -> Result<Option<u64>, String> {
+        self.max_log_size.as_deref().map(parse_size).transpose()
+    }
+
+    /// The `umask` as permission bits.
+    pub fn umask_bits(&self) -> Result<Option<u32>, String> {
+        let Some(umask) = &self.umask else {
+            return Ok(None);
+        };
+        let digits = umask.trim().trim_start_matches("0o");
+        u32::from_str_radix(digits, 8)
+            .ok()
+            .filter(|bits| *bits <= 0o777)
+            .map(Some)
+            .ok_or_else(|| format!("'{}' is not an octal mask such as 022 or 077", umask))
+    }
+
+    /// Whether the config runs on the OS rx runs on, see `platforms`.
+    pub fn runs_on_this_platform(&self) -> bool {
+        self.platforms.is_empty() || self.platforms.iter().any(|os| os_matches(os))
+    }
+
+    pub fn is_deprecated(&self) -> bool {
+        self.deprecated || self.replaced_by.is_some()
+    }
+
+    /// A config running the program and arguments of `command` as this one runs its own: with
+    /// its env, env_sets, env_file, direnv, offline mode, working directory, shell and its init, wrappers, limits and log, but none of its
+    /// pre_commands, dependencies, guards or caching.
+    pub fn for_exec(&self, command: &[String]) -> CommandDetails {
+        let command: Vec<String> = command.iter().map(|arg| shell_quote(arg)).collect();
+        CommandDetails {
+            command_type: CommandType::Shell,
+            command: command.join(" ").into(),
+            env: self.env.clone(),
+            env_sets: self.env_sets.clone(),
+            unknown_placeholders: self.unknown_placeholders,
+            shell: self.shell.clone(),
+            login_shell: self.login_shell,
+            source: self.source.clone(),
+            env_file: self.env_file.clone(),
+            direnv: self.direnv,
+            inherit_env: self.inherit_env,
+            output_encoding: self.output_encoding.clone(),
+            max_output_lines: self.max_output_lines,
+            max_log_size: self.max_log_size.clone(),
+            log_file: self.log_file.clone(),
+            keep_ansi: self.keep_ansi,
+            working_directory: self.working_directory.clone(),
+            create_working_directory: self.create_working_directory,
+            shutdown_grace_period: self.shutdown_grace_period,
+            remote: self.remote.clone(),
+            remote_dir: self.remote_dir.clone(),
+            sync: self.sync,
+            elevate: self.elevate,
+            run_as: self.run_as.clone(),
+            limits: self.limits.clone(),
+            umask: self.umask.clone(),
+            container: self.container.clone(),
+            target_dir: self.target_dir.clone(),
+            offline: self.offline,
+            ..Default::default()
+        }
+    }
+
+    /// These details with the `target` override for `triple` applied. An exact triple match
+    /// wins over an OS match.
+    pub fn for_target(&self, triple: &str) -> Cow<'_, CommandDetails> {
+        let exact = self.target.get(triple);
+        let by_os = || {
+            self.target
+                .iter()
+                .find(|(key, _)| target_os_matches(key, triple))
+                .map(|(_, target_override)| target_override)
+        };
+
+        let Some(target_override) = exact.or_else(by_os) else {
+            return Cow::Borrowed(self);
+        };
+
+        let mut details = self.clone();
+        if let Some(command) = &target_override.command {
+            details.command = command.clone().into();
+        }
+        if let Some(params) = &target_override.params {
+            details.params = params.clone();
+        }
+        details.env.extend(target_override.env.clone());
+        Cow::Owned(details)
+    }
+
+    /// The `target_dir` of the config `key`, its `${key}` placeholders replaced.
+    pub fn target_dir(&self, key: &str) -> Option<String> {
+        self.target_dir
+            .as_ref()
+            .map(|target_dir| target_dir.replace("${key}", key))
+    }
+
+    /// The `log_file` of the config `key`, its `${key}` placeholders replaced.
+    pub fn log_file(&self, key: &str) -> Option<String> {
+        self.log_file
+            .as_ref()
+            .map(|log_file| log_file.replace("${key}", key))
+    }
+
+    /// Whether the `env` variable `name` is encrypted or kept in the keychain.
+    pub fn env_is_sealed(&self, name: &str) -> bool {
+        self.env
+            .get(name)
+            .is_some_and(|value| secret::is_sealed(value))
+    }
+
+    /// Whether the config leaves its `working_directory` to the default, so its namespace's
+    /// applies.
+    pub fn has_default_working_directory(&self) -> bool {
+        self.working_directory == default_working_directory()
+    }
+
+    /// Whether the command inherits the whole environment of the process running rx.
+    pub fn inherits_env(&self) -> bool {
+        self.inherit_env.unwrap_or(true)
+    }
+
+    /// Whether `code` counts as success, a process killed by a signal (no code) never does
+    pub fn is_success(&self, code: Option<i32>) -> bool {
+        match (code, &self.success_exit_codes) {
+            (Some(code), Some(codes)) => codes.contains(&code),
+            (Some(code), None) => code == 0,
+            (None, _) => false,
+        }
+    }
+}
+
+/// Whether an OS name such as `windows`, `linux` or `macos` is the OS of `triple`.
+fn target_os_matches(os: &str, triple: &str) -> bool {
+    if os.contains('-') {
+        return false;
+    }
+    let os = match os {
+        "macos" => "darwin",
+        os => os,
+    };
+    triple.split('-').skip(1).any(|part| part == os)
+}
+
+fn default_command_type() -> CommandType {
+    CommandType::Cargo
+}
+
+fn default_command() -> CommandSource {
+    CommandSource::from("run")
+}
+
+fn default_env() -> BTreeMap<String, String> {
+    BTreeMap::new()
+}
+
+/// Whether `name` can name a `lock`, whose file is named after it.
+fn is_lock_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+fn default_allow_multiple_instances() -> bool {
+    false
+}
+fn default_working_directory() -> String {
+    String::from("${workspaceFolder}")
+}
+fn is_default_working_directory(working_directory: &str) -> bool {
+    working_directory == default_working_directory()
+}
+fn default_pre_command() -> BTreeSet<String> {
+    BTreeSet::new()
+}
+
+fn serialize_env<S>(env: &BTreeMap<String, String>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    #[derive(Serialize)]
+    struct Keychain<'a> {
+        keychain: &'a str,
+    }
+
+    let mut map = serializer.serialize_map(Some(env.len()))?;
+    for (k, v) in env {
+        if let Some(entry) = v.strip_prefix(secret::KEYCHAIN) {
+            map.serialize_entry(k, &Keychain { keychain: entry })?;
+        } else if let Ok(bool_val) = v.parse::<bool>() {
+            map.serialize_entry(k, &bool_val)?;
+        } else if let Ok(int_val) = v.parse::<i64>() {
+            map.serialize_entry(k, &int_val)?;
+        } else {
+            // Fallback to string if it's neither bool nor int
+            map.serialize_entry(k, v)?;
+        }
+    }
+    map.end()
+}
+
+/// An env value as written in the file.
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum EnvValue {
+    Bool(bool),
+    Int(i64),
+    String(String),
+    /// Read from the OS keychain, `service/account`
+    Keychain {
+        keychain: String,
+    },
+}
+
+/// Reads back what `serialize_env` writes, env values saved as bools or integers become strings
+/// again and `{ keychain = "service/account" }` a `keychain:` value.
+fn deserialize_env<'de, D>(deserializer: D) -> Result<BTreeMap<String, String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let env = BTreeMap::<String, EnvValue>::deserialize(deserializer)?;
+    Ok(env
+        .into_iter()
+        .map(|(key, value)| {
+            let value = match value {
+                EnvValue::Bool(value) => value.to_string(),
+                EnvValue::Int(value) => value.to_string(),
+                EnvValue::String(value) => value,
+                EnvValue::Keychain { keychain } => format!("{}{}", secret::KEYCHAIN, keychain),
+            };
+            (key, value)
+        })
+        .collect())
+}
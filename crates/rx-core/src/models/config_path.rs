@@ -0,0 +1,229 @@
+use toml::Value;
+
+use crate::{
+    errors::ConfigError,
+    models::config::{CommandContext, Config},
+};
+
+/// Splits a dotted path like `commands.run.configs.server.params` into its keys. Keys holding
+/// dots can be quoted: `target."x86_64-pc-windows-msvc"`.
+pub fn parse_path(path: &str) -> Result<Vec<String>, ConfigError> {
+    let mut keys = Vec::new();
+    let mut key = String::new();
+    let mut quoted = false;
+
+    for c in path.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            '.' if !quoted => keys.push(std::mem::take(&mut key)),
+            c => key.push(c),
+        }
+    }
+    keys.push(key);
+
+    if quoted || keys.iter().any(String::is_empty) {
+        return Err(ConfigError::InvalidPath(path.to_string()));
+    }
+    Ok(keys)
+}
+
+impl Config {
+    /// The value at a dotted path of the config as written to the file.
+    pub fn get_path(&self, path: &str) -> Result<Value, ConfigError> {
+        let root = self.to_value();
+        let mut value = &root;
+
+        for key in parse_path(path)? {
+            value = match value {
+                Value::Table(table) => table.get(&key),
+                Value::Array(array) => key.parse::<usize>().ok().and_then(|i| array.get(i)),
+                _ => None,
+            }
+            .ok_or_else(|| ConfigError::InvalidPath(path.to_string()))?;
+        }
+
+        Ok(value.clone())
+    }
+
+    /// Sets the value at a dotted path, creating missing tables on the way.
+    ///
+    /// `raw` is parsed according to the type of the value it replaces: strings are taken
+    /// verbatim, bools and numbers must parse, arrays and tables take inline TOML (tables also
+    /// accept `KEY=VALUE,...`). New values are parsed as inline TOML when possible and kept as
+    /// strings otherwise, or as `KEY=VALUE,...` when a string doesn't fit. The config is only
+    /// changed when the result is valid, unknown keys are rejected.
+    pub fn set_path(&mut self, path: &str, raw: &str) -> Result<(), ConfigError> {
+        self.set_path_as(path, raw, |raw| {
+            parse_inline(raw).unwrap_or(Value::String(raw.into()))
+        })
+        .or_else(|err| match parse_pairs(raw) {
+            Some(pairs) => self
+                .set_path_as(path, raw, |_| pairs.clone())
+                .map_err(|_| err),
+            None => Err(err),
+        })
+    }
+
+    /// [`Config::set_path`] with `new` parsing a value for a key the config doesn't write yet.
+    fn set_path_as(
+        &mut self,
+        path: &str,
+        raw: &str,
+        new: impl Fn(&str) -> Value,
+    ) -> Result<(), ConfigError> {
+        let keys = parse_path(path)?;
+        let mut root = self.to_value();
+
+        let (last, parents) = keys.split_last().expect("a parsed path has a key");
+        let mut value = &mut root;
+        for key in parents {
+            value = match value {
+                Value::Table(table) => table
+                    .entry(key.clone())
+                    .or_insert_with(|| Value::Table(Default::default())),
+                Value::Array(array) => key
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|i| array.get_mut(i))
+                    .ok_or_else(|| ConfigError::InvalidPath(path.to_string()))?,
+                _ => return Err(ConfigError::InvalidPath(path.to_string())),
+            };
+        }
+
+        let slot = match value {
+            Value::Table(table) => table.get_mut(last.as_str()),
+            Value::Array(array) => last.parse::<usize>().ok().and_then(|i| array.get_mut(i)),
+            _ => return Err(ConfigError::InvalidPath(path.to_string())),
+        };
+        let invalid =
+            |reason: &str| ConfigError::InvalidValue(path.to_string(), reason.to_string());
+
+        let new_value = match slot {
+            Some(slot) => {
+                *slot = parse_like(slot, raw).map_err(|reason| invalid(&reason))?;
+                None
+            }
+            None => match value {
+                Value::Table(table) => {
+                    let new_value = new(raw);
+                    table.insert(last.clone(), new_value.clone());
+                    Some(new_value)
+                }
+                _ => return Err(ConfigError::InvalidPath(path.to_string())),
+            },
+        };
+
+        let mut updated: Config = root
+            .try_into()
+            .map_err(|err: toml::de::Error| invalid(err.message()))?;
+        updated.validate()?;
+
+        // Unknown keys are dropped when deserializing, a new value that didn't survive is a typo.
+        // Values equal to a field's default are never written and can't be told apart.
+        if let Some(new_value) = new_value {
+            if !is_empty(&new_value) && updated.get_path(path).is_err() {
+                return Err(ConfigError::InvalidPath(path.to_string()));
+            }
+        }
+
+        updated.included = self.included.clone();
+        *self = updated;
+        Ok(())
+    }
+
+    /// Sets `fields` of the config `key` of `context` as [`Config::set_path`] does, each a
+    /// path below the config such as `params` or `limits.memory` with its raw value.
+    pub fn set_fields(
+        &mut self,
+        context: CommandContext,
+        key: &str,
+        fields: &[(String, String)],
+    ) -> Result<(), ConfigError> {
+        if !self.commands.get_configs(context).iter().any(|k| k == key) {
+            return Err(ConfigError::ConfigKeyNotFound(format!(
+                "{}::{}",
+                context, key
+            )));
+        }
+        for (field, raw) in fields {
+            let path = format!("commands.{}.configs.\"{}\".{}", context, key, field);
+            self.set_path(&path, raw)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn to_value(&self) -> Value {
+        Value::try_from(self).expect("the config model always serializes to TOML")
+    }
+}
+
+/// Parses `raw` as a value of the same type as `current`.
+fn parse_like(current: &Value, raw: &str) -> Result<Value, String> {
+    match current {
+        Value::String(_) => Ok(Value::String(raw.to_string())),
+        Value::Boolean(_) => raw
+            .parse()
+            .map(Value::Boolean)
+            .map_err(|_| format!("expected true or false, got '{}'", raw)),
+        Value::Integer(_) => raw
+            .parse()
+            .map(Value::Integer)
+            .map_err(|_| format!("expected an integer, got '{}'", raw)),
+        Value::Float(_) => raw
+            .parse()
+            .map(Value::Float)
+            .map_err(|_| format!("expected a number, got '{}'", raw)),
+        Value::Array(_) => match parse_inline(raw) {
+            Some(value @ Value::Array(_)) => Ok(value),
+            _ => Err(format!(
+                "expected an array like [\"a\", \"b\"], got '{}'",
+                raw
+            )),
+        },
+        Value::Table(_) => match parse_inline(raw) {
+            Some(value @ Value::Table(_)) => Ok(value),
+            _ => parse_pairs(raw).ok_or_else(|| {
+                format!(
+                    "expected {{ KEY = \"value\" }} or KEY=VALUE,..., got '{}'",
+                    raw
+                )
+            }),
+        },
+        Value::Datetime(_) => {
+            parse_inline(raw).ok_or_else(|| format!("expected a datetime, got '{}'", raw))
+        }
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Boolean(value) => !value,
+        Value::String(value) => value.is_empty(),
+        Value::Array(value) => value.is_empty(),
+        Value::Table(value) => value.is_empty(),
+        _ => false,
+    }
+}
+
+/// Parses `raw` as an inline TOML value such as `true`, `3`, `["a"]` or `{ A = "b" }`.
+fn parse_inline(raw: &str) -> Option<Value> {
+    toml::from_str::<toml::Table>(&format!("value = {}", raw))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+}
+
+/// Parses `KEY=VALUE,KEY2=VALUE2` into a table of strings, an empty string into an empty table.
+fn parse_pairs(raw: &str) -> Option<Value> {
+    raw.split(',')
+        .filter(|pair| !pair.trim().is_empty())
+        .map(|pair| {
+            pair.split_once('=').map(|(key, value)| {
+                (
+                    key.trim().to_string(),
+                    Value::String(value.trim().to_string()),
+                )
+            })
+        })
+        .collect::<Option<toml::Table>>()
+        .map(Value::Table)
+}
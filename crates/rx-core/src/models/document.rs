@@ -0,0 +1,248 @@
+use std::borrow::Cow;
+use toml_edit::{ArrayOfTables, DocumentMut, InlineTable, Item, Key, Table, Value};
+
+/// `current`, a TOML config file, changed by what `updated` holds that `loaded` doesn't, both
+/// written by rx: `loaded` from the model as read from `current` and `updated` from the model
+/// to save. Only the keys whose value changed between the two are written, so the comments,
+/// formatting and order of the rest of the file are kept, and defaults rx writes out but the
+/// file leaves implicit stay implicit. New keys go at the end of their table, new tables after
+/// their siblings, and a table under a new key but otherwise unchanged, a renamed config, is
+/// renamed where it is. `updated` as it is when `current` isn't valid TOML.
+pub fn patch(current: &str, loaded: &str, updated: &str) -> String {
+    let (Ok(mut document), Ok(target)) = (
+        current.parse::<DocumentMut>(),
+        updated.parse::<DocumentMut>(),
+    ) else {
+        return updated.to_string();
+    };
+    // Everything counts as changed when what was loaded can't be told
+    let base = loaded.parse::<DocumentMut>().unwrap_or_default();
+    patch_table(document.as_table_mut(), base.as_table(), target.as_table());
+    document.to_string()
+}
+
+fn patch_table(table: &mut Table, base: &Table, target: &Table) {
+    let mut base = Cow::Borrowed(base);
+    for (from, to) in renamed(&base, target) {
+        rename_key(table, &from, &to);
+        let base = base.to_mut();
+        if let Some((_, item)) = base.remove_entry(&from) {
+            base.insert(&to, item);
+        }
+    }
+
+    let removed: Vec<String> = base
+        .iter()
+        .filter(|(key, _)| !target.contains_key(key))
+        .map(|(key, _)| key.to_string())
+        .collect();
+    for key in removed {
+        table.remove(&key);
+    }
+
+    for (key, target_item) in target.iter() {
+        let base_item = base.get(key);
+        if base_item.is_some_and(|base_item| same_item(base_item, target_item)) {
+            continue;
+        }
+        match table.get_mut(key) {
+            Some(item) => patch_item(item, base_item, target_item),
+            None => {
+                table.insert(key, unpositioned(target_item));
+            }
+        }
+    }
+}
+
+/// The tables of `base` missing from `target` paired with the same tables under a new key.
+fn renamed(base: &Table, target: &Table) -> Vec<(String, String)> {
+    let mut added: Vec<(&str, &Item)> = target
+        .iter()
+        .filter(|(key, item)| item.is_table_like() && !base.contains_key(key))
+        .collect();
+    let mut renamed = Vec::new();
+    for (key, item) in base.iter() {
+        if !item.is_table_like() || target.contains_key(key) {
+            continue;
+        }
+        if let Some(index) = added.iter().position(|(_, added)| same_item(item, added)) {
+            let (new_key, _) = added.remove(index);
+            renamed.push((key.to_string(), new_key.to_string()));
+        }
+    }
+    renamed
+}
+
+/// Renames `from` of `table` to `to`, keeping its item, the decor of its key and its place
+/// among the keys of the table.
+fn rename_key(table: &mut Table, from: &str, to: &str) {
+    if table.contains_key(to) {
+        return;
+    }
+    let Some(index) = table.iter().position(|(key, _)| key == from) else {
+        return;
+    };
+    let keys: Vec<String> = table
+        .iter()
+        .skip(index)
+        .map(|(key, _)| key.to_string())
+        .collect();
+    let entries: Vec<(Key, Item)> = keys
+        .iter()
+        .filter_map(|key| table.remove_entry(key))
+        .collect();
+    for (key, item) in entries {
+        let key = if key.get() == from {
+            Key::new(to)
+                .with_leaf_decor(key.leaf_decor().clone())
+                .with_dotted_decor(key.dotted_decor().clone())
+        } else {
+            key
+        };
+        table.insert_formatted(&key, item);
+    }
+}
+
+/// Changes `item` of the file from `base`, what rx loaded, to `target`, the two differing.
+fn patch_item(item: &mut Item, base: Option<&Item>, target: &Item) {
+    let empty = Table::new();
+    let base_table = match base {
+        Some(Item::Table(base)) => base.clone(),
+        Some(Item::Value(Value::InlineTable(base))) => base.clone().into_table(),
+        _ => empty.clone(),
+    };
+    match (item, target) {
+        (Item::Table(table), Item::Table(target)) => patch_table(table, &base_table, target),
+        (Item::ArrayOfTables(tables), Item::ArrayOfTables(target)) => {
+            let base = base.and_then(Item::as_array_of_tables);
+            patch_array_of_tables(tables, base, target)
+        }
+        // A map written inline, e.g. `env = { PORT = "3000" }`, stays inline
+        (Item::Value(Value::InlineTable(inline)), Item::Table(target)) => patch_inline_table(
+            inline,
+            &base_table.into_inline_table(),
+            &target.clone().into_inline_table(),
+        ),
+        (Item::Value(value), Item::Value(target)) => {
+            patch_value(value, base.and_then(Item::as_value), target)
+        }
+        (item, target) => *item = unpositioned(target),
+    }
+}
+
+fn patch_array_of_tables(
+    tables: &mut ArrayOfTables,
+    base: Option<&ArrayOfTables>,
+    target: &ArrayOfTables,
+) {
+    while tables.len() > target.len() {
+        tables.remove(tables.len() - 1);
+    }
+    let empty = Table::new();
+    for (index, target_table) in target.iter().enumerate() {
+        let base_table = base.and_then(|base| base.get(index)).unwrap_or(&empty);
+        match tables.get_mut(index) {
+            Some(table) => patch_table(table, base_table, target_table),
+            None => tables.push(unpositioned_table(target_table)),
+        }
+    }
+}
+
+fn patch_inline_table(inline: &mut InlineTable, base: &InlineTable, target: &InlineTable) {
+    let removed: Vec<String> = base
+        .iter()
+        .filter(|(key, _)| !target.contains_key(key))
+        .map(|(key, _)| key.to_string())
+        .collect();
+    for key in removed {
+        inline.remove(&key);
+    }
+
+    for (key, target_value) in target.iter() {
+        let base_value = base.get(key);
+        if base_value.is_some_and(|base_value| same_value(base_value, target_value)) {
+            continue;
+        }
+        match inline.get_mut(key) {
+            Some(value) => patch_value(value, base_value, target_value),
+            None => {
+                inline.insert(key, target_value.clone());
+            }
+        }
+    }
+}
+
+/// Replaces `value` unless it's equal to `target` as written differently, keeping the
+/// whitespace and comment around it. An inline table is patched from `base` key by key.
+fn patch_value(value: &mut Value, base: Option<&Value>, target: &Value) {
+    if same_value(value, target) {
+        return;
+    }
+    if let (Value::InlineTable(inline), Value::InlineTable(target)) = (&mut *value, target) {
+        let base = match base {
+            Some(Value::InlineTable(base)) => base.clone(),
+            _ => InlineTable::new(),
+        };
+        return patch_inline_table(inline, &base, target);
+    }
+    let decor = value.decor().clone();
+    *value = target.clone();
+    *value.decor_mut() = decor;
+}
+
+/// Whether two items rx wrote are the same.
+fn same_item(a: &Item, b: &Item) -> bool {
+    match (a, b) {
+        (Item::Value(a), Item::Value(b)) => same_value(a, b),
+        (Item::Table(a), Item::Table(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(key, a)| b.get(key).is_some_and(|b| same_item(a, b)))
+        }
+        (Item::ArrayOfTables(a), Item::ArrayOfTables(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .zip(b.iter())
+                    .all(|(a, b)| same_item(&Item::Table(a.clone()), &Item::Table(b.clone())))
+        }
+        _ => false,
+    }
+}
+
+/// Whether two values are the same once read, e.g. `'a'` and `"a"`.
+fn same_value(a: &Value, b: &Value) -> bool {
+    let read = |value: &Value| {
+        let mut value = value.clone();
+        value.decor_mut().clear();
+        format!("value = {}", value)
+            .parse::<toml::Table>()
+            .ok()
+            .and_then(|mut table| table.remove("value"))
+    };
+    read(a).is_some_and(|a| Some(a) == read(b))
+}
+
+/// A copy of `item` whose tables have no position of their own, so they're written after the
+/// tables before them in the file rather than where they were in the other document.
+fn unpositioned(item: &Item) -> Item {
+    match item {
+        Item::Table(table) => Item::Table(unpositioned_table(table)),
+        Item::ArrayOfTables(tables) => {
+            let mut copy = ArrayOfTables::new();
+            for table in tables.iter() {
+                copy.push(unpositioned_table(table));
+            }
+            Item::ArrayOfTables(copy)
+        }
+        item => item.clone(),
+    }
+}
+
+fn unpositioned_table(table: &Table) -> Table {
+    let mut copy = Table::new();
+    copy.set_implicit(table.is_implicit());
+    for (key, item) in table.iter() {
+        copy.insert(key, unpositioned(item));
+    }
+    copy
+}
@@ -0,0 +1,64 @@
+use std::{path::Path, str::FromStr};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::errors::ConfigError;
+
+/// The syntax of a config file, told by its extension. The fields and what they mean are the
+/// same in each.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConfigFormat {
+    #[default]
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl FromStr for ConfigFormat {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "toml" => Ok(ConfigFormat::Toml),
+            "yaml" | "yml" => Ok(ConfigFormat::Yaml),
+            "json" => Ok(ConfigFormat::Json),
+            _ => Err(ConfigError::UnknownConfigFormat(s.to_string())),
+        }
+    }
+}
+
+impl ConfigFormat {
+    /// YAML for a `.yaml` or `.yml` file, JSON for a `.json` one and TOML for anything else.
+    pub fn from_path(path: &Path) -> Self {
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(|extension| extension.parse().ok())
+            .unwrap_or_default()
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ConfigFormat::Toml => "toml",
+            ConfigFormat::Yaml => "yaml",
+            ConfigFormat::Json => "json",
+        }
+    }
+
+    pub fn parse<T: DeserializeOwned>(&self, content: &str) -> Result<T, String> {
+        match self {
+            ConfigFormat::Toml => toml::from_str(content).map_err(|err| err.to_string()),
+            ConfigFormat::Yaml => serde_yaml::from_str(content).map_err(|err| err.to_string()),
+            ConfigFormat::Json => serde_json::from_str(content).map_err(|err| err.to_string()),
+        }
+    }
+
+    pub fn to_string<T: Serialize>(&self, value: &T) -> Result<String, String> {
+        match self {
+            ConfigFormat::Toml => toml::to_string_pretty(value).map_err(|err| err.to_string()),
+            ConfigFormat::Yaml => serde_yaml::to_string(value).map_err(|err| err.to_string()),
+            ConfigFormat::Json => serde_json::to_string_pretty(value)
+                .map(|json| json + "\n")
+                .map_err(|err| err.to_string()),
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::errors::ConfigError;
+use crate::models::{
+    config::{CommandContext, CommandDetails, Config},
+    format::ConfigFormat,
+};
+
+/// What a config gained from its `include` files, kept so saving only writes the main file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Included {
+    /// Every file read, in the order they were merged
+    files: Vec<PathBuf>,
+    entries: Vec<IncludedEntry>,
+}
+
+/// An entry as it was loaded from an included file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct IncludedEntry {
+    context: CommandContext,
+    key: String,
+    file: PathBuf,
+    details: CommandDetails,
+}
+
+/// A file listed in `include`. It only adds configs, the contexts' `default` and options stay
+/// in the main file, and may include more files relative to itself.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct IncludedFile {
+    #[serde(default)]
+    include: Vec<String>,
+    #[serde(default)]
+    commands: BTreeMap<String, IncludedConfigs>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct IncludedConfigs {
+    #[serde(default)]
+    configs: BTreeMap<String, CommandDetails>,
+}
+
+impl Config {
+    /// Merges the files `include` lists into the config, `file` being the main config file.
+    /// Files are merged in the order listed, the matches of a pattern sorted by path and each
+    /// file followed by its own includes. A file is read once, and an entry defined by two
+    /// files is an error.
+    pub(crate) fn resolve_includes(&mut self, file: &Path) -> Result<(), Box<dyn Error>> {
+        let mut visited = vec![fs::canonicalize(file).unwrap_or_else(|_| file.to_path_buf())];
+        let patterns = self.include.clone();
+        self.include_files(file, file, &patterns, &mut visited)
+    }
+
+    fn include_files(
+        &mut self,
+        main: &Path,
+        from: &Path,
+        patterns: &[String],
+        visited: &mut Vec<PathBuf>,
+    ) -> Result<(), Box<dyn Error>> {
+        let dir = from.parent().unwrap_or(Path::new(""));
+        for pattern in patterns {
+            for path in expand(dir, pattern)? {
+                let canonical = fs::canonicalize(&path)?;
+                if visited.contains(&canonical) {
+                    continue;
+                }
+                visited.push(canonical);
+
+                let content = fs::read_to_string(&path)
+                    .map_err(|err| format!("cannot read {}: {}", path.display(), err))?;
+                let included: IncludedFile = ConfigFormat::from_path(&path)
+                    .parse(&content)
+                    .map_err(|err| format!("{}: {}", path.display(), err))?;
+                self.included.files.push(path.clone());
+
+                for (context, configs) in included.commands {
+                    let context: CommandContext = context.parse()?;
+                    for (key, details) in configs.configs {
+                        self.add_included(main, context, key, details, &path)?;
+                    }
+                }
+                self.include_files(main, &path, &included.include, visited)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn add_included(
+        &mut self,
+        main: &Path,
+        context: CommandContext,
+        key: String,
+        details: CommandDetails,
+        file: &Path,
+    ) -> Result<(), ConfigError> {
+        let command_config = self.commands.get_or_default_config(context);
+        if command_config.configs.contains_key(&key) {
+            let first = self
+                .origin(context, &key)
+                .unwrap_or(main)
+                .display()
+                .to_string();
+            return Err(ConfigError::IncludeConflict(
+                format!("{}::{}", context, key),
+                first,
+                file.display().to_string(),
+            ));
+        }
+
+        command_config.update_config(&key, details.clone());
+        self.included.entries.push(IncludedEntry {
+            context,
+            key,
+            file: file.to_path_buf(),
+            details,
+        });
+        Ok(())
+    }
+
+    /// Adds an entry of the team config kept in `file` unless this config has one of that key,
+    /// see [`Config::merge_remote`]. Returns whether it was added.
+    pub(crate) fn add_underneath(
+        &mut self,
+        context: CommandContext,
+        key: &str,
+        details: &CommandDetails,
+        file: &Path,
+    ) -> bool {
+        let command_config = self.commands.get_or_default_config(context);
+        if command_config.configs.contains_key(key) {
+            return false;
+        }
+        command_config.update_config(key, details.clone());
+        self.included.entries.push(IncludedEntry {
+            context,
+            key: key.to_string(),
+            file: file.to_path_buf(),
+            details: details.clone(),
+        });
+        true
+    }
+
+    /// The file an entry was included from, `None` for entries of the main file.
+    pub fn origin(&self, context: CommandContext, key: &str) -> Option<&Path> {
+        self.included
+            .entries
+            .iter()
+            .find(|entry| entry.context == context && entry.key == key)
+            .map(|entry| entry.file.as_path())
+    }
+
+    /// The files merged into the config, to watch along with the main file.
+    pub fn included_files(&self) -> &[PathBuf] {
+        &self.included.files
+    }
+
+    /// The config as written to the main file, without the included entries. Those have to be
+    /// changed in their own file, so an included entry that was changed, renamed or removed is
+    /// an error.
+    pub(crate) fn main_file_config(&self) -> Result<Config, ConfigError> {
+        let mut config = self.clone();
+        for entry in &self.included.entries {
+            let removed = config
+                .commands
+                .get_config_mut(entry.context)
+                .and_then(|command_config| command_config.configs.remove(&entry.key));
+            if removed.as_ref() != Some(&entry.details) {
+                return Err(ConfigError::IncludedEntryChanged(
+                    format!("{}::{}", entry.context, entry.key),
+                    entry.file.display().to_string(),
+                ));
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// The files `pattern` matches relative to `dir`, sorted. A plain path has to exist, a glob
+/// may match nothing.
+fn expand(dir: &Path, pattern: &str) -> Result<Vec<PathBuf>, ConfigError> {
+    let invalid = |reason: String| ConfigError::InvalidValue("include".to_string(), reason);
+    let full = normalize(&dir.join(pattern));
+    let mut paths: Vec<PathBuf> = glob::glob(&full.to_string_lossy())
+        .map_err(|err| invalid(format!("invalid pattern '{}': {}", pattern, err.msg)))?
+        .filter_map(Result::ok)
+        .filter(|path| path.is_file())
+        .collect();
+    paths.sort();
+
+    if paths.is_empty() && !pattern.contains(['*', '?', '[']) {
+        return Err(invalid(format!("no such file '{}'", full.display())));
+    }
+    Ok(paths)
+}
+
+/// `path` with the `..` following a directory resolved, so `team/../shared.toml` is reported
+/// as `shared.toml`.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(
+                    normalized.components().next_back(),
+                    Some(Component::Normal(_))
+                ) =>
+            {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
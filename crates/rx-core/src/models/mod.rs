@@ -0,0 +1,7 @@
+pub mod command;
+pub mod config;
+pub mod config_path;
+pub mod document;
+pub mod format;
+pub mod include;
+pub mod params;
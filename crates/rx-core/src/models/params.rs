@@ -0,0 +1,166 @@
+use std::fmt::{self, Display};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::helpers::shell_quote;
+
+/// The `params` of a config: a line split into arguments like a shell would, or an array of
+/// arguments passed on as they are.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum Params {
+    /// `params = "--features 'a b'"`, quotes and backslashes work as in a POSIX shell
+    Line(String),
+    /// `params = ["--features", "a b"]`
+    Argv(Vec<String>),
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params::Line(String::new())
+    }
+}
+
+impl From<&str> for Params {
+    fn from(line: &str) -> Self {
+        Params::Line(line.to_string())
+    }
+}
+
+impl From<String> for Params {
+    fn from(line: String) -> Self {
+        Params::Line(line)
+    }
+}
+
+impl From<Vec<String>> for Params {
+    fn from(argv: Vec<String>) -> Self {
+        Params::Argv(argv)
+    }
+}
+
+/// The params as a line, the arguments of an array quoted where needed.
+impl Display for Params {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Params::Line(line) => write!(f, "{}", line),
+            Params::Argv(argv) => {
+                let quoted: Vec<String> = argv.iter().map(|arg| shell_quote(arg)).collect();
+                write!(f, "{}", quoted.join(" "))
+            }
+        }
+    }
+}
+
+impl Params {
+    pub fn is_default(&self) -> bool {
+        *self == Params::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Params::Line(line) => line.trim().is_empty(),
+            Params::Argv(argv) => argv.is_empty(),
+        }
+    }
+
+    /// The arguments as written, a line split but placeholders left alone.
+    pub fn words(&self) -> Vec<String> {
+        match self {
+            Params::Line(line) => split(line).0,
+            Params::Argv(argv) => argv.clone(),
+        }
+    }
+
+    /// The arguments with `interpolate` applied to each. Placeholders are resolved after
+    /// splitting, so a value holding spaces stays a single argument.
+    pub fn args(&self, interpolate: impl Fn(&str) -> String) -> Vec<String> {
+        self.words().iter().map(|word| interpolate(word)).collect()
+    }
+
+    /// The params for a shell command line, a line as written and an array quoted, with
+    /// `interpolate` applied.
+    pub fn shell_line(&self, interpolate: impl Fn(&str) -> String) -> String {
+        match self {
+            Params::Line(line) => interpolate(line),
+            Params::Argv(argv) => {
+                let quoted: Vec<String> = argv
+                    .iter()
+                    .map(|arg| shell_quote(&interpolate(arg)))
+                    .collect();
+                quoted.join(" ")
+            }
+        }
+    }
+
+    /// The params followed by `args`, keeping the form they are written in.
+    pub fn with_args(&self, args: &[String]) -> Params {
+        match self {
+            Params::Line(line) => {
+                let line = std::iter::once(line.clone())
+                    .chain(args.iter().map(|arg| shell_quote(arg)))
+                    .filter(|part| !part.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                Params::Line(line)
+            }
+            Params::Argv(argv) => Params::Argv(argv.iter().chain(args).cloned().collect()),
+        }
+    }
+
+    /// Checks that the quotes of a line are closed.
+    pub fn check(&self) -> Result<(), String> {
+        match self {
+            Params::Line(line) => match split(line).1 {
+                Some(quote) => Err(format!("unterminated {} quote", quote)),
+                None => Ok(()),
+            },
+            Params::Argv(_) => Ok(()),
+        }
+    }
+}
+
+/// Splits `line` into words like a POSIX shell, without expanding anything. A `${..}`
+/// placeholder is kept whole. Also returns the quote left open, the open word running to the
+/// end of the line.
+fn split(line: &str) -> (Vec<String>, Option<char>) {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote: Option<char> = None;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some('\''), '\'') | (Some('"'), '"') => quote = None,
+            (Some('\''), c) => word.get_or_insert_with(String::new).push(c),
+            (_, '\\') => {
+                match chars.next_if(|next| quote.is_none() || "\"\\$`".contains(*next)) {
+                    Some(escaped) => word.get_or_insert_with(String::new).push(escaped),
+                    // Inside double quotes a backslash before anything else is kept
+                    None if quote.is_some() => word.get_or_insert_with(String::new).push('\\'),
+                    None => {}
+                }
+            }
+            (_, '$') if chars.peek() == Some(&'{') => {
+                let word = word.get_or_insert_with(String::new);
+                word.push('$');
+                for c in chars.by_ref() {
+                    word.push(c);
+                    if c == '}' {
+                        break;
+                    }
+                }
+            }
+            (Some(_), c) => word.get_or_insert_with(String::new).push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => words.extend(word.take()),
+            (None, c) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    (words, quote)
+}
@@ -0,0 +1,170 @@
+use std::{
+    io::{self, Write},
+    process::{Command, Stdio},
+    time::Duration,
+};
+
+/// Title and body of the notification sent when a run finishes.
+pub fn completion_message(label: &str, code: Option<i32>, elapsed: Duration) -> (String, String) {
+    let title = match code {
+        Some(0) => format!("rx: {} succeeded", label),
+        Some(code) => format!("rx: {} failed ({})", label, code),
+        None => format!("rx: {} was stopped", label),
+    };
+    let body = format!("Finished in {}", format_duration(elapsed));
+
+    (title, body)
+}
+
+pub fn format_duration(elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs_f64();
+    if seconds < 60.0 {
+        format!("{:.1}s", seconds)
+    } else {
+        format!("{}m {:02}s", elapsed.as_secs() / 60, elapsed.as_secs() % 60)
+    }
+}
+
+/// The message posted to the webhooks when the run of `label` finishes: its outcome, exit
+/// code, duration and `host`, then the last lines it wrote to stderr, if any.
+pub fn webhook_message(
+    label: &str,
+    code: Option<i32>,
+    elapsed: Duration,
+    stderr: &[String],
+    host: &str,
+) -> String {
+    let outcome = match code {
+        Some(0) => "succeeded".to_string(),
+        Some(code) => format!("failed with exit code {}", code),
+        None => "was stopped".to_string(),
+    };
+    let mut message = format!(
+        "rx: {} {} after {} on {}",
+        label,
+        outcome,
+        format_duration(elapsed),
+        host
+    );
+    if !stderr.is_empty() {
+        message.push_str(&format!("\n```\n{}\n```", stderr.join("\n")));
+    }
+    message
+}
+
+/// The JSON body posting `message` to `url`: Discord takes it as `content`, Slack and most
+/// other webhooks as `text`.
+pub fn webhook_payload(url: &str, message: &str) -> String {
+    let field = match url.contains("discord.com/api/webhooks") {
+        true => "content",
+        false => "text",
+    };
+    serde_json::json!({ field: message }).to_string()
+}
+
+/// Posts `payload` to the webhook `url` with curl.
+pub fn post_webhook(url: &str, payload: &str) -> io::Result<()> {
+    let mut curl = Command::new("curl")
+        .args(["-fsS", "--max-time", "10", "-X", "POST"])
+        .args([
+            "-H",
+            "Content-Type: application/json",
+            "--data-binary",
+            "@-",
+        ])
+        .arg(url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = curl.stdin.take() {
+        stdin.write_all(payload.as_bytes())?;
+    }
+    let output = curl.wait_with_output()?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+/// Name of the machine rx runs on, as the webhook messages give it.
+pub fn host_name() -> String {
+    #[cfg(unix)]
+    {
+        let mut name = [0u8; 256];
+        let result = unsafe { libc::gethostname(name.as_mut_ptr().cast(), name.len()) };
+        if result == 0 {
+            let end = name
+                .iter()
+                .position(|byte| *byte == 0)
+                .unwrap_or(name.len());
+            return String::from_utf8_lossy(&name[..end]).into_owned();
+        }
+    }
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .unwrap_or_else(|_| "unknown host".to_string())
+}
+
+/// Shows a native desktop notification using the platform's own tooling.
+pub fn send(title: &str, body: &str) -> io::Result<()> {
+    let status = notification_command(title, body)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "notification command exited with {}",
+            status
+        )))
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn notification_command(title: &str, body: &str) -> Command {
+    let script = format!(
+        "display notification {} with title {}",
+        apple_script_string(body),
+        apple_script_string(title)
+    );
+    let mut command = Command::new("osascript");
+    command.arg("-e").arg(script);
+    command
+}
+
+#[cfg(target_os = "macos")]
+fn apple_script_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn notification_command(title: &str, body: &str) -> Command {
+    let mut command = Command::new("notify-send");
+    command.args(["--app-name", "rx", title, body]);
+    command
+}
+
+#[cfg(windows)]
+fn notification_command(title: &str, body: &str) -> Command {
+    let script = format!(
+        "[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] > $null; \
+         $template = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02); \
+         $text = $template.GetElementsByTagName('text'); \
+         $text.Item(0).AppendChild($template.CreateTextNode('{}')) > $null; \
+         $text.Item(1).AppendChild($template.CreateTextNode('{}')) > $null; \
+         [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('rx').Show([Windows.UI.Notifications.ToastNotification]::new($template))",
+        title.replace('\'', "''"),
+        body.replace('\'', "''")
+    );
+    let mut command = Command::new("powershell");
+    command.args(["-NoProfile", "-Command", &script]);
+    command
+}
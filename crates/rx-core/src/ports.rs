@@ -0,0 +1,208 @@
+use std::{
+    fmt::{self, Display},
+    net::TcpListener,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// How long a process asked to stop gets to free its port before it is killed.
+const STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Decides whether the process in a conflict over a port of a `kill_conflicts` config, given by
+/// its label, is stopped. Asked before it is.
+pub type PortConflictHandler = Arc<dyn Fn(&str, &PortConflict) -> bool + Send + Sync>;
+
+/// A port a config needs that some process already listens on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortConflict {
+    pub port: u16,
+    /// The listening process, when it can be found
+    pub pid: Option<u32>,
+    pub process: Option<String>,
+}
+
+impl PortConflict {
+    /// What holds the port, e.g. `pid 4242 (node)`.
+    pub fn holder(&self) -> String {
+        match (self.pid, &self.process) {
+            (Some(pid), Some(process)) => format!("pid {} ({})", pid, process),
+            (Some(pid), None) => format!("pid {}", pid),
+            _ => "another process".to_string(),
+        }
+    }
+}
+
+impl Display for PortConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "port {} is held by {}", self.port, self.holder())
+    }
+}
+
+/// Whether nothing listens on TCP `port`, on any address.
+pub fn is_free(port: u16) -> bool {
+    TcpListener::bind(("0.0.0.0", port)).is_ok()
+}
+
+/// What holds `port`, `None` when it's free.
+pub fn conflict(port: u16) -> Option<PortConflict> {
+    if is_free(port) {
+        return None;
+    }
+    let pid = listener_pid(port);
+    Some(PortConflict {
+        port,
+        pid,
+        process: pid.and_then(process_name),
+    })
+}
+
+/// Stops the process `pid` holding `port`, asking it first and killing it when the port isn't
+/// free after a few seconds. Whether the port is free now.
+pub fn stop(pid: u32, port: u16) -> bool {
+    signal(pid, false);
+    if wait_free(port, STOP_TIMEOUT) {
+        return true;
+    }
+    signal(pid, true);
+    wait_free(port, Duration::from_secs(1))
+}
+
+fn wait_free(port: u16, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        if is_free(port) {
+            return true;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+    is_free(port)
+}
+
+#[cfg(unix)]
+fn signal(pid: u32, kill: bool) {
+    let signal = if kill { libc::SIGKILL } else { libc::SIGTERM };
+    unsafe {
+        libc::kill(pid as libc::pid_t, signal);
+    }
+}
+
+#[cfg(windows)]
+fn signal(pid: u32, _kill: bool) {
+    let _ = std::process::Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/F"])
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status();
+}
+
+/// The process listening on `port`: from /proc on Linux, which only shows the sockets of
+/// rx's own user's processes unless rx runs as root, from lsof or netstat elsewhere.
+#[cfg(target_os = "linux")]
+pub fn listener_pid(port: u16) -> Option<u32> {
+    let inodes: Vec<String> = ["/proc/net/tcp", "/proc/net/tcp6"]
+        .iter()
+        .filter_map(|table| std::fs::read_to_string(table).ok())
+        .flat_map(|table| listening_inodes(&table, port))
+        .collect();
+    if inodes.is_empty() {
+        return None;
+    }
+    let sockets: Vec<String> = inodes
+        .iter()
+        .map(|inode| format!("socket:[{}]", inode))
+        .collect();
+
+    std::fs::read_dir("/proc")
+        .ok()?
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+        .find(|pid| {
+            std::fs::read_dir(format!("/proc/{}/fd", pid))
+                .into_iter()
+                .flatten()
+                .flatten()
+                .filter_map(|fd| std::fs::read_link(fd.path()).ok())
+                .any(|link| {
+                    sockets
+                        .iter()
+                        .any(|socket| link.as_os_str() == socket.as_str())
+                })
+        })
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+pub fn listener_pid(port: u16) -> Option<u32> {
+    let output = std::process::Command::new("lsof")
+        .args(["-nP", &format!("-iTCP:{}", port), "-sTCP:LISTEN", "-t"])
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.trim().parse().ok())
+}
+
+#[cfg(windows)]
+pub fn listener_pid(port: u16) -> Option<u32> {
+    let output = std::process::Command::new("netstat")
+        .args(["-ano", "-p", "TCP"])
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    let suffix = format!(":{}", port);
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                [_, local, _, "LISTENING", pid] if local.ends_with(&suffix) => pid.parse().ok(),
+                _ => None,
+            }
+        })
+}
+
+/// The inodes of the sockets listening on `port` in a /proc/net/tcp `table`.
+pub fn listening_inodes(table: &str, port: u16) -> Vec<String> {
+    const LISTEN: &str = "0A";
+    table
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let local_port = fields.get(1)?.rsplit(':').next()?;
+            if *fields.get(3)? != LISTEN || u16::from_str_radix(local_port, 16).ok()? != port {
+                return None;
+            }
+            fields.get(9).map(|inode| inode.to_string())
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn process_name(pid: u32) -> Option<String> {
+    let name = std::fs::read_to_string(format!("/proc/{}/comm", pid)).ok()?;
+    Some(name.trim().to_string()).filter(|name| !name.is_empty())
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn process_name(pid: u32) -> Option<String> {
+    let output = std::process::Command::new("ps")
+        .args(["-p", &pid.to_string(), "-o", "comm="])
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Some(name).filter(|name| !name.is_empty())
+}
+
+#[cfg(windows)]
+fn process_name(_pid: u32) -> Option<String> {
+    None
+}
@@ -0,0 +1,88 @@
+use std::io::{self, BufRead, Write};
+
+/// Asks questions one line at a time, such as `rx add` does for the fields it wasn't given.
+/// Questions go to `output`, which is stderr for the CLI so stdout stays clean.
+pub struct Prompt<R, W> {
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> Prompt<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        Self { input, output }
+    }
+
+    /// Asks until `parse` accepts the answer, printing its error after each rejection. An empty
+    /// answer stands for `default` when there is one.
+    pub fn ask<T>(
+        &mut self,
+        question: &str,
+        default: Option<&str>,
+        parse: impl Fn(&str) -> Result<T, String>,
+    ) -> io::Result<T> {
+        loop {
+            match default {
+                Some(default) if !default.is_empty() => {
+                    write!(self.output, "{} [{}]: ", question, default)?
+                }
+                _ => write!(self.output, "{}: ", question)?,
+            }
+            self.output.flush()?;
+
+            let answer = self.read_line()?;
+            let answer = match default {
+                Some(default) if answer.is_empty() => default,
+                _ => answer.as_str(),
+            };
+            match parse(answer) {
+                Ok(value) => return Ok(value),
+                Err(err) => writeln!(self.output, "  {}", err)?,
+            }
+        }
+    }
+
+    /// Asks a yes or no question, an empty answer standing for `default`.
+    pub fn confirm(&mut self, question: &str, default: bool) -> io::Result<bool> {
+        let choices = if default { "Y/n" } else { "y/N" };
+        self.ask(
+            &format!("{} [{}]", question, choices),
+            None,
+            |answer| match answer.to_lowercase().as_str() {
+                "" => Ok(default),
+                "y" | "yes" => Ok(true),
+                "n" | "no" => Ok(false),
+                _ => Err("answer y or n".to_string()),
+            },
+        )
+    }
+
+    /// Asks for one value per line until an empty answer, checking each with `parse`.
+    pub fn ask_many<T>(
+        &mut self,
+        question: &str,
+        parse: impl Fn(&str) -> Result<T, String>,
+    ) -> io::Result<Vec<T>> {
+        let mut values = Vec::new();
+        loop {
+            let value = self.ask(question, Some(""), |answer| match answer {
+                "" => Ok(None),
+                answer => parse(answer).map(Some),
+            })?;
+            match value {
+                Some(value) => values.push(value),
+                None => return Ok(values),
+            }
+        }
+    }
+
+    fn read_line(&mut self) -> io::Result<String> {
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "input ended before every question was answered",
+            ));
+        }
+        Ok(line.trim().to_string())
+    }
+}
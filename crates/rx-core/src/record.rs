@@ -0,0 +1,74 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    helpers::{is_valid_env_var_name, shell_quote},
+    models::config::{CommandDetails, CommandType},
+};
+
+/// The config entry `rx record` saves for `command`, typed in a shell in `cwd`:
+///
+/// - the `NAME=value` words leading the command and the `env` given along end up in its env,
+///   the latter winning
+/// - `cargo <subcommand> ...` becomes a cargo config with the rest as its params, anything
+///   else a shell config running the command line
+/// - `cwd` is its working directory, relative to `${workspaceFolder}` when inside
+///   `workspace_folder`
+pub fn recorded_details(
+    command: &[String],
+    env: &[(String, String)],
+    cwd: &Path,
+    workspace_folder: &Path,
+) -> Result<CommandDetails, String> {
+    let assignments = command
+        .iter()
+        .take_while(|word| {
+            word.split_once('=')
+                .is_some_and(|(name, _)| is_valid_env_var_name(name))
+        })
+        .count();
+    let mut vars: BTreeMap<String, String> = command[..assignments]
+        .iter()
+        .filter_map(|word| word.split_once('='))
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+    vars.extend(env.iter().cloned());
+
+    let quoted: Vec<String> = command[assignments..]
+        .iter()
+        .map(|word| shell_quote(word))
+        .collect();
+    let (command_type, line, params) = match quoted.as_slice() {
+        [] => return Err("no command to record, only variables".to_string()),
+        [program, subcommand, params @ ..]
+            if program == "cargo" && !subcommand.starts_with(['-', '+']) =>
+        {
+            (CommandType::Cargo, subcommand.clone(), params.join(" "))
+        }
+        words => (CommandType::Shell, words.join(" "), String::new()),
+    };
+
+    Ok(CommandDetails {
+        command_type,
+        command: line.into(),
+        params: params.into(),
+        env: vars,
+        working_directory: config_working_directory(cwd, workspace_folder),
+        ..Default::default()
+    })
+}
+
+/// The `working_directory` a config saves for the absolute `dir`: relative to
+/// `${workspaceFolder}` when inside `workspace_folder`, so the config still works from a
+/// checkout elsewhere.
+pub fn config_working_directory(dir: &Path, workspace_folder: &Path) -> String {
+    // Dropping the `.` components, `./crates/api` is just `crates/api`
+    let dir: PathBuf = dir.components().collect();
+    match dir.strip_prefix(workspace_folder) {
+        Ok(relative) if relative.as_os_str().is_empty() => "${workspaceFolder}".to_string(),
+        Ok(relative) => format!("${{workspaceFolder}}/{}", relative.display()),
+        Err(_) => dir.display().to_string(),
+    }
+}
@@ -0,0 +1,282 @@
+use std::{
+    error::Error,
+    fs, io,
+    path::{Path, PathBuf},
+    process::{self, Command, Stdio},
+    time::Duration,
+};
+
+use tracing::debug;
+
+use crate::{
+    config_diff::{diff_configs, ConfigChange},
+    errors::ExecutionError,
+    models::{
+        config::{CommandContext, CommandDetails, Config, RemoteConfig, DEFAULT_REMOTE_TTL},
+        format::ConfigFormat,
+    },
+    store::{fnv1a, FNV_OFFSET_BASIS},
+};
+
+/// The file of the repository a `[remote_config]` with `git` reads unless `path` says otherwise.
+pub const DEFAULT_REMOTE_PATH: &str = "rx.toml";
+
+/// Where fetched team configs are kept by their source, shared by every project.
+pub fn remote_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("cargo_runner/remote"))
+}
+
+impl RemoteConfig {
+    /// The file read from the repository.
+    pub fn file(&self) -> &str {
+        self.path.as_deref().unwrap_or(DEFAULT_REMOTE_PATH)
+    }
+
+    /// Where the team config is fetched from, e.g. `git@github.com:team/rx-config.git#rx.toml`.
+    pub fn source(&self) -> String {
+        match (&self.url, &self.git) {
+            (Some(url), _) => url.clone(),
+            (None, git) => {
+                let mut source = format!("{}#{}", git.as_deref().unwrap_or_default(), self.file());
+                if let Some(rev) = &self.rev {
+                    source.push_str(&format!("@{}", rev));
+                }
+                source
+            }
+        }
+    }
+
+    /// The copy kept in `dir`, named after the source with the extension of the file.
+    pub fn cache_path(&self, dir: &Path) -> PathBuf {
+        let name = self.url.as_deref().unwrap_or(self.file());
+        let format = ConfigFormat::from_path(Path::new(name));
+        let hash = fnv1a(FNV_OFFSET_BASIS, self.source().as_bytes());
+        dir.join(format!("{:016x}.{}", hash, format.extension()))
+    }
+
+    /// How long ago the copy in `dir` was fetched, `None` when there is none.
+    pub fn age(&self, dir: &Path) -> Option<Duration> {
+        let modified = fs::metadata(self.cache_path(dir)).ok()?.modified().ok()?;
+        Some(modified.elapsed().unwrap_or_default())
+    }
+
+    /// Whether the copy in `dir` is missing or older than `ttl`.
+    pub fn is_stale(&self, dir: &Path) -> bool {
+        let ttl = Duration::from_secs(self.ttl.unwrap_or(DEFAULT_REMOTE_TTL));
+        self.age(dir).is_none_or(|age| age >= ttl)
+    }
+
+    /// Fetches the team config into `dir`, with curl for `url` and a shallow clone for `git`.
+    /// The copy kept is only replaced by a file that reads as a config.
+    pub fn fetch(&self, dir: &Path) -> Result<(), Box<dyn Error>> {
+        self.check()
+            .map_err(|reason| format!("Invalid remote_config: {}", reason))?;
+        fs::create_dir_all(dir)
+            .map_err(|err| format!("cannot create {}: {}", dir.display(), err))?;
+        let cached = self.cache_path(dir);
+        let partial = dir.join(format!(
+            "{}.{}.part",
+            cached.file_name().unwrap_or_default().to_string_lossy(),
+            process::id()
+        ));
+
+        let fetched = match (&self.url, &self.git) {
+            (Some(url), _) => download(url, &partial),
+            (None, Some(git)) => self.clone_file(git, dir, &partial),
+            (None, None) => unreachable!("checked above"),
+        };
+        let checked = fetched.and_then(|_| {
+            let content = fs::read_to_string(&partial)?;
+            ConfigFormat::from_path(&cached)
+                .parse::<Config>(&content)
+                .map_err(|err| format!("{} is not a config: {}", self.source(), err))?;
+            Ok(())
+        });
+        if let Err(err) = checked {
+            let _ = fs::remove_file(&partial);
+            return Err(err);
+        }
+
+        fs::rename(&partial, &cached)
+            .map_err(|err| format!("cannot write {}: {}", cached.display(), err))?;
+        debug!(source = %self.source(), path = %cached.display(), "fetched the team config");
+        Ok(())
+    }
+
+    /// Copies the file of the repository `git` to `to`, through a clone in `dir` removed after.
+    fn clone_file(&self, git: &str, dir: &Path, to: &Path) -> Result<(), Box<dyn Error>> {
+        let checkout = to.with_extension("clone");
+        let _ = fs::remove_dir_all(&checkout);
+        let mut command = Command::new("git");
+        command.args(["clone", "--quiet", "--depth", "1"]);
+        if let Some(rev) = &self.rev {
+            command.args(["--branch", rev]);
+        }
+        let output = command
+            .arg(git)
+            .arg(&checkout)
+            .current_dir(dir)
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|err| match err.kind() {
+                io::ErrorKind::NotFound => ExecutionError::ToolMissing(
+                    "git".into(),
+                    "your system's package manager, e.g. apt install git".into(),
+                ),
+                _ => ExecutionError::Spawn(format!("git clone {}", git), err),
+            })?;
+
+        let copied = match output.status.success() {
+            true => fs::copy(checkout.join(self.file()), to)
+                .map(|_| ())
+                .map_err(|err| format!("cannot read {}: {}", self.source(), err).into()),
+            false => Err(format!(
+                "Could not clone {}: {}",
+                git,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .into()),
+        };
+        let _ = fs::remove_dir_all(&checkout);
+        copied
+    }
+
+    /// The copy kept in `dir`, `None` before the first fetch.
+    fn load_cached(&self, dir: &Path) -> Result<Option<Config>, Box<dyn Error>> {
+        let path = self.cache_path(dir);
+        let Ok(content) = fs::read_to_string(&path) else {
+            return Ok(None);
+        };
+        let team = ConfigFormat::from_path(&path)
+            .parse(&content)
+            .map_err(|err| format!("{}: {}", path.display(), err))?;
+        Ok(Some(team))
+    }
+}
+
+/// Downloads `url` to `to` with curl, over HTTPS only.
+fn download(url: &str, to: &Path) -> Result<(), Box<dyn Error>> {
+    let output = Command::new("curl")
+        .args([
+            "--fail",
+            "--silent",
+            "--show-error",
+            "--location",
+            "--proto",
+            "=https",
+        ])
+        .arg("--output")
+        .arg(to)
+        .arg(url)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => ExecutionError::ToolMissing(
+                "curl".into(),
+                "your system's package manager, e.g. apt install curl".into(),
+            ),
+            _ => ExecutionError::Spawn(format!("curl {}", url), err),
+        })?;
+    if !output.status.success() {
+        let reason = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(format!("Could not download {}: {}", url, reason).into());
+    }
+    Ok(())
+}
+
+/// The entries of `config`, context by context.
+fn entries(config: &Config) -> Vec<(CommandContext, &String, &CommandDetails)> {
+    CommandContext::ALL
+        .into_iter()
+        .filter_map(|context| Some((context, config.commands.get_config(context)?)))
+        .flat_map(|(context, command_config)| {
+            command_config
+                .configs
+                .iter()
+                .map(move |(key, details)| (context, key, details))
+        })
+        .collect()
+}
+
+/// A config of `entries` alone, to compare entries without the rest of their configs.
+fn with_entries<'a>(
+    entries: impl IntoIterator<Item = (CommandContext, &'a String, &'a CommandDetails)>,
+) -> Config {
+    let mut config = Config::default();
+    for (context, key, details) in entries {
+        config
+            .commands
+            .get_or_default_config(context)
+            .update_config(key, details.clone());
+    }
+    config
+}
+
+impl Config {
+    /// Merges the configs of the team config kept in `dir` underneath this config's, which
+    /// take precedence over those of the same key. Nothing is fetched, see
+    /// [`RemoteConfig::fetch`], and before the first fetch nothing is merged.
+    pub fn merge_remote(&mut self, dir: &Path) -> Result<(), Box<dyn Error>> {
+        let Some(remote) = self.remote_config.clone() else {
+            return Ok(());
+        };
+        let Some(team) = remote.load_cached(dir)? else {
+            debug!(source = %remote.source(), "the team config was not fetched yet");
+            return Ok(());
+        };
+        let file = remote.cache_path(dir);
+        for (context, key, details) in entries(&team) {
+            self.add_underneath(context, key, details, &file);
+        }
+        Ok(())
+    }
+}
+
+/// What `rx sync` found fetching the team config.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SyncReport {
+    /// What the team config changed since the copy fetched before, going from that copy to
+    /// the new one
+    pub updates: Vec<ConfigChange>,
+    /// How the entries of `config` overriding those of the team config differ from them,
+    /// going from the team's to the local ones
+    pub drift: Vec<ConfigChange>,
+}
+
+/// Fetches the team config of `config`, loaded with its copy in `dir` merged, and compares
+/// the new copy to the one before and to the entries of `config` overriding it.
+pub fn sync(config: &Config, dir: &Path) -> Result<SyncReport, Box<dyn Error>> {
+    let remote = config
+        .remote_config
+        .as_ref()
+        .ok_or("There is no [remote_config] to sync from")?;
+    let before = remote.load_cached(dir)?.unwrap_or_default();
+    remote.fetch(dir)?;
+    let after = remote
+        .load_cached(dir)?
+        .ok_or_else(|| format!("cannot read {}", remote.cache_path(dir).display()))?;
+
+    let updates = diff_configs(
+        &with_entries(entries(&before)),
+        &with_entries(entries(&after)),
+    );
+
+    let file = remote.cache_path(dir);
+    let overridden: Vec<_> = entries(&after)
+        .into_iter()
+        .filter(|(context, key, _)| {
+            config
+                .commands
+                .get_config(*context)
+                .is_some_and(|command_config| command_config.configs.contains_key(*key))
+                && config.origin(*context, key) != Some(file.as_path())
+        })
+        .collect();
+    let local = overridden.iter().filter_map(|(context, key, _)| {
+        let details = config.commands.get_config(*context)?.configs.get(*key)?;
+        Some((*context, *key, details))
+    });
+    let drift = diff_configs(&with_entries(overridden.clone()), &with_entries(local));
+
+    Ok(SyncReport { updates, drift })
+}
@@ -0,0 +1,61 @@
+use std::{collections::BTreeMap, error::Error, fs, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{errors::ConfigError, models::config::CommandContext, store::Store};
+
+/// The last config run in a project, what `rx rerun` runs again.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct LastRun {
+    /// `context::key` of the config
+    pub label: String,
+    /// Extra arguments given after `--`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// `--env` overrides
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Labels of the steps, matrix cells and dependencies that failed, or of the config itself
+    /// when it failed on its own. Empty after a successful run
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failed: Vec<String>,
+}
+
+impl LastRun {
+    fn path(store: &Store) -> PathBuf {
+        store.path("last_run.toml")
+    }
+
+    /// The last run, `None` before the first.
+    pub fn load(store: &Store) -> Result<Option<LastRun>, Box<dyn Error>> {
+        let path = Self::path(store);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        Ok(Some(toml::from_str(&fs::read_to_string(path)?)?))
+    }
+
+    pub fn save(&self, store: &Store) -> Result<(), Box<dyn Error>> {
+        let path = Self::path(store);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Splits a label such as `test::unit` or `build::release [target=x86_64]` into its context,
+/// key and matrix cell.
+pub fn parse_label(label: &str) -> Result<(CommandContext, &str, Option<&str>), ConfigError> {
+    let (label, cell) = match label.split_once(" [") {
+        Some((label, cell)) => (label, cell.strip_suffix(']')),
+        None => (label, None),
+    };
+    let (context, key) = label
+        .split_once("::")
+        .ok_or_else(|| ConfigError::ConfigKeyNotFound(label.to_string()))?;
+    Ok((context.parse()?, key, cell))
+}
@@ -0,0 +1,191 @@
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    path::Path,
+    str::FromStr,
+};
+
+use serde::Serialize;
+
+use crate::{
+    errors::ConfigError,
+    executor::{runner::Runner, shutdown::ShutdownSignal},
+    models::config::{CommandContext, Config},
+    test_filter::TestFilter,
+    workspace::{Member, Target},
+};
+
+/// How `rx runnables` writes the runnables it found.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RunnablesFormat {
+    /// A runnable per line, its label and command line separated by a tab
+    #[default]
+    Text,
+    Json,
+}
+
+impl FromStr for RunnablesFormat {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(RunnablesFormat::Text),
+            "json" => Ok(RunnablesFormat::Json),
+            _ => Err(ConfigError::UnknownRunnablesFormat(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunnableKind {
+    /// Run the binary the file is the root of
+    Run,
+    /// Run the test function enclosing the line
+    Test,
+    /// Run the tests of the module enclosing the line, or of the integration test target
+    TestModule,
+    /// Run the bench target the file belongs to
+    Bench,
+}
+
+impl Display for RunnableKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kind = match self {
+            RunnableKind::Run => "run",
+            RunnableKind::Test => "test",
+            RunnableKind::TestModule => "test module",
+            RunnableKind::Bench => "bench",
+        };
+        write!(f, "{}", kind)
+    }
+}
+
+/// Something to run at a location in the sources, through the default config of its context.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Runnable {
+    pub kind: RunnableKind,
+    /// e.g. `test config::tests::test_load` or `run serve`
+    pub label: String,
+    pub context: CommandContext,
+    pub key: String,
+    /// Arguments given to the config, what goes after `--` in `rx <context> <key> -- <args>`
+    pub args: Vec<String>,
+    /// The command line it runs, resolved from the config
+    pub command_line: String,
+}
+
+/// The runnables at `line` of `file`, a path in one of the workspace `members`: running the
+/// binary it is the root of, the test function and test module enclosing the line and the
+/// bench target it belongs to, most specific first.
+pub fn runnables(
+    config: &Config,
+    members: &[Member],
+    file: &Path,
+    line: usize,
+) -> Result<Vec<Runnable>, Box<dyn Error>> {
+    let member = members
+        .iter()
+        .filter(|member| file.starts_with(&member.manifest_dir))
+        .max_by_key(|member| member.manifest_dir.components().count())
+        .ok_or_else(|| format!("{} is not in a package of the workspace", file.display()))?;
+    let target = |kind: &str| {
+        member
+            .targets
+            .iter()
+            .find(|target| target.is(kind) && owns(target, file))
+    };
+
+    let mut found = Vec::new();
+    let filter = TestFilter::at_location(file, line)
+        .map_err(|err| format!("cannot read {}: {}", file.display(), err))?;
+    if filter.exact {
+        found.push((
+            RunnableKind::Test,
+            filter.path.clone(),
+            CommandContext::Test,
+            filter.cargo_args(&[]),
+        ));
+    }
+    let module = TestFilter {
+        path: match filter.exact {
+            true => filter
+                .path
+                .rsplit_once("::")
+                .map(|(module, _)| module.to_string())
+                .unwrap_or_default(),
+            false => filter.path.clone(),
+        },
+        exact: false,
+        ..filter
+    };
+    if !module.path.is_empty() || module.test_target.is_some() {
+        let label = match (&module.test_target, module.path.is_empty()) {
+            (Some(target), true) => target.clone(),
+            _ => module.path.clone(),
+        };
+        let args = module.cargo_args(&[]);
+        found.push((RunnableKind::TestModule, label, CommandContext::Test, args));
+    }
+    if let Some(bin) = target("bin").filter(|bin| bin.src_path == file) {
+        found.push((
+            RunnableKind::Run,
+            bin.name.clone(),
+            CommandContext::Run,
+            Vec::new(),
+        ));
+    }
+    if let Some(bench) = target("bench") {
+        let args = vec!["--bench".to_string(), bench.name.clone()];
+        found.push((
+            RunnableKind::Bench,
+            bench.name.clone(),
+            CommandContext::Bench,
+            args,
+        ));
+    }
+
+    let mut runnables = Vec::new();
+    for (kind, name, context, mut args) in found {
+        let mut member = member.clone();
+        if kind == RunnableKind::Run {
+            member.binary_name = Some(name.clone());
+        }
+        let runner = Runner::new(config, context, ShutdownSignal::new()).package(&member);
+        // Contexts without a default config have nothing to run it with
+        let Ok(key) = runner.resolve_key(None) else {
+            continue;
+        };
+        // A run config not naming `${binaryName}` would run the package's default binary
+        let details = runner.details(&key)?;
+        let names_binary = format!("{} {}", details.command, details.params);
+        if kind == RunnableKind::Run && !names_binary.contains("${binaryName}") {
+            args = vec!["--bin".to_string(), name.clone()];
+        }
+        runnables.push(Runnable {
+            kind,
+            label: format!("{} {}", kind, name),
+            context,
+            command_line: runner.command_line(&key, &args)?,
+            key,
+            args,
+        });
+    }
+    Ok(runnables)
+}
+
+/// Whether `file` is the root of `target`, or a module next to a `main.rs` root in a
+/// directory of its own such as `benches/parse/`.
+fn owns(target: &Target, file: &Path) -> bool {
+    match target.src_path.parent() {
+        Some(dir) if target.src_path.ends_with("main.rs") && !dir.ends_with("src") => {
+            file.starts_with(dir)
+        }
+        _ => target.src_path == file,
+    }
+}
+
+/// `runnables` as a JSON array.
+pub fn to_json(runnables: &[Runnable]) -> String {
+    serde_json::to_string_pretty(runnables).expect("runnables serialize to JSON")
+}
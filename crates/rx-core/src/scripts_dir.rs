@@ -0,0 +1,84 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use tracing::debug;
+
+use crate::{
+    helpers::shell_quote,
+    interpolation::InterpolationContext,
+    models::config::{is_key_pattern, CommandContext, CommandDetails, CommandType, Config},
+};
+
+/// The executable files directly in `dir` by the key they run as, their file name, sorted.
+/// Hidden files and names that would read as a key pattern are left out.
+pub fn scripts_in(dir: &Path) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut scripts: Vec<(String, PathBuf)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| is_executable(path))
+        .filter_map(|path| {
+            let name = path.file_name()?.to_str()?.to_string();
+            let usable = !name.starts_with('.') && !is_key_pattern(&name);
+            usable.then_some((name, path))
+        })
+        .collect();
+    scripts.sort();
+    scripts
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::metadata(path)
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+/// Windows has no executable bit, what runs is up to the file's extension
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+impl Config {
+    /// Adds a script config for each executable of the script context's `scripts_dir`,
+    /// resolved for the project of `cwd`, underneath the configs of the same key. The entries
+    /// stay out of the saved file like included ones, see [`Config::origin`].
+    pub fn merge_scripts_dir(&mut self, cwd: &Path) {
+        let Some(scripts_dir) = self
+            .commands
+            .get_config(CommandContext::Script)
+            .and_then(|command_config| command_config.scripts_dir.clone())
+        else {
+            return;
+        };
+        let interpolation = InterpolationContext::from_dir(cwd);
+        let workspace_folder = interpolation
+            .get("workspaceFolder")
+            .map_or(cwd.to_path_buf(), PathBuf::from);
+        let dir = workspace_folder.join(interpolation.interpolate(&scripts_dir));
+
+        let scripts = scripts_in(&dir);
+        debug!(dir = %dir.display(), scripts = scripts.len(), "scanning the scripts dir");
+        for (key, path) in scripts {
+            let description = path
+                .strip_prefix(&workspace_folder)
+                .unwrap_or(&path)
+                .display()
+                .to_string();
+            let details = CommandDetails {
+                command_type: CommandType::Shell,
+                command: shell_quote(&path.to_string_lossy()).into(),
+                description: Some(description),
+                working_directory: "${workspaceFolder}".to_string(),
+                ..Default::default()
+            };
+            self.add_underneath(CommandContext::Script, &key, &details, &path);
+        }
+    }
+}
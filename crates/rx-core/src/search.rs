@@ -0,0 +1,118 @@
+use regex::Regex;
+
+use crate::{
+    env::masked,
+    models::config::{CommandContext, Config},
+};
+
+/// What `rx search` looks for.
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    /// The characters in order, not necessarily next to each other, ignoring case
+    Fuzzy(String),
+    Regex(Regex),
+}
+
+impl Pattern {
+    pub fn fuzzy(pattern: &str) -> Self {
+        Pattern::Fuzzy(pattern.to_lowercase())
+    }
+
+    pub fn regex(pattern: &str) -> Result<Self, regex::Error> {
+        Regex::new(pattern).map(Pattern::Regex)
+    }
+
+    /// How well `text` matches, lower is better: 0 when it holds the pattern as is, 1 when
+    /// only its characters are there in order. `None` when it doesn't match.
+    fn score(&self, text: &str) -> Option<usize> {
+        match self {
+            Pattern::Regex(regex) => regex.is_match(text).then_some(0),
+            Pattern::Fuzzy(pattern) => {
+                let text = text.to_lowercase();
+                if text.contains(pattern.as_str()) {
+                    return Some(0);
+                }
+                let mut chars = text.chars();
+                pattern
+                    .chars()
+                    .all(|wanted| chars.any(|c| c == wanted))
+                    .then_some(1)
+            }
+        }
+    }
+}
+
+/// A field of a config that matched a search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchMatch {
+    /// `context::key` of the config
+    pub label: String,
+    pub context: CommandContext,
+    pub key: String,
+    /// `key`, `command`, `params`, `description` or `env.NAME`
+    pub field: String,
+    /// The field's value, masked for env variables that look like credentials
+    pub text: String,
+}
+
+/// The keys, commands, params, descriptions and env values of every config available on this
+/// platform matching `pattern`, exact matches first, then by context, key and field.
+pub fn search(config: &Config, pattern: &Pattern) -> Vec<SearchMatch> {
+    let mut matches = Vec::new();
+    for context in CommandContext::ALL {
+        let Some(command_config) = config.commands.get_config(context) else {
+            continue;
+        };
+        let mut keys: Vec<&String> = command_config
+            .configs
+            .iter()
+            .filter(|(_, details)| details.runs_on_this_platform())
+            .map(|(key, _)| key)
+            .collect();
+        keys.sort();
+
+        for key in keys {
+            let details = &command_config.configs[key];
+            let mut fields = vec![
+                ("key".to_string(), key.clone()),
+                ("command".to_string(), details.command.to_string()),
+                ("params".to_string(), details.params.to_string()),
+            ];
+            fields.extend(
+                details
+                    .description
+                    .iter()
+                    .map(|description| ("description".to_string(), description.clone())),
+            );
+            let mut env: Vec<(&String, &String)> = details.env.iter().collect();
+            env.sort();
+            fields.extend(
+                env.into_iter()
+                    .map(|(name, value)| (format!("env.{}", name), masked(name, value).into())),
+            );
+
+            for (field, text) in fields {
+                if text.is_empty() {
+                    continue;
+                }
+                if let Some(score) = pattern.score(&text) {
+                    let label = format!("{}::{}", context, key);
+                    matches.push((
+                        score,
+                        SearchMatch {
+                            label,
+                            context,
+                            key: key.clone(),
+                            field,
+                            text,
+                        },
+                    ));
+                }
+            }
+        }
+    }
+
+    // Stable, so each score keeps the context, key and field order
+    matches.sort_by_key(|(score, _)| *score);
+    matches.into_iter().map(|(_, found)| found).collect()
+}
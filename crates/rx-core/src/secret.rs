@@ -0,0 +1,314 @@
+use std::{
+    io::{self, BufRead, IsTerminal, Read, Write},
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::errors::ExecutionError;
+
+/// Prefix of an `env` value encrypted with age or GPG, the ciphertext following in base64.
+pub const ENCRYPTED: &str = "enc:";
+
+/// Prefix of an `env` value kept in the OS keychain, the name of its entry following.
+pub const KEYCHAIN: &str = "keychain:";
+
+/// The service rx's entries are stored under in the OS keychain.
+const KEYCHAIN_SERVICE: &str = "rx";
+
+/// How age's binary format starts, anything else is taken for GPG.
+const AGE_HEADER: &[u8] = b"age-encryption.org/";
+
+/// Whether `value` is encrypted or kept in the keychain rather than written out.
+pub fn is_sealed(value: &str) -> bool {
+    value.starts_with(ENCRYPTED) || value.starts_with(KEYCHAIN)
+}
+
+/// The value of the variable `name` a command gets for `value`: decrypted, read from the
+/// keychain, or `value` itself when it's neither.
+pub fn reveal(name: &str, value: &str) -> Result<String, ExecutionError> {
+    if let Some(entry) = value.strip_prefix(KEYCHAIN) {
+        return keychain_get(name, entry);
+    }
+    let Some(encoded) = value.strip_prefix(ENCRYPTED) else {
+        return Ok(value.to_string());
+    };
+
+    let ciphertext = decode(encoded.trim())
+        .ok_or_else(|| secret_error(name, "what follows enc: isn't base64"))?;
+    let plaintext = if ciphertext.starts_with(AGE_HEADER) {
+        let mut age = Command::new("age");
+        age.arg("--decrypt").arg("--identity").arg(age_identity());
+        pipe(name, "age", &mut age, &ciphertext)?
+    } else {
+        let mut gpg = Command::new("gpg");
+        gpg.args(["--batch", "--quiet", "--decrypt"]);
+        pipe(name, "gpg", &mut gpg, &ciphertext)?
+    };
+    String::from_utf8(plaintext).map_err(|_| secret_error(name, "it decrypted to binary data"))
+}
+
+/// Encrypts `value` of the variable `name` to `recipients`, age recipients (`age1...` or SSH
+/// public keys) or GPG key ids, as an `enc:` value anyone of them can decrypt.
+pub fn encrypt(name: &str, value: &str, recipients: &[String]) -> Result<String, ExecutionError> {
+    if recipients.is_empty() {
+        return Err(secret_error(
+            name,
+            "there is no one to encrypt it to, list the age recipients or GPG key ids in settings.secret_recipients",
+        ));
+    }
+
+    let age_recipients = recipients
+        .iter()
+        .filter(|recipient| is_age_recipient(recipient))
+        .count();
+    let ciphertext = if age_recipients == recipients.len() {
+        let mut age = Command::new("age");
+        age.arg("--encrypt");
+        for recipient in recipients {
+            age.arg("--recipient").arg(recipient);
+        }
+        pipe(name, "age", &mut age, value.as_bytes())?
+    } else if age_recipients == 0 {
+        let mut gpg = Command::new("gpg");
+        gpg.args(["--batch", "--yes", "--encrypt"]);
+        for recipient in recipients {
+            gpg.arg("--recipient").arg(recipient);
+        }
+        pipe(name, "gpg", &mut gpg, value.as_bytes())?
+    } else {
+        return Err(secret_error(
+            name,
+            "settings.secret_recipients mixes age recipients and GPG key ids",
+        ));
+    };
+    Ok(format!("{}{}", ENCRYPTED, encode(&ciphertext)))
+}
+
+/// Whether `recipient` is one age encrypts to rather than a GPG key id.
+pub fn is_age_recipient(recipient: &str) -> bool {
+    recipient.starts_with("age1") || recipient.starts_with("ssh-")
+}
+
+/// The file of age identities `enc:` values are decrypted with: `RX_AGE_IDENTITY`, or
+/// `rx/age.txt` in the user's config dir.
+pub fn age_identity() -> PathBuf {
+    match std::env::var_os("RX_AGE_IDENTITY") {
+        Some(path) => PathBuf::from(path),
+        None => dirs::config_dir()
+            .unwrap_or_default()
+            .join("rx")
+            .join("age.txt"),
+    }
+}
+
+/// The service and account of the keychain entry `entry`, `service/account` or an account of
+/// rx's own service.
+pub fn keychain_entry(entry: &str) -> (&str, &str) {
+    entry.split_once('/').unwrap_or((KEYCHAIN_SERVICE, entry))
+}
+
+/// The keychain entry `entry`, for the variable `name`.
+#[cfg(feature = "keychain")]
+fn keychain_get(name: &str, entry: &str) -> Result<String, ExecutionError> {
+    let (service, account) = keychain_entry(entry);
+    keyring::Entry::new(service, account)
+        .and_then(|stored| stored.get_password())
+        .map_err(|err| match err {
+            keyring::Error::NoEntry => no_entry(name, entry),
+            err => secret_error(name, &format!("the keychain failed: {}", err)),
+        })
+}
+
+/// Stores `value` of the variable `name` as the keychain entry `entry`, replacing the one there.
+#[cfg(feature = "keychain")]
+pub fn keychain_set(name: &str, entry: &str, value: &str) -> Result<(), ExecutionError> {
+    let (service, account) = keychain_entry(entry);
+    keyring::Entry::new(service, account)
+        .and_then(|stored| stored.set_password(value))
+        .map_err(|err| secret_error(name, &format!("the keychain failed: {}", err)))
+}
+
+#[cfg(not(feature = "keychain"))]
+fn keychain_get(name: &str, entry: &str) -> Result<String, ExecutionError> {
+    let (tool, mut command) = keychain_command(name, entry, false)?;
+    let output = pipe(name, tool, &mut command, &[]).map_err(|err| match err {
+        ExecutionError::Secret(..) => no_entry(name, entry),
+        err => err,
+    })?;
+    Ok(String::from_utf8_lossy(&output)
+        .trim_end_matches(['\n', '\r'])
+        .to_string())
+}
+
+#[cfg(not(feature = "keychain"))]
+pub fn keychain_set(name: &str, entry: &str, value: &str) -> Result<(), ExecutionError> {
+    let (tool, mut command) = keychain_command(name, entry, true)?;
+    // A bare -w, last, has security ask for the value, and again to confirm it, on stdin so it
+    // never shows in the process list
+    let input = match cfg!(target_os = "macos") {
+        true => format!("{0}\n{0}\n", value),
+        false => value.to_string(),
+    };
+    pipe(name, tool, &mut command, input.as_bytes())?;
+    Ok(())
+}
+
+/// Without the `keychain` feature, the command reading or storing a keychain entry: `security`
+/// with the login keychain on macOS, `secret-tool` with the Secret Service (GNOME Keyring,
+/// KWallet) elsewhere.
+#[cfg(all(not(feature = "keychain"), not(windows)))]
+fn keychain_command(
+    _name: &str,
+    entry: &str,
+    store: bool,
+) -> Result<(&'static str, Command), ExecutionError> {
+    let (service, account) = keychain_entry(entry);
+    if cfg!(target_os = "macos") {
+        let mut security = Command::new("security");
+        match store {
+            true => security.args(["add-generic-password", "-U"]),
+            false => security.arg("find-generic-password"),
+        };
+        security.args(["-s", service, "-a", account, "-w"]);
+        return Ok(("security", security));
+    }
+
+    let mut secret_tool = Command::new("secret-tool");
+    match store {
+        true => secret_tool
+            .arg("store")
+            .arg(format!("--label={}/{}", service, account)),
+        false => secret_tool.arg("lookup"),
+    };
+    secret_tool.args(["service", service, "account", account]);
+    Ok(("secret-tool", secret_tool))
+}
+
+#[cfg(all(not(feature = "keychain"), windows))]
+fn keychain_command(
+    name: &str,
+    _entry: &str,
+    _store: bool,
+) -> Result<(&'static str, Command), ExecutionError> {
+    Err(secret_error(
+        name,
+        "the Windows Credential Manager needs rx built with `--features keychain`",
+    ))
+}
+
+fn no_entry(name: &str, entry: &str) -> ExecutionError {
+    secret_error(
+        name,
+        &format!(
+            "the keychain has no entry '{}', store it with `rx secret set --keychain`",
+            entry
+        ),
+    )
+}
+
+/// Runs `command` of `tool` with `input` on its stdin and returns its stdout.
+fn pipe(
+    name: &str,
+    tool: &str,
+    command: &mut Command,
+    input: &[u8],
+) -> Result<Vec<u8>, ExecutionError> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => ExecutionError::ToolMissing(tool.into(), install_hint(tool)),
+            _ => ExecutionError::Spawn(tool.to_string(), err),
+        })?;
+    // Secrets are small enough to fit the pipe before the tool reads them
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input)
+            .map_err(|err| ExecutionError::Spawn(tool.to_string(), err))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|err| ExecutionError::Spawn(tool.to_string(), err))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(secret_error(
+            name,
+            &format!("{} failed: {}", tool, stderr.trim()),
+        ));
+    }
+    Ok(output.stdout)
+}
+
+fn install_hint(tool: &str) -> String {
+    let package = match tool {
+        "gpg" => "gnupg",
+        "secret-tool" => "libsecret-tools",
+        tool => tool,
+    };
+    format!(
+        "your system's package manager, e.g. apt install {}",
+        package
+    )
+}
+
+fn secret_error(name: &str, reason: &str) -> ExecutionError {
+    ExecutionError::Secret(name.to_string(), reason.to_string())
+}
+
+/// Reads a value from stdin, up to the end of its first line, without echoing it when stdin is
+/// a terminal.
+pub fn read_value() -> io::Result<String> {
+    let stdin = io::stdin();
+    let mut value = String::new();
+    if !stdin.is_terminal() {
+        stdin.lock().read_to_string(&mut value)?;
+        return Ok(value.lines().next().unwrap_or_default().to_string());
+    }
+
+    let echo = echo_off();
+    let read = stdin.lock().read_line(&mut value);
+    #[cfg(unix)]
+    if let Some(saved) = echo {
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &saved);
+        }
+        eprintln!();
+    }
+    #[cfg(not(unix))]
+    let _ = echo;
+    read?;
+    Ok(value.trim_end_matches(['\n', '\r']).to_string())
+}
+
+/// Stops the terminal echoing what's typed, returning its settings to restore.
+#[cfg(unix)]
+fn echo_off() -> Option<libc::termios> {
+    unsafe {
+        let mut saved: libc::termios = std::mem::zeroed();
+        if libc::tcgetattr(libc::STDIN_FILENO, &mut saved) != 0 {
+            return None;
+        }
+        let mut quiet = saved;
+        quiet.c_lflag &= !libc::ECHO;
+        (libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &quiet) == 0).then_some(saved)
+    }
+}
+
+#[cfg(not(unix))]
+fn echo_off() -> Option<()> {
+    None
+}
+
+/// `bytes` in standard base64, padded.
+pub fn encode(bytes: &[u8]) -> String {
+    STANDARD.encode(bytes)
+}
+
+/// The bytes of standard base64 `encoded`, `None` when it isn't.
+pub fn decode(encoded: &str) -> Option<Vec<u8>> {
+    STANDARD.decode(encoded).ok()
+}
@@ -0,0 +1,181 @@
+use std::io::{self, BufRead};
+
+use serde::Serialize;
+
+/// Where `rx serve` listens unless told otherwise.
+pub const DEFAULT_PORT: u16 = 7878;
+
+/// Largest request body read, a run is requested with a small JSON object.
+const MAX_BODY: usize = 64 * 1024;
+
+/// An HTTP request to `rx serve`, with the headers it looks at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+    pub method: String,
+    /// Without the query string
+    pub path: String,
+    pub host: Option<String>,
+    pub origin: Option<String>,
+    pub body: String,
+}
+
+impl HttpRequest {
+    /// Reads a request off `reader`. `None` when the client closed the connection before
+    /// sending one.
+    pub fn read(reader: &mut impl BufRead) -> io::Result<Option<Self>> {
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line)? == 0 {
+            return Ok(None);
+        }
+        let mut parts = request_line.split_whitespace();
+        let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+            return Err(invalid("malformed request line"));
+        };
+        let path = target.split('?').next().unwrap_or(target).to_string();
+
+        let mut request = HttpRequest {
+            method: method.to_string(),
+            path,
+            host: None,
+            origin: None,
+            body: String::new(),
+        };
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 {
+                return Err(invalid("headers cut short"));
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            let Some((name, value)) = line.split_once(':') else {
+                return Err(invalid("malformed header"));
+            };
+            let value = value.trim().to_string();
+            match name.trim().to_ascii_lowercase().as_str() {
+                "host" => request.host = Some(value),
+                "origin" => request.origin = Some(value),
+                "content-length" => {
+                    content_length = value
+                        .parse()
+                        .map_err(|_| invalid("malformed content-length"))?
+                }
+                _ => {}
+            }
+        }
+
+        if content_length > MAX_BODY {
+            return Err(invalid("request body too large"));
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body)?;
+        request.body = String::from_utf8(body).map_err(|_| invalid("body is not UTF-8"))?;
+        Ok(Some(request))
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// What `rx serve` answers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Route {
+    /// `GET /`, the dashboard page
+    Dashboard,
+    /// `GET /api/configs`, every config as a [`crate::daemon::ConfigSummary`]
+    Configs,
+    /// `GET /api/runs`, the runs started from the dashboard as [`RunSummary`]s
+    Runs,
+    /// `POST /api/runs` with a [`crate::daemon::RunRequest`]
+    StartRun,
+    /// `GET /api/runs/{id}/events`, the run's [`crate::events::RunEvent`]s as server-sent
+    /// events, from its start
+    RunEvents(u64),
+    /// `POST /api/runs/{id}/kill`
+    KillRun(u64),
+}
+
+impl Route {
+    /// The route of `method` and `path`, `None` when there is none.
+    pub fn find(method: &str, path: &str) -> Option<Route> {
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        match (method, segments.as_slice()) {
+            ("GET", [""]) => Some(Route::Dashboard),
+            ("GET", ["api", "configs"]) => Some(Route::Configs),
+            ("GET", ["api", "runs"]) => Some(Route::Runs),
+            ("POST", ["api", "runs"]) => Some(Route::StartRun),
+            ("GET", ["api", "runs", id, "events"]) => id.parse().ok().map(Route::RunEvents),
+            ("POST", ["api", "runs", id, "kill"]) => id.parse().ok().map(Route::KillRun),
+            _ => None,
+        }
+    }
+}
+
+/// Whether `request` could come from the dashboard on `port`: addressed to the loopback
+/// address rx listens on, so a site rebinding its domain to 127.0.0.1 can't reach it, and when
+/// it changes something, not sent by another site's page.
+pub fn is_allowed(request: &HttpRequest, port: u16) -> bool {
+    let hosts = [format!("127.0.0.1:{}", port), format!("localhost:{}", port)];
+    let host_allowed = request
+        .host
+        .as_deref()
+        .is_some_and(|host| hosts.iter().any(|allowed| allowed == host));
+    let origin_allowed = request.method == "GET"
+        || request.origin.as_deref().is_none_or(|origin| {
+            hosts
+                .iter()
+                .any(|allowed| origin == format!("http://{}", allowed))
+        });
+    host_allowed && origin_allowed
+}
+
+/// A run started from the dashboard.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RunSummary {
+    pub id: u64,
+    pub label: String,
+    pub running: bool,
+    /// Once it finished, 130 when it was stopped
+    pub exit_code: Option<i32>,
+    /// Milliseconds since the epoch
+    pub started_ms: u64,
+}
+
+/// A whole response carrying `body`, after which the connection closes.
+pub fn response(status: &str, content_type: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )
+}
+
+/// A `200 OK` response carrying `value` as JSON.
+pub fn json_response(value: &impl Serialize) -> String {
+    let body = serde_json::to_string(value).unwrap_or_default();
+    response("200 OK", "application/json", &body)
+}
+
+/// An error response, `{"error":"..."}`.
+pub fn error_response(status: &str, message: &str) -> String {
+    let body = serde_json::json!({ "error": message }).to_string();
+    response(status, "application/json", &body)
+}
+
+/// The head of a response streaming server-sent events, see [`sse_event`].
+pub const EVENT_STREAM_HEAD: &str = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n";
+
+/// `data` as a server-sent event.
+pub fn sse_event(data: &str) -> String {
+    let mut event: String = data
+        .lines()
+        .map(|line| format!("data: {}\n", line))
+        .collect();
+    event.push('\n');
+    event
+}
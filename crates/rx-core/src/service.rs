@@ -0,0 +1,277 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::{
+    errors::{ConfigError, ExecutionError},
+    executor::{ready::Services, runner::Runner},
+    models::config::{CommandContext, Config},
+    store::Store,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A service `rx up` started.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ServiceRecord {
+    pub pid: u32,
+    /// Seconds since the unix epoch
+    pub started_at: u64,
+    /// File the service's stdout and stderr are appended to
+    pub log: PathBuf,
+}
+
+impl ServiceRecord {
+    pub fn uptime(&self) -> Duration {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        Duration::from_secs(now.saturating_sub(self.started_at))
+    }
+}
+
+/// The services of a project started with `rx up`, by key in the script context.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct ServiceRegistry {
+    #[serde(default)]
+    pub services: BTreeMap<String, ServiceRecord>,
+}
+
+impl ServiceRegistry {
+    fn path(store: &Store) -> PathBuf {
+        store.path("services.toml")
+    }
+
+    /// The services started, none before the first.
+    pub fn load(store: &Store) -> Result<ServiceRegistry, Box<dyn Error>> {
+        let path = Self::path(store);
+        if !path.exists() {
+            return Ok(ServiceRegistry::default());
+        }
+
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, store: &Store) -> Result<(), Box<dyn Error>> {
+        let path = Self::path(store);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Where the output of the service `key` goes.
+pub fn log_path(store: &Store, key: &str) -> PathBuf {
+    store.path(&format!("services/{}.log", key))
+}
+
+/// Cuts the log at `path` to its latest half when it's bigger than `max_bytes`, starting at a
+/// line and after a marker telling how much was dropped. Whether it was cut.
+pub fn trim_log(path: &Path, max_bytes: u64) -> io::Result<bool> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(err) => return Err(err),
+    };
+    let size = file.metadata()?.len();
+    if size <= max_bytes {
+        return Ok(false);
+    }
+
+    // From the byte before, to tell whether the half starts a line
+    file.seek(SeekFrom::Start(size - max_bytes / 2 - 1))?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail)?;
+    let start = tail
+        .iter()
+        .position(|byte| *byte == b'\n')
+        .map_or(tail.len(), |newline| newline + 1);
+    let dropped = size - (tail.len() - start) as u64;
+    let mut trimmed = format!("[rx: log truncated, {} bytes dropped]\n", dropped).into_bytes();
+    trimmed.extend_from_slice(&tail[start..]);
+    fs::write(path, trimmed)?;
+    Ok(true)
+}
+
+/// Keys of the script configs with `service = true`, ordered.
+pub fn service_keys(config: &Config) -> Vec<String> {
+    config
+        .commands
+        .get_config(CommandContext::Script)
+        .map(|script| {
+            let mut keys: Vec<String> = script
+                .configs
+                .iter()
+                .filter(|(_, details)| details.service)
+                .map(|(key, _)| key.clone())
+                .collect();
+            keys.sort();
+            keys
+        })
+        .unwrap_or_default()
+}
+
+impl<'a> Runner<'a> {
+    /// Starts the service `key` in the background after its pre_commands, its output appended
+    /// to `log`, and returns its pid. It runs on after rx exits, detached from rx's terminal
+    /// and in a process group of its own that [`stop`] ends.
+    pub fn spawn_service(&self, key: &str, log: &Path) -> Result<u32, ExecutionError> {
+        let label = self.label(key);
+        let details = self.details(key)?;
+        if !details.service {
+            return Err(ExecutionError::NotAService(label));
+        }
+
+        thread::scope(|scope| {
+            let mut services = Services::new(scope);
+            let result = self.run_pre_commands(key, &mut Vec::new(), &mut services);
+            services.stop(self);
+            result
+        })?;
+
+        let details = self.target_details(key, details, &[]);
+        self.ensure_plugin(key, &details, &[])?;
+        self.ensure_ports(key, &details)?;
+        let (details, _) = self.inline_script(key, &details, true)?;
+        self.ensure_working_directory(&details)?;
+        let mut command = self.build_command(&details, &[])?;
+
+        let spawn_error = |err| ExecutionError::Spawn(label.clone(), err);
+        if let Some(parent) = log.parent() {
+            fs::create_dir_all(parent).map_err(spawn_error)?;
+        }
+        let max_log_bytes = details.max_log_bytes().map_err(|reason| {
+            ConfigError::InvalidValue(format!("{}.max_log_size", label), reason)
+        })?;
+        if let Some(max_bytes) = max_log_bytes {
+            match trim_log(log, max_bytes) {
+                Ok(true) => info!(label, log = %log.display(), "trimmed the log"),
+                Ok(false) => {}
+                Err(err) => warn!(label, %err, "could not trim the log"),
+            }
+        }
+        let output = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(log)
+            .map_err(spawn_error)?;
+        command
+            .stdin(Stdio::null())
+            .stdout(output.try_clone().map_err(spawn_error)?)
+            .stderr(output);
+        detach(&mut command);
+
+        let child = command.spawn().map_err(spawn_error)?;
+        info!(label, pid = child.id(), log = %log.display(), "service started");
+        Ok(child.id())
+    }
+}
+
+/// Whether the process `pid` still runs.
+#[cfg(unix)]
+pub fn is_running(pid: u32) -> bool {
+    let pid = pid as libc::pid_t;
+    unsafe {
+        // A service rx started itself and that exited is gone once reaped
+        let mut status = 0;
+        if libc::waitpid(pid, &mut status, libc::WNOHANG) == pid {
+            return false;
+        }
+        libc::kill(pid, 0) == 0
+            || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+    }
+}
+
+#[cfg(windows)]
+pub fn is_running(pid: u32) -> bool {
+    Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .is_ok_and(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .split_whitespace()
+                .any(|field| field == pid.to_string())
+        })
+}
+
+/// Stops the service `pid` and what it started, asking first and killing them when they're
+/// still running after `grace_period`. Whether it stopped.
+pub fn stop(pid: u32, grace_period: Duration) -> bool {
+    signal(pid, false);
+    let deadline = Instant::now() + grace_period;
+    while Instant::now() < deadline {
+        if !is_running(pid) {
+            return true;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+    signal(pid, true);
+    let deadline = Instant::now() + Duration::from_secs(1);
+    while is_running(pid) && Instant::now() < deadline {
+        thread::sleep(POLL_INTERVAL);
+    }
+    !is_running(pid)
+}
+
+#[cfg(unix)]
+fn signal(pid: u32, kill: bool) {
+    let signal = if kill { libc::SIGKILL } else { libc::SIGTERM };
+    // The service leads its own session, so its pid is also its process group's id
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), signal);
+    }
+}
+
+#[cfg(windows)]
+fn signal(pid: u32, kill: bool) {
+    let mut taskkill = Command::new("taskkill");
+    taskkill.args(["/PID", &pid.to_string(), "/T"]);
+    if kill {
+        taskkill.arg("/F");
+    }
+    let _ = taskkill
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
+/// Detaches `command` from rx: a session of its own on unix, so closing rx's terminal doesn't
+/// hang it up, and no console on Windows.
+fn detach(command: &mut Command) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        // Only async-signal-safe calls between fork and exec
+        unsafe {
+            command.pre_exec(|| {
+                if libc::setsid() == -1 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const DETACHED_PROCESS: u32 = 0x0000_0008;
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+        command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+    }
+}
@@ -0,0 +1,116 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize},
+        Arc, Mutex, OnceLock,
+    },
+};
+
+use tokio::runtime::{Builder, Handle, Runtime, RuntimeFlavor};
+
+/// What rx keeps across the runs of a process: the runtime their processes are driven on, the
+/// temp files still in use, who reads the terminal key by key and what was found out about the
+/// machine, such as which tools are installed. Clones share it.
+///
+/// The CLI makes one for the process and hands it to every [`crate::executor::runner::Runner`];
+/// an embedder holds one for as long as it runs configs, a runner given none starts its own.
+#[derive(Debug, Clone, Default)]
+pub struct Session(Arc<State>);
+
+#[derive(Debug, Default)]
+struct State {
+    /// Started on first use, see [`Session::runtime`]
+    runtime: OnceLock<Runtime>,
+    /// The temp resources of the runs still going, see [`crate::executor::temp::remove_live`]
+    live: Mutex<Vec<PathBuf>>,
+    /// Numbers the temp paths of the process, see [`crate::executor::temp::temp_path`]
+    temp_paths: AtomicUsize,
+    /// Set while something reads rx's stdin key by key, so the keystrokes go to one reader
+    stdin_claimed: AtomicBool,
+    /// Whether a tool could be run, by its name, each checked once
+    installed: Mutex<HashMap<&'static str, bool>>,
+    /// The subcommands `cargo --list` lists, aliases included
+    cargo_subcommands: OnceLock<Vec<String>>,
+    /// The triple rustc compiles for by default
+    host_triple: OnceLock<String>,
+    /// `sudo`, or `doas` where only it is installed
+    #[cfg(unix)]
+    elevation_tool: OnceLock<&'static str>,
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The scheduler the processes of the runs are driven on: waiting for them to exit, pumping
+    /// their output, feeding their stdin and probing their readiness. Started on first use.
+    pub fn runtime(&self) -> &Runtime {
+        self.0.runtime.get_or_init(|| {
+            Builder::new_multi_thread()
+                .thread_name("rx-runtime")
+                .enable_all()
+                .build()
+                .expect("failed to start the tokio runtime")
+        })
+    }
+
+    /// Runs `future` to completion from blocking code, the facade the rest of rx calls the async
+    /// core through. Called on a worker of a multi-threaded runtime, e.g. an embedder's, the
+    /// worker hands its other tasks off while it blocks.
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        match Handle::try_current() {
+            Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+                tokio::task::block_in_place(|| handle.block_on(future))
+            }
+            _ => self.runtime().block_on(future),
+        }
+    }
+
+    pub(crate) fn live(&self) -> &Mutex<Vec<PathBuf>> {
+        &self.0.live
+    }
+
+    pub(crate) fn temp_paths(&self) -> &AtomicUsize {
+        &self.0.temp_paths
+    }
+
+    pub(crate) fn stdin_claimed(&self) -> &AtomicBool {
+        &self.0.stdin_claimed
+    }
+
+    /// Whether `tool` can be run, `check` telling the first time it's asked.
+    pub(crate) fn installed(&self, tool: &'static str, check: impl FnOnce() -> bool) -> bool {
+        if let Some(installed) = self.0.installed.lock().unwrap().get(tool) {
+            return *installed;
+        }
+        // Checked unlocked, a check runs a process
+        let installed = check();
+        self.0.installed.lock().unwrap().insert(tool, installed);
+        installed
+    }
+
+    pub(crate) fn cargo_subcommands(&self, list: impl FnOnce() -> Vec<String>) -> &[String] {
+        self.0.cargo_subcommands.get_or_init(list)
+    }
+
+    pub(crate) fn host_triple(&self, find: impl FnOnce() -> String) -> &str {
+        self.0.host_triple.get_or_init(find)
+    }
+
+    #[cfg(unix)]
+    pub(crate) fn elevation_tool(&self, find: impl FnOnce() -> &'static str) -> &'static str {
+        self.0.elevation_tool.get_or_init(find)
+    }
+}
+
+impl Drop for State {
+    fn drop(&mut self) {
+        // Dropped from a task of the runtime, waiting for it to shut down would panic
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    helpers::{default_config_path, save_config_file},
+    models::{config::Config, format::ConfigFormat},
+};
+
+/// A config file, handed to whatever reads or writes it rather than kept in a global, so one
+/// process can work on several configs, e.g. to diff or merge them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigSource {
+    path: PathBuf,
+    /// Create the missing directories of the file when saving it
+    create_dirs: bool,
+}
+
+impl ConfigSource {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            create_dirs: false,
+        }
+    }
+
+    pub fn create_dirs(mut self, create_dirs: bool) -> Self {
+        self.create_dirs = create_dirs;
+        self
+    }
+
+    /// The file given with `--config`, otherwise the one [`default_config_path`] finds.
+    pub fn resolve(path: Option<PathBuf>) -> Result<Self, Box<dyn Error>> {
+        path.or_else(default_config_path)
+            .map(Self::new)
+            .ok_or_else(|| {
+                "Could not find a home directory, give the config file with --config".into()
+            })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn format(&self) -> ConfigFormat {
+        ConfigFormat::from_path(&self.path)
+    }
+
+    pub fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    /// Reads the file with its includes.
+    pub fn load(&self) -> Result<Config, Box<dyn Error>> {
+        Config::load(Some(self.path.clone()))
+    }
+
+    /// Writes the main file of `config`, in the format of its extension, see
+    /// [`save_config_file`].
+    pub fn save(&self, config: &Config) -> Result<(), Box<dyn Error>> {
+        let content = config.to_file_string(&self.path)?;
+        save_config_file(&self.path, &content, self.create_dirs)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,281 @@
+use std::{
+    env,
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    str::FromStr,
+};
+
+use crate::{errors::ConfigError, helpers::shell_quote};
+
+/// How the output of the commands of a run is shown, `--ui`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UiMode {
+    /// Lines as the commands print them
+    #[default]
+    Interleaved,
+    /// Each line after the label of the command that printed it
+    Prefixed,
+    /// A region of the terminal per command, or a tmux pane when inside tmux
+    Split,
+}
+
+impl FromStr for UiMode {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "interleaved" => Ok(UiMode::Interleaved),
+            "prefixed" => Ok(UiMode::Prefixed),
+            "split" => Ok(UiMode::Split),
+            _ => Err(ConfigError::UnknownUiMode(s.to_string())),
+        }
+    }
+}
+
+/// Where a command shown in a [`SplitScreen`] is at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionStatus {
+    Running,
+    Succeeded,
+    Failed,
+    Skipped,
+}
+
+impl RegionStatus {
+    fn marker(self) -> char {
+        match self {
+            RegionStatus::Running => '▶',
+            RegionStatus::Succeeded => '✓',
+            RegionStatus::Failed => '✗',
+            RegionStatus::Skipped => '↷',
+        }
+    }
+}
+
+struct Region {
+    label: String,
+    status: RegionStatus,
+    lines: Vec<String>,
+}
+
+/// The output of commands running at once, each in a region of the terminal with a title line
+/// and its last lines below. Regions open in the order their commands first show up and share
+/// the height evenly. The whole output is kept for [`SplitScreen::transcript`].
+pub struct SplitScreen {
+    regions: Vec<Region>,
+    width: usize,
+    height: usize,
+}
+
+impl SplitScreen {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            regions: Vec::new(),
+            width,
+            height,
+        }
+    }
+
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.height = height;
+    }
+
+    /// Adds a line of output to the region of `label`.
+    pub fn push(&mut self, label: &str, line: &str) {
+        let line = line.trim_end_matches('\r').replace('\t', "    ");
+        self.region(label).lines.push(line);
+    }
+
+    pub fn set_status(&mut self, label: &str, status: RegionStatus) {
+        self.region(label).status = status;
+    }
+
+    /// Gives the regions still running `status`, once the run is over.
+    pub fn settle(&mut self, status: RegionStatus) {
+        for region in &mut self.regions {
+            if region.status == RegionStatus::Running {
+                region.status = status;
+            }
+        }
+    }
+
+    /// The screen as terminal escapes, drawn over the previous frame from the top left.
+    pub fn frame(&self) -> String {
+        let mut rows: Vec<String> = Vec::with_capacity(self.height);
+        let count = self.regions.len();
+        for (index, region) in self.regions.iter().enumerate() {
+            let mut height = (self.height / count.max(1)).max(2);
+            if index == count - 1 {
+                height = height.max(self.height.saturating_sub(rows.len()));
+            }
+
+            let title = format!("{} {} ", region.status.marker(), region.label);
+            let fill = self.width.saturating_sub(title.chars().count());
+            rows.push(format!("{}{}", title, "─".repeat(fill)));
+            let shown = height - 1;
+            let start = region.lines.len().saturating_sub(shown);
+            rows.extend(region.lines[start..].iter().cloned());
+            rows.extend((region.lines.len() - start..shown).map(|_| String::new()));
+        }
+        rows.truncate(self.height);
+
+        let mut frame = String::from("\x1b[H");
+        for (index, row) in rows.iter().enumerate() {
+            if index > 0 {
+                frame.push_str("\r\n");
+            }
+            frame.extend(row.chars().take(self.width));
+            frame.push_str("\x1b[K");
+        }
+        frame.push_str("\x1b[J");
+        frame
+    }
+
+    /// The full output of every region after its title, to print once the screen is left.
+    pub fn transcript(&self) -> String {
+        let mut transcript = String::new();
+        for region in &self.regions {
+            transcript.push_str(&format!("{} {}\n", region.status.marker(), region.label));
+            for line in &region.lines {
+                transcript.push_str(line);
+                transcript.push('\n');
+            }
+        }
+        transcript
+    }
+
+    fn region(&mut self, label: &str) -> &mut Region {
+        let index = match self.regions.iter().position(|region| region.label == label) {
+            Some(index) => index,
+            None => {
+                self.regions.push(Region {
+                    label: label.to_string(),
+                    status: RegionStatus::Running,
+                    lines: Vec::new(),
+                });
+                self.regions.len() - 1
+            }
+        };
+        &mut self.regions[index]
+    }
+}
+
+/// Columns and rows of the terminal on stderr, from `COLUMNS` and `LINES` when it can't be
+/// asked, 80x24 otherwise.
+pub fn terminal_size() -> (usize, usize) {
+    #[cfg(unix)]
+    {
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        let asked = unsafe { libc::ioctl(libc::STDERR_FILENO, libc::TIOCGWINSZ, &mut size) } == 0;
+        if asked && size.ws_col > 0 && size.ws_row > 0 {
+            return (size.ws_col as usize, size.ws_row as usize);
+        }
+    }
+    let from_env = |name: &str, default: usize| {
+        env::var(name)
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .filter(|value| *value > 0)
+            .unwrap_or(default)
+    };
+    (from_env("COLUMNS", 80), from_env("LINES", 24))
+}
+
+/// Whether rx runs inside a tmux session.
+pub fn inside_tmux() -> bool {
+    env::var_os("TMUX").is_some_and(|value| !value.is_empty())
+}
+
+/// tmux panes following the output of commands, one per label, split off the pane rx runs in.
+/// rx appends the lines to a log file each pane tails, and a pane waits for Enter to close once
+/// rx exits.
+pub struct TmuxPanes {
+    dir: PathBuf,
+    /// The log of each label, `None` when its pane couldn't be opened
+    logs: Vec<(String, Option<File>)>,
+}
+
+impl TmuxPanes {
+    /// Panes whose logs go in `dir`.
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            logs: Vec::new(),
+        }
+    }
+
+    /// Adds a line to the pane of `label`, opening it on the first line. An error when the pane
+    /// couldn't be opened, for the line to be shown some other way.
+    pub fn push(&mut self, label: &str, line: &str) -> io::Result<()> {
+        let index = match self.logs.iter().position(|(logged, _)| logged == label) {
+            Some(index) => index,
+            None => {
+                let log = self.open(label);
+                if let Err(err) = &log {
+                    tracing::warn!("could not open a tmux pane for {}: {}", label, err);
+                }
+                self.logs.push((label.to_string(), log.ok()));
+                self.logs.len() - 1
+            }
+        };
+        match &mut self.logs[index].1 {
+            Some(log) => writeln!(log, "{}", line),
+            None => Err(io::Error::other(format!("no tmux pane for {}", label))),
+        }
+    }
+
+    fn open(&self, label: &str) -> io::Result<File> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join(format!("{}.log", self.logs.len()));
+        let log = File::create(&path)?;
+
+        let script = pane_script(&path, label, std::process::id());
+        let pane = tmux(&[
+            "split-window",
+            "-d",
+            "-P",
+            "-F",
+            "#{pane_id}",
+            "sh",
+            "-c",
+            &script,
+        ])?;
+        tmux(&["select-pane", "-t", &pane, "-T", label])?;
+        tmux(&["select-layout", "tiled"])?;
+        Ok(log)
+    }
+}
+
+/// The shell script of a pane: tails `log` while the rx process `pid` lives, then removes it,
+/// and its dir once empty, and waits for Enter.
+pub fn pane_script(log: &Path, label: &str, pid: u32) -> String {
+    let dir = log.parent().unwrap_or(Path::new("."));
+    let log = shell_quote(&log.to_string_lossy());
+    format!(
+        "tail -n +1 -f {log} & tail=$!; \
+         while kill -0 {pid} 2>/dev/null; do sleep 1; done; \
+         sleep 1; kill $tail; rm -f {log}; rmdir {dir} 2>/dev/null; \
+         printf '\\n%s finished, press Enter to close' {label}; read _",
+        log = log,
+        dir = shell_quote(&dir.to_string_lossy()),
+        pid = pid,
+        label = shell_quote(label)
+    )
+}
+
+/// Runs `tmux` with `args`, returning what it printed.
+fn tmux(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("tmux")
+        .args(args)
+        .stdin(Stdio::null())
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
@@ -0,0 +1,339 @@
+use std::{
+    cmp::Reverse,
+    collections::BTreeMap,
+    error::Error,
+    fmt::Write,
+    fs,
+    path::PathBuf,
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{backup::civil_from_days, errors::ConfigError, notify::format_duration, store::Store};
+
+/// Runs kept per label, older ones are dropped as new ones are recorded.
+pub const HISTORY_LIMIT: usize = 100;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct RunRecord {
+    pub success: bool,
+    pub duration_ms: u64,
+    /// Seconds since the unix epoch
+    pub finished_at: u64,
+}
+
+/// The runs of a project by label such as `test::unit`, oldest first.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct RunHistory {
+    #[serde(default)]
+    pub runs: BTreeMap<String, Vec<RunRecord>>,
+}
+
+/// Durations of the successful runs of a label and how often it failed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunStats {
+    pub label: String,
+    pub runs: usize,
+    /// `None` while no run succeeded
+    pub average: Option<Duration>,
+    pub median: Option<Duration>,
+    pub p95: Option<Duration>,
+    /// Share of the runs that failed, from 0 to 1
+    pub failure_rate: f64,
+}
+
+/// How `rx report` writes its summary.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// Tables to paste into an issue or a wiki
+    #[default]
+    Markdown,
+    Json,
+}
+
+impl FromStr for ReportFormat {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "markdown" | "md" => Ok(ReportFormat::Markdown),
+            "json" => Ok(ReportFormat::Json),
+            _ => Err(ConfigError::UnknownReportFormat(s.to_string())),
+        }
+    }
+}
+
+/// A summary of the recorded runs of a project, made from the local history alone.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Report {
+    pub configs: usize,
+    pub runs: usize,
+    pub failures: usize,
+    /// Seconds since the unix epoch the first and last recorded runs finished at
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+    /// The configs run the most
+    pub most_run: Vec<ReportEntry>,
+    /// The configs that failed, the highest failure rate first
+    pub failing: Vec<ReportEntry>,
+    /// The configs with the longest average successful run
+    pub slowest: Vec<ReportEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReportEntry {
+    pub label: String,
+    pub runs: usize,
+    pub failures: usize,
+    pub failure_rate: f64,
+    /// `None` while no run succeeded
+    pub average_ms: Option<u64>,
+    pub p95_ms: Option<u64>,
+}
+
+impl Report {
+    /// The report of `history`, keeping the first `top` configs of each list.
+    pub fn new(history: &RunHistory, top: usize) -> Report {
+        let entries: Vec<ReportEntry> = history
+            .stats()
+            .into_iter()
+            .map(|stats| ReportEntry {
+                failures: history.runs[&stats.label]
+                    .iter()
+                    .filter(|run| !run.success)
+                    .count(),
+                label: stats.label,
+                runs: stats.runs,
+                failure_rate: stats.failure_rate,
+                average_ms: stats.average.map(|average| average.as_millis() as u64),
+                p95_ms: stats.p95.map(|p95| p95.as_millis() as u64),
+            })
+            .collect();
+        let finished_at = || history.runs.values().flatten().map(|run| run.finished_at);
+
+        let mut most_run = entries.clone();
+        most_run.sort_by_key(|entry| Reverse(entry.runs));
+        most_run.truncate(top);
+        let mut failing: Vec<ReportEntry> = entries
+            .iter()
+            .filter(|entry| entry.failures > 0)
+            .cloned()
+            .collect();
+        failing.sort_by(|a, b| {
+            b.failure_rate
+                .total_cmp(&a.failure_rate)
+                .then(b.runs.cmp(&a.runs))
+        });
+        failing.truncate(top);
+        let mut slowest: Vec<ReportEntry> = entries
+            .iter()
+            .filter(|entry| entry.average_ms.is_some())
+            .cloned()
+            .collect();
+        slowest.sort_by_key(|entry| Reverse(entry.average_ms));
+        slowest.truncate(top);
+
+        Report {
+            configs: entries.len(),
+            runs: entries.iter().map(|entry| entry.runs).sum(),
+            failures: entries.iter().map(|entry| entry.failures).sum(),
+            since: finished_at().min(),
+            until: finished_at().max(),
+            most_run,
+            failing,
+            slowest,
+        }
+    }
+
+    /// The report as pretty-printed JSON.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        Ok(serde_json::to_string_pretty(self)? + "\n")
+    }
+
+    /// The report as markdown, under a heading naming `project`.
+    pub fn to_markdown(&self, project: &str) -> String {
+        let duration = |ms: Option<u64>| {
+            ms.map_or("-".to_string(), |ms| {
+                format_duration(Duration::from_millis(ms))
+            })
+        };
+        let percent = |rate: f64| format!("{:.0}%", rate * 100.0);
+
+        let mut markdown = format!("# rx report for {}\n\n", project);
+        let _ = write!(
+            markdown,
+            "{} runs of {} configs, {} failed",
+            self.runs, self.configs, self.failures
+        );
+        if let (Some(since), Some(until)) = (self.since, self.until) {
+            let _ = write!(markdown, ", from {} to {}", date(since), date(until));
+        }
+        markdown.push_str(".\n\n");
+        let mut table = |title: &str, header: &str, rows: Vec<String>| {
+            let _ = writeln!(markdown, "## {}\n", title);
+            if rows.is_empty() {
+                markdown.push_str("None.\n\n");
+                return;
+            }
+            let columns = header.matches('|').count() - 1;
+            let _ = writeln!(markdown, "{}", header);
+            let _ = writeln!(markdown, "|{}", "---|".repeat(columns));
+            for row in rows {
+                let _ = writeln!(markdown, "{}", row);
+            }
+            markdown.push('\n');
+        };
+        table(
+            "Most run",
+            "| config | runs | failed | avg |",
+            self.most_run
+                .iter()
+                .map(|entry| {
+                    format!(
+                        "| `{}` | {} | {} | {} |",
+                        entry.label,
+                        entry.runs,
+                        percent(entry.failure_rate),
+                        duration(entry.average_ms)
+                    )
+                })
+                .collect(),
+        );
+        table(
+            "Failing",
+            "| config | failures | runs | failed |",
+            self.failing
+                .iter()
+                .map(|entry| {
+                    format!(
+                        "| `{}` | {} | {} | {} |",
+                        entry.label,
+                        entry.failures,
+                        entry.runs,
+                        percent(entry.failure_rate)
+                    )
+                })
+                .collect(),
+        );
+        table(
+            "Slowest",
+            "| config | avg | p95 | runs |",
+            self.slowest
+                .iter()
+                .map(|entry| {
+                    format!(
+                        "| `{}` | {} | {} | {} |",
+                        entry.label,
+                        duration(entry.average_ms),
+                        duration(entry.p95_ms),
+                        entry.runs
+                    )
+                })
+                .collect(),
+        );
+        markdown.truncate(markdown.trim_end().len() + 1);
+        markdown
+    }
+}
+
+/// The UTC date `seconds` after the unix epoch, as `2026-10-16`.
+fn date(seconds: u64) -> String {
+    let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+impl RunHistory {
+    fn path(store: &Store) -> PathBuf {
+        store.path("stats.toml")
+    }
+
+    /// The recorded runs, none before the first.
+    pub fn load(store: &Store) -> Result<RunHistory, Box<dyn Error>> {
+        let path = Self::path(store);
+        if !path.exists() {
+            return Ok(RunHistory::default());
+        }
+
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, store: &Store) -> Result<(), Box<dyn Error>> {
+        let path = Self::path(store);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Adds a run of `label` that just finished, keeping the last [`HISTORY_LIMIT`].
+    pub fn record(&mut self, label: &str, success: bool, duration: Duration) {
+        let finished_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        let runs = self.runs.entry(label.to_string()).or_default();
+        runs.push(RunRecord {
+            success,
+            duration_ms: duration.as_millis() as u64,
+            finished_at,
+        });
+        if runs.len() > HISTORY_LIMIT {
+            runs.drain(..runs.len() - HISTORY_LIMIT);
+        }
+    }
+
+    /// The average duration of the successful runs of `label`.
+    pub fn average(&self, label: &str) -> Option<Duration> {
+        average(&self.durations(label))
+    }
+
+    /// The stats of every label with runs, ordered by label.
+    pub fn stats(&self) -> Vec<RunStats> {
+        self.runs
+            .iter()
+            .filter(|(_, runs)| !runs.is_empty())
+            .map(|(label, runs)| {
+                let durations = self.durations(label);
+                let failures = runs.iter().filter(|run| !run.success).count();
+                RunStats {
+                    label: label.clone(),
+                    runs: runs.len(),
+                    average: average(&durations),
+                    median: percentile(&durations, 50),
+                    p95: percentile(&durations, 95),
+                    failure_rate: failures as f64 / runs.len() as f64,
+                }
+            })
+            .collect()
+    }
+
+    /// Durations of the successful runs of `label`, sorted.
+    fn durations(&self, label: &str) -> Vec<Duration> {
+        let mut durations: Vec<Duration> = self
+            .runs
+            .get(label)
+            .into_iter()
+            .flatten()
+            .filter(|run| run.success)
+            .map(|run| Duration::from_millis(run.duration_ms))
+            .collect();
+        durations.sort();
+        durations
+    }
+}
+
+fn average(durations: &[Duration]) -> Option<Duration> {
+    if durations.is_empty() {
+        return None;
+    }
+    Some(durations.iter().sum::<Duration>() / durations.len() as u32)
+}
+
+/// The nearest-rank `percent`ile of sorted `durations`.
+fn percentile(durations: &[Duration], percent: usize) -> Option<Duration> {
+    let rank = (durations.len() * percent).div_ceil(100);
+    durations.get(rank.saturating_sub(1)).copied()
+}
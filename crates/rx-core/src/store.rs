@@ -0,0 +1,51 @@
+use std::path::{Path, PathBuf};
+
+use dirs::data_local_dir;
+
+/// Per-project directory for what rx records between runs, such as benchmark results.
+#[derive(Debug, Clone)]
+pub struct Store {
+    root: PathBuf,
+}
+
+impl Store {
+    pub fn at(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// The store of the project rooted at `workspace_folder`, under the user's local data dir.
+    pub fn for_workspace(workspace_folder: &Path) -> Option<Self> {
+        let name = workspace_folder
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "root".to_string());
+        let id = format!(
+            "{}-{:016x}",
+            name,
+            fnv1a(
+                FNV_OFFSET_BASIS,
+                workspace_folder.to_string_lossy().as_bytes()
+            )
+        );
+
+        data_local_dir().map(|dir| Self::at(dir.join("cargo_runner/projects").join(id)))
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn path(&self, relative: &str) -> PathBuf {
+        self.root.join(relative)
+    }
+}
+
+pub(crate) const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+
+/// Continues an FNV-1a `hash` over `bytes`, starting from [`FNV_OFFSET_BASIS`]. Stable across
+/// Rust releases, unlike `DefaultHasher`, so store paths and cache entries never move.
+pub(crate) fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
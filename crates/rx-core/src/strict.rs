@@ -0,0 +1,219 @@
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{
+    de::{self, DeserializeOwned, Visitor},
+    forward_to_deserialize_any,
+};
+
+use crate::{
+    errors::ConfigError,
+    models::config::{
+        ArgSpec, CommandConfig, CommandDetails, Commands, Config, ContainerConfig, CoverageOptions,
+        GitHook, Hooks, Limits, NextestOptions, RemoteConfig, ServiceGroup, Settings,
+        TargetOverride, WaitFor,
+    },
+    models::format::ConfigFormat,
+    theme::Theme,
+};
+
+/// A field of a config file rx doesn't know, which it otherwise ignores.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownField {
+    pub file: PathBuf,
+    /// Dotted, e.g. `commands.run.configs.default.working_dir`
+    pub path: String,
+    /// The known field closest to it, when one is close enough to be a typo
+    pub suggestion: Option<String>,
+}
+
+impl Display for UnknownField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: unknown field '{}'", self.file.display(), self.path)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, ", did you mean '{}'?", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+/// Fails with the unknown fields of the config file at `path` and of the files it includes,
+/// for `--strict`.
+pub fn check(config: &Config, path: &Path) -> Result<(), Box<dyn Error>> {
+    let unknown = unknown_fields(config, path)?;
+    if unknown.is_empty() {
+        return Ok(());
+    }
+    Err(ConfigError::UnknownFields(unknown.iter().map(ToString::to_string).collect()).into())
+}
+
+/// The unknown fields of the config file at `path` and of the files it includes.
+pub fn unknown_fields(config: &Config, path: &Path) -> Result<Vec<UnknownField>, Box<dyn Error>> {
+    let mut unknown = Vec::new();
+    for file in std::iter::once(path).chain(config.included_files().iter().map(PathBuf::as_path)) {
+        let content = fs::read_to_string(file)
+            .map_err(|err| format!("cannot read {}: {}", file.display(), err))?;
+        let paths = ignored_paths::<Config>(&content, ConfigFormat::from_path(file))
+            .map_err(|err| format!("{}: {}", file.display(), err))?;
+        unknown.extend(paths.into_iter().map(|segments| UnknownField {
+            file: file.to_path_buf(),
+            suggestion: suggestion(&segments),
+            path: segments.join("."),
+        }));
+    }
+    Ok(unknown)
+}
+
+/// The fields of `content`, written as `format`, deserializing `T` skips, each as its keys
+/// from the top.
+pub fn ignored_paths<T: DeserializeOwned>(
+    content: &str,
+    format: ConfigFormat,
+) -> Result<Vec<Vec<String>>, String> {
+    let mut paths = Vec::new();
+    let mut ignored = |path: serde_ignored::Path| paths.push(segments(&path));
+    let _: T = match format {
+        ConfigFormat::Toml => {
+            serde_ignored::deserialize(toml::Deserializer::new(content), &mut ignored)
+                .map_err(|err| err.message().to_string())?
+        }
+        ConfigFormat::Yaml => {
+            serde_ignored::deserialize(serde_yaml::Deserializer::from_str(content), &mut ignored)
+                .map_err(|err| err.to_string())?
+        }
+        ConfigFormat::Json => serde_ignored::deserialize(
+            &mut serde_json::Deserializer::from_str(content),
+            &mut ignored,
+        )
+        .map_err(|err| err.to_string())?,
+    };
+    Ok(paths)
+}
+
+fn segments(path: &serde_ignored::Path) -> Vec<String> {
+    use serde_ignored::Path;
+    match path {
+        Path::Root => Vec::new(),
+        Path::Seq { parent, index } => {
+            let mut segments = segments(parent);
+            segments.push(index.to_string());
+            segments
+        }
+        Path::Map { parent, key } => {
+            let mut segments = segments(parent);
+            segments.push(key.clone());
+            segments
+        }
+        Path::Some { parent }
+        | Path::NewtypeStruct { parent }
+        | Path::NewtypeVariant { parent } => segments(parent),
+    }
+}
+
+/// The known field of the table holding the last segment closest to it, within an edit
+/// distance of a third of its length or starting with it, such as `working_directory` for
+/// `working_dir`.
+fn suggestion(segments: &[String]) -> Option<String> {
+    let (field, table) = segments.split_last()?;
+    let table: Vec<&str> = table.iter().map(String::as_str).collect();
+    let known = match table.as_slice() {
+        [] => field_names::<Config>(),
+        ["commands"] => field_names::<Commands>(),
+        ["commands", _] => field_names::<CommandConfig>(),
+        ["commands", _, "nextest"] => field_names::<NextestOptions>(),
+        ["commands", _, "coverage"] => field_names::<CoverageOptions>(),
+        ["commands", _, "configs", _] => field_names::<CommandDetails>(),
+        ["commands", _, "configs", _, "args", _] => field_names::<ArgSpec>(),
+        ["commands", _, "configs", _, "container"] => field_names::<ContainerConfig>(),
+        ["commands", _, "configs", _, "limits"] => field_names::<Limits>(),
+        ["commands", _, "configs", _, "wait_for"] => field_names::<WaitFor>(),
+        ["commands", _, "configs", _, "target", _] => field_names::<TargetOverride>(),
+        ["remote_config"] => field_names::<RemoteConfig>(),
+        ["hooks"] => field_names::<Hooks>(),
+        ["hooks", "git", _] => field_names::<GitHook>(),
+        ["settings"] => field_names::<Settings>(),
+        ["groups", _] => field_names::<ServiceGroup>(),
+        ["settings", "theme"] => field_names::<Theme>(),
+        _ => &[],
+    };
+
+    let limit = (field.chars().count() / 3).max(1);
+    known
+        .iter()
+        .map(|name| (edit_distance(field, name), name))
+        .filter(|(distance, name)| {
+            *distance <= limit || (field.len() >= 3 && name.starts_with(field.as_str()))
+        })
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, name)| name.to_string())
+}
+
+/// The fields a struct deserializes, as serde's derive names them.
+fn field_names<T: DeserializeOwned>() -> &'static [&'static str] {
+    let mut fields = None;
+    let _ = T::deserialize(FieldProbe(&mut fields));
+    fields.unwrap_or_default()
+}
+
+/// A deserializer that only records the fields of the struct asked for.
+struct FieldProbe<'a>(&'a mut Option<&'static [&'static str]>);
+
+#[derive(Debug)]
+struct Probed;
+
+impl Display for Probed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "probed")
+    }
+}
+
+impl Error for Probed {}
+
+impl de::Error for Probed {
+    fn custom<T: Display>(_: T) -> Self {
+        Probed
+    }
+}
+
+impl<'de> de::Deserializer<'de> for FieldProbe<'_> {
+    type Error = Probed;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Probed> {
+        Err(Probed)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        fields: &'static [&'static str],
+        _: V,
+    ) -> Result<V::Value, Probed> {
+        *self.0 = Some(fields);
+        Err(Probed)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier
+        ignored_any
+    }
+}
+
+/// Levenshtein distance between `a` and `b`, by chars.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
@@ -0,0 +1,317 @@
+use std::{
+    fs, io,
+    path::{Component, Path},
+};
+
+use crate::interpolation::InterpolationContext;
+
+/// The tests `cargo test` should run, resolved from a test name or a source location.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestFilter {
+    /// Package owning the file, so the filter also works from a workspace root
+    pub package: Option<String>,
+    /// Integration test target (`tests/<name>.rs`) the tests live in, if any
+    pub test_target: Option<String>,
+    /// Module path of a test function or module, empty to run the whole target
+    pub path: String,
+    /// Whether `path` names a single test rather than a module prefix
+    pub exact: bool,
+}
+
+impl TestFilter {
+    /// Filter for a fully qualified test name such as `models::config::tests::test_load`.
+    pub fn from_name(name: &str) -> Self {
+        Self {
+            package: None,
+            test_target: None,
+            path: name.to_string(),
+            exact: true,
+        }
+    }
+
+    /// Resolves the test function (or failing that the test module) enclosing `line` of `file`.
+    pub fn at_location(file: &Path, line: usize) -> io::Result<Self> {
+        let source = fs::read_to_string(file)?;
+        let manifest_dir = file
+            .ancestors()
+            .skip(1)
+            .find(|ancestor| ancestor.join("Cargo.toml").is_file());
+        let package = manifest_dir.and_then(|dir| {
+            InterpolationContext::from_dir(dir)
+                .get("packageName")
+                .map(str::to_string)
+        });
+        let relative = match manifest_dir {
+            Some(dir) => file.strip_prefix(dir).unwrap_or(file),
+            None => file,
+        };
+        let (test_target, file_module) = module_of_file(relative);
+
+        let scopes = parse_scopes(&source);
+        let enclosing = |kind: ScopeKind| {
+            scopes
+                .iter()
+                .filter(|scope| scope.kind == kind && scope.start <= line && line <= scope.end)
+                .max_by_key(|scope| scope.start)
+        };
+
+        let (path, exact) =
+            match enclosing(ScopeKind::TestFn).or_else(|| enclosing(ScopeKind::Module)) {
+                Some(scope) => (scope.path.clone(), scope.kind == ScopeKind::TestFn),
+                None => (Vec::new(), false),
+            };
+
+        let path = file_module
+            .into_iter()
+            .chain(path)
+            .collect::<Vec<_>>()
+            .join("::");
+
+        Ok(Self {
+            package,
+            test_target,
+            path,
+            exact,
+        })
+    }
+
+    /// Arguments for `cargo test` running the filtered tests with `extra` arguments. Target
+    /// selection and the filter go first, harness flags after the `--` separator.
+    pub fn cargo_args(&self, extra: &[String]) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(package) = &self.package {
+            args.push("-p".to_string());
+            args.push(package.clone());
+        }
+        if let Some(target) = &self.test_target {
+            args.push("--test".to_string());
+            args.push(target.clone());
+        }
+        if !self.path.is_empty() {
+            args.push(self.path.clone());
+        }
+        args.extend(extra.iter().cloned());
+
+        if self.exact && !self.path.is_empty() {
+            if !extra.iter().any(|arg| arg == "--") {
+                args.push("--".to_string());
+            }
+            args.push("--exact".to_string());
+        }
+
+        args
+    }
+}
+
+/// Integration test target and module path of a file relative to its manifest directory,
+/// based on cargo's layout.
+fn module_of_file(relative: &Path) -> (Option<String>, Vec<String>) {
+    let components: Vec<String> = relative
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect();
+
+    match components.first().map(String::as_str) {
+        Some("tests") if components.len() >= 2 => {
+            let target = components[1].trim_end_matches(".rs").to_string();
+            let modules = module_path(&components[2..], &["main.rs"]);
+            (Some(target), modules)
+        }
+        Some("src") => (None, module_path(&components[1..], &["lib.rs", "main.rs"])),
+        _ => (None, Vec::new()),
+    }
+}
+
+fn module_path(components: &[String], roots: &[&str]) -> Vec<String> {
+    let mut modules: Vec<String> = components
+        .iter()
+        .map(|part| part.trim_end_matches(".rs").to_string())
+        .collect();
+
+    let is_root = components.len() == 1 && roots.contains(&components[0].as_str());
+    if is_root || modules.last().is_some_and(|last| last == "mod") {
+        modules.pop();
+    }
+
+    modules
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScopeKind {
+    Module,
+    TestFn,
+    Other,
+}
+
+#[derive(Debug)]
+struct Scope {
+    kind: ScopeKind,
+    /// Module path from the file's root to this scope, including its own name
+    path: Vec<String>,
+    start: usize,
+    end: usize,
+}
+
+/// Finds `mod` blocks and test functions with their line ranges. This is a brace matcher over
+/// the token stream that skips strings, chars and comments, not a full Rust parser.
+fn parse_scopes(source: &str) -> Vec<Scope> {
+    let mut finished = Vec::new();
+    let mut open: Vec<Scope> = Vec::new();
+
+    // Line of the test attribute, a test function's range starts there
+    let mut pending_test: Option<usize> = None;
+    let mut pending: Option<(ScopeKind, String, usize)> = None;
+    let mut previous_word = String::new();
+
+    let mut line = 1;
+    let mut chars = source.chars().peekable();
+    let mut word = String::new();
+
+    while let Some(c) = chars.next() {
+        if c.is_alphanumeric() || c == '_' {
+            word.push(c);
+            continue;
+        }
+
+        if !word.is_empty() {
+            match previous_word.as_str() {
+                "mod" => pending = Some((ScopeKind::Module, word.clone(), line)),
+                "fn" => {
+                    pending = Some(match pending_test.take() {
+                        Some(start) => (ScopeKind::TestFn, word.clone(), start),
+                        None => (ScopeKind::Other, word.clone(), line),
+                    });
+                }
+                _ => {}
+            }
+            previous_word = std::mem::take(&mut word);
+        }
+
+        match c {
+            '\n' => line += 1,
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        line += 1;
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            '"' => {
+                let mut escaped = false;
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    if c == '"' && !escaped {
+                        break;
+                    }
+                    escaped = c == '\\' && !escaped;
+                }
+            }
+            '\'' => {
+                // A char literal, unless this is a lifetime like `'a`
+                let mut lookahead = chars.clone();
+                let is_char = match lookahead.next() {
+                    Some('\\') => true,
+                    Some(_) => lookahead.next() == Some('\''),
+                    None => false,
+                };
+                if is_char {
+                    let mut escaped = false;
+                    for c in chars.by_ref() {
+                        if c == '\'' && !escaped {
+                            break;
+                        }
+                        escaped = c == '\\' && !escaped;
+                    }
+                }
+            }
+            '#' if chars.peek() == Some(&'[') => {
+                let start = line;
+                let mut attribute = String::new();
+                let mut depth = 0;
+                for c in chars.by_ref() {
+                    match c {
+                        '[' => depth += 1,
+                        ']' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        '\n' => line += 1,
+                        _ => {}
+                    }
+                    attribute.push(c);
+                }
+                if is_test_attribute(&attribute) {
+                    pending_test = Some(start);
+                }
+            }
+            '{' => {
+                let (kind, name, start) =
+                    pending
+                        .take()
+                        .unwrap_or((ScopeKind::Other, String::new(), line));
+                let mut path = open
+                    .iter()
+                    .rev()
+                    .find(|scope| scope.kind != ScopeKind::Other)
+                    .map(|scope| scope.path.clone())
+                    .unwrap_or_default();
+                if kind != ScopeKind::Other {
+                    path.push(name);
+                }
+                open.push(Scope {
+                    kind,
+                    path,
+                    start,
+                    end: start,
+                });
+            }
+            '}' => {
+                if let Some(mut scope) = open.pop() {
+                    scope.end = line;
+                    finished.push(scope);
+                }
+            }
+            ';' => {
+                // `mod name;` and trait method declarations have no body
+                pending = None;
+            }
+            _ => {}
+        }
+    }
+
+    finished
+}
+
+/// `#[test]`, `#[tokio::test(...)]`, `#[rstest]` and similar harness attributes.
+fn is_test_attribute(attribute: &str) -> bool {
+    let path = attribute
+        .trim_start_matches('[')
+        .split(['(', ']'])
+        .next()
+        .unwrap_or_default()
+        .trim();
+    let name = path.rsplit("::").next().unwrap_or(path);
+
+    matches!(name, "test" | "rstest" | "test_case")
+}
@@ -0,0 +1,284 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    executor::output::{strip_ansi, OutputObserver},
+    store::Store,
+};
+
+/// A test that failed, with where it panicked when the output says.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FailedTest {
+    /// Full path of the test, e.g. `config::tests::test_load`
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+}
+
+/// The outcome of the tests of a run, read from the output of `cargo test` or
+/// `cargo nextest run`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TestSummary {
+    pub passed: usize,
+    pub failed: usize,
+    /// Tests marked `#[ignore]`, or those nextest skipped
+    pub ignored: usize,
+    pub failures: Vec<FailedTest>,
+}
+
+impl TestSummary {
+    /// Whether the output had no test results at all.
+    pub fn is_empty(&self) -> bool {
+        self.passed + self.failed + self.ignored == 0
+    }
+
+    /// The counts followed by the failed tests, one per line.
+    pub fn report(&self) -> String {
+        let mut report = format!(
+            "{} passed, {} failed, {} ignored",
+            self.passed, self.failed, self.ignored
+        );
+        for failure in &self.failures {
+            report.push_str(&format!("\n  {}", failure.name));
+            if let Some(file) = &failure.file {
+                report.push_str(&format!(" at {}", file));
+                if let Some(line) = failure.line {
+                    report.push_str(&format!(":{}", line));
+                }
+            }
+        }
+        report
+    }
+
+    /// The names of the failed tests.
+    pub fn failed_names(&self) -> Vec<String> {
+        self.failures
+            .iter()
+            .map(|failure| failure.name.clone())
+            .collect()
+    }
+}
+
+/// Reads the test results out of the output of a run, line by line.
+#[derive(Debug, Default)]
+pub struct TestCollector {
+    state: Mutex<Collected>,
+}
+
+#[derive(Debug, Default)]
+struct Collected {
+    summary: TestSummary,
+    /// Where each test that panicked did, by name
+    panics: HashMap<String, (String, usize)>,
+    /// Counts of nextest's closing `Summary` line, which also has the skipped tests
+    totals: Option<(usize, usize, usize)>,
+}
+
+impl TestCollector {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// `observer`, if any, with the collector reading every line first.
+    pub fn observe_output(self: &Arc<Self>, observer: Option<OutputObserver>) -> OutputObserver {
+        let collector = self.clone();
+        Arc::new(move |label, stream, line| {
+            collector.line(line);
+            if let Some(observer) = &observer {
+                observer(label, stream, line);
+            }
+        })
+    }
+
+    /// Takes in a line of output.
+    pub fn line(&self, line: &str) {
+        let line = strip_ansi(line);
+        let line = line.trim();
+        let mut state = self.state.lock().unwrap();
+        if let Some((name, location)) = parse_panic(line) {
+            state.panics.insert(name, location);
+            return;
+        }
+        if let Some(totals) = parse_nextest_totals(line) {
+            state.totals = Some(totals);
+            return;
+        }
+
+        let result = parse_libtest_result(line).or_else(|| parse_nextest_result(line));
+        let Some((name, outcome)) = result else {
+            return;
+        };
+        let summary = &mut state.summary;
+        match outcome {
+            Outcome::Passed => summary.passed += 1,
+            Outcome::Ignored => summary.ignored += 1,
+            // nextest lists the failures again at the end
+            Outcome::Failed if summary.failures.iter().any(|failure| failure.name == name) => {}
+            Outcome::Failed => {
+                summary.failed += 1;
+                summary.failures.push(FailedTest {
+                    name,
+                    file: None,
+                    line: None,
+                });
+            }
+        }
+    }
+
+    /// The results so far, the failures with where they panicked.
+    pub fn summary(&self) -> TestSummary {
+        let state = self.state.lock().unwrap();
+        let mut summary = state.summary.clone();
+        if let Some((passed, failed, ignored)) = state.totals {
+            summary.passed = passed;
+            summary.failed = failed;
+            summary.ignored = ignored;
+        }
+        for failure in &mut summary.failures {
+            if let Some((file, line)) = state.panics.get(&failure.name) {
+                failure.file = Some(file.clone());
+                failure.line = Some(*line);
+            }
+        }
+        summary
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Passed,
+    Failed,
+    Ignored,
+}
+
+/// `test config::tests::test_load ... ok`, as libtest prints it.
+fn parse_libtest_result(line: &str) -> Option<(String, Outcome)> {
+    let (name, result) = line.strip_prefix("test ")?.split_once(" ... ")?;
+    let outcome = match result {
+        "ok" => Outcome::Passed,
+        "FAILED" => Outcome::Failed,
+        result if result.starts_with("ignored") => Outcome::Ignored,
+        _ => return None,
+    };
+    Some((name.to_string(), outcome))
+}
+
+/// `PASS [   0.004s] rx-core::config_test tests::test_load`, as nextest prints it.
+fn parse_nextest_result(line: &str) -> Option<(String, Outcome)> {
+    let (status, rest) = line.split_once(' ')?;
+    let outcome = match status {
+        "PASS" => Outcome::Passed,
+        "FAIL" => Outcome::Failed,
+        "SKIP" => Outcome::Ignored,
+        _ => return None,
+    };
+    let rest = rest.trim_start().strip_prefix('[')?;
+    let (_, test) = rest.split_once(']')?;
+    let name = test.split_whitespace().last()?;
+    Some((name.to_string(), outcome))
+}
+
+/// `Summary [   0.123s] 12 tests run: 11 passed, 1 failed, 3 skipped`, nextest's last line.
+fn parse_nextest_totals(line: &str) -> Option<(usize, usize, usize)> {
+    let rest = line.strip_prefix("Summary ")?;
+    let (_, counts) = rest.split_once(" run: ")?;
+    let (mut passed, mut failed, mut skipped) = (0, 0, 0);
+    for part in counts.split(", ") {
+        let (count, what) = part.split_once(' ')?;
+        let count: usize = count.parse().ok()?;
+        if what.starts_with("passed") {
+            passed = count;
+        } else if what.starts_with("failed") {
+            failed = count;
+        } else if what.starts_with("skipped") {
+            skipped = count;
+        }
+    }
+    Some((passed, failed, skipped))
+}
+
+/// `thread 'tests::test_load' panicked at src/config.rs:10:5:`, with the thread id after the
+/// name on newer Rust, or before Rust 1.73
+/// `thread 'tests::test_load' panicked at 'message', src/config.rs:10:5`.
+fn parse_panic(line: &str) -> Option<(String, (String, usize))> {
+    let (name, rest) = line.strip_prefix("thread '")?.split_once('\'')?;
+    let rest = rest.trim_start();
+    let rest = match rest.strip_prefix('(') {
+        Some(id) => id.split_once(')')?.1.trim_start(),
+        None => rest,
+    };
+    let rest = rest.strip_prefix("panicked at ")?;
+    let location = match rest.strip_prefix('\'') {
+        Some(message) => message.rsplit_once("', ")?.1,
+        None => rest.trim_end_matches(':'),
+    };
+    let mut parts = location.rsplitn(3, ':');
+    let _column = parts.next()?;
+    let line: usize = parts.next()?.parse().ok()?;
+    let file = parts.next()?;
+    Some((name.to_string(), (file.to_string(), line)))
+}
+
+/// Arguments running only the tests `names` with the `extra` ones, the names as exact
+/// filters after the `--` separator.
+pub fn rerun_args(names: &[String], extra: &[String]) -> Vec<String> {
+    let mut args = extra.to_vec();
+    if !args.iter().any(|arg| arg == "--") {
+        args.push("--".to_string());
+    }
+    args.extend(names.iter().cloned());
+    args.push("--exact".to_string());
+    args
+}
+
+/// The tests that failed in the last run of each test config of a project, what
+/// `rx test --rerun-failed` runs.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct FailedTests {
+    /// Test names by `context::key` of the config
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tests: BTreeMap<String, Vec<String>>,
+}
+
+impl FailedTests {
+    fn path(store: &Store) -> PathBuf {
+        store.path("failed_tests.toml")
+    }
+
+    /// The failed tests, none before the first test run.
+    pub fn load(store: &Store) -> Result<FailedTests, Box<dyn Error>> {
+        let path = Self::path(store);
+        if !path.exists() {
+            return Ok(FailedTests::default());
+        }
+
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, store: &Store) -> Result<(), Box<dyn Error>> {
+        let path = Self::path(store);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Records the tests of `label` that failed in its run, forgetting them once none did.
+    pub fn record(&mut self, label: &str, summary: &TestSummary) {
+        match summary.failures.is_empty() {
+            true => self.tests.remove(label),
+            false => self.tests.insert(label.to_string(), summary.failed_names()),
+        };
+    }
+}
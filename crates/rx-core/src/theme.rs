@@ -0,0 +1,190 @@
+use std::{fmt::Write, str::FromStr, time::Duration};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{errors::ConfigError, notify::format_duration};
+
+/// When rx colors its own messages, `--color`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Color a terminal unless `NO_COLOR` is set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Whether to color output going to a terminal or not.
+    pub fn enabled(self, is_terminal: bool) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                is_terminal && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+            }
+        }
+    }
+}
+
+impl FromStr for ColorChoice {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(ConfigError::UnknownColorChoice(s.to_string())),
+        }
+    }
+}
+
+/// Styles of rx's own messages, `[settings.theme]`. Each is a list of words such as
+/// `"bold cyan"`: a color (black, red, green, yellow, blue, magenta, cyan, white, or one of them
+/// prefixed with `bright_`) and attributes (bold, dim, italic, underline).
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct Theme {
+    /// The label of the config starting to run
+    pub header: String,
+    pub success: String,
+    pub failure: String,
+    /// The `rx:` prefix and durations
+    pub muted: String,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            header: "bold cyan".to_string(),
+            success: "green".to_string(),
+            failure: "bold red".to_string(),
+            muted: "dim".to_string(),
+        }
+    }
+}
+
+impl Theme {
+    pub fn is_default(&self) -> bool {
+        self == &Theme::default()
+    }
+
+    /// Each style with its name, for validation.
+    pub fn styles(&self) -> [(&'static str, &str); 4] {
+        [
+            ("header", &self.header),
+            ("success", &self.success),
+            ("failure", &self.failure),
+            ("muted", &self.muted),
+        ]
+    }
+}
+
+/// The ANSI escape sequence of a style, the error names the first unknown word.
+pub fn escape_sequence(style: &str) -> Result<String, String> {
+    let mut codes = Vec::new();
+    for word in style.split_whitespace() {
+        let (bright, color) = match word.strip_prefix("bright_") {
+            Some(color) => (true, color),
+            None => (false, word),
+        };
+        let code = match color {
+            "bold" if !bright => 1,
+            "dim" if !bright => 2,
+            "italic" if !bright => 3,
+            "underline" if !bright => 4,
+            "black" => 30,
+            "red" => 31,
+            "green" => 32,
+            "yellow" => 33,
+            "blue" => 34,
+            "magenta" => 35,
+            "cyan" => 36,
+            "white" => 37,
+            _ => return Err(format!("unknown style '{}'", word)),
+        };
+        codes.push(if bright && code >= 30 {
+            code + 60
+        } else {
+            code
+        });
+    }
+
+    let codes: Vec<String> = codes.iter().map(ToString::to_string).collect();
+    if codes.is_empty() {
+        return Ok(String::new());
+    }
+    Ok(format!("\x1b[{}m", codes.join(";")))
+}
+
+/// Formats rx's own messages for stderr: the header and result banner of a run and its notes,
+/// colored by the theme when enabled. The ndjson event output doesn't go through it.
+#[derive(Debug, Clone, Default)]
+pub struct Presenter {
+    theme: Theme,
+    color: bool,
+}
+
+impl Presenter {
+    pub fn new(theme: Theme, color: bool) -> Self {
+        Self { theme, color }
+    }
+
+    /// `text` in `style`, unchanged when colors are off or the style is invalid.
+    pub fn paint(&self, style: &str, text: &str) -> String {
+        match escape_sequence(style) {
+            Ok(escape) if self.color && !escape.is_empty() => {
+                format!("{}{}\x1b[0m", escape, text)
+            }
+            _ => text.to_string(),
+        }
+    }
+
+    /// A line of rx's own, e.g. `rx: cargo-nextest is not installed`.
+    pub fn note(&self, message: &str) -> String {
+        format!("{} {}", self.paint(&self.theme.muted, "rx:"), message)
+    }
+
+    /// An error of rx's own, e.g. an unknown key.
+    pub fn error(&self, message: &str) -> String {
+        self.note(&self.paint(&self.theme.failure, message))
+    }
+
+    /// Put before each line of a command's output with `--ui prefixed`.
+    pub fn prefix(&self, label: &str) -> String {
+        self.paint(&self.theme.muted, &format!("[{}]", label))
+    }
+
+    /// Shown before a config runs.
+    pub fn header(&self, label: &str) -> String {
+        self.note(&self.paint(&self.theme.header, &format!("▶ {}", label)))
+    }
+
+    /// Shown when a config succeeded, with the average of its earlier runs when known.
+    pub fn success(&self, label: &str, elapsed: Duration, average: Option<Duration>) -> String {
+        let mut line = self.paint(&self.theme.success, &format!("✓ {}", label));
+        let mut timing = in_duration(elapsed);
+        if let Some(average) = average {
+            let _ = write!(timing, " (avg {})", format_duration(average));
+        }
+        let _ = write!(line, " {}", self.paint(&self.theme.muted, &timing));
+        self.note(&line)
+    }
+
+    /// Shown when a config failed, with the reason.
+    pub fn failure(&self, reason: &str, elapsed: Duration) -> String {
+        let mut line = self.paint(&self.theme.failure, &format!("✗ {}", reason));
+        let _ = write!(
+            line,
+            " {}",
+            self.paint(&self.theme.muted, &in_duration(elapsed))
+        );
+        self.note(&line)
+    }
+}
+
+fn in_duration(elapsed: Duration) -> String {
+    format!("in {}", format_duration(elapsed))
+}
@@ -0,0 +1,99 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// The phase of a run up to its first command: resolving the config, its placeholders and
+/// whatever the command's type checks before it starts
+pub const PREPARE: &str = "prepare";
+
+/// The phase of a run spent in the commands it ran, the only one that isn't rx's overhead
+pub const COMMANDS: &str = "commands";
+
+/// How long a phase of an invocation took.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Phase {
+    pub name: &'static str,
+    pub duration: Duration,
+}
+
+/// The phases of an invocation in the order they ended, for `--timings`: each lasts from the
+/// end of the one before, the first from the start. Clones add to the same phases, so the
+/// runner can end the one before its first command.
+#[derive(Debug, Clone)]
+pub struct Timings {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug)]
+struct State {
+    last: Instant,
+    phases: Vec<Phase>,
+}
+
+impl Timings {
+    /// Phases starting at `started`, e.g. when the process did.
+    pub fn since(started: Instant) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State {
+                last: started,
+                phases: Vec::new(),
+            })),
+        }
+    }
+
+    /// Ends the phase `name` now.
+    pub fn end(&self, name: &'static str) {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        let duration = now - state.last;
+        state.last = now;
+        state.phases.push(Phase { name, duration });
+    }
+
+    /// Ends the phase `name` now unless it already ended, e.g. the one before the first of
+    /// several commands.
+    pub fn end_once(&self, name: &'static str) {
+        if !self.phases().iter().any(|phase| phase.name == name) {
+            self.end(name);
+        }
+    }
+
+    pub fn phases(&self) -> Vec<Phase> {
+        self.state.lock().unwrap().phases.clone()
+    }
+
+    /// The time spent outside of the commands run.
+    pub fn overhead(&self) -> Duration {
+        self.phases()
+            .iter()
+            .filter(|phase| phase.name != COMMANDS)
+            .map(|phase| phase.duration)
+            .sum()
+    }
+
+    /// A line per phase with its duration in milliseconds, then rx's overhead and the total.
+    pub fn report(&self) -> String {
+        let phases = self.phases();
+        let total: Duration = phases.iter().map(|phase| phase.duration).sum();
+        let lines = phases
+            .iter()
+            .map(|phase| (phase.name, phase.duration))
+            .chain([("overhead", self.overhead()), ("total", total)]);
+        let width = lines
+            .clone()
+            .map(|(name, _)| name.chars().count())
+            .max()
+            .unwrap_or_default();
+        lines
+            .map(|(name, duration)| {
+                format!(
+                    "{:<width$}  {:>8.2}ms",
+                    name,
+                    duration.as_secs_f64() * 1000.0
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
@@ -0,0 +1,67 @@
+use std::{io, net::TcpStream, path::Path, pin::Pin};
+
+pub use openssl::ssl::SslAcceptor;
+use openssl::ssl::{self, Ssl, SslConnector, SslFiletype, SslMethod};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_openssl::SslStream;
+
+/// What TLS connections to `rx daemon --listen` are accepted with: its certificate chain and
+/// private key, PEM files.
+pub fn acceptor(cert: &Path, key: &Path) -> Result<SslAcceptor, String> {
+    let failed = |path: &Path, err: openssl::error::ErrorStack| {
+        format!("cannot use {}: {}", path.display(), err)
+    };
+    let mut builder = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls_server())
+        .map_err(|err| err.to_string())?;
+    builder
+        .set_certificate_chain_file(cert)
+        .map_err(|err| failed(cert, err))?;
+    builder
+        .set_private_key_file(key, SslFiletype::PEM)
+        .map_err(|err| failed(key, err))?;
+    builder
+        .check_private_key()
+        .map_err(|err| failed(key, err))?;
+    Ok(builder.build())
+}
+
+/// Connects to `host` over `stream`, checking its certificate against the system's CAs, and
+/// `ca`, a PEM file, when given, e.g. for a devbox's self-signed certificate.
+pub fn connect(
+    host: &str,
+    stream: TcpStream,
+    ca: Option<&Path>,
+) -> Result<ssl::SslStream<TcpStream>, String> {
+    let mut builder =
+        SslConnector::builder(SslMethod::tls_client()).map_err(|err| err.to_string())?;
+    if let Some(ca) = ca {
+        builder
+            .set_ca_file(ca)
+            .map_err(|err| format!("cannot use {}: {}", ca.display(), err))?;
+    }
+    builder
+        .build()
+        .connect(host, stream)
+        .map_err(|err| format!("TLS with {} failed: {}", host, err))
+}
+
+/// A TLS stream over an async stream such as a `tokio::net::TcpStream`, the server side of a
+/// connection to the daemon.
+pub type TlsStream<S> = SslStream<S>;
+
+/// Completes the handshake of a client connecting over `stream`.
+pub async fn accept<S: AsyncRead + AsyncWrite + Unpin>(
+    acceptor: &SslAcceptor,
+    stream: S,
+) -> io::Result<TlsStream<S>> {
+    let ssl = Ssl::new(acceptor.context()).map_err(io::Error::other)?;
+    let mut stream = SslStream::new(ssl, stream).map_err(io::Error::other)?;
+    Pin::new(&mut stream)
+        .accept()
+        .await
+        .map_err(|err| match err.into_io_error() {
+            Ok(err) => err,
+            Err(err) => io::Error::other(err),
+        })?;
+    Ok(stream)
+}
@@ -0,0 +1,164 @@
+use std::{
+    error::Error,
+    fs, io,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use serde::Deserialize;
+
+use crate::{checksum::sha256_hex, errors::ExecutionError};
+
+/// Where `rx self-update` looks for a newer rx.
+pub const LATEST_RELEASE: &str = "https://api.github.com/repos/codeitlikemiley/rx/releases/latest";
+
+/// The release asset listing the SHA-256 of the others, one `<digest>  <name>` line each.
+pub const CHECKSUMS: &str = "SHA256SUMS";
+
+/// A GitHub release of rx, as the releases API describes it.
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+pub struct Release {
+    pub tag_name: String,
+    #[serde(default)]
+    pub assets: Vec<Asset>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+pub struct Asset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+impl Release {
+    /// The version of the release, its tag without the leading `v`.
+    pub fn version(&self) -> &str {
+        self.tag_name.trim_start_matches('v')
+    }
+
+    pub fn asset(&self, name: &str) -> Option<&Asset> {
+        self.assets.iter().find(|asset| asset.name == name)
+    }
+}
+
+/// The latest release of rx.
+pub fn latest_release() -> Result<Release, Box<dyn Error>> {
+    let body = download(LATEST_RELEASE)?;
+    Ok(serde_json::from_slice(&body)?)
+}
+
+/// Whether `candidate` is a later version than `current`, comparing their dotted numbers. A
+/// pre-release such as `1.2.0-rc.1` comes before `1.2.0`.
+pub fn is_newer(candidate: &str, current: &str) -> bool {
+    fn parse(version: &str) -> (Vec<u64>, bool) {
+        let (numbers, pre_release) = match version.split_once('-') {
+            Some((numbers, _)) => (numbers, true),
+            None => (version, false),
+        };
+        let numbers = numbers
+            .split('.')
+            .map(|number| number.parse().unwrap_or(0))
+            .collect();
+        (numbers, pre_release)
+    }
+
+    let (candidate, candidate_pre) = parse(candidate);
+    let (current, current_pre) = parse(current);
+    match candidate.cmp(&current) {
+        std::cmp::Ordering::Equal => current_pre && !candidate_pre,
+        ordering => ordering.is_gt(),
+    }
+}
+
+/// The name of the release asset holding rx built for this platform, such as
+/// `rx-x86_64-unknown-linux-gnu` or `rx-aarch64-apple-darwin`.
+pub fn asset_name() -> String {
+    let platform = match std::env::consts::OS {
+        "linux" => "unknown-linux-gnu",
+        "macos" => "apple-darwin",
+        "windows" => "pc-windows-msvc",
+        os => os,
+    };
+    format!(
+        "rx-{}-{}{}",
+        std::env::consts::ARCH,
+        platform,
+        std::env::consts::EXE_SUFFIX
+    )
+}
+
+/// The SHA-256 `checksums` lists for `name`, lowercase.
+pub fn checksum_for(checksums: &str, name: &str) -> Option<String> {
+    checksums.lines().find_map(|line| {
+        let (digest, file) = line.split_once(char::is_whitespace)?;
+        // sha256sum marks files read in binary mode with a leading `*`
+        let file = file.trim().trim_start_matches('*');
+        (file == name).then(|| digest.to_ascii_lowercase())
+    })
+}
+
+/// Checks `binary` against the SHA-256 `checksums` lists for the asset `name`.
+pub fn verify(binary: &[u8], checksums: &str, name: &str) -> Result<(), String> {
+    let expected = checksum_for(checksums, name)
+        .ok_or_else(|| format!("{} has no checksum for {}", CHECKSUMS, name))?;
+    let actual = sha256_hex(binary);
+    if actual != expected {
+        return Err(format!(
+            "the SHA-256 of {} is {}, {} lists {}",
+            name, actual, CHECKSUMS, expected
+        ));
+    }
+    Ok(())
+}
+
+/// The body at `url`, downloaded with curl.
+pub fn download(url: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let output = Command::new("curl")
+        .args([
+            "--fail",
+            "--silent",
+            "--show-error",
+            "--location",
+            "--proto",
+            "=https",
+            "--header",
+            "User-Agent: rx",
+        ])
+        .arg(url)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => ExecutionError::ToolMissing(
+                "curl".into(),
+                "your system's package manager, e.g. apt install curl".into(),
+            ),
+            _ => ExecutionError::Spawn(format!("curl {}", url), err),
+        })?;
+    if !output.status.success() {
+        let reason = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(format!("Could not download {}: {}", url, reason).into());
+    }
+    Ok(output.stdout)
+}
+
+/// Replaces the executable at `exe` with `binary`, keeping its permissions. The new file is
+/// written next to it and renamed over it, so a failure leaves the old one in place. Windows
+/// doesn't let a running executable be replaced, only renamed, so the old one is moved aside
+/// to `<name>.old` first.
+pub fn replace_executable(exe: &Path, binary: &[u8]) -> io::Result<()> {
+    let staged = exe.with_extension(format!("update-{}", std::process::id()));
+    fs::write(&staged, binary)?;
+    let result = fs::metadata(exe)
+        .and_then(|metadata| fs::set_permissions(&staged, metadata.permissions()))
+        .and_then(|_| {
+            if cfg!(windows) {
+                let old = exe.with_extension("old");
+                let _ = fs::remove_file(&old);
+                fs::rename(exe, &old)?;
+            }
+            fs::rename(&staged, exe)
+        });
+    if result.is_err() {
+        let _ = fs::remove_file(&staged);
+    }
+    result
+}
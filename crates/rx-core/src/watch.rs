@@ -0,0 +1,78 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// What `rx run --watch` watches for a config without `watch`: the sources and manifest of a
+/// cargo package, like bacon does.
+pub const DEFAULT_WATCH: [&str; 6] = [
+    "src",
+    "tests",
+    "benches",
+    "examples",
+    "build.rs",
+    "Cargo.toml",
+];
+
+/// Notices changes to a set of files by polling their modification time and size, which is
+/// plenty for config files edited by hand.
+#[derive(Debug, Clone, Default)]
+pub struct FileWatcher {
+    stamps: Vec<(PathBuf, Option<Stamp>)>,
+}
+
+type Stamp = (SystemTime, u64);
+
+impl FileWatcher {
+    pub fn new(files: impl IntoIterator<Item = PathBuf>) -> Self {
+        let mut watcher = Self::default();
+        watcher.watch(files);
+        watcher
+    }
+
+    /// Replaces the watched files, taking their current state as unchanged.
+    pub fn watch(&mut self, files: impl IntoIterator<Item = PathBuf>) {
+        self.stamps = files
+            .into_iter()
+            .map(|path| {
+                let stamp = stamp(&path);
+                (path, stamp)
+            })
+            .collect();
+    }
+
+    /// Whether a file was modified, created or removed since the last call.
+    pub fn changed(&mut self) -> bool {
+        let mut changed = false;
+        for (path, last) in &mut self.stamps {
+            let current = stamp(path);
+            if current != *last {
+                *last = current;
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Like [`FileWatcher::changed`] for `files` as they're now, e.g. matched by globs again: a
+    /// file appearing or going away among them counts as a change. They're watched from then
+    /// on.
+    pub fn changed_among(&mut self, files: impl IntoIterator<Item = PathBuf>) -> bool {
+        let stamps: Vec<_> = files
+            .into_iter()
+            .map(|path| {
+                let stamp = stamp(&path);
+                (path, stamp)
+            })
+            .collect();
+        let changed = stamps != self.stamps;
+        self.stamps = stamps;
+        changed
+    }
+}
+
+fn stamp(path: &Path) -> Option<Stamp> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
@@ -0,0 +1,309 @@
+use std::{
+    fmt::{self, Display},
+    path::{Path, PathBuf},
+    process::Command,
+    str::FromStr,
+};
+
+use serde::Deserialize;
+
+/// A package of the cargo workspace, as `-p <member>` selects it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Member {
+    pub name: String,
+    /// Directory of the member's `Cargo.toml`
+    pub manifest_dir: PathBuf,
+    /// Its `default-run` binary or else its only one, `None` when it has none or several
+    pub binary_name: Option<String>,
+    pub targets: Vec<Target>,
+    /// The other members it depends on, dev-dependencies left out
+    pub dependencies: Vec<String>,
+}
+
+impl Member {
+    /// The names of its binary targets.
+    pub fn binaries(&self) -> Vec<String> {
+        self.targets
+            .iter()
+            .filter(|target| target.is("bin"))
+            .map(|target| target.name.clone())
+            .collect()
+    }
+}
+
+#[derive(Deserialize)]
+struct Metadata {
+    packages: Vec<Package>,
+}
+
+#[derive(Deserialize)]
+struct Package {
+    name: String,
+    manifest_path: PathBuf,
+    #[serde(default)]
+    default_run: Option<String>,
+    #[serde(default)]
+    targets: Vec<Target>,
+    #[serde(default)]
+    dependencies: Vec<Dependency>,
+}
+
+#[derive(Deserialize)]
+struct Dependency {
+    name: String,
+    /// `dev` or `build`, `None` for a normal dependency
+    #[serde(default)]
+    kind: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Lockfile {
+    #[serde(default)]
+    package: Vec<LockedPackage>,
+}
+
+#[derive(Deserialize)]
+struct LockedPackage {
+    name: String,
+    version: String,
+    source: Option<String>,
+}
+
+/// A target of a package: its library, a binary, an integration test, a bench or an example.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Target {
+    pub name: String,
+    /// `lib`, `bin`, `test`, `bench` or `example`, among others
+    #[serde(default)]
+    pub kind: Vec<String>,
+    /// Its root source file
+    #[serde(default)]
+    pub src_path: PathBuf,
+}
+
+impl Target {
+    pub fn is(&self, kind: &str) -> bool {
+        self.kind.iter().any(|k| k == kind)
+    }
+}
+
+/// The root of the workspace `dir` belongs to: the closest manifest with a `[workspace]`
+/// table, or else the closest manifest's directory.
+pub fn root_dir(dir: &Path) -> Option<PathBuf> {
+    let manifest_dirs: Vec<&Path> = dir
+        .ancestors()
+        .filter(|ancestor| ancestor.join("Cargo.toml").is_file())
+        .collect();
+    manifest_dirs
+        .iter()
+        .find(|manifest_dir| {
+            std::fs::read_to_string(manifest_dir.join("Cargo.toml"))
+                .ok()
+                .and_then(|content| content.parse::<toml::Table>().ok())
+                .is_some_and(|manifest| manifest.contains_key("workspace"))
+        })
+        .or(manifest_dirs.first())
+        .map(|manifest_dir| manifest_dir.to_path_buf())
+}
+
+/// What a project builds, deciding the defaults `rx init` seeds its config with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectKind {
+    /// A package with a binary target
+    Bin,
+    /// A package with only a library
+    Lib,
+    /// A manifest with a `[workspace]` table
+    Workspace,
+}
+
+impl Display for ProjectKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kind = match self {
+            ProjectKind::Bin => "bin",
+            ProjectKind::Lib => "lib",
+            ProjectKind::Workspace => "workspace",
+        };
+        write!(f, "{}", kind)
+    }
+}
+
+impl FromStr for ProjectKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bin" => Ok(ProjectKind::Bin),
+            "lib" => Ok(ProjectKind::Lib),
+            "workspace" => Ok(ProjectKind::Workspace),
+            _ => Err(format!(
+                "Unknown project kind '{}', expected bin, lib or workspace",
+                s
+            )),
+        }
+    }
+}
+
+/// The kind of the project `dir` belongs to, read from the manifest of its [`root_dir`].
+/// `None` outside of a cargo project.
+pub fn project_kind(dir: &Path) -> Option<ProjectKind> {
+    let root = root_dir(dir)?;
+    let manifest = std::fs::read_to_string(root.join("Cargo.toml"))
+        .ok()?
+        .parse::<toml::Table>()
+        .ok()?;
+
+    if manifest.contains_key("workspace") {
+        Some(ProjectKind::Workspace)
+    } else if manifest.contains_key("bin") || root.join("src/main.rs").is_file() {
+        Some(ProjectKind::Bin)
+    } else {
+        Some(ProjectKind::Lib)
+    }
+}
+
+/// The members of the workspace `dir` belongs to, sorted by name, from `cargo metadata`.
+pub fn members(dir: &Path) -> Result<Vec<Member>, String> {
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let output = Command::new(cargo)
+        .args(["metadata", "--no-deps", "--format-version", "1"])
+        .current_dir(dir)
+        .output()
+        .map_err(|err| format!("cannot run cargo metadata: {}", err))?;
+    if !output.status.success() {
+        return Err(format!(
+            "cargo metadata failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    parse_metadata(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Reads the members from the output of `cargo metadata --no-deps --format-version 1`.
+pub fn parse_metadata(json: &str) -> Result<Vec<Member>, String> {
+    let metadata: Metadata =
+        serde_json::from_str(json).map_err(|err| format!("invalid cargo metadata: {}", err))?;
+
+    let names: Vec<String> = metadata
+        .packages
+        .iter()
+        .map(|package| package.name.clone())
+        .collect();
+    let mut members: Vec<Member> = metadata
+        .packages
+        .into_iter()
+        .map(|package| {
+            let mut dependencies: Vec<String> = package
+                .dependencies
+                .into_iter()
+                .filter(|dependency| dependency.kind.as_deref() != Some("dev"))
+                .map(|dependency| dependency.name)
+                .filter(|name| names.contains(name))
+                .collect();
+            dependencies.sort();
+            dependencies.dedup();
+            let mut member = Member {
+                manifest_dir: package
+                    .manifest_path
+                    .parent()
+                    .map(Path::to_path_buf)
+                    .unwrap_or_default(),
+                name: package.name,
+                binary_name: None,
+                targets: package.targets,
+                dependencies,
+            };
+            member.binary_name = default_binary(package.default_run, member.binaries()).ok();
+            member
+        })
+        .collect();
+    members.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(members)
+}
+
+/// The binary `${binaryName}` is for a package with the binary targets `binaries`: its
+/// `default_run` one or else its only one. The error holds the binaries to pick from, none for
+/// a library.
+pub fn default_binary(
+    default_run: Option<String>,
+    mut binaries: Vec<String>,
+) -> Result<String, Vec<String>> {
+    match default_run {
+        Some(name) => Ok(name),
+        None if binaries.len() == 1 => Ok(binaries.remove(0)),
+        None => Err(binaries),
+    }
+}
+
+/// The member called `name`, the error lists the valid names.
+pub fn find_member<'m>(members: &'m [Member], name: &str) -> Result<&'m Member, String> {
+    members
+        .iter()
+        .find(|member| member.name == name)
+        .ok_or_else(|| {
+            let names: Vec<&str> = members.iter().map(|member| member.name.as_str()).collect();
+            format!(
+                "Unknown package '{}', expected one of: {}",
+                name,
+                names.join(", ")
+            )
+        })
+}
+
+/// Whether cargo `args` already pick the packages to act on, up to a `--` separator.
+pub fn selects_package<'a>(args: impl IntoIterator<Item = &'a str>) -> bool {
+    args.into_iter().take_while(|arg| *arg != "--").any(|arg| {
+        matches!(arg, "--package" | "--workspace" | "--all")
+            || arg.starts_with("--package=")
+            || arg.starts_with("-p")
+    })
+}
+
+/// Where cargo keeps what it downloads, `$CARGO_HOME` or `~/.cargo`.
+pub fn cargo_home() -> Option<PathBuf> {
+    std::env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".cargo")))
+}
+
+/// The registry packages of the `Cargo.lock` of the workspace `dir` belongs to that aren't in
+/// the cache of `cargo_home`, as `name version`: what cargo would download. An error when
+/// the workspace has no `Cargo.lock`, cargo then resolves the dependencies online. Git
+/// dependencies aren't checked, nothing outside of a cargo project.
+pub fn missing_packages(dir: &Path, cargo_home: &Path) -> Result<Vec<String>, String> {
+    let Some(root) = root_dir(dir) else {
+        return Ok(Vec::new());
+    };
+    let lockfile = root.join("Cargo.lock");
+    let content = std::fs::read_to_string(&lockfile)
+        .map_err(|_| format!("{} has no Cargo.lock", root.display()))?;
+    let lock: Lockfile = toml::from_str(&content)
+        .map_err(|err| format!("cannot read {}: {}", lockfile.display(), err))?;
+
+    let registry_dirs = |kind: &str| -> Vec<PathBuf> {
+        std::fs::read_dir(cargo_home.join("registry").join(kind))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .collect()
+    };
+    let (caches, sources) = (registry_dirs("cache"), registry_dirs("src"));
+    Ok(lock
+        .package
+        .iter()
+        .filter(|package| {
+            package.source.as_ref().is_some_and(|source| {
+                source.starts_with("registry+") || source.starts_with("sparse+")
+            })
+        })
+        .filter(|package| {
+            let name = format!("{}-{}", package.name, package.version);
+            let crate_file = format!("{}.crate", name);
+            !caches.iter().any(|cache| cache.join(&crate_file).is_file())
+                && !sources.iter().any(|source| source.join(&name).is_dir())
+        })
+        .map(|package| format!("{} {}", package.name, package.version))
+        .collect())
+}
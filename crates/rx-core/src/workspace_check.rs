@@ -0,0 +1,247 @@
+use std::{
+    collections::HashSet,
+    fmt::{self, Display},
+    str::FromStr,
+    sync::mpsc,
+    thread,
+    time::Instant,
+};
+
+use serde::Serialize;
+
+use crate::{errors::ConfigError, executor::shutdown::ShutdownSignal, workspace::Member};
+
+/// A cargo subcommand `rx check` runs on each workspace member.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStep {
+    Check,
+    Clippy,
+    Test,
+}
+
+impl CheckStep {
+    /// The arguments of cargo running the step on `member` alone.
+    pub fn cargo_args(self, member: &str) -> Vec<String> {
+        vec![self.to_string(), "--package".into(), member.into()]
+    }
+}
+
+impl Display for CheckStep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            CheckStep::Check => "check",
+            CheckStep::Clippy => "clippy",
+            CheckStep::Test => "test",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for CheckStep {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "check" => Ok(CheckStep::Check),
+            "clippy" => Ok(CheckStep::Clippy),
+            "test" => Ok(CheckStep::Test),
+            _ => Err(ConfigError::UnknownCheckStep(s.to_string())),
+        }
+    }
+}
+
+/// How a step went for a member.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckOutcome {
+    Passed,
+    Failed,
+    /// Not run, an earlier step of the member failed or rx was interrupted
+    Skipped,
+}
+
+/// The outcomes of the steps of a member, in the order of the steps.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MemberCheck {
+    pub member: String,
+    pub outcomes: Vec<CheckOutcome>,
+    pub duration_ms: u64,
+}
+
+/// The outcome of each step for each member, the members sorted by name.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CheckMatrix {
+    pub steps: Vec<CheckStep>,
+    pub members: Vec<MemberCheck>,
+}
+
+impl CheckMatrix {
+    /// Whether every step passed for every member.
+    pub fn success(&self) -> bool {
+        self.members.iter().all(|member| {
+            member
+                .outcomes
+                .iter()
+                .all(|outcome| *outcome == CheckOutcome::Passed)
+        })
+    }
+
+    /// The matrix as pretty-printed JSON.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        Ok(serde_json::to_string_pretty(self)? + "\n")
+    }
+
+    /// A table of the members by the steps, `✓` for a step that passed, `✗` for one that
+    /// failed and `-` for one skipped.
+    pub fn report(&self) -> String {
+        let width = self
+            .members
+            .iter()
+            .map(|member| member.member.chars().count())
+            .chain(std::iter::once("member".len()))
+            .max()
+            .unwrap_or_default();
+        let mut lines = vec![std::iter::once(format!("{:<width$}", "member"))
+            .chain(
+                self.steps
+                    .iter()
+                    .map(|step| format!("{:<6}", step.to_string())),
+            )
+            .collect::<Vec<_>>()
+            .join("  ")];
+        for member in &self.members {
+            let outcomes = member.outcomes.iter().map(|outcome| {
+                let mark = match outcome {
+                    CheckOutcome::Passed => "✓",
+                    CheckOutcome::Failed => "✗",
+                    CheckOutcome::Skipped => "-",
+                };
+                format!("{:<6}", mark)
+            });
+            lines.push(
+                std::iter::once(format!("{:<width$}", member.member))
+                    .chain(outcomes)
+                    .collect::<Vec<_>>()
+                    .join("  "),
+            );
+        }
+        lines
+            .iter()
+            .map(|line| line.trim_end())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// `members` in an order where each comes after the members it depends on, otherwise by name.
+/// The error names the members depending on each other in a cycle.
+pub fn dependency_order(members: &[Member]) -> Result<Vec<&Member>, String> {
+    let names: HashSet<&str> = members.iter().map(|member| member.name.as_str()).collect();
+    let mut pending: Vec<&Member> = members.iter().collect();
+    pending.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut ordered: Vec<&Member> = Vec::new();
+    while !pending.is_empty() {
+        let ready = pending.iter().position(|member| {
+            member.dependencies.iter().all(|dependency| {
+                !names.contains(dependency.as_str())
+                    || ordered.iter().any(|done| &done.name == dependency)
+            })
+        });
+        match ready {
+            Some(index) => ordered.push(pending.remove(index)),
+            None => {
+                let cycle: Vec<&str> = pending.iter().map(|member| member.name.as_str()).collect();
+                return Err(format!(
+                    "The members {} depend on each other",
+                    cycle.join(", ")
+                ));
+            }
+        }
+    }
+    Ok(ordered)
+}
+
+/// Runs `steps` on each of `members` with `run`, which tells whether the step passed. A member
+/// starts once the members it depends on are done, up to `jobs` members at a time, and its
+/// steps run in order until one fails. Once `shutdown` is triggered no other member starts.
+pub fn run_checks<F>(
+    members: &[Member],
+    steps: &[CheckStep],
+    jobs: usize,
+    shutdown: &ShutdownSignal,
+    run: F,
+) -> Result<CheckMatrix, String>
+where
+    F: Fn(&Member, CheckStep) -> bool + Sync,
+{
+    let mut pending = dependency_order(members)?;
+    let names: HashSet<&str> = members.iter().map(|member| member.name.as_str()).collect();
+    let mut done: HashSet<&str> = HashSet::new();
+    let mut checks = Vec::new();
+
+    thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel();
+        let mut running = 0;
+        loop {
+            if !shutdown.is_triggered() {
+                let mut index = 0;
+                while running < jobs.max(1) && index < pending.len() {
+                    let ready = pending[index].dependencies.iter().all(|dependency| {
+                        !names.contains(dependency.as_str()) || done.contains(dependency.as_str())
+                    });
+                    if !ready {
+                        index += 1;
+                        continue;
+                    }
+                    let member = pending.remove(index);
+                    let sender = sender.clone();
+                    let run = &run;
+                    scope.spawn(move || {
+                        let started = Instant::now();
+                        let mut outcomes = Vec::new();
+                        for step in steps {
+                            let failed = outcomes.contains(&CheckOutcome::Failed);
+                            outcomes.push(match failed || shutdown.is_triggered() {
+                                true => CheckOutcome::Skipped,
+                                false if run(member, *step) => CheckOutcome::Passed,
+                                false => CheckOutcome::Failed,
+                            });
+                        }
+                        let _ = sender.send(MemberCheck {
+                            member: member.name.clone(),
+                            outcomes,
+                            duration_ms: started.elapsed().as_millis() as u64,
+                        });
+                    });
+                    running += 1;
+                }
+            }
+            if running == 0 {
+                break;
+            }
+            let Ok(check) = receiver.recv() else {
+                break;
+            };
+            running -= 1;
+            if let Some(member) = members.iter().find(|member| member.name == check.member) {
+                done.insert(member.name.as_str());
+            }
+            checks.push(check);
+        }
+    });
+
+    // Those never started, interrupted before their turn
+    for member in pending {
+        checks.push(MemberCheck {
+            member: member.name.clone(),
+            outcomes: vec![CheckOutcome::Skipped; steps.len()],
+            duration_ms: 0,
+        });
+    }
+    checks.sort_by(|a, b| a.member.cmp(&b.member));
+    Ok(CheckMatrix {
+        steps: steps.to_vec(),
+        members: checks,
+    })
+}
@@ -0,0 +1,27 @@
+#[cfg(test)]
+mod tests {
+    use rx_core::about::{BuildInfo, VersionFormat};
+
+    #[test]
+    fn test_build_info_as_text_and_json() {
+        let info = BuildInfo {
+            version: "1.2.3".to_string(),
+            git_sha: "abc1234".to_string(),
+            build_date: "2024-05-01".to_string(),
+            target: "x86_64-unknown-linux-gnu".to_string(),
+            features: vec!["keychain".to_string()],
+        };
+        assert_eq!(
+            info.format(VersionFormat::Text).unwrap(),
+            "rx 1.2.3 (abc1234 2024-05-01)\n"
+        );
+
+        let format: VersionFormat = "json".parse().unwrap();
+        let json: serde_json::Value = serde_json::from_str(&info.format(format).unwrap()).unwrap();
+        assert_eq!(json["git_sha"], "abc1234");
+        assert_eq!(json["target"], "x86_64-unknown-linux-gnu");
+        assert_eq!(json["features"], serde_json::json!(["keychain"]));
+
+        assert!("yaml".parse::<VersionFormat>().is_err());
+    }
+}
@@ -0,0 +1,59 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use rx_core::{
+        artifacts::copy_artifacts,
+        diagnostics::{parse_message, CargoMessage},
+        interpolation::InterpolationContext,
+    };
+    use tempfile::TempDir;
+
+    const HOST: &str = "x86_64-unknown-linux-gnu";
+
+    #[test]
+    fn test_artifacts_are_parsed_and_copied() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let target_dir = temp_dir.path().join("target");
+        let binary = target_dir.join("aarch64-apple-darwin/release/app");
+        fs::create_dir_all(binary.parent().unwrap()).unwrap();
+        fs::write(target_dir.join("CACHEDIR.TAG"), "").unwrap();
+        fs::write(&binary, "binary").unwrap();
+
+        let line = serde_json::json!({
+            "reason": "compiler-artifact",
+            "package_id": "path+file:///src/app#0.1.0",
+            "target": {"name": "app", "kind": ["bin"]},
+            "filenames": [binary],
+            "executable": binary,
+        })
+        .to_string();
+        let Some(CargoMessage::Artifact(artifact)) = parse_message(&line) else {
+            panic!("not an artifact: {}", line);
+        };
+        assert_eq!(artifact.paths, vec![binary.clone()]);
+        assert_eq!(artifact.profile().as_deref(), Some("release"));
+        assert_eq!(artifact.target(HOST), "aarch64-apple-darwin");
+
+        let dependency = line.replace(
+            "path+file:///src/app#0.1.0",
+            "registry+https://github.com/rust-lang/crates.io-index#serde@1.0.0",
+        );
+        assert_eq!(parse_message(&dependency), Some(CargoMessage::Other));
+
+        let mut interpolation = InterpolationContext::default();
+        interpolation.set("workspaceFolder", &temp_dir.path().to_string_lossy());
+        let copies = copy_artifacts(
+            &[artifact],
+            "${workspaceFolder}/dist/${target}/${profile}",
+            &interpolation,
+            HOST,
+        )
+        .unwrap();
+        let copy = temp_dir
+            .path()
+            .join("dist/aarch64-apple-darwin/release/app");
+        assert_eq!(copies, vec![copy.clone()]);
+        assert_eq!(fs::read_to_string(copy).unwrap(), "binary");
+    }
+}
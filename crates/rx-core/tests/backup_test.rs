@@ -0,0 +1,50 @@
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use rx_core::backup::{timestamp, Backups};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_backups_keep_the_latest_and_restore() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let config_path = temp_dir.path().join("config.toml");
+        let backups = Backups::new(&config_path, 2);
+        assert_eq!(backups.create().unwrap(), None);
+
+        let versions = [
+            "[settings]\nnotify = true\n",
+            "include = [\"a.toml\"]\n",
+            "",
+        ];
+        for version in versions {
+            std::fs::write(&config_path, version).unwrap();
+            backups.create().unwrap().unwrap();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let kept = backups.list().unwrap();
+        assert_eq!(kept.len(), 2);
+        assert_eq!(std::fs::read_to_string(&kept[0].path).unwrap(), versions[1]);
+
+        std::fs::write(&config_path, "not toml [").unwrap();
+        let backup = backups.find(Some(&kept[0].timestamp)).unwrap();
+        assert_eq!(backup, kept[0]);
+        backups.restore(&backup).unwrap();
+        assert_eq!(std::fs::read_to_string(&config_path).unwrap(), versions[1]);
+        // The broken config was backed up before being replaced
+        let latest = backups.list().unwrap().pop().unwrap();
+        assert_eq!(std::fs::read_to_string(latest.path).unwrap(), "not toml [");
+        assert!(backups.restore(&backups.find(None).unwrap()).is_err());
+        assert!(backups.find(Some("1999")).is_err());
+
+        assert_eq!(
+            timestamp(UNIX_EPOCH + Duration::from_millis(1_792_145_700_123)),
+            "20261016T101500.123Z"
+        );
+        assert_eq!(timestamp(UNIX_EPOCH), "19700101T000000.000Z");
+        assert_eq!(
+            timestamp(UNIX_EPOCH + Duration::from_secs(951_782_400)),
+            "20000229T000000.000Z"
+        );
+    }
+}
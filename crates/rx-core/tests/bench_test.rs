@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod tests {
+    use rx_core::{
+        bench::{compare, parse_output, BenchRun},
+        store::Store,
+    };
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_libtest_and_criterion_output() {
+        let output = r#"
+running 2 tests
+test tests::bench_add    ... bench:       1,234 ns/iter (+/- 56)
+test tests::bench_parse  ... bench:         987.50 ns/iter (+/- 3.20)
+
+fib 20                  time:   [26.029 us 26.251 us 26.505 us]
+a/very/long/benchmark/name/that/wraps
+                        time:   [1.5000 ms 1.5100 ms 1.5200 ms]
+                        change: [-1.2% +0.4% +2.0%] (p = 0.50 > 0.05)
+"#;
+
+        let results = parse_output(output);
+        let summary: Vec<(&str, f64)> = results
+            .iter()
+            .map(|result| (result.name.as_str(), result.ns_per_iter))
+            .collect();
+
+        assert_eq!(
+            summary,
+            [
+                ("tests::bench_add", 1234.0),
+                ("tests::bench_parse", 987.5),
+                ("fib 20", 26251.0),
+                ("a/very/long/benchmark/name/that/wraps", 1_510_000.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compare_reports_regressions_above_threshold() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let store = Store::at(temp_dir.path().to_path_buf());
+
+        let baseline = BenchRun {
+            revision: "abc1234".to_string(),
+            results: parse_output(
+                "test a ... bench: 100 ns/iter (+/- 1)\ntest b ... bench: 100 ns/iter (+/- 1)",
+            ),
+        };
+        baseline.save(&store).unwrap();
+        let baseline = BenchRun::load(&store, "abc1234").unwrap().unwrap();
+
+        let current = BenchRun {
+            revision: "def5678".to_string(),
+            results: parse_output(
+                "test a ... bench: 104 ns/iter (+/- 1)\ntest b ... bench: 120 ns/iter (+/- 1)",
+            ),
+        };
+
+        let comparisons = compare(&baseline, &current);
+        let regressed: Vec<&str> = comparisons
+            .iter()
+            .filter(|comparison| comparison.is_regression(5))
+            .map(|comparison| comparison.name.as_str())
+            .collect();
+
+        assert_eq!(comparisons.len(), 2);
+        assert_eq!(regressed, ["b"]);
+    }
+}
@@ -0,0 +1,65 @@
+#[cfg(test)]
+mod tests {
+    use rx_core::{
+        artifacts::Artifact,
+        build_size::{compare, format_bytes, BuildRun},
+        store::Store,
+    };
+    use std::{fs, time::Duration};
+    use tempfile::TempDir;
+
+    const HOST: &str = "x86_64-unknown-linux-gnu";
+
+    #[test]
+    fn test_build_sizes_are_recorded_per_revision_and_compared() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let release = temp_dir.path().join("target/release");
+        fs::create_dir_all(release.join("deps")).unwrap();
+        fs::write(temp_dir.path().join("target/CACHEDIR.TAG"), "").unwrap();
+        fs::write(release.join("app"), vec![0; 3000]).unwrap();
+        fs::write(release.join("deps/libcore.rlib"), vec![0; 100]).unwrap();
+        let artifacts = [
+            Artifact {
+                name: "app".into(),
+                kind: "bin".into(),
+                paths: vec![release.join("app")],
+            },
+            Artifact {
+                name: "core".into(),
+                kind: "lib".into(),
+                paths: vec![release.join("deps/libcore.rlib")],
+            },
+        ];
+
+        let store = Store::at(temp_dir.path().join("store"));
+        let mut baseline = BuildRun {
+            revision: "abc1234".into(),
+            ..Default::default()
+        };
+        baseline.record("build::release", Duration::from_secs(20), &artifacts, HOST);
+        baseline.save(&store).unwrap();
+        let baseline = BuildRun::load(&store, "abc1234").unwrap().unwrap();
+        assert_eq!(baseline.binaries.len(), 1);
+        assert_eq!(baseline.binaries[0].name, "app");
+        assert_eq!(baseline.binaries[0].profile, "release");
+        assert_eq!(baseline.binaries[0].bytes, 3000);
+        assert!(BuildRun::load(&store, "def5678").unwrap().is_none());
+
+        fs::write(release.join("app"), vec![0; 3600]).unwrap();
+        let mut current = BuildRun {
+            revision: "def5678".into(),
+            ..Default::default()
+        };
+        current.record("build::release", Duration::from_secs(15), &artifacts, HOST);
+        let (sizes, durations) = compare(&baseline, &current);
+        assert_eq!(sizes.len(), 1);
+        assert_eq!((sizes[0].baseline, sizes[0].current), (3000, 3600));
+        assert_eq!(sizes[0].change_percent(), 20.0);
+        assert_eq!(durations[0].name, "build::release");
+        assert_eq!(durations[0].change_percent(), -25.0);
+
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(3600), "3.5 KiB");
+        assert_eq!(format_bytes(5 * 1024 * 1024), "5.0 MiB");
+    }
+}
@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod tests {
+    use rx_core::{
+        config_diff::{diff_configs, ConfigChange},
+        models::config::Config,
+    };
+
+    #[test]
+    fn test_diff_reports_entries_and_fields_not_text() {
+        let old: Config = toml::from_str(
+            r#"
+            [commands.build.configs.release]
+            command = "build"
+            params = "--release"
+
+            [commands.script.configs.deploy]
+            type = "shell"
+            command = "./deploy.sh"
+
+            [settings]
+            notify = true
+            "#,
+        )
+        .unwrap();
+        let new: Config = toml::from_str(
+            r#"
+            [commands.build]
+            default = "release"
+
+            [commands.build.configs.release]
+            command = "build"
+            params = "--release --locked"
+            tags = ["ci"]
+
+            [commands.build.configs.docs]
+            command = "doc"
+            "#,
+        )
+        .unwrap();
+
+        let changes: Vec<String> = diff_configs(&old, &new)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                "+ build::docs",
+                "~ build::release.params: \"--release\" -> \"--release --locked\"",
+                "~ build::release.tags: unset -> [\"ci\"]",
+                "~ commands.build.default: \"default\" -> \"release\"",
+                "- script::deploy",
+                "~ settings.notify: true -> unset",
+            ]
+        );
+        assert_eq!(
+            diff_configs(&new, &old)[0],
+            ConfigChange::Removed("build::docs".into())
+        );
+        assert!(diff_configs(&old, &old.clone()).is_empty());
+    }
+}
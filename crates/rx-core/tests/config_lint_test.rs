@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    use rx_core::{config_lint::lint, interpolation::InterpolationContext, models::config::Config};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_lint_reports_suspicious_entries() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        std::fs::write(temp_dir.path().join(".env"), "RUST_LOG=info\n").unwrap();
+
+        let config: Config = toml::from_str(
+            r#"
+            [commands.build]
+            default = "release"
+
+            [commands.build.configs.release]
+            command = "build --release"
+            params = "--release --locked"
+
+            [commands.build.configs.help]
+            command = ""
+
+            [commands.run]
+            default = "default"
+
+            [commands.run.configs.default]
+            command = "run"
+            working_directory = "/home/someone/app"
+            env_file = ".env"
+            env = { RUST_LOG = "debug", Path = "bin", PATH = "bin" }
+
+            [commands.test]
+            default = "unit"
+
+            [commands.test.configs.unit]
+            command = "test"
+            working_directory = "${workspaceFolder}"
+            env_file = ".env"
+            env = { RUST_LOG = "trace", API_TOKEN = "abc123", DB_PASSWORD = "enc:aHVudGVyMg==" }
+            "#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+
+        let interpolation = InterpolationContext::from_dir(temp_dir.path());
+        let warnings: Vec<String> = lint(&config, &interpolation)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            warnings,
+            [
+                "run::default: working_directory '/home/someone/app' is absolute, ${workspaceFolder} keeps the config portable",
+                "run::default: env sets both PATH and Path, which are the same variable on Windows",
+                "test::unit: env API_TOKEN holds a credential in plain text, `rx secret set` encrypts it",
+                "test::unit: env overrides RUST_LOG from env_file .env",
+                "build::help: command is empty, cargo alone only prints its help",
+                "build::help: is neither a default nor referenced by another entry or hook, it only runs when asked for by name",
+                "build::release: params repeat --release from command",
+            ]
+        );
+    }
+}
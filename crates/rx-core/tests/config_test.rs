@@ -0,0 +1,1357 @@
+#[cfg(test)]
+mod tests {
+    use rx_core::source::ConfigSource;
+    use rx_core::{
+        builders::{
+            command_config::CommandConfigBuilder,
+            config::{CommandDetailsBuilder, ConfigBuilder},
+        },
+        errors::ConfigError,
+        executor::{runner::Runner, shutdown::ShutdownSignal},
+        helpers::{default_config_path, CONFIG_PATH_ENV},
+        models::{
+            config::{is_key_pattern, CommandContext, CommandType, Config, Removal, SingleFile},
+            format::ConfigFormat,
+            params::Params,
+        },
+    };
+    use std::{fs, path::PathBuf};
+    use tempfile::TempDir;
+
+    /// Sets up the test environment, including creating a temporary config file.
+    /// Optionally accepts custom configuration content as a string.
+    ///
+    /// # Arguments
+    ///
+    /// * `custom_config` - Optional custom configuration content to write to the config file.
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing the loaded [Config] instance and the [PathBuf] to the temporary config file.
+    ///
+    /// The [TempDir] must be drop manually at the last line of the function so the file path wont be
+    /// destroyed and thus avoiding error FileNotFound
+    fn setup(custom_config: Option<&str>) -> (Config, PathBuf, TempDir) {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let config_path = temp_dir.path().join("config.toml");
+
+        // Determine the configuration content to write: custom or empty
+        let config_content = custom_config.unwrap_or("");
+
+        // Write the determined configuration content to the file
+        fs::write(&config_path, config_content).expect("Failed to write to the config file");
+
+        // Load the configuration from the newly created temp file
+        let config = Config::load(Some(config_path.clone())).expect("Loading Config Failed");
+
+        // Return the loaded configuration, the path to the config file, and the TempDir instance
+        (config, config_path, temp_dir)
+    }
+
+    /// This test check if when File is Empty then a Default Commands Should be provided
+    #[test]
+    fn test_default_config() {
+        let (config, _, _) = setup(None);
+
+        // For each command, check existence and default details
+        let run_config = config.commands.run.expect("run config should have default");
+        assert_eq!(run_config.default, "default");
+        let run_details = run_config
+            .configs
+            .get("default")
+            .expect("default run config should exist");
+        assert_eq!(
+            run_details.command,
+            "run --package ${packageName} --bin ${binaryName}"
+        );
+        assert_eq!(run_details.command_type, CommandType::Cargo);
+
+        let test_config = config
+            .commands
+            .test
+            .expect("test config should have default");
+        assert_eq!(test_config.default, "default");
+        let test_details = test_config
+            .configs
+            .get("default")
+            .expect("default test config should exist");
+        assert_eq!(test_details.command, "test");
+        assert_eq!(test_details.command_type, CommandType::Cargo);
+
+        let build_config = config
+            .commands
+            .build
+            .expect("build config should have default");
+        assert_eq!(build_config.default, "default");
+        let build_details = build_config
+            .configs
+            .get("default")
+            .expect("default build config should exist");
+        assert_eq!(build_details.command, "build");
+        assert_eq!(build_details.command_type, CommandType::Cargo);
+
+        let bench_config = config
+            .commands
+            .bench
+            .expect("bench config should have default");
+        assert_eq!(bench_config.default, "default");
+
+        let bench_details = bench_config
+            .configs
+            .get("default")
+            .expect("default bench config should exist");
+
+        assert_eq!(bench_details.command, "bench");
+        assert_eq!(bench_details.command_type, CommandType::Cargo);
+
+        assert!(
+            config.commands.script.is_none(),
+            "script config is none by default"
+        );
+    }
+    /// Since our CommandDetails Dont Use Option , Most of it would Error Out if there was a  Missing Field
+    /// A serde macro for default is added to return a default for Missing Field
+    /// This Test is partly about that Missing Field , and Also To check that config is loaded
+    /// and the Fields that was Deserialize matches
+    #[test]
+    fn test_config_load() {
+        let custom_config = r#"
+[commands.run]
+default = "custom"
+
+[commands.run.configs.custom]
+type = "cargo"
+command = "run --package ${packageName} --bin ${binaryName}"
+params = ""
+allow_multiple_instances = false
+working_directory = "${workspaceFolder}"
+pre_command = []
+
+[commands.run.configs.custom.env]
+APP_NAME = "Cargo Runner"
+COPY_TRAIT = "FALSE"
+MY_CUSTOM_VAR_1 = "TRUE"
+
+[commands.test]
+default = "default"
+
+[commands.test.configs.default]
+type = "cargo"
+command = "test"
+params = ""
+allow_multiple_instances = false
+working_directory = "${workspaceFolder}"
+pre_command = []
+
+[commands.test.configs.default.env]
+"#;
+
+        let (config, _config_path, _temp_dir) = setup(Some(custom_config));
+
+        let run_config = config.commands.run.expect("Run configuration should exist");
+
+        assert_eq!(
+            run_config.default, "custom",
+            "Default should be set to 'custom'"
+        );
+
+        // Verify the existence and contents of the 'custom' config
+        let custom_config_details = run_config
+            .configs
+            .get("custom")
+            .expect("Custom config should exist under run");
+        assert_eq!(
+            custom_config_details.command, "run --package ${packageName} --bin ${binaryName}",
+            "Command does not match"
+        );
+        assert_eq!(
+            custom_config_details.params,
+            "".into(),
+            "Params do not match"
+        );
+        assert!(
+            !custom_config_details.allow_multiple_instances,
+            "Allow Multiple Instances should be false by default"
+        );
+        assert_eq!(
+            custom_config_details.working_directory, "${workspaceFolder}",
+            "Working directory does not match"
+        );
+        assert!(
+            custom_config_details.env.contains_key("APP_NAME"),
+            "Should have APP_NAME loaded to config"
+        );
+        assert!(
+            custom_config_details.env.contains_key("COPY_TRAIT"),
+            "Should have COPY_TRAIT loaded to the config"
+        );
+        assert!(
+            custom_config_details.env.contains_key("MY_CUSTOM_VAR_1"),
+            "Should have MY_CUSTOM_VAR_1 loaded to the config"
+        );
+    }
+
+    #[test]
+    fn test_update_and_add_config_key() {
+        let (mut config, config_path, _temp_dir) = setup(None); // Use _temp_dir to hold onto the TempDir instance
+
+        let config_key = "leptos";
+
+        let context = CommandContext::Script;
+
+        let new_details = ConfigBuilder::new(context)
+            .command_type(CommandType::Shell)
+            .command("cargo leptos")
+            .params("watch")
+            .build()
+            .unwrap();
+
+        // Update the configuration for 'Run' context with new details
+        let run_config = config.commands.get_or_default_config(context);
+        run_config.update_config(config_key, new_details);
+
+        // Save the updated configuration
+        assert!(
+            config.save(Some(config_path.clone())).is_ok(),
+            "Failed to save updated configuration"
+        );
+
+        // Reload the configuration to verify updates
+        let reloaded_config =
+            Config::load(Some(config_path.clone())).expect("Failed to reload configuration");
+
+        // Assert that the updated details are present
+        assert!(
+            reloaded_config.commands.script.is_some(),
+            "Run configuration should exist"
+        );
+        let reloaded_run_config = reloaded_config.commands.script.unwrap();
+        assert!(
+            reloaded_run_config.configs.contains_key(config_key),
+            "Config key 'leptos' should be present in the run configs"
+        );
+
+        let updated_details = reloaded_run_config
+            .configs
+            .get(config_key)
+            .expect("Config key 'leptos' was not found after update");
+        assert_eq!(
+            updated_details.command, "cargo leptos",
+            "Command for 'leptos' config key did not match expected value"
+        );
+    }
+
+    #[test]
+    fn test_remove_config_key_and_default_handling() {
+        let (mut config, config_path, _temp_dir) = setup(None);
+
+        let config_key = "leptos";
+
+        let context = CommandContext::Script;
+
+        let details = ConfigBuilder::new(context)
+            .command_type(CommandType::Shell)
+            .command("cargo watch")
+            .build()
+            .unwrap();
+
+        // add leptos to configs
+        {
+            let run_config = config.commands.get_or_default_config(context);
+            run_config.update_config(config_key, details);
+        }
+
+        // Initially set 'leptos' as the default
+        // note this would error out if leptos is not created yet
+        {
+            assert!(
+                config
+                    .commands
+                    .set_default_config(context, config_key)
+                    .is_ok(),
+                "Setting default config failed"
+            );
+        }
+
+        // Now, remove the 'leptos' config key
+        {
+            let run_config = config.commands.get_or_default_config(context);
+
+            run_config.remove_config(config_key);
+        }
+
+        // Save the updated configuration
+        assert!(
+            config.save(Some(config_path.clone())).is_ok(),
+            "Failed to save configuration after removal"
+        );
+
+        // Reload the configuration to verify that the removal persists
+        let reloaded_config =
+            Config::load(Some(config_path.clone())).expect("Failed to reload configuration");
+
+        // Verify 'leptos' has been removed
+        assert!(
+            reloaded_config.commands.run.is_some(),
+            "Run configuration should exist"
+        );
+
+        let reloaded_run_config = reloaded_config.commands.run.unwrap();
+        assert!(
+            !reloaded_run_config.configs.contains_key(config_key),
+            "Config key 'leptos' should have been removed"
+        );
+
+        // Optionally, test how the removal affects the default config
+        // This will depend on your implementation of `set_default_config` and `remove_config`
+        // For example, you might check if the default has been reverted to another key
+        assert_ne!(
+            reloaded_run_config.default, config_key,
+            "Default config key should no longer be 'leptos'"
+        );
+    }
+
+    #[test]
+    fn test_target_overrides() {
+        let (config, _, temp_dir) = setup(Some(
+            r#"
+[commands.build]
+default = "default"
+
+[commands.build.configs.default]
+command = "build"
+params = "--release"
+env = { PROFILE = "release" }
+
+[commands.build.configs.default.target.x86_64-pc-windows-msvc]
+command = "xwin build"
+env = { XWIN_ARCH = "x86_64" }
+
+[commands.build.configs.default.target.windows]
+params = "--locked"
+
+[commands.build.configs.default.target.macos]
+params = "--features metal"
+"#,
+        ));
+
+        let details = &config.commands.build.as_ref().unwrap().configs["default"];
+
+        let msvc = details.for_target("x86_64-pc-windows-msvc");
+        assert_eq!(msvc.command, "xwin build");
+        assert_eq!(msvc.params, "--release".into());
+        assert_eq!(msvc.env["PROFILE"], "release");
+        assert_eq!(msvc.env["XWIN_ARCH"], "x86_64");
+
+        assert_eq!(
+            details.for_target("x86_64-pc-windows-gnu").params,
+            "--locked".into()
+        );
+        assert_eq!(
+            details.for_target("aarch64-apple-darwin").params,
+            "--features metal".into()
+        );
+        assert_eq!(*details.for_target("x86_64-unknown-linux-gnu"), *details);
+
+        drop(temp_dir);
+    }
+
+    #[test]
+    fn test_rename_config_fixes_references() {
+        let (mut config, _, temp_dir) = setup(Some(
+            r#"
+[hooks]
+before_each = ["run::dev", "echo run::dev"]
+
+[commands.run]
+default = "dev"
+
+[commands.run.configs.dev]
+command = "run"
+
+[commands.run.configs.release]
+command = "run"
+params = "--release"
+pre_command = ["dev"]
+"#,
+        ));
+
+        config
+            .rename_config(CommandContext::Run, "dev", "debug")
+            .unwrap();
+
+        let run = config.commands.run.as_ref().unwrap();
+        assert_eq!(run.default, "debug");
+        assert!(run.configs.contains_key("debug") && !run.configs.contains_key("dev"));
+        assert!(run.configs["release"].pre_command.contains("debug"));
+        assert_eq!(config.hooks.before_each, ["run::debug", "echo run::dev"]);
+
+        assert!(config
+            .rename_config(CommandContext::Run, "debug", "release")
+            .is_err());
+
+        drop(temp_dir);
+    }
+
+    #[test]
+    fn test_copy_config_within_and_across_contexts() {
+        let (mut config, _, temp_dir) = setup(None);
+        let original = config.commands.run.as_ref().unwrap().configs["default"].clone();
+
+        config
+            .copy_config(CommandContext::Run, "default", CommandContext::Run, "copy")
+            .unwrap();
+        assert_eq!(
+            config.commands.run.as_ref().unwrap().configs["copy"],
+            original
+        );
+
+        config
+            .copy_config(
+                CommandContext::Run,
+                "default",
+                CommandContext::Script,
+                "serve",
+            )
+            .unwrap();
+        assert_eq!(
+            config.commands.script.as_ref().unwrap().configs["serve"].command,
+            original.command
+        );
+
+        assert!(config
+            .copy_config(CommandContext::Run, "copy", CommandContext::Run, "default")
+            .is_err());
+
+        drop(temp_dir);
+    }
+
+    #[test]
+    fn test_set_and_get_by_dotted_path() {
+        let (mut config, _, temp_dir) = setup(None);
+
+        config
+            .set_path("commands.run.configs.server.params", "--release")
+            .unwrap();
+        config
+            .set_path("commands.run.configs.server.env", "PORT=8080,DEBUG=true")
+            .unwrap();
+        config.set_path("settings.notify", "true").unwrap();
+
+        let server = &config.commands.run.as_ref().unwrap().configs["server"];
+        assert_eq!(server.params, "--release".into());
+        assert_eq!(server.env["PORT"], "8080");
+        assert!(config.settings.notify);
+        assert_eq!(
+            config
+                .get_path("commands.run.configs.server.params")
+                .unwrap(),
+            toml::Value::String("--release".to_string())
+        );
+
+        let before = config.clone();
+        assert!(config
+            .set_path(
+                "commands.run.configs.server.allow_multiple_instances",
+                "yes"
+            )
+            .is_err());
+        assert!(config
+            .set_path("commands.run.configs.server.paramz", "--release")
+            .is_err());
+        assert!(config
+            .set_path("commands.run.configs.server.pre_command", r#"["missing"]"#)
+            .is_err());
+        assert_eq!(config, before);
+
+        drop(temp_dir);
+    }
+
+    #[test]
+    fn test_set_fields_of_a_config() {
+        let (mut config, _, temp_dir) = setup(Some(
+            r#"
+[commands.run]
+default = "server"
+
+[commands.run.configs.server]
+type = "cargo"
+command = "run"
+"#,
+        ));
+
+        config
+            .set_fields(
+                CommandContext::Run,
+                "server",
+                &[
+                    ("type".into(), "shell".into()),
+                    ("params".into(), "--release".into()),
+                    ("limits.nice".into(), "5".into()),
+                ],
+            )
+            .unwrap();
+        let server = &config.commands.run.as_ref().unwrap().configs["server"];
+        assert_eq!(server.command_type, CommandType::Shell);
+        assert_eq!(server.params, "--release".into());
+        assert_eq!(server.limits.nice, Some(5));
+
+        assert!(matches!(
+            config.set_fields(CommandContext::Run, "missing", &[]),
+            Err(ConfigError::ConfigKeyNotFound(label)) if label == "run::missing"
+        ));
+        drop(temp_dir);
+    }
+
+    /// Loading reads the file afresh each time, so repeated and concurrent loads agree
+    #[test]
+    fn test_repeated_loads_are_idempotent() {
+        let (config, config_path, temp_dir) = setup(Some(
+            r#"
+[commands.script]
+default = "hello"
+
+[commands.script.configs.hello]
+type = "shell"
+command = "echo hello"
+"#,
+        ));
+
+        assert_eq!(Config::load(Some(config_path.clone())).unwrap(), config);
+        let loads: Vec<Config> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..4)
+                .map(|_| scope.spawn(|| Config::load(Some(config_path.clone())).unwrap()))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect()
+        });
+        assert!(loads.iter().all(|loaded| *loaded == config));
+
+        fs::write(&config_path, "").unwrap();
+        let reloaded = Config::load(Some(config_path.clone())).unwrap();
+        assert!(reloaded.commands.script.is_none());
+
+        drop(temp_dir);
+    }
+
+    #[test]
+    fn test_saved_configs_keep_a_stable_order() {
+        let config: Config = toml::from_str(
+            r#"
+[commands.script]
+default = "zeta"
+
+[commands.script.configs.zeta]
+type = "shell"
+command = "true"
+env = { PORT = "3000", API_KEY = "dev", LOG = "debug" }
+
+[commands.script.configs.alpha]
+type = "shell"
+command = "true"
+
+[commands.script.configs.mid]
+type = "shell"
+command = "true"
+"#,
+        )
+        .unwrap();
+
+        let saved = config.to_toml_string().unwrap();
+        let position = |text: &str| saved.find(text).unwrap();
+        assert!(position("configs.alpha]") < position("configs.mid]"));
+        assert!(position("configs.mid]") < position("configs.zeta]"));
+        assert!(position("API_KEY") < position("LOG") && position("LOG") < position("PORT"));
+        let reloaded: Config = toml::from_str(&saved).unwrap();
+        assert_eq!(reloaded.to_toml_string().unwrap(), saved);
+    }
+
+    #[test]
+    fn test_saving_keeps_the_comments_and_formatting_of_the_file() {
+        let (mut config, config_path, temp_dir) = setup(Some(
+            r#"# Commands of the project
+[commands.run]
+default = "dev"   # what `rx run` starts
+
+[commands.run.configs.dev]
+type = "cargo"
+command = 'run'
+env = { RUST_LOG = "debug" }  # verbose
+
+# Release build
+[commands.run.configs.release]
+type = "cargo"
+command = "run"
+"#,
+        ));
+
+        config
+            .commands
+            .set_default_config(CommandContext::Run, "release")
+            .unwrap();
+        config
+            .set_path("commands.run.configs.dev.env", "RUST_LOG=info")
+            .unwrap();
+        config
+            .commands
+            .get_or_default_config(CommandContext::Run)
+            .update_config(
+                "watch",
+                ConfigBuilder::new(CommandContext::Run)
+                    .command("watch")
+                    .build()
+                    .unwrap(),
+            );
+        ConfigSource::new(&config_path).save(&config).unwrap();
+
+        let saved = fs::read_to_string(&config_path).unwrap();
+        assert!(saved.starts_with("# Commands of the project\n[commands.run]\n"));
+        assert!(saved.contains("default = \"release\"   # what `rx run` starts\n"));
+        assert!(saved.contains("command = 'run'\n"));
+        assert!(saved.contains("env = { RUST_LOG = \"info\" }  # verbose\n"));
+        assert!(saved.contains("# Release build\n[commands.run.configs.release]\n"));
+        // Entries left alone don't gain the defaults rx fills in, nor the file the contexts
+        assert!(
+            saved.contains("[commands.run.configs.release]\ntype = \"cargo\"\ncommand = \"run\"\n")
+        );
+        assert!(saved.contains("[commands.run.configs.watch]"));
+        assert!(!saved.contains("[commands.lint]"));
+        assert_eq!(Config::load(Some(config_path)).unwrap(), config);
+
+        drop(temp_dir);
+    }
+
+    #[test]
+    fn test_renaming_a_config_changes_only_its_key_in_the_file() {
+        let original = r#"# Commands of the project
+[commands.run]
+default = "dev"
+
+# The API server
+[commands.run.configs.server]   # on port 3000
+type = "cargo"
+command = "run"
+env = { PORT = "3000" }  # inline
+
+[commands.run.configs.dev]
+type = "cargo"
+command = "watch"
+"#;
+        let (mut config, config_path, temp_dir) = setup(Some(original));
+
+        config
+            .rename_config(CommandContext::Run, "server", "api")
+            .unwrap();
+        ConfigSource::new(&config_path).save(&config).unwrap();
+
+        let saved = fs::read_to_string(&config_path).unwrap();
+        assert_eq!(saved, original.replace("configs.server]", "configs.api]"));
+        assert_eq!(Config::load(Some(config_path)).unwrap(), config);
+
+        drop(temp_dir);
+    }
+
+    #[test]
+    fn test_lint_fmt_and_coverage_defaults_are_never_written() {
+        let (mut config, _, temp_dir) = setup(Some(
+            "[commands.run]\ndefault = \"dev\"\n\n[commands.run.configs.dev]\n",
+        ));
+
+        assert!(config.commands.lint.is_some());
+        assert!(config.commands.fmt.is_some());
+        assert!(config.commands.coverage.is_some());
+        let saved = config.to_toml_string().unwrap();
+        assert!(!saved.contains("[commands.lint"));
+        assert!(!saved.contains("[commands.fmt"));
+        assert!(!saved.contains("[commands.coverage"));
+
+        config
+            .set_path("commands.lint.configs.default.params", "--all-targets")
+            .unwrap();
+        assert!(config
+            .to_toml_string()
+            .unwrap()
+            .contains("[commands.lint.configs.default]"));
+
+        drop(temp_dir);
+    }
+
+    #[test]
+    fn test_saving_a_change_to_a_minimal_file_changes_one_line() {
+        let original = r#"[commands.run]
+default = "dev"
+
+[commands.run.configs.dev]
+command = "run"
+
+[commands.run.configs.release]
+command = "run"
+params = "--release"
+"#;
+        let (mut config, config_path, temp_dir) = setup(Some(original));
+
+        config
+            .commands
+            .set_default_config(CommandContext::Run, "release")
+            .unwrap();
+        ConfigSource::new(&config_path).save(&config).unwrap();
+
+        let saved = fs::read_to_string(&config_path).unwrap();
+        let changed: Vec<(&str, &str)> = original
+            .lines()
+            .zip(saved.lines())
+            .filter(|(before, after)| before != after)
+            .collect();
+        assert_eq!(saved.lines().count(), original.lines().count());
+        assert_eq!(
+            changed,
+            vec![("default = \"dev\"", "default = \"release\"")]
+        );
+
+        drop(temp_dir);
+    }
+
+    #[test]
+    fn test_default_key_by_directory() {
+        let (mut config, _, temp_dir) = setup(Some(
+            r#"
+[commands.run]
+default = { "crates/server/**" = "server", "crates/*" = "crate", "*" = "app" }
+
+[commands.run.configs.app]
+command = "run"
+
+[commands.run.configs.server]
+command = "run"
+params = "-p server"
+
+[commands.run.configs.crate]
+command = "run"
+"#,
+        ));
+        config.validate().unwrap();
+
+        let root = temp_dir.path();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\"]\n",
+        )
+        .unwrap();
+        fs::create_dir_all(root.join("crates/server/src")).unwrap();
+        fs::write(
+            root.join("crates/server/Cargo.toml"),
+            "[package]\nname = \"server\"\n",
+        )
+        .unwrap();
+        fs::create_dir_all(root.join("crates/cli")).unwrap();
+
+        let key_in = |config: &Config, dir: &str| {
+            Runner::new(config, CommandContext::Run, ShutdownSignal::new())
+                .cwd(&root.join(dir))
+                .resolve_key(None)
+        };
+        assert_eq!(key_in(&config, "").unwrap(), "app");
+        assert_eq!(key_in(&config, "crates/server").unwrap(), "server");
+        assert_eq!(key_in(&config, "crates/server/src").unwrap(), "server");
+        assert_eq!(key_in(&config, "crates/cli").unwrap(), "crate");
+
+        config
+            .rename_config(CommandContext::Run, "server", "api")
+            .unwrap();
+        assert_eq!(key_in(&config, "crates/server").unwrap(), "api");
+        config.commands.run.as_mut().unwrap().remove_config("app");
+        assert!(
+            Runner::new(&config, CommandContext::Run, ShutdownSignal::new())
+                .cwd(root)
+                .resolve_key(None)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_default_falls_back_when_its_config_is_gone() {
+        let (mut config, _, _temp_dir) = setup(Some(
+            r#"
+[commands.run]
+default = "gone"
+
+[commands.run.configs.web]
+command = "run"
+
+[commands.run.configs.api]
+command = "run"
+"#,
+        ));
+        let resolve = |config: &Config| {
+            Runner::new(config, CommandContext::Run, ShutdownSignal::new()).resolve_key(None)
+        };
+        match resolve(&config) {
+            Err(ConfigError::DanglingDefault(label, keys)) => {
+                assert_eq!(label, "run::gone");
+                assert_eq!(keys, ["api", "web"]);
+            }
+            other => panic!("expected a dangling default, got {:?}", other),
+        }
+
+        let run_config = config.commands.run.as_mut().unwrap();
+        run_config.configs.remove("api");
+        assert_eq!(resolve(&config).unwrap(), "web");
+
+        // Removing the default points it at a config that is left
+        let run_config = config.commands.run.as_mut().unwrap();
+        run_config.update_config("api", run_config.configs["web"].clone());
+        run_config.default = "web".into();
+        run_config.remove_config("web");
+        assert_eq!(run_config.default, "api");
+    }
+
+    #[test]
+    fn test_remove_config_checks_references() {
+        let (mut config, _, _temp_dir) = setup(Some(
+            r#"
+[hooks]
+after_each = ["run::dev", "echo run::dev"]
+
+[commands.run.configs.dev]
+command = "run"
+
+[commands.run.configs.release]
+command = "run"
+pre_command = ["dev"]
+
+[commands.script.configs.ci]
+type = "composite"
+steps = ["run::dev", "lint"]
+
+[commands.script.configs.lint]
+command = "cargo clippy"
+"#,
+        ));
+        let references = vec![
+            "run::release pre_command".to_string(),
+            "script::ci steps".to_string(),
+            "hooks.after_each".to_string(),
+        ];
+
+        let err = config
+            .remove_config(CommandContext::Run, "dev", Removal::Safe)
+            .unwrap_err();
+        assert!(err.to_string().contains("run::release pre_command"));
+        assert!(config
+            .commands
+            .run
+            .as_ref()
+            .unwrap()
+            .configs
+            .contains_key("dev"));
+
+        let mut forced = config.clone();
+        assert_eq!(
+            forced
+                .remove_config(CommandContext::Run, "dev", Removal::Force)
+                .unwrap(),
+            references
+        );
+        assert!(forced.commands.run.as_ref().unwrap().configs["release"]
+            .pre_command
+            .contains("dev"));
+
+        assert_eq!(
+            config
+                .remove_config(CommandContext::Run, "dev", Removal::Cascade)
+                .unwrap(),
+            references
+        );
+        let run = config.commands.run.as_ref().unwrap();
+        assert!(!run.configs.contains_key("dev"));
+        assert!(run.configs["release"].pre_command.is_empty());
+        assert_eq!(
+            config.commands.script.as_ref().unwrap().configs["ci"].steps,
+            ["lint"]
+        );
+        assert_eq!(config.hooks.after_each, ["echo run::dev"]);
+
+        assert_eq!(
+            config.remove_config(CommandContext::Script, "lint", Removal::Safe).unwrap_err().to_string(),
+            "'script::lint' is referenced by script::ci steps, use --cascade to remove the references too or --force to keep them"
+        );
+    }
+
+    #[test]
+    fn test_replaced_by_follows_renames_and_removals() {
+        let (mut config, _, _temp_dir) = setup(Some(
+            r#"
+[commands.script]
+default = "ship"
+
+[commands.script.configs.deploy]
+command = "echo deploy"
+
+[commands.script.configs.ship]
+command = "echo ship"
+replaced_by = "deploy"
+"#,
+        ));
+        let ship = &config.commands.script.as_ref().unwrap().configs["ship"];
+        assert!(ship.is_deprecated());
+        config.validate().unwrap();
+
+        config
+            .rename_config(CommandContext::Script, "deploy", "release")
+            .unwrap();
+        let script = config.commands.script.as_ref().unwrap();
+        assert_eq!(
+            script.configs["ship"].replaced_by.as_deref(),
+            Some("release")
+        );
+        assert_eq!(
+            config.references(CommandContext::Script, "release"),
+            ["script::ship replaced_by"]
+        );
+
+        config
+            .remove_config(CommandContext::Script, "release", Removal::Force)
+            .unwrap();
+        assert_eq!(
+            config.validate().unwrap_err().to_string(),
+            "Invalid value for 'commands.script.configs.ship.replaced_by': 'release' is not a key of the script context"
+        );
+    }
+
+    #[test]
+    fn test_tagged_configs_across_contexts() {
+        let (config, _, _temp_dir) = setup(Some(
+            r#"
+[commands.run.configs.default]
+command = "run"
+tags = ["smoke"]
+
+[commands.test.configs.default]
+command = "test"
+tags = ["slow", "smoke"]
+
+[commands.test.configs.unit]
+command = "test --lib"
+tags = ["fast"]
+"#,
+        ));
+        assert_eq!(
+            config.tagged("smoke", None),
+            ["run::default", "test::default"]
+        );
+        assert_eq!(
+            config.tagged("smoke", Some(CommandContext::Test)),
+            ["test::default"]
+        );
+        assert!(config.tagged("db", None).is_empty());
+    }
+
+    #[test]
+    fn test_key_patterns_match_configs_of_the_context() {
+        let (config, _, _temp_dir) = setup(Some(
+            r#"
+[commands.test.configs.unit-config]
+command = "test config"
+
+[commands.test.configs.unit-runner]
+command = "test runner"
+
+[commands.test.configs.integration]
+command = "test --test '*'"
+
+[commands.script.configs.deploy-staging]
+command = "deploy staging"
+
+[commands.script.configs.deploy-prod]
+command = "deploy prod"
+
+[commands.script.configs.deploy-dev]
+command = "deploy dev"
+"#,
+        ));
+        assert_eq!(
+            config.matching(CommandContext::Test, "unit-*"),
+            ["test::unit-config", "test::unit-runner"]
+        );
+        assert_eq!(
+            config.matching(CommandContext::Script, "deploy-{staging,prod}"),
+            ["script::deploy-prod", "script::deploy-staging"]
+        );
+        assert_eq!(
+            config.matching(CommandContext::Script, "deploy-{st{a,u}ging,dev}"),
+            ["script::deploy-dev", "script::deploy-staging"]
+        );
+        assert!(config.matching(CommandContext::Run, "*").is_empty());
+
+        assert!(is_key_pattern("unit-*"));
+        assert!(is_key_pattern("deploy-{staging,prod}"));
+        assert!(!is_key_pattern("deploy-{staging}"));
+        assert!(!is_key_pattern("deploy-staging"));
+        assert!(is_key_pattern("api/"));
+    }
+
+    #[test]
+    fn test_included_files() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let main = temp_dir.path().join("config.toml");
+        fs::write(
+            &main,
+            "include = [\"team/*.toml\"]\n[commands.run]\ndefault = \"default\"\n[commands.run.configs.default]\ncommand_type = \"cargo\"\ncommand = \"run\"\n",
+        )
+        .unwrap();
+        fs::create_dir(temp_dir.path().join("team")).unwrap();
+        let ci = temp_dir.path().join("team/ci.toml");
+        fs::write(
+            &ci,
+            "include = [\"../shared.toml\"]\n[commands.run.configs.ci]\ncommand_type = \"shell\"\ncommand = \"make ci\"\n",
+        )
+        .unwrap();
+        let shared = temp_dir.path().join("shared.toml");
+        fs::write(
+            &shared,
+            "[commands.script.configs.deploy]\ncommand_type = \"shell\"\ncommand = \"./deploy\"\n",
+        )
+        .unwrap();
+
+        let mut config = Config::load(Some(main.clone())).unwrap();
+        assert_eq!(config.included_files(), [ci.clone(), shared.clone()]);
+        assert_eq!(config.origin(CommandContext::Run, "ci"), Some(ci.as_path()));
+        assert_eq!(config.origin(CommandContext::Run, "default"), None);
+        assert!(config.get_path("commands.script.configs.deploy").is_ok());
+        let saved = config.to_toml_string().unwrap();
+        assert!(saved.starts_with("include = [\"team/*.toml\"]"));
+        assert!(!saved.contains("make ci"));
+
+        config
+            .rename_config(CommandContext::Run, "ci", "check")
+            .unwrap();
+        assert_eq!(
+            config.to_toml_string().unwrap_err().to_string(),
+            format!(
+                "'run::ci' is included from {}, change it there",
+                ci.display()
+            )
+        );
+
+        fs::write(&shared, "[commands.run.configs.ci]\ncommand = \"test\"\n").unwrap();
+        assert_eq!(
+            Config::load(Some(main)).unwrap_err().to_string(),
+            format!(
+                "'run::ci' is defined in both {} and {}",
+                ci.display(),
+                shared.display()
+            )
+        );
+    }
+
+    #[test]
+    fn test_params_line_and_argv() {
+        let config: Config = toml::from_str(
+            r#"
+[commands.run.configs.line]
+params = "--features 'serde json' --message=\"a b\" ${input:note:default=two words}"
+
+[commands.run.configs.argv]
+params = ["--features", "serde json", "${workspaceFolder}"]
+"#,
+        )
+        .unwrap();
+        let configs = &config
+            .commands
+            .get_config(CommandContext::Run)
+            .unwrap()
+            .configs;
+
+        let line = &configs["line"].params;
+        assert_eq!(
+            line.words(),
+            [
+                "--features",
+                "serde json",
+                "--message=a b",
+                "${input:note:default=two words}"
+            ]
+        );
+        let argv = &configs["argv"].params;
+        assert_eq!(
+            argv.args(|word| word.replace("${workspaceFolder}", "/my app")),
+            ["--features", "serde json", "/my app"]
+        );
+        assert_eq!(
+            argv.to_string(),
+            "--features 'serde json' '${workspaceFolder}'"
+        );
+        assert_eq!(
+            argv.with_args(&["--release".to_string()]),
+            Params::Argv(vec![
+                "--features".into(),
+                "serde json".into(),
+                "${workspaceFolder}".into(),
+                "--release".into()
+            ])
+        );
+        assert_eq!(
+            Params::from("--bin").with_args(&["my app".to_string()]),
+            Params::from("--bin 'my app'")
+        );
+        let reloaded: Config = toml::from_str(&config.to_toml_string().unwrap()).unwrap();
+        assert_eq!(reloaded, config, "an array stays an array when saved");
+
+        let unterminated: Config =
+            toml::from_str("[commands.run.configs.default]\nparams = \"--features 'a b\"\n")
+                .unwrap();
+        assert_eq!(
+            unterminated.validate().unwrap_err().to_string(),
+            "Invalid value for 'commands.run.configs.default.params': unterminated ' quote"
+        );
+    }
+
+    #[test]
+    fn test_yaml_and_json_configs_load_and_save_like_toml() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let toml_path = temp_dir.path().join(".rx.toml");
+        fs::write(
+            &toml_path,
+            "[commands.script.configs.hello]\ntype = \"shell\"\ncommand = \"echo hi\"\nenv = { GREETING = \"hi\" }\n",
+        )
+        .unwrap();
+        let config = Config::load(Some(toml_path.clone())).unwrap();
+
+        for (name, format) in [
+            (".rx.yaml", ConfigFormat::Yaml),
+            (".rx.json", ConfigFormat::Json),
+        ] {
+            let path = temp_dir.path().join(name);
+            assert_eq!(ConfigFormat::from_path(&path), format);
+            config.save(Some(path.clone())).unwrap();
+            assert_eq!(
+                fs::read_to_string(&path).unwrap(),
+                config.to_string_as(format).unwrap()
+            );
+            assert_eq!(Config::load(Some(path)).unwrap(), config);
+        }
+        assert!(fs::read_to_string(temp_dir.path().join(".rx.yaml"))
+            .unwrap()
+            .contains("command: echo hi"));
+        assert_eq!(ConfigFormat::from_path(&toml_path), ConfigFormat::Toml);
+        assert_eq!("yml".parse::<ConfigFormat>().unwrap(), ConfigFormat::Yaml);
+        assert!("ini".parse::<ConfigFormat>().is_err());
+    }
+
+    #[test]
+    fn test_json_schema_describes_the_config_file() {
+        let schema: serde_json::Value = serde_json::from_str(&Config::json_schema()).unwrap();
+        assert_eq!(schema["$schema"], "http://json-schema.org/draft-07/schema#");
+        assert!(schema["properties"]["commands"].is_object());
+
+        let details = &schema["definitions"]["CommandDetails"]["properties"];
+        assert!(details["type"].is_object(), "fields keep their serde names");
+        assert!(details["depends_on"].is_object());
+        assert_eq!(
+            details["env"]["additionalProperties"]["$ref"], "#/definitions/EnvValue",
+            "env values may be bools, integers or keychain tables, not only strings"
+        );
+        let command_types = serde_json::to_string(&schema["definitions"]["CommandType"]).unwrap();
+        assert!(command_types.contains("composite"));
+    }
+
+    #[test]
+    fn test_single_file_runs_with_its_own_defaults() {
+        let config = Config::default();
+        let details = config.settings.single_file.details("scratch/my file.rs");
+        assert_eq!(details.command_type, CommandType::Shell);
+        assert_eq!(
+            details.command.to_string(),
+            "cargo +nightly -Zscript 'scratch/my file.rs'"
+        );
+        assert!(!SingleFile::applies("missing.rs"));
+
+        let config: Config = toml::from_str(
+            "[settings.single_file]\ncommand = \"rust-script ${file}\"\nenv = { RUST_LOG = \"debug\" }\n",
+        )
+        .unwrap();
+        let details = config.settings.single_file.details("demo.rs");
+        assert_eq!(details.command.to_string(), "rust-script demo.rs");
+        assert_eq!(details.env["RUST_LOG"], "debug");
+
+        let config: Config =
+            toml::from_str("[settings.single_file]\ncommand = \"rust-script\"\n").unwrap();
+        assert_eq!(
+            config.validate().unwrap_err().to_string(),
+            "Invalid value for 'settings.single_file.command': has no ${file} standing for the file to run"
+        );
+    }
+
+    #[test]
+    fn test_default_config_path() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let path = temp_dir.path().join("rx.toml");
+
+        std::env::set_var(CONFIG_PATH_ENV, &path);
+        assert_eq!(default_config_path(), Some(path));
+
+        std::env::set_var(CONFIG_PATH_ENV, "");
+        let default = default_config_path().expect("the tests run with a home directory");
+        assert!(default.ends_with("config.toml"));
+        assert!(default
+            .components()
+            .any(|component| component.as_os_str() == "cargo_runner"));
+        std::env::remove_var(CONFIG_PATH_ENV);
+    }
+
+    #[test]
+    fn test_builders_check_placeholders_and_default_keys() {
+        let details = CommandDetailsBuilder::new(CommandContext::Script)
+            .command("./deploy.sh ${input:region:default=eu}")
+            .env_var("TARGET", "$${HOME}/out")
+            .tag("ci")
+            .depends_on("db")
+            .target_dir("target/${key}")
+            .build()
+            .unwrap();
+        assert_eq!(details.tags, ["ci"]);
+        assert_eq!(details.depends_on, ["db"]);
+        assert_eq!(details.target_dir.as_deref(), Some("target/${key}"));
+
+        for (builder, field) in [
+            (
+                ConfigBuilder::new(CommandContext::Build).params("--features ${"),
+                "params",
+            ),
+            (
+                ConfigBuilder::new(CommandContext::Build).command("build ${}"),
+                "command",
+            ),
+            (
+                ConfigBuilder::new(CommandContext::Build).env_var("OUT", "${a${b}}"),
+                "env.OUT",
+            ),
+            (
+                ConfigBuilder::new(CommandContext::Build).working_directory("${input:}"),
+                "working_directory",
+            ),
+        ] {
+            match builder.build() {
+                Err(ConfigError::InvalidValue(path, _)) => assert_eq!(path, field),
+                other => panic!("expected an invalid {}, got {:?}", field, other),
+            }
+        }
+
+        let tests = CommandConfigBuilder::new(CommandContext::Test)
+            .default_key("unit")
+            .config("unit", details.clone())
+            .build()
+            .unwrap();
+        assert!(tests.default.refers_to("unit"));
+        assert!(matches!(
+            CommandConfigBuilder::new(CommandContext::Test)
+                .config("unit", details.clone())
+                .build(),
+            Err(ConfigError::ConfigKeyNotFound(key)) if key == "test::default"
+        ));
+        assert!(matches!(
+            CommandConfigBuilder::new(CommandContext::Build)
+                .config("default", details)
+                .runner(rx_core::models::config::TestRunner::Nextest)
+                .build(),
+            Err(ConfigError::InvalidValue(..))
+        ));
+    }
+
+    #[test]
+    fn test_two_config_sources_load_and_merge_in_one_process() {
+        let temp_dir = TempDir::new().unwrap();
+        let ours = ConfigSource::new(temp_dir.path().join("ours.toml"));
+        let theirs = ConfigSource::new(temp_dir.path().join("theirs.yaml"));
+        fs::write(
+            ours.path(),
+            r#"
+            [commands.build]
+            default = "release"
+            [commands.build.configs.release]
+            command = "build"
+            params = "--release"
+            "#,
+        )
+        .unwrap();
+        fs::write(
+            theirs.path(),
+            r#"
+commands:
+  build:
+    default: release
+    configs:
+      release: { command: build, params: --release --locked }
+      docs: { command: doc }
+  script:
+    default: deploy
+    configs:
+      deploy: { type: shell, command: ./deploy.sh }
+"#,
+        )
+        .unwrap();
+
+        let mut config = ours.load().unwrap();
+        let other = theirs.load().unwrap();
+        assert!(matches!(
+            config.clone().merge(&other, false),
+            Err(ConfigError::MergeConflict(label)) if label == "build::release"
+        ));
+        assert_eq!(
+            config.merge(&other, true).unwrap(),
+            ["build::docs", "build::release", "script::deploy"]
+        );
+        assert_eq!(config.merge(&other, false).unwrap(), Vec::<String>::new());
+        let scripts = config.commands.get_config(CommandContext::Script).unwrap();
+        assert!(scripts.default.refers_to("deploy"));
+        config.validate().unwrap();
+
+        ours.save(&config).unwrap();
+        assert_eq!(ours.load().unwrap(), config);
+        assert!(theirs.load().unwrap().merge(&config, false).is_ok());
+    }
+
+    #[test]
+    fn test_save_explains_why_the_file_cannot_be_written() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = Config::default();
+
+        let nested = ConfigSource::new(temp_dir.path().join("missing/dir/rx.toml"));
+        match nested.save(&config).unwrap_err().downcast::<ConfigError>() {
+            Ok(err) => assert!(matches!(*err, ConfigError::MissingConfigDir(_))),
+            Err(err) => panic!("expected a missing directory, got {}", err),
+        }
+        nested.clone().create_dirs(true).save(&config).unwrap();
+        assert_eq!(nested.load().unwrap(), config);
+
+        let read_only = temp_dir.path().join("read-only.toml");
+        fs::write(&read_only, "").unwrap();
+        let mut permissions = fs::metadata(&read_only).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&read_only, permissions).unwrap();
+        let err = config.save(Some(read_only.clone())).unwrap_err();
+        assert!(err.to_string().contains("read-only"), "{}", err);
+        assert_eq!(fs::read_to_string(&read_only).unwrap(), "");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let target = temp_dir.path().join("dotfiles.toml");
+            fs::write(&target, "").unwrap();
+            fs::set_permissions(&target, fs::Permissions::from_mode(0o600)).unwrap();
+            let link = temp_dir.path().join("linked.toml");
+            std::os::unix::fs::symlink(&target, &link).unwrap();
+            config.save(Some(link.clone())).unwrap();
+            assert!(fs::symlink_metadata(&link)
+                .unwrap()
+                .file_type()
+                .is_symlink());
+            let mode = fs::metadata(&target).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+            assert_eq!(Config::load(Some(target)).unwrap(), config);
+        }
+    }
+}
@@ -0,0 +1,67 @@
+#[cfg(test)]
+mod tests {
+    use rx_core::{
+        builders::config::ConfigBuilder,
+        executor::{runner::Runner, shutdown::ShutdownSignal},
+        models::config::{CommandContext, Config, CoverageFormat, CoverageOptions},
+    };
+
+    #[test]
+    fn test_coverage_options_become_llvm_cov_flags() {
+        let mut config = Config::default();
+        let coverage = config
+            .commands
+            .get_or_default_config(CommandContext::Coverage);
+        coverage.update_config(
+            "report",
+            ConfigBuilder::new(CommandContext::Coverage)
+                .params("--workspace --html -- --nocapture")
+                .build()
+                .unwrap(),
+        );
+        coverage.coverage = Some(CoverageOptions {
+            format: CoverageFormat::Lcov,
+            output: Some("lcov.info".into()),
+            open: false,
+            fail_under: Some(80),
+        });
+
+        let argv = |config: &Config, key: &str| {
+            let runner = Runner::new(config, CommandContext::Coverage, ShutdownSignal::new());
+            let command = runner
+                .build_command(runner.details(key).unwrap(), &[])
+                .unwrap();
+            command
+                .get_args()
+                .map(|arg| arg.to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        assert_eq!(
+            argv(&config, "default"),
+            "llvm-cov --lcov --output-path lcov.info --fail-under-lines 80"
+        );
+        // The report the params ask for wins
+        assert_eq!(
+            argv(&config, "report"),
+            "llvm-cov --workspace --html --fail-under-lines 80 -- --nocapture"
+        );
+
+        let options = |format, open, fail_under| CoverageOptions {
+            format,
+            output: None,
+            open,
+            fail_under,
+        };
+        assert!(options(CoverageFormat::Html, true, Some(100))
+            .check()
+            .is_ok());
+        assert!(options(CoverageFormat::Text, false, Some(101))
+            .check()
+            .is_err());
+        assert!(options(CoverageFormat::Lcov, true, None).check().is_err());
+        config.commands.coverage.as_mut().unwrap().coverage =
+            Some(options(CoverageFormat::Text, true, None));
+        assert!(config.validate().is_err());
+    }
+}
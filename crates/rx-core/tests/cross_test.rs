@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod tests {
+    use rx_core::{
+        executor::cross::translate, helpers::host_triple, models::config::CrossRunner,
+        session::Session,
+    };
+
+    fn argv(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_cross_runners_take_over_foreign_targets() {
+        let session = Session::new();
+        let build = argv("cargo build --release --target aarch64-unknown-linux-gnu");
+        assert_eq!(
+            translate(&session, &build, CrossRunner::Cross),
+            Some(argv(
+                "cross build --release --target aarch64-unknown-linux-gnu"
+            ))
+        );
+        assert_eq!(
+            translate(&session, &build, CrossRunner::Zigbuild),
+            Some(argv(
+                "cargo zigbuild --release --target aarch64-unknown-linux-gnu"
+            ))
+        );
+        assert_eq!(translate(&session, &build, CrossRunner::Native), None);
+
+        let test = argv("cargo test --target=aarch64-unknown-linux-gnu");
+        assert_eq!(
+            translate(&session, &test, CrossRunner::Cross),
+            Some(argv("cross test --target=aarch64-unknown-linux-gnu"))
+        );
+        assert_eq!(
+            translate(&session, &test, CrossRunner::Zigbuild),
+            None,
+            "zigbuild only builds"
+        );
+
+        assert_eq!(
+            translate(&session, &argv("cargo build"), CrossRunner::Cross),
+            None
+        );
+        let host = argv(&format!("cargo build --target {}", host_triple(&session)));
+        assert_eq!(translate(&session, &host, CrossRunner::Cross), None);
+    }
+}
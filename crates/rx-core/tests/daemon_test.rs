@@ -0,0 +1,230 @@
+#[cfg(test)]
+mod tests {
+    use rx_core::{
+        builders::config::ConfigBuilder,
+        daemon::{
+            list_configs, same_token, ClientEvent, DaemonEvent, DaemonRequest, RemoteAddress,
+            RunRequest,
+        },
+        executor::output::OutputStream,
+        models::config::{CommandContext, Config},
+        watch::FileWatcher,
+    };
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_requests_and_config_listing() {
+        let request = DaemonRequest::from_json(
+            r#"{"request":"run","context":"test","key":"unit","env":{"RUST_LOG":"debug"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            request,
+            DaemonRequest::Run(RunRequest {
+                context: CommandContext::Test,
+                key: Some("unit".into()),
+                args: Vec::new(),
+                env: [("RUST_LOG".to_string(), "debug".to_string())].into(),
+                inputs: Default::default(),
+                cwd: None,
+                no_cache: false,
+                package: None,
+                fix: false,
+            })
+        );
+        assert_eq!(
+            DaemonRequest::from_json(r#"{"request":"kill"}"#).unwrap(),
+            DaemonRequest::Kill { label: None }
+        );
+        assert!(DaemonRequest::from_json(r#"{"request":"restart"}"#).is_err());
+
+        let mut config = Config::default();
+        let tests = config.commands.get_or_default_config(CommandContext::Test);
+        for key in ["unit", "default"] {
+            let details = ConfigBuilder::new(CommandContext::Test).build().unwrap();
+            tests.update_config(key, details);
+        }
+        let unit = ConfigBuilder::new(CommandContext::Test)
+            .description("Unit tests only")
+            .build()
+            .unwrap();
+        tests.update_config("unit", unit);
+
+        let configs = DaemonEvent::Configs {
+            configs: list_configs(&config, Some(CommandContext::Test)),
+        };
+        assert_eq!(
+            configs.to_json(),
+            r#"{"event":"configs","configs":[{"label":"test::default","context":"test","key":"default","type":"cargo","default":true},{"label":"test::unit","context":"test","key":"unit","type":"cargo","default":false,"description":"Unit tests only"}]}"#
+        );
+    }
+
+    #[test]
+    fn test_file_watcher_notices_changes() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let path = temp_dir.path().join("config.toml");
+        std::fs::write(&path, "[commands]\n").unwrap();
+
+        let mut watcher = FileWatcher::new([path.clone()]);
+        assert!(!watcher.changed());
+        std::fs::write(&path, "[commands]\n[settings]\n").unwrap();
+        assert!(watcher.changed());
+        assert!(!watcher.changed(), "a change is reported once");
+        std::fs::remove_file(&path).unwrap();
+        assert!(watcher.changed());
+    }
+
+    #[test]
+    fn test_remote_addresses_and_tokens() {
+        let address = |raw: &str| raw.parse::<RemoteAddress>();
+        assert_eq!(
+            address("devbox:7879").unwrap(),
+            RemoteAddress {
+                host: "devbox".into(),
+                port: 7879,
+                tls: true,
+            }
+        );
+        assert!(address("tls://devbox:7879").unwrap().tls);
+        assert!(!address("tcp://localhost:7879").unwrap().tls);
+        assert_eq!(address("[::1]:7879").unwrap().host, "::1");
+        assert!(address("devbox").is_err());
+        assert!(address(":7879").is_err());
+        assert!(address("devbox:port").is_err());
+        assert!(address("http://devbox:7879").is_err());
+
+        assert!(same_token("s3cret", "s3cret"));
+        assert!(!same_token("s3cret", "s3creT"));
+        assert!(!same_token("s3cret", "s3cre"));
+        assert!(!same_token("s3cret", ""));
+
+        assert_eq!(
+            DaemonRequest::from_json(r#"{"request":"auth","token":"s3cret"}"#).unwrap(),
+            DaemonRequest::Auth {
+                token: "s3cret".into()
+            }
+        );
+        assert_eq!(
+            DaemonEvent::Authenticated.to_json(),
+            r#"{"event":"authenticated"}"#
+        );
+    }
+
+    #[test]
+    fn test_client_events() {
+        let event = |json: &str| ClientEvent::from_json(json).unwrap();
+        assert_eq!(
+            event(r#"{"event":"started","label":"run::app"}"#),
+            ClientEvent::Started {
+                label: "run::app".into()
+            }
+        );
+        assert_eq!(
+            event(r#"{"event":"output_chunk","label":"run::app","stream":"stderr","data":"hi"}"#),
+            ClientEvent::OutputChunk {
+                stream: OutputStream::Stderr,
+                data: "hi".into()
+            }
+        );
+        assert_eq!(
+            event(
+                r#"{"event":"finished","label":"run::app","success":false,"exit_code":3,"duration_ms":5}"#
+            ),
+            ClientEvent::Finished {
+                exit_code: 3,
+                error: None
+            }
+        );
+        assert_eq!(
+            event(r#"{"event":"step_started","label":"run::app"}"#),
+            ClientEvent::Other
+        );
+        assert!(ClientEvent::from_json("not json").is_err());
+    }
+
+    #[cfg(all(unix, feature = "tls"))]
+    #[test]
+    fn test_tls_round_trip() {
+        use std::io::{BufRead, BufReader, Write};
+
+        use openssl::{
+            asn1::Asn1Time,
+            hash::MessageDigest,
+            pkey::PKey,
+            rsa::Rsa,
+            x509::{extension::SubjectAlternativeName, X509Name, X509},
+        };
+        use rx_core::{session::Session, tls};
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+
+        let session = Session::new();
+        let dir = TempDir::new().unwrap();
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let mut name = X509Name::builder().unwrap();
+        name.append_entry_by_text("CN", "localhost").unwrap();
+        let name = name.build();
+        let mut cert = X509::builder().unwrap();
+        cert.set_version(2).unwrap();
+        cert.set_subject_name(&name).unwrap();
+        cert.set_issuer_name(&name).unwrap();
+        cert.set_pubkey(&key).unwrap();
+        cert.set_not_before(&Asn1Time::days_from_now(0).unwrap())
+            .unwrap();
+        cert.set_not_after(&Asn1Time::days_from_now(1).unwrap())
+            .unwrap();
+        let san = SubjectAlternativeName::new()
+            .dns("localhost")
+            .build(&cert.x509v3_context(None, None))
+            .unwrap();
+        cert.append_extension(san).unwrap();
+        cert.sign(&key, MessageDigest::sha256()).unwrap();
+        let cert_path = dir.path().join("cert.pem");
+        let key_path = dir.path().join("key.pem");
+        std::fs::write(&cert_path, cert.build().to_pem().unwrap()).unwrap();
+        std::fs::write(&key_path, key.private_key_to_pem_pkcs8().unwrap()).unwrap();
+
+        let acceptor = tls::acceptor(&cert_path, &key_path).unwrap();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        listener.set_nonblocking(true).unwrap();
+
+        let client = std::thread::spawn(move || {
+            let tcp = std::net::TcpStream::connect(("127.0.0.1", port)).unwrap();
+            let mut stream = tls::connect("localhost", tcp, Some(&cert_path)).unwrap();
+            stream.write_all(b"ping\n").unwrap();
+            let mut line = String::new();
+            BufReader::new(stream).read_line(&mut line).unwrap();
+            line
+        });
+        session.block_on(async {
+            let listener = tokio::net::TcpListener::from_std(listener).unwrap();
+            let (tcp, _) = listener.accept().await.unwrap();
+            let stream = tls::accept(&acceptor, tcp).await.unwrap();
+            let (reader, mut writer) = tokio::io::split(stream);
+            let mut lines = tokio::io::BufReader::new(reader).lines();
+            let line = lines.next_line().await.unwrap().unwrap();
+            writer
+                .write_all(format!("{} pong\n", line).as_bytes())
+                .await
+                .unwrap();
+            writer.flush().await.unwrap();
+        });
+        assert_eq!(client.join().unwrap(), "ping pong\n");
+
+        // A client that doesn't trust the certificate is turned away
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let client = std::thread::spawn(move || {
+            let tcp = std::net::TcpStream::connect(("127.0.0.1", port)).unwrap();
+            tls::connect("localhost", tcp, None).map(|_| ())
+        });
+        let (tcp, _) = listener.accept().unwrap();
+        tcp.set_nonblocking(true).unwrap();
+        let accepted = session.block_on(async {
+            let tcp = tokio::net::TcpStream::from_std(tcp).unwrap();
+            tls::accept(&acceptor, tcp).await.map(|_| ())
+        });
+        assert!(accepted.is_err());
+        assert!(client.join().unwrap().is_err());
+    }
+}
@@ -0,0 +1,31 @@
+#[cfg(test)]
+mod tests {
+    use rx_core::{
+        diff::unified_diff,
+        models::config::{CommandContext, Config},
+    };
+
+    #[test]
+    fn test_unified_diff_of_config_change() {
+        let mut config = Config::default();
+        let current = config.to_toml_string().unwrap();
+
+        let details = config.commands.run.as_ref().unwrap().configs["default"].clone();
+        let run_config = config.commands.get_or_default_config(CommandContext::Run);
+        run_config.update_config("release", details);
+        run_config.default = "release".into();
+
+        let diff = unified_diff(&current, &config.to_toml_string().unwrap(), "config.toml");
+
+        assert!(diff.starts_with("--- a/config.toml\n+++ b/config.toml\n"));
+        assert!(diff.contains("-default = \"default\"\n+default = \"release\""));
+        assert!(diff.contains("+[commands.run.configs.release]"));
+    }
+
+    #[test]
+    fn test_unified_diff_is_empty_without_changes() {
+        let current = Config::default().to_toml_string().unwrap();
+
+        assert!(unified_diff(&current, &current, "config.toml").is_empty());
+    }
+}
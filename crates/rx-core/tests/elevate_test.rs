@@ -0,0 +1,74 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rx_core::{
+        executor::{runner::Runner, shutdown::ShutdownSignal},
+        models::config::{CommandContext, Config},
+    };
+
+    #[cfg(unix)]
+    #[test]
+    fn test_elevated_commands_keep_their_env() {
+        use rx_core::executor::elevate::wrap_with;
+
+        let argv = vec!["cargo".to_string(), "run".to_string()];
+        let env = HashMap::from([("PORT".to_string(), "80".to_string())]);
+        assert_eq!(
+            wrap_with("doas", argv.clone(), Some("www"), &env),
+            ["doas", "-u", "www", "env", "PORT=80", "cargo", "run"]
+        );
+        assert_eq!(
+            wrap_with("sudo", argv, None, &HashMap::new()),
+            ["sudo", "cargo", "run"]
+        );
+    }
+
+    #[test]
+    fn test_dry_run_shows_the_command_lines() {
+        let config: Config = toml::from_str(
+            r#"
+            [commands.script]
+            default = "serve"
+
+            [commands.script.configs.serve]
+            type = "shell"
+            command = "serve --port 80"
+            env = { API_TOKEN = "secret" }
+            pre_command = ["prep"]
+
+            [commands.script.configs.prep]
+            type = "shell"
+            command = "echo prep"
+            "#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+
+        let lines = Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .dry_run(None, &["--verbose".to_string()])
+            .unwrap();
+        let labels: Vec<&str> = lines.iter().map(|(label, _)| label.as_str()).collect();
+        assert_eq!(labels, ["script::prep", "script::serve"]);
+        assert!(lines[1].1.ends_with("'serve --port 80 --verbose'"));
+
+        let elevated: Config = toml::from_str(
+            r#"
+            [commands.script]
+            default = "serve"
+
+            [commands.script.configs.serve]
+            type = "shell"
+            command = "serve"
+            remote = "deploy@example.com"
+            run_as = "www"
+            "#,
+        )
+        .unwrap();
+        assert!(elevated
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("it can't be elevated"));
+    }
+}
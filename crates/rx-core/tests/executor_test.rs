@@ -0,0 +1,2694 @@
+#[cfg(test)]
+mod tests {
+    use rx_core::{
+        builders::config::ConfigBuilder,
+        daemon::list_configs,
+        env::EnvSource,
+        errors::{ConfigError, ExecutionError},
+        events::RunEvent,
+        executor::{
+            composite::StepEvent,
+            debug::{build_plan, parse_executables},
+            direnv,
+            handle::{RunHandle, RunOutput},
+            hooks::HookEntry,
+            nextest,
+            output::{OutputObserver, OutputStream},
+            queue::RunQueue,
+            registry::CommandExecutor,
+            runner::{Invocation, Runner, StdinSource},
+            shutdown::ShutdownSignal,
+            vars::PlaceholderSource,
+        },
+        interpolation::{InterpolationContext, UnknownPlaceholders},
+        models::config::{
+            ArgType, BuiltinCondition, CommandContext, CommandDetails, CommandType, Condition,
+            Config, ContainerConfig, Limits, NextestOptions, OnFailure, PreCommandPolicy, Sandbox,
+        },
+        ports,
+        store::Store,
+        timings::{Timings, COMMANDS, PREPARE},
+    };
+    use std::{
+        os::unix::process::ExitStatusExt,
+        process::{Command, ExitStatus},
+        sync::{Arc, Mutex},
+        thread,
+        time::{Duration, Instant},
+    };
+    use tempfile::TempDir;
+
+    /// Creates a [Config] with a single shell script under `key` in the script context
+    fn script_config(key: &str, command: &str, grace_period: Option<u64>) -> Config {
+        let mut config = Config::default();
+        let mut details = ConfigBuilder::new(CommandContext::Script)
+            .command_type(CommandType::Shell)
+            .command(command)
+            .working_directory("")
+            .build()
+            .unwrap();
+        details.shutdown_grace_period = grace_period;
+
+        config
+            .commands
+            .get_or_default_config(CommandContext::Script)
+            .update_config(key, details);
+        config
+    }
+
+    #[test]
+    fn test_pre_commands_run_before_the_command() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let log = temp_dir.path().join("log.txt");
+        let log = log.to_string_lossy();
+
+        let mut config = script_config("prepare", &format!("echo prepare >> {}", log), None);
+        let main = ConfigBuilder::new(CommandContext::Script)
+            .command_type(CommandType::Shell)
+            .command(&format!("echo main >> {}", log))
+            .working_directory("")
+            .pre_command(["prepare".to_string()].into_iter().collect())
+            .build()
+            .unwrap();
+        config
+            .commands
+            .get_or_default_config(CommandContext::Script)
+            .update_config("main", main);
+
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new());
+        let status = runner.run(Some("main"), &[]).expect("run should succeed");
+
+        assert!(status.success());
+        let output = std::fs::read_to_string(log.as_ref()).unwrap();
+        assert_eq!(output, "prepare\nmain\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_pre_command_killed_by_a_signal_fails_with_its_exit_code() {
+        let mut config = script_config("prepare", "kill -TERM $$", None);
+        let main = ConfigBuilder::new(CommandContext::Script)
+            .command_type(CommandType::Shell)
+            .command("exit 0")
+            .working_directory("")
+            .pre_command(["prepare".to_string()].into_iter().collect())
+            .build()
+            .unwrap();
+        config
+            .commands
+            .get_or_default_config(CommandContext::Script)
+            .update_config("main", main);
+
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new());
+        assert!(matches!(
+            runner.run(Some("main"), &[]),
+            Err(ExecutionError::CommandFailed(label, Some(143))) if label == "script::prepare"
+        ));
+    }
+
+    #[test]
+    fn test_success_exit_codes_let_pre_command_chain_continue() {
+        let mut config = script_config("lint", "exit 2", None);
+        let main = ConfigBuilder::new(CommandContext::Script)
+            .command_type(CommandType::Shell)
+            .command("exit 0")
+            .working_directory("")
+            .pre_command(["lint".to_string()].into_iter().collect())
+            .build()
+            .unwrap();
+        let script_config = config
+            .commands
+            .get_or_default_config(CommandContext::Script);
+        script_config.update_config("main", main);
+
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new());
+        assert!(
+            matches!(
+                runner.run(Some("main"), &[]),
+                Err(ExecutionError::CommandFailed(_, Some(2)))
+            ),
+            "exit code 2 is a failure by default"
+        );
+
+        let script_config = config
+            .commands
+            .get_or_default_config(CommandContext::Script);
+        let lint = script_config.configs.get_mut("lint").unwrap();
+        lint.success_exit_codes = Some(vec![0, 2]);
+
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new());
+        let status = runner
+            .run(Some("main"), &[])
+            .expect("chain should continue");
+        assert!(status.success());
+    }
+
+    #[test]
+    fn test_pre_command_policy_decides_whether_the_config_runs() {
+        let mut config = script_config("warm", "exit 4", None);
+        let main = ConfigBuilder::new(CommandContext::Script)
+            .command_type(CommandType::Shell)
+            .command("exit 0")
+            .working_directory("")
+            .pre_command(["warm".to_string()].into_iter().collect())
+            .build()
+            .unwrap();
+        config
+            .commands
+            .get_or_default_config(CommandContext::Script)
+            .update_config("main", main);
+        let set_policy = |config: &mut Config, policy: PreCommandPolicy| {
+            config
+                .commands
+                .get_or_default_config(CommandContext::Script)
+                .configs
+                .get_mut("main")
+                .unwrap()
+                .pre_command_policy = policy;
+        };
+
+        set_policy(&mut config, PreCommandPolicy::Continue);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let observed = events.clone();
+        let status = Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .observe_steps(Arc::new(move |event| {
+                observed.lock().unwrap().push(event.clone())
+            }))
+            .run(Some("main"), &[])
+            .unwrap();
+        assert!(status.success());
+        assert!(events
+            .lock()
+            .unwrap()
+            .contains(&StepEvent::PreCommandIgnored(
+                "script::warm".to_string(),
+                "'script::warm' failed with exit code 4".to_string()
+            )));
+
+        // Without anyone to ask the config is aborted
+        set_policy(&mut config, PreCommandPolicy::Prompt);
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new());
+        assert!(matches!(
+            runner.run(Some("main"), &[]),
+            Err(ExecutionError::CommandFailed(label, Some(4))) if label == "script::warm"
+        ));
+        let asked = Arc::new(Mutex::new(Vec::new()));
+        let answered = asked.clone();
+        let status = Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .on_pre_command_failure(Arc::new(move |label, _| {
+                answered.lock().unwrap().push(label.to_string());
+                true
+            }))
+            .run(Some("main"), &[])
+            .unwrap();
+        assert!(status.success());
+        assert_eq!(*asked.lock().unwrap(), ["script::warm"]);
+
+        set_policy(&mut config, PreCommandPolicy::AbortOnFailure);
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new());
+        assert!(runner.run(Some("main"), &[]).is_err());
+    }
+
+    #[test]
+    fn test_global_hooks_wrap_the_run() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let log = temp_dir.path().join("log.txt");
+        let log = log.to_string_lossy();
+
+        let mut config = script_config("fails", &format!("echo main >> {}; exit 4", log), None);
+        config.hooks.before_each = vec![format!("echo before $RX_KEY >> {}", log)];
+        config.hooks.after_each = vec![format!("echo after $RX_EXIT_CODE >> {}", log)];
+        config.hooks.on_success = vec![format!("echo success >> {}", log)];
+        config.hooks.on_failure = vec![format!("echo failure >> {}", log)];
+
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new());
+        let status = runner.run_with_hooks(Some("fails"), &[]).unwrap();
+
+        assert_eq!(status.code(), Some(4));
+        let output = std::fs::read_to_string(log.as_ref()).unwrap();
+        assert_eq!(output, "before fails\nmain\nafter 4\nfailure\n");
+    }
+
+    #[test]
+    fn test_hook_entry_parsing() {
+        assert_eq!(
+            HookEntry::parse("build::release"),
+            HookEntry::Config(CommandContext::Build, "release")
+        );
+        assert_eq!(
+            HookEntry::parse("echo a::b"),
+            HookEntry::Inline("echo a::b")
+        );
+        assert_eq!(
+            HookEntry::parse("notify-send done"),
+            HookEntry::Inline("notify-send done")
+        );
+    }
+
+    #[test]
+    fn test_remote_command_runs_over_ssh() {
+        let mut config = script_config("bench", "", None);
+        let script_config = config
+            .commands
+            .get_or_default_config(CommandContext::Script);
+        let bench = script_config.configs.get_mut("bench").unwrap();
+        bench.command_type = CommandType::Cargo;
+        bench.command = "bench".into();
+        bench.params = "--bench parser".into();
+        bench
+            .env
+            .insert("RUST_LOG".to_string(), "info warn".to_string());
+        bench.remote = Some("dev@server".to_string());
+        bench.remote_dir = Some("~/work/rx".to_string());
+
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new());
+        let details = runner.details("bench").unwrap();
+        let command = runner
+            .build_command(details, &["--quick".to_string()])
+            .unwrap();
+        let args: Vec<_> = command
+            .get_args()
+            .map(|arg| arg.to_string_lossy())
+            .collect();
+
+        assert_eq!(command.get_program(), "ssh");
+        assert_eq!(
+            args,
+            [
+                "dev@server",
+                "cd \"$HOME\"/work/rx && export RUST_LOG='info warn' && cargo bench --bench parser --quick"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_container_command_maps_workspace_and_working_directory() {
+        let mut config = script_config("ci", "cargo test --locked", None);
+        let script_config = config
+            .commands
+            .get_or_default_config(CommandContext::Script);
+        let ci = script_config.configs.get_mut("ci").unwrap();
+        ci.working_directory = "${workspaceFolder}/crates/core".to_string();
+        ci.env.insert("CI".to_string(), "true".to_string());
+        ci.container = Some(ContainerConfig {
+            image: "rust:1.77".to_string(),
+            ..Default::default()
+        });
+
+        let mut interpolation = InterpolationContext::default();
+        interpolation.set("workspaceFolder", "/home/dev/project");
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .interpolation(interpolation);
+        let details = runner.details("ci").unwrap();
+        let command = runner.build_command(details, &[]).unwrap();
+        let args: Vec<_> = command
+            .get_args()
+            .map(|arg| arg.to_string_lossy())
+            .collect();
+
+        assert_eq!(command.get_program(), "docker");
+        let expected = [
+            "-v",
+            "/home/dev/project:/workspace",
+            "-w",
+            "/workspace/crates/core",
+            "-e",
+            "CI=true",
+            "rust:1.77",
+            "sh",
+            "-c",
+            "cargo test --locked",
+        ];
+        assert_eq!(args[args.len() - expected.len()..], expected);
+    }
+
+    #[test]
+    fn test_unknown_placeholders_are_kept_emptied_or_refused() {
+        let mut config = script_config("default", "echo $${HOME} ${key} ${nothing}", None);
+        let mut interpolation = InterpolationContext::default();
+        interpolation.set("key", "default");
+        let command_line = |config: &Config| {
+            let runner = Runner::new(config, CommandContext::Script, ShutdownSignal::new())
+                .interpolation(interpolation.clone());
+            let details = runner.details("default").unwrap();
+            runner.build_command(details, &[]).map(|command| {
+                command
+                    .get_args()
+                    .last()
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+        };
+
+        assert_eq!(
+            command_line(&config).unwrap(),
+            "echo ${HOME} default ${nothing}"
+        );
+
+        config.settings.unknown_placeholders = UnknownPlaceholders::Error;
+        let err = command_line(&config).unwrap_err().to_string();
+        assert!(err.contains("${nothing}"), "{}", err);
+        assert!(!err.contains("${HOME}"), "{}", err);
+
+        let script_config = config
+            .commands
+            .get_or_default_config(CommandContext::Script);
+        script_config
+            .configs
+            .get_mut("default")
+            .unwrap()
+            .unknown_placeholders = Some(UnknownPlaceholders::Empty);
+        assert_eq!(command_line(&config).unwrap(), "echo ${HOME} default ");
+    }
+
+    #[test]
+    fn test_target_dir_gives_each_cargo_config_its_own_build_directory() {
+        let mut config = script_config("default", "", None);
+        let script_config = config
+            .commands
+            .get_or_default_config(CommandContext::Script);
+        let bench = script_config.configs.get_mut("default").unwrap();
+        bench.command_type = CommandType::Cargo;
+        bench.command = "bench".into();
+        bench.working_directory = "${workspaceFolder}".to_string();
+        bench.target_dir = Some("target/${key}".to_string());
+        config.validate().unwrap();
+
+        let mut interpolation = InterpolationContext::default();
+        interpolation.set("workspaceFolder", "/home/dev/project");
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .interpolation(interpolation);
+        let details = runner.target_details("default", runner.details("default").unwrap(), &[]);
+        assert_eq!(
+            details.env["CARGO_TARGET_DIR"],
+            "/home/dev/project/target/default"
+        );
+        assert_eq!(
+            runner.target_dir("default", &details),
+            Some("/home/dev/project/target/default".into())
+        );
+
+        let script_config = config
+            .commands
+            .get_or_default_config(CommandContext::Script);
+        let bench = script_config.configs.get_mut("default").unwrap();
+        bench.command_type = CommandType::Shell;
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("commands.script.configs.default.target_dir"));
+    }
+
+    /// A child ignoring SIGINT must still be killed, with its grandchildren, once the grace
+    /// period is over
+    #[cfg(unix)]
+    #[test]
+    fn test_shutdown_kills_process_group_after_grace_period() {
+        let config = script_config("stubborn", "trap '' INT; sleep 30 & sleep 30", Some(1));
+        let shutdown = ShutdownSignal::new();
+        let runner = Runner::new(&config, CommandContext::Script, shutdown.clone());
+
+        let trigger = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(200));
+            shutdown.trigger();
+        });
+
+        let started = Instant::now();
+        let result = runner.run(Some("stubborn"), &[]);
+        trigger.join().unwrap();
+
+        assert!(matches!(result, Err(ExecutionError::Interrupted(_))));
+        assert!(
+            started.elapsed() < Duration::from_secs(5),
+            "process group should be killed shortly after the grace period"
+        );
+    }
+
+    #[test]
+    fn test_nextest_translates_cargo_test_filters() {
+        let argv = |line: &str| line.split(' ').map(String::from).collect::<Vec<_>>();
+        let options = NextestOptions {
+            partition: Some("count:1/2".to_string()),
+            ..Default::default()
+        };
+
+        let translated = nextest::translate(
+            &argv(
+                "cargo test -p demo --test api tests::test_load -- --exact --skip slow --nocapture",
+            ),
+            &options,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            translated.join(" "),
+            "cargo nextest run -p demo --test api --no-capture \
+             -E (test(=tests::test_load)) - (test(=slow)) --partition count:1/2"
+        );
+
+        assert_eq!(
+            nextest::translate(&argv("cargo test --release config"), &options, None).unwrap(),
+            argv("cargo nextest run --release config --partition count:1/2")
+        );
+        assert!(nextest::translate(&argv("cargo test --doc"), &options, None).is_none());
+        assert!(nextest::translate(&argv("cargo build"), &options, None).is_none());
+    }
+
+    #[test]
+    fn test_debug_build_plan_and_executables() {
+        let argv = |line: &str| line.split(' ').map(String::from).collect::<Vec<_>>();
+
+        let (build, args) =
+            build_plan(&argv("cargo run --bin demo --release -- --port 80")).unwrap();
+        assert_eq!(
+            build,
+            argv("cargo build --bin demo --release --message-format=json-render-diagnostics")
+        );
+        assert_eq!(args, ["--port", "80"]);
+
+        let (build, args) = build_plan(&argv(
+            "cargo test -p demo --test api tests::load -- --exact",
+        ))
+        .unwrap();
+        assert_eq!(
+            build,
+            argv("cargo test --no-run -p demo --test api --message-format=json-render-diagnostics")
+        );
+        assert_eq!(args, ["tests::load", "--exact"]);
+        assert!(build_plan(&argv("cargo fmt")).is_none());
+
+        let messages = r#"{"reason":"compiler-artifact","target":{"kind":["custom-build"]},"executable":"/t/build/build-script-build"}
+{"reason":"compiler-artifact","target":{"kind":["lib"]},"executable":null}
+{"reason":"compiler-artifact","target":{"kind":["bin"]},"executable":"/t/debug/demo"}
+{"reason":"build-finished","success":true}"#;
+        assert_eq!(
+            parse_executables(messages),
+            [std::path::PathBuf::from("/t/debug/demo")]
+        );
+    }
+
+    #[test]
+    fn test_environment_layers_env_file_config_and_overrides() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        std::fs::write(
+            temp_dir.path().join(".env"),
+            "# shared\nexport HOST=localhost\nPORT=3000 # dev\nNAME=\"from file\"\n",
+        )
+        .unwrap();
+
+        let mut config = script_config("serve", "true", None);
+        let details = config.commands.script.as_mut().unwrap();
+        let details = details.configs.get_mut("serve").unwrap();
+        details.working_directory = temp_dir.path().to_string_lossy().to_string();
+        details.env_file = Some(".env".to_string());
+        details.env.insert("PORT".to_string(), "4000".to_string());
+
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .env_overrides(vec![("NAME".to_string(), "cli".to_string())]);
+        let environment = runner
+            .environment(runner.details("serve").unwrap())
+            .unwrap();
+
+        let variables: Vec<_> = environment
+            .iter()
+            .map(|(name, value, source)| format!("{}={} ({})", name, value, source))
+            .collect();
+        assert_eq!(
+            variables,
+            [
+                "HOST=localhost (env_file)",
+                "NAME=cli (cli)",
+                "PORT=4000 (config)"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_env_sets_are_shared_and_overridden_by_the_config_env() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [envsets]
+            db = { DATABASE_URL = "postgres://localhost/app", POOL = 5 }
+            aws = { AWS_REGION = "eu-west-1", POOL = 10 }
+
+            [commands.script]
+            default = "migrate"
+
+            [commands.script.configs.migrate]
+            type = "shell"
+            command = "true"
+            env_sets = ["db", "aws"]
+            env = { AWS_REGION = "us-east-1" }
+            "#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new());
+        let environment = runner
+            .environment(runner.details("migrate").unwrap())
+            .unwrap();
+        let variables: Vec<_> = environment
+            .iter()
+            .map(|(name, value, source)| format!("{}={} ({})", name, value, source))
+            .collect();
+        assert_eq!(
+            variables,
+            [
+                "AWS_REGION=us-east-1 (config)",
+                "DATABASE_URL=postgres://localhost/app (env_sets)",
+                "POOL=10 (env_sets)"
+            ]
+        );
+
+        let details = config.commands.script.as_mut().unwrap();
+        let details = details.configs.get_mut("migrate").unwrap();
+        details.env_sets.push("redis".to_string());
+        assert_eq!(
+            config.validate().unwrap_err().to_string(),
+            "Invalid value for 'commands.script.configs.migrate.env_sets': No env set 'redis', add it to [envsets] or remove it from env_sets"
+        );
+    }
+
+    #[test]
+    fn test_direnv_exports_are_read_and_stay_beneath_the_config_env() {
+        let exported = direnv::parse_export(
+            r#"{"DATABASE_URL": "postgres://localhost/dev", "OLD": null, "DIRENV_DIFF": "eJy"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            exported,
+            [(
+                "DATABASE_URL".to_string(),
+                "postgres://localhost/dev".to_string()
+            )]
+        );
+        assert!(direnv::parse_export("").unwrap().is_empty());
+        assert!(direnv::parse_export("direnv: loading").is_err());
+
+        // Without an .envrc there's nothing to load, direnv installed or not
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let mut config = script_config("serve", "true", None);
+        let details = config.commands.script.as_mut().unwrap();
+        let details = details.configs.get_mut("serve").unwrap();
+        details.working_directory = temp_dir.path().to_string_lossy().to_string();
+        details.direnv = true;
+        details.env.insert("PORT".to_string(), "3000".to_string());
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new());
+        let environment = runner
+            .environment(runner.details("serve").unwrap())
+            .unwrap();
+        let sources: Vec<_> = environment.iter().map(|(_, _, source)| source).collect();
+        assert_eq!(sources, [EnvSource::Config]);
+    }
+
+    #[test]
+    fn test_sourced_files_and_login_shell_wrap_the_command() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        std::fs::write(temp_dir.path().join("init.sh"), "export GREETING=hi\n").unwrap();
+
+        let mut config = script_config("greet", "echo $GREETING > out.txt", None);
+        let details = config.commands.script.as_mut().unwrap();
+        let details = details.configs.get_mut("greet").unwrap();
+        details.working_directory = temp_dir.path().to_string_lossy().to_string();
+        details.source = vec!["init.sh".to_string(), "~/.nvm/nvm.sh".to_string()];
+
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new());
+        assert_eq!(
+            runner.command_line("greet", &[]).unwrap(),
+            r#"sh -c '. ./init.sh && . ~/.nvm/nvm.sh && echo $GREETING > out.txt'"#
+        );
+
+        let details = config.commands.script.as_mut().unwrap();
+        let details = details.configs.get_mut("greet").unwrap();
+        details.source.pop();
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new());
+        runner.run(Some("greet"), &[]).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(temp_dir.path().join("out.txt")).unwrap(),
+            "hi\n"
+        );
+
+        let details = config.commands.script.as_mut().unwrap();
+        let details = details.configs.get_mut("greet").unwrap();
+        details.login_shell = true;
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new());
+        assert!(runner
+            .command_line("greet", &[])
+            .unwrap()
+            .ends_with(r#" -l -c '. ./init.sh && echo $GREETING > out.txt'"#));
+    }
+
+    #[test]
+    fn test_clean_environment_keeps_only_configured_and_allowed_variables() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let mut config = script_config("env", "env > env.txt", None);
+        let details = config.commands.script.as_mut().unwrap();
+        let details = details.configs.get_mut("env").unwrap();
+        details.working_directory = temp_dir.path().to_string_lossy().to_string();
+        details.env.insert("LEVEL".to_string(), "9".to_string());
+
+        let run = |config: &Config| {
+            Runner::new(config, CommandContext::Script, ShutdownSignal::new())
+                .run(Some("env"), &[])
+                .unwrap();
+            std::fs::read_to_string(temp_dir.path().join("env.txt")).unwrap()
+        };
+
+        // cargo sets CARGO_MANIFEST_DIR for the test process
+        let inherited = run(&config);
+        assert!(inherited.contains("CARGO_MANIFEST_DIR="));
+
+        let details = config.commands.script.as_mut().unwrap();
+        details.configs.get_mut("env").unwrap().inherit_env = Some(false);
+        let clean = run(&config);
+        assert!(!clean.contains("CARGO_MANIFEST_DIR="));
+        assert!(clean.lines().any(|line| line == "LEVEL=9"));
+        assert!(clean.lines().any(|line| line.starts_with("PATH=")));
+    }
+
+    #[test]
+    fn test_inline_script_runs_from_temp_file() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let log = temp_dir.path().join("log.txt");
+        let script = format!(
+            "\n    echo \"$0\" > {log}\n    for arg in \"$@\"; do\n      echo \"$arg\" >> {log}\n    done\n",
+            log = log.display()
+        );
+
+        let mut config = script_config("inline", &script, None);
+        let details = config.commands.script.as_mut().unwrap();
+        details.configs.get_mut("inline").unwrap().params = "--from-params".into();
+
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new());
+        let status = runner
+            .run(Some("inline"), &["--from-args".to_string()])
+            .unwrap();
+        assert!(status.success());
+
+        let log = std::fs::read_to_string(log).unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+        assert_eq!(lines[1..], ["--from-params", "--from-args"]);
+        assert!(
+            !std::path::Path::new(lines[0]).exists(),
+            "the script file should be removed after the run"
+        );
+    }
+
+    #[test]
+    fn test_composite_runs_steps_until_one_fails() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let log = temp_dir.path().join("log.txt");
+        let log = log.to_string_lossy();
+
+        let mut config = script_config("first", &format!("echo first >> {}", log), None);
+        let script = config.commands.script.as_mut().unwrap();
+        script.update_config(
+            "fail",
+            CommandDetails {
+                command: "exit 3".into(),
+                ..script.configs["first"].clone()
+            },
+        );
+        script.update_config(
+            "last",
+            CommandDetails {
+                command: format!("echo last >> {}", log).into(),
+                ..script.configs["first"].clone()
+            },
+        );
+        script.update_config(
+            "all",
+            CommandDetails {
+                command_type: CommandType::Composite,
+                steps: vec!["first".into(), "script::fail".into(), "last".into()],
+                ..Default::default()
+            },
+        );
+        script.update_config(
+            "again",
+            CommandDetails {
+                command_type: CommandType::Composite,
+                steps: vec!["all".into()],
+                ..Default::default()
+            },
+        );
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .observe_steps(Arc::new(move |event: &StepEvent| {
+                recorded.lock().unwrap().push(event.clone())
+            }));
+
+        assert!(matches!(
+            runner.run(Some("all"), &[]),
+            Err(ExecutionError::CommandFailed(label, Some(3))) if label == "script::fail"
+        ));
+        assert_eq!(std::fs::read_to_string(log.as_ref()).unwrap(), "first\n");
+
+        let finished: Vec<_> = events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|event| match event {
+                StepEvent::Finished(report) => Some((report.label.clone(), report.success)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            finished,
+            [
+                ("script::first".to_string(), true),
+                ("script::fail".to_string(), false)
+            ]
+        );
+
+        let script = config.commands.script.as_mut().unwrap();
+        script.configs.get_mut("all").unwrap().steps = vec!["again".into()];
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new());
+        assert!(matches!(
+            runner.run(Some("all"), &[]),
+            Err(ExecutionError::StepCycle(label)) if label == "script::all"
+        ));
+    }
+
+    #[test]
+    fn test_missing_working_directory_is_refused_or_created() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let dir = temp_dir.path().join("out/reports");
+        let mut config = script_config("report", "touch report.txt", None);
+        let details = config
+            .commands
+            .get_or_default_config(CommandContext::Script)
+            .configs
+            .get_mut("report")
+            .unwrap();
+        details.working_directory = dir.to_string_lossy().into_owned();
+
+        let err = Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .run(Some("report"), &[])
+            .unwrap_err();
+        assert!(
+            matches!(&err, ExecutionError::WorkingDirectory(path, _) if path == &dir.display().to_string())
+        );
+        assert!(err.to_string().contains("create_working_directory"));
+        assert!(!dir.exists());
+
+        config
+            .commands
+            .get_or_default_config(CommandContext::Script)
+            .configs
+            .get_mut("report")
+            .unwrap()
+            .create_working_directory = true;
+        Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .run(Some("report"), &[])
+            .unwrap();
+        assert!(dir.join("report.txt").is_file());
+    }
+
+    #[test]
+    fn test_timings_end_the_prepare_phase_at_the_first_command() {
+        let config: Config = toml::from_str(
+            r#"
+[commands.script.configs.setup]
+type = "shell"
+command = "sleep 0.05"
+
+[commands.script.configs.serve]
+type = "shell"
+command = "sleep 0.05"
+pre_command = ["setup"]
+"#,
+        )
+        .unwrap();
+        let timings = Timings::since(Instant::now());
+        timings.end("load config");
+        Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .timings(timings.clone())
+            .run(Some("serve"), &[])
+            .unwrap();
+        timings.end(COMMANDS);
+
+        let phases = timings.phases();
+        let names: Vec<&str> = phases.iter().map(|phase| phase.name).collect();
+        assert_eq!(names, ["load config", PREPARE, COMMANDS]);
+        assert!(phases[2].duration >= Duration::from_millis(100));
+        assert_eq!(timings.overhead(), phases[0].duration + phases[1].duration);
+        assert!(timings
+            .report()
+            .lines()
+            .last()
+            .unwrap()
+            .starts_with("total"));
+    }
+
+    #[test]
+    fn test_on_failure_continues_or_runs_a_cleanup_step() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let log = temp_dir.path().join("log.txt");
+        let log = log.to_string_lossy();
+        let mut config: Config = toml::from_str(&format!(
+            r#"
+[commands.script]
+default = "ci"
+
+[commands.script.configs.setup]
+type = "shell"
+command = "echo setup >> {log}"
+
+[commands.script.configs.lint]
+type = "shell"
+command = "exit 1"
+
+[commands.script.configs.test]
+type = "shell"
+command = "exit 2"
+
+[commands.script.configs.teardown]
+type = "shell"
+command = "echo teardown >> {log}"
+
+[commands.script.configs.ci]
+type = "composite"
+steps = ["setup", "lint", "test", "teardown"]
+on_failure = {{ lint = "continue", test = "run:teardown" }}
+"#,
+        ))
+        .unwrap();
+        config.validate().unwrap();
+
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new());
+        assert!(matches!(
+            runner.run(Some("ci"), &[]),
+            Err(ExecutionError::CommandFailed(label, Some(2))) if label == "script::test"
+        ));
+        assert_eq!(
+            std::fs::read_to_string(log.as_ref()).unwrap(),
+            "setup\nteardown\n"
+        );
+
+        let ci = config
+            .commands
+            .get_or_default_config(CommandContext::Script)
+            .configs
+            .get_mut("ci")
+            .unwrap();
+        ci.parallel = true;
+        ci.steps = vec!["lint".into(), "test".into()];
+        ci.on_failure.insert("test".into(), OnFailure::Continue);
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new());
+        assert!(runner.run(Some("ci"), &[]).unwrap().success());
+        assert_eq!(
+            config.references(CommandContext::Script, "teardown"),
+            Vec::<String>::new()
+        );
+
+        let ci = config
+            .commands
+            .get_or_default_config(CommandContext::Script)
+            .configs
+            .get_mut("ci")
+            .unwrap();
+        ci.on_failure
+            .insert("test".into(), "run:missing".parse().unwrap());
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidValue(path, _)) if path == "commands.script.configs.ci.on_failure.test"
+        ));
+        assert!("retry".parse::<OnFailure>().is_err());
+    }
+
+    #[cfg(unix)]
+    #[cfg(unix)]
+    #[test]
+    fn test_depends_on_runs_each_dependency_once_before_the_config() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let log = temp_dir.path().join("log.txt");
+        let log = log.to_string_lossy();
+        let config: Config = toml::from_str(&format!(
+            r#"
+            [commands.script.configs.default]
+            type = "shell"
+            command = "echo release >> {log}"
+            depends_on = ["a", "b"]
+
+            [commands.script.configs.a]
+            type = "shell"
+            command = "echo a >> {log}; sleep 0.5; echo a done >> {log}"
+            depends_on = ["build::default"]
+
+            [commands.script.configs.b]
+            type = "shell"
+            command = "echo b >> {log}; sleep 0.5; echo b done >> {log}"
+            depends_on = ["build::default"]
+
+            [commands.build.configs.default]
+            type = "shell"
+            command = "echo build >> {log}"
+            "#
+        ))
+        .unwrap();
+        config.validate().unwrap();
+
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new()).jobs(2);
+        let plan = runner.plan(None, &[]).unwrap();
+        let children: Vec<_> = plan
+            .children
+            .iter()
+            .map(|node| (node.label.as_str(), node.children.len()))
+            .collect();
+        assert_eq!(children, [("script::a", 1), ("script::b", 1)]);
+        assert!(plan.children[1].children[0].repeated);
+        let labels: Vec<String> = runner
+            .dry_run(None, &[])
+            .unwrap()
+            .into_iter()
+            .map(|(label, _)| label)
+            .collect();
+        assert_eq!(
+            labels,
+            [
+                "build::default",
+                "script::a",
+                "script::b",
+                "script::default"
+            ]
+        );
+
+        runner.run(None, &[]).unwrap();
+        let lines: Vec<String> = std::fs::read_to_string(log.as_ref())
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect();
+        assert_eq!(lines.first().map(String::as_str), Some("build"));
+        assert_eq!(lines.last().map(String::as_str), Some("release"));
+        assert_eq!(lines.len(), 6, "build runs once: {:?}", lines);
+        // a and b don't depend on each other, so both start before either is done
+        assert!(lines[1..3].iter().all(|line| !line.ends_with("done")));
+
+        let mut cyclic = config.clone();
+        cyclic
+            .commands
+            .get_or_default_config(CommandContext::Build)
+            .configs
+            .get_mut("default")
+            .unwrap()
+            .depends_on = vec!["script::default".to_string()];
+        assert!(cyclic
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("configs depend on each other"));
+    }
+
+    #[test]
+    fn test_wait_for_starts_the_next_step_once_a_service_is_ready() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let dir = temp_dir.path().to_string_lossy();
+
+        let mut config = script_config(
+            "server",
+            &format!("sleep 0.3; touch {}/ready; echo listening; sleep 30", dir),
+            None,
+        );
+        let script = config.commands.script.as_mut().unwrap();
+        let server = script.configs["server"].clone();
+        script.configs.get_mut("server").unwrap().wait_for =
+            Some(toml::from_str(&format!("log = \"^listen\"\nfile = \"{}/ready\"", dir)).unwrap());
+        script.update_config(
+            "client",
+            CommandDetails {
+                command: format!("test -f {0}/ready && echo ok > {0}/client", dir).into(),
+                ..server.clone()
+            },
+        );
+        script.update_config(
+            "default",
+            CommandDetails {
+                command_type: CommandType::Composite,
+                steps: vec!["server".into(), "client".into()],
+                ..Default::default()
+            },
+        );
+        script.update_config(
+            "with_server",
+            CommandDetails {
+                pre_command: ["server".to_string()].into_iter().collect(),
+                ..script.configs["client"].clone()
+            },
+        );
+        config.validate().unwrap();
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        let started = Instant::now();
+        Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .observe_steps(Arc::new(move |event: &StepEvent| {
+                recorded.lock().unwrap().push(event.clone())
+            }))
+            .run(Some("default"), &[])
+            .unwrap();
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert!(temp_dir.path().join("client").exists());
+        let order: Vec<String> = events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|event| match event {
+                StepEvent::Started(label) => Some(format!("started {}", label)),
+                StepEvent::Ready(label, _) => Some(format!("ready {}", label)),
+                StepEvent::Finished(report) => {
+                    Some(format!("finished {} {}", report.label, report.success))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            order,
+            [
+                "started script::server",
+                "ready script::server",
+                "started script::client",
+                "finished script::client true",
+                "finished script::server true",
+            ]
+        );
+
+        std::fs::remove_file(temp_dir.path().join("client")).unwrap();
+        std::fs::remove_file(temp_dir.path().join("ready")).unwrap();
+        Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .run(Some("with_server"), &[])
+            .unwrap();
+        assert!(temp_dir.path().join("client").exists());
+
+        let script = config.commands.script.as_mut().unwrap();
+        let wait_for = script
+            .configs
+            .get_mut("server")
+            .unwrap()
+            .wait_for
+            .as_mut()
+            .unwrap();
+        wait_for.log = None;
+        wait_for.file = Some(format!("{}/never", dir));
+        wait_for.timeout = Some(1);
+        assert!(matches!(
+            Runner::new(&config, CommandContext::Script, ShutdownSignal::new()).run(Some("default"), &[]),
+            Err(ExecutionError::NotReady(label, reason))
+                if label == "script::server" && reason.contains("never")
+        ));
+
+        let script = config.commands.script.as_mut().unwrap();
+        script.configs.get_mut("server").unwrap().wait_for = Some(Default::default());
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_guards_skip_configs_and_their_pre_commands() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let log = temp_dir.path().join("log.txt");
+        let log = log.to_string_lossy();
+
+        let guards: CommandDetails = toml::from_str(
+            r#"
+            only_if = "test -n \"$RX_GUARD\""
+            skip_if = { os = "plan9" }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            guards.only_if,
+            Some(Condition::Shell("test -n \"$RX_GUARD\"".into()))
+        );
+
+        let mut config = script_config("prepare", &format!("echo prepare >> {}", log), None);
+        let script = config.commands.script.as_mut().unwrap();
+        let echo = |word: &str| CommandDetails {
+            command: format!("echo {} >> {}", word, log).into(),
+            ..script.configs["prepare"].clone()
+        };
+        let guarded = CommandDetails {
+            pre_command: ["prepare".to_string()].into_iter().collect(),
+            env: [("RX_GUARD".to_string(), "1".to_string())].into(),
+            ..echo("guarded")
+        };
+        let other_os = CommandDetails {
+            only_if: Some(Condition::Builtin(BuiltinCondition {
+                os: Some("plan9".into()),
+                ..Default::default()
+            })),
+            ..echo("other_os")
+        };
+        let has_log = CommandDetails {
+            skip_if: Some(Condition::Builtin(BuiltinCondition {
+                file_exists: Some(log.to_string()),
+                ..Default::default()
+            })),
+            ..echo("has_log")
+        };
+        script.update_config(
+            "guarded",
+            CommandDetails {
+                only_if: guards.only_if,
+                skip_if: guards.skip_if,
+                ..guarded
+            },
+        );
+        script.update_config("other_os", other_os);
+        script.update_config("has_log", has_log);
+
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new());
+        assert!(runner.run(Some("other_os"), &[]).unwrap().success());
+        assert!(runner.run(Some("has_log"), &[]).unwrap().success());
+        assert!(runner.run(Some("guarded"), &[]).unwrap().success());
+        assert!(runner.run(Some("has_log"), &[]).unwrap().success());
+
+        let skip_reason = runner
+            .skip_reason("other_os", runner.details("other_os").unwrap())
+            .unwrap();
+        assert_eq!(
+            skip_reason.as_deref(),
+            Some("only_if os = \"plan9\" does not hold")
+        );
+        assert_eq!(
+            std::fs::read_to_string(log.as_ref()).unwrap(),
+            "has_log\nprepare\nguarded\n"
+        );
+    }
+
+    #[test]
+    fn test_platforms_hide_and_refuse_configs_of_other_oses() {
+        let other = match std::env::consts::OS {
+            "windows" => "linux",
+            _ => "windows",
+        };
+        let mut config = script_config("native", "true", None);
+        let script = config.commands.script.as_mut().unwrap();
+        script.update_config(
+            "foreign",
+            CommandDetails {
+                platforms: vec![other.into()],
+                ..script.configs["native"].clone()
+            },
+        );
+        script.update_config(
+            "all",
+            CommandDetails {
+                command_type: CommandType::Composite,
+                steps: vec!["foreign".into(), "native".into()],
+                ..Default::default()
+            },
+        );
+        script.configs.get_mut("native").unwrap().platforms =
+            vec![std::env::consts::OS.into(), other.into()];
+        script.default = "native".into();
+        config.validate().unwrap();
+
+        let labels: Vec<String> = list_configs(&config, Some(CommandContext::Script))
+            .into_iter()
+            .map(|entry| entry.label)
+            .collect();
+        assert_eq!(labels, ["script::all", "script::native"]);
+
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new());
+        assert!(matches!(
+            runner.run(Some("foreign"), &[]),
+            Err(ExecutionError::Config(ConfigError::UnsupportedPlatform(label, platforms)))
+                if label == "script::foreign" && platforms == [other]
+        ));
+        assert!(runner.run(Some("all"), &[]).unwrap().success());
+        assert_eq!(
+            runner
+                .skip_reason("foreign", runner.details("foreign").unwrap())
+                .unwrap(),
+            Some(format!("not available on {}", std::env::consts::OS))
+        );
+
+        config
+            .commands
+            .script
+            .as_mut()
+            .unwrap()
+            .configs
+            .get_mut("native")
+            .unwrap()
+            .platforms = vec!["plan9".into()];
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidValue(path, _))
+                if path == "commands.script.configs.native.platforms"
+        ));
+    }
+
+    #[test]
+    fn test_typed_args_resolve_from_overrides_or_defaults() {
+        let mut config = script_config(
+            "server",
+            "serve --port ${arg:port} --host ${arg:host}",
+            None,
+        );
+        let script = config.commands.script.as_mut().unwrap();
+        script.default = "server".into();
+        let server = script.configs.get_mut("server").unwrap();
+        server.args = toml::from_str(
+            r#"
+            port = { type = "int", default = 8080 }
+            host = { description = "Address to bind" }
+            "#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new());
+        assert!(matches!(
+            runner.command_line("server", &[]),
+            Err(ExecutionError::Config(ConfigError::MissingArg(name))) if name == "host"
+        ));
+        let given = |values: &[(&str, &str)]| {
+            let values: Vec<(String, String)> = values
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect();
+            Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+                .arg_values("server", &values)
+        };
+        let runner = given(&[("host", "0.0.0.0")]).unwrap();
+        assert_eq!(
+            runner.command_line("server", &[]).unwrap(),
+            "sh -c 'serve --port 8080 --host 0.0.0.0'"
+        );
+        let runner = given(&[("host", "::1"), ("port", "9090")]).unwrap();
+        assert_eq!(
+            runner.command_line("server", &[]).unwrap(),
+            "sh -c 'serve --port 9090 --host ::1'"
+        );
+        assert!(matches!(
+            given(&[("port", "ninety")]),
+            Err(ConfigError::InvalidValue(path, reason))
+                if path == "--arg port" && reason == "'ninety' is not an int"
+        ));
+        assert!(matches!(
+            given(&[("verbose", "true")]),
+            Err(ConfigError::InvalidValue(path, reason))
+                if path == "--arg verbose" && reason == "script::server takes host, port"
+        ));
+
+        let server = config
+            .commands
+            .script
+            .as_mut()
+            .unwrap()
+            .configs
+            .get_mut("server")
+            .unwrap();
+        server.args.get_mut("port").unwrap().arg_type = ArgType::Bool;
+        assert!(config.validate().is_err());
+        let server = config
+            .commands
+            .script
+            .as_mut()
+            .unwrap()
+            .configs
+            .get_mut("server")
+            .unwrap();
+        server.args.remove("port");
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidValue(_, reason))
+                if reason == "'${arg:port}' refers to no entry of its args"
+        ));
+    }
+
+    #[test]
+    fn test_unchanged_inputs_skip_the_run() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let dir = temp_dir.path();
+        std::fs::create_dir(dir.join("src")).unwrap();
+        std::fs::write(dir.join("src/main.rs"), "fn main() {}").unwrap();
+
+        let mut config = script_config("build", "echo built >> log.txt && touch out.txt", None);
+        let build = config
+            .commands
+            .script
+            .as_mut()
+            .unwrap()
+            .configs
+            .get_mut("build")
+            .unwrap();
+        build.working_directory = dir.to_string_lossy().to_string();
+        build.inputs = vec!["src/**".into()];
+        build.outputs = vec!["out.txt".into()];
+
+        let run = |no_cache: bool| {
+            Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+                .store(Store::at(dir.join("store")))
+                .no_cache(no_cache)
+                .run(Some("build"), &[])
+                .unwrap();
+            std::fs::read_to_string(dir.join("log.txt"))
+                .unwrap()
+                .lines()
+                .count()
+        };
+
+        assert_eq!(run(false), 1);
+        assert_eq!(run(false), 1, "nothing changed");
+        std::fs::write(dir.join("src/main.rs"), "fn main() { }").unwrap();
+        assert_eq!(run(false), 2, "an input changed");
+        std::fs::remove_file(dir.join("out.txt")).unwrap();
+        assert_eq!(run(false), 3, "an output is missing");
+        assert_eq!(run(true), 4, "--no-cache");
+        assert_eq!(run(false), 4);
+    }
+
+    #[test]
+    fn test_unechoed_output_and_pre_commands_become_events() {
+        let mut config = script_config("main", "echo main", None);
+        let script = config.commands.script.as_mut().unwrap();
+        script.update_config(
+            "prepare",
+            ConfigBuilder::new(CommandContext::Script)
+                .command_type(CommandType::Shell)
+                .command("echo prepared >&2")
+                .working_directory("")
+                .build()
+                .unwrap(),
+        );
+        script.configs.get_mut("main").unwrap().pre_command = ["prepare".to_string()].into();
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let step_events = events.clone();
+        let output_events = events.clone();
+        Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .echo_output(false)
+            .observe_steps(Arc::new(move |event: &StepEvent| {
+                step_events.lock().unwrap().push(RunEvent::from(event))
+            }))
+            .observe_output(Arc::new(move |label, stream, line| {
+                output_events.lock().unwrap().push(RunEvent::OutputChunk {
+                    label: label.to_string(),
+                    stream,
+                    data: line.to_string(),
+                })
+            }))
+            .run(Some("main"), &[])
+            .unwrap();
+
+        let lines: Vec<String> = events
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|event| match event {
+                RunEvent::PreCommandFinished { label, .. } => {
+                    format!(r#"{{"event":"pre_command_finished","label":"{}"}}"#, label)
+                }
+                event => event.to_json(),
+            })
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                r#"{"event":"pre_command_started","label":"script::prepare"}"#,
+                r#"{"event":"output_chunk","label":"script::prepare","stream":"stderr","data":"prepared"}"#,
+                r#"{"event":"pre_command_finished","label":"script::prepare"}"#,
+                r#"{"event":"output_chunk","label":"script::main","stream":"stdout","data":"main"}"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_git_placeholders_and_branch_guard() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let repo = temp_dir.path().canonicalize().unwrap();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args(["-c", "user.name=rx", "-c", "user.email=rx@example.com"])
+                .args(args)
+                .current_dir(&repo)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        };
+        git(&["init", "--quiet", "--initial-branch", "release/1.0"]);
+        git(&["commit", "--quiet", "--allow-empty", "--message", "initial"]);
+
+        let interpolation = InterpolationContext::from_dir(&repo);
+        assert_eq!(interpolation.get("gitBranch"), Some("release/1.0"));
+        assert_eq!(interpolation.get("gitRoot"), Some(repo.to_str().unwrap()));
+        let sha = interpolation.get("gitShortSha").unwrap();
+        assert_eq!(
+            interpolation.interpolate("app-${gitShortSha}.tar"),
+            format!("app-{}.tar", sha)
+        );
+
+        let config = script_config("deploy", "true", None);
+        let only_on = |branch: &str| CommandDetails {
+            only_if: Some(Condition::Builtin(BuiltinCondition {
+                branch: Some(branch.into()),
+                ..Default::default()
+            })),
+            ..config.commands.script.as_ref().unwrap().configs["deploy"].clone()
+        };
+        let (release, main) = (only_on("release/*"), only_on("main"));
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .interpolation(interpolation);
+        assert_eq!(runner.skip_reason("deploy", &release).unwrap(), None);
+        assert_eq!(
+            runner.skip_reason("deploy", &main).unwrap().as_deref(),
+            Some("only_if branch = \"main\" does not hold")
+        );
+    }
+
+    #[test]
+    fn test_package_placeholders_are_read_on_use_and_cached_by_manifest() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let dir = temp_dir.path().canonicalize().unwrap();
+        let manifest = dir.join("Cargo.toml");
+        std::fs::write(&manifest, "[package]\nname = \"first\"\n").unwrap();
+        std::fs::create_dir(dir.join("src")).unwrap();
+        std::fs::write(dir.join("src/main.rs"), "fn main() {}\n").unwrap();
+        let cache = Store::for_workspace(&dir).unwrap().path("package.toml");
+        let _ = std::fs::remove_file(&cache);
+
+        let interpolation = InterpolationContext::from_dir(&dir);
+        assert_eq!(interpolation.interpolate("echo hi"), "echo hi");
+        assert!(!cache.exists());
+        assert_eq!(
+            interpolation.interpolate("${packageName} ${binaryName}"),
+            "first first"
+        );
+        assert!(cache.exists());
+
+        // Read from the cache while the manifest is unchanged, again once it changed
+        std::fs::write(&manifest, "[package]\nname = \"second\"\n").unwrap();
+        let modified = std::fs::metadata(&cache).unwrap().modified().unwrap();
+        let file = std::fs::File::options()
+            .write(true)
+            .open(&manifest)
+            .unwrap();
+        file.set_modified(modified + Duration::from_secs(60))
+            .unwrap();
+        assert_eq!(
+            InterpolationContext::from_dir(&dir).get("packageName"),
+            Some("second")
+        );
+        let _ = std::fs::remove_file(&cache);
+    }
+
+    #[test]
+    fn test_input_placeholders() {
+        let mut config = script_config(
+            "migrate",
+            "diesel migration run --to ${input:version}",
+            None,
+        );
+        let script = config.commands.script.as_mut().unwrap();
+        let mut greet = script.configs["migrate"].clone();
+        greet.command =
+            "echo ${input:message:default=hello} ${input:version:default=latest}".into();
+        script.update_config("greet", greet);
+        script
+            .configs
+            .get_mut("migrate")
+            .unwrap()
+            .pre_command
+            .insert("greet".into());
+
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new());
+        let inputs = runner.find_inputs("migrate").unwrap();
+        let inputs: Vec<(&str, Option<&str>)> = inputs
+            .iter()
+            .map(|input| (input.name.as_str(), input.default.as_deref()))
+            .collect();
+        assert_eq!(
+            inputs,
+            [("version", Some("latest")), ("message", Some("hello"))]
+        );
+
+        let mut interpolation = InterpolationContext::default();
+        let command = "echo ${input:message:default=hello} ${input:port}";
+        assert_eq!(
+            interpolation.interpolate(command),
+            "echo hello ${input:port}"
+        );
+        interpolation.set_input("port", "8080");
+        interpolation.set_input("message", "hi");
+        assert_eq!(interpolation.interpolate(command), "echo hi 8080");
+    }
+
+    #[test]
+    fn test_matrix_runs_every_cell() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let log = temp_dir.path().join("log.txt");
+        let command = format!(
+            "echo ${{matrix.os}}-${{matrix.arch}} >> {} && test ${{matrix.arch}} != arm",
+            log.display()
+        );
+        let mut config = script_config("build", &command, None);
+        let details = config
+            .commands
+            .script
+            .as_mut()
+            .unwrap()
+            .configs
+            .get_mut("build")
+            .unwrap();
+        details
+            .matrix
+            .insert("os".into(), vec!["linux".into(), "mac".into()]);
+        details
+            .matrix
+            .insert("arch".into(), vec!["x86".into(), "arm".into()]);
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let observed = events.clone();
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .observe_steps(Arc::new(move |event| {
+                observed.lock().unwrap().push(event.clone())
+            }));
+        assert!(matches!(
+            runner.run(Some("build"), &[]),
+            Err(ExecutionError::CommandFailed(label, Some(1))) if label == "script::build [arch=arm, os=linux]"
+        ));
+        assert_eq!(
+            std::fs::read_to_string(&log).unwrap(),
+            "linux-x86\nmac-x86\nlinux-arm\nmac-arm\n"
+        );
+
+        let events = events.lock().unwrap();
+        let Some(StepEvent::MatrixFinished(label, reports)) = events.last() else {
+            panic!("expected the matrix summary last, got {:?}", events.last());
+        };
+        assert_eq!(label, "script::build");
+        let outcomes: Vec<(&str, bool)> = reports
+            .iter()
+            .map(|report| (report.label.as_str(), report.success))
+            .collect();
+        assert_eq!(
+            outcomes,
+            [
+                ("script::build [arch=x86, os=linux]", true),
+                ("script::build [arch=x86, os=mac]", true),
+                ("script::build [arch=arm, os=linux]", false),
+                ("script::build [arch=arm, os=mac]", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_failed_steps_and_only_cell_rerun_what_failed() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let log = temp_dir.path().join("log.txt");
+        let command = format!(
+            "echo ${{matrix.arch}} >> {} && test ${{matrix.arch}} != arm",
+            log.display()
+        );
+        let mut config = script_config("build", &command, None);
+        let script = config.commands.script.as_mut().unwrap();
+        script
+            .configs
+            .get_mut("build")
+            .unwrap()
+            .matrix
+            .insert("arch".into(), vec!["x86".into(), "arm".into()]);
+        script.update_config(
+            "all",
+            CommandDetails {
+                command_type: CommandType::Composite,
+                steps: vec!["build".into()],
+                ..Default::default()
+            },
+        );
+
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new());
+        assert!(runner.run(Some("all"), &[]).is_err());
+        assert_eq!(
+            runner.failed_steps(),
+            ["script::build [arch=arm]"],
+            "the composite and the matrix config fail because of the cell"
+        );
+
+        std::fs::remove_file(&log).unwrap();
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .only_cell("build", "arch=arm")
+            .unwrap();
+        assert!(!runner.run(Some("build"), &[]).unwrap().success());
+        assert_eq!(std::fs::read_to_string(&log).unwrap(), "arm\n");
+        assert!(
+            Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+                .only_cell("build", "arch=riscv")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_sandbox_runs_in_a_tempdir_with_the_inputs() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let dir = temp_dir.path();
+        std::fs::create_dir(dir.join("src")).unwrap();
+        std::fs::write(dir.join("src/input.txt"), "input\n").unwrap();
+        std::fs::write(dir.join("unrelated.txt"), "").unwrap();
+
+        let mut config = script_config(
+            "gen",
+            "test ! -e unrelated.txt && cp src/input.txt out.txt && touch junk.txt && umask > umask.txt",
+            None,
+        );
+        let details = config
+            .commands
+            .script
+            .as_mut()
+            .unwrap()
+            .configs
+            .get_mut("gen")
+            .unwrap();
+        details.working_directory = dir.to_string_lossy().to_string();
+        details.inputs = vec!["src".into()];
+        details.outputs = vec!["out.txt".into(), "umask.txt".into()];
+        details.sandbox = Some(Sandbox::Tempdir);
+        details.umask = Some("077".into());
+
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new());
+        assert!(runner.run(Some("gen"), &[]).unwrap().success());
+        assert_eq!(
+            std::fs::read_to_string(dir.join("out.txt")).unwrap(),
+            "input\n"
+        );
+        assert!(
+            !dir.join("junk.txt").exists(),
+            "only the outputs are copied back"
+        );
+        #[cfg(unix)]
+        assert_eq!(
+            std::fs::read_to_string(dir.join("umask.txt"))
+                .unwrap()
+                .trim(),
+            "0077"
+        );
+    }
+
+    #[test]
+    fn test_stdin_and_stdin_file_feed_the_command() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let dir = temp_dir.path();
+        std::fs::write(dir.join("input.txt"), "from the file\n").unwrap();
+        std::fs::write(dir.join("other.txt"), "from --stdin\n").unwrap();
+        let log = dir.join("log.txt");
+
+        let mut config = Config::default();
+        let script = config
+            .commands
+            .get_or_default_config(CommandContext::Script);
+        let cat = |stdin: Option<&str>, stdin_file: Option<&str>, pre_command: &[&str]| {
+            let mut details = ConfigBuilder::new(CommandContext::Script)
+                .command_type(CommandType::Shell)
+                .command(&format!("cat >> {}", log.display()))
+                .working_directory(&dir.to_string_lossy())
+                .pre_command(pre_command.iter().map(|key| key.to_string()).collect())
+                .build()
+                .unwrap();
+            details.stdin = stdin.map(str::to_string);
+            details.stdin_file = stdin_file.map(str::to_string);
+            details
+        };
+        script.update_config("text", cat(Some("hello ${input:name}\n"), None, &[]));
+        script.update_config("file", cat(None, Some("input.txt"), &["text"]));
+        config
+            .commands
+            .set_default_config(CommandContext::Script, "file")
+            .unwrap();
+        config.validate().unwrap();
+
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .inputs([("name".to_string(), "rx".to_string())]);
+        assert!(runner.run(Some("file"), &[]).unwrap().success());
+        assert_eq!(
+            std::fs::read_to_string(&log).unwrap(),
+            "hello rx\nfrom the file\n"
+        );
+
+        // --stdin replaces the stdin of the config run, not of its pre_commands
+        std::fs::remove_file(&log).unwrap();
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .inputs([("name".to_string(), "rx".to_string())])
+            .stdin(StdinSource::File(dir.join("other.txt")));
+        assert!(runner.run(Some("file"), &[]).unwrap().success());
+        assert_eq!(
+            std::fs::read_to_string(&log).unwrap(),
+            "hello rx\nfrom --stdin\n"
+        );
+
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .stdin(StdinSource::File(dir.join("missing.txt")));
+        assert!(matches!(
+            runner.run(Some("text"), &[]),
+            Err(ExecutionError::StdinFile(..))
+        ));
+
+        config
+            .commands
+            .get_or_default_config(CommandContext::Script)
+            .update_config("both", cat(Some("text"), Some("input.txt"), &[]));
+        assert_eq!(
+            config.validate().unwrap_err().to_string(),
+            "Invalid value for 'commands.script.configs.both.stdin': stdin and stdin_file can't both be set"
+        );
+    }
+
+    #[test]
+    fn test_output_filter_reads_stdout_and_keeps_the_exit_code() {
+        let mut config = script_config("upper", "tr a-z A-Z", None);
+        let script = config
+            .commands
+            .get_or_default_config(CommandContext::Script);
+        let filtered = |filter: &str| CommandDetails {
+            command_type: CommandType::Shell,
+            command: "echo hello; echo oops >&2; exit 3".into(),
+            output_filter: Some(filter.to_string()),
+            ..Default::default()
+        };
+        script.update_config("inline", filtered("sed s/hello/bye/"));
+        script.update_config("config", filtered("script::upper"));
+        assert_eq!(
+            config.references(CommandContext::Script, "upper"),
+            ["script::config output_filter"]
+        );
+
+        for (key, expected) in [("inline", "bye"), ("config", "HELLO")] {
+            let lines = Arc::new(Mutex::new(Vec::new()));
+            let observed = lines.clone();
+            let status = Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+                .echo_output(false)
+                .observe_output(Arc::new(move |_, stream, line| {
+                    observed
+                        .lock()
+                        .unwrap()
+                        .push(format!("{:?} {}", stream, line))
+                }))
+                .run(Some(key), &[])
+                .unwrap();
+            assert_eq!(status.code(), Some(3));
+
+            let mut lines = lines.lock().unwrap().clone();
+            lines.sort();
+            assert_eq!(lines, ["Stderr oops", &format!("Stdout {}", expected)]);
+        }
+    }
+
+    #[test]
+    fn test_output_encoding_decodes_the_output() {
+        let mut config = script_config("default", "printf 'caf\\351\\n'; printf 'cr\\350me'", None);
+        let script = config
+            .commands
+            .get_or_default_config(CommandContext::Script);
+        let mut latin = script.configs["default"].clone();
+        script.update_config("utf8", latin.clone());
+        latin.output_encoding = Some("windows-1252".to_string());
+        script.update_config("default", latin);
+        config.validate().unwrap();
+
+        for (key, expected) in [
+            ("default", ["café", "crème"]),
+            ("utf8", ["caf\u{fffd}", "cr\u{fffd}me"]),
+        ] {
+            let lines = Arc::new(Mutex::new(Vec::new()));
+            let observed = lines.clone();
+            Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+                .echo_output(false)
+                .observe_output(Arc::new(move |_, _, line| {
+                    observed.lock().unwrap().push(line.to_string())
+                }))
+                .run(Some(key), &[])
+                .unwrap();
+            assert_eq!(*lines.lock().unwrap(), expected);
+        }
+
+        let script = config
+            .commands
+            .get_or_default_config(CommandContext::Script);
+        script.configs.get_mut("utf8").unwrap().output_encoding = Some("klingon".to_string());
+        assert!(config.validate().is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_pty_attaches_the_command_to_a_terminal() {
+        let mut config = script_config(
+            "default",
+            "[ -t 0 ] && [ -t 1 ] && echo terminal; echo err >&2",
+            None,
+        );
+        let script = config
+            .commands
+            .get_or_default_config(CommandContext::Script);
+        script.configs.get_mut("default").unwrap().pty = true;
+        config.validate().unwrap();
+
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let observed = lines.clone();
+        Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .echo_output(false)
+            .observe_output(Arc::new(move |_, stream, line| {
+                observed
+                    .lock()
+                    .unwrap()
+                    .push(format!("{:?} {}", stream, line))
+            }))
+            .run(None, &[])
+            .unwrap();
+        assert_eq!(*lines.lock().unwrap(), ["Stdout terminal", "Stdout err"]);
+
+        let script = config
+            .commands
+            .get_or_default_config(CommandContext::Script);
+        script.configs.get_mut("default").unwrap().stdin = Some("input".to_string());
+        assert!(config.validate().is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_a_command_reading_the_terminal_gets_its_foreground() {
+        use std::{
+            fs::File,
+            io::{Read, Write},
+            os::{fd::FromRawFd, unix::process::CommandExt},
+            sync::mpsc,
+        };
+
+        const NAME: &str = "tests::test_a_command_reading_the_terminal_gets_its_foreground";
+        const IN_TERMINAL: &str = "RX_TEST_IN_TERMINAL";
+
+        // Run again below, as the foreground of a terminal of its own
+        if std::env::var_os(IN_TERMINAL).is_some() {
+            let config = script_config("default", "read line && echo \"read $line\"", None);
+            let status = Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+                .run(None, &[])
+                .unwrap();
+            assert!(status.success());
+            // rx has the terminal back
+            assert_eq!(unsafe { libc::tcgetpgrp(0) }, unsafe { libc::getpgrp() });
+            return;
+        }
+
+        let (mut master, mut slave) = (0, 0);
+        let opened = unsafe {
+            libc::openpty(
+                &mut master,
+                &mut slave,
+                std::ptr::null_mut(),
+                std::ptr::null(),
+                std::ptr::null(),
+            )
+        };
+        assert_eq!(opened, 0);
+        let mut master = unsafe { File::from_raw_fd(master) };
+        let slave = unsafe { File::from_raw_fd(slave) };
+        let mut test = Command::new(std::env::current_exe().unwrap());
+        test.args(["--exact", NAME, "--nocapture", "--test-threads=1"])
+            .env(IN_TERMINAL, "1")
+            .stdin(slave.try_clone().unwrap())
+            .stdout(slave.try_clone().unwrap())
+            .stderr(slave.try_clone().unwrap());
+        unsafe {
+            test.pre_exec(|| {
+                if libc::setsid() == -1 || libc::ioctl(0, libc::TIOCSCTTY as _, 0) == -1 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        let mut child = test.spawn().unwrap();
+        // Nothing but the child keeps the terminal open, so reading it ends with the child
+        drop((test, slave));
+
+        master.write_all(b"hello\n").unwrap();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut output = Vec::new();
+            let mut buffer = [0; 1024];
+            // Ends with an error once the terminal is closed on the other side
+            while let Ok(read @ 1..) = master.read(&mut buffer) {
+                output.extend_from_slice(&buffer[..read]);
+            }
+            let _ = sender.send(String::from_utf8_lossy(&output).into_owned());
+        });
+
+        let deadline = Instant::now() + Duration::from_secs(20);
+        let status = loop {
+            if let Some(status) = child.try_wait().unwrap() {
+                break status;
+            }
+            if Instant::now() > deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                panic!("the command reading the terminal was stopped");
+            }
+            thread::sleep(Duration::from_millis(50));
+        };
+        let output = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(status.success(), "{}", output);
+        assert!(output.contains("read hello"), "{}", output);
+    }
+
+    #[test]
+    fn test_parallel_steps_and_cells_run_side_by_side() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let log = temp_dir.path().join("log.txt");
+        let command = format!("sleep 0.3 && echo ${{matrix.n}} >> {}", log.display());
+        let mut config = script_config("cell", &command, None);
+        let script = config
+            .commands
+            .get_or_default_config(CommandContext::Script);
+        let mut cell = script.configs["cell"].clone();
+        cell.parallel = true;
+        cell.matrix
+            .insert("n".into(), vec!["1".into(), "2".into(), "3".into()]);
+        script.update_config("cell", cell);
+        let mut slow = script.configs["cell"].clone();
+        slow.command = format!("sleep 0.3 && echo slow >> {}", log.display()).into();
+        slow.matrix.clear();
+        script.update_config("slow", slow);
+        script.update_config(
+            "all",
+            CommandDetails {
+                command_type: CommandType::Composite,
+                steps: vec!["cell".into(), "slow".into()],
+                parallel: true,
+                ..Default::default()
+            },
+        );
+
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new());
+        let started = Instant::now();
+        assert!(runner.run(Some("all"), &[]).unwrap().success());
+        assert!(started.elapsed() < Duration::from_millis(800));
+        let mut lines: Vec<_> = std::fs::read_to_string(&log)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect();
+        lines.sort();
+        assert_eq!(lines, ["1", "2", "3", "slow"]);
+
+        // A panicking step comes back once the others are done
+        std::fs::remove_file(&log).unwrap();
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .observe_steps(Arc::new(|event: &StepEvent| {
+                if matches!(event, StepEvent::Started(label) if label == "script::slow") {
+                    panic!("observer failed");
+                }
+            }));
+        let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            runner.run(Some("all"), &[])
+        }));
+        assert!(panicked.is_err());
+        assert_eq!(std::fs::read_to_string(&log).unwrap().lines().count(), 3);
+    }
+
+    #[test]
+    fn test_requires_ports_refuses_to_start_on_a_port_in_use() {
+        let listener = std::net::TcpListener::bind(("0.0.0.0", 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let log = temp_dir.path().join("log.txt");
+
+        let mut config = script_config("default", &format!("echo ran > {}", log.display()), None);
+        let script = config
+            .commands
+            .get_or_default_config(CommandContext::Script);
+        let details = script.configs.get_mut("default").unwrap();
+        details.requires_ports = vec![port];
+        details.kill_conflicts = true;
+        config.validate().unwrap();
+
+        // Declining to stop the holder, rx's own test process here, leaves the port in use
+        let asked = Arc::new(Mutex::new(Vec::new()));
+        let answered = asked.clone();
+        let result = Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .on_port_conflict(Arc::new(move |label, conflict| {
+                answered
+                    .lock()
+                    .unwrap()
+                    .push((label.to_string(), conflict.port));
+                false
+            }))
+            .run(None, &[]);
+        match result {
+            Err(ExecutionError::PortInUse(label, conflict)) => {
+                assert_eq!(label, "script::default");
+                assert_eq!(conflict.port, port);
+                #[cfg(target_os = "linux")]
+                assert_eq!(conflict.pid, Some(std::process::id()));
+            }
+            other => panic!("expected the port to be in use, got {:?}", other),
+        }
+        assert!(!log.exists());
+        if cfg!(target_os = "linux") {
+            assert_eq!(
+                *asked.lock().unwrap(),
+                [("script::default".to_string(), port)]
+            );
+        }
+
+        drop(listener);
+        Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .run(None, &[])
+            .unwrap();
+        assert!(log.exists());
+
+        let table = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 4242 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:1F90 0100007F:D431 01 00000000:00000000 00:00000000 00000000  1000        0 4343 1 0000000000000000 20 4 30 10 -1";
+        assert_eq!(ports::listening_inodes(table, 8080), ["4242"]);
+        assert!(ports::listening_inodes(table, 8081).is_empty());
+
+        let script = config
+            .commands
+            .get_or_default_config(CommandContext::Script);
+        script.configs.get_mut("default").unwrap().requires_ports = Vec::new();
+        assert!(config.validate().is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_open_files_and_core_dumps_limits() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let log = temp_dir.path().join("limits.txt");
+        let command = format!(
+            "ulimit -n > {log}; ulimit -c >> {log}; ulimit -Hc >> {log}",
+            log = log.display()
+        );
+        let run = |limits: Limits| {
+            let mut config = script_config("limited", &command, None);
+            config
+                .commands
+                .get_or_default_config(CommandContext::Script)
+                .configs
+                .get_mut("limited")
+                .unwrap()
+                .limits = limits;
+            let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new());
+            assert!(runner.run(Some("limited"), &[]).unwrap().success());
+            std::fs::read_to_string(&log)
+                .unwrap()
+                .lines()
+                .map(|line| line.trim().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let lines = run(Limits {
+            open_files: Some(64),
+            core_dumps: Some(false),
+            ..Default::default()
+        });
+        assert_eq!(lines[..2], ["64", "0"]);
+
+        let lines = run(Limits {
+            core_dumps: Some(true),
+            ..Default::default()
+        });
+        assert_eq!(lines[1], lines[2]);
+
+        let mut config = script_config("limited", "true", None);
+        config
+            .commands
+            .set_default_config(CommandContext::Script, "limited")
+            .unwrap();
+        let script = config
+            .commands
+            .get_or_default_config(CommandContext::Script);
+        script.configs.get_mut("limited").unwrap().limits.open_files = Some(0);
+        assert!(config.validate().is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_open_files_past_the_hard_limit_stop_at_it_without_the_rights() {
+        let output = std::process::Command::new("sh")
+            .args(["-c", "ulimit -Hn"])
+            .output()
+            .unwrap();
+        let Ok(hard) = String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .parse::<u64>()
+        else {
+            // Unlimited, nothing is past it
+            return;
+        };
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let log = temp_dir.path().join("open_files.txt");
+        let mut config = script_config("limited", &format!("ulimit -n > {}", log.display()), None);
+        let script = config
+            .commands
+            .get_or_default_config(CommandContext::Script);
+        script.configs.get_mut("limited").unwrap().limits.open_files = Some(hard + 1);
+
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new());
+        assert!(runner.run(Some("limited"), &[]).unwrap().success());
+        let open_files: u64 = std::fs::read_to_string(&log)
+            .unwrap()
+            .trim()
+            .parse()
+            .unwrap();
+        // Raised by root, the hard limit for everyone else
+        assert!(open_files == hard + 1 || open_files == hard);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_limits_hold_the_command() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let log = temp_dir.path().join("limits.txt");
+
+        let mut details = ConfigBuilder::new(CommandContext::Script)
+            .command_type(CommandType::Shell)
+            .command(&format!(
+                "nice > {log}; ulimit -v >> {log}; grep Cpus_allowed_list /proc/self/status >> {log}",
+                log = log.display()
+            ))
+            .build()
+            .unwrap();
+        details.limits = Limits {
+            memory: Some("512M".to_string()),
+            nice: Some(5),
+            cpus: vec![0],
+            ..Default::default()
+        };
+        let mut config = Config::default();
+        config
+            .commands
+            .get_or_default_config(CommandContext::Script)
+            .update_config("limited", details);
+        config
+            .commands
+            .set_default_config(CommandContext::Script, "limited")
+            .unwrap();
+        config.validate().unwrap();
+
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new());
+        assert!(runner.run(None, &[]).unwrap().success());
+        let output = std::fs::read_to_string(&log).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0].trim(), "5");
+        assert_eq!(lines[1].trim(), "524288");
+        assert!(lines[2].ends_with("\t0"), "{}", lines[2]);
+
+        let limits = |memory: &str| Limits {
+            memory: Some(memory.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(limits("4G").memory_bytes(), Ok(Some(4 << 30)));
+        assert_eq!(limits("1024").memory_bytes(), Ok(Some(1024)));
+        assert!(limits("1.5G").memory_bytes().is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_max_output_lines_keeps_the_head_and_tail() {
+        let mut config = script_config("noisy", "seq 1 10", None);
+        config
+            .commands
+            .get_or_default_config(CommandContext::Script)
+            .configs
+            .get_mut("noisy")
+            .unwrap()
+            .max_output_lines = Some(5);
+
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let observed = lines.clone();
+        let status = Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .echo_output(false)
+            .observe_output(Arc::new(move |_, _, line| {
+                observed.lock().unwrap().push(line.to_string())
+            }))
+            .run(Some("noisy"), &[])
+            .unwrap();
+        assert!(status.success());
+        assert_eq!(
+            *lines.lock().unwrap(),
+            [
+                "1",
+                "2",
+                "3",
+                "[rx: output truncated, 5 lines dropped]",
+                "9",
+                "10"
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_megabytes_of_output_reach_a_slow_observer_whole() {
+        let config = script_config(
+            "noisy",
+            "yes out | head -n 200000 & yes err | head -n 200000 >&2; wait",
+            None,
+        );
+
+        let counts = Arc::new(Mutex::new((0, 0)));
+        let counted = counts.clone();
+        let status = Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .echo_output(false)
+            .observe_output(Arc::new(move |_, stream, line| {
+                let mut counts = counted.lock().unwrap();
+                match stream {
+                    OutputStream::Stdout => {
+                        assert_eq!(line, "out");
+                        counts.0 += 1;
+                    }
+                    OutputStream::Stderr => {
+                        assert_eq!(line, "err");
+                        counts.1 += 1;
+                    }
+                }
+                // Slower than the command writes, which then waits on its pipes
+                if (counts.0 + counts.1) % 20000 == 0 {
+                    thread::sleep(Duration::from_millis(20));
+                }
+            }))
+            .run(Some("noisy"), &[])
+            .unwrap();
+        assert!(status.success());
+        assert_eq!(*counts.lock().unwrap(), (200000, 200000));
+    }
+
+    #[test]
+    fn test_log_file_gets_the_output_without_ansi_escapes() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let mut config = script_config("default", "printf '\\033[31mred\\033[0m\\n'", None);
+        let script = config
+            .commands
+            .get_or_default_config(CommandContext::Script);
+        let mut details = script.configs["default"].clone();
+        details.log_file = Some(format!("{}/logs/${{key}}.log", temp_dir.path().display()));
+        script.update_config("default", details.clone());
+        details.keep_ansi = true;
+        script.update_config("colored", details);
+        config.validate().unwrap();
+
+        for key in ["default", "colored", "default"] {
+            let status = Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+                .echo_output(false)
+                .run(Some(key), &[])
+                .unwrap();
+            assert!(status.success());
+        }
+        let log = |key: &str| {
+            std::fs::read_to_string(temp_dir.path().join(format!("logs/{}.log", key))).unwrap()
+        };
+        assert_eq!(log("default"), "red\nred\n");
+        assert_eq!(log("colored"), "\u{1b}[31mred\u{1b}[0m\n");
+        assert_eq!(
+            rx_core::executor::output::strip_ansi("\u{1b}]0;title\u{7}a\u{1b}[1;2Hb\u{1b}=c"),
+            "abc"
+        );
+    }
+
+    #[test]
+    fn test_for_exec_runs_a_command_in_the_environment_of_a_config() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let mut config = script_config("server", "exit 1", None);
+        let script = config
+            .commands
+            .get_or_default_config(CommandContext::Script);
+        let server = script.configs.get_mut("server").unwrap();
+        server.env.insert("PORT".to_string(), "8080".to_string());
+        server.working_directory = temp_dir.path().to_string_lossy().into_owned();
+        server.pre_command.insert("missing".to_string());
+        let exec = server.for_exec(&[
+            "sh".to_string(),
+            "-c".to_string(),
+            "echo \"$PORT $(pwd)\"; echo \"$1\"".to_string(),
+            "sh".to_string(),
+            "it's".to_string(),
+        ]);
+        script.update_config("server", exec);
+
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let observed = lines.clone();
+        let status = Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .echo_output(false)
+            .observe_output(Arc::new(move |_, _, line| {
+                observed.lock().unwrap().push(line.to_string())
+            }))
+            .run(Some("server"), &[])
+            .unwrap();
+        assert!(status.success());
+        let working_directory = temp_dir.path().canonicalize().unwrap();
+        assert_eq!(
+            *lines.lock().unwrap(),
+            [
+                format!("8080 {}", working_directory.display()),
+                "it's".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_queue_runs_parallel_steps_one_at_a_time() {
+        let mut config = script_config("a", "sleep 0.2", None);
+        let script = config
+            .commands
+            .get_or_default_config(CommandContext::Script);
+        let step = script.configs["a"].clone();
+        script.update_config("b", step.clone());
+        script.update_config("c", step);
+        script.update_config(
+            "all",
+            CommandDetails {
+                command_type: CommandType::Composite,
+                steps: vec!["a".into(), "b".into(), "c".into()],
+                parallel: true,
+                ..Default::default()
+            },
+        );
+
+        let queue = RunQueue::new(Some(1));
+        let positions = Arc::new(Mutex::new(Vec::new()));
+        let recorded = positions.clone();
+        let watched = queue.clone();
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .queue(queue.clone())
+            .observe_steps(Arc::new(move |event: &StepEvent| {
+                if let StepEvent::Queued(_, position) = event {
+                    assert!(watched.snapshot().running.len() <= 1);
+                    recorded.lock().unwrap().push(*position);
+                }
+            }));
+
+        let started = Instant::now();
+        assert!(runner.run(Some("all"), &[]).unwrap().success());
+        assert!(started.elapsed() >= Duration::from_millis(600));
+        let mut positions = positions.lock().unwrap().clone();
+        positions.sort();
+        assert_eq!(positions, [1, 1, 2]);
+        assert_eq!(queue.snapshot().running.len(), 0);
+        assert!(queue.snapshot().queued.is_empty());
+    }
+
+    #[test]
+    fn test_commands_sharing_a_lock_never_run_at_once() {
+        let lock = format!("rx-test-{}", std::process::id());
+        let mut config = script_config("a", "sleep 0.2", None);
+        let script = config
+            .commands
+            .get_or_default_config(CommandContext::Script);
+        let mut step = script.configs["a"].clone();
+        step.allow_multiple_instances = true;
+        step.lock = Some(lock.clone());
+        script.update_config("a", step.clone());
+        script.update_config("b", step);
+        script.update_config(
+            "both",
+            CommandDetails {
+                command_type: CommandType::Composite,
+                steps: vec!["a".into(), "b".into()],
+                parallel: true,
+                ..Default::default()
+            },
+        );
+        config
+            .commands
+            .set_default_config(CommandContext::Script, "both")
+            .unwrap();
+        config.validate().unwrap();
+
+        let waited = Arc::new(Mutex::new(Vec::new()));
+        let recorded = waited.clone();
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .observe_steps(Arc::new(move |event: &StepEvent| {
+                if let StepEvent::Locked(_, name) = event {
+                    recorded.lock().unwrap().push(name.clone());
+                }
+            }));
+
+        let started = Instant::now();
+        assert!(runner.run(Some("both"), &[]).unwrap().success());
+        assert!(started.elapsed() >= Duration::from_millis(400));
+        assert_eq!(*waited.lock().unwrap(), [lock]);
+
+        let details = config
+            .commands
+            .get_or_default_config(CommandContext::Script)
+            .configs
+            .get_mut("a")
+            .unwrap();
+        details.lock = Some("../db".to_string());
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidValue(path, _)) if path == "commands.script.configs.a.lock"
+        ));
+    }
+
+    /// Greets the command line, taking the exit code 3 for a success.
+    struct Greeter {
+        lines: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl CommandExecutor for Greeter {
+        fn resolve(
+            &self,
+            runner: &Runner,
+            details: &CommandDetails,
+            args: &[String],
+        ) -> Result<Invocation, ExecutionError> {
+            let script = format!(
+                "echo hello $GREETING {}; exit 3",
+                runner.shell_line(details, args)
+            );
+            Ok(Invocation::Argv(vec!["sh".into(), "-c".into(), script]))
+        }
+
+        fn spawn(
+            &self,
+            _details: &CommandDetails,
+            command: &mut Command,
+        ) -> Result<(), ExecutionError> {
+            command.env("GREETING", "dear");
+            Ok(())
+        }
+
+        fn stream(&self, _details: &CommandDetails) -> Option<OutputObserver> {
+            let lines = self.lines.clone();
+            Some(Arc::new(move |_: &str, _: OutputStream, line: &str| {
+                lines.lock().unwrap().push(line.to_string())
+            }))
+        }
+
+        fn finalize(
+            &self,
+            _details: &CommandDetails,
+            status: ExitStatus,
+        ) -> Result<ExitStatus, ExecutionError> {
+            match status.code() {
+                Some(3) => Ok(ExitStatus::from_raw(0)),
+                _ => Ok(status),
+            }
+        }
+    }
+
+    #[test]
+    fn test_custom_command_types_run_with_registered_executors() {
+        let config: Config = toml::from_str(
+            r#"
+            [commands.script.configs.greet]
+            type = "greeter"
+            command = "${who}"
+            params = "again"
+            working_directory = ""
+            "#,
+        )
+        .unwrap();
+        let details = &config.commands.script.as_ref().unwrap().configs["greet"];
+        assert_eq!(details.command_type, CommandType::Custom("greeter".into()));
+        assert!(toml::to_string(&config)
+            .unwrap()
+            .contains(r#"type = "greeter""#));
+
+        let mut interpolation = InterpolationContext::default();
+        interpolation.set("who", "app");
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .interpolation(interpolation)
+            .echo_output(false);
+        let err = runner.run(Some("greet"), &[]).unwrap_err();
+        assert!(matches!(
+            &err,
+            ExecutionError::UnknownExecutor(name, names) if name == "greeter" && names == "cargo, shell"
+        ));
+
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let runner = runner.executor(
+            "greeter",
+            Arc::new(Greeter {
+                lines: lines.clone(),
+            }),
+        );
+        let status = runner.run(Some("greet"), &["twice".into()]).unwrap();
+        assert!(status.success());
+        assert_eq!(*lines.lock().unwrap(), ["hello dear app again twice"]);
+    }
+
+    #[test]
+    fn test_run_handle_streams_output_and_cancels_the_run() {
+        let config = Arc::new(script_config("serve", "echo listening; sleep 30", Some(1)));
+        let mut handle = RunHandle::start(
+            config,
+            CommandContext::Script,
+            Some("serve".into()),
+            Vec::new(),
+            |runner| runner.echo_output(false),
+        );
+
+        let first_line = handle.events().iter().find_map(|event| match event {
+            RunOutput::Line { label, line, .. } => Some((label, line)),
+            RunOutput::Step(_) => None,
+        });
+        assert_eq!(
+            first_line,
+            Some(("script::serve".to_string(), "listening".to_string()))
+        );
+        assert!(handle
+            .wait_with_timeout(Duration::from_millis(50))
+            .is_none());
+        assert!(!handle.is_finished());
+
+        let started = Instant::now();
+        handle.cancel();
+        assert!(matches!(
+            handle.wait_with_timeout(Duration::from_secs(10)),
+            Some(Err(ExecutionError::Interrupted(label))) if label == "script::serve"
+        ));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(handle.is_finished());
+        assert!(handle.events().iter().next().is_none());
+        assert!(matches!(handle.wait(), Err(ExecutionError::Interrupted(_))));
+    }
+
+    #[test]
+    fn test_namespaces_give_their_configs_a_working_directory() {
+        let config: Config = toml::from_str(
+            r#"
+[namespaces.api]
+working_directory = "${workspaceFolder}/services/api"
+
+[namespaces."api/admin"]
+working_directory = "${workspaceFolder}/services/admin"
+
+[commands.script]
+default = "api/serve"
+
+[commands.script.configs."api/serve"]
+command = "cargo run"
+
+[commands.script.configs."api/migrate"]
+command = "sqlx migrate run"
+working_directory = "${workspaceFolder}/db"
+
+[commands.script.configs."api/admin/serve"]
+command = "cargo run"
+
+[commands.script.configs."web/dev"]
+command = "npm run dev"
+"#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+        assert_eq!(
+            config.matching(CommandContext::Script, "api/"),
+            [
+                "script::api/admin/serve",
+                "script::api/migrate",
+                "script::api/serve"
+            ]
+        );
+        assert_eq!(
+            config.namespace("api/admin/serve").map(|(name, _)| name),
+            Some("api/admin")
+        );
+        assert!(config.namespace("apiserve").is_none());
+
+        let mut interpolation = InterpolationContext::default();
+        interpolation.set("workspaceFolder", "/repo");
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .interpolation(interpolation);
+        let working_directory = |key: &str| {
+            runner
+                .target_details(key, runner.details(key).unwrap(), &[])
+                .working_directory
+                .clone()
+        };
+        assert_eq!(
+            working_directory("api/serve"),
+            "${workspaceFolder}/services/api"
+        );
+        assert_eq!(working_directory("api/migrate"), "${workspaceFolder}/db");
+        assert_eq!(
+            working_directory("api/admin/serve"),
+            "${workspaceFolder}/services/admin"
+        );
+        assert_eq!(working_directory("web/dev"), "${workspaceFolder}");
+
+        let mut invalid = config.clone();
+        invalid.namespaces.insert("web/".into(), Default::default());
+        assert!(matches!(
+            invalid.validate(),
+            Err(ConfigError::InvalidValue(path, _)) if path == "namespaces.web/"
+        ));
+    }
+
+    #[test]
+    fn test_placeholders_list_values_and_flag_unresolved_ones() {
+        let config: Config = toml::from_str(
+            r#"
+[commands.script]
+default = "serve"
+
+[commands.script.configs.serve]
+type = "shell"
+command = "serve --port ${arg:port} --db ${RX_TEST_UNSET_DB_URL} ${workspaceFolder}"
+env = { RUST_LOG = "debug", TOKEN = "keychain:rx/token" }
+args = { port = { type = "int", default = 8080 } }
+"#,
+        )
+        .unwrap();
+        let mut interpolation = InterpolationContext::default();
+        interpolation.set("workspaceFolder", "/repo");
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .interpolation(interpolation);
+
+        let placeholders = runner.placeholders(Some("serve")).unwrap();
+        let find = |name: &str| {
+            placeholders
+                .placeholders
+                .iter()
+                .find(|placeholder| placeholder.name == name)
+                .unwrap()
+        };
+        assert_eq!(find("arg:port").value.as_deref(), Some("8080"));
+        assert_eq!(find("arg:port").source, PlaceholderSource::Arg);
+        assert!(find("workspaceFolder").used);
+        assert_eq!(find("RUST_LOG").source, PlaceholderSource::Env);
+        assert_eq!(find("TOKEN").value.as_deref(), Some("(sealed)"));
+        assert!(find("gitBranch").value.is_none() && !find("gitBranch").used);
+        let unresolved: Vec<&str> = placeholders
+            .unresolved()
+            .iter()
+            .map(|placeholder| placeholder.name.as_str())
+            .collect();
+        assert_eq!(unresolved, ["RX_TEST_UNSET_DB_URL"]);
+        assert!(placeholders.report().contains("✗ ${RX_TEST_UNSET_DB_URL}"));
+
+        let placeholders = runner.placeholders(None).unwrap();
+        assert!(placeholders.unresolved().is_empty());
+        assert!(placeholders
+            .placeholders
+            .iter()
+            .all(|placeholder| placeholder.name != "arg:port"));
+    }
+}
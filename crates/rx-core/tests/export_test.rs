@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod tests {
+    use rx_core::{
+        builders::config::ConfigBuilder,
+        export::{github_actions, ExportFormat, WorkflowOptions},
+        interpolation::InterpolationContext,
+        models::config::{CommandContext, CommandDetails, CommandType, Config},
+    };
+
+    fn config() -> Config {
+        let mut config = Config::default();
+        let release = ConfigBuilder::new(CommandContext::Build)
+            .params("--release")
+            .build()
+            .unwrap();
+        config
+            .commands
+            .get_or_default_config(CommandContext::Build)
+            .update_config("release", release);
+
+        let scripts = config
+            .commands
+            .get_or_default_config(CommandContext::Script);
+        let package = ConfigBuilder::new(CommandContext::Script)
+            .command("tar czf dist.tgz target/release/app")
+            .env([("LEVEL".to_string(), "9".to_string())].into())
+            .build()
+            .unwrap();
+        scripts.update_config("package", package);
+        scripts.update_config(
+            "ship",
+            CommandDetails {
+                command_type: CommandType::Composite,
+                steps: vec!["build::release".into(), "package".into()],
+                ..Default::default()
+            },
+        );
+        config
+    }
+
+    #[test]
+    fn test_export_cargo_make_and_xtask() {
+        let config = config();
+        let interpolation = InterpolationContext::default();
+
+        let cargo_make = ExportFormat::CargoMake
+            .export(&config, &interpolation)
+            .unwrap();
+        let tasks: toml::Table = toml::from_str(&cargo_make.content).unwrap();
+        let tasks = tasks["tasks"].as_table().unwrap();
+
+        assert_eq!(
+            tasks["build-release"]["args"].as_array().unwrap(),
+            &["build", "--release", "${@}"].map(toml::Value::from)
+        );
+        assert_eq!(
+            tasks["build-release"]["cwd"].as_str(),
+            Some("${CARGO_MAKE_WORKSPACE_WORKING_DIRECTORY}")
+        );
+        assert_eq!(
+            tasks["script-package"]["script"].as_str(),
+            Some("tar czf dist.tgz target/release/app")
+        );
+        assert_eq!(tasks["script-package"]["env"]["LEVEL"].as_str(), Some("9"));
+        assert_eq!(
+            tasks["script-ship"]["dependencies"].as_array().unwrap(),
+            &["build-release", "script-package"].map(toml::Value::from)
+        );
+
+        let xtask = ExportFormat::Xtask.export(&config, &interpolation).unwrap();
+        assert!(xtask.content.contains(
+            "shell(\"tar czf dist.tgz target/release/app \\\"$@\\\"\", args, &[(\"LEVEL\", \"9\")], root)"
+        ));
+        assert!(xtask.content.contains(
+            "run(\"build-release\", &[], root)?;\n            run(\"script-package\", &[], root)?;"
+        ));
+    }
+
+    #[test]
+    fn test_export_github_actions_workflow() {
+        let config = config();
+
+        let workflow = ExportFormat::GithubActions
+            .export(&config, &InterpolationContext::default())
+            .unwrap();
+        let (_, jobs) = workflow.content.split_once("jobs:\n").unwrap();
+        let jobs: Vec<&str> = jobs
+            .lines()
+            .filter(|line| line.starts_with("  ") && !line.starts_with("   "))
+            .filter(|line| line.ends_with(':'))
+            .collect();
+        assert_eq!(
+            jobs,
+            [
+                "  fmt-default:",
+                "  lint-default:",
+                "  test-default:",
+                "  build-default:"
+            ]
+        );
+        assert!(workflow.content.contains("  RX_CONFIG: \"rx.toml\"\n"));
+        assert!(workflow.content.contains("          components: clippy\n"));
+
+        let options = WorkflowOptions {
+            configs: vec!["build::release".to_string()],
+            config_path: ".config/rx.toml".to_string(),
+        };
+        let workflow = github_actions(&config, &options).unwrap();
+        assert!(workflow
+            .content
+            .contains("  RX_CONFIG: \".config/rx.toml\"\n"));
+        assert!(workflow
+            .content
+            .contains("  build-release:\n    name: \"build::release\"\n"));
+        assert!(workflow
+            .content
+            .ends_with("      - run: rx build release\n"));
+
+        let options = WorkflowOptions {
+            configs: vec!["build::debug".to_string()],
+            ..Default::default()
+        };
+        assert!(github_actions(&config, &options).is_err());
+    }
+}
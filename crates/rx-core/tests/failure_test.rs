@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rx_core::{
+        errors::ExecutionError,
+        executor::{runner::Runner, shutdown::ShutdownSignal},
+        failure::{FailedStage, FailureReporter},
+        models::config::{CommandContext, Config},
+    };
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_summary_names_the_failed_pre_command_with_its_stderr() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let config: Config = toml::from_str(&format!(
+            r#"
+            [commands.script]
+            default = "main"
+
+            [commands.script.configs.prep]
+            type = "shell"
+            command = "echo one >&2; echo two >&2; echo three >&2; exit 3"
+            working_directory = "{dir}"
+            log_file = "logs/${{key}}.log"
+
+            [commands.script.configs.main]
+            type = "shell"
+            command = "echo main"
+            working_directory = "{dir}"
+            pre_command = ["prep"]
+            "#,
+            dir = temp_dir.path().display()
+        ))
+        .unwrap();
+
+        let reporter = FailureReporter::new(2);
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .echo_output(false)
+            .observe_output(reporter.observe_output(None))
+            .observe_steps(reporter.observe_steps(Arc::new(|_| {})));
+        let err = runner.run(Some("main"), &[]).unwrap_err();
+        assert!(
+            matches!(&err, ExecutionError::CommandFailed(label, Some(3)) if label == "script::prep")
+        );
+
+        let summary = reporter.summarize(&runner, "main", &[], &err);
+        assert_eq!(summary.failed, "script::prep");
+        assert_eq!(summary.stage, FailedStage::PreCommand);
+        assert_eq!(summary.exit_code, Some(3));
+        assert_eq!(summary.stderr, ["two", "three"]);
+        assert!(summary.command.as_deref().unwrap().contains("exit 3"));
+        assert_eq!(
+            summary.log_file,
+            Some(temp_dir.path().join("logs/prep.log"))
+        );
+        assert_eq!(
+            summary.lines()[0],
+            "pre_command script::prep failed with exit code 3"
+        );
+    }
+}
@@ -0,0 +1,155 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use rx_core::{
+        builders::config::ConfigBuilder,
+        executor::temp::{leftovers, owner, remove_live, TempResource},
+        gc::{collect, find_garbage, find_runtime_garbage, remove_stale_configs, Garbage},
+        interpolation::InterpolationContext,
+        models::config::{CommandContext, Config},
+        service::{ServiceRecord, ServiceRegistry},
+        session::Session,
+        stats::{RunHistory, RunRecord},
+        store::Store,
+    };
+    use tempfile::TempDir;
+
+    const DAY: u64 = 86_400;
+
+    #[test]
+    fn test_gc_finds_stale_configs_and_what_gone_ones_left() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let dir = temp_dir.path();
+        let store = Store::at(dir.join("store"));
+        let now = 100 * DAY;
+
+        let mut config = Config::default();
+        let tests = config.commands.get_or_default_config(CommandContext::Test);
+        for key in ["fresh", "old", "new"] {
+            tests.update_config(
+                key,
+                ConfigBuilder::new(CommandContext::Test)
+                    .working_directory(&dir.to_string_lossy())
+                    .build()
+                    .unwrap(),
+            );
+            tests.configs.get_mut(key).unwrap().target_dir = Some("targets/${key}".to_string());
+        }
+
+        let run = |days_ago: u64| RunRecord {
+            success: true,
+            duration_ms: 10,
+            finished_at: now - days_ago * DAY,
+        };
+        let mut history = RunHistory::default();
+        history
+            .runs
+            .insert("test::fresh".into(), vec![run(40), run(2)]);
+        history.runs.insert("test::old".into(), vec![run(45)]);
+        history.runs.insert("test::gone".into(), vec![run(1)]);
+        history.save(&store).unwrap();
+        fs::create_dir_all(store.path("cache/test")).unwrap();
+        fs::write(store.path("cache/test/fresh.toml"), "").unwrap();
+        fs::write(store.path("cache/test/gone.toml"), "").unwrap();
+        fs::create_dir_all(store.path("services")).unwrap();
+        fs::write(store.path("services/web.log"), "").unwrap();
+        let mut registry = ServiceRegistry::default();
+        registry.services.insert(
+            "web".into(),
+            ServiceRecord {
+                pid: 999_999_999,
+                started_at: 0,
+                log: store.path("services/web.log"),
+            },
+        );
+        registry.save(&store).unwrap();
+        for target in ["fresh", "old", "gone"] {
+            fs::create_dir_all(dir.join("targets").join(target)).unwrap();
+            fs::write(dir.join("targets").join(target).join("CACHEDIR.TAG"), "").unwrap();
+        }
+        fs::create_dir_all(dir.join("targets/debug")).unwrap();
+
+        let interpolation = InterpolationContext::default();
+        let garbage = find_garbage(&config, &store, &interpolation, 30, now).unwrap();
+        assert_eq!(
+            garbage,
+            [
+                Garbage::StaleConfig {
+                    context: CommandContext::Test,
+                    key: "old".into(),
+                    last_run: now - 45 * DAY,
+                },
+                Garbage::History("test::gone".into()),
+                Garbage::History("test::old".into()),
+                Garbage::Cache(store.path("cache/test/gone.toml")),
+                Garbage::Pid {
+                    key: "web".into(),
+                    pid: 999_999_999,
+                },
+                Garbage::Log(store.path("services/web.log")),
+                Garbage::TargetDir(dir.join("targets/gone")),
+                Garbage::TargetDir(dir.join("targets/old")),
+            ]
+        );
+
+        remove_stale_configs(&mut config, &garbage).unwrap();
+        collect(&store, &garbage).unwrap();
+        let tests = config.commands.get_config(CommandContext::Test).unwrap();
+        assert!(!tests.configs.contains_key("old"));
+        assert!(tests.configs.contains_key("new"));
+        let history = RunHistory::load(&store).unwrap();
+        assert_eq!(history.runs.keys().collect::<Vec<_>>(), ["test::fresh"]);
+        assert!(store.path("cache/test/fresh.toml").exists());
+        assert!(ServiceRegistry::load(&store).unwrap().services.is_empty());
+        assert!(dir.join("targets/fresh").exists() && dir.join("targets/debug").exists());
+        assert!(!dir.join("targets/gone").exists());
+        assert!(find_garbage(&config, &store, &interpolation, 30, now)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_gc_runtime_sweeps_what_gone_rx_processes_left() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let dir = temp_dir.path();
+        let store = Store::at(dir.join("store"));
+        let own = std::process::id();
+        fs::write(dir.join("rx-script-999999999-0"), "").unwrap();
+        fs::create_dir_all(dir.join("rx-sandbox-999999999-3/src")).unwrap();
+        fs::write(dir.join(format!("rx-script-{}-0", own)), "").unwrap();
+        fs::write(dir.join("rx-999999999.sock"), "").unwrap();
+        fs::write(dir.join("other-999999999-0"), "").unwrap();
+        assert_eq!(owner("rx-panes-42"), Some(42));
+        assert_eq!(owner("rx-user.sock"), None);
+
+        let garbage = find_runtime_garbage(&store, dir).unwrap();
+        assert_eq!(
+            garbage,
+            [
+                Garbage::Runtime(dir.join("rx-sandbox-999999999-3")),
+                Garbage::Runtime(dir.join("rx-script-999999999-0")),
+            ]
+        );
+        collect(&store, &garbage).unwrap();
+        assert!(leftovers(dir).is_empty());
+        assert!(dir.join(format!("rx-script-{}-0", own)).exists());
+
+        let path = dir.join(format!("rx-sandbox-{}-1", own));
+        fs::create_dir(&path).unwrap();
+        fs::write(path.join("input"), "").unwrap();
+        let session = Session::new();
+        drop(TempResource::new(&session, path.clone()));
+        assert!(!path.exists());
+
+        // An exit skipping the drops removes what the session still tracks, only its own
+        let (kept, other) = (dir.join("kept"), dir.join("other"));
+        fs::write(&kept, "").unwrap();
+        fs::write(&other, "").unwrap();
+        let _kept = TempResource::new(&session, kept.clone());
+        let _other = TempResource::new(&Session::new(), other.clone());
+        remove_live(&session);
+        assert!(!kept.exists());
+        assert!(other.exists());
+    }
+}
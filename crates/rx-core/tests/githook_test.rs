@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use rx_core::{
+        githook::{hook_script, install, is_rx_hook, uninstall},
+        models::config::{CommandType, Config, GitHook},
+    };
+
+    #[test]
+    fn test_git_hook_runs_its_configs_as_a_composite() {
+        let mut config = Config::default();
+        let hook = GitHook {
+            run: vec!["fmt".into(), "test::default".into()],
+            parallel: true,
+        };
+        let composite = hook.to_composite(&config).unwrap();
+        assert_eq!(composite.command_type, CommandType::Composite);
+        assert_eq!(composite.steps, ["fmt::default", "test::default"]);
+        assert!(composite.parallel);
+
+        config.hooks.git.insert("pre-commit".into(), hook);
+        assert!(config.validate().is_ok());
+
+        config.hooks.git.insert(
+            "pre-comit".into(),
+            GitHook {
+                run: vec!["lint".into()],
+                parallel: false,
+            },
+        );
+        assert!(config.validate().is_err());
+        config.hooks.git.remove("pre-comit");
+
+        config.hooks.git.get_mut("pre-commit").unwrap().run = vec!["test::missing".into()];
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("hooks.git.pre-commit.run"), "{}", err);
+    }
+
+    #[test]
+    fn test_install_leaves_foreign_hooks_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = hook_script(
+            "pre-push",
+            Path::new("/usr/bin/rx"),
+            Path::new("/work/rx.toml"),
+        );
+        assert!(
+            script.ends_with("exec /usr/bin/rx --config /work/rx.toml hook run pre-push \"$@\"\n")
+        );
+
+        let path = install(dir.path(), "pre-push", &script, false).unwrap();
+        assert!(is_rx_hook(&path));
+        assert!(install(dir.path(), "pre-push", &script, false).is_ok());
+        assert!(uninstall(dir.path(), "pre-push").unwrap());
+
+        fs::write(&path, "#!/bin/sh\nexit 0\n").unwrap();
+        assert!(install(dir.path(), "pre-push", &script, false).is_err());
+        assert!(!uninstall(dir.path(), "pre-push").unwrap());
+        assert!(install(dir.path(), "pre-push", &script, true).is_ok());
+        assert!(is_rx_hook(&path));
+    }
+}
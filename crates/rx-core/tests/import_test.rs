@@ -0,0 +1,143 @@
+#[cfg(test)]
+mod tests {
+    use rx_core::{
+        import::{parse_bacon, parse_justfile, parse_makefile, ImportFormat},
+        models::config::CommandType,
+    };
+
+    #[test]
+    fn test_justfile_recipes_become_script_configs() {
+        let justfile = r#"
+set shell := ["bash", "-c"]
+version := "1.2"
+
+# Build the release binary
+build:
+    cargo build --release
+
+[private]
+test filter="" *args: build
+    cargo test {{filter}} -- {{args}}
+
+release: build (tag "v1")
+    @echo releasing {{version}}
+    -rm -rf dist
+
+ci: build test
+"#;
+        let recipes = parse_justfile(justfile);
+        let names: Vec<&str> = recipes.iter().map(|recipe| recipe.name.as_str()).collect();
+        assert_eq!(names, ["build", "test", "release", "ci"]);
+
+        let build = recipes[0].to_details();
+        assert_eq!(build.command, "cargo build --release");
+        assert!(build.pre_command.is_empty());
+
+        let test = recipes[1].to_details();
+        assert_eq!(
+            test.command,
+            "set -e\nfilter=\"${1-}\"; shift $(( $# > 0 ))\ncargo test \"$filter\" -- \"$@\""
+        );
+        assert!(test.pre_command.contains("build"));
+
+        let release = &recipes[2];
+        assert_eq!(release.dependencies, ["build", "tag"]);
+        assert_eq!(release.notes.len(), 1, "the argument of tag is dropped");
+        assert_eq!(
+            release.to_details().command,
+            "set -e\necho releasing 1.2\nrm -rf dist || true"
+        );
+
+        let ci = recipes[3].to_details();
+        assert_eq!(ci.command_type, CommandType::Composite);
+        assert_eq!(ci.steps, ["build", "test"]);
+    }
+
+    #[test]
+    fn test_makefile_targets_become_script_configs() {
+        let makefile = "CARGO ?= cargo
+FLAGS := --release
+OUT = target/app
+
+.PHONY: all build
+
+all: build
+
+build:
+\t@$(CARGO) build $(FLAGS) \\
+\t\t--locked
+
+\t-echo $$HOME
+
+$(OUT): src/main.rs build
+\tcp target/release/app $@ # from $<
+
+%.o: %.c
+\tcc $<
+";
+        let recipes = parse_makefile(makefile);
+        let names: Vec<&str> = recipes.iter().map(|recipe| recipe.name.as_str()).collect();
+        assert_eq!(names, ["all", "build", "target/app"]);
+
+        assert_eq!(recipes[0].to_details().steps, ["build"]);
+        assert_eq!(
+            recipes[1].to_details().command,
+            "set -e\ncargo build --release --locked\necho $HOME || true"
+        );
+
+        let app = &recipes[2];
+        assert_eq!(app.dependencies, ["build"]);
+        assert_eq!(app.inputs, ["src/main.rs"]);
+        assert_eq!(app.outputs, ["target/app"]);
+        assert_eq!(
+            app.body,
+            ["cp target/release/app target/app # from src/main.rs"]
+        );
+    }
+
+    #[test]
+    fn test_bacon_jobs_and_cargo_watch_recipes_become_watched_configs() {
+        let bacon = r#"
+default_job = "check"
+
+[jobs.clippy]
+command = ["cargo", "clippy", "--all-targets", "--color", "always"]
+need_stdout = false
+
+[jobs.run]
+command = ["cargo", "run", "--", "--port", "8080"]
+watch = ["assets"]
+default_watch = false
+env = { RUST_LOG = "debug" }
+kill = ["kill", "-s", "INT"]
+"#;
+        let recipes = parse_bacon(bacon).unwrap();
+        let names: Vec<&str> = recipes.iter().map(|recipe| recipe.name.as_str()).collect();
+        assert_eq!(names, ["clippy", "run"]);
+
+        let clippy = recipes[0].to_details();
+        assert_eq!(clippy.command, "cargo clippy --all-targets --color always");
+        assert!(clippy.watch.is_empty());
+
+        let run = recipes[1].to_details();
+        assert_eq!(run.command, "cargo run -- --port 8080");
+        assert_eq!(run.watch, ["assets"]);
+        assert_eq!(run.env["RUST_LOG"], "debug");
+        assert_eq!(recipes[1].notes.len(), 1);
+        assert!(parse_bacon("jobs = 1").is_err());
+
+        let justfile = r#"
+dev:
+    cargo watch -c -w src -w templates -x 'test -- --nocapture' -s "echo done"
+
+check:
+    cargo-watch
+"#;
+        let recipes = ImportFormat::Just.parse(justfile).unwrap();
+        let dev = recipes[0].to_details();
+        assert_eq!(dev.command, "cargo test -- --nocapture && echo done");
+        assert_eq!(dev.watch, ["src", "templates"]);
+        assert!(recipes[0].notes.is_empty());
+        assert_eq!(recipes[1].to_details().command, "cargo check");
+    }
+}
@@ -0,0 +1,20 @@
+#[cfg(test)]
+mod tests {
+    use rx_core::{executor::shutdown::ShutdownSignal, keys::KeyAction};
+
+    #[test]
+    fn test_keys_map_to_actions() {
+        assert_eq!(KeyAction::from_key(b'r'), Some(KeyAction::Restart));
+        assert_eq!(KeyAction::from_key(b'Q'), Some(KeyAction::Stop));
+        assert_eq!(KeyAction::from_key(b'c'), Some(KeyAction::ClearScreen));
+        assert_eq!(KeyAction::from_key(b'p'), Some(KeyAction::TogglePause));
+        assert_eq!(KeyAction::from_key(b'x'), None);
+
+        // A restart stops the run with the signal, then clears it for the next one
+        let shutdown = ShutdownSignal::new();
+        shutdown.trigger();
+        assert!(shutdown.is_triggered());
+        shutdown.reset();
+        assert!(!shutdown.is_triggered());
+    }
+}
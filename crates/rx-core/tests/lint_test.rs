@@ -0,0 +1,106 @@
+#[cfg(test)]
+mod tests {
+    use rx_core::{
+        builders::config::ConfigBuilder,
+        diagnostics::{parse_message, CargoMessage, Diagnostic, DiagnosticSummary},
+        events::RunEvent,
+        executor::{runner::Runner, shutdown::ShutdownSignal},
+        models::config::{CommandContext, Config},
+    };
+
+    #[test]
+    fn test_fix_and_clippy_diagnostics() {
+        let mut config = Config::default();
+        config
+            .commands
+            .get_or_default_config(CommandContext::Lint)
+            .update_config(
+                "strict",
+                ConfigBuilder::new(CommandContext::Lint)
+                    .params("--all-targets -- -D warnings")
+                    .build()
+                    .unwrap(),
+            );
+
+        let argv = |context: CommandContext, key: &str, fix: bool| {
+            let runner = Runner::new(&config, context, ShutdownSignal::new()).fix(fix);
+            let command = runner
+                .build_command(runner.details(key).unwrap(), &[])
+                .unwrap();
+            command
+                .get_args()
+                .map(|arg| arg.to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        assert_eq!(
+            argv(CommandContext::Lint, "strict", false),
+            "clippy --all-targets -- -D warnings"
+        );
+        assert_eq!(
+            argv(CommandContext::Lint, "strict", true),
+            "clippy --all-targets --fix --allow-dirty -- -D warnings"
+        );
+        assert_eq!(argv(CommandContext::Fmt, "default", false), "fmt --check");
+        assert_eq!(argv(CommandContext::Fmt, "default", true), "fmt");
+
+        let line = r#"{"reason":"compiler-message","package_id":"app 0.1.0","message":{"level":"warning","message":"unneeded `return` statement","code":{"code":"clippy::needless_return","explanation":null},"spans":[{"file_name":"src/main.rs","line_start":3,"column_start":5,"is_primary":true}],"rendered":"warning: unneeded `return` statement\n"}}"#;
+        let Some(CargoMessage::Diagnostic(diagnostic)) = parse_message(line) else {
+            panic!("not a diagnostic: {}", line);
+        };
+        assert_eq!(
+            RunEvent::Diagnostic {
+                label: "lint::default".into(),
+                diagnostic
+            }
+            .to_json(),
+            r#"{"event":"diagnostic","label":"lint::default","level":"warning","message":"unneeded `return` statement","code":"clippy::needless_return","file":"src/main.rs","line":3,"column":5,"rendered":"warning: unneeded `return` statement\n"}"#
+        );
+        assert_eq!(
+            parse_message(r#"{"reason":"build-finished","success":true}"#),
+            Some(CargoMessage::Other)
+        );
+        assert_eq!(parse_message("    Checking app v0.1.0"), None);
+    }
+
+    #[test]
+    fn test_json_messages_and_summary() {
+        let config = Config::default();
+        let argv = |context: CommandContext| {
+            let runner = Runner::new(&config, context, ShutdownSignal::new()).json_messages(true);
+            let command = runner
+                .build_command(runner.details("default").unwrap(), &[])
+                .unwrap();
+            command
+                .get_args()
+                .map(|arg| arg.to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        assert_eq!(argv(CommandContext::Build), "build --message-format=json");
+        assert_eq!(
+            argv(CommandContext::Fmt),
+            "fmt --check",
+            "fmt doesn't compile"
+        );
+
+        let diagnostic = |level: &str, file: Option<&str>| Diagnostic {
+            level: level.into(),
+            message: "unused variable: `x`".into(),
+            code: Some("unused_variables".into()),
+            file: file.map(String::from),
+            line: file.map(|_| 2),
+            column: file.map(|_| 9),
+            rendered: None,
+        };
+        let mut summary = DiagnosticSummary::default();
+        summary.add(diagnostic("warning", Some("src/main.rs")));
+        summary.add(diagnostic("error", Some("src/lib.rs")));
+        summary.add(diagnostic("warning", None));
+        assert_eq!((summary.errors, summary.warnings), (1, 1));
+        assert_eq!(
+            summary.report(1),
+            "1 error, 1 warning\n  src/main.rs:2:9: warning[unused_variables]: unused variable: `x`\n  ... and 1 more"
+        );
+    }
+}
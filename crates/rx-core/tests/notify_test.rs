@@ -0,0 +1,100 @@
+#[cfg(test)]
+mod tests {
+    use rx_core::{
+        models::config::{Config, NotifyOn},
+        notify::{completion_message, format_duration, webhook_message, webhook_payload},
+    };
+    use std::time::Duration;
+
+    #[test]
+    fn test_webhook_messages_for_slack_and_discord() {
+        let message = webhook_message(
+            "script::deploy",
+            Some(3),
+            Duration::from_secs(75),
+            &["error: connection refused".to_string()],
+            "build-box",
+        );
+        assert_eq!(
+            message,
+            "rx: script::deploy failed with exit code 3 after 1m 15s on build-box\n```\nerror: connection refused\n```"
+        );
+        assert_eq!(
+            webhook_payload("https://hooks.slack.com/services/T0/B0/x", "done"),
+            r#"{"text":"done"}"#
+        );
+        assert_eq!(
+            webhook_payload("https://discord.com/api/webhooks/1/x", "done"),
+            r#"{"content":"done"}"#
+        );
+
+        let mut config: Config = toml::from_str(
+            r#"
+            [commands.script]
+            default = "deploy"
+
+            [commands.script.configs.deploy]
+            command = "./deploy.sh"
+            notify_on = ["failure"]
+            "#,
+        )
+        .unwrap();
+        let details = &config.commands.script.as_ref().unwrap().configs["deploy"];
+        assert_eq!(details.notify_on, [NotifyOn::Failure]);
+        assert_eq!(
+            config.validate().unwrap_err().to_string(),
+            "Invalid value for 'commands.script.configs.deploy.notify_on': there is no webhook in [notifications] to post to"
+        );
+
+        config.notifications.webhooks = vec!["hooks.slack.com/services/T0/B0/x".to_string()];
+        assert_eq!(
+            config.validate().unwrap_err().to_string(),
+            "Invalid value for 'notifications.webhooks': 'hooks.slack.com/services/T0/B0/x' is not an http:// or https:// URL"
+        );
+        config.notifications.webhooks[0].insert_str(0, "https://");
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn test_completion_notifications() {
+        assert_eq!(format_duration(Duration::from_millis(2340)), "2.3s");
+        assert_eq!(format_duration(Duration::from_secs(125)), "2m 05s");
+        assert_eq!(
+            completion_message("run::server", Some(0), Duration::from_secs(3)),
+            (
+                "rx: run::server succeeded".to_string(),
+                "Finished in 3.0s".to_string()
+            )
+        );
+        assert_eq!(
+            completion_message("test::unit", Some(101), Duration::from_secs(61)).0,
+            "rx: test::unit failed (101)"
+        );
+        assert_eq!(
+            completion_message("run::server", None, Duration::ZERO).0,
+            "rx: run::server was stopped"
+        );
+
+        let config: Config = toml::from_str(
+            r#"
+            [settings]
+            notify = true
+
+            [commands.run]
+            default = "server"
+
+            [commands.run.configs.server]
+            command = "run"
+
+            [commands.run.configs.watch]
+            command = "watch"
+            notify = false
+            "#,
+        )
+        .unwrap();
+        let configs = &config.commands.run.as_ref().unwrap().configs;
+        let notifies = |key: &str| configs[key].notify.unwrap_or(config.settings.notify);
+        assert!(notifies("server"));
+        assert!(!notifies("watch"));
+    }
+}
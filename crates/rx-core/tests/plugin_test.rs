@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod tests {
+    use rx_core::{
+        executor::{
+            plugin::{install_args, listed_subcommands, plugin},
+            runner::Runner,
+            shutdown::ShutdownSignal,
+        },
+        models::config::{CommandContext, Config},
+    };
+
+    fn argv(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_missing_cargo_subcommands_name_their_install() {
+        assert_eq!(
+            plugin(&argv("cargo +nightly udeps --all-targets")),
+            Some("udeps")
+        );
+        assert_eq!(plugin(&argv("cargo --locked nextest run")), Some("nextest"));
+        assert_eq!(plugin(&argv("cargo build --release")), None);
+        assert_eq!(
+            listed_subcommands("Installed Commands:\n    build    Compile\n    deny\n"),
+            ["build", "deny"]
+        );
+        assert_eq!(install_args("deny", None), ["cargo-deny"]);
+        assert_eq!(
+            install_args("udeps", Some("cargo-udeps --locked")),
+            ["cargo-udeps", "--locked"]
+        );
+
+        let config: Config = toml::from_str(
+            r#"
+            [commands.lint]
+            default = "unused"
+
+            [commands.lint.configs.unused]
+            command = "rx-missing-plugin"
+            install_hint = "rx-missing-plugin --locked"
+            "#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+
+        let err = Runner::new(&config, CommandContext::Lint, ShutdownSignal::new())
+            .run(None, &[])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "cargo rx-missing-plugin is not installed, install it with: cargo install rx-missing-plugin --locked, or set auto_install = true"
+        );
+
+        let shell: Result<Config, _> = toml::from_str(
+            r#"
+            [commands.script.configs.default]
+            type = "shell"
+            command = "echo"
+            auto_install = true
+            "#,
+        );
+        assert!(shell
+            .unwrap()
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("only cargo configs run a subcommand cargo can install"));
+    }
+}
@@ -0,0 +1,43 @@
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use rx_core::{models::config::CommandType, prompt::Prompt};
+
+    #[test]
+    fn test_prompt_repeats_until_the_answer_is_valid() {
+        let input = Cursor::new("ruby\n\nA=1\nlower=2\nB=2\n\nmaybe\nN\n\n");
+        let mut output = Vec::new();
+        let mut prompt = Prompt::new(input, &mut output);
+
+        let command_type = prompt
+            .ask("Command type", Some("cargo"), |answer| {
+                answer.parse::<CommandType>().map_err(|err| err.to_string())
+            })
+            .unwrap();
+        assert_eq!(command_type, CommandType::Cargo, "empty takes the default");
+
+        let env = prompt
+            .ask_many("Env", |answer| match answer.split_once('=') {
+                Some((key, value)) if key.chars().all(|c| c.is_ascii_uppercase()) => {
+                    Ok((key.to_string(), value.to_string()))
+                }
+                _ => Err("not ALL_CAPS".to_string()),
+            })
+            .unwrap();
+        assert_eq!(env, [("A".into(), "1".into()), ("B".into(), "2".into())]);
+        assert!(!prompt.confirm("Write", true).unwrap());
+        assert!(
+            prompt.confirm("Write", true).unwrap(),
+            "empty takes the default"
+        );
+        assert!(prompt.ask("More", None, |_| Ok(())).is_err(), "input ended");
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("Command type [cargo]: "));
+        assert!(output.contains("Unknown command type 'ruby'"));
+        assert!(output.contains("not ALL_CAPS"));
+        assert!(output.contains("Write [Y/n]: "));
+        assert!(output.contains("answer y or n"));
+    }
+}
@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use rx_core::{
+        models::{config::CommandType, params::Params},
+        record::recorded_details,
+    };
+
+    fn words(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_recorded_details_keep_the_variables_and_directory_of_the_command() {
+        let workspace = Path::new("/repo");
+        let details = recorded_details(
+            &words("RUST_LOG=debug PORT=3000 cargo run -p api"),
+            &[("PORT".into(), "8080".into())],
+            Path::new("/repo/services/api"),
+            workspace,
+        )
+        .unwrap();
+        assert_eq!(details.command_type, CommandType::Cargo);
+        assert_eq!(details.command.line(), "run");
+        assert_eq!(details.params, Params::from("-p api"));
+        assert_eq!(details.env["RUST_LOG"], "debug");
+        assert_eq!(details.env["PORT"], "8080");
+        assert_eq!(details.working_directory, "${workspaceFolder}/services/api");
+
+        let mut command = words("npm run dev --");
+        command.push("--host 0.0.0.0".into());
+        let details = recorded_details(&command, &[], workspace, workspace).unwrap();
+        assert_eq!(details.command_type, CommandType::Shell);
+        assert_eq!(details.command.line(), "npm run dev -- '--host 0.0.0.0'");
+        assert!(details.env.is_empty());
+        assert_eq!(details.working_directory, "${workspaceFolder}");
+
+        let details = recorded_details(
+            &words("cargo +nightly fmt"),
+            &[],
+            Path::new("/tmp"),
+            workspace,
+        )
+        .unwrap();
+        assert_eq!(details.command_type, CommandType::Shell);
+        assert_eq!(details.working_directory, "/tmp");
+
+        assert!(recorded_details(&words("RUST_LOG=debug"), &[], workspace, workspace).is_err());
+    }
+}
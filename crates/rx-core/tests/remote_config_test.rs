@@ -0,0 +1,90 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use rx_core::{
+        config_diff::ConfigChange,
+        models::config::{CommandContext, Config, RemoteConfig},
+        remote_config::sync,
+    };
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_sync_merges_the_team_config_under_the_local_one() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let repo = temp_dir.path().join("team");
+        let cache = temp_dir.path().join("cache");
+        fs::create_dir_all(&repo).unwrap();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args(["-c", "user.name=rx", "-c", "user.email=rx@example.com"])
+                .args(args)
+                .current_dir(&repo)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        };
+        let publish = |params: &str| {
+            fs::write(
+                repo.join("rx.toml"),
+                format!(
+                    "[commands.run.configs.app]\ncommand = \"run\"\nparams = \"--release\"\n\n\
+                     [commands.run.configs.server]\ncommand = \"run\"\nparams = \"{}\"\n",
+                    params
+                ),
+            )
+            .unwrap();
+            git(&["add", "rx.toml"]);
+            git(&["commit", "--quiet", "--message", "publish"]);
+        };
+        git(&["init", "--quiet"]);
+        publish("-p server");
+
+        let local: Config = toml::from_str(&format!(
+            "[remote_config]\ngit = \"{}\"\n\n[commands.run.configs.app]\ncommand = \"run\"\n",
+            repo.display()
+        ))
+        .unwrap();
+        let remote = local.remote_config.clone().unwrap();
+        assert!(remote.is_stale(&cache));
+        let report = sync(&local, &cache).unwrap();
+        assert!(!remote.is_stale(&cache));
+        assert_eq!(
+            report.updates,
+            [
+                ConfigChange::Added("run::app".into()),
+                ConfigChange::Added("run::server".into())
+            ]
+        );
+
+        let mut config = local.clone();
+        config.merge_remote(&cache).unwrap();
+        let run = config.commands.get_config(CommandContext::Run).unwrap();
+        assert_eq!(run.configs["app"].params.to_string(), "");
+        assert_eq!(run.configs["server"].params.to_string(), "-p server");
+        assert_eq!(
+            config.origin(CommandContext::Run, "server"),
+            Some(remote.cache_path(&cache).as_path())
+        );
+        assert!(!config.to_toml_string().unwrap().contains("server"));
+
+        publish("-p server --release");
+        let report = sync(&config, &cache).unwrap();
+        assert_eq!(report.updates.len(), 1);
+        assert_eq!(
+            report.updates[0].to_string(),
+            "~ run::server.params: \"-p server\" -> \"-p server --release\""
+        );
+        assert_eq!(
+            report.drift[0].to_string(),
+            "~ run::app.params: \"--release\" -> unset"
+        );
+
+        let both = RemoteConfig {
+            url: Some("https://example.com/rx.toml".into()),
+            git: Some(repo.display().to_string()),
+            ..Default::default()
+        };
+        assert!(both.check().is_err());
+    }
+}
@@ -0,0 +1,40 @@
+#[cfg(test)]
+mod tests {
+    use rx_core::{
+        models::config::CommandContext,
+        rerun::{parse_label, LastRun},
+        store::Store,
+    };
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_last_run_round_trip_and_labels() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let store = Store::at(temp_dir.path().to_path_buf());
+        assert_eq!(LastRun::load(&store).unwrap(), None);
+
+        let last_run = LastRun {
+            label: "test::all".into(),
+            args: vec!["--nocapture".into()],
+            env: [("RUST_LOG".to_string(), "debug".to_string())].into(),
+            failed: vec!["test::unit".into(), "build::release [target=x86_64]".into()],
+        };
+        last_run.save(&store).unwrap();
+        assert_eq!(LastRun::load(&store).unwrap(), Some(last_run));
+
+        assert_eq!(
+            parse_label("test::unit").unwrap(),
+            (CommandContext::Test, "unit", None)
+        );
+        assert_eq!(
+            parse_label("build::release [target=x86_64, os=linux]").unwrap(),
+            (
+                CommandContext::Build,
+                "release",
+                Some("target=x86_64, os=linux")
+            )
+        );
+        assert!(parse_label("unit").is_err());
+        assert!(parse_label("nope::unit").is_err());
+    }
+}
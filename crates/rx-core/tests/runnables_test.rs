@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use rx_core::{
+        models::config::{CommandContext, Config},
+        runnables::{runnables, RunnableKind},
+        workspace::parse_metadata,
+    };
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_runnables_at_a_location() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let dir = temp_dir.path();
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::create_dir_all(dir.join("benches")).unwrap();
+        fs::write(
+            dir.join("Cargo.toml"),
+            "[package]\nname = \"demo\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        let main = dir.join("src/main.rs");
+        fs::write(
+            &main,
+            "fn main() {}\n\n#[cfg(test)]\nmod tests {\n    #[test]\n    fn test_it() {\n        assert!(true);\n    }\n}\n",
+        )
+        .unwrap();
+        let bench = dir.join("benches/parse.rs");
+        fs::write(&bench, "fn main() {}\n").unwrap();
+
+        let metadata = format!(
+            r#"{{"packages": [{{
+                "name": "demo",
+                "manifest_path": "{manifest}",
+                "targets": [
+                    {{"name": "demo", "kind": ["bin"], "src_path": "{main}"}},
+                    {{"name": "parse", "kind": ["bench"], "src_path": "{bench}"}}
+                ]
+            }}]}}"#,
+            manifest = dir.join("Cargo.toml").display(),
+            main = main.display(),
+            bench = bench.display(),
+        );
+        let members = parse_metadata(&metadata).unwrap();
+        let config = Config::default();
+
+        let found = runnables(&config, &members, &main, 7).unwrap();
+        let labels: Vec<(RunnableKind, &str)> = found
+            .iter()
+            .map(|runnable| (runnable.kind, runnable.label.as_str()))
+            .collect();
+        assert_eq!(
+            labels,
+            [
+                (RunnableKind::Test, "test tests::test_it"),
+                (RunnableKind::TestModule, "test module tests"),
+                (RunnableKind::Run, "run demo"),
+            ]
+        );
+        assert_eq!(found[0].context, CommandContext::Test);
+        assert_eq!(
+            found[0].command_line,
+            "cargo test -p demo tests::test_it -- --exact"
+        );
+        assert_eq!(found[2].command_line, "cargo run --package demo --bin demo");
+
+        let found = runnables(&config, &members, &bench, 1).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].args, ["--bench", "parse"]);
+        assert_eq!(
+            found[0].command_line,
+            "cargo bench --package demo --bench parse"
+        );
+
+        let outside = TempDir::new().expect("Failed to create a temporary directory");
+        assert!(runnables(&config, &members, &outside.path().join("x.rs"), 1).is_err());
+    }
+}
@@ -0,0 +1,59 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use rx_core::{
+        checksum::sha256_hex,
+        executor::script::fetch,
+        models::{command::CommandSource, config::Config},
+    };
+
+    #[test]
+    fn test_sha256_matches_the_reference_digests() {
+        assert_eq!(
+            sha256_hex(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            sha256_hex(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+    }
+
+    #[test]
+    fn test_script_from_url_is_pinned_by_its_sha256() {
+        let body = "echo deploying\n";
+        let sha256 = sha256_hex(body.as_bytes());
+        let mut config: Config = toml::from_str(&format!(
+            "[commands.script]\ndefault = \"deploy\"\n[commands.script.configs.deploy]\ntype = \"shell\"\ncommand = {{ url = \"https://example.invalid/deploy.sh\", sha256 = \"{}\" }}\n",
+            sha256
+        ))
+        .unwrap();
+        config.validate().unwrap();
+        let details = &config.commands.script.as_ref().unwrap().configs["deploy"];
+        let CommandSource::Url(script) = &details.command else {
+            panic!("expected a script URL, got {:?}", details.command);
+        };
+
+        // A kept download is used as long as it matches
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(&sha256), body).unwrap();
+        assert_eq!(fetch(script, dir.path()).unwrap(), body);
+
+        // One that changed is dropped, and the unreachable URL can't replace it
+        fs::write(dir.path().join(&sha256), "echo tampered\n").unwrap();
+        assert!(fetch(script, dir.path()).is_err());
+        assert!(!dir.path().join(&sha256).exists());
+
+        let script = config.commands.script.as_mut().unwrap();
+        let deploy = script.configs.get_mut("deploy").unwrap();
+        if let CommandSource::Url(url) = &mut deploy.command {
+            url.url = "http://example.invalid/deploy.sh".into();
+        }
+        assert!(config.validate().is_err());
+    }
+}
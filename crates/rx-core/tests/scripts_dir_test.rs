@@ -0,0 +1,75 @@
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use rx_core::{
+        builders::config::ConfigBuilder,
+        executor::{runner::Runner, shutdown::ShutdownSignal},
+        interpolation::InterpolationContext,
+        models::config::{CommandContext, CommandType, Config},
+    };
+    use tempfile::TempDir;
+
+    #[cfg(unix)]
+    fn write_script(path: &Path, body: &str, mode: u32) {
+        use std::os::unix::fs::PermissionsExt;
+
+        fs::write(path, body).unwrap();
+        fs::set_permissions(path, fs::Permissions::from_mode(mode)).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_executables_of_the_scripts_dir_become_script_configs() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let root = temp_dir.path();
+        fs::write(root.join("Cargo.toml"), "[package]\nname = \"app\"\n").unwrap();
+        let scripts = root.join("scripts");
+        fs::create_dir(&scripts).unwrap();
+        write_script(
+            &scripts.join("deploy.sh"),
+            "#!/bin/sh\necho \"deployed $1\" > deployed.txt\n",
+            0o755,
+        );
+        write_script(&scripts.join("lint"), "#!/bin/sh\nexit 1\n", 0o755);
+        write_script(&scripts.join("notes.txt"), "not a script\n", 0o644);
+        write_script(&scripts.join(".hidden"), "#!/bin/sh\n", 0o755);
+
+        let mut config = Config::default();
+        let lint = ConfigBuilder::new(CommandContext::Script)
+            .command_type(CommandType::Shell)
+            .command("cargo clippy")
+            .build()
+            .unwrap();
+        let scripts_config = config
+            .commands
+            .get_or_default_config(CommandContext::Script);
+        scripts_config.update_config("lint", lint);
+        scripts_config.scripts_dir = Some("scripts/".to_string());
+        let saved = config.to_toml_string().unwrap();
+
+        config.merge_scripts_dir(&root.join("scripts"));
+        let mut keys = config.commands.get_configs(CommandContext::Script);
+        keys.sort();
+        assert_eq!(keys, ["deploy.sh", "lint"]);
+        // The configured entry wins, and the scanned one isn't saved
+        assert_eq!(config.origin(CommandContext::Script, "lint"), None);
+        assert_eq!(
+            config.origin(CommandContext::Script, "deploy.sh"),
+            Some(scripts.join("deploy.sh").as_path())
+        );
+        assert_eq!(config.to_toml_string().unwrap(), saved);
+
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new())
+            .cwd(root)
+            .interpolation(InterpolationContext::from_dir(root));
+        let status = runner
+            .run(Some("deploy.sh"), &["staging".to_string()])
+            .unwrap();
+        assert!(status.success());
+        assert_eq!(
+            fs::read_to_string(root.join("deployed.txt")).unwrap(),
+            "deployed staging\n"
+        );
+    }
+}
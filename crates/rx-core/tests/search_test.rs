@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod tests {
+    use rx_core::{
+        models::config::Config,
+        search::{search, Pattern},
+    };
+
+    #[test]
+    fn test_search_finds_matching_fields_across_contexts() {
+        let config: Config = toml::from_str(
+            r#"
+            [commands.build.configs.release]
+            command = "build"
+            params = "--release --locked"
+
+            [commands.script.configs.deploy]
+            type = "shell"
+            command = "./deploy.sh"
+            description = "Ship the release build"
+            env = { REGION = "eu-west-1", API_TOKEN = "release-token" }
+            "#,
+        )
+        .unwrap();
+        let found = |pattern: &Pattern| -> Vec<String> {
+            search(&config, pattern)
+                .into_iter()
+                .map(|found| format!("{} {} {}", found.label, found.field, found.text))
+                .collect()
+        };
+
+        assert_eq!(
+            found(&Pattern::fuzzy("RELEASE")),
+            [
+                "build::release key release",
+                "build::release params --release --locked",
+                "script::deploy description Ship the release build",
+            ]
+        );
+        assert_eq!(
+            found(&Pattern::fuzzy("rlse")),
+            [
+                "build::release key release",
+                "build::release params --release --locked",
+                "script::deploy description Ship the release build",
+            ]
+        );
+        assert_eq!(
+            found(&Pattern::fuzzy("dply"))[0],
+            "script::deploy key deploy"
+        );
+        assert_eq!(
+            found(&Pattern::regex(r"^eu-\w+-\d$").unwrap()),
+            ["script::deploy env.REGION eu-west-1"]
+        );
+        assert!(found(&Pattern::fuzzy("token")).is_empty());
+        assert!(Pattern::regex("(").is_err());
+    }
+}
@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod tests {
+    use rx_core::{
+        errors::ExecutionError,
+        models::config::{CommandContext, Config},
+        secret::{decode, encode, encrypt, is_sealed, keychain_entry, reveal},
+    };
+
+    #[test]
+    fn test_secret_values_are_revealed_only_when_sealed() {
+        for bytes in [
+            &b""[..],
+            b"f",
+            b"fo",
+            b"foo",
+            b"age-encryption.org/v1\n\xff\x00",
+        ] {
+            assert_eq!(decode(&encode(bytes)).as_deref(), Some(bytes));
+        }
+        assert_eq!(encode(b"hunter2"), "aHVudGVyMg==");
+        assert_eq!(decode("aHVudGVyMg"), None);
+        assert_eq!(decode("aHVu dGVy"), None);
+
+        assert!(is_sealed("enc:aHVudGVyMg=="));
+        assert!(is_sealed("keychain:DB_PASSWORD"));
+        assert!(!is_sealed("postgres://localhost"));
+        assert_eq!(
+            reveal("DATABASE_URL", "postgres://localhost").unwrap(),
+            "postgres://localhost"
+        );
+        assert!(matches!(
+            reveal("DB_PASSWORD", "enc:not base64!"),
+            Err(ExecutionError::Secret(name, _)) if name == "DB_PASSWORD"
+        ));
+        assert!(encrypt("DB_PASSWORD", "hunter2", &[])
+            .unwrap_err()
+            .to_string()
+            .contains("settings.secret_recipients"));
+        assert!(encrypt(
+            "DB_PASSWORD",
+            "hunter2",
+            &[
+                "age1qyqszqgpqyqszqgpqyqszqgpqyqszqgp".to_string(),
+                "ops@example.com".to_string()
+            ]
+        )
+        .unwrap_err()
+        .to_string()
+        .contains("mixes age recipients and GPG key ids"));
+    }
+
+    #[test]
+    fn test_keychain_values_are_read_from_tables() {
+        assert_eq!(
+            keychain_entry("my-service/api-key"),
+            ("my-service", "api-key")
+        );
+        assert_eq!(keychain_entry("DB_PASSWORD"), ("rx", "DB_PASSWORD"));
+
+        let config: Config = toml::from_str(
+            r#"
+            [commands.script]
+            default = "default"
+
+            [commands.script.configs.default]
+            type = "shell"
+            command = "echo $API_KEY"
+            env = { API_KEY = { keychain = "my-service/api-key" }, MODE = "dev" }
+            "#,
+        )
+        .unwrap();
+        let details = &config
+            .commands
+            .get_config(CommandContext::Script)
+            .unwrap()
+            .configs["default"];
+        assert_eq!(details.env["API_KEY"], "keychain:my-service/api-key");
+        assert!(details.env_is_sealed("API_KEY"));
+        assert!(!details.env_is_sealed("MODE"));
+
+        let saved = config.to_toml_string().unwrap();
+        assert!(saved.contains("keychain = \"my-service/api-key\""));
+        assert_eq!(toml::from_str::<Config>(&saved).unwrap(), config);
+    }
+}
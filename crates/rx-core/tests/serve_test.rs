@@ -0,0 +1,43 @@
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use rx_core::serve::{is_allowed, sse_event, HttpRequest, Route};
+
+    #[test]
+    fn test_read_and_route_a_dashboard_request() {
+        let body = r#"{"context":"test","key":"unit"}"#;
+        let raw = format!(
+            "POST /api/runs?from=ui HTTP/1.1\r\nHost: localhost:7878\r\nOrigin: http://localhost:7878\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
+
+        let mut request = HttpRequest::read(&mut Cursor::new(raw)).unwrap().unwrap();
+
+        assert_eq!(request.path, "/api/runs");
+        assert_eq!(request.body, body);
+        assert_eq!(
+            Route::find(&request.method, &request.path),
+            Some(Route::StartRun)
+        );
+        assert_eq!(
+            Route::find("GET", "/api/runs/3/events"),
+            Some(Route::RunEvents(3))
+        );
+        assert_eq!(Route::find("GET", "/api/runs/x/events"), None);
+        assert!(is_allowed(&request, 7878));
+
+        // Another site's page, or a domain rebound to 127.0.0.1
+        request.origin = Some("http://example.com".to_string());
+        assert!(!is_allowed(&request, 7878));
+        request.origin = None;
+        request.host = Some("example.com:7878".to_string());
+        assert!(!is_allowed(&request, 7878));
+
+        assert_eq!(
+            sse_event("{\"event\":\"started\"}"),
+            "data: {\"event\":\"started\"}\n\n"
+        );
+    }
+}
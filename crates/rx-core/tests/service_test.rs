@@ -0,0 +1,145 @@
+#[cfg(test)]
+mod tests {
+    use std::{fs, thread, time::Duration};
+
+    use rx_core::{
+        builders::config::ConfigBuilder,
+        errors::ExecutionError,
+        executor::{runner::Runner, shutdown::ShutdownSignal},
+        models::config::{CommandContext, CommandType, Config, ServiceGroup},
+        service::{
+            self, is_running, log_path, service_keys, trim_log, ServiceRecord, ServiceRegistry,
+        },
+        store::Store,
+    };
+    use tempfile::TempDir;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_services_run_in_the_background_until_stopped() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let store = Store::at(temp_dir.path().to_path_buf());
+
+        let mut config = Config::default();
+        let script = config
+            .commands
+            .get_or_default_config(CommandContext::Script);
+        let mut server = ConfigBuilder::new(CommandContext::Script)
+            .command_type(CommandType::Shell)
+            .command("echo up; sleep 30")
+            .working_directory("")
+            .build()
+            .unwrap();
+        script.update_config("once", server.clone());
+        server.service = true;
+        script.update_config("server", server);
+        assert_eq!(service_keys(&config), ["server"]);
+
+        let runner = Runner::new(&config, CommandContext::Script, ShutdownSignal::new());
+        assert!(matches!(
+            runner.spawn_service("once", &log_path(&store, "once")),
+            Err(ExecutionError::NotAService(label)) if label == "script::once"
+        ));
+
+        let log = log_path(&store, "server");
+        let pid = runner.spawn_service("server", &log).unwrap();
+        let mut registry = ServiceRegistry::default();
+        registry.services.insert(
+            "server".to_string(),
+            ServiceRecord {
+                pid,
+                started_at: 0,
+                log: log.clone(),
+            },
+        );
+        registry.save(&store).unwrap();
+        assert_eq!(ServiceRegistry::load(&store).unwrap(), registry);
+
+        thread::sleep(Duration::from_millis(300));
+        assert!(is_running(pid));
+        assert_eq!(std::fs::read_to_string(&log).unwrap(), "up\n");
+
+        assert!(service::stop(pid, Duration::from_secs(5)));
+        assert!(!is_running(pid));
+
+        let script = config
+            .commands
+            .get_or_default_config(CommandContext::Script);
+        let server = script.configs["server"].clone();
+        config
+            .commands
+            .get_or_default_config(CommandContext::Run)
+            .update_config("default", server);
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("commands.run.configs.default.service"));
+    }
+
+    #[test]
+    fn test_groups_start_services_after_their_dependencies() {
+        let mut config = Config::default();
+        let script = config
+            .commands
+            .get_or_default_config(CommandContext::Script);
+        let mut service = ConfigBuilder::new(CommandContext::Script)
+            .command_type(CommandType::Shell)
+            .command("sleep 30")
+            .build()
+            .unwrap();
+        service.service = true;
+        script.update_config("default", service.clone());
+        script.update_config("db", service.clone());
+        service.depends_on = vec!["db".to_string()];
+        script.update_config("api", service.clone());
+        service.depends_on = vec!["api".to_string(), "db".to_string()];
+        script.update_config("web", service);
+        config.groups.insert(
+            "dev".to_string(),
+            ServiceGroup {
+                services: vec!["web".to_string(), "api".to_string()],
+            },
+        );
+        config.validate().unwrap();
+
+        assert_eq!(
+            config.service_order(&["dev".to_string()]).unwrap(),
+            ["db", "api", "web"]
+        );
+        assert_eq!(
+            config.service_order(&["api".to_string()]).unwrap(),
+            ["db", "api"]
+        );
+        assert_eq!(
+            config.references(CommandContext::Script, "api"),
+            ["script::web depends_on", "groups.dev"]
+        );
+
+        let script = config
+            .commands
+            .get_or_default_config(CommandContext::Script);
+        script.configs.get_mut("db").unwrap().depends_on = vec!["web".to_string()];
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("services depend on each other: api -> db -> web -> api"));
+    }
+
+    #[test]
+    fn test_trim_log_keeps_the_latest_lines() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let log = temp_dir.path().join("web.log");
+        let content: String = (1..=20).map(|line| format!("line {:02}\n", line)).collect();
+        fs::write(&log, &content).unwrap();
+
+        assert!(!trim_log(&log, 1000).unwrap());
+        assert!(!trim_log(&temp_dir.path().join("missing.log"), 10).unwrap());
+        assert!(trim_log(&log, 60).unwrap());
+        assert_eq!(
+            fs::read_to_string(&log).unwrap(),
+            "[rx: log truncated, 136 bytes dropped]\nline 18\nline 19\nline 20\n"
+        );
+    }
+}
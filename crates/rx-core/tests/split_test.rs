@@ -0,0 +1,54 @@
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use rx_core::split::{pane_script, RegionStatus, SplitScreen, UiMode};
+
+    #[test]
+    fn test_split_screen_shows_the_last_lines_of_each_command() {
+        let mut screen = SplitScreen::new(20, 6);
+        for line in ["a1", "a2", "a3"] {
+            screen.push("script::a", line);
+        }
+        screen.push("script::b", "b1\r");
+        screen.set_status("script::a", RegionStatus::Succeeded);
+
+        let frame = screen.frame();
+        let rows: Vec<&str> = frame
+            .trim_start_matches("\x1b[H")
+            .trim_end_matches("\x1b[J")
+            .split("\r\n")
+            .map(|row| row.trim_end_matches("\x1b[K"))
+            .collect();
+
+        assert_eq!(
+            rows,
+            [
+                "✓ script::a ────────",
+                "a2",
+                "a3",
+                "▶ script::b ────────",
+                "b1",
+                "",
+            ]
+        );
+
+        screen.settle(RegionStatus::Failed);
+        assert_eq!(
+            screen.transcript(),
+            "✓ script::a\na1\na2\na3\n✗ script::b\nb1\n"
+        );
+    }
+
+    #[test]
+    fn test_ui_mode_and_pane_script() {
+        assert_eq!("split".parse::<UiMode>().unwrap(), UiMode::Split);
+        assert!("tiled".parse::<UiMode>().is_err());
+
+        let script = pane_script(Path::new("/tmp/rx-panes-1/0.log"), "script::a b", 42);
+        assert!(script.starts_with("tail -n +1 -f /tmp/rx-panes-1/0.log & tail=$!;"));
+        assert!(script.contains("while kill -0 42 2>/dev/null"));
+        assert!(script.contains("rmdir /tmp/rx-panes-1 2>/dev/null"));
+        assert!(script.contains("'script::a b'"));
+    }
+}
@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rx_core::{
+        stats::{Report, ReportEntry, ReportFormat, RunHistory, HISTORY_LIMIT},
+        store::Store,
+    };
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_run_history_stats() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let store = Store::at(temp_dir.path().to_path_buf());
+        assert_eq!(RunHistory::load(&store).unwrap(), RunHistory::default());
+
+        let mut history = RunHistory::default();
+        for seconds in 1..=20 {
+            history.record("test::unit", true, Duration::from_secs(seconds));
+        }
+        history.record("test::unit", false, Duration::from_millis(100));
+        history.record("build::release", false, Duration::from_secs(5));
+        history.save(&store).unwrap();
+
+        let history = RunHistory::load(&store).unwrap();
+        assert_eq!(
+            history.average("test::unit"),
+            Some(Duration::from_millis(10500)),
+            "failed runs don't count towards the durations"
+        );
+        let stats = history.stats();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].label, "build::release");
+        assert_eq!(stats[0].average, None);
+        assert_eq!(stats[0].failure_rate, 1.0);
+        assert_eq!(stats[1].runs, 21);
+        assert_eq!(stats[1].median, Some(Duration::from_secs(10)));
+        assert_eq!(stats[1].p95, Some(Duration::from_secs(19)));
+        assert_eq!(stats[1].failure_rate, 1.0 / 21.0);
+
+        let mut history = RunHistory::default();
+        for _ in 0..HISTORY_LIMIT + 5 {
+            history.record("run::default", true, Duration::from_secs(1));
+        }
+        assert_eq!(history.runs["run::default"].len(), HISTORY_LIMIT);
+    }
+
+    #[test]
+    fn test_report_ranks_the_most_run_failing_and_slowest_configs() {
+        let mut history = RunHistory::default();
+        for _ in 0..3 {
+            history.record("test::unit", true, Duration::from_secs(2));
+        }
+        history.record("test::unit", false, Duration::from_secs(1));
+        history.record("build::release", true, Duration::from_secs(40));
+        history.record("lint::default", false, Duration::from_secs(3));
+
+        let report = Report::new(&history, 2);
+        assert_eq!((report.configs, report.runs, report.failures), (3, 6, 2));
+        let labels = |entries: &[ReportEntry]| {
+            entries
+                .iter()
+                .map(|entry| entry.label.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(labels(&report.most_run), ["test::unit", "build::release"]);
+        assert_eq!(labels(&report.failing), ["lint::default", "test::unit"]);
+        assert_eq!(labels(&report.slowest), ["build::release", "test::unit"]);
+        assert_eq!(report.slowest[1].average_ms, Some(2000));
+
+        let markdown = report.to_markdown("rx");
+        assert!(markdown.starts_with("# rx report for rx\n\n6 runs of 3 configs, 2 failed"));
+        assert!(markdown.contains("| `lint::default` | 1 | 1 | 100% |"));
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(json["failing"][1]["failures"], 1);
+        assert_eq!("json".parse::<ReportFormat>().unwrap(), ReportFormat::Json);
+        assert!("xml".parse::<ReportFormat>().is_err());
+    }
+}
@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use rx_core::{
+        models::config::Config,
+        strict::{edit_distance, unknown_fields},
+    };
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", "env"), 3);
+        assert_eq!(edit_distance("notfy", "notify"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_unknown_fields_suggest_the_closest_known_field() {
+        let dir = std::env::temp_dir().join(format!("rx-strict-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("rx.toml");
+        fs::write(&path, "include = [\"more.toml\"]\n[settings]\nnotfy = true\n[commands.run]\ndefault = \"app\"\n[commands.run.configs.app]\ntype = \"shell\"\ncommand = \"echo app\"\nworking_dir = \"web\"\nenv = { working_dir = \"kept\" }\n").unwrap();
+        fs::write(
+            dir.join("more.toml"),
+            "[commands.test.configs.unit]\ntype = \"shell\"\ncommand = \"echo unit\"\nzzz = 1\n",
+        )
+        .unwrap();
+
+        let config = Config::load(Some(path.clone())).unwrap();
+        let unknown = unknown_fields(&config, &path).unwrap();
+        let found: Vec<(String, Option<String>)> = unknown
+            .iter()
+            .map(|field| (field.path.clone(), field.suggestion.clone()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("settings.notfy".to_string(), Some("notify".to_string())),
+                (
+                    "commands.run.configs.app.working_dir".to_string(),
+                    Some("working_directory".to_string())
+                ),
+                ("commands.test.configs.unit.zzz".to_string(), None),
+            ]
+        );
+        assert_eq!(
+            unknown[1].to_string(),
+            format!(
+                "{}: unknown field 'commands.run.configs.app.working_dir', did you mean 'working_directory'?",
+                path.display()
+            )
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,100 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use rx_core::test_filter::TestFilter;
+    use tempfile::TempDir;
+
+    const SOURCE: &str = r#"pub fn parse(input: &str) -> usize {
+    input.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // braces in strings and comments are ignored: { "}"
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("{"), 1);
+    }
+
+    mod nested {
+        #[tokio::test(flavor = "current_thread")]
+        async fn test_async() {
+            let _ = '}';
+        }
+
+        #[test]
+        fn test_after<'a>() {
+            let _: &'a str = "";
+        }
+    }
+}
+"#;
+
+    fn crate_with(file: &str) -> (TempDir, std::path::PathBuf) {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"demo\"\n",
+        )
+        .unwrap();
+        let path = dir.path().join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, SOURCE).unwrap();
+        (dir, path)
+    }
+
+    #[test]
+    fn test_location_resolves_enclosing_test_or_module() {
+        let (_dir, path) = crate_with("src/models/parser.rs");
+
+        let in_test = TestFilter::at_location(&path, 12).unwrap();
+        assert_eq!(in_test.path, "models::parser::tests::test_parse");
+        assert!(in_test.exact);
+
+        let in_nested = TestFilter::at_location(&path, 18).unwrap();
+        assert_eq!(in_nested.path, "models::parser::tests::nested::test_async");
+
+        let after_char_literal = TestFilter::at_location(&path, 23).unwrap();
+        assert_eq!(
+            after_char_literal.path,
+            "models::parser::tests::nested::test_after"
+        );
+
+        let in_module = TestFilter::at_location(&path, 7).unwrap();
+        assert_eq!(in_module.path, "models::parser::tests");
+        assert!(!in_module.exact);
+
+        let outside = TestFilter::at_location(&path, 2).unwrap();
+        assert_eq!(outside.path, "models::parser");
+        assert!(!outside.exact);
+    }
+
+    #[test]
+    fn test_integration_test_cargo_args() {
+        let (_dir, path) = crate_with("tests/parser_test.rs");
+
+        let filter = TestFilter::at_location(&path, 11).unwrap();
+        assert_eq!(
+            filter.cargo_args(&["--release".to_string()]),
+            [
+                "-p",
+                "demo",
+                "--test",
+                "parser_test",
+                "tests::test_parse",
+                "--release",
+                "--",
+                "--exact"
+            ]
+        );
+
+        let filter = TestFilter::from_name("config::tests::test_load");
+        assert_eq!(
+            filter.cargo_args(&["--".to_string(), "--nocapture".to_string()]),
+            ["config::tests::test_load", "--", "--nocapture", "--exact"]
+        );
+    }
+}
@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod tests {
+    use rx_core::test_summary::{rerun_args, FailedTest, FailedTests, TestCollector};
+
+    #[test]
+    fn test_summary_reads_cargo_test_and_nextest_output() {
+        let cargo_test = TestCollector::new();
+        for line in [
+            "running 4 tests",
+            "test config::tests::test_load ... ok",
+            "test config::tests::test_save ... \u{1b}[31mFAILED\u{1b}[0m",
+            "test config::tests::test_slow ... ignored, takes a minute",
+            "test runner::tests::test_spawn ... FAILED",
+            "---- config::tests::test_save stdout ----",
+            "thread 'config::tests::test_save' (4030) panicked at src/config.rs:42:9:",
+            "---- runner::tests::test_spawn stdout ----",
+            "thread 'runner::tests::test_spawn' panicked at 'boom', src/runner.rs:7:5",
+            "failures:",
+            "test result: FAILED. 1 passed; 2 failed; 1 ignored; 0 measured; 0 filtered out",
+        ] {
+            cargo_test.line(line);
+        }
+        let summary = cargo_test.summary();
+        assert_eq!((summary.passed, summary.failed, summary.ignored), (1, 2, 1));
+        assert_eq!(
+            summary.failures,
+            [
+                FailedTest {
+                    name: "config::tests::test_save".into(),
+                    file: Some("src/config.rs".into()),
+                    line: Some(42),
+                },
+                FailedTest {
+                    name: "runner::tests::test_spawn".into(),
+                    file: Some("src/runner.rs".into()),
+                    line: Some(7),
+                },
+            ]
+        );
+        assert_eq!(
+            summary.report(),
+            "1 passed, 2 failed, 1 ignored\n  config::tests::test_save at src/config.rs:42\n  runner::tests::test_spawn at src/runner.rs:7"
+        );
+
+        let nextest = TestCollector::new();
+        for line in [
+            "        PASS [   0.004s] demo::config_test tests::test_load",
+            "        FAIL [   0.010s] demo::config_test tests::test_save",
+            "thread 'tests::test_save' panicked at tests/config_test.rs:12:5:",
+            "     Summary [   0.020s] 2 tests run: 1 passed, 1 failed, 3 skipped",
+            "        FAIL [   0.010s] demo::config_test tests::test_save",
+        ] {
+            nextest.line(line);
+        }
+        let summary = nextest.summary();
+        assert_eq!((summary.passed, summary.failed, summary.ignored), (1, 1, 3));
+        assert_eq!(summary.failed_names(), ["tests::test_save"]);
+        assert_eq!(summary.failures[0].line, Some(12));
+
+        assert_eq!(
+            rerun_args(&summary.failed_names(), &["--release".into()]),
+            ["--release", "--", "tests::test_save", "--exact"]
+        );
+        let mut failed_tests = FailedTests::default();
+        failed_tests.record("test::unit", &summary);
+        assert_eq!(failed_tests.tests["test::unit"], ["tests::test_save"]);
+        failed_tests.record("test::unit", &TestCollector::new().summary());
+        assert!(failed_tests.tests.is_empty());
+    }
+}
@@ -0,0 +1,50 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rx_core::{
+        models::config::Config,
+        theme::{escape_sequence, ColorChoice, Presenter, Theme},
+    };
+
+    #[test]
+    fn test_themed_messages() {
+        let plain = Presenter::new(Theme::default(), false);
+        assert_eq!(plain.header("run::default"), "rx: ▶ run::default");
+        assert_eq!(
+            plain.success("run::default", Duration::from_millis(1200), None),
+            "rx: ✓ run::default in 1.2s"
+        );
+        assert_eq!(
+            plain.success(
+                "test::unit",
+                Duration::from_millis(3200),
+                Some(Duration::from_millis(2900))
+            ),
+            "rx: ✓ test::unit in 3.2s (avg 2.9s)"
+        );
+
+        let colored = Presenter::new(Theme::default(), true);
+        assert_eq!(
+            colored.error("Unknown key"),
+            "\x1b[2mrx:\x1b[0m \x1b[1;31mUnknown key\x1b[0m"
+        );
+        assert_eq!(
+            escape_sequence("bright_green underline").unwrap(),
+            "\x1b[92;4m"
+        );
+        assert!(escape_sequence("bright_bold").is_err());
+
+        assert_eq!("never".parse::<ColorChoice>().unwrap(), ColorChoice::Never);
+        assert!(ColorChoice::Always.enabled(false));
+        assert!(!ColorChoice::Never.enabled(true));
+        assert!("sometimes".parse::<ColorChoice>().is_err());
+
+        let config: Config = toml::from_str("[settings.theme]\nheader = \"blinking\"\n").unwrap();
+        assert_eq!(config.settings.theme.success, "green");
+        assert_eq!(
+            config.validate().unwrap_err().to_string(),
+            "Invalid value for 'settings.theme.header': unknown style 'blinking'"
+        );
+    }
+}
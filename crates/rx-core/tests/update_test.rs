@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    use rx_core::{
+        checksum::sha256_hex,
+        update::{asset_name, checksum_for, is_newer, replace_executable, verify, Release},
+    };
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_self_update_verifies_and_replaces_the_binary() {
+        assert!(is_newer("0.2.0", "0.1.0"));
+        assert!(is_newer("0.10.0", "0.9.3"));
+        assert!(is_newer("1.0.0", "1.0.0-rc.2"));
+        assert!(!is_newer("0.1.0", "0.1.0"));
+        assert!(!is_newer("1.0.0-rc.2", "1.0.0"));
+        assert!(!is_newer("0.9.9", "0.10.0"));
+
+        let name = asset_name();
+        let release: Release = serde_json::from_str(&format!(
+            r#"{{"tag_name": "v0.2.0", "assets": [
+                {{"name": "{}", "browser_download_url": "https://example.com/rx"}},
+                {{"name": "SHA256SUMS", "browser_download_url": "https://example.com/sums"}}
+            ]}}"#,
+            name
+        ))
+        .unwrap();
+        assert_eq!(release.version(), "0.2.0");
+        assert_eq!(
+            release.asset(&name).unwrap().browser_download_url,
+            "https://example.com/rx"
+        );
+
+        let binary = b"new rx";
+        let checksums = format!(
+            "{}  rx-other\n{} *{}\n",
+            sha256_hex(b"other"),
+            sha256_hex(binary).to_uppercase(),
+            name
+        );
+        assert_eq!(checksum_for(&checksums, &name), Some(sha256_hex(binary)));
+        verify(binary, &checksums, &name).unwrap();
+        assert!(verify(b"tampered", &checksums, &name)
+            .unwrap_err()
+            .contains("SHA256SUMS lists"));
+        assert!(verify(binary, &checksums, "rx-missing")
+            .unwrap_err()
+            .contains("no checksum for rx-missing"));
+
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let exe = temp_dir.path().join("rx");
+        std::fs::write(&exe, b"old rx").unwrap();
+        replace_executable(&exe, binary).unwrap();
+        assert_eq!(std::fs::read(&exe).unwrap(), binary);
+        // Nothing staged is left behind
+        assert!(!std::fs::read_dir(temp_dir.path()).unwrap().any(|entry| {
+            entry
+                .unwrap()
+                .file_name()
+                .to_string_lossy()
+                .contains("update-")
+        }));
+    }
+}
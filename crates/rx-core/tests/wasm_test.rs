@@ -0,0 +1,66 @@
+#[cfg(test)]
+mod tests {
+    use rx_core::{
+        executor::wasm::{runner, translate},
+        models::config::WasmRunner,
+    };
+
+    fn argv(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_wasm_runners_take_over_wasm32_targets() {
+        let test = argv("cargo test --target wasm32-unknown-unknown parse");
+        assert_eq!(runner(&test, None), Some(WasmRunner::WasmPack));
+        assert_eq!(
+            translate(&test, WasmRunner::WasmPack),
+            Some(argv("wasm-pack test --headless --chrome -- parse"))
+        );
+
+        let run = argv("cargo run --release --target=wasm32-unknown-unknown -- --port 80");
+        assert_eq!(runner(&run, None), Some(WasmRunner::Trunk));
+        assert_eq!(
+            translate(&run, WasmRunner::Trunk),
+            Some(argv("trunk serve --release"))
+        );
+        assert_eq!(translate(&run, WasmRunner::WasmPack), None);
+
+        let wasi = argv("cargo run --target wasm32-wasip1");
+        assert_eq!(runner(&wasi, None), Some(WasmRunner::Wasmtime));
+        assert_eq!(
+            translate(&wasi, WasmRunner::Wasmtime),
+            Some(vec![
+                "cargo".to_string(),
+                "--config".to_string(),
+                "target.wasm32-wasip1.runner=\"wasmtime\"".to_string(),
+                "run".to_string(),
+                "--target".to_string(),
+                "wasm32-wasip1".to_string(),
+            ])
+        );
+        let build = argv("cargo build --target wasm32-wasip1");
+        assert_eq!(
+            translate(&build, WasmRunner::Wasmtime),
+            None,
+            "building needs no runner"
+        );
+
+        assert_eq!(
+            runner(&argv("cargo build --target wasm32-unknown-unknown"), None),
+            None
+        );
+        assert_eq!(
+            runner(
+                &argv("cargo test --target wasm32-unknown-unknown"),
+                Some(WasmRunner::Wasmtime)
+            ),
+            Some(WasmRunner::Wasmtime)
+        );
+        assert_eq!(
+            runner(&argv("cargo test --target x86_64-unknown-linux-gnu"), None),
+            None
+        );
+        assert_eq!(runner(&argv("cargo test"), Some(WasmRunner::Trunk)), None);
+    }
+}
@@ -0,0 +1,313 @@
+#[cfg(test)]
+mod tests {
+    use rx_core::{
+        builders::config::ConfigBuilder,
+        executor::{runner::Runner, shutdown::ShutdownSignal},
+        interpolation::InterpolationContext,
+        models::config::{CommandContext, Config},
+        workspace::{
+            find_member, missing_packages, parse_metadata, project_kind, selects_package,
+            ProjectKind,
+        },
+        workspace_check::{dependency_order, run_checks, CheckOutcome, CheckStep},
+    };
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+
+    const METADATA: &str = r#"{
+        "packages": [
+            {
+                "name": "server",
+                "manifest_path": "/ws/crates/server/Cargo.toml",
+                "targets": [
+                    {"name": "server", "kind": ["lib"]},
+                    {"name": "serve", "kind": ["bin"]}
+                ]
+            },
+            {
+                "name": "cli",
+                "manifest_path": "/ws/crates/cli/Cargo.toml",
+                "targets": [{"name": "cli", "kind": ["lib"]}]
+            }
+        ],
+        "workspace_root": "/ws"
+    }"#;
+
+    #[test]
+    fn test_package_flag_targets_a_member() {
+        let members = parse_metadata(METADATA).unwrap();
+        let names: Vec<&str> = members.iter().map(|member| member.name.as_str()).collect();
+        assert_eq!(names, ["cli", "server"]);
+        let server = find_member(&members, "server").unwrap();
+        assert_eq!(server.binary_name.as_deref(), Some("serve"));
+        assert_eq!(server.manifest_dir.to_string_lossy(), "/ws/crates/server");
+        assert!(find_member(&members, "web")
+            .unwrap_err()
+            .ends_with("expected one of: cli, server"));
+
+        assert!(selects_package(["test", "-p", "cli"]));
+        assert!(selects_package(["test", "--workspace"]));
+        assert!(!selects_package(["run", "--", "-p"]));
+
+        let mut config = Config::default();
+        let tests = config.commands.get_or_default_config(CommandContext::Test);
+        tests.update_config(
+            "nocapture",
+            ConfigBuilder::new(CommandContext::Test)
+                .params("-- --nocapture")
+                .build()
+                .unwrap(),
+        );
+
+        let argv = |context: CommandContext, key: &str| {
+            let runner = Runner::new(&config, context, ShutdownSignal::new()).package(server);
+            let command = runner
+                .build_command(runner.details(key).unwrap(), &[])
+                .unwrap();
+            command
+                .get_args()
+                .map(|arg| arg.to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        assert_eq!(
+            argv(CommandContext::Test, "nocapture"),
+            "test --package server -- --nocapture"
+        );
+        // The default run config already names `${packageName}`
+        assert_eq!(
+            argv(CommandContext::Run, "default"),
+            "run --package server --bin serve"
+        );
+    }
+
+    #[test]
+    fn test_binary_name_falls_back_to_default_run_then_the_only_binary() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let dir = temp_dir.path();
+        std::fs::create_dir_all(dir.join("src/bin")).unwrap();
+        std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"app\"\n").unwrap();
+        std::fs::write(dir.join("src/main.rs"), "fn main() {}").unwrap();
+        let binary_name = || {
+            InterpolationContext::from_dir(dir)
+                .get("binaryName")
+                .map(String::from)
+        };
+        assert_eq!(binary_name().as_deref(), Some("app"));
+
+        std::fs::write(dir.join("src/bin/migrate.rs"), "fn main() {}").unwrap();
+        assert_eq!(binary_name(), None);
+        assert_eq!(
+            InterpolationContext::from_dir(dir).binaries(),
+            ["app", "migrate"]
+        );
+        std::fs::write(
+            dir.join("Cargo.toml"),
+            "[package]\nname = \"app\"\ndefault-run = \"migrate\"\n",
+        )
+        .unwrap();
+        assert_eq!(binary_name().as_deref(), Some("migrate"));
+
+        let mut config = Config::default();
+        let runs = config.commands.get_or_default_config(CommandContext::Run);
+        runs.update_config(
+            "default",
+            ConfigBuilder::new(CommandContext::Run)
+                .command("run --bin ${binaryName}")
+                .build()
+                .unwrap(),
+        );
+        let mut interpolation = InterpolationContext::default();
+        interpolation.set_binary(Err(vec!["app".to_string(), "migrate".to_string()]));
+        let runner = Runner::new(&config, CommandContext::Run, ShutdownSignal::new())
+            .interpolation(interpolation.clone());
+        assert_eq!(
+            runner.binary_candidates("default").unwrap(),
+            ["app", "migrate"]
+        );
+        let err = runner
+            .build_command(runner.details("default").unwrap(), &[])
+            .unwrap_err()
+            .to_string();
+        assert!(err.ends_with("one of: app, migrate"), "{}", err);
+        let picked = Runner::new(&config, CommandContext::Run, ShutdownSignal::new())
+            .interpolation(interpolation.clone())
+            .binary_name("app");
+        assert!(picked.binary_candidates("default").unwrap().is_empty());
+
+        let runs = config.commands.get_or_default_config(CommandContext::Run);
+        runs.configs.get_mut("default").unwrap().binary_name = Some("migrate".to_string());
+        let runner = Runner::new(&config, CommandContext::Run, ShutdownSignal::new())
+            .interpolation(interpolation);
+        let command = runner
+            .build_command(runner.details("default").unwrap(), &[])
+            .unwrap();
+        assert_eq!(command.get_args().last().unwrap(), "migrate");
+    }
+
+    #[test]
+    fn test_init_seeds_defaults_for_the_project_kind() {
+        let temp_dir = TempDir::new().expect("Failed to create a temporary directory");
+        let dir = temp_dir.path();
+        assert_eq!(project_kind(dir), None);
+
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"app\"\n").unwrap();
+        std::fs::write(dir.join("src/lib.rs"), "").unwrap();
+        assert_eq!(project_kind(&dir.join("src")), Some(ProjectKind::Lib));
+        std::fs::write(dir.join("src/main.rs"), "fn main() {}").unwrap();
+        assert_eq!(project_kind(dir), Some(ProjectKind::Bin));
+        std::fs::write(dir.join("Cargo.toml"), "[workspace]\nmembers = [\"app\"]\n").unwrap();
+        assert_eq!(project_kind(dir), Some(ProjectKind::Workspace));
+
+        let lib = Config::for_project(ProjectKind::Lib);
+        assert!(lib.commands.get_config(CommandContext::Run).is_none());
+        lib.validate().unwrap();
+
+        let workspace = Config::for_project(ProjectKind::Workspace);
+        let params = |context| {
+            workspace.commands.get_config(context).unwrap().configs["default"]
+                .params
+                .to_string()
+        };
+        assert_eq!(params(CommandContext::Test), "--workspace");
+        assert_eq!(params(CommandContext::Fmt), "--all --check");
+        assert_eq!(params(CommandContext::Run), "");
+        assert_eq!("lib".parse::<ProjectKind>(), Ok(ProjectKind::Lib));
+        assert!("app".parse::<ProjectKind>().is_err());
+    }
+
+    #[test]
+    fn test_offline_configs_pass_offline_and_find_packages_to_download() {
+        let project = TempDir::new().expect("Failed to create a temporary directory");
+        std::fs::write(
+            project.path().join("Cargo.toml"),
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        let cargo_home = TempDir::new().expect("Failed to create a temporary directory");
+        assert!(missing_packages(project.path(), cargo_home.path())
+            .unwrap_err()
+            .ends_with("has no Cargo.lock"));
+
+        std::fs::write(
+            project.path().join("Cargo.lock"),
+            r#"
+version = 3
+
+[[package]]
+name = "app"
+version = "0.1.0"
+
+[[package]]
+name = "serde"
+version = "1.0.200"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "toml"
+version = "0.8.12"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#,
+        )
+        .unwrap();
+        let cache = cargo_home
+            .path()
+            .join("registry/cache/index.crates.io-6f17d22bba15001f");
+        std::fs::create_dir_all(&cache).unwrap();
+        std::fs::write(cache.join("serde-1.0.200.crate"), "").unwrap();
+        assert_eq!(
+            missing_packages(project.path(), cargo_home.path()).unwrap(),
+            ["toml 0.8.12"]
+        );
+
+        let mut config = Config::default();
+        config.settings.offline = true;
+        let runner = Runner::new(&config, CommandContext::Test, ShutdownSignal::new());
+        let details = runner.details("default").unwrap();
+        let args: Vec<String> = runner
+            .build_command(details, &["--".to_string(), "--nocapture".to_string()])
+            .unwrap()
+            .get_args()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect();
+        assert_eq!(args, ["test", "--offline", "--", "--nocapture"]);
+        assert_eq!(
+            runner
+                .environment(details)
+                .unwrap()
+                .get("CARGO_NET_OFFLINE"),
+            Some("true")
+        );
+
+        let fmt = Runner::new(&config, CommandContext::Fmt, ShutdownSignal::new());
+        let fmt_args = fmt.command_line("default", &[]).unwrap();
+        assert!(!fmt_args.contains("--offline"));
+    }
+
+    #[test]
+    fn test_check_runs_members_after_their_dependencies() {
+        let package = |name: &str, dependencies: &str| {
+            format!(
+                r#"{{"name": "{0}", "manifest_path": "/ws/{0}/Cargo.toml", "dependencies": [{1}]}}"#,
+                name, dependencies
+            )
+        };
+        let metadata = format!(
+            r#"{{"packages": [{}, {}, {}]}}"#,
+            package(
+                "app",
+                r#"{"name": "core"}, {"name": "serde"}, {"name": "tools", "kind": "dev"}"#
+            ),
+            package("core", ""),
+            package("tools", r#"{"name": "core", "kind": "build"}"#),
+        );
+        let members = parse_metadata(&metadata).unwrap();
+        assert_eq!(members[0].dependencies, ["core"]);
+        assert_eq!(members[2].dependencies, ["core"]);
+        let order: Vec<&str> = dependency_order(&members)
+            .unwrap()
+            .iter()
+            .map(|member| member.name.as_str())
+            .collect();
+        assert_eq!(order, ["core", "app", "tools"]);
+
+        let ran = Mutex::new(Vec::new());
+        let steps = [CheckStep::Check, CheckStep::Clippy, CheckStep::Test];
+        let matrix = run_checks(
+            &members,
+            &steps,
+            4,
+            &ShutdownSignal::new(),
+            |member, step| {
+                ran.lock()
+                    .unwrap()
+                    .push(format!("{} {}", step, member.name));
+                !(member.name == "app" && step == CheckStep::Clippy)
+            },
+        )
+        .unwrap();
+        let ran = ran.into_inner().unwrap();
+        assert_eq!(ran[..3], ["check core", "clippy core", "test core"]);
+        assert_eq!(ran.len(), 8);
+        assert_eq!(
+            matrix.members[0].outcomes,
+            [
+                CheckOutcome::Passed,
+                CheckOutcome::Failed,
+                CheckOutcome::Skipped
+            ]
+        );
+        assert!(!matrix.success());
+        assert_eq!(
+            matrix.report(),
+            "member  check   clippy  test\napp     ✓       ✗       -\ncore    ✓       ✓       ✓\ntools   ✓       ✓       ✓"
+        );
+        assert_eq!("clippy".parse::<CheckStep>().unwrap(), CheckStep::Clippy);
+        assert!("build".parse::<CheckStep>().is_err());
+
+        let mut cyclic = members.clone();
+        cyclic[1].dependencies = vec!["app".into()];
+        assert!(dependency_order(&cyclic).is_err());
+    }
+}
@@ -0,0 +1,920 @@
+use std::{error::Error, path::PathBuf};
+
+use clap::{builder::PossibleValuesParser, ArgAction, Args, Parser, Subcommand};
+use clap_complete::{engine::ArgValueCompleter, env::Shells};
+use rx_core::{
+    about::VersionFormat,
+    daemon::RemoteAddress,
+    diagnostics::MessageFormat,
+    events::EventFormat,
+    executor::{debug::Debugger, runner::StdinSource},
+    export::ExportFormat,
+    gc::DEFAULT_STALE_DAYS,
+    githook::GIT_HOOKS,
+    import::ImportFormat,
+    models::{
+        config::{CommandContext, CommandType},
+        format::ConfigFormat,
+    },
+    runnables::RunnablesFormat,
+    serve::DEFAULT_PORT,
+    split::UiMode,
+    stats::ReportFormat,
+    theme::ColorChoice,
+    workspace::{self, Member, ProjectKind},
+    workspace_check::CheckStep,
+};
+
+use crate::commands::{
+    completions::{complete_keys, complete_members},
+    run::EventTarget,
+};
+
+#[derive(Parser)]
+#[command(
+    name = "rx",
+    version,
+    about = "Cargo Runner",
+    disable_version_flag = true,
+    arg_required_else_help = true
+)]
+pub struct Cli {
+    /// Print the version, `--output json` adding the git sha, build date, features and target
+    #[arg(long, short = 'V')]
+    pub version: bool,
+
+    /// How `--version` prints: `text` or `json`
+    #[arg(long, value_name = "FORMAT", requires = "version")]
+    pub output: Option<VersionFormat>,
+
+    /// Path to the config file, defaults to $RX_CONFIG or config.toml in the platform's config
+    /// dir, ~/.config/cargo_runner on Linux
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Print the diff a config change would make and exit without writing it
+    #[arg(long, global = true)]
+    pub diff_only: bool,
+
+    /// Create the missing directories of the config file when saving it
+    #[arg(long, global = true)]
+    pub create_dirs: bool,
+
+    /// Write config changes, or run the configs a key pattern matches, without asking for
+    /// confirmation
+    #[arg(long, short, global = true)]
+    pub yes: bool,
+
+    /// Only print the command's own output, rx still exits with its exit code
+    #[arg(long, short, global = true)]
+    pub quiet: bool,
+
+    /// When to color rx's own messages: auto, always or never. `auto` colors a terminal unless
+    /// NO_COLOR is set
+    #[arg(long, global = true, value_name = "WHEN", default_value = "auto")]
+    pub color: ColorChoice,
+
+    /// Log what rx does, e.g. how a command was resolved: -v info, -vv debug, -vvv trace.
+    /// RX_LOG takes a filter such as `rx_core::executor=trace` instead
+    #[arg(long, short, global = true, action = ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Reject unknown fields in the config files, suggesting the closest known one, and fail
+    /// instead of warning when running a deprecated config. `settings.strict` turns it on too
+    #[arg(long, global = true)]
+    pub strict: bool,
+
+    /// Append the logs to a file instead of stderr
+    #[arg(long, global = true, value_name = "PATH")]
+    pub log_file: Option<PathBuf>,
+
+    /// Print how long each phase of rx took once done, telling its own overhead apart from
+    /// the commands it ran
+    #[arg(long, global = true)]
+    pub timings: bool,
+
+    /// Run on the `rx daemon --listen` at ADDR instead, e.g. devbox:7879 over TLS or
+    /// tcp://localhost:7879 through a tunnel, authenticating with $RX_REMOTE_TOKEN.
+    /// $RX_REMOTE_CA names the CAs to check its certificate with besides the system's
+    #[arg(long, global = true, value_name = "ADDR")]
+    pub remote: Option<RemoteAddress>,
+
+    #[command(subcommand)]
+    pub command: Option<CliCommand>,
+}
+
+#[derive(Subcommand)]
+pub enum CliCommand {
+    /// Run a config of the `run` context
+    Run(RunArgs),
+    /// Run a config of the `test` context
+    Test(TestArgs),
+    /// Run a config of the `build` context and report the artifacts it produced
+    Build(BuildArgs),
+    /// Run a config of the `bench` context and record its results
+    Bench(BenchArgs),
+    /// Run a config of the `script` context
+    Script(RunArgs),
+    /// Run a config of the `lint` context, `cargo clippy` by default
+    Lint(FixArgs),
+    /// Run a config of the `fmt` context, `cargo fmt --check` by default
+    Fmt(FixArgs),
+    /// Run a config of the `coverage` context, `cargo llvm-cov` by default
+    Coverage(RunArgs),
+    /// Build a config's executable and launch it under a debugger
+    Debug(DebugArgs),
+    /// Create the config file with defaults for the project in the current directory
+    Init(InitArgs),
+    /// Add or replace a config entry, asking for the fields not given when run in a terminal
+    Add(AddArgs),
+    /// Change the default key of a context
+    SetDefault {
+        context: CommandContext,
+        key: String,
+    },
+    /// Copy a config entry to a new key, optionally into another context
+    Copy {
+        context: CommandContext,
+        src_key: String,
+        dst_key: String,
+        /// Context to copy into, defaults to the source context
+        #[arg(long, value_name = "CONTEXT")]
+        to: Option<CommandContext>,
+    },
+    /// Read or change a single config value by its dotted path
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Rename a config entry and update the default, pre_commands and hooks referring to it
+    Rename {
+        context: CommandContext,
+        old_key: String,
+        new_key: String,
+    },
+    /// Remove a config entry, refused while other entries refer to it
+    Remove {
+        context: CommandContext,
+        key: String,
+        /// Also remove the pre_command, steps and hook entries referring to it
+        #[arg(long, conflicts_with = "force")]
+        cascade: bool,
+        /// Remove it even though other entries refer to it
+        #[arg(long)]
+        force: bool,
+    },
+    /// Remove the directory a cargo config with `target_dir` builds into
+    Clean {
+        context: CommandContext,
+        key: String,
+    },
+    /// Print the command line a config runs, placeholders resolved and wrappers included,
+    /// without running it
+    Show {
+        context: CommandContext,
+        /// The context's default when left out
+        key: Option<String>,
+        /// Copy it to the clipboard as well, to paste into a CI config or a chat
+        #[arg(long)]
+        copy: bool,
+    },
+    /// List the placeholders rx resolves in the current directory and their values, those of a
+    /// config included with `--key`. Exits with 1 when the config refers to one without a value
+    Vars {
+        /// Context of `--key`, `run` by default, its default config without `--key`
+        #[arg(long)]
+        context: Option<CommandContext>,
+        /// The config whose args, env and placeholders to list as well
+        #[arg(long)]
+        key: Option<String>,
+        /// Print them as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Convert the recipes of a Justfile, the targets of a Makefile or the jobs of a bacon.toml
+    /// into script configs, a `cargo watch` recipe into the command it watches
+    Import(ImportArgs),
+    /// Write the configs as a cargo-make Makefile.toml or the main.rs of an xtask crate
+    Export(ExportArgs),
+    /// List the entries of every context by label, followed by their description, as `rx config
+    /// list` does
+    List(ListArgs),
+    /// List the members of the cargo workspace, the names `--package` accepts
+    Members,
+    /// Run cargo check, clippy and test on workspace members, each after the members it
+    /// depends on, and print a table of what passed
+    Check(CheckArgs),
+    /// Warn about valid but likely unintended configs, exiting with 1 when there are any
+    LintConfig,
+    /// List what can run at a line of a source file: its binary, the enclosing test function
+    /// and test module and its bench target, with the command lines the config resolves them to
+    Runnables {
+        #[arg(long, value_name = "PATH")]
+        file: PathBuf,
+        #[arg(long, value_name = "N")]
+        line: usize,
+        /// `text` or `json`
+        #[arg(long, value_name = "FORMAT", default_value = "text")]
+        output: RunnablesFormat,
+    },
+    /// Find the configs whose key, command, params, description or env values match PATTERN,
+    /// its characters in order ignoring case, printing the label and field of each match.
+    /// Exits with 1 when nothing matches
+    Search {
+        pattern: String,
+        /// Take PATTERN as a regular expression
+        #[arg(long)]
+        regex: bool,
+    },
+    /// Print a JSON Schema of the config file, for editors to complete and validate it, e.g.
+    /// with a `#:schema ./rx.schema.json` line at the top of the file for taplo
+    Schema {
+        /// File to write, printed to stdout when not given
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Show the average, median and p95 duration and the failure rate of each config run in
+    /// this project
+    Stats,
+    /// Print the script registering rx's completions with SHELL, e.g. `source <(rx completions
+    /// bash)` in ~/.bashrc. The shell asks rx for the candidates as it completes, so the config
+    /// keys, scripts and workspace members offered are those of the moment
+    Completions {
+        #[arg(value_parser = PossibleValuesParser::new(Shells::builtins().names()))]
+        shell: String,
+    },
+    /// List where rx reads and keeps its files: the config file, the project's history, the
+    /// cache, the locks and the daemon's socket
+    Env {
+        /// Print them as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List the configs not run for DAYS and the history, cache entries, service logs and pids
+    /// and per-key target dirs left behind by configs that are gone. A config never run, or that
+    /// another entry refers to, is kept
+    Gc {
+        #[arg(long, value_name = "DAYS", default_value_t = DEFAULT_STALE_DAYS)]
+        days: u64,
+        /// Remove them, the configs once the change to the config file is confirmed
+        #[arg(long)]
+        apply: bool,
+        /// Only list what rx processes that crashed or were killed left: temp scripts, sandbox
+        /// dirs and pane logs, and the pids of services that stopped
+        #[arg(long, conflicts_with = "days")]
+        runtime: bool,
+    },
+    /// Fetch the team config of `[remote_config]`, ahead of its ttl, and list what the team
+    /// changed since the last fetch and how the configs here overriding its entries differ
+    Sync,
+    /// Run the last config run in this project again with its arguments and env
+    Rerun {
+        /// Only run its steps, matrix cells and dependencies that failed, or the config itself
+        /// when it failed on its own
+        #[arg(long)]
+        failed: bool,
+    },
+    /// Run a one-off command with the env, env_file, working directory, placeholders, wrappers
+    /// and log of a config, without its pre_commands, dependencies or guards
+    Exec {
+        /// The config to borrow them from, `context::key` or a key of the run context
+        #[arg(long, value_name = "LABEL")]
+        like: Option<String>,
+        /// Environment variable as KEY=VALUE overriding the config's env, can be repeated
+        #[arg(long, value_parser = parse_env)]
+        env: Vec<(String, String)>,
+        /// The program and its arguments
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
+    },
+    /// Run a command typed as in a shell once and, when it succeeds, save it as a config entry
+    /// with the current directory and the variables set before it, e.g.
+    /// `rx record run api -- RUST_LOG=debug cargo run -p api`
+    Record {
+        context: CommandContext,
+        key: String,
+        /// Environment variable as KEY=VALUE to run and save the command with, can be repeated
+        #[arg(long, value_parser = parse_env)]
+        env: Vec<(String, String)>,
+        /// Replace the config of that key if there is one
+        #[arg(long)]
+        force: bool,
+        /// The command, `NAME=value` words before the program included
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
+    },
+    /// Summarize the runs recorded in this project, the configs run the most, failing most
+    /// often and running slowest, as markdown or JSON to share. Reads the local history only
+    Report(ReportArgs),
+    /// Serve run, list, validate and kill requests over a unix socket, answering with ndjson
+    /// events, for editor integrations
+    Daemon(DaemonArgs),
+    /// List the processes the daemon runs and those waiting for their turn under
+    /// `max_concurrent_runs`, in order
+    Queue {
+        /// Socket of the daemon, defaults to rx.sock in $XDG_RUNTIME_DIR
+        #[arg(long, value_name = "PATH")]
+        socket: Option<PathBuf>,
+        /// Print the daemon's answer as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Serve a dashboard on localhost listing the configs, starting and stopping runs and
+    /// streaming their output, with its JSON API under /api
+    Serve(ServeArgs),
+    /// Start script configs with `service = true` in the background, all of them by default,
+    /// after the services they depend on
+    Up(UpArgs),
+    /// Stop services started with `rx up`, all of them by default
+    Down(ServiceArgs),
+    /// List the services and whether they're running, with their pid, uptime and log file
+    Status,
+    /// Install or remove git hooks running the configs of `[hooks.git]`
+    Hook {
+        #[command(subcommand)]
+        action: HookAction,
+    },
+    /// Keep env values of configs encrypted or in the OS keychain rather than in plain text
+    Secret {
+        #[command(subcommand)]
+        action: SecretAction,
+    },
+    /// Update rx to its latest GitHub release, verified against the SHA-256 the release lists
+    SelfUpdate {
+        /// Only report whether a newer release is available
+        #[arg(long)]
+        check: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SecretAction {
+    /// Set an env variable of a config to a value read from stdin, encrypted with age or GPG to
+    /// `settings.secret_recipients`
+    Set {
+        context: CommandContext,
+        key: String,
+        name: String,
+        /// Store the value in the OS keychain and refer to its entry instead of encrypting it,
+        /// NAME of rx's service by default or ENTRY given as `service/account`
+        #[arg(long, value_name = "ENTRY")]
+        keychain: Option<Option<String>>,
+    },
+    /// Print the value of an env variable of a config, decrypted
+    Get {
+        context: CommandContext,
+        key: String,
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum HookAction {
+    /// Write the git hooks, each running its `[hooks.git.<hook>]` configs before git goes on
+    Install {
+        /// Hooks to install, those of `[hooks.git]` by default
+        #[arg(value_parser = PossibleValuesParser::new(GIT_HOOKS))]
+        hooks: Vec<String>,
+        /// Replace hooks rx didn't write
+        #[arg(long)]
+        force: bool,
+    },
+    /// Remove the git hooks rx installed
+    Uninstall {
+        /// Hooks to remove, those of `[hooks.git]` by default
+        #[arg(value_parser = PossibleValuesParser::new(GIT_HOOKS))]
+        hooks: Vec<String>,
+    },
+    /// Run the configs of a git hook, what the installed hook calls
+    #[command(hide = true)]
+    Run {
+        #[arg(value_parser = PossibleValuesParser::new(GIT_HOOKS))]
+        hook: String,
+        /// Arguments git passes to the hook, unused
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Print the value at a path such as `commands.run.configs.default.params`
+    Get { path: String },
+    /// Set the value at a path, parsed according to the type of the value it replaces
+    Set {
+        path: String,
+        /// The new value, which may start with `-`, e.g. `"--release"`
+        #[arg(allow_hyphen_values = true)]
+        value: String,
+    },
+    /// List the entries of every context by label, followed by their description
+    List(ListArgs),
+    /// Back up the config file, as rx does before each change it makes to it
+    Backup {
+        /// List the backups, oldest first, instead of taking one
+        #[arg(long)]
+        list: bool,
+    },
+    /// Put back the backup taken at a timestamp such as 20261016T101500.123Z (a unique prefix is
+    /// enough), the latest one by default. The config as it was is backed up first
+    Restore { timestamp: Option<String> },
+    /// Write the config file in another format, next to it with that format's extension unless
+    /// `--output` is given. The files it includes are left as they are
+    Convert {
+        /// `toml`, `yaml` or `json`
+        #[arg(long, value_name = "FORMAT")]
+        to: ConfigFormat,
+        #[arg(long, short, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+    /// Show the entries the second config adds and removes and the fields it changes, their
+    /// includes merged in. With one file it's compared to the config file, and `--against
+    /// default` compares the file (the config file without one) to what `rx init` writes for
+    /// this project. Exits with 1 when they differ
+    Diff {
+        #[arg(required_unless_present = "against")]
+        first: Option<PathBuf>,
+        #[arg(conflicts_with = "against")]
+        second: Option<PathBuf>,
+        #[arg(long, value_name = "BASE", value_parser = PossibleValuesParser::new(["default"]))]
+        against: Option<String>,
+    },
+    /// Add the entries of another config file to this one. An entry both define differently
+    /// is refused unless `--overwrite` takes the other file's
+    Merge {
+        other: PathBuf,
+        #[arg(long)]
+        overwrite: bool,
+    },
+}
+
+impl RunArgs {
+    /// The workspace member selected with `--package`, looked up from the current directory.
+    pub fn member(&self) -> Result<Option<Member>, Box<dyn Error>> {
+        let Some(name) = &self.package else {
+            return Ok(None);
+        };
+        let members = workspace::members(&std::env::current_dir()?)?;
+        Ok(Some(workspace::find_member(&members, name)?.clone()))
+    }
+
+    /// Whether `--cwd`, `--type`, `--params`, `--set` or `--save` change the config for the run.
+    pub fn overrides_config(&self) -> bool {
+        self.cwd.is_some()
+            || self.command_type.is_some()
+            || self.params.is_some()
+            || !self.fields.is_empty()
+            || self.save
+    }
+
+    /// The stdin given with `--stdin`.
+    pub fn stdin_source(&self) -> Option<StdinSource> {
+        self.stdin.as_ref().map(|path| {
+            if path.as_os_str() == "-" {
+                StdinSource::Inherit
+            } else {
+                StdinSource::File(path.clone())
+            }
+        })
+    }
+
+    /// Where lifecycle events go, `None` when progress is printed as text.
+    pub fn event_target(&self) -> Option<EventTarget> {
+        match (&self.event_socket, self.event_format) {
+            (Some(path), _) => Some(EventTarget::Socket(path.clone())),
+            (None, EventFormat::Ndjson) => Some(EventTarget::Stdout),
+            (None, EventFormat::Text) => None,
+        }
+    }
+}
+
+impl CliCommand {
+    /// Returns the context and arguments when this is one of the run subcommands.
+    pub fn run_args(&self) -> Option<(CommandContext, &RunArgs)> {
+        match self {
+            CliCommand::Run(args) => Some((CommandContext::Run, args)),
+            CliCommand::Test(args) => Some((CommandContext::Test, &args.run)),
+            CliCommand::Build(args) => Some((CommandContext::Build, &args.run)),
+            CliCommand::Bench(args) => Some((CommandContext::Bench, &args.run)),
+            CliCommand::Script(args) => Some((CommandContext::Script, args)),
+            CliCommand::Lint(args) => Some((CommandContext::Lint, &args.run)),
+            CliCommand::Fmt(args) => Some((CommandContext::Fmt, &args.run)),
+            CliCommand::Coverage(args) => Some((CommandContext::Coverage, args)),
+            _ => None,
+        }
+    }
+
+    /// Where `rx build` copies its artifacts, see [`BuildArgs`].
+    pub fn copy_to(&self) -> Option<String> {
+        match self {
+            CliCommand::Build(args) => args.copy_to.clone(),
+            _ => None,
+        }
+    }
+
+    /// The revision `rx build` compares its sizes with, see [`BuildArgs`].
+    pub fn compare_size(&self) -> Option<String> {
+        match self {
+            CliCommand::Build(args) => args.compare_size.clone(),
+            _ => None,
+        }
+    }
+
+    /// Whether fixes were asked for, see [`FixArgs`].
+    pub fn fix(&self) -> bool {
+        match self {
+            CliCommand::Lint(args) | CliCommand::Fmt(args) => args.fix,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct RunArgs {
+    /// Config key to run, defaults to the context's `default`. A glob such as `unit-*` or
+    /// `deploy-{staging,prod}` runs every config whose key it matches, once confirmed
+    #[arg(add = ArgValueCompleter::new(complete_keys))]
+    pub key: Option<String>,
+
+    /// Save the config with the extra arguments appended to its params under a new key
+    #[arg(long, value_name = "KEY")]
+    pub save_as: Option<String>,
+
+    /// Environment variable as KEY=VALUE overriding the config's env, can be repeated
+    #[arg(long, value_parser = parse_env)]
+    pub env: Vec<(String, String)>,
+
+    /// Value of an `${input:NAME}` placeholder as NAME=VALUE, can be repeated. The inputs not
+    /// given are asked for in a terminal
+    #[arg(long, value_name = "NAME=VALUE", value_parser = parse_env)]
+    pub input: Vec<(String, String)>,
+
+    /// Value of one of the config's `args` as NAME=VALUE, checked against its type, can be
+    /// repeated. The args not given take their defaults
+    #[arg(long = "arg", value_name = "NAME=VALUE", value_parser = parse_env)]
+    pub arg_values: Vec<(String, String)>,
+
+    /// Run in this directory instead of the config's working_directory
+    #[arg(long, value_name = "DIR")]
+    pub cwd: Option<PathBuf>,
+
+    /// Run as this command type instead of the config's, e.g. `shell`
+    #[arg(long = "type", value_name = "TYPE")]
+    pub command_type: Option<String>,
+
+    /// Params replacing the config's, e.g. `--params "--release"`
+    #[arg(long, value_name = "PARAMS", allow_hyphen_values = true)]
+    pub params: Option<String>,
+
+    /// Any other field of the config as FIELD=VALUE for this run, parsed as `rx config set`
+    /// does, e.g. `pty=true` or `limits.memory=1G`, can be repeated
+    #[arg(long = "set", value_name = "FIELD=VALUE", value_parser = parse_env)]
+    pub fields: Vec<(String, String)>,
+
+    /// Write `--cwd`, `--type`, `--params`, `--set` and `--env` to the config instead of only
+    /// running with them
+    #[arg(long, conflicts_with_all = ["save_as", "tag"])]
+    pub save: bool,
+
+    /// Feed the command this file instead of its config's stdin, `-` passes rx's own stdin
+    /// through
+    #[arg(long, value_name = "PATH")]
+    pub stdin: Option<PathBuf>,
+
+    /// Print the environment the command would run with instead of running it
+    #[arg(long)]
+    pub env_show: bool,
+
+    /// Print the command lines the run would execute as a tree of the configs it depends on
+    /// and its pre_commands, wrappers such as sudo included, instead of running it
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Run at most N of the configs of `depends_on` at a time, as many as there are CPUs by
+    /// default
+    #[arg(long, short, value_name = "N")]
+    pub jobs: Option<usize>,
+
+    /// Workspace member to target: cargo commands get `--package <MEMBER>` and
+    /// `${packageName}`/`${binaryName}` resolve to it, see `rx members`
+    #[arg(long, short, value_name = "MEMBER", add = ArgValueCompleter::new(complete_members))]
+    pub package: Option<String>,
+
+    /// Run even when the config's `inputs` are unchanged since its last successful run
+    #[arg(long)]
+    pub no_cache: bool,
+
+    /// How progress is reported: `text` lines on stderr, or `ndjson` lifecycle events on stdout
+    /// with the commands' output carried in `output_chunk` events
+    #[arg(long, value_name = "FORMAT", default_value = "text")]
+    pub event_format: EventFormat,
+
+    /// Unix socket to send ndjson events to instead, the output still goes to the terminal
+    #[arg(long, value_name = "PATH")]
+    pub event_socket: Option<PathBuf>,
+
+    /// `json` has cargo print JSON messages and rx summarize the diagnostics at the end, or
+    /// send them as `diagnostic` events with `--event-format ndjson`
+    #[arg(long, value_name = "FORMAT", default_value = "human")]
+    pub message_format: MessageFormat,
+
+    /// How the commands' output is shown: `interleaved` as they print it, `prefixed` with the
+    /// label of the command before each line, or `split` into a region of the terminal per
+    /// command, tmux panes when inside tmux. Ignored with ndjson events
+    #[arg(long, value_name = "MODE", default_value = "interleaved")]
+    pub ui: UiMode,
+
+    /// Control the run from the keyboard: r restarts it, or runs it again once finished, q
+    /// stops it, c clears the screen and p pauses the output. Needs a terminal on stdin
+    #[arg(long, conflicts_with_all = ["dry_run", "env_show", "save_as"])]
+    pub keys: bool,
+
+    /// Run it again whenever a file its `watch` globs match changes, by default the package's
+    /// sources and manifest, stopping a run still going first. Ctrl-C quits
+    #[arg(long, conflicts_with_all = ["dry_run", "env_show", "save_as", "keys", "tag"])]
+    pub watch: bool,
+
+    /// Run the configs tagged TAG one after another instead of a single key, those of every
+    /// context for `rx run`
+    #[arg(
+        long,
+        value_name = "TAG",
+        conflicts_with_all = ["key", "save_as", "cwd", "command_type", "params", "fields"]
+    )]
+    pub tag: Option<String>,
+
+    /// Run the configs a key pattern or `--tag` picks at once instead of one after another
+    #[arg(long, conflicts_with = "save_as")]
+    pub parallel: bool,
+
+    /// Extra arguments appended to the command
+    #[arg(last = true)]
+    pub args: Vec<String>,
+}
+
+#[derive(Args)]
+pub struct TestArgs {
+    #[command(flatten)]
+    pub run: RunArgs,
+
+    /// Run only the test with this full path, e.g. `config::tests::test_load`
+    #[arg(long, value_name = "PATH", conflicts_with = "file")]
+    pub name: Option<String>,
+
+    /// Run the test enclosing `--line` in this file
+    #[arg(long, value_name = "PATH", requires = "line")]
+    pub file: Option<PathBuf>,
+
+    /// Line in `--file`, runs the enclosing test function or test module
+    #[arg(long, value_name = "N", requires = "file")]
+    pub line: Option<usize>,
+
+    /// Run only the tests that failed in the last run of the config
+    #[arg(long, conflicts_with_all = ["name", "file"])]
+    pub rerun_failed: bool,
+}
+
+#[derive(Args)]
+pub struct BuildArgs {
+    #[command(flatten)]
+    pub run: RunArgs,
+
+    /// Copy the built binaries and libraries into this directory once the build succeeds,
+    /// `${profile}` and `${target}` resolve per artifact, e.g. `dist/${target}/${profile}`
+    #[arg(long, value_name = "DIR")]
+    pub copy_to: Option<String>,
+
+    /// Build for this target triple, through the config's `cross_runner` when it isn't the host
+    #[arg(long, value_name = "TRIPLE")]
+    pub target: Option<String>,
+
+    /// Compare the binary sizes and build time with those recorded for a git revision (branch,
+    /// tag or sha)
+    #[arg(long, value_name = "BASELINE")]
+    pub compare_size: Option<String>,
+}
+
+#[derive(Args)]
+pub struct FixArgs {
+    #[command(flatten)]
+    pub run: RunArgs,
+
+    /// Apply the fixes: `--fix --allow-dirty` for clippy, no `--check` for fmt
+    #[arg(long)]
+    pub fix: bool,
+}
+
+#[derive(Args)]
+pub struct BenchArgs {
+    #[command(flatten)]
+    pub run: RunArgs,
+
+    /// Compare against the results recorded for a git revision (branch, tag or sha)
+    #[arg(long, value_name = "BASELINE")]
+    pub compare: Option<String>,
+
+    /// Percent slowdown reported as a regression, overrides `settings.bench_threshold`
+    #[arg(long, value_name = "PERCENT")]
+    pub threshold: Option<u32>,
+}
+
+#[derive(Args)]
+pub struct DebugArgs {
+    /// Config key to debug, defaults to the context's `default`
+    pub key: Option<String>,
+
+    /// Context of the config, `run` or `test`
+    #[arg(long, default_value = "run")]
+    pub context: CommandContext,
+
+    /// `lldb`, `gdb`, or `dap` to print a launch configuration for an editor instead
+    #[arg(long, default_value = "lldb")]
+    pub debugger: Debugger,
+
+    /// Extra arguments appended to the command
+    #[arg(last = true)]
+    pub args: Vec<String>,
+}
+
+#[derive(Args)]
+pub struct ImportArgs {
+    /// `just`, `make` or `bacon`
+    pub format: ImportFormat,
+
+    /// File to import, defaults to the tool's own file in the current directory
+    #[arg(long)]
+    pub file: Option<PathBuf>,
+
+    /// Replace script configs that already exist under a recipe's name
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Args)]
+pub struct ExportArgs {
+    /// `cargo-make`, `xtask` or `gha` for a GitHub Actions workflow running rx
+    pub format: ExportFormat,
+
+    /// Config the workflow runs, as `context::key` or a context for its default, can be
+    /// repeated. Defaults to those of fmt, lint, test and build, gha only
+    #[arg(long, value_name = "LABEL")]
+    pub select: Vec<String>,
+
+    /// File to write, printed to stdout when not given
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct ReportArgs {
+    /// `markdown` or `json`
+    #[arg(long, value_name = "FORMAT", default_value = "markdown")]
+    pub format: ReportFormat,
+
+    /// Configs listed in each section
+    #[arg(long, default_value_t = 10)]
+    pub top: usize,
+
+    /// File to write, printed to stdout when not given
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct ListArgs {
+    /// Prefix each entry with the file defining it, the main config or an included file
+    #[arg(long)]
+    pub show_origin: bool,
+
+    /// Only the entries tagged TAG
+    #[arg(long, value_name = "TAG")]
+    pub tag: Option<String>,
+}
+
+#[derive(Args)]
+pub struct CheckArgs {
+    /// Check every member of the workspace instead of the one in the current directory
+    #[arg(long)]
+    pub workspace: bool,
+
+    /// Member to check instead of the one in the current directory, can be repeated
+    #[arg(
+        long,
+        short,
+        value_name = "MEMBER",
+        conflicts_with = "workspace",
+        add = ArgValueCompleter::new(complete_members)
+    )]
+    pub package: Vec<String>,
+
+    /// Steps to run on each member, in order, a member's next step only once the last passed
+    #[arg(long, value_delimiter = ',', default_value = "check,clippy,test")]
+    pub steps: Vec<CheckStep>,
+
+    /// Check at most N members at a time, as many as there are CPUs by default
+    #[arg(long, short, value_name = "N")]
+    pub jobs: Option<usize>,
+
+    /// Print the outcomes as JSON instead of a table
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args)]
+pub struct DaemonArgs {
+    /// Socket to listen on, defaults to rx.sock in $XDG_RUNTIME_DIR
+    #[arg(long, value_name = "PATH")]
+    pub socket: Option<PathBuf>,
+    /// Also listen on TCP, e.g. 0.0.0.0:7879, for `rx --remote`. Clients authenticate with
+    /// the token in $RX_DAEMON_TOKEN, and only on loopback without --tls-cert
+    #[arg(long, value_name = "ADDR")]
+    pub listen: Option<String>,
+    /// Certificate chain the TCP connections use TLS with, a PEM file
+    #[arg(long, value_name = "PATH", requires_all = ["listen", "tls_key"])]
+    pub tls_cert: Option<PathBuf>,
+    /// Private key of --tls-cert, a PEM file
+    #[arg(long, value_name = "PATH", requires = "tls_cert")]
+    pub tls_key: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct ServeArgs {
+    /// Port to listen on, on 127.0.0.1 only
+    #[arg(long, default_value_t = DEFAULT_PORT)]
+    pub port: u16,
+}
+
+#[derive(Args)]
+pub struct UpArgs {
+    /// Keys of the services in the script context or names of `[groups]`
+    pub keys: Vec<String>,
+    /// Return once a group started instead of following its services' output
+    #[arg(short, long)]
+    pub detach: bool,
+}
+
+#[derive(Args)]
+pub struct ServiceArgs {
+    /// Keys of the services in the script context or names of `[groups]`
+    pub keys: Vec<String>,
+}
+
+#[derive(Args)]
+pub struct InitArgs {
+    /// Seed the defaults for a bin, lib or workspace project instead of the detected kind
+    #[arg(long)]
+    pub kind: Option<ProjectKind>,
+
+    /// Replace an existing config file
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Args, Clone)]
+pub struct AddArgs {
+    pub context: CommandContext,
+    pub key: String,
+
+    #[arg(long = "type")]
+    pub command_type: Option<CommandType>,
+
+    /// What the config is for, shown when configs are listed
+    #[arg(long)]
+    pub description: Option<String>,
+
+    #[arg(long)]
+    pub command: Option<String>,
+
+    /// Params of the command, e.g. `--params "--release"`
+    #[arg(long, allow_hyphen_values = true)]
+    pub params: Option<String>,
+
+    /// Environment variable as KEY=VALUE, can be repeated
+    #[arg(long, value_parser = parse_env)]
+    pub env: Vec<(String, String)>,
+
+    /// Key of the same context to run first, can be repeated
+    #[arg(long)]
+    pub pre_command: Vec<String>,
+
+    #[arg(long)]
+    pub working_directory: Option<String>,
+
+    #[arg(long)]
+    pub allow_multiple_instances: bool,
+
+    /// Also make the new entry the context's default
+    #[arg(long)]
+    pub default: bool,
+
+    /// Don't ask for the omitted fields, which otherwise happens when stdin is a terminal
+    #[arg(long)]
+    pub no_input: bool,
+}
+
+fn parse_env(value: &str) -> Result<(String, String), String> {
+    value
+        .split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected KEY=VALUE, got '{}'", value))
+}
@@ -0,0 +1,170 @@
+use std::{
+    error::Error,
+    io::{stderr, stdin, IsTerminal},
+};
+
+use rx_core::{
+    builders::config::ConfigBuilder,
+    errors::ConfigError,
+    helpers::is_valid_env_var_name,
+    models::config::{CommandDetails, CommandType, Config},
+    prompt::Prompt,
+    validator::Validator,
+};
+
+use crate::cli::AddArgs;
+
+pub fn add(config: &mut Config, args: &AddArgs) -> Result<(), Box<dyn Error>> {
+    let args = &if !args.no_input && stdin().is_terminal() {
+        ask_omitted(config, args)?
+    } else {
+        args.clone()
+    };
+
+    let context = args.context;
+    let config_key = args.key.clone();
+    let valid_pre_command_keys = config.commands.get_configs(context);
+
+    let pre_command_validator = Validator(move |details: &CommandDetails| {
+        if details.pre_command.contains(&config_key) {
+            return Err(ConfigError::InvalidPreCommand(format!(
+                "You cannot use {} as a pre_command",
+                config_key
+            )));
+        }
+        for pre_command in &details.pre_command {
+            if !valid_pre_command_keys.contains(pre_command) {
+                return Err(ConfigError::InvalidPreCommand(format!(
+                    "Pre-command must be any of the following: [{}]",
+                    valid_pre_command_keys.join(",")
+                )));
+            }
+        }
+        Ok(())
+    });
+
+    let env_validator = Validator(move |details| {
+        for key in details.env.keys() {
+            if !is_valid_env_var_name(key) {
+                return Err(ConfigError::InvalidEnvFormat);
+            }
+        }
+        Ok(())
+    });
+
+    let mut builder = ConfigBuilder::new(context)
+        .params(args.params.as_deref().unwrap_or_default())
+        .env(args.env.iter().cloned().collect())
+        .pre_command(args.pre_command.iter().cloned().collect())
+        .allow_multiple_instances(args.allow_multiple_instances)
+        .add_validator(pre_command_validator)
+        .add_validator(env_validator);
+
+    if let Some(command_type) = &args.command_type {
+        builder = builder.command_type(command_type.clone());
+    }
+    if let Some(description) = args.description.as_deref().filter(|text| !text.is_empty()) {
+        builder = builder.description(description);
+    }
+    if let Some(command) = &args.command {
+        builder = builder.command(command);
+    }
+    if let Some(working_directory) = &args.working_directory {
+        builder = builder.working_directory(working_directory);
+    }
+
+    let command_details = builder.build()?;
+
+    config
+        .commands
+        .get_or_default_config(context)
+        .update_config(&args.key, command_details);
+
+    if args.default {
+        config.commands.set_default_config(context, &args.key)?;
+    }
+
+    Ok(())
+}
+
+/// Walks through the fields missing from `args`, checking each answer before moving on.
+fn ask_omitted(config: &Config, args: &AddArgs) -> Result<AddArgs, Box<dyn Error>> {
+    let mut prompt = Prompt::new(stdin().lock(), stderr());
+    let mut args = args.clone();
+    let defaults = ConfigBuilder::new(args.context).build()?;
+
+    let command_type = match args.command_type.clone() {
+        Some(command_type) => command_type,
+        None => prompt.ask(
+            "Command type (cargo, shell, composite)",
+            Some(&defaults.command_type.to_string()),
+            |answer| answer.parse::<CommandType>().map_err(|err| err.to_string()),
+        )?,
+    };
+    args.command_type = Some(command_type.clone());
+
+    if args.description.is_none() {
+        let description = prompt.ask("Description, empty for none", Some(""), |answer| {
+            Ok(answer.to_string())
+        })?;
+        args.description = Some(description);
+    }
+
+    if args.command.is_none() && command_type != CommandType::Composite {
+        // The context's default command only makes sense for its default type
+        let default = if command_type == defaults.command_type {
+            defaults.command.line()
+        } else {
+            ""
+        };
+        let command = prompt.ask("Command", Some(default), |answer| match answer {
+            "" => Err("a command is required".to_string()),
+            answer => Ok(answer.to_string()),
+        })?;
+        args.command = Some(command);
+    }
+    if args.params.is_none() && command_type != CommandType::Composite {
+        args.params = Some(prompt.ask("Params", Some(""), |answer| Ok(answer.to_string()))?);
+    }
+    if args.working_directory.is_none() {
+        let working_directory = prompt.ask(
+            "Working directory",
+            Some(&defaults.working_directory),
+            |answer| Ok(answer.to_string()),
+        )?;
+        args.working_directory = Some(working_directory);
+    }
+    if args.env.is_empty() {
+        args.env = prompt.ask_many(
+            "Environment variable as KEY=VALUE, empty to finish",
+            |answer| {
+                let (key, value) = answer
+                    .split_once('=')
+                    .ok_or_else(|| format!("expected KEY=VALUE, got '{}'", answer))?;
+                if !is_valid_env_var_name(key) {
+                    return Err(ConfigError::InvalidEnvFormat.to_string());
+                }
+                Ok((key.to_string(), value.to_string()))
+            },
+        )?;
+    }
+
+    let keys: Vec<String> = config
+        .commands
+        .get_configs(args.context)
+        .into_iter()
+        .filter(|key| *key != args.key)
+        .collect();
+    if args.pre_command.is_empty() && !keys.is_empty() {
+        let question = format!("pre_command, one of {}, empty to finish", keys.join(", "));
+        args.pre_command = prompt.ask_many(&question, |answer| {
+            if keys.iter().any(|key| key == answer) {
+                Ok(answer.to_string())
+            } else {
+                Err(format!("'{}' is not a key of {}", answer, args.context))
+            }
+        })?;
+    }
+
+    Ok(args)
+}
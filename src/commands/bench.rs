@@ -0,0 +1,146 @@
+use std::{
+    error::Error,
+    io::{stdin, IsTerminal},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use rx_core::{
+    bench::{self, BenchRun, DEFAULT_REGRESSION_THRESHOLD},
+    executor::output::{OutputObserver, OutputStream},
+    git,
+    interpolation::InterpolationContext,
+    models::config::{CommandContext, Config},
+    session::Session,
+    store::Store,
+};
+
+use crate::{
+    cli::BenchArgs,
+    commands::run::{run, RunOptions},
+};
+
+/// Runs a bench config, records its results for the current git revision and optionally
+/// compares them with a baseline, returning 1 when a regression exceeds the threshold.
+pub fn bench(
+    config: &Config,
+    session: &Session,
+    args: &BenchArgs,
+    quiet: bool,
+) -> Result<i32, Box<dyn Error>> {
+    let output = Arc::new(Mutex::new(String::new()));
+    let sink = output.clone();
+    let observer: OutputObserver = Arc::new(move |_, stream, line| {
+        if stream == OutputStream::Stdout {
+            let mut output = sink.lock().unwrap();
+            output.push_str(line);
+            output.push('\n');
+        }
+    });
+
+    let code = run(
+        config,
+        CommandContext::Bench,
+        args.run.key.as_deref(),
+        &args.run.args,
+        RunOptions {
+            env: args.run.env.clone(),
+            inputs: args.run.input.clone(),
+            interactive: stdin().is_terminal(),
+            stdin: args.run.stdin_source(),
+            no_cache: args.run.no_cache,
+            quiet,
+            observer: Some(observer),
+            events: args.run.event_target(),
+            package: args.run.member()?,
+            session: session.clone(),
+            ..Default::default()
+        },
+    )?;
+    if code != 0 {
+        return Ok(code);
+    }
+
+    let results = bench::parse_output(&output.lock().unwrap());
+    if results.is_empty() {
+        if args.compare.is_some() {
+            return Err("No benchmark results found in the output to compare".into());
+        }
+        return Ok(0);
+    }
+
+    let cwd = std::env::current_dir()?;
+    let workspace_folder = InterpolationContext::from_dir(&cwd)
+        .get("workspaceFolder")
+        .map(PathBuf::from)
+        .unwrap_or(cwd);
+    let store = Store::for_workspace(&workspace_folder).ok_or("Could not find a data directory")?;
+
+    let current = BenchRun {
+        revision: git::revision(&workspace_folder).unwrap_or_else(|| "unversioned".to_string()),
+        results,
+    };
+
+    // Load the baseline first, comparing against the current revision must not see this run
+    let baseline = match &args.compare {
+        Some(baseline_name) => {
+            let revision = git::short_sha(&workspace_folder, baseline_name)
+                .unwrap_or_else(|| baseline_name.clone());
+            let baseline = BenchRun::load(&store, &revision)?.ok_or_else(|| {
+                format!(
+                    "No benchmark results recorded for '{}', run `rx bench` on it first",
+                    baseline_name
+                )
+            })?;
+            Some(baseline)
+        }
+        None => None,
+    };
+
+    current.save(&store)?;
+
+    let Some(baseline) = baseline else {
+        return Ok(0);
+    };
+
+    let threshold = args
+        .threshold
+        .or(config.settings.bench_threshold)
+        .unwrap_or(DEFAULT_REGRESSION_THRESHOLD);
+    let comparisons = bench::compare(&baseline, &current);
+
+    println!(
+        "\nComparing {} against {} (threshold {}%)",
+        current.revision, baseline.revision, threshold
+    );
+    for comparison in &comparisons {
+        let marker = if comparison.is_regression(threshold) {
+            "REGRESSION"
+        } else {
+            ""
+        };
+        let line = format!(
+            "  {:<40} {:>14.1} ns -> {:>14.1} ns  {:>+7.2}%  {}",
+            comparison.name,
+            comparison.baseline_ns,
+            comparison.current_ns,
+            comparison.change_percent,
+            marker
+        );
+        println!("{}", line.trim_end());
+    }
+
+    let regressions = comparisons
+        .iter()
+        .filter(|comparison| comparison.is_regression(threshold))
+        .count();
+    if regressions > 0 {
+        eprintln!(
+            "rx: {} benchmark(s) regressed by more than {}%",
+            regressions, threshold
+        );
+        return Ok(1);
+    }
+
+    Ok(0)
+}
@@ -0,0 +1,82 @@
+use std::{
+    error::Error,
+    process::{Command, Stdio},
+    sync::Mutex,
+    thread,
+};
+
+use rx_core::{
+    executor::shutdown::ShutdownSignal,
+    workspace::{self, Member},
+    workspace_check::run_checks,
+};
+
+use crate::{cli::CheckArgs, commands::run::INTERRUPTED_EXIT_CODE};
+
+/// Runs the steps of `args` on the workspace members it picks, then prints the output of the
+/// steps that failed and the table of outcomes. Exits with 1 when a step failed.
+pub fn check(args: &CheckArgs, quiet: bool) -> Result<i32, Box<dyn Error>> {
+    let cwd = std::env::current_dir()?;
+    let members = workspace::members(&cwd)?;
+    let selected: Vec<Member> = if args.workspace {
+        members
+    } else if !args.package.is_empty() {
+        args.package
+            .iter()
+            .map(|name| workspace::find_member(&members, name).cloned())
+            .collect::<Result<_, _>>()?
+    } else {
+        // The member whose directory is the closest around the current one
+        let member = members
+            .iter()
+            .filter(|member| cwd.starts_with(&member.manifest_dir))
+            .max_by_key(|member| member.manifest_dir.components().count())
+            .ok_or("No workspace member in the current directory, pass --workspace or --package")?;
+        vec![member.clone()]
+    };
+    let root = workspace::root_dir(&cwd).unwrap_or(cwd);
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let jobs = args
+        .jobs
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, usize::from));
+
+    let shutdown = ShutdownSignal::new();
+    shutdown.install_handler()?;
+    // The output of each step that failed, shown once everything ran
+    let failures = Mutex::new(Vec::new());
+    let matrix = run_checks(&selected, &args.steps, jobs, &shutdown, |member, step| {
+        let label = format!("{} {}", step, member.name);
+        if !quiet {
+            eprintln!("rx: ▶ {}", label);
+        }
+        let output = Command::new(&cargo)
+            .args(step.cargo_args(&member.name))
+            .current_dir(&root)
+            .stdin(Stdio::null())
+            .output();
+        let failure = match output {
+            Ok(output) if output.status.success() => return true,
+            Ok(output) => [output.stdout, output.stderr]
+                .iter()
+                .map(|bytes| String::from_utf8_lossy(bytes).trim_end().to_string())
+                .filter(|text| !text.is_empty())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Err(err) => format!("cannot run {}: {}", cargo, err),
+        };
+        failures.lock().unwrap().push((label, failure));
+        false
+    })?;
+
+    for (label, output) in failures.into_inner().unwrap() {
+        eprintln!("rx: ✗ {}\n{}\n", label, output);
+    }
+    match args.json {
+        true => print!("{}", matrix.to_json()?),
+        false => println!("{}", matrix.report()),
+    }
+    if shutdown.is_triggered() {
+        return Ok(INTERRUPTED_EXIT_CODE);
+    }
+    Ok(if matrix.success() { 0 } else { 1 })
+}
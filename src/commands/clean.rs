@@ -0,0 +1,46 @@
+use std::{error::Error, fs};
+
+use rx_core::{
+    executor::{runner::Runner, shutdown::ShutdownSignal},
+    models::config::{CommandContext, Config},
+};
+
+/// Removes the `target_dir` the cargo config `context::key` builds into.
+pub fn clean(
+    config: &Config,
+    context: CommandContext,
+    key: &str,
+    quiet: bool,
+) -> Result<i32, Box<dyn Error>> {
+    let runner = Runner::new(config, context, ShutdownSignal::new());
+    let details = runner.details(key)?;
+    let label = runner.label(key);
+    let target_dir = runner
+        .target_dir(key, details)
+        .ok_or_else(|| format!("'{}' has no target_dir to clean", label))?;
+
+    let Ok(dir) = target_dir.canonicalize() else {
+        if !quiet {
+            eprintln!(
+                "rx: {} doesn't exist, nothing to clean",
+                target_dir.display()
+            );
+        }
+        return Ok(0);
+    };
+    // A target_dir like `.` would take the project with it
+    if std::env::current_dir()?.canonicalize()?.starts_with(&dir) {
+        return Err(format!(
+            "Refusing to remove {}, the directory rx runs in is inside it",
+            target_dir.display()
+        )
+        .into());
+    }
+
+    fs::remove_dir_all(&dir)
+        .map_err(|err| format!("cannot remove {}: {}", target_dir.display(), err))?;
+    if !quiet {
+        eprintln!("rx: removed {}", target_dir.display());
+    }
+    Ok(0)
+}
@@ -0,0 +1,97 @@
+use std::{
+    error::Error,
+    ffi::OsStr,
+    io::{stdout, Write},
+    path::PathBuf,
+};
+
+use clap_complete::{engine::CompletionCandidate, env::Shells};
+use rx_core::{
+    daemon::list_configs, models::config::CommandContext, source::ConfigSource, workspace,
+};
+
+use crate::load::load_config;
+
+/// The environment variable through which a shell asks rx for completions.
+pub const COMPLETE_ENV: &str = "RX_COMPLETE";
+
+/// Prints the script registering rx's completions with `shell`, one of
+/// [`Shells::builtins`].
+pub fn completions(shell: &str) -> Result<i32, Box<dyn Error>> {
+    let shells = Shells::builtins();
+    let completer = shells
+        .completer(shell)
+        .ok_or_else(|| format!("Unknown shell '{}'", shell))?;
+    let mut stdout = stdout().lock();
+    completer.write_registration(COMPLETE_ENV, "rx", "rx", "rx", &mut stdout)?;
+    stdout.flush()?;
+    Ok(0)
+}
+
+/// The keys of the context being completed that start with `current`, with their description
+/// as the hint: those of `rx script` for `rx script <TAB>`, and so on.
+pub fn complete_keys(current: &OsStr) -> Vec<CompletionCandidate> {
+    let Some(current) = current.to_str() else {
+        return Vec::new();
+    };
+    let args: Vec<String> = std::env::args().collect();
+    // The team config's and the scripts_dir's keys complete too
+    let Some(config) = ConfigSource::resolve(config_path(&args))
+        .ok()
+        .and_then(|source| load_config(&source).ok())
+    else {
+        return Vec::new();
+    };
+
+    list_configs(&config, context(&args))
+        .into_iter()
+        .filter(|entry| entry.key.starts_with(current))
+        .map(|entry| CompletionCandidate::new(entry.key).help(entry.description.map(Into::into)))
+        .collect()
+}
+
+/// The members of the cargo workspace in the current directory that start with `current`.
+pub fn complete_members(current: &OsStr) -> Vec<CompletionCandidate> {
+    let Some(current) = current.to_str() else {
+        return Vec::new();
+    };
+    let Ok(members) = std::env::current_dir()
+        .map_err(|err| err.to_string())
+        .and_then(|cwd| workspace::members(&cwd))
+    else {
+        return Vec::new();
+    };
+
+    members
+        .into_iter()
+        .filter(|member| member.name.starts_with(current))
+        .map(|member| CompletionCandidate::new(member.name))
+        .collect()
+}
+
+/// The context of the subcommand on the command line being completed, its first word naming
+/// one, `None` for every context.
+fn context(args: &[String]) -> Option<CommandContext> {
+    args.iter()
+        .skip_while(|arg| *arg != "--")
+        .skip(2)
+        .find_map(|arg| {
+            CommandContext::ALL
+                .into_iter()
+                .find(|context| context.to_string() == *arg)
+        })
+}
+
+/// The `--config` given on the command line being completed.
+fn config_path(args: &[String]) -> Option<PathBuf> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(PathBuf::from(path));
+        }
+        if arg == "--config" {
+            return args.next().map(PathBuf::from);
+        }
+    }
+    None
+}
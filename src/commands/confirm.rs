@@ -0,0 +1,112 @@
+use std::{
+    error::Error,
+    fs,
+    io::{stderr, stdin, IsTerminal},
+    path::Path,
+};
+
+use rx_core::{
+    backup::{Backups, DEFAULT_BACKUP_LIMIT},
+    diff::{colorize, unified_diff},
+    models::config::Config,
+    prompt::Prompt,
+    source::ConfigSource,
+};
+
+use crate::cli::Cli;
+
+/// How a change to the config file is shown and confirmed, the same for every subcommand that
+/// rewrites it.
+pub struct Confirmation {
+    diff_only: bool,
+    quiet: bool,
+    yes: bool,
+    color: bool,
+}
+
+impl Confirmation {
+    pub fn new(cli: &Cli) -> Self {
+        Self {
+            diff_only: cli.diff_only,
+            quiet: cli.quiet,
+            yes: cli.yes,
+            color: cli.color.enabled(stderr().is_terminal()),
+        }
+    }
+
+    /// Prints the diff from `current` to `updated` of the file at `path` to stderr and asks
+    /// whether to write it. `--diff-only` stops after the diff and `--yes` goes ahead without
+    /// asking, while `--quiet` leaves the diff out unless it's asked about. Without a terminal
+    /// to ask on, only `--yes` writes.
+    pub fn confirm(
+        &self,
+        path: &Path,
+        current: &str,
+        updated: &str,
+    ) -> Result<bool, Box<dyn Error>> {
+        let diff = unified_diff(current, updated, &path.to_string_lossy());
+        let asking = !self.diff_only && !self.yes && stdin().is_terminal();
+
+        if self.quiet && !self.diff_only && !asking {
+            // Nothing to show
+        } else if diff.is_empty() {
+            eprintln!("No changes to {}", path.display());
+        } else if self.color {
+            eprint!("{}", colorize(&diff));
+        } else {
+            eprint!("{}", diff);
+        }
+
+        if self.diff_only {
+            return Ok(false);
+        }
+        if diff.is_empty() || self.yes {
+            return Ok(true);
+        }
+        if !asking {
+            return Err(format!(
+                "Not changing {} without confirmation, pass --yes to write it without a terminal",
+                path.display()
+            )
+            .into());
+        }
+
+        let question = format!("Write these changes to {}", path.display());
+        Ok(Prompt::new(stdin().lock(), stderr()).confirm(&question, true)?)
+    }
+}
+
+/// Writes `config` to the file of `source` once the change is confirmed, backing up the file
+/// first. Returns whether the file was written.
+pub fn save_config(
+    config: &Config,
+    source: &ConfigSource,
+    confirmation: &Confirmation,
+) -> Result<bool, Box<dyn Error>> {
+    let path = source.path();
+    let current = fs::read_to_string(path).unwrap_or_default();
+    let updated = config.to_file_string(path)?;
+    if !confirmation.confirm(path, &current, &updated)? {
+        return Ok(false);
+    }
+
+    if current != updated {
+        Backups::new(path, backup_limit(config)).create()?;
+    }
+    source.save(config)?;
+    Ok(true)
+}
+
+/// The backups of the config of `source`, kept up to the limit it sets. Backing up and
+/// restoring work on a config that no longer loads, with the default limit.
+pub fn backups(source: &ConfigSource) -> Backups {
+    let limit = source
+        .load()
+        .map(|config| backup_limit(&config))
+        .unwrap_or(DEFAULT_BACKUP_LIMIT);
+    Backups::new(source.path(), limit)
+}
+
+fn backup_limit(config: &Config) -> usize {
+    config.settings.backup_limit.unwrap_or(DEFAULT_BACKUP_LIMIT)
+}
@@ -0,0 +1,574 @@
+#[cfg(unix)]
+pub use unix::{daemon, queue};
+
+/// The variable holding the token clients of `rx daemon --listen` authenticate with.
+pub const TOKEN_VARIABLE: &str = "RX_DAEMON_TOKEN";
+
+/// `rx daemon` needs unix domain sockets.
+#[cfg(not(unix))]
+pub fn daemon(
+    _config: rx_core::models::config::Config,
+    _session: &rx_core::session::Session,
+    _source: &rx_core::source::ConfigSource,
+    _args: &crate::cli::DaemonArgs,
+    _quiet: bool,
+) -> Result<i32, Box<dyn std::error::Error>> {
+    Err("rx daemon is only supported on unix".into())
+}
+
+/// `rx queue` asks the daemon over a unix socket.
+#[cfg(not(unix))]
+pub fn queue(_socket: &std::path::Path, _json: bool) -> Result<i32, Box<dyn std::error::Error>> {
+    Err("rx queue is only supported on unix".into())
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::{
+        collections::{hash_map::Entry, HashMap},
+        error::Error,
+        fs,
+        io::{self, BufRead, BufReader, ErrorKind, Write},
+        os::unix::net::UnixStream,
+        path::{Path, PathBuf},
+        pin::pin,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc, Mutex, RwLock,
+        },
+        time::Duration,
+    };
+
+    use rx_core::{
+        daemon::{
+            default_socket_path, list_configs, same_token, DaemonEvent, DaemonRequest, RunRequest,
+        },
+        executor::{
+            queue::{QueueSnapshot, RunQueue},
+            runner::Runner,
+            shutdown::ShutdownSignal,
+        },
+        interpolation::InterpolationContext,
+        models::config::Config,
+        session::Session,
+        source::ConfigSource,
+        watch::FileWatcher,
+        workspace::{self, Member},
+    };
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt},
+        net::{TcpListener, UnixListener},
+        sync::{mpsc, Notify},
+        task::JoinSet,
+    };
+
+    #[cfg(feature = "tls")]
+    use rx_core::tls::{self, SslAcceptor as Acceptor};
+
+    use super::TOKEN_VARIABLE;
+    use crate::{
+        cli::DaemonArgs,
+        commands::run::{run, EventTarget, RunOptions, INTERRUPTED_EXIT_CODE},
+        load,
+    };
+
+    const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+    /// Built without the `tls` feature, no TLS connection is ever accepted.
+    #[cfg(not(feature = "tls"))]
+    enum Acceptor {}
+
+    /// State shared by the connections of a daemon.
+    struct Daemon {
+        /// The config as last loaded, replaced when the file changes
+        config: RwLock<Arc<Config>>,
+        source: ConfigSource,
+        /// Shared by the runs, whose processes are driven on its runtime
+        session: Session,
+        quiet: bool,
+        connections: Mutex<Vec<Connection>>,
+        /// Placeholders per request `cwd`, resolving them reads the workspace's manifests
+        interpolations: Mutex<HashMap<PathBuf, InterpolationContext>>,
+        /// Workspace members per request `cwd`, from `cargo metadata`
+        members: Mutex<HashMap<PathBuf, Vec<Member>>>,
+        /// Ids, labels and shutdown signals of the runs in progress
+        runs: Mutex<Vec<(u64, String, ShutdownSignal)>>,
+        /// Wakes the daemon waiting for the runs to stop as it shuts down
+        run_finished: Notify,
+        next_run: AtomicU64,
+        /// Where the processes of every run wait for their turn
+        queue: RunQueue,
+    }
+
+    /// Listens on the socket of `args`, and on TCP with `--listen`, until rx is interrupted,
+    /// serving each connection as a task of the shared runtime. A connection sends one request
+    /// per line and gets ndjson events back, over TCP once it sent the token of
+    /// [`TOKEN_VARIABLE`] in an `auth` request.
+    pub fn daemon(
+        config: Config,
+        session: &Session,
+        source: &ConfigSource,
+        args: &DaemonArgs,
+        quiet: bool,
+    ) -> Result<i32, Box<dyn Error>> {
+        let socket = &args.socket.clone().unwrap_or_else(default_socket_path);
+        let token: Option<Arc<str>> = match &args.listen {
+            Some(_) => match std::env::var(TOKEN_VARIABLE) {
+                Ok(token) if !token.is_empty() => Some(token.into()),
+                _ => {
+                    return Err(format!(
+                        "--listen needs the token clients authenticate with in {}",
+                        TOKEN_VARIABLE
+                    )
+                    .into())
+                }
+            },
+            None => None,
+        };
+        let acceptor = match (&args.tls_cert, &args.tls_key) {
+            (Some(cert), Some(key)) => Some(Arc::new(acceptor(cert, key)?)),
+            _ => None,
+        };
+        if socket.exists() {
+            if UnixStream::connect(socket).is_ok() {
+                return Err(
+                    format!("a daemon is already listening on {}", socket.display()).into(),
+                );
+            }
+            // Left behind by a daemon that didn't shut down cleanly
+            fs::remove_file(socket)?;
+        }
+
+        let queue = RunQueue::new(config.settings.max_concurrent_runs);
+        let daemon = Arc::new(Daemon {
+            config: RwLock::new(Arc::new(config)),
+            source: source.clone(),
+            session: session.clone(),
+            quiet,
+            connections: Mutex::new(Vec::new()),
+            interpolations: Mutex::new(HashMap::new()),
+            members: Mutex::new(HashMap::new()),
+            runs: Mutex::new(Vec::new()),
+            run_finished: Notify::new(),
+            next_run: AtomicU64::new(0),
+            queue,
+        });
+        let shutdown = ShutdownSignal::new();
+        shutdown.install_handler()?;
+
+        session.block_on(async {
+            let tcp = match &args.listen {
+                Some(address) => {
+                    let tcp = TcpListener::bind(address)
+                        .await
+                        .map_err(|err| format!("cannot listen on {}: {}", address, err))?;
+                    let local = tcp.local_addr()?;
+                    // Only a client on the same host may send the token in the clear
+                    if acceptor.is_none() && !local.ip().is_loopback() {
+                        return Err(format!(
+                            "listening on {} needs --tls-cert and --tls-key",
+                            local
+                        )
+                        .into());
+                    }
+                    if !quiet {
+                        let scheme = if acceptor.is_some() { "tls" } else { "tcp" };
+                        eprintln!("rx: daemon listening on {}://{}", scheme, local);
+                    }
+                    Some(tcp)
+                }
+                None => None,
+            };
+            let listener = UnixListener::bind(socket)
+                .map_err(|err| format!("cannot listen on {}: {}", socket.display(), err))?;
+            if !quiet {
+                eprintln!("rx: daemon listening on {}", socket.display());
+            }
+
+            let mut tasks = JoinSet::new();
+            tasks.spawn(daemon.clone().watch());
+            let accepted = loop {
+                tokio::select! {
+                    accepted = listener.accept() => match accepted {
+                        Ok((stream, _)) => {
+                            tasks.spawn(daemon.clone().serve(stream, None));
+                        }
+                        Err(err) => break Err(err),
+                    },
+                    accepted = accept_tcp(tcp.as_ref()) => match accepted {
+                        Ok(stream) => {
+                            let serving = daemon.clone().serve_tcp(stream, token.clone(), acceptor.clone());
+                            tasks.spawn(serving);
+                        }
+                        Err(err) => break Err(err),
+                    },
+                    () = shutdown.triggered() => break Ok(()),
+                }
+            };
+
+            let _ = fs::remove_file(socket);
+            // Give the runs their grace period to stop their process groups
+            daemon.kill(None);
+            daemon.runs_stopped().await;
+            tasks.abort_all();
+            accepted?;
+            Ok(INTERRUPTED_EXIT_CODE)
+        })
+    }
+
+    impl Daemon {
+        fn config(&self) -> Arc<Config> {
+            self.config.read().unwrap().clone()
+        }
+
+        /// The config file and the files it includes.
+        fn watched_files(&self) -> Vec<PathBuf> {
+            let config = self.config();
+            std::iter::once(self.source.path().to_path_buf())
+                .chain(config.included_files().iter().cloned())
+                .collect()
+        }
+
+        fn load_config(&self) -> Result<Config, Box<dyn Error>> {
+            let config = load::load_config(&self.source)?;
+            config.validate()?;
+            Ok(config)
+        }
+
+        /// Reloads the config whenever it or a file it includes changes.
+        async fn watch(self: Arc<Self>) {
+            let mut watcher = FileWatcher::new(self.watched_files());
+            let mut interval = tokio::time::interval(WATCH_INTERVAL);
+            loop {
+                interval.tick().await;
+                if watcher.changed() {
+                    self.reload();
+                    // The includes may now list other files
+                    watcher.watch(self.watched_files());
+                }
+            }
+        }
+
+        /// Swaps in the changed config file when it's valid, and tells every connection either
+        /// way. Runs in progress finish with the config they started with.
+        fn reload(&self) {
+            let event = match self.load_config() {
+                Ok(config) => {
+                    self.queue.set_max(config.settings.max_concurrent_runs);
+                    *self.config.write().unwrap() = Arc::new(config);
+                    DaemonEvent::ConfigReloaded
+                }
+                Err(err) => DaemonEvent::ConfigError {
+                    error: err.to_string(),
+                },
+            };
+
+            if !self.quiet {
+                match &event {
+                    DaemonEvent::ConfigError { error } => eprintln!("rx: config error: {}", error),
+                    _ => eprintln!("rx: config reloaded"),
+                }
+            }
+            for connection in self.connections.lock().unwrap().iter() {
+                let _ = connection.reply(&event);
+            }
+        }
+
+        /// Serves a client of `--listen`, over TLS with `acceptor`.
+        async fn serve_tcp(
+            self: Arc<Self>,
+            stream: tokio::net::TcpStream,
+            token: Option<Arc<str>>,
+            acceptor: Option<Arc<Acceptor>>,
+        ) {
+            match acceptor {
+                #[cfg(feature = "tls")]
+                Some(acceptor) => {
+                    if let Ok(stream) = tls::accept(&acceptor, stream).await {
+                        self.serve(stream, token).await;
+                    }
+                }
+                #[cfg(not(feature = "tls"))]
+                Some(acceptor) => match *acceptor {},
+                None => self.serve(stream, token).await,
+            }
+        }
+
+        /// Answers the requests of a client until it goes away. With `token`, the first request
+        /// must be an `auth` with it, the connection is closed otherwise.
+        async fn serve(
+            self: Arc<Self>,
+            stream: impl AsyncRead + AsyncWrite + Send + 'static,
+            token: Option<Arc<str>>,
+        ) {
+            let (reader, writer) = tokio::io::split(stream);
+            let connection = Connection::open(writer);
+            let mut authenticated = token.is_none();
+            if authenticated {
+                self.connections.lock().unwrap().push(connection.clone());
+            }
+
+            let mut lines = tokio::io::BufReader::new(reader).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                let result = match (DaemonRequest::from_json(&line), &token) {
+                    (Ok(DaemonRequest::Auth { token: given }), Some(token))
+                        if !authenticated && same_token(token, &given) =>
+                    {
+                        authenticated = true;
+                        self.connections.lock().unwrap().push(connection.clone());
+                        connection.reply(&DaemonEvent::Authenticated)
+                    }
+                    _ if !authenticated => {
+                        let message = "unauthorized, the first request must be an auth with \
+                                       the daemon's token";
+                        let _ = connection.reply(&DaemonEvent::Error {
+                            message: message.to_string(),
+                        });
+                        break;
+                    }
+                    (Ok(request), _) => self.clone().handle(request, &connection).await,
+                    (Err(err), _) => connection.reply(&DaemonEvent::Error { message: err }),
+                };
+                if result.is_err() {
+                    break;
+                }
+            }
+
+            self.connections
+                .lock()
+                .unwrap()
+                .retain(|other| !other.same(&connection));
+        }
+
+        async fn handle(
+            self: Arc<Self>,
+            request: DaemonRequest,
+            connection: &Connection,
+        ) -> io::Result<()> {
+            match request {
+                DaemonRequest::Run(request) => {
+                    // A run blocks until its processes are done, it gets a blocking thread
+                    let daemon = self.clone();
+                    let events = connection.clone();
+                    let result = tokio::task::spawn_blocking(move || {
+                        daemon.run(request, &events).map_err(|err| err.to_string())
+                    })
+                    .await
+                    .unwrap_or_else(|err| Err(err.to_string()));
+                    match result {
+                        Ok(_) => Ok(()),
+                        Err(message) => connection.reply(&DaemonEvent::Error { message }),
+                    }
+                }
+                DaemonRequest::List { context } => connection.reply(&DaemonEvent::Configs {
+                    configs: list_configs(&self.config(), context),
+                }),
+                DaemonRequest::Validate => {
+                    let event = match self.load_config() {
+                        Ok(_) => DaemonEvent::Valid,
+                        Err(err) => DaemonEvent::Invalid {
+                            error: err.to_string(),
+                        },
+                    };
+                    connection.reply(&event)
+                }
+                DaemonRequest::Kill { label } => connection.reply(&DaemonEvent::Killed {
+                    labels: self.kill(label.as_deref()),
+                }),
+                DaemonRequest::Queue => connection.reply(&DaemonEvent::Queue {
+                    queue: self.queue.snapshot(),
+                }),
+                // Over the unix socket, or again once authenticated
+                DaemonRequest::Auth { .. } => connection.reply(&DaemonEvent::Authenticated),
+            }
+        }
+
+        /// Runs the requested config, streaming its events to `connection`. Errors, such as an
+        /// unknown key, are left to the caller to report.
+        fn run(&self, request: RunRequest, connection: &Connection) -> Result<i32, Box<dyn Error>> {
+            let cwd = match request.cwd {
+                Some(cwd) => cwd,
+                None => std::env::current_dir()?,
+            };
+            let mut interpolation = self
+                .interpolations
+                .lock()
+                .unwrap()
+                .entry(cwd.clone())
+                .or_insert_with(|| InterpolationContext::from_dir(&cwd))
+                .clone();
+            // The checkout may have changed since the placeholders were cached
+            interpolation.set_git(&cwd);
+
+            let package = match &request.package {
+                Some(name) => Some(self.member(&cwd, name)?),
+                None => None,
+            };
+
+            let config = self.config();
+            let shutdown = ShutdownSignal::new();
+            let runner = Runner::new(&config, request.context, shutdown.clone()).cwd(&cwd);
+            let key = runner.resolve_key(request.key.as_deref())?;
+            let id = self.next_run.fetch_add(1, Ordering::SeqCst);
+            self.runs
+                .lock()
+                .unwrap()
+                .push((id, runner.label(&key), shutdown.clone()));
+
+            let options = RunOptions {
+                env: request.env.into_iter().collect(),
+                no_cache: request.no_cache,
+                inputs: request.inputs.into_iter().collect(),
+                quiet: true,
+                events: Some(EventTarget::Connection(Box::new(connection.clone()))),
+                shutdown: Some(shutdown),
+                interpolation: Some(interpolation),
+                package,
+                fix: request.fix,
+                queue: Some(self.queue.clone()),
+                session: self.session.clone(),
+                ..Default::default()
+            };
+            let result = run(&config, request.context, Some(&key), &request.args, options);
+
+            self.runs.lock().unwrap().retain(|(run, ..)| *run != id);
+            self.run_finished.notify_waiters();
+            result
+        }
+
+        /// Completes once no run is in progress.
+        async fn runs_stopped(&self) {
+            loop {
+                let mut finished = pin!(self.run_finished.notified());
+                // Registered before checking, so a run finishing in between isn't missed
+                finished.as_mut().enable();
+                if self.runs.lock().unwrap().is_empty() {
+                    return;
+                }
+                finished.await;
+            }
+        }
+
+        /// The workspace member `name` seen from `cwd`, asking cargo only once per `cwd`.
+        fn member(&self, cwd: &Path, name: &str) -> Result<Member, Box<dyn Error>> {
+            let mut members = self.members.lock().unwrap();
+            let members = match members.entry(cwd.to_path_buf()) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry.insert(workspace::members(cwd)?),
+            };
+            Ok(workspace::find_member(members, name)?.clone())
+        }
+
+        /// Triggers the shutdown of the runs of `label`, or of every run, returning their labels.
+        fn kill(&self, label: Option<&str>) -> Vec<String> {
+            let runs = self.runs.lock().unwrap();
+            runs.iter()
+                .filter(|(_, run, _)| label.is_none_or(|label| label == run))
+                .map(|(_, run, shutdown)| {
+                    shutdown.trigger();
+                    run.clone()
+                })
+                .collect()
+        }
+    }
+
+    /// Prints what the daemon listening on `socket` runs and what waits for its turn, as
+    /// JSON with `json`.
+    pub fn queue(socket: &Path, json: bool) -> Result<i32, Box<dyn Error>> {
+        let mut stream = UnixStream::connect(socket)
+            .map_err(|err| format!("no daemon listening on {}: {}", socket.display(), err))?;
+        stream.write_all(format!("{}\n", r#"{"request":"queue"}"#).as_bytes())?;
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line)?;
+        if json {
+            print!("{}", line);
+            return Ok(0);
+        }
+
+        let queue = QueueSnapshot::from_json(&line)?;
+        for label in &queue.running {
+            println!("running  {}", label);
+        }
+        for (position, label) in queue.queued.iter().enumerate() {
+            println!("queued {} {}", position + 1, label);
+        }
+        let limit = match queue.max {
+            Some(max) => format!("at most {}", max),
+            None => "no limit".to_string(),
+        };
+        eprintln!(
+            "rx: {} running ({}), {} queued",
+            queue.running.len(),
+            limit,
+            queue.queued.len()
+        );
+        Ok(0)
+    }
+
+    /// The next client of `tcp`, never when the daemon doesn't listen on TCP.
+    async fn accept_tcp(tcp: Option<&TcpListener>) -> io::Result<tokio::net::TcpStream> {
+        match tcp {
+            Some(tcp) => tcp.accept().await.map(|(stream, _)| stream),
+            None => std::future::pending().await,
+        }
+    }
+
+    /// What TLS connections are accepted with, `--tls-cert` and `--tls-key`.
+    #[cfg(feature = "tls")]
+    fn acceptor(cert: &Path, key: &Path) -> Result<Acceptor, Box<dyn Error>> {
+        Ok(tls::acceptor(cert, key)?)
+    }
+
+    #[cfg(not(feature = "tls"))]
+    fn acceptor(_cert: &Path, _key: &Path) -> Result<Acceptor, Box<dyn Error>> {
+        Err("--tls-cert needs rx built with the tls feature".into())
+    }
+
+    /// A client's stream, shared by the runs it requested and the daemon's broadcasts. What's
+    /// written is queued for a task writing it out in order, so writing never blocks, be it
+    /// from a run's thread or from a task of the runtime.
+    #[derive(Clone)]
+    struct Connection(mpsc::UnboundedSender<Vec<u8>>);
+
+    impl Connection {
+        /// Starts the task writing to `writer`, which ends once the client is gone or every
+        /// clone of the connection is dropped.
+        fn open(mut writer: impl AsyncWriteExt + Unpin + Send + 'static) -> Self {
+            let (sender, mut receiver) = mpsc::unbounded_channel::<Vec<u8>>();
+            tokio::spawn(async move {
+                while let Some(bytes) = receiver.recv().await {
+                    if writer.write_all(&bytes).await.is_err() {
+                        break;
+                    }
+                }
+            });
+            Connection(sender)
+        }
+
+        fn reply(&self, event: &DaemonEvent) -> io::Result<()> {
+            let line = format!("{}\n", event.to_json());
+            self.clone().write_all(line.as_bytes())
+        }
+
+        fn same(&self, other: &Connection) -> bool {
+            self.0.same_channel(&other.0)
+        }
+    }
+
+    impl Write for Connection {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0
+                .send(buf.to_vec())
+                .map_err(|_| io::Error::from(ErrorKind::BrokenPipe))?;
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+}
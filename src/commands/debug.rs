@@ -0,0 +1,34 @@
+use std::error::Error;
+
+use rx_core::{
+    executor::{runner::Runner, shutdown::ShutdownSignal},
+    models::config::Config,
+    session::Session,
+};
+
+use crate::cli::DebugArgs;
+
+/// Builds the config's executable and launches it under the chosen debugger, or prints a DAP
+/// launch configuration for `--debugger dap`. Returns the debugger's exit code.
+pub fn debug(config: &Config, session: &Session, args: &DebugArgs) -> Result<i32, Box<dyn Error>> {
+    // Ctrl-C belongs to the debugger, rx only has to survive it
+    let shutdown = ShutdownSignal::new();
+    shutdown.install_handler()?;
+
+    let runner = Runner::new(config, args.context, shutdown).session(session);
+    let target = runner.build_debug_target(args.key.as_deref(), &args.args)?;
+
+    match target.command(args.debugger) {
+        Some(mut command) => {
+            let program = command.get_program().to_string_lossy().to_string();
+            let status = command
+                .status()
+                .map_err(|err| format!("could not start {}: {}", program, err))?;
+            Ok(status.code().unwrap_or(1))
+        }
+        None => {
+            println!("{}", target.dap_launch());
+            Ok(0)
+        }
+    }
+}
@@ -0,0 +1,17 @@
+use std::{error::Error, path::Path};
+
+use rx_core::{about::Locations, interpolation::InterpolationContext};
+
+/// Prints where rx reads and keeps its files, the project being the one of the current
+/// directory. Works without a config file, whose path is listed all the same.
+pub fn env(config_path: &Path, json: bool) -> Result<i32, Box<dyn Error>> {
+    let cwd = std::env::current_dir()?;
+    let interpolation = InterpolationContext::from_dir(&cwd);
+    let workspace_folder = interpolation.get("workspaceFolder").map(Path::new);
+    let locations = Locations::new(config_path, workspace_folder.unwrap_or(&cwd));
+    match json {
+        true => print!("{}", locations.to_json()?),
+        false => println!("{}", locations.report()),
+    }
+    Ok(0)
+}
@@ -0,0 +1,61 @@
+use std::error::Error;
+
+use rx_core::{
+    errors::ExecutionError,
+    executor::{
+        composite::parse_step,
+        runner::{exit_code, Runner},
+        shutdown::ShutdownSignal,
+    },
+    models::config::{CommandContext, CommandDetails, Config},
+    session::Session,
+};
+
+use crate::commands::run::INTERRUPTED_EXIT_CODE;
+
+/// Key `rx exec` runs under without `--like`
+const EXEC_KEY: &str = "exec";
+
+/// Runs `command` the way the config labeled `like` runs its own, see
+/// [`CommandDetails::for_exec`], in the script context from the current directory without
+/// one. Exits with the command's exit code.
+pub fn exec(
+    config: &Config,
+    session: &Session,
+    like: Option<&str>,
+    env: &[(String, String)],
+    command: &[String],
+) -> Result<i32, Box<dyn Error>> {
+    let (context, key, details) = match like {
+        Some(label) => {
+            let (context, key) = parse_step(label, CommandContext::Run)?;
+            let runner = Runner::new(config, context, ShutdownSignal::new());
+            let key = runner.resolve_key(Some(key))?;
+            let details = runner.details(&key)?.for_exec(command);
+            (context, key, details)
+        }
+        None => (
+            CommandContext::Script,
+            EXEC_KEY.to_string(),
+            CommandDetails::default().for_exec(command),
+        ),
+    };
+    // The command stands in for the config, `${key}` in its log_file or target_dir included
+    let mut config = config.clone();
+    config
+        .commands
+        .get_or_default_config(context)
+        .update_config(&key, details);
+
+    let shutdown = ShutdownSignal::new();
+    shutdown.install_handler()?;
+    let runner = Runner::new(&config, context, shutdown)
+        .session(session)
+        .env_overrides(env.to_vec());
+    match runner.run(Some(&key), &[]) {
+        Ok(status) => Ok(exit_code(&status)),
+        Err(ExecutionError::CommandFailed(_, code)) => Ok(code.unwrap_or(1)),
+        Err(ExecutionError::Interrupted(_)) => Ok(INTERRUPTED_EXIT_CODE),
+        Err(err) => Err(Box::new(err)),
+    }
+}
@@ -0,0 +1,77 @@
+use std::{
+    error::Error,
+    fs,
+    path::{Component, Path},
+};
+
+use rx_core::{
+    export::{github_actions, ExportFormat, WorkflowOptions},
+    interpolation::InterpolationContext,
+    models::config::Config,
+    workspace::root_dir,
+};
+
+use crate::cli::ExportArgs;
+
+pub fn export(
+    config: &Config,
+    config_path: &Path,
+    args: &ExportArgs,
+    quiet: bool,
+) -> Result<i32, Box<dyn Error>> {
+    let cwd = std::env::current_dir()?;
+    let export = match args.format {
+        ExportFormat::GithubActions => {
+            let mut options = WorkflowOptions {
+                configs: args.select.clone(),
+                ..Default::default()
+            };
+            let root = root_dir(&cwd).unwrap_or_else(|| cwd.clone());
+            match project_path(config_path, &root) {
+                Some(path) => options.config_path = path,
+                None if !quiet => eprintln!(
+                    "rx: {} is outside the project, commit a copy as {} for the workflow to read",
+                    config_path.display(),
+                    options.config_path
+                ),
+                None => {}
+            }
+            github_actions(config, &options)?
+        }
+        format => format.export(config, &InterpolationContext::from_dir(&cwd))?,
+    };
+
+    if !quiet {
+        for note in &export.notes {
+            eprintln!("rx: {}", note);
+        }
+    }
+
+    match &args.output {
+        Some(path) => {
+            fs::write(path, &export.content)
+                .map_err(|err| format!("cannot write {}: {}", path.display(), err))?;
+            if !quiet {
+                eprintln!("Wrote {}", path.display());
+            }
+        }
+        None => print!("{}", export.content),
+    }
+
+    Ok(0)
+}
+
+/// `path` relative to the project `root` with `/` separators, `None` when it's outside.
+fn project_path(path: &Path, root: &Path) -> Option<String> {
+    let path = fs::canonicalize(path).ok()?;
+    let root = fs::canonicalize(root).ok()?;
+    let relative = path.strip_prefix(root).ok()?;
+    let parts: Vec<String> = relative
+        .components()
+        .map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect::<Option<_>>()?;
+    Some(parts.join("/"))
+}
@@ -0,0 +1,81 @@
+use std::{
+    error::Error,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use rx_core::{
+    gc::{collect, find_garbage, find_runtime_garbage, remove_stale_configs, Garbage},
+    interpolation::InterpolationContext,
+    models::config::Config,
+    source::ConfigSource,
+};
+
+use crate::commands::{
+    confirm::{save_config, Confirmation},
+    run::workspace_store,
+};
+
+/// Lists the configs not run for `days` and what the project keeps for configs that are gone,
+/// or with `apply` removes them, the configs once the change to the config file is confirmed.
+/// With `runtime` only what crashed rx processes left is.
+pub fn gc(
+    config: &Config,
+    source: &ConfigSource,
+    days: u64,
+    apply: bool,
+    runtime: bool,
+    confirmation: &Confirmation,
+    quiet: bool,
+) -> Result<i32, Box<dyn Error>> {
+    let (_, store) = workspace_store()?;
+    let interpolation = InterpolationContext::from_dir(&std::env::current_dir()?);
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let garbage = match runtime {
+        true => find_runtime_garbage(&store, &std::env::temp_dir())?,
+        false => find_garbage(config, &store, &interpolation, days, now)?,
+    };
+    if garbage.is_empty() {
+        if !quiet {
+            eprintln!("rx: nothing to collect");
+        }
+        return Ok(0);
+    }
+
+    for item in &garbage {
+        match item {
+            Garbage::StaleConfig { last_run, .. } => println!(
+                "{}, last run {} days ago",
+                item,
+                now.saturating_sub(*last_run) / 86_400
+            ),
+            _ => println!("{}", item),
+        }
+    }
+    if !apply {
+        if !quiet {
+            let flags = if runtime {
+                "--runtime --apply"
+            } else {
+                "--apply"
+            };
+            eprintln!("rx: `rx gc {}` removes them", flags);
+        }
+        return Ok(0);
+    }
+
+    if garbage
+        .iter()
+        .any(|item| matches!(item, Garbage::StaleConfig { .. }))
+    {
+        let mut updated = config.clone();
+        remove_stale_configs(&mut updated, &garbage)?;
+        if !save_config(&updated, source, confirmation)? {
+            return Ok(0);
+        }
+    }
+    collect(&store, &garbage)?;
+    if !quiet {
+        eprintln!("rx: removed {} items", garbage.len());
+    }
+    Ok(0)
+}
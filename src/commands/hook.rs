@@ -0,0 +1,113 @@
+use std::{
+    error::Error,
+    fs,
+    io::{stdin, IsTerminal},
+    path::Path,
+};
+
+use rx_core::{
+    git::hooks_dir,
+    githook::{hook_script, install, uninstall},
+    models::config::{CommandContext, Config},
+    session::Session,
+    theme::Presenter,
+};
+
+use crate::commands::run::{run, with_steps, RunOptions};
+
+/// Writes a hook for each of `hooks`, those of `[hooks.git]` when none are given, each
+/// running `rx hook run` with the config at `config_path`.
+pub fn install_hooks(
+    config: &Config,
+    config_path: &Path,
+    hooks: &[String],
+    force: bool,
+    quiet: bool,
+) -> Result<i32, Box<dyn Error>> {
+    config.validate()?;
+    let hooks = selected(config, hooks)?;
+    let dir = hooks_dir(&std::env::current_dir()?).ok_or("not inside a git repository")?;
+    let rx = std::env::current_exe()?;
+    let config_path = fs::canonicalize(config_path)?;
+
+    for hook in hooks {
+        if !config.hooks.git.contains_key(&hook) && !quiet {
+            eprintln!(
+                "rx: {} has no [hooks.git.{}] yet, it will do nothing until it does",
+                hook, hook
+            );
+        }
+        let path = install(&dir, &hook, &hook_script(&hook, &rx, &config_path), force)?;
+        if !quiet {
+            eprintln!("Installed {}", path.display());
+        }
+    }
+    Ok(0)
+}
+
+/// Removes the hooks rx installed among `hooks`, those of `[hooks.git]` when none are given.
+pub fn uninstall_hooks(
+    config: &Config,
+    hooks: &[String],
+    quiet: bool,
+) -> Result<i32, Box<dyn Error>> {
+    let hooks = selected(config, hooks)?;
+    let dir = hooks_dir(&std::env::current_dir()?).ok_or("not inside a git repository")?;
+
+    for hook in hooks {
+        let removed = uninstall(&dir, &hook)?;
+        if !quiet {
+            if removed {
+                eprintln!("Removed {}", dir.join(&hook).display());
+            } else {
+                eprintln!("rx: no hook installed by rx for {}", hook);
+            }
+        }
+    }
+    Ok(0)
+}
+
+/// Runs the configs of `[hooks.git.<hook>]` as a composite config, what an installed hook does.
+pub fn run_hook(
+    config: &Config,
+    session: &Session,
+    hook: &str,
+    quiet: bool,
+    presenter: Presenter,
+) -> Result<i32, Box<dyn Error>> {
+    config.validate()?;
+    let Some(git_hook) = config.hooks.git.get(hook) else {
+        if !quiet {
+            eprintln!("rx: no [hooks.git.{}] in the config, nothing to run", hook);
+        }
+        return Ok(0);
+    };
+    let composite = git_hook.to_composite(config)?;
+    let Some(first) = composite.steps.first() else {
+        return Ok(0);
+    };
+
+    // The steps are labels, so the composite can sit in any context that has configs
+    let context: CommandContext = first.split("::").next().unwrap_or_default().parse()?;
+    let key = format!("hooks.git.{}", hook);
+    let config = with_steps(config, context, &key, composite.steps, composite.parallel);
+
+    let options = RunOptions {
+        quiet,
+        interactive: stdin().is_terminal(),
+        presenter,
+        session: session.clone(),
+        ..Default::default()
+    };
+    run(&config, context, Some(&key), &[], options)
+}
+
+fn selected(config: &Config, hooks: &[String]) -> Result<Vec<String>, Box<dyn Error>> {
+    if !hooks.is_empty() {
+        return Ok(hooks.to_vec());
+    }
+    if config.hooks.git.is_empty() {
+        return Err("no [hooks.git] in the config, add one or name the hooks to install".into());
+    }
+    Ok(config.hooks.git.keys().cloned().collect())
+}
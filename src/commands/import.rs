@@ -0,0 +1,52 @@
+use std::{error::Error, fs, path::PathBuf};
+
+use rx_core::models::config::{CommandContext, Config};
+
+use crate::cli::ImportArgs;
+
+/// Adds a script config per recipe or job of the file, keeping existing configs unless `--force`.
+pub fn import(config: &mut Config, args: &ImportArgs, quiet: bool) -> Result<(), Box<dyn Error>> {
+    let path = match &args.file {
+        Some(file) => file.clone(),
+        None => args
+            .format
+            .file_names()
+            .iter()
+            .map(PathBuf::from)
+            .find(|path| path.is_file())
+            .ok_or_else(|| {
+                format!(
+                    "No {} in the current directory, pass one with --file",
+                    args.format.file_names().join(" or ")
+                )
+            })?,
+    };
+    let content = fs::read_to_string(&path)
+        .map_err(|err| format!("cannot read {}: {}", path.display(), err))?;
+
+    let scripts = config
+        .commands
+        .get_or_default_config(CommandContext::Script);
+    let recipes = args
+        .format
+        .parse(&content)
+        .map_err(|err| format!("cannot parse {}: {}", path.display(), err))?;
+    for recipe in recipes {
+        let label = format!("{}::{}", CommandContext::Script, recipe.name);
+        if scripts.configs.contains_key(&recipe.name) && !args.force {
+            if !quiet {
+                eprintln!("rx: kept the existing {}, use --force to replace it", label);
+            }
+            continue;
+        }
+
+        if !quiet {
+            for note in &recipe.notes {
+                eprintln!("rx: {}: {}", label, note);
+            }
+        }
+        scripts.update_config(&recipe.name, recipe.to_details());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use std::{
+    error::Error,
+    fs,
+    io::{stderr, stdin, BufRead, IsTerminal, Write},
+    path::Path,
+};
+
+use rx_core::{
+    helpers::write_to_config_file,
+    models::config::Config,
+    prompt::Prompt,
+    workspace::{project_kind, ProjectKind},
+};
+
+use crate::cli::InitArgs;
+
+/// `rx init`: writes the config seeded for the kind of the current project, asking to confirm
+/// the detected kind when run in a terminal.
+pub fn init(config_path: &Path, args: &InitArgs, quiet: bool) -> Result<i32, Box<dyn Error>> {
+    if config_path.exists() && !args.force {
+        return Err(format!(
+            "{} already exists, pass --force to replace it",
+            config_path.display()
+        )
+        .into());
+    }
+
+    let kind = match args.kind {
+        Some(kind) => kind,
+        None if stdin().is_terminal() => {
+            ask_kind(&mut Prompt::new(stdin().lock(), stderr()), detected_kind()?)?
+        }
+        None => detected_kind()?,
+    };
+    create(config_path, kind, quiet)?;
+    Ok(0)
+}
+
+/// Offers to create the missing config at `config_path` before its first use, returning whether
+/// it was created. Only for a terminal, elsewhere there is nobody to ask.
+pub fn offer(config_path: &Path, quiet: bool) -> Result<bool, Box<dyn Error>> {
+    let mut prompt = Prompt::new(stdin().lock(), stderr());
+    let question = format!("No config at {}, create one", config_path.display());
+    let create_config = prompt.confirm(&question, true)?;
+    if !create_config {
+        return Ok(false);
+    }
+
+    let kind = ask_kind(&mut prompt, detected_kind()?)?;
+    create(config_path, kind, quiet)?;
+    Ok(true)
+}
+
+/// The config `rx init` writes for the current project when the detected kind is kept.
+pub fn initial_config() -> Result<Config, Box<dyn Error>> {
+    Ok(Config::for_project(detected_kind()?))
+}
+
+/// The kind of the project in the current directory, a binary outside of a cargo project.
+fn detected_kind() -> Result<ProjectKind, Box<dyn Error>> {
+    Ok(project_kind(&std::env::current_dir()?).unwrap_or(ProjectKind::Bin))
+}
+
+fn ask_kind<R: BufRead, W: Write>(
+    prompt: &mut Prompt<R, W>,
+    detected: ProjectKind,
+) -> Result<ProjectKind, Box<dyn Error>> {
+    Ok(prompt.ask(
+        "Project kind (bin, lib, workspace)",
+        Some(&detected.to_string()),
+        |answer| answer.parse::<ProjectKind>(),
+    )?)
+}
+
+fn create(config_path: &Path, kind: ProjectKind, quiet: bool) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = config_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    write_to_config_file(config_path, &toml::to_string(&Config::for_project(kind))?)?;
+    if !quiet {
+        eprintln!(
+            "rx: created {} with the defaults for a {} project",
+            config_path.display(),
+            kind
+        );
+    }
+    Ok(())
+}
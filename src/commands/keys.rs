@@ -0,0 +1,100 @@
+use std::{
+    error::Error,
+    io::{self, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use rx_core::{
+    executor::shutdown::ShutdownSignal,
+    keys::{KeyAction, KeyReader, KEY_HELP},
+    models::config::{CommandContext, Config},
+};
+
+use crate::commands::{
+    run::{run, RunOptions},
+    view::OutputView,
+};
+
+const KEY_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Runs `key` of `context` like [`run`], controlled from the keyboard: `r` restarts the run,
+/// or runs it again once finished, `q` stops it or quits, `c` clears the screen and `p`
+/// pauses the output. `options` makes the options of each run.
+pub fn run_with_keys(
+    config: &Config,
+    context: CommandContext,
+    key: Option<&str>,
+    args: &[String],
+    options: impl Fn() -> RunOptions,
+) -> Result<i32, Box<dyn Error>> {
+    let first = options();
+    let presenter = first.presenter.clone();
+    let quiet = first.quiet;
+    let view = OutputView::new(first.ui, &presenter);
+    // Ctrl-C and q stop the run, r too but starts it again
+    let shutdown = ShutdownSignal::new();
+    shutdown.install_handler()?;
+    let restart = Arc::new(AtomicBool::new(false));
+
+    let _reader = {
+        let shutdown = shutdown.clone();
+        let restart = restart.clone();
+        let view = view.clone();
+        let presenter = presenter.clone();
+        KeyReader::start(&first.session, move |action| match action {
+            KeyAction::Restart => {
+                restart.store(true, Ordering::SeqCst);
+                shutdown.trigger();
+            }
+            KeyAction::Stop => shutdown.trigger(),
+            KeyAction::ClearScreen => {
+                let mut stdout = io::stdout().lock();
+                let _ = write!(stdout, "\x1b[2J\x1b[3J\x1b[H");
+                let _ = stdout.flush();
+            }
+            KeyAction::TogglePause => {
+                let paused = view.toggle_pause();
+                if !quiet {
+                    let note = match paused {
+                        true => "output paused, p shows it",
+                        false => "output resumed",
+                    };
+                    eprintln!("{}", presenter.note(note));
+                }
+            }
+        })
+        .map_err(|err| format!("--keys needs a terminal on stdin: {}", err))?
+    };
+    if !quiet {
+        eprintln!("{}", presenter.note(KEY_HELP));
+    }
+
+    let mut next = Some(first);
+    loop {
+        let mut options = next.take().unwrap_or_else(&options);
+        options.shutdown = Some(shutdown.clone());
+        options.view = Some(view.clone());
+        let code = run(config, context, key, args, options)?;
+
+        if !restart.load(Ordering::SeqCst) && !shutdown.is_triggered() {
+            if !quiet {
+                eprintln!("{}", presenter.note("r runs it again, q quits"));
+            }
+            while !restart.load(Ordering::SeqCst) && !shutdown.is_triggered() {
+                thread::sleep(KEY_POLL_INTERVAL);
+            }
+        }
+        if !restart.swap(false, Ordering::SeqCst) {
+            return Ok(code);
+        }
+        shutdown.reset();
+        if !quiet {
+            eprintln!("{}", presenter.note("restarting"));
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use std::{error::Error, path::Path};
+
+use rx_core::{daemon::list_configs, models::config::Config};
+
+use crate::cli::ListArgs;
+
+/// Prints the entries of `config` by label, tab-separated from their description and, with
+/// `--show-origin`, preceded by the file defining them, `config_path` for its own.
+pub fn list(config: &Config, config_path: &Path, args: &ListArgs) -> Result<i32, Box<dyn Error>> {
+    let entries = list_configs(config, None)
+        .into_iter()
+        .filter(|entry| args.tag.as_ref().is_none_or(|tag| entry.tags.contains(tag)));
+    for entry in entries {
+        if args.show_origin {
+            let origin = config
+                .origin(entry.context, &entry.key)
+                .unwrap_or(config_path);
+            print!("{}\t", origin.display());
+        }
+        match &entry.description {
+            Some(description) => println!("{}\t{}", entry.label, description),
+            None => println!("{}", entry.label),
+        }
+    }
+    Ok(0)
+}
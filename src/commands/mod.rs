@@ -0,0 +1,33 @@
+pub mod add;
+pub mod bench;
+pub mod check;
+pub mod clean;
+pub mod completions;
+pub mod confirm;
+pub mod daemon;
+pub mod debug;
+pub mod env;
+pub mod exec;
+pub mod export;
+pub mod gc;
+pub mod hook;
+pub mod import;
+pub mod init;
+pub mod keys;
+pub mod list;
+pub mod record;
+pub mod remote;
+pub mod rerun;
+pub mod run;
+pub mod runnables;
+pub mod schema;
+pub mod secret;
+pub mod serve;
+pub mod service;
+pub mod show;
+pub mod sync;
+pub mod update;
+pub mod vars;
+pub mod version;
+pub mod view;
+pub mod watch;
@@ -0,0 +1,76 @@
+use std::{error::Error, path::Path};
+
+use rx_core::{
+    errors::{ConfigError, ExecutionError},
+    executor::{
+        runner::{exit_code, Runner},
+        shutdown::ShutdownSignal,
+    },
+    interpolation::InterpolationContext,
+    models::config::{CommandContext, Config},
+    record::recorded_details,
+};
+
+use crate::commands::run::INTERRUPTED_EXIT_CODE;
+
+/// Runs `command` as the config `key` of `context` would, see [`recorded_details`], and adds
+/// that config once the command succeeded. Exits with the command's exit code, leaving the
+/// config as it was when it failed.
+pub fn record(
+    config: &mut Config,
+    context: CommandContext,
+    key: &str,
+    env: &[(String, String)],
+    force: bool,
+    command: &[String],
+    quiet: bool,
+) -> Result<i32, Box<dyn Error>> {
+    let label = format!("{}::{}", context, key);
+    if !force
+        && config
+            .commands
+            .get_configs(context)
+            .iter()
+            .any(|existing| existing == key)
+    {
+        return Err(ConfigError::ConfigKeyExists(label).into());
+    }
+    let cwd = std::env::current_dir()?;
+    let interpolation = InterpolationContext::from_dir(&cwd);
+    let workspace_folder = interpolation.get("workspaceFolder").map(Path::new);
+    let details = recorded_details(command, env, &cwd, workspace_folder.unwrap_or(&cwd))
+        .map_err(|reason| ConfigError::InvalidValue(label.clone(), reason))?;
+
+    let mut recorded = config.clone();
+    recorded
+        .commands
+        .get_or_default_config(context)
+        .update_config(key, details.clone());
+    let shutdown = ShutdownSignal::new();
+    shutdown.install_handler()?;
+    let runner = Runner::new(&recorded, context, shutdown).interpolation(interpolation);
+    let code = match runner.run(Some(key), &[]) {
+        Ok(status) => exit_code(&status),
+        Err(ExecutionError::CommandFailed(_, code)) => code.unwrap_or(1),
+        Err(ExecutionError::Interrupted(_)) => INTERRUPTED_EXIT_CODE,
+        Err(err) => return Err(Box::new(err)),
+    };
+    if code != 0 {
+        if !quiet {
+            eprintln!(
+                "rx: {} not recorded, the command exited with {}",
+                label, code
+            );
+        }
+        return Ok(code);
+    }
+
+    config
+        .commands
+        .get_or_default_config(context)
+        .update_config(key, details);
+    if !quiet {
+        eprintln!("rx: recorded {}", label);
+    }
+    Ok(0)
+}
@@ -0,0 +1,277 @@
+#[cfg(unix)]
+pub use unix::remote;
+
+/// The variable holding the token of the daemon `rx --remote` drives.
+pub const TOKEN_VARIABLE: &str = "RX_REMOTE_TOKEN";
+
+/// The variable naming a PEM file of CAs to check the daemon's certificate with besides the
+/// system's, e.g. for a self-signed certificate.
+#[cfg(feature = "tls")]
+pub const CA_VARIABLE: &str = "RX_REMOTE_CA";
+
+/// `rx --remote` talks to the daemon the way `rx daemon` serves it, unix only.
+#[cfg(not(unix))]
+pub fn remote(
+    _address: &rx_core::daemon::RemoteAddress,
+    _command: &crate::cli::CliCommand,
+    _session: &rx_core::session::Session,
+    _quiet: bool,
+) -> Result<i32, Box<dyn std::error::Error>> {
+    Err("rx --remote is only supported on unix".into())
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::{
+        collections::BTreeMap,
+        error::Error,
+        io::{self, BufRead, BufReader, Read, Write},
+        net::{Shutdown, TcpStream},
+        sync::{Arc, Mutex},
+        thread,
+    };
+
+    use rx_core::{
+        daemon::{ClientEvent, DaemonRequest, RemoteAddress, RunRequest},
+        diagnostics::MessageFormat,
+        events::EventFormat,
+        executor::{output::OutputStream, shutdown::ShutdownSignal},
+        session::Session,
+        split::UiMode,
+        test_filter::TestFilter,
+    };
+
+    use super::TOKEN_VARIABLE;
+    use crate::{
+        cli::{CliCommand, RunArgs},
+        commands::run::INTERRUPTED_EXIT_CODE,
+    };
+
+    trait Stream: Read + Write + Send {}
+
+    impl<S: Read + Write + Send> Stream for S {}
+
+    /// A connection to the daemon, over TLS or not.
+    type Connection = BufReader<Box<dyn Stream>>;
+
+    /// Runs a config of `command` on the daemon listening at `address`, printing its output as
+    /// it comes, or its events with `--event-format ndjson`, and returns its exit code. Ctrl-C
+    /// stops the run on the daemon.
+    pub fn remote(
+        address: &RemoteAddress,
+        command: &CliCommand,
+        session: &Session,
+        quiet: bool,
+    ) -> Result<i32, Box<dyn Error>> {
+        let Some((context, args)) = command.run_args() else {
+            return Err("--remote only drives runs, e.g. `rx --remote host:port run`".into());
+        };
+        if let Some(flag) = local_only(command, args) {
+            return Err(format!("{} isn't supported with --remote", flag).into());
+        }
+        let token = std::env::var(TOKEN_VARIABLE)
+            .map_err(|_| format!("--remote needs the daemon's token in {}", TOKEN_VARIABLE))?;
+        let request = DaemonRequest::Run(RunRequest {
+            context,
+            key: args.key.clone(),
+            args: cargo_args(command, args),
+            env: args.env.iter().cloned().collect(),
+            inputs: args.input.iter().cloned().collect::<BTreeMap<_, _>>(),
+            cwd: None,
+            no_cache: args.no_cache,
+            package: args.package.clone(),
+            fix: command.fix(),
+        });
+
+        let (mut stream, socket) = connect(address, &token)?;
+        send(&mut stream, &request)?;
+        if !quiet {
+            eprintln!("rx: running on {}:{}", address.host, address.port);
+        }
+
+        // Ctrl-C stops the run on the daemon, which then reports it finished
+        let label = Arc::new(Mutex::new(None::<String>));
+        let shutdown = ShutdownSignal::new();
+        shutdown.install_handler()?;
+        {
+            let (address, token, label, shutdown, session) = (
+                address.clone(),
+                token.clone(),
+                label.clone(),
+                shutdown.clone(),
+                session.clone(),
+            );
+            thread::spawn(move || {
+                session.block_on(shutdown.triggered());
+                let label = label.lock().unwrap().clone();
+                // Not started yet, giving up on it is enough
+                let killed = label.is_some_and(|label| kill(&address, &token, label).is_ok());
+                if !killed {
+                    let _ = socket.shutdown(Shutdown::Both);
+                }
+            });
+        }
+
+        let ndjson = matches!(args.event_format, EventFormat::Ndjson);
+        let mut stdout = io::stdout();
+        let mut stderr = io::stderr();
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if stream.read_line(&mut line).unwrap_or(0) == 0 {
+                if shutdown.is_triggered() {
+                    return Ok(INTERRUPTED_EXIT_CODE);
+                }
+                return Err("the daemon closed the connection before the run finished".into());
+            }
+            if ndjson {
+                stdout.write_all(line.as_bytes())?;
+                stdout.flush()?;
+            }
+            match ClientEvent::from_json(&line)? {
+                ClientEvent::Started { label: started } => {
+                    label.lock().unwrap().get_or_insert(started);
+                }
+                ClientEvent::OutputChunk { stream, data } if !ndjson => {
+                    let out: &mut dyn Write = match stream {
+                        OutputStream::Stdout => &mut stdout,
+                        OutputStream::Stderr => &mut stderr,
+                    };
+                    // A chunk is a line of the command's output
+                    writeln!(out, "{}", data)?;
+                    out.flush()?;
+                }
+                ClientEvent::Finished { exit_code, error } => {
+                    if let (Some(error), false) = (error, quiet || ndjson) {
+                        eprintln!("rx: {}", error);
+                    }
+                    return Ok(exit_code);
+                }
+                ClientEvent::Error { message } => return Err(message.into()),
+                _ => {}
+            }
+        }
+    }
+
+    /// The first flag given that only makes sense for a run of the local config or files, or
+    /// that the daemon's events can't carry.
+    fn local_only(command: &CliCommand, args: &RunArgs) -> Option<&'static str> {
+        let (file, rerun_failed) = match command {
+            CliCommand::Test(test) => (test.file.is_some(), test.rerun_failed),
+            _ => (false, false),
+        };
+        let (compare, threshold) = match command {
+            CliCommand::Bench(bench) => (bench.compare.is_some(), bench.threshold.is_some()),
+            _ => (false, false),
+        };
+        [
+            (args.cwd.is_some(), "--cwd"),
+            (args.save_as.is_some(), "--save-as"),
+            (args.save, "--save"),
+            (!args.arg_values.is_empty(), "--arg"),
+            (args.command_type.is_some(), "--type"),
+            (args.params.is_some(), "--params"),
+            (!args.fields.is_empty(), "--set"),
+            (args.stdin.is_some(), "--stdin"),
+            (args.env_show, "--env-show"),
+            (args.dry_run, "--dry-run"),
+            (args.jobs.is_some(), "--jobs"),
+            (args.event_socket.is_some(), "--event-socket"),
+            (args.keys, "--keys"),
+            (args.watch, "--watch"),
+            (args.tag.is_some(), "--tag"),
+            (args.parallel, "--parallel"),
+            (
+                args.message_format != MessageFormat::Human,
+                "--message-format",
+            ),
+            (args.ui != UiMode::Interleaved, "--ui"),
+            (file, "--file"),
+            (rerun_failed, "--rerun-failed"),
+            (compare, "--compare"),
+            (threshold, "--threshold"),
+            (command.copy_to().is_some(), "--copy-to"),
+            (command.compare_size().is_some(), "--compare-size"),
+        ]
+        .into_iter()
+        .find_map(|(given, flag)| given.then_some(flag))
+    }
+
+    /// The arguments the run passes on to its command, `--target` of `rx build` and `--name` of
+    /// `rx test` as the cargo arguments they stand for, as a local run does.
+    fn cargo_args(command: &CliCommand, args: &RunArgs) -> Vec<String> {
+        match command {
+            CliCommand::Build(build) => build
+                .target
+                .iter()
+                .flat_map(|target| ["--target".to_string(), target.clone()])
+                .chain(args.args.iter().cloned())
+                .collect(),
+            CliCommand::Test(test) => match &test.name {
+                Some(name) => TestFilter::from_name(name).cargo_args(&args.args),
+                None => args.args.clone(),
+            },
+            _ => args.args.clone(),
+        }
+    }
+
+    /// Connects and authenticates with `token`. The TCP socket comes along to close the
+    /// connection from another thread.
+    fn connect(
+        address: &RemoteAddress,
+        token: &str,
+    ) -> Result<(Connection, TcpStream), Box<dyn Error>> {
+        let tcp = TcpStream::connect((address.host.as_str(), address.port)).map_err(|err| {
+            format!(
+                "no daemon listening on {}:{}: {}",
+                address.host, address.port, err
+            )
+        })?;
+        let socket = tcp.try_clone()?;
+        let stream: Box<dyn Stream> = match address.tls {
+            true => tls(&address.host, tcp)?,
+            false => Box::new(tcp),
+        };
+        let mut stream = BufReader::new(stream);
+        send(
+            &mut stream,
+            &DaemonRequest::Auth {
+                token: token.to_string(),
+            },
+        )?;
+        let mut line = String::new();
+        stream.read_line(&mut line)?;
+        match ClientEvent::from_json(&line) {
+            Ok(ClientEvent::Authenticated) => Ok((stream, socket)),
+            Ok(ClientEvent::Error { message }) => Err(message.into()),
+            _ => Err("the daemon didn't accept the token".into()),
+        }
+    }
+
+    /// TLS with `host` over `tcp`, its certificate checked against the system's CAs and those
+    /// of [`super::CA_VARIABLE`].
+    #[cfg(feature = "tls")]
+    fn tls(host: &str, tcp: TcpStream) -> Result<Box<dyn Stream>, Box<dyn Error>> {
+        let ca = std::env::var_os(super::CA_VARIABLE).map(std::path::PathBuf::from);
+        Ok(Box::new(rx_core::tls::connect(host, tcp, ca.as_deref())?))
+    }
+
+    #[cfg(not(feature = "tls"))]
+    fn tls(_host: &str, _tcp: TcpStream) -> Result<Box<dyn Stream>, Box<dyn Error>> {
+        Err("tls:// needs rx built with the tls feature, tcp:// connects in the clear".into())
+    }
+
+    fn send(stream: &mut Connection, request: &DaemonRequest) -> io::Result<()> {
+        let line = format!("{}\n", request.to_json());
+        let stream = stream.get_mut();
+        stream.write_all(line.as_bytes())?;
+        stream.flush()
+    }
+
+    /// Stops the run of `label` over a connection of its own.
+    fn kill(address: &RemoteAddress, token: &str, label: String) -> Result<(), Box<dyn Error>> {
+        let (mut stream, _) = connect(address, token)?;
+        send(&mut stream, &DaemonRequest::Kill { label: Some(label) })?;
+        Ok(())
+    }
+}
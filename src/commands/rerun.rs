@@ -0,0 +1,80 @@
+use std::{
+    error::Error,
+    io::{stdin, IsTerminal},
+};
+
+use rx_core::{
+    models::config::Config,
+    rerun::{parse_label, LastRun},
+    session::Session,
+    theme::Presenter,
+};
+
+use crate::commands::run::{run, workspace_store, RunOptions, INTERRUPTED_EXIT_CODE};
+
+/// Runs the last config run in this project again with its arguments and env, or with
+/// `failed` only its steps, matrix cells and dependencies that failed, one after another.
+/// The arguments only go to the config they were given to.
+pub fn rerun(
+    config: &Config,
+    session: &Session,
+    failed: bool,
+    quiet: bool,
+    presenter: Presenter,
+) -> Result<i32, Box<dyn Error>> {
+    let (_, store) = workspace_store()?;
+    let last_run = LastRun::load(&store)?.ok_or("No run recorded in this project yet")?;
+    let labels = match failed {
+        true if last_run.failed.is_empty() => {
+            if !quiet {
+                let message = format!("nothing failed in the last run of '{}'", last_run.label);
+                eprintln!("{}", presenter.note(&message));
+            }
+            return Ok(0);
+        }
+        true => last_run.failed.clone(),
+        false => vec![last_run.label.clone()],
+    };
+    let (run_context, run_key, _) = parse_label(&last_run.label)?;
+
+    let mut code = 0;
+    let mut still_failed = Vec::new();
+    for (index, label) in labels.iter().enumerate() {
+        let (context, key, cell) = parse_label(label)?;
+        let args = match context == run_context && key == run_key {
+            true => last_run.args.clone(),
+            false => Vec::new(),
+        };
+        let options = RunOptions {
+            env: last_run.env.clone().into_iter().collect(),
+            quiet,
+            interactive: stdin().is_terminal(),
+            presenter: presenter.clone(),
+            cell: cell.map(String::from),
+            session: session.clone(),
+            ..Default::default()
+        };
+        let step_code = run(config, context, Some(key), &args, options)?;
+        if step_code == INTERRUPTED_EXIT_CODE {
+            still_failed.extend(labels[index..].iter().cloned());
+            code = step_code;
+            break;
+        }
+        if step_code != 0 {
+            still_failed.extend(LastRun::load(&store)?.map_or_else(Vec::new, |run| run.failed));
+            if code == 0 {
+                code = step_code;
+            }
+        }
+    }
+
+    // Each step recorded itself as the last run, the next `rx rerun` starts from this one
+    if failed {
+        LastRun {
+            failed: still_failed,
+            ..last_run
+        }
+        .save(&store)?;
+    }
+    Ok(code)
+}
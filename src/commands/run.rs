@@ -0,0 +1,1220 @@
+use std::{
+    error::Error,
+    fs,
+    io::{self, IsTerminal, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use rx_core::{
+    artifacts::{copy_artifacts, Artifact},
+    build_size::{self, format_bytes, BuildRun},
+    diagnostics::{self, CargoMessage, DiagnosticSummary, MessageFormat},
+    env::{masked, process_env, EnvSource, LayeredEnv},
+    errors::{ConfigError, ExecutionError},
+    events::{millis, RunEvent},
+    executor::{
+        composite::{StepEvent, StepObserver, StepReport},
+        graph::PlanNode,
+        nextest,
+        output::OutputObserver,
+        queue::RunQueue,
+        runner::{exit_code, PreCommandFailureHandler, Runner, StdinSource},
+        shutdown::ShutdownSignal,
+    },
+    failure::{FailureReporter, DEFAULT_SUMMARY_LINES},
+    git,
+    helpers::host_triple,
+    interpolation::{Input, InterpolationContext},
+    models::config::{CommandContext, CommandDetails, CommandType, Config, NotifyOn, TestRunner},
+    notify::{self, format_duration},
+    ports::PortConflictHandler,
+    prompt::Prompt,
+    record::config_working_directory,
+    rerun::LastRun,
+    secret,
+    session::Session,
+    source::ConfigSource,
+    split::UiMode,
+    stats::{Report, ReportFormat, RunHistory},
+    store::Store,
+    test_summary::{FailedTests, TestCollector, TestSummary},
+    theme::Presenter,
+    timings::{Timings, COMMANDS, PREPARE},
+    workspace::{self, Member},
+};
+
+use crate::{
+    cli::{ReportArgs, RunArgs},
+    commands::{
+        confirm::{save_config, Confirmation},
+        view::OutputView,
+    },
+};
+
+/// Exit code used when rx itself is interrupted, mirroring a shell's 128 + SIGINT
+pub const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// Diagnostics listed after a `--message-format json` run, the rest are only counted
+const SUMMARY_LIMIT: usize = 10;
+
+/// How [`run`] runs a config, besides the config and its arguments.
+#[derive(Default)]
+pub struct RunOptions {
+    /// `--env` overrides
+    pub env: Vec<(String, String)>,
+    /// `--input` values of the config's `${input:..}` placeholders
+    pub inputs: Vec<(String, String)>,
+    /// `--arg` values of the config's `args`
+    pub arg_values: Vec<(String, String)>,
+    /// Ask for the inputs without a value on stdin
+    pub interactive: bool,
+    /// `--stdin` of the config run, its own `stdin` or `stdin_file` otherwise
+    pub stdin: Option<StdinSource>,
+    /// Run even when the config's inputs are unchanged
+    pub no_cache: bool,
+    pub quiet: bool,
+    pub observer: Option<OutputObserver>,
+    /// Where to send ndjson lifecycle events instead of printing progress lines
+    pub events: Option<EventTarget>,
+    /// Signal stopping the run, a fresh one triggered by Ctrl-C by default
+    pub shutdown: Option<ShutdownSignal>,
+    /// Placeholders resolved from the current directory by default
+    pub interpolation: Option<InterpolationContext>,
+    /// Workspace member selected with `--package`
+    pub package: Option<Member>,
+    /// Let lint and fmt configs apply their fixes
+    pub fix: bool,
+    pub message_format: MessageFormat,
+    /// Directory `rx build` copies the artifacts to, with `${profile}` and `${target}`
+    pub copy_to: Option<String>,
+    /// Git revision whose build `rx build` compares the binary sizes and build time with
+    pub compare_size: Option<String>,
+    /// Formats rx's own lines, uncolored by default
+    pub presenter: Presenter,
+    /// How the commands' output is shown, as they print it by default
+    pub ui: UiMode,
+    /// Shows the output instead of a view for `ui`, always passing it through the view so it
+    /// can be paused
+    pub view: Option<OutputView>,
+    /// Refuse to run a deprecated config instead of warning
+    pub strict: bool,
+    /// How many configs `depends_on` runs at a time, as many as there are CPUs by default
+    pub jobs: Option<usize>,
+    /// Run only this cell of the config's matrix, as labels show it, e.g. `target=x86_64`
+    pub cell: Option<String>,
+    /// Shared with the other runs of a daemon or dashboard, a queue of the run's own following
+    /// `max_concurrent_runs` otherwise
+    pub queue: Option<RunQueue>,
+    /// Gets the phases of the run, see `--timings`
+    pub timings: Option<Timings>,
+    /// Holds the runtime and the temp files of the run, its own by default
+    pub session: Session,
+}
+
+pub enum EventTarget {
+    /// rx's stdout, with the commands' output only carried in the events
+    Stdout,
+    /// A unix socket, the commands' output still goes to the terminal
+    Socket(PathBuf),
+    /// A client of `rx daemon`, with the commands' output only carried in the events
+    Connection(Box<dyn Write + Send>),
+}
+
+/// Writes events to their target one JSON line at a time, from whichever thread emits them.
+#[derive(Clone)]
+struct EventWriter(Arc<Mutex<Box<dyn Write + Send>>>);
+
+impl EventWriter {
+    fn open(target: EventTarget) -> io::Result<Self> {
+        let writer: Box<dyn Write + Send> = match target {
+            EventTarget::Stdout => Box::new(io::stdout()),
+            EventTarget::Connection(writer) => writer,
+            #[cfg(unix)]
+            EventTarget::Socket(path) => Box::new(std::os::unix::net::UnixStream::connect(path)?),
+            #[cfg(not(unix))]
+            EventTarget::Socket(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "event sockets are only supported on unix",
+                ))
+            }
+        };
+        Ok(EventWriter(Arc::new(Mutex::new(writer))))
+    }
+
+    /// Emits `event`, a reader that went away doesn't stop the run.
+    fn emit(&self, event: &RunEvent) {
+        let mut writer = self.0.lock().unwrap_or_else(|err| err.into_inner());
+        let line = format!("{}\n", event.to_json());
+        let _ = writer
+            .write_all(line.as_bytes())
+            .and_then(|_| writer.flush());
+    }
+}
+
+/// `config` with a composite config under `key` of `context` running the configs labeled
+/// `steps`, one after another or with `parallel` at once.
+pub fn with_steps(
+    config: &Config,
+    context: CommandContext,
+    key: &str,
+    steps: Vec<String>,
+    parallel: bool,
+) -> Config {
+    let mut config = config.clone();
+    config
+        .commands
+        .get_or_default_config(context)
+        .update_config(
+            key,
+            CommandDetails {
+                command_type: CommandType::Composite,
+                steps,
+                parallel,
+                ..Default::default()
+            },
+        );
+    config
+}
+
+/// `config` with the standalone Rust `file` as a config of the run context under its path,
+/// made from `[settings.single_file]`.
+pub fn with_single_file(config: &Config, file: &str) -> Config {
+    let mut config = config.clone();
+    let details = config.settings.single_file.details(file);
+    config
+        .commands
+        .get_or_default_config(CommandContext::Run)
+        .update_config(file, details);
+    config
+}
+
+pub fn run(
+    config: &Config,
+    context: CommandContext,
+    key: Option<&str>,
+    args: &[String],
+    options: RunOptions,
+) -> Result<i32, Box<dyn Error>> {
+    let RunOptions {
+        env,
+        inputs,
+        arg_values,
+        interactive,
+        stdin,
+        no_cache,
+        quiet,
+        observer,
+        events: event_target,
+        shutdown,
+        interpolation,
+        package,
+        fix,
+        message_format,
+        copy_to,
+        compare_size,
+        presenter,
+        ui,
+        view,
+        strict,
+        jobs,
+        cell,
+        queue,
+        timings,
+        session,
+    } = options;
+    let shutdown = match shutdown {
+        Some(shutdown) => shutdown,
+        None => {
+            let shutdown = ShutdownSignal::new();
+            shutdown.install_handler()?;
+            shutdown
+        }
+    };
+
+    let mut runner = Runner::new(config, context, shutdown)
+        .session(&session)
+        .env_overrides(env.clone())
+        .no_cache(no_cache)
+        .fix(fix)
+        .json_messages(message_format == MessageFormat::Json)
+        .report_artifacts(context == CommandContext::Build);
+    let interpolation = match interpolation {
+        Some(interpolation) => interpolation,
+        None => InterpolationContext::from_dir(&std::env::current_dir()?),
+    };
+    runner = runner
+        .interpolation(interpolation.clone())
+        .raw_output(quiet);
+    if let Some(member) = &package {
+        runner = runner.package(member);
+    }
+    if let Some(stdin) = stdin {
+        runner = runner.stdin(stdin);
+    }
+    if let Some(jobs) = jobs {
+        runner = runner.jobs(jobs);
+    }
+    if let Some(timings) = &timings {
+        runner = runner.timings(timings.clone());
+    }
+    let max_concurrent_runs = config.settings.max_concurrent_runs;
+    if let Some(queue) = queue.or_else(|| max_concurrent_runs.map(|max| RunQueue::new(Some(max)))) {
+        runner = runner.queue(queue);
+    }
+    runner = runner.on_port_conflict(port_conflict_handler(&presenter, interactive, quiet));
+    if interactive {
+        runner = runner.on_pre_command_failure(pre_command_failure_handler(&presenter));
+    }
+    let key = runner.resolve_key(key)?;
+    // A baseline that was never built fails before the build rather than after it
+    let workspace_folder = interpolation
+        .get("workspaceFolder")
+        .map(PathBuf::from)
+        .unwrap_or_default();
+    let size_baseline = compare_size
+        .map(|baseline| load_build(runner.project_store(), &workspace_folder, &baseline))
+        .transpose()?;
+    let echo_output = matches!(event_target, None | Some(EventTarget::Socket(_)));
+    let own_view = view.is_none();
+    let view = view.unwrap_or_else(|| OutputView::new(ui, &presenter));
+    let reporter = FailureReporter::new(
+        config
+            .settings
+            .summary_lines
+            .unwrap_or(DEFAULT_SUMMARY_LINES),
+    );
+    // Test runs are read for their results and those posted to webhooks for their stderr, the
+    // reporter gets the stderr lines wherever rx reads the output anyway
+    let tests = (context == CommandContext::Test).then(TestCollector::new);
+    let notifies = runner
+        .details(&key)
+        .is_ok_and(|details| !details.notify_on.is_empty());
+    let observed = observer.is_some() || tests.is_some() || notifies;
+    let observer = match &tests {
+        Some(tests) => Some(tests.observe_output(observer)),
+        None => observer,
+    };
+    let observer = Some(reporter.observe_output(observer));
+    let summary = Arc::new(Mutex::new(DiagnosticSummary::default()));
+    let artifacts = Arc::new(Mutex::new(Vec::new()));
+    let events = event_target
+        .map(EventWriter::open)
+        .transpose()
+        .map_err(|err| format!("could not open the event socket: {}", err))?;
+    match &events {
+        Some(events) => {
+            let step_events = events.clone();
+            runner = runner.observe_steps(reporter.observe_steps(Arc::new(move |event| {
+                step_events.emit(&RunEvent::from(event))
+            })));
+
+            let output_events = events.clone();
+            let artifacts = artifacts.clone();
+            runner = runner.observe_output(Arc::new(move |label, stream, line| {
+                if let Some(observer) = &observer {
+                    observer(label, stream, line);
+                }
+                let event = match diagnostics::parse_message(line) {
+                    Some(CargoMessage::Diagnostic(diagnostic)) => RunEvent::Diagnostic {
+                        label: label.to_string(),
+                        diagnostic,
+                    },
+                    Some(CargoMessage::Artifact(artifact)) => {
+                        artifacts.lock().unwrap().push(artifact.clone());
+                        RunEvent::Artifact {
+                            label: label.to_string(),
+                            artifact,
+                        }
+                    }
+                    // Cargo's other messages would only be noise next to the diagnostics
+                    Some(CargoMessage::Other) => return,
+                    None => RunEvent::OutputChunk {
+                        label: label.to_string(),
+                        stream,
+                        data: line.to_string(),
+                    },
+                };
+                output_events.emit(&event);
+            }));
+            runner = runner.echo_output(echo_output);
+        }
+        None if message_format == MessageFormat::Json || context == CommandContext::Build => {
+            // Cargo's JSON stays out of the terminal, everything else is echoed as usual
+            let summary = summary.clone();
+            let artifacts = artifacts.clone();
+            let output_view = view.clone();
+            runner = runner.echo_output(false);
+            runner = runner.observe_output(Arc::new(move |label, stream, line| {
+                if let Some(observer) = &observer {
+                    observer(label, stream, line);
+                }
+                match diagnostics::parse_message(line) {
+                    Some(CargoMessage::Diagnostic(diagnostic)) => {
+                        summary.lock().unwrap().add(diagnostic)
+                    }
+                    Some(CargoMessage::Artifact(artifact)) => {
+                        artifacts.lock().unwrap().push(artifact)
+                    }
+                    Some(CargoMessage::Other) => {}
+                    None => output_view.line(label, stream, line),
+                }
+            }));
+            runner = runner
+                .observe_steps(reporter.observe_steps(step_reporter(&view, &presenter, quiet)));
+        }
+        None if own_view && view.is_interleaved() => {
+            if let Some(observer) = observer.filter(|_| observed) {
+                runner = runner.observe_output(observer);
+            }
+            runner = runner
+                .observe_steps(reporter.observe_steps(step_reporter(&view, &presenter, quiet)));
+        }
+        None => {
+            let output_view = view.clone();
+            runner = runner.echo_output(false);
+            runner = runner.observe_output(Arc::new(move |label, stream, line| {
+                if let Some(observer) = &observer {
+                    observer(label, stream, line);
+                }
+                output_view.line(label, stream, line);
+            }));
+            runner = runner
+                .observe_steps(reporter.observe_steps(step_reporter(&view, &presenter, quiet)));
+        }
+    }
+    runner.check_platform(&key)?;
+    runner = runner.arg_values(&key, &arg_values)?;
+    if let Some(cell) = &cell {
+        runner = runner.only_cell(&key, cell)?;
+    }
+    let inputs = input_values(&runner.find_inputs(&key)?, inputs, interactive)?;
+    runner = runner.inputs(inputs);
+    let binaries = runner.binary_candidates(&key)?;
+    if interactive && binaries.len() > 1 {
+        runner = runner.binary_name(&pick("binary", &binaries)?);
+    }
+
+    let details = runner.details(&key)?;
+    if details.is_deprecated() {
+        let replacement = details.replaced_by.as_ref().map(|key| runner.label(key));
+        let deprecated = ExecutionError::Deprecated(runner.label(&key), replacement);
+        if strict {
+            return Err(Box::new(deprecated));
+        }
+        if !quiet {
+            eprintln!("{}", presenter.note(&deprecated.to_string()));
+        }
+    }
+    let uses_nextest = config
+        .commands
+        .get_config(context)
+        .is_some_and(|command_config| command_config.runner == Some(TestRunner::Nextest));
+    if context == CommandContext::Test && uses_nextest && !quiet && !nextest::is_installed(&session)
+    {
+        eprintln!(
+            "{}",
+            presenter.note("cargo-nextest is not installed, running cargo test instead")
+        );
+    }
+    if let Some(warning) = runner.offline_warning(details).filter(|_| !quiet) {
+        eprintln!("{}", presenter.note(&warning));
+    }
+
+    let started = Instant::now();
+    let test_summary = || {
+        tests
+            .as_ref()
+            .map(|tests| tests.summary())
+            .filter(|summary| !summary.is_empty())
+    };
+    let finished = |code: i32, failure: Option<&ExecutionError>| {
+        if let Some((events, summary)) = events.as_ref().zip(test_summary()) {
+            events.emit(&RunEvent::Tests {
+                label: runner.label(&key),
+                summary,
+            });
+        }
+        let summary = failure
+            .filter(|failure| !matches!(failure, ExecutionError::Interrupted(_)))
+            .map(|failure| reporter.summarize(&runner, &key, args, failure));
+        if code != INTERRUPTED_EXIT_CODE {
+            let stderr = summary.as_ref().map_or(&[][..], |summary| &summary.stderr);
+            let label = runner.label(&key);
+            notify_webhooks(config, details, &label, code, stderr, started.elapsed())
+                .into_iter()
+                .filter(|_| !quiet && events.is_none())
+                .for_each(|err| eprintln!("{}", presenter.note(&err)));
+        }
+        match (&events, summary) {
+            (Some(events), Some(summary)) => events.emit(&RunEvent::Failure {
+                label: runner.label(&key),
+                summary,
+            }),
+            (None, Some(summary)) if !quiet => {
+                for (number, line) in summary.lines().iter().enumerate() {
+                    match number {
+                        0 => eprintln!("{}", presenter.error(line)),
+                        _ => eprintln!("{}", presenter.note(line)),
+                    }
+                }
+            }
+            _ => {}
+        }
+        if let Some(events) = &events {
+            events.emit(&RunEvent::Finished {
+                label: runner.label(&key),
+                success: failure.is_none(),
+                exit_code: code,
+                duration_ms: millis(started.elapsed()),
+                error: failure.map(ToString::to_string),
+            });
+        }
+    };
+    if let Some(events) = &events {
+        events.emit(&RunEvent::Started {
+            label: runner.label(&key),
+        });
+    } else if !quiet {
+        eprintln!("{}", presenter.header(&runner.label(&key)));
+    }
+
+    let result = runner.run_with_hooks(Some(&key), args);
+    if let Some(timings) = &timings {
+        // Nothing started when the run was cached or skipped, all of it was rx's
+        timings.end_once(PREPARE);
+        timings.end(COMMANDS);
+    }
+    view.finish(matches!(&result, Ok(status) if details.is_success(status.code())));
+    let (code, failure) = match result {
+        Ok(status) if details.is_success(status.code()) => (0, None),
+        Ok(status) => {
+            let code = exit_code(&status);
+            // A code listed as success can't be passed through as a failure
+            let code = if code == 0 { 1 } else { code };
+            let err = ExecutionError::CommandFailed(runner.label(&key), status.code());
+            (code, Some(err))
+        }
+        Err(err @ ExecutionError::CommandFailed(_, code)) => (code.unwrap_or(1), Some(err)),
+        Err(err @ ExecutionError::Interrupted(_)) => (INTERRUPTED_EXIT_CODE, Some(err)),
+        Err(err) => {
+            finished(1, Some(&err));
+            return Err(Box::new(err));
+        }
+    };
+    finished(code, failure.as_ref());
+    // An interrupted run says nothing about how long the config takes
+    let average = match runner.project_store() {
+        Some(store) if code != INTERRUPTED_EXIT_CODE => record_run(
+            &store,
+            &runner.label(&key),
+            failure.is_none(),
+            started.elapsed(),
+        ),
+        _ => None,
+    };
+    if let Some(store) = runner.project_store() {
+        record_last_run(&runner, &key, args, &env, code, &store);
+    }
+
+    if let Some(summary) = test_summary() {
+        if events.is_none() && !quiet {
+            eprintln!("{}", presenter.note(&summary.report()));
+        }
+        if let Some(store) = runner
+            .project_store()
+            .filter(|_| code != INTERRUPTED_EXIT_CODE)
+        {
+            record_failed_tests(&store, &runner.label(&key), &summary);
+        }
+    }
+
+    if events.is_none() && message_format == MessageFormat::Json && !quiet {
+        eprintln!(
+            "{}",
+            presenter.note(&summary.lock().unwrap().report(SUMMARY_LIMIT))
+        );
+    }
+
+    let artifacts = artifacts.lock().unwrap().clone();
+    if events.is_none() && !quiet {
+        for path in artifacts.iter().flat_map(|artifact| &artifact.paths) {
+            eprintln!("{}", presenter.note(&format!("built {}", path.display())));
+        }
+    }
+    let recorded_build = match runner.project_store() {
+        Some(store) if context == CommandContext::Build && code == 0 => record_build(
+            &store,
+            &workspace_folder,
+            &runner.label(&key),
+            started.elapsed(),
+            &artifacts,
+            host_triple(&session),
+        ),
+        _ => None,
+    };
+    if let Some((baseline, current)) = size_baseline.as_ref().zip(recorded_build.as_ref()) {
+        if events.is_none() {
+            print_size_comparison(baseline, current);
+        }
+    }
+    if let Some(destination) = copy_to.filter(|_| code == 0) {
+        let copies = copy_artifacts(
+            &artifacts,
+            &destination,
+            &interpolation,
+            host_triple(&session),
+        )?;
+        if events.is_none() && !quiet {
+            for copy in &copies {
+                eprintln!(
+                    "{}",
+                    presenter.note(&format!("copied to {}", copy.display()))
+                );
+            }
+        }
+    }
+
+    if details.notify.unwrap_or(config.settings.notify) {
+        let reported_code = (code != INTERRUPTED_EXIT_CODE).then_some(code);
+        let (title, body) =
+            notify::completion_message(&runner.label(&key), reported_code, started.elapsed());
+        if let Err(err) = notify::send(&title, &body) {
+            if !quiet {
+                eprintln!(
+                    "{}",
+                    presenter.note(&format!("could not send notification: {}", err))
+                );
+            }
+        }
+    }
+
+    if events.is_none() && !quiet {
+        match failure {
+            Some(failure) => eprintln!(
+                "{}",
+                presenter.failure(&failure.to_string(), started.elapsed())
+            ),
+            None => eprintln!(
+                "{}",
+                presenter.success(&runner.label(&key), started.elapsed(), average)
+            ),
+        }
+    }
+
+    Ok(code)
+}
+
+/// Posts the outcome of the run of `label` to the `[notifications]` webhooks when its
+/// `notify_on` lists it, returning why the posts that didn't go through failed.
+fn notify_webhooks(
+    config: &Config,
+    details: &CommandDetails,
+    label: &str,
+    code: i32,
+    stderr: &[String],
+    elapsed: Duration,
+) -> Vec<String> {
+    let outcome = match code {
+        0 => NotifyOn::Success,
+        _ => NotifyOn::Failure,
+    };
+    if !details.notify_on.contains(&outcome) {
+        return Vec::new();
+    }
+    let message = notify::webhook_message(label, Some(code), elapsed, stderr, &notify::host_name());
+    let mut errors = Vec::new();
+    for url in &config.notifications.webhooks {
+        let posted = secret::reveal("notifications.webhooks", url)
+            .map_err(|err| err.to_string())
+            .and_then(|url| {
+                notify::post_webhook(&url, &notify::webhook_payload(&url, &message))
+                    .map_err(|err| err.to_string())
+            });
+        if let Err(err) = posted {
+            errors.push(format!("could not post to a webhook: {}", err));
+        }
+    }
+    errors
+}
+
+/// Asks whether to stop what holds a port when interactive, otherwise says it's stopped
+/// unless `quiet`.
+fn port_conflict_handler(
+    presenter: &Presenter,
+    interactive: bool,
+    quiet: bool,
+) -> PortConflictHandler {
+    let presenter = presenter.clone();
+    Arc::new(move |label, conflict| {
+        let message = format!(
+            "'{}' needs port {}, held by {}",
+            label,
+            conflict.port,
+            conflict.holder()
+        );
+        if interactive {
+            let mut prompt = Prompt::new(io::stdin().lock(), io::stderr());
+            let question = presenter.note(&format!("{}, stop it?", message));
+            return prompt.confirm(&question, true).unwrap_or(false);
+        }
+        if !quiet {
+            eprintln!("{}", presenter.note(&format!("{}, stopping it", message)));
+        }
+        true
+    })
+}
+
+/// Asks whether to run a config with `pre_command_policy = "prompt"` after its pre_command
+/// failed.
+fn pre_command_failure_handler(presenter: &Presenter) -> PreCommandFailureHandler {
+    let presenter = presenter.clone();
+    Arc::new(move |_, err| {
+        let mut prompt = Prompt::new(io::stdin().lock(), io::stderr());
+        let question = presenter.note(&format!("{}, run anyway?", err));
+        prompt.confirm(&question, false).unwrap_or(false)
+    })
+}
+
+/// Values for the config's inputs: those given, then answers when interactive. Without an
+/// answer an input falls back to its default, and is an error if it has none.
+fn input_values(
+    inputs: &[Input],
+    mut values: Vec<(String, String)>,
+    interactive: bool,
+) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    let mut prompt = Prompt::new(io::stdin().lock(), io::stderr());
+    for input in inputs {
+        if values.iter().any(|(name, _)| *name == input.name) {
+            continue;
+        }
+        if interactive {
+            let value = prompt.ask(&input.name, input.default.as_deref(), |answer| {
+                Ok(answer.to_string())
+            })?;
+            values.push((input.name.clone(), value));
+        } else if input.default.is_none() {
+            return Err(Box::new(ConfigError::MissingInput(input.name.clone())));
+        }
+    }
+    Ok(values)
+}
+
+/// Asks which of `choices` is meant, by name or by its number in the list.
+fn pick(what: &str, choices: &[String]) -> Result<String, Box<dyn Error>> {
+    let mut prompt = Prompt::new(io::stdin().lock(), io::stderr());
+    for (number, choice) in choices.iter().enumerate() {
+        eprintln!("  {}) {}", number + 1, choice);
+    }
+    let choice = prompt.ask(what, None, |answer| {
+        answer
+            .parse::<usize>()
+            .ok()
+            .and_then(|number| choices.get(number.checked_sub(1)?))
+            .or_else(|| choices.iter().find(|choice| *choice == answer))
+            .cloned()
+            .ok_or_else(|| format!("expected one of: {}", choices.join(", ")))
+    })?;
+    Ok(choice)
+}
+
+/// Lists the configs a key pattern matched and asks whether to run them. `yes`, `quiet` (which
+/// lists nothing) and a stdin other than a terminal go ahead without asking.
+pub fn confirm_matches(
+    labels: &[String],
+    presenter: &Presenter,
+    yes: bool,
+    quiet: bool,
+) -> Result<bool, Box<dyn Error>> {
+    if quiet {
+        return Ok(true);
+    }
+    eprintln!(
+        "{}",
+        presenter.note(&format!("the pattern matches {} configs:", labels.len()))
+    );
+    for label in labels {
+        eprintln!("  {}", label);
+    }
+    if yes || !io::stdin().is_terminal() {
+        return Ok(true);
+    }
+    let question = format!("Run these {} configs", labels.len());
+    Ok(Prompt::new(io::stdin().lock(), io::stderr()).confirm(&question, true)?)
+}
+
+/// The key to run for the `default` of `context` when its config is gone: the only one left,
+/// or the one picked when `interactive`. When interactive the default is then pointed at it in
+/// the file, once confirmed. `None` when the default exists, or there is nothing to fall back on
+/// and the run reports the keys.
+pub fn repair_default(
+    config: &mut Config,
+    context: CommandContext,
+    source: &ConfigSource,
+    confirmation: &Confirmation,
+    interactive: bool,
+    presenter: &Presenter,
+) -> Result<Option<String>, Box<dyn Error>> {
+    let runner = Runner::new(config, context, ShutdownSignal::new());
+    let Ok(default) = runner.default_key() else {
+        return Ok(None);
+    };
+    let Some(command_config) = config.commands.get_config(context) else {
+        return Ok(None);
+    };
+    if command_config.configs.contains_key(&default) {
+        return Ok(None);
+    }
+    let keys: Vec<String> = command_config
+        .keys()
+        .into_iter()
+        .filter(|key| command_config.configs[*key].runs_on_this_platform())
+        .map(String::from)
+        .collect();
+    let label = runner.label(&default);
+    let key = match keys.as_slice() {
+        [only] => only.clone(),
+        [] => return Ok(None),
+        _ if interactive => {
+            eprintln!(
+                "{}",
+                presenter.note(&format!("the default '{}' does not exist, pick one", label))
+            );
+            pick("key", &keys)?
+        }
+        _ => return Ok(None),
+    };
+    eprintln!(
+        "{}",
+        presenter.note(&format!(
+            "the default '{}' does not exist, running '{}'",
+            label, key
+        ))
+    );
+
+    if interactive {
+        if let Some(command_config) = config.commands.get_config_mut(context) {
+            command_config.default.rename(&default, &key);
+        }
+        save_config(config, source, confirmation)?;
+    }
+    Ok(Some(key))
+}
+
+/// Reports the steps on stderr unless `quiet`, those the view shows aside.
+fn step_reporter(view: &OutputView, presenter: &Presenter, quiet: bool) -> StepObserver {
+    let view = view.clone();
+    let presenter = presenter.clone();
+    Arc::new(move |event| {
+        if !view.step(event) && !quiet {
+            report_step(&presenter, event)
+        }
+    })
+}
+
+/// Prints a line when a step of a composite config starts and when it ends, and when a config
+/// is skipped or cached.
+fn report_step(presenter: &Presenter, event: &StepEvent) {
+    let line = match event {
+        StepEvent::Started(label) => presenter.header(label),
+        StepEvent::Skipped(label, reason) => {
+            presenter.note(&format!("↷ {} skipped, {}", label, reason))
+        }
+        StepEvent::Cached(label) => {
+            presenter.note(&format!("✓ {} cached, inputs unchanged", label))
+        }
+        StepEvent::PreCommandStarted(_) | StepEvent::PreCommandFinished(_) => return,
+        StepEvent::PreCommandIgnored(_, reason) => {
+            presenter.note(&format!("↷ {}, running anyway", reason))
+        }
+        StepEvent::Ready(label, elapsed) => {
+            presenter.success(&format!("{} ready", label), *elapsed, None)
+        }
+        StepEvent::Queued(label, position) => {
+            presenter.note(&format!("⧗ {} queued at position {}", label, position))
+        }
+        StepEvent::Locked(label, lock) => {
+            presenter.note(&format!("⧗ {} waits for the lock '{}'", label, lock))
+        }
+        StepEvent::Finished(report) if report.success => {
+            presenter.success(&report.label, report.elapsed, None)
+        }
+        StepEvent::Finished(report) => failed_step(presenter, report),
+        StepEvent::MatrixFinished(label, reports) => {
+            let passed = reports.iter().filter(|report| report.success).count();
+            let mut lines = vec![presenter.note(&format!(
+                "{}: {} of {} cells passed",
+                label,
+                passed,
+                reports.len()
+            ))];
+            lines.extend(reports.iter().map(|report| {
+                if report.success {
+                    presenter.success(&report.label, report.elapsed, None)
+                } else {
+                    failed_step(presenter, report)
+                }
+            }));
+            lines.join("\n")
+        }
+    };
+    eprintln!("{}", line);
+}
+
+fn failed_step(presenter: &Presenter, report: &StepReport) -> String {
+    let outcome = match (&report.error, report.code) {
+        (Some(error), _) => format!(" ({})", error),
+        (None, Some(code)) => format!(" (exit code {})", code),
+        _ => String::new(),
+    };
+    presenter.failure(&format!("{}{}", report.label, outcome), report.elapsed)
+}
+
+/// Adds the run to the project's history, returning the average of the earlier successful
+/// runs. The history is best effort, one that can't be read or written is left alone.
+fn record_run(store: &Store, label: &str, success: bool, elapsed: Duration) -> Option<Duration> {
+    let mut history = RunHistory::load(store).ok()?;
+    let average = history.average(label);
+    history.record(label, success, elapsed);
+    let _ = history.save(store);
+    average
+}
+
+/// The build recorded for `baseline`, a git revision of the workspace.
+fn load_build(
+    store: Option<Store>,
+    workspace_folder: &Path,
+    baseline: &str,
+) -> Result<BuildRun, Box<dyn Error>> {
+    let store = store.ok_or("Could not find a data directory")?;
+    let revision =
+        git::short_sha(workspace_folder, baseline).unwrap_or_else(|| baseline.to_string());
+    let build = BuildRun::load(&store, &revision)?.ok_or_else(|| {
+        format!(
+            "No build recorded for '{}', run `rx build` on it first",
+            baseline
+        )
+    })?;
+    Ok(build)
+}
+
+/// Records the sizes of the binaries of the build of `label` and how long it took for the
+/// current git revision, a failure to save it is no reason to fail the run.
+fn record_build(
+    store: &Store,
+    workspace_folder: &Path,
+    label: &str,
+    elapsed: Duration,
+    artifacts: &[Artifact],
+    host: &str,
+) -> Option<BuildRun> {
+    let revision = git::revision(workspace_folder).unwrap_or_else(|| "unversioned".to_string());
+    let mut build = BuildRun::load(store, &revision).ok()?.unwrap_or(BuildRun {
+        revision,
+        ..Default::default()
+    });
+    build.record(label, elapsed, artifacts, host);
+    let _ = build.save(store);
+    Some(build)
+}
+
+/// Prints how the binary sizes and build times of `current` changed since `baseline`.
+fn print_size_comparison(baseline: &BuildRun, current: &BuildRun) {
+    let (sizes, durations) = build_size::compare(baseline, current);
+    println!(
+        "\nComparing {} against {}",
+        current.revision, baseline.revision
+    );
+    for change in &sizes {
+        println!(
+            "  {:<50} {:>10} -> {:>10}  {:>+7.2}%",
+            change.name,
+            format_bytes(change.baseline),
+            format_bytes(change.current),
+            change.change_percent()
+        );
+    }
+    for change in &durations {
+        println!(
+            "  {:<50} {:>10} -> {:>10}  {:>+7.2}%",
+            change.name,
+            format_duration(Duration::from_millis(change.baseline)),
+            format_duration(Duration::from_millis(change.current)),
+            change.change_percent()
+        );
+    }
+    if sizes.is_empty() && durations.is_empty() {
+        println!("  nothing built in both");
+    }
+}
+
+/// Keeps the tests of `label` that failed for `rx test --rerun-failed`, a failure to save it
+/// is no reason to fail the run.
+fn record_failed_tests(store: &Store, label: &str, summary: &TestSummary) {
+    let Ok(mut failed_tests) = FailedTests::load(store) else {
+        return;
+    };
+    failed_tests.record(label, summary);
+    let _ = failed_tests.save(store);
+}
+
+/// Keeps what `rx rerun` needs to run `key` again, its failed steps or itself when it failed.
+fn record_last_run(
+    runner: &Runner,
+    key: &str,
+    args: &[String],
+    env: &[(String, String)],
+    code: i32,
+    store: &Store,
+) {
+    let failed = match code {
+        0 => Vec::new(),
+        _ => {
+            let steps = runner.failed_steps();
+            match steps.is_empty() {
+                true => vec![runner.label(key)],
+                false => steps,
+            }
+        }
+    };
+    let last_run = LastRun {
+        label: runner.label(key),
+        args: args.to_vec(),
+        env: env.iter().cloned().collect(),
+        failed,
+    };
+    let _ = last_run.save(store);
+}
+
+/// Prints the duration stats and failure rate of every config run in this project.
+pub fn show_stats() -> Result<i32, Box<dyn Error>> {
+    let (workspace_folder, store) = workspace_store()?;
+    let stats = RunHistory::load(&store)?.stats();
+    if stats.is_empty() {
+        eprintln!("rx: no runs recorded for {}", workspace_folder.display());
+        return Ok(0);
+    }
+
+    let width = stats
+        .iter()
+        .map(|stats| stats.label.len())
+        .max()
+        .unwrap_or(0);
+    let duration = |duration: Option<Duration>| duration.map_or("-".to_string(), format_duration);
+    println!(
+        "{:<width$}  {:>5}  {:>8}  {:>8}  {:>8}  {:>7}",
+        "config", "runs", "avg", "median", "p95", "failed"
+    );
+    for stats in &stats {
+        println!(
+            "{:<width$}  {:>5}  {:>8}  {:>8}  {:>8}  {:>6.0}%",
+            stats.label,
+            stats.runs,
+            duration(stats.average),
+            duration(stats.median),
+            duration(stats.p95),
+            stats.failure_rate * 100.0
+        );
+    }
+    Ok(0)
+}
+
+/// Writes a summary of the runs recorded in this project to `args.output` or stdout.
+pub fn report(args: &ReportArgs, quiet: bool) -> Result<i32, Box<dyn Error>> {
+    let (workspace_folder, store) = workspace_store()?;
+    let history = RunHistory::load(&store)?;
+    if history.stats().is_empty() && !quiet {
+        eprintln!("rx: no runs recorded for {}", workspace_folder.display());
+    }
+
+    let report = Report::new(&history, args.top);
+    let content = match args.format {
+        ReportFormat::Markdown => {
+            let project = workspace_folder.file_name().map_or_else(
+                || workspace_folder.display().to_string(),
+                |name| name.to_string_lossy().to_string(),
+            );
+            report.to_markdown(&project)
+        }
+        ReportFormat::Json => report.to_json()?,
+    };
+    match &args.output {
+        Some(path) => {
+            fs::write(path, &content)
+                .map_err(|err| format!("cannot write {}: {}", path.display(), err))?;
+            if !quiet {
+                eprintln!("Wrote {}", path.display());
+            }
+        }
+        None => print!("{}", content),
+    }
+    Ok(0)
+}
+
+/// The folder of the workspace rx runs in and where its history is kept.
+pub fn workspace_store() -> Result<(PathBuf, Store), Box<dyn Error>> {
+    let cwd = std::env::current_dir()?;
+    let workspace_folder = InterpolationContext::from_dir(&cwd)
+        .get("workspaceFolder")
+        .map(PathBuf::from)
+        .unwrap_or(cwd);
+    let store = Store::for_workspace(&workspace_folder).ok_or("Could not find a data directory")?;
+    Ok((workspace_folder, store))
+}
+
+/// The label of the test config `key` and the tests that failed in its last run, none when
+/// none did.
+pub fn failed_tests(
+    config: &Config,
+    context: CommandContext,
+    key: Option<&str>,
+) -> Result<(String, Vec<String>), Box<dyn Error>> {
+    let runner = Runner::new(config, context, ShutdownSignal::new());
+    let label = runner.label(&runner.resolve_key(key)?);
+    let (_, store) = workspace_store()?;
+    let names = FailedTests::load(&store)?
+        .tests
+        .remove(&label)
+        .unwrap_or_default();
+    Ok((label, names))
+}
+
+/// Prints the names of the workspace members, one per line, for shell completion.
+pub fn list_members() -> Result<i32, Box<dyn Error>> {
+    for member in workspace::members(&std::env::current_dir()?)? {
+        println!("{}", member.name);
+    }
+    Ok(0)
+}
+
+/// Prints the environment the config would run with, the process variables it inherits
+/// included, with the layer each variable comes from and credentials masked.
+pub fn show_env(
+    config: &Config,
+    context: CommandContext,
+    key: Option<&str>,
+    args: &[String],
+    env: &[(String, String)],
+) -> Result<i32, Box<dyn Error>> {
+    let runner = Runner::new(config, context, ShutdownSignal::new()).env_overrides(env.to_vec());
+    let key = runner.resolve_key(key)?;
+    let details = runner.target_details(&key, runner.details(&key)?, args);
+
+    let environment = LayeredEnv::new()
+        .layer(EnvSource::Process, process_env(details.inherits_env()))
+        .layer_all(runner.environment(&details)?);
+
+    for (name, value, source) in environment.iter() {
+        let value = match runner.is_sealed(&details, name, source) {
+            true => "********",
+            false => masked(name, value),
+        };
+        println!("{}={}  # {}", name, value, source);
+    }
+
+    Ok(0)
+}
+
+/// Prints the command line of each process the run would start, labeled with its config.
+pub fn dry_run(
+    config: &Config,
+    context: CommandContext,
+    key: Option<&str>,
+    args: &[String],
+    env: &[(String, String)],
+    arg_values: &[(String, String)],
+) -> Result<i32, Box<dyn Error>> {
+    let runner = Runner::new(config, context, ShutdownSignal::new()).env_overrides(env.to_vec());
+    let key = runner.resolve_key(key)?;
+    let runner = runner.arg_values(&key, arg_values)?;
+    let plan = runner.plan(Some(&key), args)?;
+    println!("{}: {}", plan.label, plan.command_line);
+    print_plan(&plan.children, "");
+
+    Ok(0)
+}
+
+/// Prints `nodes` as the branches of a tree, each line starting with `indent`.
+fn print_plan(nodes: &[PlanNode], indent: &str) {
+    for (index, node) in nodes.iter().enumerate() {
+        let last = index + 1 == nodes.len();
+        let (branch, continuation) = match last {
+            true => ("└── ", "    "),
+            false => ("├── ", "│   "),
+        };
+        match node.repeated {
+            true => println!("{}{}{} (runs once, above)", indent, branch, node.label),
+            false => println!("{}{}{}: {}", indent, branch, node.label, node.command_line),
+        }
+        print_plan(&node.children, &format!("{}{}", indent, continuation));
+    }
+}
+
+/// Stores the invoked config with its extra arguments folded into `params` as `new_key`.
+pub fn save_as(
+    config: &mut Config,
+    context: CommandContext,
+    args: &RunArgs,
+    new_key: &str,
+) -> Result<(), Box<dyn Error>> {
+    let runner = Runner::new(config, context, ShutdownSignal::new());
+    let key = runner.resolve_key(args.key.as_deref())?;
+    let mut details = runner.details(&key)?.clone();
+
+    details.params = details.params.with_args(&args.args);
+    details.env.extend(args.env.iter().cloned());
+
+    config
+        .commands
+        .get_or_default_config(context)
+        .update_config(new_key, details);
+
+    Ok(())
+}
+
+/// Applies `--cwd`, `--type`, `--params` and `--set` to the config `args` runs, and with
+/// `--save` its `--env` too, so they're written along. Returns the key of the config.
+pub fn override_config(
+    config: &mut Config,
+    context: CommandContext,
+    args: &RunArgs,
+) -> Result<String, Box<dyn Error>> {
+    let key =
+        Runner::new(config, context, ShutdownSignal::new()).resolve_key(args.key.as_deref())?;
+    let mut fields = Vec::new();
+    if let Some(dir) = &args.cwd {
+        let dir = match dir.to_string_lossy() {
+            // A placeholder is resolved when running, like the config's own
+            dir if dir.contains("${") => dir.into_owned(),
+            _ => {
+                let cwd = std::env::current_dir()?;
+                let interpolation = InterpolationContext::from_dir(&cwd);
+                let workspace_folder = interpolation.get("workspaceFolder").map(Path::new);
+                config_working_directory(&cwd.join(dir), workspace_folder.unwrap_or(&cwd))
+            }
+        };
+        fields.push(("working_directory".to_string(), dir));
+    }
+    if let Some(command_type) = &args.command_type {
+        fields.push(("type".to_string(), command_type.clone()));
+    }
+    if let Some(params) = &args.params {
+        fields.push(("params".to_string(), params.clone()));
+    }
+    fields.extend(args.fields.iter().cloned());
+    config.set_fields(context, &key, &fields)?;
+
+    if args.save {
+        if let Some(details) = config
+            .commands
+            .get_or_default_config(context)
+            .configs
+            .get_mut(&key)
+        {
+            details.env.extend(args.env.iter().cloned());
+        }
+    }
+    Ok(key)
+}
@@ -0,0 +1,31 @@
+use std::{error::Error, fs, path::Path};
+
+use rx_core::{
+    models::config::Config,
+    runnables::{runnables, to_json, RunnablesFormat},
+    workspace::members,
+};
+
+/// Prints what can run at `line` of `file`, with the command lines the config resolves them to.
+pub fn list_runnables(
+    config: &Config,
+    file: &Path,
+    line: usize,
+    format: RunnablesFormat,
+) -> Result<i32, Box<dyn Error>> {
+    let file =
+        fs::canonicalize(file).map_err(|err| format!("cannot read {}: {}", file.display(), err))?;
+    let dir = file.parent().unwrap_or(&file);
+    let members = members(dir)?;
+    let runnables = runnables(config, &members, &file, line)?;
+
+    match format {
+        RunnablesFormat::Text => {
+            for runnable in &runnables {
+                println!("{}\t{}", runnable.label, runnable.command_line);
+            }
+        }
+        RunnablesFormat::Json => println!("{}", to_json(&runnables)),
+    }
+    Ok(0)
+}
@@ -0,0 +1,19 @@
+use std::{error::Error, fs, path::Path};
+
+use rx_core::models::config::Config;
+
+/// Prints the JSON Schema of the config file, or writes it to `output`.
+pub fn schema(output: Option<&Path>, quiet: bool) -> Result<i32, Box<dyn Error>> {
+    let schema = Config::json_schema();
+    match output {
+        Some(path) => {
+            fs::write(path, format!("{}\n", schema))
+                .map_err(|err| format!("cannot write {}: {}", path.display(), err))?;
+            if !quiet {
+                eprintln!("Wrote {}", path.display());
+            }
+        }
+        None => println!("{}", schema),
+    }
+    Ok(0)
+}
@@ -0,0 +1,79 @@
+use std::{
+    error::Error,
+    io::{stdin, IsTerminal},
+};
+
+use rx_core::{
+    errors::ConfigError,
+    helpers::is_valid_env_var_name,
+    models::config::{CommandContext, CommandDetails, Config},
+    secret::{self, KEYCHAIN},
+};
+
+/// Sets the env variable `name` of `context::key` to a value read from stdin, encrypted to
+/// `settings.secret_recipients`, or stored in the OS keychain as the entry `keychain`, `name`
+/// when it's given without one.
+pub fn set_secret(
+    config: &mut Config,
+    context: CommandContext,
+    key: &str,
+    name: &str,
+    keychain: Option<&Option<String>>,
+) -> Result<(), Box<dyn Error>> {
+    if !is_valid_env_var_name(name) {
+        return Err(ConfigError::InvalidEnvFormat.into());
+    }
+    details(config, context, key)?;
+
+    if stdin().is_terminal() {
+        eprint!("Value of {}: ", name);
+    }
+    let value = secret::read_value()?;
+    if value.is_empty() {
+        return Err(format!("No value given for {}", name).into());
+    }
+    let sealed = match keychain {
+        Some(entry) => {
+            let entry = entry.as_deref().unwrap_or(name);
+            secret::keychain_set(name, entry, &value)?;
+            format!("{}{}", KEYCHAIN, entry)
+        }
+        None => secret::encrypt(name, &value, &config.settings.secret_recipients)?,
+    };
+
+    let details = config
+        .commands
+        .get_config_mut(context)
+        .and_then(|command_config| command_config.configs.get_mut(key))
+        .ok_or_else(|| ConfigError::ConfigKeyNotFound(format!("{}::{}", context, key)))?;
+    details.env.insert(name.to_string(), sealed);
+    Ok(())
+}
+
+/// Prints the value of the env variable `name` of `context::key`, decrypted or read from the
+/// keychain.
+pub fn get_secret(
+    config: &Config,
+    context: CommandContext,
+    key: &str,
+    name: &str,
+) -> Result<i32, Box<dyn Error>> {
+    let value = details(config, context, key)?
+        .env
+        .get(name)
+        .ok_or_else(|| format!("'{}::{}' has no env variable {}", context, key, name))?;
+    println!("{}", secret::reveal(name, value)?);
+    Ok(0)
+}
+
+fn details<'a>(
+    config: &'a Config,
+    context: CommandContext,
+    key: &str,
+) -> Result<&'a CommandDetails, ConfigError> {
+    config
+        .commands
+        .get_config(context)
+        .and_then(|command_config| command_config.configs.get(key))
+        .ok_or_else(|| ConfigError::ConfigKeyNotFound(format!("{}::{}", context, key)))
+}
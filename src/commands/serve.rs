@@ -0,0 +1,379 @@
+use std::{
+    error::Error,
+    io::{self, BufReader, ErrorKind, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use rx_core::{
+    daemon::{list_configs, DaemonEvent, RunRequest},
+    events::millis,
+    executor::{queue::RunQueue, runner::Runner, shutdown::ShutdownSignal},
+    interpolation::InterpolationContext,
+    models::config::Config,
+    serve::{
+        error_response, is_allowed, json_response, response, sse_event, HttpRequest, Route,
+        RunSummary, EVENT_STREAM_HEAD,
+    },
+    session::Session,
+    source::ConfigSource,
+    workspace,
+};
+
+use crate::{
+    commands::run::{run, EventTarget, RunOptions, INTERRUPTED_EXIT_CODE},
+    load,
+};
+
+const DASHBOARD: &str = include_str!("dashboard.html");
+
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long a client gets to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// An event stream with nothing new gets a comment this often, to notice clients that left.
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Finished runs kept for the dashboard, the oldest are dropped past it.
+const KEPT_RUNS: usize = 50;
+
+/// State shared by the connections of `rx serve`.
+struct Server {
+    source: ConfigSource,
+    /// Shared by the runs, whose processes are driven on its runtime
+    session: Session,
+    port: u16,
+    runs: Mutex<Vec<Arc<Run>>>,
+    next_run: AtomicU64,
+    /// Where the processes of every run wait for their turn
+    queue: RunQueue,
+}
+
+/// A run started from the dashboard, with its events as JSON lines for the event streams.
+struct Run {
+    id: u64,
+    label: String,
+    started_ms: u64,
+    shutdown: ShutdownSignal,
+    log: Mutex<RunLog>,
+    changed: Condvar,
+}
+
+#[derive(Default)]
+struct RunLog {
+    events: Vec<String>,
+    /// Bytes of an event not written whole yet
+    partial: Vec<u8>,
+    exit_code: Option<i32>,
+}
+
+/// Serves the dashboard and its JSON API on localhost `port` until rx is interrupted, each
+/// connection from its own thread. The config file is read again for every listing and run,
+/// so changes to it show up without a restart.
+pub fn serve(
+    source: &ConfigSource,
+    session: &Session,
+    port: u16,
+    quiet: bool,
+) -> Result<i32, Box<dyn Error>> {
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .map_err(|err| format!("cannot listen on 127.0.0.1:{}: {}", port, err))?;
+    listener.set_nonblocking(true)?;
+
+    let server = Arc::new(Server {
+        source: source.clone(),
+        session: session.clone(),
+        port,
+        runs: Mutex::new(Vec::new()),
+        next_run: AtomicU64::new(0),
+        queue: RunQueue::default(),
+    });
+    let shutdown = ShutdownSignal::new();
+    shutdown.install_handler()?;
+
+    if !quiet {
+        eprintln!("rx: dashboard on http://127.0.0.1:{}", port);
+    }
+
+    while !shutdown.is_triggered() {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let server = server.clone();
+                thread::spawn(move || server.serve(stream));
+            }
+            Err(err) if err.kind() == ErrorKind::WouldBlock => thread::sleep(ACCEPT_INTERVAL),
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    // Give the runs their grace period to stop their process groups
+    let runs = server.runs.lock().unwrap().clone();
+    for run in &runs {
+        run.shutdown.trigger();
+    }
+    while runs
+        .iter()
+        .any(|run| run.log.lock().unwrap().exit_code.is_none())
+    {
+        thread::sleep(ACCEPT_INTERVAL);
+    }
+    Ok(INTERRUPTED_EXIT_CODE)
+}
+
+impl Server {
+    fn load_config(&self) -> Result<Config, Box<dyn Error>> {
+        let config = load::load_config(&self.source)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn serve(&self, mut stream: TcpStream) {
+        let _ = stream.set_nonblocking(false);
+        let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+        let Ok(reader) = stream.try_clone() else {
+            return;
+        };
+
+        let request = match HttpRequest::read(&mut BufReader::new(reader)) {
+            Ok(Some(request)) => request,
+            Ok(None) => return,
+            Err(err) => {
+                let _ = write!(
+                    stream,
+                    "{}",
+                    error_response("400 Bad Request", &err.to_string())
+                );
+                return;
+            }
+        };
+        let answer = if !is_allowed(&request, self.port) {
+            error_response(
+                "403 Forbidden",
+                "only the dashboard on localhost can use this API",
+            )
+        } else {
+            match Route::find(&request.method, &request.path) {
+                Some(Route::RunEvents(id)) => {
+                    match self.find_run(id) {
+                        Some(run) => {
+                            let _ = stream_events(&run, &mut stream);
+                        }
+                        None => {
+                            let _ = write!(
+                                stream,
+                                "{}",
+                                error_response("404 Not Found", "no such run")
+                            );
+                        }
+                    }
+                    return;
+                }
+                Some(route) => self.answer(route, &request.body),
+                None => error_response("404 Not Found", "no such page"),
+            }
+        };
+        let _ = stream.write_all(answer.as_bytes());
+    }
+
+    fn answer(&self, route: Route, body: &str) -> String {
+        match route {
+            Route::Dashboard => response("200 OK", "text/html; charset=utf-8", DASHBOARD),
+            Route::Configs => match self.load_config() {
+                Ok(config) => json_response(&list_configs(&config, None)),
+                Err(err) => error_response("500 Internal Server Error", &err.to_string()),
+            },
+            Route::Runs => {
+                let runs: Vec<RunSummary> = self
+                    .runs
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .map(|run| run.summary())
+                    .collect();
+                json_response(&runs)
+            }
+            Route::StartRun => {
+                let request = match RunRequest::from_json(body) {
+                    Ok(request) => request,
+                    Err(err) => return error_response("400 Bad Request", &err),
+                };
+                match self.start_run(request) {
+                    Ok(run) => json_response(&run.summary()),
+                    Err(err) => error_response("400 Bad Request", &err.to_string()),
+                }
+            }
+            Route::KillRun(id) => match self.find_run(id) {
+                Some(run) => {
+                    run.shutdown.trigger();
+                    json_response(&run.summary())
+                }
+                None => error_response("404 Not Found", "no such run"),
+            },
+            Route::RunEvents(_) => unreachable!("event streams are answered as they come"),
+        }
+    }
+
+    fn find_run(&self, id: u64) -> Option<Arc<Run>> {
+        let runs = self.runs.lock().unwrap();
+        runs.iter().find(|run| run.id == id).cloned()
+    }
+
+    /// Starts the requested config on its own thread. Errors, such as an unknown key, are
+    /// returned before it starts.
+    fn start_run(&self, request: RunRequest) -> Result<Arc<Run>, Box<dyn Error>> {
+        let config = self.load_config()?;
+        let cwd = match request.cwd {
+            Some(cwd) => cwd,
+            None => std::env::current_dir()?,
+        };
+        let package = match &request.package {
+            Some(name) => Some(workspace::find_member(&workspace::members(&cwd)?, name)?.clone()),
+            None => None,
+        };
+
+        let shutdown = ShutdownSignal::new();
+        let runner = Runner::new(&config, request.context, shutdown.clone()).cwd(&cwd);
+        let key = runner.resolve_key(request.key.as_deref())?;
+        runner.details(&key)?;
+        self.queue.set_max(config.settings.max_concurrent_runs);
+        let queue = self.queue.clone();
+        let session = self.session.clone();
+        let started = Arc::new(Run {
+            id: self.next_run.fetch_add(1, Ordering::SeqCst),
+            label: runner.label(&key),
+            started_ms: millis(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default(),
+            ),
+            shutdown: shutdown.clone(),
+            log: Mutex::new(RunLog::default()),
+            changed: Condvar::new(),
+        });
+
+        let mut runs = self.runs.lock().unwrap();
+        runs.push(started.clone());
+        let finished = runs.iter().filter(|run| !run.is_running()).count();
+        let mut dropped = finished.saturating_sub(KEPT_RUNS);
+        runs.retain(|run| {
+            let keep = dropped == 0 || run.is_running();
+            if !keep {
+                dropped -= 1;
+            }
+            keep
+        });
+        drop(runs);
+
+        let running = started.clone();
+        thread::spawn(move || {
+            let options = RunOptions {
+                env: request.env.into_iter().collect(),
+                no_cache: request.no_cache,
+                inputs: request.inputs.into_iter().collect(),
+                quiet: true,
+                events: Some(EventTarget::Connection(Box::new(RunWriter(
+                    running.clone(),
+                )))),
+                shutdown: Some(shutdown),
+                interpolation: Some(InterpolationContext::from_dir(&cwd)),
+                package,
+                fix: request.fix,
+                queue: Some(queue),
+                session,
+                ..Default::default()
+            };
+            let code = match run(&config, request.context, Some(&key), &request.args, options) {
+                Ok(code) => code,
+                Err(err) => {
+                    let event = DaemonEvent::Error {
+                        message: err.to_string(),
+                    };
+                    running.push(event.to_json());
+                    1
+                }
+            };
+            running.log.lock().unwrap().exit_code = Some(code);
+            running.changed.notify_all();
+        });
+        Ok(started)
+    }
+}
+
+impl Run {
+    fn is_running(&self) -> bool {
+        self.log.lock().unwrap().exit_code.is_none()
+    }
+
+    fn summary(&self) -> RunSummary {
+        let exit_code = self.log.lock().unwrap().exit_code;
+        RunSummary {
+            id: self.id,
+            label: self.label.clone(),
+            running: exit_code.is_none(),
+            exit_code,
+            started_ms: self.started_ms,
+        }
+    }
+
+    fn push(&self, event: String) {
+        self.log.lock().unwrap().events.push(event);
+        self.changed.notify_all();
+    }
+}
+
+/// Where a run writes its events, one JSON line at a time.
+struct RunWriter(Arc<Run>);
+
+impl Write for RunWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut log = self.0.log.lock().unwrap();
+        log.partial.extend_from_slice(buf);
+        while let Some(end) = log.partial.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = log.partial.drain(..=end).collect();
+            let event = String::from_utf8_lossy(&line).trim_end().to_string();
+            log.events.push(event);
+        }
+        self.0.changed.notify_all();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Sends the events of `run` from its start as they come, until it finished or the client left.
+fn stream_events(run: &Run, stream: &mut TcpStream) -> io::Result<()> {
+    stream.write_all(EVENT_STREAM_HEAD.as_bytes())?;
+    let mut sent = 0;
+    loop {
+        let (events, finished) = {
+            let log = run.log.lock().unwrap();
+            let (log, _) = run
+                .changed
+                .wait_timeout_while(log, KEEPALIVE_INTERVAL, |log| {
+                    log.events.len() == sent && log.exit_code.is_none()
+                })
+                .unwrap();
+            (log.events[sent..].to_vec(), log.exit_code.is_some())
+        };
+
+        if events.is_empty() && !finished {
+            stream.write_all(b": keepalive\n\n")?;
+        }
+        for event in &events {
+            stream.write_all(sse_event(event).as_bytes())?;
+        }
+        sent += events.len();
+        stream.flush()?;
+        if finished {
+            return Ok(());
+        }
+    }
+}
@@ -0,0 +1,274 @@
+use std::{
+    cmp::Reverse,
+    error::Error,
+    fs::{self, File},
+    io::{Read, Seek, SeekFrom},
+    path::PathBuf,
+    slice, thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use rx_core::{
+    executor::{
+        runner::Runner,
+        shutdown::{ShutdownSignal, DEFAULT_GRACE_PERIOD},
+    },
+    models::config::{CommandContext, Config},
+    notify::format_duration,
+    service::{self, is_running, log_path, service_keys, ServiceRecord, ServiceRegistry},
+    session::Session,
+    store::Store,
+    theme::Presenter,
+};
+
+/// How often the logs of a group's services are checked for new lines.
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Starts the services `keys`, every service when none are given, skipping those running. Each
+/// starts once those it depends on are ready. Naming a group follows the output of its services
+/// until Ctrl-C, unless `detach`.
+pub fn up(
+    config: &Config,
+    session: &Session,
+    keys: &[String],
+    detach: bool,
+    presenter: &Presenter,
+    quiet: bool,
+) -> Result<i32, Box<dyn Error>> {
+    config.validate()?;
+    let names = match keys.is_empty() {
+        true => service_keys(config),
+        false => keys.to_vec(),
+    };
+    if names.is_empty() {
+        return Err("No script config is a service, set service = true on one".into());
+    }
+    let follow = !detach && names.iter().any(|name| config.groups.contains_key(name));
+    let keys = config.service_order(&names)?;
+
+    let shutdown = ShutdownSignal::new();
+    shutdown.install_handler()?;
+    let runner = Runner::new(config, CommandContext::Script, shutdown.clone()).session(session);
+    let store = project_store(&runner)?;
+    let mut registry = ServiceRegistry::load(&store)?;
+    let mut logs = Vec::new();
+
+    for key in keys {
+        let label = runner.label(&key);
+        let log = log_path(&store, &key);
+        let offset = fs::metadata(&log).map_or(0, |metadata| metadata.len());
+        if let Some(record) = registry
+            .services
+            .get(&key)
+            .filter(|record| is_running(record.pid))
+        {
+            if !quiet {
+                let message = format!("'{}' is already running (pid {})", label, record.pid);
+                eprintln!("{}", presenter.note(&message));
+            }
+            logs.push((key, log, offset));
+            continue;
+        }
+
+        let started = Instant::now();
+        let pid = runner.spawn_service(&key, &log)?;
+        let started_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        registry.services.insert(
+            key.clone(),
+            ServiceRecord {
+                pid,
+                started_at,
+                log: log.clone(),
+            },
+        );
+        registry.save(&store)?;
+        if !quiet {
+            let message = format!(
+                "started '{}' (pid {}), logging to {}",
+                label,
+                pid,
+                log.display()
+            );
+            eprintln!("{}", presenter.note(&message));
+        }
+
+        runner.wait_for_service(&key, pid, &log, offset)?;
+        if runner.details(&key)?.wait_for.is_some() && !quiet {
+            let ready = format!("{} ready", label);
+            eprintln!("{}", presenter.success(&ready, started.elapsed(), None));
+        }
+        logs.push((key, log, offset));
+    }
+
+    if follow {
+        follow_logs(logs, &registry, &shutdown, presenter)?;
+        if !quiet {
+            let message = format!(
+                "the services keep running, `rx down {}` stops them",
+                names.join(" ")
+            );
+            eprintln!("{}", presenter.note(&message));
+        }
+    }
+    Ok(0)
+}
+
+/// Prints the lines the services' logs get past their offset, prefixed with the service's key,
+/// until Ctrl-C or every service exited.
+fn follow_logs(
+    mut logs: Vec<(String, PathBuf, u64)>,
+    registry: &ServiceRegistry,
+    shutdown: &ShutdownSignal,
+    presenter: &Presenter,
+) -> Result<(), Box<dyn Error>> {
+    loop {
+        for (key, log, offset) in &mut logs {
+            let Ok(mut file) = File::open(&*log) else {
+                continue;
+            };
+            file.seek(SeekFrom::Start(*offset))?;
+            let mut output = Vec::new();
+            file.read_to_end(&mut output)?;
+            // A line still being written is read again once it ends
+            let Some(end) = output.iter().rposition(|byte| *byte == b'\n') else {
+                continue;
+            };
+            for line in String::from_utf8_lossy(&output[..end]).lines() {
+                println!("{} {}", presenter.prefix(key), line);
+            }
+            *offset += end as u64 + 1;
+        }
+
+        if shutdown.is_triggered() {
+            return Ok(());
+        }
+        let exited = logs.iter().all(|(key, _, _)| {
+            registry
+                .services
+                .get(key)
+                .is_none_or(|record| !is_running(record.pid))
+        });
+        if exited {
+            eprintln!("{}", presenter.note("every service exited"));
+            return Ok(());
+        }
+        thread::sleep(FOLLOW_INTERVAL);
+    }
+}
+
+/// Stops the services `keys`, every service started when none are given, a group along with
+/// the services it depends on. Services are stopped before those they depend on.
+pub fn down(
+    config: &Config,
+    keys: &[String],
+    presenter: &Presenter,
+    quiet: bool,
+) -> Result<i32, Box<dyn Error>> {
+    let runner = Runner::new(config, CommandContext::Script, ShutdownSignal::new());
+    let store = project_store(&runner)?;
+    let mut registry = ServiceRegistry::load(&store)?;
+    let keys = match keys.is_empty() {
+        true => {
+            let order = config.service_order(&service_keys(config))?;
+            let mut keys: Vec<String> = registry.services.keys().cloned().collect();
+            keys.sort_by_key(|key| Reverse(order.iter().position(|service| service == key)));
+            keys
+        }
+        false => {
+            let mut order = Vec::new();
+            for name in keys {
+                let services = match config.groups.contains_key(name) {
+                    true => config.service_order(slice::from_ref(name))?,
+                    false => vec![name.clone()],
+                };
+                for service in services.into_iter().rev() {
+                    if !order.contains(&service) {
+                        order.push(service);
+                    }
+                }
+            }
+            order
+        }
+    };
+
+    for key in keys {
+        let label = runner.label(&key);
+        let Some(record) = registry.services.remove(&key) else {
+            if !quiet {
+                eprintln!("{}", presenter.note(&format!("'{}' isn't running", label)));
+            }
+            continue;
+        };
+        let message = if !is_running(record.pid) {
+            format!("'{}' had already exited", label)
+        } else {
+            let grace_period = runner
+                .details(&key)
+                .ok()
+                .and_then(|details| details.shutdown_grace_period)
+                .map_or(DEFAULT_GRACE_PERIOD, Duration::from_secs);
+            if !service::stop(record.pid, grace_period) {
+                registry.services.insert(key, record.clone());
+                registry.save(&store)?;
+                return Err(format!("Could not stop '{}' (pid {})", label, record.pid).into());
+            }
+            format!("stopped '{}'", label)
+        };
+        registry.save(&store)?;
+        if !quiet {
+            eprintln!("{}", presenter.note(&message));
+        }
+    }
+    Ok(0)
+}
+
+/// Prints the services of the config and those started, with whether they're running.
+pub fn status(config: &Config) -> Result<i32, Box<dyn Error>> {
+    let runner = Runner::new(config, CommandContext::Script, ShutdownSignal::new());
+    let registry = ServiceRegistry::load(&project_store(&runner)?)?;
+    let mut keys = service_keys(config);
+    keys.extend(registry.services.keys().cloned());
+    keys.sort();
+    keys.dedup();
+    if keys.is_empty() {
+        eprintln!("rx: no services, set service = true on a script config");
+        return Ok(0);
+    }
+
+    let width = keys.iter().map(String::len).max().unwrap_or(0).max(7);
+    println!(
+        "{:<width$}  {:<7}  {:>7}  {:>8}  log",
+        "service", "state", "pid", "up"
+    );
+    for key in &keys {
+        match registry.services.get(key) {
+            Some(record) => {
+                let (state, up) = match is_running(record.pid) {
+                    true => ("running", format_duration(record.uptime())),
+                    false => ("exited", "-".to_string()),
+                };
+                println!(
+                    "{:<width$}  {:<7}  {:>7}  {:>8}  {}",
+                    key,
+                    state,
+                    record.pid,
+                    up,
+                    record.log.display()
+                );
+            }
+            None => println!(
+                "{:<width$}  {:<7}  {:>7}  {:>8}  -",
+                key, "stopped", "-", "-"
+            ),
+        }
+    }
+    Ok(0)
+}
+
+fn project_store(runner: &Runner) -> Result<Store, Box<dyn Error>> {
+    Ok(runner
+        .project_store()
+        .ok_or("Could not find a data directory")?)
+}
@@ -0,0 +1,31 @@
+use std::error::Error;
+
+use arboard::Clipboard;
+use rx_core::{
+    executor::{runner::Runner, shutdown::ShutdownSignal},
+    models::config::{CommandContext, Config},
+};
+
+/// Prints the command line `context::key` runs, and copies it to the clipboard with `copy`.
+pub fn show(
+    config: &Config,
+    context: CommandContext,
+    key: Option<&str>,
+    copy: bool,
+    quiet: bool,
+) -> Result<i32, Box<dyn Error>> {
+    let runner = Runner::new(config, context, ShutdownSignal::new());
+    let key = runner.resolve_key(key)?;
+    let command_line = runner.command_line(&key, &[])?;
+    println!("{}", command_line);
+
+    if copy {
+        Clipboard::new()
+            .and_then(|mut clipboard| clipboard.set_text(command_line))
+            .map_err(|err| format!("Cannot copy to the clipboard: {}", err))?;
+        if !quiet {
+            eprintln!("rx: copied the command line of {}", runner.label(&key));
+        }
+    }
+    Ok(0)
+}
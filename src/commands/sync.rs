@@ -0,0 +1,66 @@
+use std::error::Error;
+
+use rx_core::{
+    models::config::Config,
+    notify::format_duration,
+    remote_config::{remote_dir, sync},
+    theme::Presenter,
+};
+
+/// Fetches the team config of `[remote_config]` and prints what it changed since the copy
+/// fetched before, then how the configs overriding its entries differ from them.
+pub fn sync_remote(config: &Config, quiet: bool) -> Result<i32, Box<dyn Error>> {
+    let dir = remote_dir().ok_or("Could not find a cache directory for the team config")?;
+    let report = sync(config, &dir)?;
+    if !quiet {
+        if let Some(remote) = &config.remote_config {
+            eprintln!("rx: fetched the team config from {}", remote.source());
+        }
+    }
+
+    if report.updates.is_empty() {
+        if !quiet {
+            eprintln!("rx: the team config is unchanged");
+        }
+    } else {
+        println!("Changed by the team:");
+        for change in &report.updates {
+            println!("  {}", change);
+        }
+    }
+    if !report.drift.is_empty() {
+        println!("Overridden here:");
+        for change in &report.drift {
+            println!("  {}", change);
+        }
+    }
+    Ok(0)
+}
+
+/// Fetches the team config again when its copy is older than its `ttl`. A failed fetch is
+/// reported and the copy there is, if any, used still. Returns whether it was fetched.
+pub fn refresh_remote(config: &Config, presenter: &Presenter) -> bool {
+    let (Some(remote), Some(dir)) = (&config.remote_config, remote_dir()) else {
+        return false;
+    };
+    if !remote.is_stale(&dir) {
+        return false;
+    }
+    match remote.fetch(&dir) {
+        Ok(()) => true,
+        Err(err) => {
+            let fallback = match remote.age(&dir) {
+                Some(age) => format!("using the copy from {} ago", format_duration(age)),
+                None => "its configs are left out until `rx sync` fetches it".to_string(),
+            };
+            eprintln!(
+                "{}",
+                presenter.note(&format!(
+                    "cannot fetch the team config: {}, {}",
+                    err, fallback
+                ))
+            );
+            false
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use std::{error::Error, fs};
+
+use rx_core::update::{
+    asset_name, download, is_newer, latest_release, replace_executable, verify, CHECKSUMS,
+};
+
+/// Replaces the running rx with its latest release when that one is newer, after checking the
+/// download against the release's checksums. With `check` it only reports the newer release.
+pub fn self_update(check: bool, quiet: bool) -> Result<i32, Box<dyn Error>> {
+    let current = env!("CARGO_PKG_VERSION");
+    let release = latest_release()?;
+    let latest = release.version();
+    if !is_newer(latest, current) {
+        if !quiet {
+            eprintln!("rx: {} is up to date", current);
+        }
+        return Ok(0);
+    }
+    if check {
+        println!("rx {} is available, this is {}", latest, current);
+        return Ok(0);
+    }
+
+    let name = asset_name();
+    let asset = release.asset(&name).ok_or_else(|| {
+        format!(
+            "Release {} has no build for this platform, {}",
+            release.tag_name, name
+        )
+    })?;
+    let checksums = release.asset(CHECKSUMS).ok_or_else(|| {
+        format!(
+            "Release {} has no {} to verify the download with",
+            release.tag_name, CHECKSUMS
+        )
+    })?;
+    let checksums = String::from_utf8(download(&checksums.browser_download_url)?)?;
+    let binary = download(&asset.browser_download_url)?;
+    verify(&binary, &checksums, &name)?;
+
+    // The file itself rather than a symlink to it, such as a package manager's
+    let exe = fs::canonicalize(std::env::current_exe()?)?;
+    replace_executable(&exe, &binary)
+        .map_err(|err| format!("Could not replace {}: {}", exe.display(), err))?;
+    if !quiet {
+        eprintln!(
+            "rx: updated {} from {} to {}",
+            exe.display(),
+            current,
+            latest
+        );
+    }
+    Ok(0)
+}
@@ -0,0 +1,49 @@
+use std::error::Error;
+
+use rx_core::{
+    executor::{runner::Runner, shutdown::ShutdownSignal},
+    models::config::{CommandContext, Config},
+};
+
+/// Prints the placeholders known in the current directory, those of the config `key` of
+/// `context` included when either is given. Exits with 1 when the config refers to a
+/// placeholder without a value.
+pub fn vars(
+    config: &Config,
+    context: Option<CommandContext>,
+    key: Option<&str>,
+    json: bool,
+    quiet: bool,
+) -> Result<i32, Box<dyn Error>> {
+    let runner = Runner::new(
+        config,
+        context.unwrap_or(CommandContext::Run),
+        ShutdownSignal::new(),
+    );
+    let key = match (context, key) {
+        (None, None) => None,
+        (_, key) => Some(runner.resolve_key(key)?),
+    };
+    let placeholders = runner.placeholders(key.as_deref())?;
+    match json {
+        true => print!("{}", placeholders.to_json()?),
+        false => println!("{}", placeholders.report()),
+    }
+
+    let unresolved = placeholders.unresolved();
+    if unresolved.is_empty() {
+        return Ok(0);
+    }
+    if !quiet {
+        let names: Vec<String> = unresolved
+            .iter()
+            .map(|placeholder| format!("${{{}}}", placeholder.name))
+            .collect();
+        eprintln!(
+            "rx: {} refers to {} without a value",
+            runner.label(key.as_deref().unwrap_or_default()),
+            names.join(", ")
+        );
+    }
+    Ok(1)
+}
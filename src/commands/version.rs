@@ -0,0 +1,24 @@
+use std::error::Error;
+
+use rx_core::about::{BuildInfo, VersionFormat};
+
+/// The cargo features rx was built with.
+const FEATURES: &[(&str, bool)] = &[("keychain", cfg!(feature = "keychain"))];
+
+/// Prints what this build of rx is, see build.rs for where the git sha, date and target come
+/// from.
+pub fn version(format: VersionFormat) -> Result<i32, Box<dyn Error>> {
+    let info = BuildInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_sha: env!("RX_GIT_SHA").to_string(),
+        build_date: env!("RX_BUILD_DATE").to_string(),
+        target: env!("RX_TARGET").to_string(),
+        features: FEATURES
+            .iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(name, _)| name.to_string())
+            .collect(),
+    };
+    print!("{}", info.format(format)?);
+    Ok(0)
+}
@@ -0,0 +1,205 @@
+use std::{
+    io::{self, IsTerminal, Write},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use rx_core::{
+    executor::{composite::StepEvent, output::OutputStream},
+    split::{inside_tmux, terminal_size, RegionStatus, SplitScreen, TmuxPanes, UiMode},
+    theme::Presenter,
+};
+
+/// Lines coming closer together than this are drawn with the next one.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Where the commands' output goes, per `--ui`.
+#[derive(Clone)]
+pub struct OutputView {
+    view: View,
+    /// Lines held back while paused, `None` when not
+    held: Arc<Mutex<Option<Vec<HeldLine>>>>,
+}
+
+type HeldLine = (String, OutputStream, String);
+
+#[derive(Clone)]
+enum View {
+    Interleaved,
+    Prefixed(Presenter),
+    Screen(Arc<Mutex<Screen>>),
+    Tmux(Arc<Mutex<TmuxPanes>>, Presenter),
+}
+
+struct Screen {
+    screen: SplitScreen,
+    /// When the screen was last drawn, `None` until it replaces the terminal's content
+    drawn: Option<Instant>,
+}
+
+impl OutputView {
+    /// The view for `mode`. Split is tmux panes inside tmux, a split screen on a terminal and
+    /// prefixed lines otherwise.
+    pub fn new(mode: UiMode, presenter: &Presenter) -> Self {
+        let view = match mode {
+            UiMode::Interleaved => View::Interleaved,
+            UiMode::Prefixed => View::Prefixed(presenter.clone()),
+            UiMode::Split if inside_tmux() => {
+                let dir = std::env::temp_dir().join(format!("rx-panes-{}", std::process::id()));
+                View::Tmux(Arc::new(Mutex::new(TmuxPanes::new(dir))), presenter.clone())
+            }
+            UiMode::Split if io::stderr().is_terminal() => {
+                let (width, height) = terminal_size();
+                View::Screen(Arc::new(Mutex::new(Screen {
+                    screen: SplitScreen::new(width, height),
+                    drawn: None,
+                })))
+            }
+            UiMode::Split => View::Prefixed(presenter.clone()),
+        };
+        OutputView {
+            view,
+            held: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Whether the output is echoed as the commands print it.
+    pub fn is_interleaved(&self) -> bool {
+        matches!(self.view, View::Interleaved)
+    }
+
+    /// Shows a line of output of the command labeled `label`, or holds it back while paused.
+    pub fn line(&self, label: &str, stream: OutputStream, line: &str) {
+        let mut held = self.held.lock().unwrap_or_else(|err| err.into_inner());
+        match held.as_mut() {
+            Some(held) => held.push((label.to_string(), stream, line.to_string())),
+            None => self.show(label, stream, line),
+        }
+    }
+
+    /// Pauses the output, or shows the lines held back and resumes it. Whether it's now paused.
+    pub fn toggle_pause(&self) -> bool {
+        let mut held = self.held.lock().unwrap_or_else(|err| err.into_inner());
+        match held.take() {
+            Some(lines) => {
+                for (label, stream, line) in lines {
+                    self.show(&label, stream, &line);
+                }
+                false
+            }
+            None => {
+                *held = Some(Vec::new());
+                true
+            }
+        }
+    }
+
+    fn show(&self, label: &str, stream: OutputStream, line: &str) {
+        match &self.view {
+            View::Interleaved => echo_line(stream, line),
+            View::Prefixed(presenter) => {
+                echo_line(stream, &format!("{} {}", presenter.prefix(label), line))
+            }
+            View::Screen(screen) => {
+                let mut screen = screen.lock().unwrap_or_else(|err| err.into_inner());
+                screen.screen.push(label, line);
+                if screen
+                    .drawn
+                    .is_none_or(|drawn| drawn.elapsed() >= REDRAW_INTERVAL)
+                {
+                    screen.draw();
+                }
+            }
+            View::Tmux(panes, presenter) => {
+                let mut panes = panes.lock().unwrap_or_else(|err| err.into_inner());
+                if panes.push(label, line).is_err() {
+                    echo_line(stream, &format!("{} {}", presenter.prefix(label), line));
+                }
+            }
+        }
+    }
+
+    /// Shows a step starting or ending in its region. `false` when the view doesn't, for the
+    /// step to be reported as usual.
+    pub fn step(&self, event: &StepEvent) -> bool {
+        let View::Screen(screen) = &self.view else {
+            return false;
+        };
+        let mut screen = screen.lock().unwrap_or_else(|err| err.into_inner());
+        match event {
+            StepEvent::Started(label) | StepEvent::PreCommandStarted(label) => {
+                screen.screen.set_status(label, RegionStatus::Running)
+            }
+            StepEvent::Finished(report) | StepEvent::PreCommandFinished(report) => {
+                let status = if report.success {
+                    RegionStatus::Succeeded
+                } else {
+                    RegionStatus::Failed
+                };
+                screen.screen.set_status(&report.label, status);
+            }
+            StepEvent::Skipped(label, _) => screen.screen.set_status(label, RegionStatus::Skipped),
+            StepEvent::Cached(label) => screen.screen.set_status(label, RegionStatus::Succeeded),
+            StepEvent::MatrixFinished(..)
+            | StepEvent::Ready(..)
+            | StepEvent::PreCommandIgnored(..)
+            | StepEvent::Queued(..)
+            | StepEvent::Locked(..) => {}
+        }
+        screen.draw();
+        true
+    }
+
+    /// Ends the run's view: the split screen gives the terminal back and prints the output of
+    /// each command in turn, the regions still running taking the run's outcome.
+    pub fn finish(&self, success: bool) {
+        if let Some(lines) = self
+            .held
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .take()
+        {
+            for (label, stream, line) in lines {
+                self.show(&label, stream, &line);
+            }
+        }
+        let View::Screen(screen) = &self.view else {
+            return;
+        };
+        let mut screen = screen.lock().unwrap_or_else(|err| err.into_inner());
+        if screen.drawn.take().is_some() {
+            let mut stderr = io::stderr().lock();
+            let _ = write!(stderr, "\x1b[?25h\x1b[?1049l");
+            let _ = stderr.flush();
+        }
+        screen.screen.settle(if success {
+            RegionStatus::Succeeded
+        } else {
+            RegionStatus::Failed
+        });
+        print!("{}", screen.screen.transcript());
+    }
+}
+
+impl Screen {
+    /// Draws the screen on stderr, switching to the alternate screen the first time.
+    fn draw(&mut self) {
+        let mut stderr = io::stderr().lock();
+        if self.drawn.is_none() {
+            let _ = write!(stderr, "\x1b[?1049h\x1b[?25l");
+        }
+        let (width, height) = terminal_size();
+        self.screen.resize(width, height);
+        let _ = write!(stderr, "{}", self.screen.frame());
+        let _ = stderr.flush();
+        self.drawn = Some(Instant::now());
+    }
+}
+
+/// Writes a line of a command's output to rx's matching stream.
+fn echo_line(stream: OutputStream, line: &str) {
+    let _ = match stream {
+        OutputStream::Stdout => writeln!(io::stdout().lock(), "{}", line),
+        OutputStream::Stderr => writeln!(io::stderr().lock(), "{}", line),
+    };
+}
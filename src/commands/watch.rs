@@ -0,0 +1,90 @@
+use std::{
+    error::Error,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use rx_core::{
+    executor::{runner::Runner, shutdown::ShutdownSignal},
+    models::config::{CommandContext, Config},
+    watch::FileWatcher,
+};
+
+use crate::commands::run::{run, RunOptions};
+
+/// How often the watched files are checked, globs matched again included.
+const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Runs `key` of `context` like [`run`], then again whenever a file its `watch` matches
+/// changes, stopping a run still going first. Ctrl-C quits. `options` makes the options of
+/// each run.
+pub fn run_watching(
+    config: &Config,
+    context: CommandContext,
+    key: Option<&str>,
+    args: &[String],
+    options: impl Fn() -> RunOptions,
+) -> Result<i32, Box<dyn Error>> {
+    let first = options();
+    let presenter = first.presenter.clone();
+    let quiet = first.quiet;
+    let session = first.session.clone();
+    let shutdown = ShutdownSignal::new();
+    shutdown.install_handler()?;
+    let runner = Runner::new(config, context, ShutdownSignal::new());
+    let key = runner.resolve_key(key)?;
+    let watcher = FileWatcher::new(runner.watched_files(&key)?);
+    let changed = Arc::new(AtomicBool::new(false));
+
+    // Polls the files on the shared runtime while the runs go on
+    let watching = session.runtime().spawn({
+        let config = config.clone();
+        let key = key.clone();
+        let changed = changed.clone();
+        let shutdown = shutdown.clone();
+        async move {
+            let runner = Runner::new(&config, context, ShutdownSignal::new());
+            let mut watcher = watcher;
+            let mut interval = tokio::time::interval(WATCH_POLL_INTERVAL);
+            loop {
+                interval.tick().await;
+                // A glob that can't be matched now may again once the file is fixed
+                let Ok(files) = runner.watched_files(&key) else {
+                    continue;
+                };
+                if watcher.changed_among(files) {
+                    changed.store(true, Ordering::SeqCst);
+                    shutdown.trigger();
+                }
+            }
+        }
+    });
+
+    let result = (|| {
+        let mut next = Some(first);
+        loop {
+            let mut options = next.take().unwrap_or_else(&options);
+            options.shutdown = Some(shutdown.clone());
+            let code = run(config, context, Some(&key), args, options)?;
+
+            if !changed.load(Ordering::SeqCst) && !shutdown.is_triggered() {
+                if !quiet {
+                    eprintln!("{}", presenter.note("watching for changes, Ctrl-C quits"));
+                }
+                session.block_on(shutdown.triggered());
+            }
+            if !changed.swap(false, Ordering::SeqCst) {
+                return Ok(code);
+            }
+            shutdown.reset();
+            if !quiet {
+                eprintln!("{}", presenter.note("files changed, running again"));
+            }
+        }
+    })();
+    watching.abort();
+    result
+}
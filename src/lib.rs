@@ -1,7 +0,0 @@
-pub mod builders;
-pub mod errors;
-pub mod global;
-pub mod helpers;
-pub mod models;
-pub mod types;
-pub mod validator;
@@ -0,0 +1,15 @@
+use std::error::Error;
+
+use rx_core::{models::config::Config, remote_config::remote_dir, source::ConfigSource};
+
+/// The config the commands work on: the file of `source` with its includes, and underneath its
+/// configs those of the team config fetched for `[remote_config]` and the scripts of
+/// `scripts_dir` in the project of the current directory.
+pub fn load_config(source: &ConfigSource) -> Result<Config, Box<dyn Error>> {
+    let mut config = source.load()?;
+    if let Some(dir) = remote_dir() {
+        config.merge_remote(&dir)?;
+    }
+    config.merge_scripts_dir(&std::env::current_dir()?);
+    Ok(config)
+}
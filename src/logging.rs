@@ -0,0 +1,40 @@
+use std::{error::Error, fs::File, path::Path, sync::Mutex};
+
+use tracing_subscriber::EnvFilter;
+
+/// Environment variable with a filter such as `rx_core=trace`, overriding `-v`
+pub const LOG_ENV: &str = "RX_LOG";
+
+/// Sends rx's diagnostic logs to stderr, or to `log_file`. Nothing is logged by default, each
+/// `-v` raises the level from info to debug to trace, and `RX_LOG` takes precedence over both.
+/// Logs on stderr are colored when `color` is set.
+pub fn init(verbose: u8, log_file: Option<&Path>, color: bool) -> Result<(), Box<dyn Error>> {
+    let level = match verbose {
+        0 => "off",
+        1 => "info",
+        2 => "debug",
+        _ => "trace",
+    };
+    let filter = match std::env::var(LOG_ENV) {
+        Ok(directives) if !directives.trim().is_empty() => {
+            EnvFilter::try_new(directives).map_err(|err| format!("invalid {}: {}", LOG_ENV, err))?
+        }
+        _ => EnvFilter::new(format!("rx={level},rx_core={level}")),
+    };
+
+    let logs = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_target(true);
+    match log_file {
+        Some(path) => {
+            let file = File::options()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|err| format!("cannot open {}: {}", path.display(), err))?;
+            logs.with_ansi(false).with_writer(Mutex::new(file)).init();
+        }
+        None => logs.with_ansi(color).with_writer(std::io::stderr).init(),
+    }
+    Ok(())
+}
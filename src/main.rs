@@ -1,114 +1,712 @@
+mod cli;
+mod commands;
+mod load;
+mod logging;
+
 use std::{
-    collections::{BTreeSet, HashMap},
     error::Error,
-    path::PathBuf,
+    fs,
+    io::{stderr, stdin, stdout, IsTerminal},
+    process,
+    time::Instant,
 };
 
-use rx::{
-    builders::config::ConfigBuilder,
-    errors::ConfigError,
-    helpers::{
-        default_config_path, ensure_config_directory_and_file, init_config, is_valid_env_var_name,
-    },
-    models::config::{CommandContext, CommandDetails, Config},
-    validator::Validator,
+use clap::{error::ErrorKind, CommandFactory, Parser};
+use clap_complete::CompleteEnv;
+use rx_core::{
+    config_diff::diff_configs,
+    config_lint,
+    daemon::default_socket_path,
+    diff::colorize,
+    executor::temp::remove_live,
+    helpers::save_config_file,
+    interpolation::InterpolationContext,
+    models::config::{is_key_pattern, CommandContext, Config, DefaultKey, Removal, SingleFile},
+    search::{search, Pattern},
+    session::Session,
+    source::ConfigSource,
+    strict,
+    test_filter::TestFilter,
+    test_summary::rerun_args,
+    theme::{Presenter, Theme},
+    timings::Timings,
 };
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let (config_path, config_key, command, params, env, pre_commands) = fetch_params()?;
+use crate::{
+    cli::{Cli, CliCommand, ConfigAction, HookAction, SecretAction, TestArgs},
+    commands::{
+        add::add,
+        bench::bench,
+        check::check,
+        clean::clean,
+        completions::{completions, COMPLETE_ENV},
+        confirm::{backups, save_config, Confirmation},
+        daemon::{daemon, queue},
+        debug::debug,
+        env::env,
+        exec::exec,
+        export::export,
+        gc::gc,
+        hook::{install_hooks, run_hook, uninstall_hooks},
+        import::import,
+        init::{init, initial_config, offer},
+        keys::run_with_keys,
+        list::list,
+        record::record,
+        remote::remote,
+        rerun::rerun,
+        run::{
+            confirm_matches, dry_run, failed_tests, list_members, override_config, repair_default,
+            report, run, save_as, show_env, show_stats, with_single_file, with_steps, RunOptions,
+        },
+        runnables::list_runnables,
+        schema::schema,
+        secret::{get_secret, set_secret},
+        serve::serve,
+        service::{down, status, up},
+        show::show,
+        sync::{refresh_remote, sync_remote},
+        update::self_update,
+        vars::vars,
+        version::version,
+        watch::run_watching,
+    },
+    load::load_config,
+};
 
-    init_config(config_path.clone());
+fn main() {
+    // Answers the shell asking for completions, before anything is printed
+    CompleteEnv::with_factory(Cli::command)
+        .var(COMPLETE_ENV)
+        .complete();
+    let timings = Timings::since(Instant::now());
+    let cli = Cli::parse();
+    let color = cli.color.enabled(stderr().is_terminal());
+    let show_timings = cli.timings;
+    // Every run of the process shares its runtime and temp files
+    let session = Session::new();
 
-    let mut config: Config = Config::load(Some(config_path.clone()))?;
+    let result = logging::init(cli.verbose, cli.log_file.as_deref(), color).and_then(|_| {
+        timings.end("startup");
+        execute(cli, &timings, &session)
+    });
+    if show_timings {
+        timings.end("finish");
+        eprintln!("{}", timings.report());
+    }
+    // Exiting skips the drop of what threads still running hold
+    remove_live(&session);
+    match result {
+        Ok(code) => process::exit(code),
+        Err(err) => {
+            // The config may be what failed to load, so its theme can't be relied on
+            let presenter = Presenter::new(Theme::default(), color);
+            eprintln!("{}", presenter.error(&err.to_string()));
+            process::exit(1);
+        }
+    }
+}
 
-    let valid_pre_command_keys = config.commands.get_configs(CommandContext::Run);
+/// Runs the parsed command line and returns the code rx should exit with.
+fn execute(mut cli: Cli, timings: &Timings, session: &Session) -> Result<i32, Box<dyn Error>> {
+    if cli.version {
+        return version(cli.output.unwrap_or_default());
+    }
+    let Some(command) = cli.command.take() else {
+        Cli::command()
+            .error(ErrorKind::MissingSubcommand, "a subcommand is required")
+            .exit()
+    };
+    if let CliCommand::SelfUpdate { check } = &command {
+        return self_update(*check, cli.quiet);
+    }
+    if let CliCommand::Schema { output } = &command {
+        return schema(output.as_deref(), cli.quiet);
+    }
+    if let CliCommand::Completions { shell } = &command {
+        return completions(shell);
+    }
+    if let Some(address) = &cli.remote {
+        return remote(address, &command, session, cli.quiet);
+    }
+    let source = ConfigSource::resolve(cli.config.clone())?.create_dirs(cli.create_dirs);
+    let config_path = source.path();
+    if let CliCommand::Env { json } = &command {
+        return env(config_path, *json);
+    }
+    if let CliCommand::Init(args) = &command {
+        return init(config_path, args, cli.quiet);
+    }
+    if let CliCommand::Config {
+        action: ConfigAction::Backup { list },
+    } = &command
+    {
+        let backups = backups(&source);
+        if *list {
+            for backup in backups.list()? {
+                println!("{}\t{}", backup.timestamp, backup.path.display());
+            }
+        } else {
+            let backup = backups
+                .create()?
+                .ok_or_else(|| format!("Nothing to back up at {}", config_path.display()))?;
+            println!("{}", backup.path.display());
+        }
+        return Ok(0);
+    }
 
-    let pre_command_validator = Validator(move |details: &CommandDetails| {
-        if details.pre_command.contains(config_key) {
-            return Err(ConfigError::InvalidPreCommand(format!(
-                "You cannot use {} as a pre_command",
-                config_key
-            )));
+    if let CliCommand::Config {
+        action: ConfigAction::Restore { timestamp },
+    } = &command
+    {
+        let backups = backups(&source);
+        let backup = backups.find(timestamp.as_deref())?;
+        let current = fs::read_to_string(config_path).unwrap_or_default();
+        let confirmation = Confirmation::new(&cli);
+        if !confirmation.confirm(config_path, &current, &backups.read(&backup)?)? {
+            return Ok(0);
         }
-        for pre_command in &details.pre_command {
-            if !valid_pre_command_keys.contains(pre_command) {
-                return Err(ConfigError::InvalidPreCommand(format!(
-                    "Pre-command must be any of the following: [{}]",
-                    valid_pre_command_keys.join(",")
-                )));
-            }
+        backups.restore(&backup)?;
+        if !cli.quiet {
+            eprintln!(
+                "rx: restored {} from {}",
+                config_path.display(),
+                backup.timestamp
+            );
         }
-        Ok(())
-    });
+        return Ok(0);
+    }
 
-    let env_validator = Validator(move |details| {
-        for key in details.env.keys() {
-            if !is_valid_env_var_name(key) {
-                return Err(ConfigError::InvalidEnvFormat);
+    if let CliCommand::Config {
+        action:
+            ConfigAction::Diff {
+                first,
+                second,
+                against,
+            },
+    } = &command
+    {
+        let (old, new) = match (first, second, against) {
+            (first, _, Some(_)) => {
+                let config = first.as_ref().map_or(source.clone(), ConfigSource::new);
+                (initial_config()?, load_config(&config)?)
+            }
+            (Some(first), Some(second), None) => (
+                load_config(&ConfigSource::new(first))?,
+                load_config(&ConfigSource::new(second))?,
+            ),
+            (Some(first), None, None) => (
+                load_config(&source)?,
+                load_config(&ConfigSource::new(first))?,
+            ),
+            (None, _, None) => unreachable!("clap requires FIRST without --against"),
+        };
+        let changes = diff_configs(&old, &new);
+        let color = cli.color.enabled(stdout().is_terminal());
+        for change in &changes {
+            match color {
+                true => print!("{}", colorize(&change.to_string())),
+                false => println!("{}", change),
             }
         }
-        Ok(())
-    });
+        return Ok(if changes.is_empty() { 0 } else { 1 });
+    }
+
+    if !config_path.exists() {
+        // Without a terminal there is nobody to ask, and a silent default would hide a typo
+        let created = stdin().is_terminal() && offer(config_path, cli.quiet)?;
+        if !created {
+            return Err(format!(
+                "No config file at {}, run `rx init` to create one",
+                config_path.display()
+            )
+            .into());
+        }
+    }
+
+    let mut config: Config = load_config(&source)?;
+    // A team config past its ttl is fetched again before anything reads it
+    if !matches!(command, CliCommand::Sync) && refresh_remote(&config, &presenter(&config, &cli)) {
+        config = load_config(&source)?;
+    }
+    let strict = cli.strict || config.settings.strict;
+    if strict {
+        strict::check(&config, config_path)?;
+    }
+    timings.end("load config");
+
+    if let CliCommand::Bench(args) = &command {
+        if args.run.save_as.is_none() {
+            return bench(&config, session, args, cli.quiet);
+        }
+    }
+
+    if let CliCommand::Config {
+        action: ConfigAction::Get { path },
+    } = &command
+    {
+        match config.get_path(path)? {
+            toml::Value::String(value) => println!("{}", value),
+            toml::Value::Table(table) => print!("{}", toml::to_string_pretty(&table)?),
+            value => println!("{}", value),
+        }
+        return Ok(0);
+    }
+
+    if let CliCommand::List(args)
+    | CliCommand::Config {
+        action: ConfigAction::List(args),
+    } = &command
+    {
+        return list(&config, config_path, args);
+    }
+
+    if let CliCommand::Config {
+        action: ConfigAction::Convert { to, output },
+    } = &command
+    {
+        let output = output
+            .clone()
+            .unwrap_or_else(|| config_path.with_extension(to.extension()));
+        if output.exists() {
+            return Err(format!("{} already exists", output.display()).into());
+        }
+        save_config_file(&output, &config.to_string_as(*to)?, cli.create_dirs)?;
+        if !cli.quiet {
+            eprintln!("rx: wrote {}", output.display());
+        }
+        return Ok(0);
+    }
+
+    if let CliCommand::Debug(args) = &command {
+        return debug(&config, session, args);
+    }
+
+    if let CliCommand::Export(args) = &command {
+        return export(&config, config_path, args, cli.quiet);
+    }
+
+    if let CliCommand::Runnables { file, line, output } = &command {
+        return list_runnables(&config, file, *line, *output);
+    }
+
+    if let CliCommand::Search { pattern, regex } = &command {
+        let pattern = match regex {
+            true => Pattern::regex(pattern)?,
+            false => Pattern::fuzzy(pattern),
+        };
+        let matches = search(&config, &pattern);
+        for found in &matches {
+            println!("{}\t{}\t{}", found.label, found.field, found.text);
+        }
+        return Ok(if matches.is_empty() { 1 } else { 0 });
+    }
+
+    if let CliCommand::Sync = &command {
+        return sync_remote(&config, cli.quiet);
+    }
+
+    if let CliCommand::Gc {
+        days,
+        apply,
+        runtime,
+    } = &command
+    {
+        let confirmation = Confirmation::new(&cli);
+        return gc(
+            &config,
+            &source,
+            *days,
+            *apply,
+            *runtime,
+            &confirmation,
+            cli.quiet,
+        );
+    }
+
+    if let CliCommand::Members = &command {
+        return list_members();
+    }
+
+    if let CliCommand::Check(args) = &command {
+        return check(args, cli.quiet);
+    }
+
+    if let CliCommand::Stats = &command {
+        return show_stats();
+    }
+
+    if let CliCommand::Report(args) = &command {
+        return report(args, cli.quiet);
+    }
 
-    let context = CommandContext::Run;
+    if let CliCommand::Rerun { failed } = &command {
+        return rerun(
+            &config,
+            session,
+            *failed,
+            cli.quiet,
+            presenter(&config, &cli),
+        );
+    }
 
-    let run_command_details = ConfigBuilder::new(context)
-        .command(command)
-        .pre_command(pre_commands)
-        .env(env)
-        .params(params)
-        .add_validator(pre_command_validator)
-        .add_validator(env_validator)
-        .build()?;
+    if let CliCommand::Vars { context, key, json } = &command {
+        return vars(&config, *context, key.as_deref(), *json, cli.quiet);
+    }
 
-    let run_config = config.commands.get_or_default_config(context);
+    if let CliCommand::Exec { like, env, command } = &command {
+        return exec(&config, session, like.as_deref(), env, command);
+    }
 
-    run_config.update_config(config_key, run_command_details);
+    if let CliCommand::LintConfig = &command {
+        config.validate()?;
+        let interpolation = InterpolationContext::from_dir(&std::env::current_dir()?);
+        let warnings = config_lint::lint(&config, &interpolation);
+        for warning in &warnings {
+            println!("{}", warning);
+        }
+        if !cli.quiet {
+            eprintln!("rx: {} warning(s)", warnings.len());
+        }
+        return Ok(if warnings.is_empty() { 0 } else { 1 });
+    }
+
+    if let CliCommand::Daemon(args) = &command {
+        return daemon(config, session, &source, args, cli.quiet);
+    }
+
+    if let CliCommand::Queue { socket, json } = &command {
+        let socket = socket.clone().unwrap_or_else(default_socket_path);
+        return queue(&socket, *json);
+    }
+
+    if let CliCommand::Serve(args) = &command {
+        return serve(&source, session, args.port, cli.quiet);
+    }
+
+    match &command {
+        CliCommand::Up(args) => {
+            let presenter = presenter(&config, &cli);
+            return up(
+                &config,
+                session,
+                &args.keys,
+                args.detach,
+                &presenter,
+                cli.quiet,
+            );
+        }
+        CliCommand::Down(args) => {
+            return down(&config, &args.keys, &presenter(&config, &cli), cli.quiet)
+        }
+        CliCommand::Status => return status(&config),
+        _ => {}
+    }
+
+    if let CliCommand::Hook { action } = &command {
+        return match action {
+            HookAction::Install { hooks, force } => {
+                install_hooks(&config, config_path, hooks, *force, cli.quiet)
+            }
+            HookAction::Uninstall { hooks } => uninstall_hooks(&config, hooks, cli.quiet),
+            HookAction::Run { hook, .. } => {
+                run_hook(&config, session, hook, cli.quiet, presenter(&config, &cli))
+            }
+        };
+    }
+
+    if let CliCommand::Secret {
+        action: SecretAction::Get { context, key, name },
+    } = &command
+    {
+        return get_secret(&config, *context, key, name);
+    }
 
-    config
-        .commands
-        .set_default_config(CommandContext::Run, config_key)?;
+    if let CliCommand::Clean { context, key } = &command {
+        return clean(&config, *context, key, cli.quiet);
+    }
 
-    config.save(Some(config_path))?;
+    if let CliCommand::Show { context, key, copy } = &command {
+        return show(&config, *context, key.as_deref(), *copy, cli.quiet);
+    }
 
-    Ok(())
+    if let Some((context, args)) = command.run_args() {
+        if let Some(new_key) = &args.save_as {
+            save_as(&mut config, context, args, new_key)?;
+            if !save_config(&config, &source, &Confirmation::new(&cli))? {
+                return Ok(0);
+            }
+            let options = RunOptions {
+                quiet: cli.quiet,
+                interactive: stdin().is_terminal(),
+                presenter: presenter(&config, &cli),
+                timings: Some(timings.clone()),
+                session: session.clone(),
+                ..Default::default()
+            };
+            return run(&config, context, Some(new_key), &[], options);
+        }
+        // The overrides only last for this run unless saved
+        if args.overrides_config() {
+            override_config(&mut config, context, args)?;
+            if args.save && !save_config(&config, &source, &Confirmation::new(&cli))? {
+                return Ok(0);
+            }
+        }
+        // A default left pointing at a removed config falls back on one that exists
+        let mut fallback = None;
+        if args.key.is_none() && args.tag.is_none() {
+            let presenter = presenter(&config, &cli);
+            fallback = repair_default(
+                &mut config,
+                context,
+                &source,
+                &Confirmation::new(&cli),
+                stdin().is_terminal(),
+                &presenter,
+            )?;
+        }
+        // The tagged configs, or those a key pattern matches, run as the steps of a composite
+        // config of their own
+        let (config, key) = match &args.tag {
+            Some(tag) => {
+                let scope = (context != CommandContext::Run).then_some(context);
+                let steps = config.tagged(tag, scope);
+                if steps.is_empty() {
+                    return Err(format!("No config is tagged '{}'", tag).into());
+                }
+                let key = format!("tags.{}", tag);
+                (
+                    with_steps(&config, context, &key, steps, args.parallel),
+                    Some(key),
+                )
+            }
+            None => match args.key.as_deref() {
+                // A pattern of keys runs the configs it matches, unless a key is named so
+                Some(pattern)
+                    if is_key_pattern(pattern)
+                        && !config
+                            .commands
+                            .get_configs(context)
+                            .iter()
+                            .any(|key| key == pattern) =>
+                {
+                    let steps = config.matching(context, pattern);
+                    if steps.is_empty() {
+                        return Err(format!("No {} config matches '{}'", context, pattern).into());
+                    }
+                    let listed = !args.dry_run && !args.env_show;
+                    if listed
+                        && !confirm_matches(&steps, &presenter(&config, &cli), cli.yes, cli.quiet)?
+                    {
+                        return Ok(0);
+                    }
+                    let key = format!("matches.{}", pattern);
+                    (
+                        with_steps(&config, context, &key, steps, args.parallel),
+                        Some(key),
+                    )
+                }
+                // A standalone Rust file runs as a config of its own, unless a key is named so
+                Some(file)
+                    if context == CommandContext::Run
+                        && SingleFile::applies(file)
+                        && !config
+                            .commands
+                            .get_configs(context)
+                            .iter()
+                            .any(|key| key == file) =>
+                {
+                    (with_single_file(&config, file), args.key.clone())
+                }
+                _ => (config.clone(), args.key.clone().or(fallback)),
+            },
+        };
+        let extra_args = match &command {
+            CliCommand::Test(test_args) if test_args.rerun_failed => {
+                match failed_tests(&config, context, key.as_deref())? {
+                    (label, names) if names.is_empty() => {
+                        if !cli.quiet {
+                            let message = format!("no failed tests recorded for '{}'", label);
+                            eprintln!("{}", presenter(&config, &cli).note(&message));
+                        }
+                        return Ok(0);
+                    }
+                    (_, names) => rerun_args(&names, &args.args),
+                }
+            }
+            CliCommand::Test(test_args) => test_filter(test_args)?
+                .map(|filter| filter.cargo_args(&args.args))
+                .unwrap_or_else(|| args.args.clone()),
+            CliCommand::Build(build_args) => build_args
+                .target
+                .iter()
+                .flat_map(|target| ["--target".to_string(), target.clone()])
+                .chain(args.args.iter().cloned())
+                .collect(),
+            _ => args.args.clone(),
+        };
+        if args.dry_run {
+            return dry_run(
+                &config,
+                context,
+                key.as_deref(),
+                &extra_args,
+                &args.env,
+                &args.arg_values,
+            );
+        }
+        if args.env_show {
+            return show_env(&config, context, key.as_deref(), &extra_args, &args.env);
+        }
+        let package = args.member()?;
+        let options = || RunOptions {
+            env: args.env.clone(),
+            inputs: args.input.clone(),
+            arg_values: args.arg_values.clone(),
+            // With --keys the keys are read from stdin instead of the inputs
+            interactive: stdin().is_terminal() && !args.keys,
+            stdin: args.stdin_source(),
+            no_cache: args.no_cache,
+            quiet: cli.quiet,
+            observer: None,
+            events: args.event_target(),
+            package: package.clone(),
+            fix: command.fix(),
+            message_format: args.message_format,
+            copy_to: command.copy_to(),
+            compare_size: command.compare_size(),
+            presenter: presenter(&config, &cli),
+            ui: args.ui,
+            strict,
+            jobs: args.jobs,
+            timings: Some(timings.clone()),
+            session: session.clone(),
+            ..Default::default()
+        };
+        if args.keys {
+            return run_with_keys(&config, context, key.as_deref(), &extra_args, options);
+        }
+        if args.watch {
+            return run_watching(&config, context, key.as_deref(), &extra_args, options);
+        }
+        return run(&config, context, key.as_deref(), &extra_args, options());
+    }
+
+    match &command {
+        CliCommand::Add(args) => add(&mut config, args)?,
+        CliCommand::SetDefault { context, key } => {
+            config.commands.set_default_config(*context, key)?
+        }
+        CliCommand::Copy {
+            context,
+            src_key,
+            dst_key,
+            to,
+        } => config.copy_config(*context, src_key, to.unwrap_or(*context), dst_key)?,
+        CliCommand::Config {
+            action: ConfigAction::Set { path, value },
+        } => config.set_path(path, value)?,
+        CliCommand::Config {
+            action: ConfigAction::Merge { other, overwrite },
+        } => {
+            let merged = config.merge(&ConfigSource::new(other).load()?, *overwrite)?;
+            if !cli.quiet {
+                for label in merged {
+                    eprintln!("rx: merged {}", label);
+                }
+            }
+        }
+        CliCommand::Rename {
+            context,
+            old_key,
+            new_key,
+        } => config.rename_config(*context, old_key, new_key)?,
+        CliCommand::Remove {
+            context,
+            key,
+            cascade,
+            force,
+        } => {
+            let removal = match (cascade, force) {
+                (true, _) => Removal::Cascade,
+                (_, true) => Removal::Force,
+                _ => Removal::Safe,
+            };
+            let previous_default = config
+                .commands
+                .get_config(*context)
+                .map(|command_config| command_config.default.clone());
+            let references = config.remove_config(*context, key, removal)?;
+            if !cli.quiet {
+                let effect = match removal {
+                    Removal::Cascade => "removed the reference from",
+                    _ => "left a dangling reference in",
+                };
+                for reference in references {
+                    eprintln!("rx: {} {}", effect, reference);
+                }
+            }
+            // Removing the default falls back to another entry, which shouldn't go unnoticed
+            if let Some(command_config) = config.commands.get_config(*context) {
+                if let DefaultKey::Key(default) = &command_config.default {
+                    if previous_default.as_ref() != Some(&command_config.default)
+                        && command_config.configs.contains_key(default)
+                    {
+                        eprintln!("rx: {}::{} is the default now", context, default);
+                    }
+                }
+            }
+        }
+        CliCommand::Import(args) => import(&mut config, args, cli.quiet)?,
+        CliCommand::Record {
+            context,
+            key,
+            env,
+            force,
+            command,
+        } => {
+            let code = record(&mut config, *context, key, env, *force, command, cli.quiet)?;
+            if code != 0 {
+                return Ok(code);
+            }
+        }
+        CliCommand::Secret {
+            action:
+                SecretAction::Set {
+                    context,
+                    key,
+                    name,
+                    keychain,
+                },
+        } => set_secret(&mut config, *context, key, name, keychain.as_ref())?,
+        _ => unreachable!("run subcommands are handled above"),
+    }
+
+    save_config(&config, &source, &Confirmation::new(&cli))?;
+
+    Ok(0)
 }
 
-fn get_config_path() -> PathBuf {
-    std::env::args()
-        .nth(1)
-        .map(PathBuf::from)
-        .unwrap_or_else(default_config_path)
+/// Resolves `rx test --name` or `--file`/`--line` to the tests cargo should run.
+fn test_filter(args: &TestArgs) -> Result<Option<TestFilter>, Box<dyn Error>> {
+    if let Some(name) = &args.name {
+        return Ok(Some(TestFilter::from_name(name)));
+    }
+    match (&args.file, args.line) {
+        (Some(file), Some(line)) => {
+            let file = fs::canonicalize(file)
+                .map_err(|err| format!("cannot read {}: {}", file.display(), err))?;
+            Ok(Some(TestFilter::at_location(&file, line)?))
+        }
+        _ => Ok(None),
+    }
 }
 
-#[allow(warnings)]
-fn fetch_params<'a>() -> Result<
-    (
-        PathBuf,
-        &'a str,
-        &'a str,
-        &'a str,
-        HashMap<String, String>,
-        BTreeSet<String>,
-    ),
-    Box<dyn Error>,
-> {
-    let config_path = get_config_path();
-    ensure_config_directory_and_file(&config_path)?;
-
-    let pre_commands: BTreeSet<String> = ["default"].into_iter().map(String::from).collect();
-
-    let config_key = "leptos";
-    let params = "watch";
-
-    let command = "leptos";
-    let env: HashMap<String, String> = [
-        ("APP_NAME", "Cargo Runner"),
-        ("MY_CUSTOM_VAR_1", "TRUE"),
-        ("COPY_TRAIT", "FALSE"),
-    ]
-    .iter()
-    .map(|(k, v)| (String::from(*k), String::from(*v)))
-    .collect();
-    Ok((config_path, config_key, command, params, env, pre_commands))
+/// Formats rx's messages around a run with the config's theme, colored as `--color` asks.
+fn presenter(config: &Config, cli: &Cli) -> Presenter {
+    Presenter::new(
+        config.settings.theme.clone(),
+        cli.color.enabled(stderr().is_terminal()),
+    )
 }
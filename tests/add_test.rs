@@ -0,0 +1,29 @@
+#[cfg(test)]
+mod tests {
+    use std::{fs, process::Command};
+
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_add_takes_params_starting_with_a_dash() {
+        let dir = TempDir::new().expect("Failed to create a temporary directory");
+        fs::write(dir.path().join("rx.toml"), "").unwrap();
+
+        let output = Command::new(env!("CARGO_BIN_EXE_rx"))
+            .args(["--config", "rx.toml", "--yes", "add", "run", "fast"])
+            .args([
+                "--type",
+                "cargo",
+                "--command",
+                "run",
+                "--params",
+                "--release -q",
+            ])
+            .current_dir(dir.path())
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        let config = fs::read_to_string(dir.path().join("rx.toml")).unwrap();
+        assert!(config.contains("params = \"--release -q\""), "{}", config);
+    }
+}
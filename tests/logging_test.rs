@@ -0,0 +1,66 @@
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path, process::Command};
+
+    use tempfile::TempDir;
+
+    const CONFIG: &str = "[commands.script]
+default = \"greet\"
+
+[commands.script.configs.greet]
+type = \"shell\"
+command = \"echo hi\"
+";
+
+    /// Runs `rx script greet` with `args` before the subcommand and `RX_LOG` set to `filter`.
+    fn rx(dir: &Path, args: &[&str], filter: Option<&str>) -> std::process::Output {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_rx"));
+        command
+            .args(["--config", "rx.toml"])
+            .args(args)
+            .args(["script", "greet"])
+            .current_dir(dir)
+            .env_remove("RX_LOG");
+        if let Some(filter) = filter {
+            command.env("RX_LOG", filter);
+        }
+        command.output().unwrap()
+    }
+
+    #[test]
+    fn test_verbosity_log_file_and_rx_log() {
+        let dir = TempDir::new().expect("Failed to create a temporary directory");
+        fs::write(dir.path().join("rx.toml"), CONFIG).unwrap();
+
+        let output = rx(dir.path(), &[], None);
+        assert!(output.status.success(), "{:?}", output);
+        assert!(!String::from_utf8_lossy(&output.stderr).contains("spawning"));
+
+        let output = rx(dir.path(), &["-v", "--log-file", "rx.log"], None);
+        assert!(output.status.success(), "{:?}", output);
+        assert!(!String::from_utf8_lossy(&output.stderr).contains("spawning"));
+        let logs = fs::read_to_string(dir.path().join("rx.log")).unwrap();
+        assert!(
+            logs.contains("INFO rx_core::executor::runner: spawning"),
+            "{}",
+            logs
+        );
+        assert!(!logs.contains("DEBUG"), "{}", logs);
+
+        let output = rx(dir.path(), &["-vv"], None);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("loading config"), "{}", stderr);
+        assert!(!stderr.contains("TRACE"), "{}", stderr);
+
+        // RX_LOG takes precedence over -v
+        let output = rx(dir.path(), &["-vvv"], Some("rx_core::models=debug"));
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("loading config"), "{}", stderr);
+        assert!(!stderr.contains("spawning"), "{}", stderr);
+
+        let output = rx(dir.path(), &[], Some("=["));
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("invalid RX_LOG"), "{}", stderr);
+    }
+}
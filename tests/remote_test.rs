@@ -0,0 +1,114 @@
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        process::{Command, Output},
+        thread,
+    };
+
+    use rx_core::daemon::{DaemonRequest, RunRequest};
+    use tempfile::TempDir;
+
+    /// Runs rx with `args` against `--remote tcp://127.0.0.1:port`, from an empty directory.
+    fn rx_remote(port: u16, args: &[&str]) -> Output {
+        let dir = TempDir::new().expect("Failed to create a temporary directory");
+        Command::new(env!("CARGO_BIN_EXE_rx"))
+            .arg("--remote")
+            .arg(format!("tcp://127.0.0.1:{}", port))
+            .args(args)
+            .current_dir(dir.path())
+            .env("RX_REMOTE_TOKEN", "secret")
+            .output()
+            .unwrap()
+    }
+
+    /// A daemon answering one connection: it accepts the token, takes the run request and
+    /// reports the run finished, returning the request.
+    fn fake_daemon() -> (u16, thread::JoinHandle<RunRequest>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let daemon = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            let mut lines = BufReader::new(stream).lines();
+            let auth = DaemonRequest::from_json(&lines.next().unwrap().unwrap()).unwrap();
+            assert_eq!(
+                auth,
+                DaemonRequest::Auth {
+                    token: "secret".into()
+                }
+            );
+            writer
+                .write_all(b"{\"event\":\"authenticated\"}\n")
+                .unwrap();
+            let DaemonRequest::Run(request) =
+                DaemonRequest::from_json(&lines.next().unwrap().unwrap()).unwrap()
+            else {
+                panic!("expected a run request");
+            };
+            writer
+                .write_all(b"{\"event\":\"finished\",\"exit_code\":0}\n")
+                .unwrap();
+            request
+        });
+        (port, daemon)
+    }
+
+    #[test]
+    fn test_remote_forwards_the_target_and_test_name() {
+        let (port, daemon) = fake_daemon();
+        let output = rx_remote(
+            port,
+            &[
+                "build",
+                "--target",
+                "aarch64-apple-darwin",
+                "--",
+                "--locked",
+            ],
+        );
+        assert!(output.status.success(), "{:?}", output);
+        let request = daemon.join().unwrap();
+        assert_eq!(
+            request.args,
+            ["--target", "aarch64-apple-darwin", "--locked"]
+        );
+        assert_eq!(request.cwd, None);
+
+        let (port, daemon) = fake_daemon();
+        let output = rx_remote(port, &["test", "--name", "config::tests::test_load"]);
+        assert!(output.status.success(), "{:?}", output);
+        let request = daemon.join().unwrap();
+        assert!(request
+            .args
+            .contains(&"config::tests::test_load".to_string()));
+        assert!(request.args.contains(&"--exact".to_string()));
+    }
+
+    #[test]
+    fn test_remote_refuses_flags_it_cannot_carry() {
+        // Nothing listens on port 1, the flag is refused before connecting
+        for args in [
+            &["build", "--copy-to", "dist"][..],
+            &["build", "--compare-size", "main"],
+            &["test", "--file", "src/lib.rs", "--line", "3"],
+            &["test", "--rerun-failed"],
+            &["run", "--message-format", "json"],
+            &["run", "--ui", "prefixed"],
+            &["run", "--cwd", "/tmp"],
+            &["bench", "--compare", "main"],
+            &["bench", "--threshold", "10"],
+        ] {
+            let output = rx_remote(1, args);
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            assert_eq!(output.status.code(), Some(1), "{:?}: {}", args, stderr);
+            assert!(
+                stderr.contains("isn't supported with --remote"),
+                "{:?}: {}",
+                args,
+                stderr
+            );
+        }
+    }
+}
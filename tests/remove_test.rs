@@ -0,0 +1,36 @@
+#[cfg(test)]
+mod tests {
+    use std::{fs, process::Command};
+
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_removing_the_default_says_which_config_is_the_default_now() {
+        let dir = TempDir::new().expect("Failed to create a temporary directory");
+        let config = "[commands.run]
+default = \"server\"
+
+[commands.run.configs.server]
+command = \"run\"
+
+[commands.run.configs.worker]
+command = \"run\"
+";
+        fs::write(dir.path().join("rx.toml"), config).unwrap();
+
+        let output = Command::new(env!("CARGO_BIN_EXE_rx"))
+            .args(["--config", "rx.toml", "--yes", "remove", "run", "server"])
+            .current_dir(dir.path())
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("rx: run::worker is the default now"),
+            "{}",
+            stderr
+        );
+        let saved = fs::read_to_string(dir.path().join("rx.toml")).unwrap();
+        assert!(saved.contains("default = \"worker\""), "{}", saved);
+    }
+}
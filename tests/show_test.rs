@@ -0,0 +1,66 @@
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path, process::Command};
+
+    use tempfile::TempDir;
+
+    const CONFIG: &str = "[commands.run]
+default = \"server\"
+
+[commands.run.configs.server]
+type = \"cargo\"
+command = \"run\"
+params = \"--release\"
+";
+
+    /// Runs `rx show` with `args` on the config file of `dir`, without a display to copy to.
+    fn show(dir: &Path, args: &[&str]) -> std::process::Output {
+        Command::new(env!("CARGO_BIN_EXE_rx"))
+            .args(["--config", "rx.toml", "show"])
+            .args(args)
+            .current_dir(dir)
+            .env_remove("DISPLAY")
+            .env_remove("WAYLAND_DISPLAY")
+            .output()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_show_prints_the_command_line_of_the_default_or_a_key() {
+        let dir = TempDir::new().expect("Failed to create a temporary directory");
+        fs::write(dir.path().join("rx.toml"), CONFIG).unwrap();
+
+        for args in [&["run"][..], &["run", "server"]] {
+            let output = show(dir.path(), args);
+            assert!(output.status.success(), "{:?}", output);
+            assert_eq!(
+                String::from_utf8_lossy(&output.stdout),
+                "cargo run --release\n"
+            );
+        }
+
+        let output = show(dir.path(), &["run", "missing"]);
+        assert!(!output.status.success());
+        assert!(output.stdout.is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_show_copy_without_a_clipboard_still_prints_and_fails() {
+        let dir = TempDir::new().expect("Failed to create a temporary directory");
+        fs::write(dir.path().join("rx.toml"), CONFIG).unwrap();
+
+        let output = show(dir.path(), &["run", "--copy"]);
+        assert_eq!(output.status.code(), Some(1), "{:?}", output);
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "cargo run --release\n"
+        );
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("Cannot copy to the clipboard"),
+            "{}",
+            stderr
+        );
+    }
+}